@@ -0,0 +1,87 @@
+//! Throughput of scanning all classes of a class path, one class after the other and on all cores. Criterion reports
+//! the throughput in elements per second, where each element is a class.
+//!
+//! By default this scans the test resources. To scan something closer to real life, set `CAFEBABE_BENCH_CLASS_PATH`
+//! to a class path, e.g. the jmods of a JDK:
+//!
+//! ```sh
+//! CAFEBABE_BENCH_CLASS_PATH=$(ls -d $JAVA_HOME/jmods/*.jmod | paste -sd:) cargo bench -p cafebabe --features parallel
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+
+use cafebabe::class_path::ClassPath;
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rayon::prelude::*;
+
+fn class_path_entries() -> Vec<PathBuf> {
+    match env::var_os("CAFEBABE_BENCH_CLASS_PATH") {
+        Some(class_path) => env::split_paths(&class_path).collect(),
+        None => [
+            "res/jars/examples.jar",
+            "res/jars/examples.jmod",
+            "res/java8",
+            "res/java11",
+            "res/java17",
+            "res/java21",
+            "res/java25",
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect(),
+    }
+}
+
+fn scan(c: &mut Criterion) {
+    let paths = class_path_entries();
+    let class_path = ClassPath::par_open(&paths).expect("can't open the class path");
+    let classes = class_path.classes().count();
+    let valid = class_path
+        .classes()
+        .filter(|class| class.class_file.is_ok())
+        .count();
+    println!(
+        "scanning {classes} classes in {} entries, of which {valid} are valid",
+        paths.len()
+    );
+
+    let mut group = c.benchmark_group("scan");
+    group.throughput(Throughput::Elements(classes as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            class_path
+                .classes()
+                .filter(|class| class.class_file.is_ok())
+                .count()
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            class_path
+                .par_classes()
+                .filter(|class| class.class_file.is_ok())
+                .count()
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("open");
+    group.throughput(Throughput::Elements(paths.len() as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let mut class_path = ClassPath::new();
+            for path in &paths {
+                class_path.add(path).expect("can't open the class path");
+            }
+            class_path
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| ClassPath::par_open(&paths).expect("can't open the class path"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scan);
+criterion_main!(benches);
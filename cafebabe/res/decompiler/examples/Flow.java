@@ -0,0 +1,99 @@
+package examples;
+
+import java.util.ArrayList;
+import java.util.List;
+import java.util.function.Function;
+import java.util.function.Supplier;
+
+public class Flow {
+    private static final String GREETING = "Hello";
+
+    private int count;
+
+    public Flow(int count) {
+        this.count = count;
+    }
+
+    public int sum(int[] values) {
+        int total = 0;
+        for (int i = 0; i < values.length; i++) {
+            total += values[i];
+        }
+        return total;
+    }
+
+    public static int max(int a, int b) {
+        return a > b ? a : b;
+    }
+
+    public static boolean inRange(int value, int low, int high) {
+        return value >= low && value <= high;
+    }
+
+    public static String classify(int value) {
+        if (value < 0) {
+            return "negative";
+        } else if (value == 0) {
+            return "zero";
+        }
+        return "positive";
+    }
+
+    public static int countDown(int start) {
+        int steps = 0;
+        while (start > 0) {
+            start -= 2;
+            steps++;
+        }
+        return steps;
+    }
+
+    public static String name(int day) {
+        String name;
+        switch (day) {
+            case 1:
+                name = "Monday";
+                break;
+            case 2:
+                name = "Tuesday";
+                break;
+            default:
+                name = "Other";
+        }
+        return name;
+    }
+
+    public static int parse(String text) {
+        try {
+            return Integer.parseInt(text);
+        } catch (NumberFormatException e) {
+            return -1;
+        }
+    }
+
+    public int close(AutoCloseable resource) throws Exception {
+        try {
+            return count;
+        } finally {
+            resource.close();
+        }
+    }
+
+    public String greet(String who) {
+        return GREETING + ", " + who + " #" + count;
+    }
+
+    public static List<Integer> lengths(List<String> words) {
+        List<Integer> lengths = new ArrayList<>();
+        words.forEach(word -> lengths.add(word.length()));
+        return lengths;
+    }
+
+    public static Function<String, Integer> parser() {
+        return Integer::parseInt;
+    }
+
+    public static Supplier<String> supplier(String value) {
+        return () -> value.trim();
+    }
+}
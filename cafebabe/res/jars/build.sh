@@ -0,0 +1,39 @@
+#!/bin/sh
+# Builds the jar fixtures from the classes and sources in ../java8 and ../java17 and in multi-release/.
+set -e
+cd "$(dirname "$0")"
+
+printf 'Main-Class: examples.ClassWithMethods\nClass-Path: lib/first.jar lib/second.jar\nAutomatic-Module-Name: cafebabe.examples\nImplementation-Title: cafebabe examples with a long title that needs to be continued on the next line of the manifest\n\nName: examples/ClassWithCode.class\nSealed: true\n' > /tmp/MANIFEST.MF
+jar --create --file examples.jar --manifest /tmp/MANIFEST.MF -C ../java17 examples
+jar --create --no-compress --file examples-stored.jar -C ../java8 examples
+
+# The overrides are inconsistent on purpose, which the jar tool refuses, so the jar is zipped directly.
+out=$(mktemp -d)
+javac --release 8 -d "$out/base" multi-release/base/examples/*.java
+javac --release 11 -d "$out/9" multi-release/9/examples/*.java
+javac --release 11 -d "$out/11" multi-release/11/examples/*.java
+javac --release 17 -d "$out/17" -cp "$out/base" multi-release/17/examples/*.java
+python3 - "$out" <<'PYTHON'
+import os, sys, zipfile
+out = sys.argv[1]
+for name, manifest in [("multi-release.jar", "Multi-Release: true\n"), ("multi-release-disabled.jar", "")]:
+    with zipfile.ZipFile(name, "w", zipfile.ZIP_DEFLATED) as jar:
+        jar.writestr("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\n" + manifest)
+        for version in ["base", "9", "11", "17"]:
+            for root, _, files in sorted(os.walk(os.path.join(out, version))):
+                for file in sorted(files):
+                    path = os.path.join(root, file)
+                    entry = os.path.relpath(path, os.path.join(out, version))
+                    if version != "base":
+                        entry = "META-INF/versions/%s/%s" % (version, entry)
+                    jar.write(path, entry)
+PYTHON
+rm -rf "$out"
+
+# A jmod of the module in ../java17/module, with a legal notice to have a second section.
+out=$(mktemp -d)
+javac -d "$out/classes" $(find ../java17/module -name '*.java')
+mkdir "$out/legal" && echo "Example notice" > "$out/legal/NOTICE"
+rm -f examples.jmod
+jmod create --class-path "$out/classes" --legal-notices "$out/legal" --main-class examples.Main --module-version 1.0 examples.jmod
+rm -rf "$out"
@@ -0,0 +1,4 @@
+package examples;
+
+public class Extra {
+}
@@ -0,0 +1,11 @@
+package examples;
+
+public class Greeter {
+    public String greet() {
+        return Helper.greeting();
+    }
+
+    public String greetLoudly() {
+        return greet().toUpperCase();
+    }
+}
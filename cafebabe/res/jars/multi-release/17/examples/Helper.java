@@ -0,0 +1,7 @@
+package examples;
+
+class Helper {
+    static String greeting() {
+        return "Hello from Java 17";
+    }
+}
@@ -0,0 +1,7 @@
+package examples;
+
+public class Greeter {
+    public String greet() {
+        return "Hello from Java 9, but compiled for Java 11";
+    }
+}
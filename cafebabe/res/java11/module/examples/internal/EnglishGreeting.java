@@ -0,0 +1,9 @@
+package examples.internal;
+
+import examples.spi.Greeting;
+
+public class EnglishGreeting implements Greeting {
+    public String greet() {
+        return "Hello";
+    }
+}
@@ -0,0 +1,15 @@
+module examples {
+    requires java.logging;
+    requires transitive java.sql;
+    requires static java.compiler;
+
+    exports examples;
+    exports examples.spi;
+    exports examples.internal to java.logging;
+
+    opens examples.internal;
+
+    uses examples.spi.Greeting;
+
+    provides examples.spi.Greeting with examples.internal.EnglishGreeting;
+}
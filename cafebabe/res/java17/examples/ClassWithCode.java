@@ -0,0 +1,51 @@
+package examples;
+
+public class ClassWithCode {
+    private static final long BIG = 1234567890123L;
+
+    private double ratio = 0.5;
+
+    public int sum(int[] values) {
+        int total = 0;
+        for (int value : values) {
+            total += value;
+        }
+        return total;
+    }
+
+    public String describe(int value) {
+        switch (value) {
+            case 1:
+                return "one";
+            case 2:
+                return "two";
+            case 3:
+                return "three";
+            default:
+                return "many";
+        }
+    }
+
+    public String sparse(int value) {
+        switch (value) {
+            case 1:
+                return "one";
+            case 1000:
+                return "thousand";
+            default:
+                return "other";
+        }
+    }
+
+    public int parse(String value) {
+        try {
+            return Integer.parseInt(value);
+        } catch (NumberFormatException e) {
+            return -1;
+        }
+    }
+
+    public long big() {
+        return BIG * 2 + (long) ratio;
+    }
+}
@@ -0,0 +1,14 @@
+package examples;
+
+import java.util.function.Function;
+import java.util.function.Supplier;
+
+public class ClassWithLambda {
+    public Supplier<String> greeter(String name) {
+        return () -> "Hello, " + name;
+    }
+
+    public Function<String, Integer> length() {
+        return String::length;
+    }
+}
@@ -0,0 +1,21 @@
+package examples;
+
+import java.io.IOException;
+import java.util.function.Supplier;
+
+public class ClassWithMethods {
+    @Deprecated
+    public int legacyField;
+
+    public void write(final String message, int times) throws IOException, InterruptedException {
+    }
+
+    @Deprecated
+    public static String legacy() {
+        return "legacy";
+    }
+
+    public Supplier<String> supplier() {
+        return () -> "supplied";
+    }
+}
@@ -0,0 +1,4 @@
+package examples;
+
+public record RecordClass(int x, String y) {
+}
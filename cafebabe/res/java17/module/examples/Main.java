@@ -0,0 +1,11 @@
+package examples;
+
+import java.util.ServiceLoader;
+
+import examples.spi.Greeting;
+
+public class Main {
+    public static void main(String[] args) {
+        ServiceLoader.load(Greeting.class).forEach(greeting -> System.out.println(greeting.greet()));
+    }
+}
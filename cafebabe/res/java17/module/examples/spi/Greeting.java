@@ -0,0 +1,5 @@
+package examples.spi;
+
+public interface Greeting {
+    String greet();
+}
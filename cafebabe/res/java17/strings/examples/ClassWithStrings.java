@@ -0,0 +1,8 @@
+package examples;
+
+public class ClassWithStrings {
+    public static final String NULL = "a\u0000b";
+    public static final String EMOJI = "smile \uD83D\uDE00";
+    public static final String ACCENTS = "caf\u00e9 \u20ac";
+    public static final String UNPAIRED = "low \uD800 end";
+}
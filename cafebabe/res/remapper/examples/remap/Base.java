@@ -0,0 +1,9 @@
+package examples.remap;
+
+public class Base {
+    protected int count;
+
+    public void reset() {
+        count = 0;
+    }
+}
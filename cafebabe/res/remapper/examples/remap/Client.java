@@ -0,0 +1,9 @@
+package examples.remap;
+
+public class Client {
+    public String use(Derived derived) {
+        derived.reset();
+        derived.count++;
+        return derived.greeting();
+    }
+}
@@ -0,0 +1,4 @@
+package examples.remap;
+
+public class Derived extends Base implements Greeter {
+}
@@ -0,0 +1,7 @@
+package examples.remap;
+
+public interface Greeter {
+    default String greeting() {
+        return "Hello";
+    }
+}
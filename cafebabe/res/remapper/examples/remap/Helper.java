@@ -0,0 +1,10 @@
+package examples.remap;
+
+public class Helper {
+    public static class Inner {
+    }
+
+    static void greet() {
+        System.out.println("Hello");
+    }
+}
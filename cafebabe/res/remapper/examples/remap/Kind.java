@@ -0,0 +1,6 @@
+package examples.remap;
+
+public enum Kind {
+    FIRST,
+    SECOND
+}
@@ -0,0 +1,31 @@
+package examples.remap;
+
+import java.util.List;
+import java.util.function.Supplier;
+
+@Marker(kind = Kind.SECOND, type = Helper.class)
+public class Library {
+    public static final String NAME = "examples.remap.Helper";
+
+    private List<Helper.Inner> items;
+
+    public Helper helper(@Marker Helper.Inner inner) {
+        Runnable greeting = () -> Helper.greet();
+        greeting.run();
+        Supplier<Helper> supplier = Helper::new;
+        return supplier.get();
+    }
+
+    public Class<?> load() throws ReflectiveOperationException {
+        return Class.forName("examples.remap.Helper");
+    }
+
+    public Object anonymous() {
+        return new Object() {
+            @Override
+            public String toString() {
+                return items.toString();
+            }
+        };
+    }
+}
@@ -0,0 +1,11 @@
+package examples.remap;
+
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+
+@Retention(RetentionPolicy.RUNTIME)
+public @interface Marker {
+    Kind kind() default Kind.FIRST;
+
+    Class<?> type() default Object.class;
+}
@@ -0,0 +1,4 @@
+package examples.remap;
+
+public record Point(Helper x, Helper.Inner y) {
+}
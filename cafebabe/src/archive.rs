@@ -0,0 +1,442 @@
+//! Reading of the archives that classes are distributed in: jar and zip files, jmod files and jimages, and writing of
+//! jar files.
+//!
+//! All of them give access to their classes and other resources through [`ResourceLookup`].
+
+mod jimage;
+mod jmod;
+mod manifest;
+mod multi_release;
+mod types;
+mod writer;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::result::Result;
+
+use log::debug;
+
+use crate::read_class_data_with_options;
+use crate::types::ClassFile;
+use crate::version::ParseOptions;
+pub use jimage::{ImageLocation, JImage, jimage_hash};
+pub use jmod::{JmodFile, JmodSection};
+pub use manifest::{Manifest, ManifestSection, read_manifest};
+pub use types::{ArchiveError, Compression, JarEntry, VersionConflict, VersionConflictKind};
+pub use writer::JarWriter;
+
+const MANIFEST_NAME: &str = "META-INF/MANIFEST.MF";
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_SIGNATURE: u32 = 0x06054b50;
+const ZIP64_END_SIGNATURE: u32 = 0x06064b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+/// The fixed size of the end of central directory record, which is followed by a comment of at most 64 KiB.
+const END_SIZE: usize = 22;
+
+/// Whether an entry belongs to the signature of a signed jar, e.g. `META-INF/SIGNER.SF` or `META-INF/SIGNER.RSA`.
+pub(crate) fn is_signature_file(name: &str) -> bool {
+    let Some(file_name) = name.strip_prefix("META-INF/") else {
+        return false;
+    };
+    let file_name = file_name.to_ascii_uppercase();
+    !file_name.contains('/')
+        && (file_name.starts_with("SIG-")
+            || [".SF", ".RSA", ".DSA", ".EC"]
+                .iter()
+                .any(|extension| file_name.ends_with(extension)))
+}
+
+/// The `length` bytes at `idx`, or an error if the archive ends before them.
+fn bytes_at(data: &[u8], idx: usize, length: usize) -> Result<&[u8], ArchiveError> {
+    idx.checked_add(length)
+        .and_then(|end| data.get(idx..end))
+        .ok_or(ArchiveError::InvalidArchive)
+}
+
+fn le_u16(data: &[u8], idx: usize) -> Result<u16, ArchiveError> {
+    let bytes = bytes_at(data, idx, 2)?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn le_u32(data: &[u8], idx: usize) -> Result<u32, ArchiveError> {
+    let bytes = bytes_at(data, idx, 4)?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn le_u64(data: &[u8], idx: usize) -> Result<u64, ArchiveError> {
+    Ok(u64::from(le_u32(data, idx)?) | (u64::from(le_u32(data, idx + 4)?) << 32))
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+/// The CRC-32 checksum of zip files.
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        CRC_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// The central directory as `(entry count, offset, prefix length)`, from the (ZIP64) end of central directory record.
+///
+/// The prefix is data in front of the archive, such as the header of a jmod file, which the offsets in the archive
+/// don't account for.
+fn read_end_of_central_directory(data: &[u8]) -> Result<(u64, u64, u64), ArchiveError> {
+    if data.len() < END_SIZE {
+        return Err(ArchiveError::InvalidArchive);
+    }
+    let lowest = data.len().saturating_sub(END_SIZE + usize::from(u16::MAX));
+    let end_idx = (lowest..=data.len() - END_SIZE)
+        .rev()
+        .find(|idx| le_u32(data, *idx).is_ok_and(|signature| signature == END_SIGNATURE))
+        .ok_or(ArchiveError::InvalidArchive)?;
+
+    let entry_count = le_u16(data, end_idx + 10)?;
+    let directory_size = le_u32(data, end_idx + 12)?;
+    let directory_offset = le_u32(data, end_idx + 16)?;
+    if entry_count != u16::MAX && directory_offset != u32::MAX {
+        let prefix_length = (end_idx as u64)
+            .checked_sub(u64::from(directory_size) + u64::from(directory_offset))
+            .ok_or(ArchiveError::InvalidArchive)?;
+        return Ok((
+            u64::from(entry_count),
+            u64::from(directory_offset),
+            prefix_length,
+        ));
+    }
+
+    let locator_idx = end_idx
+        .checked_sub(20)
+        .ok_or(ArchiveError::InvalidArchive)?;
+    if le_u32(data, locator_idx)? != ZIP64_LOCATOR_SIGNATURE {
+        return Ok((u64::from(entry_count), u64::from(directory_offset), 0));
+    }
+    let zip64_end_idx = le_u64(data, locator_idx + 8)? as usize;
+    if le_u32(data, zip64_end_idx)? != ZIP64_END_SIGNATURE {
+        return Err(ArchiveError::InvalidArchive);
+    }
+    Ok((
+        le_u64(data, zip64_end_idx + 32)?,
+        le_u64(data, zip64_end_idx + 48)?,
+        0,
+    ))
+}
+
+/// Replaces the sizes and offset that don't fit in 32 bits by their values in the ZIP64 extra field.
+fn read_zip64_extra(extra: &[u8], entry: &mut JarEntry) -> Result<(), ArchiveError> {
+    let mut idx = 0;
+    while idx + 4 <= extra.len() {
+        let id = le_u16(extra, idx)?;
+        let size = usize::from(le_u16(extra, idx + 2)?);
+        if id == ZIP64_EXTRA_ID {
+            let mut value_idx = idx + 4;
+            for value in [
+                &mut entry.size,
+                &mut entry.compressed_size,
+                &mut entry.header_offset,
+            ] {
+                if *value == u64::from(u32::MAX) {
+                    *value = le_u64(extra, value_idx)?;
+                    value_idx += 8;
+                }
+            }
+        }
+        idx += 4 + size;
+    }
+    Ok(())
+}
+
+fn read_central_directory_entry(
+    data: &[u8],
+    start_idx: usize,
+) -> Result<(JarEntry, usize), ArchiveError> {
+    if le_u32(data, start_idx)? != CENTRAL_HEADER_SIGNATURE {
+        return Err(ArchiveError::InvalidArchive);
+    }
+    let flags = le_u16(data, start_idx + 8)?;
+    let method = le_u16(data, start_idx + 10)?;
+    let name_length = usize::from(le_u16(data, start_idx + 28)?);
+    let extra_length = usize::from(le_u16(data, start_idx + 30)?);
+    let comment_length = usize::from(le_u16(data, start_idx + 32)?);
+
+    let name_idx = start_idx + 46;
+    let extra_idx = name_idx + name_length;
+    let name_bytes = bytes_at(data, name_idx, name_length)?;
+    let extra = bytes_at(data, extra_idx, extra_length)?;
+
+    let mut entry = JarEntry {
+        name: String::from_utf8_lossy(name_bytes).into_owned(),
+        compression: Compression::from_method(method),
+        crc32: le_u32(data, start_idx + 16)?,
+        compressed_size: u64::from(le_u32(data, start_idx + 20)?),
+        size: u64::from(le_u32(data, start_idx + 24)?),
+        encrypted: flags & 1 != 0,
+        header_offset: u64::from(le_u32(data, start_idx + 42)?),
+    };
+    read_zip64_extra(extra, &mut entry)?;
+    debug!(
+        "found archive entry; name={}, compression={:?}, size={}",
+        entry.name, entry.compression, entry.size
+    );
+
+    Ok((entry, extra_idx + extra_length + comment_length))
+}
+
+/// A jar or zip file held in memory. Entries are decompressed and classes parsed only when they are read.
+#[derive(Debug)]
+pub struct JarFile {
+    data: Vec<u8>,
+    entries: Vec<JarEntry>,
+    /// The position of each entry in `entries` by name; the last one wins for duplicate names.
+    entry_indices: HashMap<String, usize>,
+    /// The versioned entries below `META-INF/versions/` by the name they override, ordered by release.
+    versioned_indices: HashMap<String, Vec<(u16, usize)>>,
+    manifest: Option<Manifest>,
+    /// How the classes of the jar are parsed.
+    options: ParseOptions,
+}
+
+impl JarFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<JarFile, ArchiveError> {
+        JarFile::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Reads the central directory and the manifest of a jar or zip file, which may be preceded by other data.
+    pub fn from_bytes(data: Vec<u8>) -> Result<JarFile, ArchiveError> {
+        let (entry_count, directory_offset, prefix_length) = read_end_of_central_directory(&data)?;
+        let mut entries = Vec::new();
+        let mut current_idx = usize::try_from(directory_offset + prefix_length)
+            .map_err(|_| ArchiveError::InvalidArchive)?;
+        for _ in 0..entry_count {
+            let (mut entry, next_idx) = read_central_directory_entry(&data, current_idx)?;
+            entry.header_offset += prefix_length;
+            entries.push(entry);
+            current_idx = next_idx;
+        }
+        let entry_indices = entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry.name.clone(), idx))
+            .collect();
+        let versioned_indices = multi_release::versioned_indices(&entries);
+
+        let mut jar_file = JarFile {
+            data,
+            entries,
+            entry_indices,
+            versioned_indices,
+            manifest: None,
+            options: ParseOptions::default(),
+        };
+        if let Some(entry) = jar_file.entry(MANIFEST_NAME) {
+            let manifest = read_manifest(&jar_file.read(entry)?)?;
+            jar_file.manifest = Some(manifest);
+        }
+        Ok(jar_file)
+    }
+
+    /// Parses the classes of the jar with the options instead of the default ones.
+    pub fn with_options(mut self, options: ParseOptions) -> JarFile {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// All entries in the order of the central directory.
+    pub fn entries(&self) -> &[JarEntry] {
+        &self.entries
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&JarEntry> {
+        self.entry_indices.get(name).map(|idx| &self.entries[*idx])
+    }
+
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+
+    /// The binary names of all classes in the jar in their internal form, in the order of the central directory.
+    pub fn class_names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().filter_map(JarEntry::class_name)
+    }
+
+    /// Reads the uncompressed data of an entry and checks it against its size and checksum.
+    pub fn read(&self, entry: &JarEntry) -> Result<Vec<u8>, ArchiveError> {
+        if entry.encrypted {
+            return Err(ArchiveError::UnsupportedEntry(entry.name.clone()));
+        }
+        let header_idx =
+            usize::try_from(entry.header_offset).map_err(|_| ArchiveError::InvalidArchive)?;
+        if le_u32(&self.data, header_idx)? != LOCAL_HEADER_SIGNATURE {
+            return Err(ArchiveError::InvalidArchive);
+        }
+        let name_length = usize::from(le_u16(&self.data, header_idx + 26)?);
+        let extra_length = usize::from(le_u16(&self.data, header_idx + 28)?);
+        let data_idx = header_idx + 30 + name_length + extra_length;
+        let compressed_size =
+            usize::try_from(entry.compressed_size).map_err(|_| ArchiveError::InvalidArchive)?;
+        let compressed = bytes_at(&self.data, data_idx, compressed_size)?;
+
+        let data = match entry.compression {
+            Compression::Stored => compressed.to_vec(),
+            Compression::Deflated => miniz_oxide::inflate::decompress_to_vec(compressed)
+                .map_err(|_| ArchiveError::CorruptEntry(entry.name.clone()))?,
+            Compression::Unsupported(_) => {
+                return Err(ArchiveError::UnsupportedEntry(entry.name.clone()));
+            }
+        };
+        if data.len() as u64 != entry.size || crc32(&data) != entry.crc32 {
+            return Err(ArchiveError::CorruptEntry(entry.name.clone()));
+        }
+        Ok(data)
+    }
+
+    pub fn read_entry(&self, name: &str) -> Result<Vec<u8>, ArchiveError> {
+        match self.entry(name) {
+            Some(entry) => self.read(entry),
+            None => Err(ArchiveError::EntryNotFound(name.to_string())),
+        }
+    }
+
+    /// Reads and parses the class in an entry.
+    pub fn read_class_entry(&self, entry: &JarEntry) -> Result<ClassFile, ArchiveError> {
+        let data = self.read(entry)?;
+        read_class_data_with_options(&data, &self.options)
+            .map_err(|error| ArchiveError::InvalidClass(entry.name.clone(), error))
+    }
+
+    /// Reads and parses the class with a binary name in its internal form, e.g. `java/lang/Object`.
+    pub fn read_class(&self, class_name: &str) -> Result<ClassFile, ArchiveError> {
+        let name = format!("{class_name}.class");
+        match self.entry(&name) {
+            Some(entry) => self.read_class_entry(entry),
+            None => Err(ArchiveError::EntryNotFound(name)),
+        }
+    }
+
+    /// Reads and parses all classes, in the order of the central directory.
+    pub fn classes(&self) -> impl Iterator<Item = (&str, Result<ClassFile, ArchiveError>)> {
+        self.class_names()
+            .map(|class_name| (class_name, self.read_class(class_name)))
+    }
+}
+
+/// Lookup of resources by their name relative to the root of the classes they belong to, e.g. `java/lang/Object.class`,
+/// for all kinds of archives.
+pub trait ResourceLookup {
+    /// The names of all resources, e.g. `java/lang/Object.class` or `META-INF/MANIFEST.MF`.
+    fn resource_names(&self) -> Vec<&str>;
+
+    /// Reads a resource, or gives `None` if there is no resource with the name.
+    fn find_resource(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError>;
+
+    /// Whether there is a resource with the name, without reading it.
+    fn contains_resource(&self, name: &str) -> bool {
+        self.resource_names().contains(&name)
+    }
+
+    /// Reads and parses a class by its binary name in its internal form, e.g. `java/lang/Object`.
+    fn find_class(&self, class_name: &str) -> Result<Option<ClassFile>, ArchiveError> {
+        self.find_class_with_options(class_name, &ParseOptions::default())
+    }
+
+    /// Reads and parses a class like [`find_class`](ResourceLookup::find_class), with the options.
+    fn find_class_with_options(
+        &self,
+        class_name: &str,
+        options: &ParseOptions,
+    ) -> Result<Option<ClassFile>, ArchiveError> {
+        let name = format!("{class_name}.class");
+        match self.find_resource(&name)? {
+            Some(data) => read_class_data_with_options(&data, options)
+                .map(Some)
+                .map_err(|error| ArchiveError::InvalidClass(name, error)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The entries of a jar, without directories. Versioned entries of multi-release jars are only found by their full
+/// name, see [`JarFile::resolve_entry`].
+impl ResourceLookup for JarFile {
+    fn resource_names(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.is_directory())
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    fn find_resource(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        self.entry(name).map(|entry| self.read(entry)).transpose()
+    }
+
+    fn contains_resource(&self, name: &str) -> bool {
+        self.entry(name).is_some()
+    }
+
+    /// Parses the class with the options of the jar, see [`JarFile::with_options`].
+    fn find_class(&self, class_name: &str) -> Result<Option<ClassFile>, ArchiveError> {
+        self.find_class_with_options(class_name, &self.options)
+    }
+}
+
+/// The classes section of a jmod file.
+impl ResourceLookup for JmodFile {
+    fn resource_names(&self) -> Vec<&str> {
+        self.entries(JmodSection::Classes)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    fn find_resource(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        let name = format!("{}{name}", JmodSection::Classes.directory());
+        self.archive()
+            .entry(&name)
+            .map(|entry| self.archive().read(entry))
+            .transpose()
+    }
+
+    fn contains_resource(&self, name: &str) -> bool {
+        let name = format!("{}{name}", JmodSection::Classes.directory());
+        self.archive().entry(&name).is_some()
+    }
+}
+
+/// The resources of all modules of a jimage, by their name relative to their module.
+impl ResourceLookup for JImage {
+    fn resource_names(&self) -> Vec<&str> {
+        JImage::resource_names(self).collect()
+    }
+
+    fn find_resource(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        self.read_resource(name)
+    }
+
+    fn contains_resource(&self, name: &str) -> bool {
+        JImage::contains_resource(self, name)
+    }
+}
@@ -0,0 +1,486 @@
+//! Reading of jimage files, the format of the `lib/modules` runtime image of a JDK.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::result::Result;
+
+use log::debug;
+
+use crate::archive::{ArchiveError, bytes_at};
+
+const MAGIC: u32 = 0xcafedada;
+const HEADER_SIZE: usize = 7 * 4;
+const HASH_MULTIPLIER: i32 = 0x01000193;
+
+const ATTRIBUTE_END: u8 = 0;
+const ATTRIBUTE_MODULE: u8 = 1;
+const ATTRIBUTE_PARENT: u8 = 2;
+const ATTRIBUTE_BASE: u8 = 3;
+const ATTRIBUTE_EXTENSION: u8 = 4;
+const ATTRIBUTE_OFFSET: u8 = 5;
+const ATTRIBUTE_COMPRESSED: u8 = 6;
+const ATTRIBUTE_UNCOMPRESSED: u8 = 7;
+
+const COMPRESSED_MAGIC: u32 = 0xcafefafa;
+const COMPRESSED_HEADER_SIZE: usize = 29;
+
+const CONSTANT_UTF8: u8 = 1;
+const CONSTANT_LONG: u8 = 5;
+const CONSTANT_DOUBLE: u8 = 6;
+const EXTERNALIZED_STRING: u8 = 23;
+const EXTERNALIZED_STRING_DESCRIPTOR: u8 = 25;
+
+/// The hash of the names in the lookup table of a jimage, over their UTF-8 encoding.
+pub fn jimage_hash(name: &str, seed: i32) -> i32 {
+    let hash = name.bytes().fold(seed, |hash, byte| {
+        hash.wrapping_mul(HASH_MULTIPLIER) ^ i32::from(byte)
+    });
+    hash & 0x7fffffff
+}
+
+/// Where a resource of a jimage is stored, with its name split into the parts that are stored in the image.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ImageLocation {
+    pub module: String,
+    /// The directory of the resource within its module, e.g. `java/lang`.
+    pub parent: String,
+    pub base: String,
+    pub extension: String,
+    /// The offset of the content, relative to the end of the index of the image.
+    pub offset: u64,
+    /// The size of the stored content if it is compressed, or else 0.
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+}
+
+impl ImageLocation {
+    /// The name of the resource relative to its module, e.g. `java/lang/Object.class`.
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        if !self.parent.is_empty() {
+            name.push_str(&self.parent);
+            name.push('/');
+        }
+        name.push_str(&self.base);
+        if !self.extension.is_empty() {
+            name.push('.');
+            name.push_str(&self.extension);
+        }
+        name
+    }
+
+    /// The name under which the resource is looked up in the image, e.g. `/java.base/java/lang/Object.class`.
+    pub fn full_name(&self) -> String {
+        match self.module.is_empty() {
+            true => self.name(),
+            false => format!("/{}/{}", self.module, self.name()),
+        }
+    }
+}
+
+/// A jimage held in memory, as produced by `jlink` for the `lib/modules` file of a runtime image.
+///
+/// Resources are stored per module. Besides them the image holds a `/packages/<package>` entry for each package that
+/// lists the modules that contain it.
+#[derive(Debug)]
+pub struct JImage {
+    data: Vec<u8>,
+    big_endian: bool,
+    pub major_version: u16,
+    pub minor_version: u16,
+    table_length: usize,
+    redirect_start: usize,
+    offsets_start: usize,
+    locations_start: usize,
+    strings_start: usize,
+    index_size: usize,
+    /// The location offsets of the resources of all modules by their name relative to the module; the first
+    /// module in the image wins for names that several modules contain, such as `module-info.class`.
+    resource_offsets: HashMap<String, u32>,
+}
+
+impl JImage {
+    pub fn open(path: impl AsRef<Path>) -> Result<JImage, ArchiveError> {
+        JImage::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<JImage, ArchiveError> {
+        let magic = data.get(0..4).ok_or(ArchiveError::InvalidArchive)?;
+        let big_endian = match [magic[0], magic[1], magic[2], magic[3]] {
+            bytes if u32::from_le_bytes(bytes) == MAGIC => false,
+            bytes if u32::from_be_bytes(bytes) == MAGIC => true,
+            _ => return Err(ArchiveError::InvalidArchive),
+        };
+        let mut image = JImage {
+            data,
+            big_endian,
+            major_version: 0,
+            minor_version: 0,
+            table_length: 0,
+            redirect_start: HEADER_SIZE,
+            offsets_start: 0,
+            locations_start: 0,
+            strings_start: 0,
+            index_size: 0,
+            resource_offsets: HashMap::new(),
+        };
+
+        let version = image.u32_at(4)?;
+        image.major_version = (version >> 16) as u16;
+        image.minor_version = version as u16;
+        if image.major_version != 1 {
+            log::error!("unsupported jimage version; version={version:#x}");
+            return Err(ArchiveError::InvalidArchive);
+        }
+        image.table_length = image.u32_at(16)? as usize;
+        let locations_size = image.u32_at(20)? as usize;
+        let strings_size = image.u32_at(24)? as usize;
+        image.offsets_start = image.redirect_start + 4 * image.table_length;
+        image.locations_start = image.offsets_start + 4 * image.table_length;
+        image.strings_start = image.locations_start + locations_size;
+        image.index_size = image.strings_start + strings_size;
+        if image.index_size > image.data.len() {
+            return Err(ArchiveError::InvalidArchive);
+        }
+
+        for idx in 0..image.table_length {
+            let offset = image.u32_at(image.offsets_start + 4 * idx)?;
+            let location = image.location_at(offset)?;
+            if is_resource(&location) {
+                image
+                    .resource_offsets
+                    .entry(location.name())
+                    .or_insert(offset);
+            }
+        }
+        debug!(
+            "found jimage; resources={}, modules={}",
+            image.resource_offsets.len(),
+            image.modules()?.len()
+        );
+        Ok(image)
+    }
+
+    fn u32_at(&self, idx: usize) -> Result<u32, ArchiveError> {
+        let bytes = bytes_at(&self.data, idx, 4)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    fn u64_at(&self, data: &[u8], idx: usize) -> Result<u64, ArchiveError> {
+        let bytes: [u8; 8] = bytes_at(data, idx, 8)?
+            .try_into()
+            .map_err(|_| ArchiveError::InvalidArchive)?;
+        Ok(match self.big_endian {
+            true => u64::from_be_bytes(bytes),
+            false => u64::from_le_bytes(bytes),
+        })
+    }
+
+    /// The NUL-terminated string at an offset of the strings table, as raw bytes.
+    fn string_bytes(&self, offset: u32) -> Result<&[u8], ArchiveError> {
+        let start = self.strings_start + offset as usize;
+        let strings = self
+            .data
+            .get(start..self.index_size)
+            .ok_or(ArchiveError::InvalidArchive)?;
+        let length = strings
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or(ArchiveError::InvalidArchive)?;
+        Ok(&strings[..length])
+    }
+
+    fn string(&self, offset: u32) -> Result<String, ArchiveError> {
+        Ok(String::from_utf8_lossy(self.string_bytes(offset)?).into_owned())
+    }
+
+    /// Decodes the attributes of the location at an offset of the locations table.
+    fn location_at(&self, offset: u32) -> Result<ImageLocation, ArchiveError> {
+        let mut attributes = [0u64; 8];
+        let mut idx = self.locations_start + offset as usize;
+        loop {
+            let byte = *self.data.get(idx).ok_or(ArchiveError::InvalidArchive)?;
+            let kind = byte >> 3;
+            if kind == ATTRIBUTE_END {
+                break;
+            }
+            let length = usize::from(byte & 7) + 1;
+            let bytes = bytes_at(&self.data, idx + 1, length)?;
+            let value = bytes
+                .iter()
+                .fold(0u64, |value, byte| (value << 8) | u64::from(*byte));
+            match attributes.get_mut(usize::from(kind)) {
+                Some(attribute) => *attribute = value,
+                None => return Err(ArchiveError::InvalidArchive),
+            }
+            idx += 1 + length;
+        }
+        Ok(ImageLocation {
+            module: self.string(attributes[usize::from(ATTRIBUTE_MODULE)] as u32)?,
+            parent: self.string(attributes[usize::from(ATTRIBUTE_PARENT)] as u32)?,
+            base: self.string(attributes[usize::from(ATTRIBUTE_BASE)] as u32)?,
+            extension: self.string(attributes[usize::from(ATTRIBUTE_EXTENSION)] as u32)?,
+            offset: attributes[usize::from(ATTRIBUTE_OFFSET)],
+            compressed_size: attributes[usize::from(ATTRIBUTE_COMPRESSED)],
+            uncompressed_size: attributes[usize::from(ATTRIBUTE_UNCOMPRESSED)],
+        })
+    }
+
+    /// Looks a location up by its full name, e.g. `/java.base/java/lang/Object.class`, through the perfect hash
+    /// table of the image.
+    pub fn find_location(&self, full_name: &str) -> Result<Option<ImageLocation>, ArchiveError> {
+        if self.table_length == 0 {
+            return Ok(None);
+        }
+        let count = self.table_length as i32;
+        let slot = jimage_hash(full_name, HASH_MULTIPLIER) % count;
+        let redirect = self.u32_at(self.redirect_start + 4 * slot as usize)? as i32;
+        let idx = match redirect {
+            0 => return Ok(None),
+            // A negative redirect `r` is the index `-r - 1`, which is `!r` without overflowing for `i32::MIN`.
+            redirect if redirect < 0 => !redirect,
+            seed => jimage_hash(full_name, seed) % count,
+        };
+        if idx >= count {
+            return Err(ArchiveError::InvalidArchive);
+        }
+        let offset = self.u32_at(self.offsets_start + 4 * idx as usize)?;
+        let location = self.location_at(offset)?;
+        Ok((location.full_name() == full_name).then_some(location))
+    }
+
+    /// All locations of the image in the order of its table, including the `/packages/` entries.
+    pub fn locations(&self) -> Result<Vec<ImageLocation>, ArchiveError> {
+        (0..self.table_length)
+            .map(|idx| self.location_at(self.u32_at(self.offsets_start + 4 * idx)?))
+            .collect()
+    }
+
+    /// The names of all modules of the image, sorted.
+    pub fn modules(&self) -> Result<Vec<String>, ArchiveError> {
+        let mut modules: Vec<String> = self
+            .locations()?
+            .into_iter()
+            .filter(is_resource)
+            .map(|location| location.module)
+            .collect();
+        modules.sort();
+        modules.dedup();
+        Ok(modules)
+    }
+
+    /// The module that contains a resource, e.g. `java.base` for `java/lang/Object.class`.
+    pub fn module_of(&self, name: &str) -> Result<Option<String>, ArchiveError> {
+        match self.resource_offsets.get(name) {
+            Some(offset) => Ok(Some(self.location_at(*offset)?.module)),
+            None => Ok(None),
+        }
+    }
+
+    /// The names of all resources relative to their module, e.g. `java/lang/Object.class`.
+    pub fn resource_names(&self) -> impl Iterator<Item = &str> {
+        self.resource_offsets.keys().map(String::as_str)
+    }
+
+    pub fn contains_resource(&self, name: &str) -> bool {
+        self.resource_offsets.contains_key(name)
+    }
+
+    pub fn find_resource_location(
+        &self,
+        name: &str,
+    ) -> Result<Option<ImageLocation>, ArchiveError> {
+        self.resource_offsets
+            .get(name)
+            .map(|offset| self.location_at(*offset))
+            .transpose()
+    }
+
+    /// Reads the content of a location, decompressing it if it is compressed.
+    pub fn read(&self, location: &ImageLocation) -> Result<Vec<u8>, ArchiveError> {
+        let size = match location.compressed_size {
+            0 => location.uncompressed_size,
+            compressed_size => compressed_size,
+        };
+        let start = usize::try_from(location.offset)
+            .ok()
+            .and_then(|offset| self.index_size.checked_add(offset))
+            .ok_or(ArchiveError::InvalidArchive)?;
+        let size = usize::try_from(size).map_err(|_| ArchiveError::InvalidArchive)?;
+        let mut content = bytes_at(&self.data, start, size)?.to_vec();
+        if location.compressed_size == 0 {
+            return Ok(content);
+        }
+
+        // Resources can be compressed several times, each compression adding a header in front.
+        while self.u32_at_content(&content, 0) == Some(COMPRESSED_MAGIC) {
+            if content.len() < COMPRESSED_HEADER_SIZE {
+                return Err(ArchiveError::CorruptEntry(location.full_name()));
+            }
+            let uncompressed_size = self.u64_at(&content, 12)?;
+            let decompressor_offset = self
+                .u32_at_content(&content, 20)
+                .ok_or(ArchiveError::InvalidArchive)?;
+            let decompressor = self.string(decompressor_offset)?;
+            let compressed = &content[COMPRESSED_HEADER_SIZE..];
+            content = match decompressor.as_str() {
+                "zip" => miniz_oxide::inflate::decompress_to_vec_zlib(compressed)
+                    .map_err(|_| ArchiveError::CorruptEntry(location.full_name()))?,
+                "compact-cp" => self
+                    .expand_shared_strings(compressed)
+                    .ok_or_else(|| ArchiveError::CorruptEntry(location.full_name()))?,
+                _ => return Err(ArchiveError::UnsupportedEntry(location.full_name())),
+            };
+            if content.len() as u64 != uncompressed_size {
+                return Err(ArchiveError::CorruptEntry(location.full_name()));
+            }
+        }
+        Ok(content)
+    }
+
+    fn u32_at_content(&self, content: &[u8], idx: usize) -> Option<u32> {
+        let bytes: [u8; 4] = content.get(idx..idx + 4)?.try_into().ok()?;
+        Some(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    /// Reads a resource by its name relative to its module, e.g. `java/lang/Object.class`.
+    pub fn read_resource(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        match self.find_resource_location(name)? {
+            Some(location) => Ok(Some(self.read(&location)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Restores a class whose constant pool strings were moved to the strings table of the image by the
+    /// string sharing plugin of `jlink` (`--compress=1`).
+    fn expand_shared_strings(&self, content: &[u8]) -> Option<Vec<u8>> {
+        let mut reader = SharedStringsReader { content, idx: 0 };
+        let mut out = Vec::with_capacity(content.len() * 2);
+        out.extend_from_slice(reader.bytes(8)?);
+        let count = reader.u16()?;
+        out.extend_from_slice(&count.to_be_bytes());
+
+        let mut entry = 1;
+        while entry < count {
+            let tag = reader.u8()?;
+            match tag {
+                CONSTANT_UTF8 => {
+                    let length = reader.u16()?;
+                    push_utf8(&mut out, reader.bytes(usize::from(length))?);
+                }
+                EXTERNALIZED_STRING => {
+                    let offset = reader.compressed_int()?;
+                    push_utf8(&mut out, self.string_bytes(offset).ok()?);
+                }
+                EXTERNALIZED_STRING_DESCRIPTOR => {
+                    let descriptor = self.shared_descriptor(&mut reader)?;
+                    push_utf8(&mut out, &descriptor);
+                }
+                tag => {
+                    let size = match tag {
+                        3 | 4 | 9..=12 | 17 | 18 => 4,
+                        CONSTANT_LONG | CONSTANT_DOUBLE => 8,
+                        7 | 8 | 16 | 19 | 20 => 2,
+                        15 => 3,
+                        _ => return None,
+                    };
+                    out.push(tag);
+                    out.extend_from_slice(reader.bytes(size)?);
+                    if tag == CONSTANT_LONG || tag == CONSTANT_DOUBLE {
+                        entry += 1;
+                    }
+                }
+            }
+            entry += 1;
+        }
+        out.extend_from_slice(&content[reader.idx..]);
+        Some(out)
+    }
+
+    /// Rebuilds a descriptor whose class names were replaced by indices of their package and simple name.
+    fn shared_descriptor(&self, reader: &mut SharedStringsReader) -> Option<Vec<u8>> {
+        let descriptor_offset = reader.compressed_int()?;
+        let indices_length = reader.compressed_int()? as usize;
+        let indices_bytes = reader.bytes(indices_length)?;
+        let mut indices_reader = SharedStringsReader {
+            content: indices_bytes,
+            idx: 0,
+        };
+        let mut indices = Vec::new();
+        while indices_reader.idx < indices_bytes.len() {
+            indices.push(indices_reader.compressed_int()?);
+        }
+
+        let mut indices = indices.into_iter();
+        let mut descriptor = Vec::new();
+        for byte in self.string_bytes(descriptor_offset).ok()? {
+            descriptor.push(*byte);
+            if *byte == b'L' {
+                let package = self.string_bytes(indices.next()?).ok()?;
+                if !package.is_empty() {
+                    descriptor.extend_from_slice(package);
+                    descriptor.push(b'/');
+                }
+                descriptor.extend_from_slice(self.string_bytes(indices.next()?).ok()?);
+            }
+        }
+        Some(descriptor)
+    }
+}
+
+/// Whether a location is a resource of a module, rather than one of the `/packages/` entries.
+fn is_resource(location: &ImageLocation) -> bool {
+    !location.module.is_empty() && location.module != "modules" && location.module != "packages"
+}
+
+fn push_utf8(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.push(CONSTANT_UTF8);
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(bytes);
+}
+
+struct SharedStringsReader<'a> {
+    content: &'a [u8],
+    idx: usize,
+}
+
+impl<'a> SharedStringsReader<'a> {
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.content.get(self.idx..self.idx.checked_add(length)?)?;
+        self.idx += length;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.bytes(2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// An integer in the compressed form of the string sharing plugin: a header byte with the high bit set holds
+    /// the number of bytes in its next two bits and the highest bits of the value in the rest, otherwise the value
+    /// takes four bytes.
+    fn compressed_int(&mut self) -> Option<u32> {
+        let header = self.u8()?;
+        let (length, value) = match header & 0x80 {
+            0 => (4, u32::from(header)),
+            _ => (usize::from((header >> 5) & 3), u32::from(header & 0x1f)),
+        };
+        let rest = self.bytes(length.checked_sub(1)?)?;
+        Some(
+            rest.iter()
+                .fold(value, |value, byte| (value << 8) | u32::from(*byte)),
+        )
+    }
+}
@@ -0,0 +1,99 @@
+//! Reading of jmod files, the packaged modules in the `jmods` directory of a JDK.
+
+use std::path::Path;
+use std::result::Result;
+
+use crate::archive::{ArchiveError, JarEntry, JarFile};
+
+const MAGIC: [u8; 2] = [0x4a, 0x4d];
+
+/// The sections of a jmod file, each a directory of its zip archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum JmodSection {
+    Classes,
+    Config,
+    HeaderFiles,
+    LegalNotices,
+    ManPages,
+    NativeCommands,
+    NativeLibraries,
+}
+
+impl JmodSection {
+    pub const ALL: [JmodSection; 7] = [
+        JmodSection::Classes,
+        JmodSection::Config,
+        JmodSection::HeaderFiles,
+        JmodSection::LegalNotices,
+        JmodSection::ManPages,
+        JmodSection::NativeCommands,
+        JmodSection::NativeLibraries,
+    ];
+
+    /// The directory of the section in the archive, e.g. `classes/`.
+    pub fn directory(&self) -> &'static str {
+        match self {
+            JmodSection::Classes => "classes/",
+            JmodSection::Config => "conf/",
+            JmodSection::HeaderFiles => "include/",
+            JmodSection::LegalNotices => "legal/",
+            JmodSection::ManPages => "man/",
+            JmodSection::NativeCommands => "bin/",
+            JmodSection::NativeLibraries => "lib/",
+        }
+    }
+}
+
+/// A jmod file held in memory: a header of the magic number `JM` and a version, followed by a zip archive.
+#[derive(Debug)]
+pub struct JmodFile {
+    pub major_version: u8,
+    pub minor_version: u8,
+    archive: JarFile,
+}
+
+impl JmodFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<JmodFile, ArchiveError> {
+        JmodFile::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<JmodFile, ArchiveError> {
+        match data.get(0..4) {
+            Some(&[first, second, major_version, minor_version])
+                if [first, second] == MAGIC && major_version == 1 =>
+            {
+                Ok(JmodFile {
+                    major_version,
+                    minor_version,
+                    archive: JarFile::from_bytes(data)?,
+                })
+            }
+            Some(header) => {
+                log::error!("invalid jmod header; header={header:?}, expected magic={MAGIC:?}");
+                Err(ArchiveError::InvalidArchive)
+            }
+            None => Err(ArchiveError::InvalidArchive),
+        }
+    }
+
+    /// The entries of a section with their names relative to the section, e.g. `java/lang/Object.class` in the
+    /// classes section.
+    pub fn entries(&self, section: JmodSection) -> impl Iterator<Item = (&str, &JarEntry)> {
+        self.archive.entries().iter().filter_map(move |entry| {
+            let name = entry.name.strip_prefix(section.directory())?;
+            (!name.is_empty() && !entry.is_directory()).then_some((name, entry))
+        })
+    }
+
+    /// Reads an entry of a section by its name relative to the section.
+    pub fn read(&self, section: JmodSection, name: &str) -> Result<Vec<u8>, ArchiveError> {
+        self.archive
+            .read_entry(&format!("{}{name}", section.directory()))
+    }
+
+    /// The archive of the jmod file, with the names of its entries including the section directories.
+    pub fn archive(&self) -> &JarFile {
+        &self.archive
+    }
+}
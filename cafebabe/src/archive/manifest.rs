@@ -0,0 +1,125 @@
+use std::result::Result;
+
+use crate::archive::ArchiveError;
+
+/// A named section of a manifest, holding the attributes of a single entry.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ManifestSection {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+/// The `META-INF/MANIFEST.MF` of a jar.
+///
+/// The attributes of the main section that matter for running and resolving a jar are available as fields, all
+/// other attributes are kept in `attributes` in the order of the manifest.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Manifest {
+    /// `Main-Class`, in its binary name such as `com.example.Main`.
+    pub main_class: Option<String>,
+    /// `Class-Path`, the relative URLs of the jars the jar depends on.
+    pub class_path: Vec<String>,
+    /// `Multi-Release`, which enables the versioned entries below `META-INF/versions/`.
+    pub multi_release: bool,
+    /// `Automatic-Module-Name`, the module name of the jar when it is put on the module path.
+    pub automatic_module_name: Option<String>,
+    /// All attributes of the main section, including the ones above.
+    pub attributes: Vec<(String, String)>,
+    /// The sections of individual entries, each starting with a `Name` attribute.
+    pub sections: Vec<ManifestSection>,
+}
+
+impl Manifest {
+    /// The value of an attribute of the main section. Attribute names are case-insensitive.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        find_attribute(&self.attributes, name)
+    }
+
+    pub fn section(&self, name: &str) -> Option<&ManifestSection> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+}
+
+fn find_attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Joins continuation lines, which start with a single space, to the lines they continue.
+fn logical_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_section = false;
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match line.strip_prefix(' ') {
+            Some(continuation) if in_section => {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(continuation);
+                }
+            }
+            _ => {
+                in_section = !line.is_empty();
+                lines.push(line.to_string());
+            }
+        }
+    }
+    lines
+}
+
+fn read_attribute(line: &str) -> Result<(String, String), ArchiveError> {
+    match line.split_once(": ") {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => {
+            log::error!("invalid manifest line; line={line}");
+            Err(ArchiveError::InvalidManifest)
+        }
+    }
+}
+
+/// Parses a manifest as specified in the JAR file specification.
+pub fn read_manifest(data: &[u8]) -> Result<Manifest, ArchiveError> {
+    let text = std::str::from_utf8(data).map_err(|_| ArchiveError::InvalidManifest)?;
+    let mut groups: Vec<Vec<(String, String)>> = vec![Vec::new()];
+    for line in logical_lines(text) {
+        if line.is_empty() {
+            if groups.last().is_some_and(|group| !group.is_empty()) {
+                groups.push(Vec::new());
+            }
+            continue;
+        }
+        if let Some(group) = groups.last_mut() {
+            group.push(read_attribute(&line)?);
+        }
+    }
+
+    let mut groups = groups.into_iter();
+    let attributes = groups.next().unwrap_or_default();
+    let mut sections = Vec::new();
+    for attributes in groups.filter(|group| !group.is_empty()) {
+        let Some(name) = find_attribute(&attributes, "Name") else {
+            log::error!("manifest section without name; attributes={attributes:?}");
+            return Err(ArchiveError::InvalidManifest);
+        };
+        sections.push(ManifestSection {
+            name: name.to_string(),
+            attributes,
+        });
+    }
+
+    Ok(Manifest {
+        main_class: find_attribute(&attributes, "Main-Class").map(str::to_string),
+        class_path: find_attribute(&attributes, "Class-Path")
+            .map(|value| value.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default(),
+        multi_release: find_attribute(&attributes, "Multi-Release")
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("true")),
+        automatic_module_name: find_attribute(&attributes, "Automatic-Module-Name")
+            .map(str::to_string),
+        attributes,
+        sections,
+    })
+}
@@ -0,0 +1,271 @@
+use std::collections::{BTreeSet, HashMap};
+use std::result::Result;
+
+use crate::archive::{ArchiveError, JarEntry, JarFile, VersionConflict, VersionConflictKind};
+use crate::errors::ClassFileError;
+use crate::types::{AccessFlags, ClassFile, FieldAccessFlags, MethodAccessFlags};
+
+const VERSIONS_PREFIX: &str = "META-INF/versions/";
+/// The first release that reads versioned entries; lower directories are ignored.
+const FIRST_VERSIONED_RELEASE: u16 = 9;
+
+/// The release and the overridden name of an entry below `META-INF/versions/`, such as `(11, "a/B.class")` for
+/// `META-INF/versions/11/a/B.class`.
+fn versioned_name(name: &str) -> Option<(u16, &str)> {
+    let (release, name) = name.strip_prefix(VERSIONS_PREFIX)?.split_once('/')?;
+    match (release.parse().ok(), name.is_empty()) {
+        (Some(release), false) => Some((release, name)),
+        _ => None,
+    }
+}
+
+pub(super) fn versioned_indices(entries: &[JarEntry]) -> HashMap<String, Vec<(u16, usize)>> {
+    let mut indices: HashMap<String, Vec<(u16, usize)>> = HashMap::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        if let Some((release, name)) = versioned_name(&entry.name)
+            && release >= FIRST_VERSIONED_RELEASE
+        {
+            indices
+                .entry(name.to_string())
+                .or_default()
+                .push((release, idx));
+        }
+    }
+    for versions in indices.values_mut() {
+        versions.sort();
+    }
+    indices
+}
+
+/// The public API of a class as a set of descriptions, e.g. `method public static main([Ljava/lang/String;)V`.
+fn public_api(class_file: &ClassFile) -> Result<BTreeSet<String>, ClassFileError> {
+    let mut api = BTreeSet::new();
+    let class_flags = class_file.access_flags
+        & (AccessFlags::ACC_PUBLIC
+            | AccessFlags::ACC_FINAL
+            | AccessFlags::ACC_INTERFACE
+            | AccessFlags::ACC_ABSTRACT
+            | AccessFlags::ACC_ANNOTATION
+            | AccessFlags::ACC_ENUM);
+    let names: Vec<&str> = class_flags.iter_names().map(|(name, _)| name).collect();
+    api.insert(format!("class flags {}", names.join(" ")));
+    if let Some(super_class_name) = class_file.super_class_name()? {
+        api.insert(format!("extends {super_class_name}"));
+    }
+    for interface_name in class_file.interface_names()? {
+        api.insert(format!("implements {interface_name}"));
+    }
+
+    let pool = &class_file.constant_pool;
+    for field in &class_file.fields {
+        let flags = field.access_flags;
+        if flags.contains(FieldAccessFlags::ACC_SYNTHETIC)
+            || !flags.intersects(FieldAccessFlags::ACC_PUBLIC | FieldAccessFlags::ACC_PROTECTED)
+        {
+            continue;
+        }
+        let names: Vec<&str> = flags.iter_names().map(|(name, _)| name).collect();
+        api.insert(format!(
+            "field {} {}:{}",
+            names.join(" "),
+            pool.string_entry(field.name_idx)?,
+            pool.string_entry(field.descriptor_idx)?
+        ));
+    }
+    for method in &class_file.methods {
+        let flags = method.access_flags;
+        if flags.intersects(MethodAccessFlags::ACC_SYNTHETIC | MethodAccessFlags::ACC_BRIDGE)
+            || !flags.intersects(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_PROTECTED)
+        {
+            continue;
+        }
+        let flags = flags - (MethodAccessFlags::ACC_SYNCHRONIZED | MethodAccessFlags::ACC_STRICT);
+        let names: Vec<&str> = flags.iter_names().map(|(name, _)| name).collect();
+        api.insert(format!(
+            "method {} {}{}",
+            names.join(" "),
+            pool.string_entry(method.name_idx)?,
+            pool.string_entry(method.descriptor_idx)?
+        ));
+    }
+    Ok(api)
+}
+
+impl JarFile {
+    /// Whether the manifest enables the versioned entries below `META-INF/versions/`.
+    pub fn is_multi_release(&self) -> bool {
+        self.manifest
+            .as_ref()
+            .is_some_and(|manifest| manifest.multi_release)
+    }
+
+    /// The entry that a Java runtime of the given release, such as 17, reads for an entry name.
+    ///
+    /// In a multi-release jar this is the entry in the highest `META-INF/versions/N/` directory with N up to the
+    /// release, or else the base entry. Other jars always give the base entry.
+    pub fn resolve_entry(&self, name: &str, release: u16) -> Option<&JarEntry> {
+        if self.is_multi_release()
+            && let Some(versions) = self.versioned_indices.get(name)
+            && let Some((_, idx)) = versions
+                .iter()
+                .rev()
+                .find(|(version, _)| *version <= release)
+        {
+            return Some(&self.entries[*idx]);
+        }
+        self.entry(name)
+    }
+
+    pub fn read_entry_for_release(
+        &self,
+        name: &str,
+        release: u16,
+    ) -> Result<Vec<u8>, ArchiveError> {
+        match self.resolve_entry(name, release) {
+            Some(entry) => self.read(entry),
+            None => Err(ArchiveError::EntryNotFound(name.to_string())),
+        }
+    }
+
+    /// Reads and parses the class a Java runtime of the given release loads for a binary name.
+    pub fn read_class_for_release(
+        &self,
+        class_name: &str,
+        release: u16,
+    ) -> Result<ClassFile, ArchiveError> {
+        let name = format!("{class_name}.class");
+        match self.resolve_entry(&name, release) {
+            Some(entry) => self.read_class_entry(entry),
+            None => Err(ArchiveError::EntryNotFound(name)),
+        }
+    }
+
+    /// The binary names of all classes visible to a Java runtime of the given release: the base classes followed by
+    /// the classes that only exist in versioned directories, sorted by name. Other classes below `META-INF/` are left
+    /// out.
+    pub fn class_names_for_release(&self, release: u16) -> Vec<&str> {
+        let mut class_names: Vec<&str> = self
+            .class_names()
+            .filter(|class_name| !class_name.starts_with("META-INF/"))
+            .collect();
+        if !self.is_multi_release() {
+            return class_names;
+        }
+        let mut versioned: Vec<&str> = self
+            .versioned_indices
+            .iter()
+            .filter(|(name, versions)| {
+                self.entry(name).is_none()
+                    && versions.iter().any(|(version, _)| *version <= release)
+            })
+            .filter_map(|(name, _)| name.strip_suffix(".class"))
+            .collect();
+        versioned.sort();
+        class_names.extend(versioned);
+        class_names
+    }
+
+    /// Checks the versioned classes of the jar against the classes they override, like `jar --validate`.
+    ///
+    /// Each versioned class is compared with its base class, or for a class without a base entry with the class of
+    /// the lowest release. Jars that aren't multi-release have no conflicts.
+    pub fn check_versions(&self) -> Result<Vec<VersionConflict>, ArchiveError> {
+        let mut conflicts = Vec::new();
+        if !self.is_multi_release() {
+            return Ok(conflicts);
+        }
+
+        for entry in &self.entries {
+            let Some((release, name)) = versioned_name(&entry.name) else {
+                continue;
+            };
+            if release < FIRST_VERSIONED_RELEASE {
+                conflicts.push(VersionConflict {
+                    entry: entry.name.clone(),
+                    release,
+                    kind: VersionConflictKind::UnsupportedRelease,
+                });
+                continue;
+            }
+            let Some(class_name) = name.strip_suffix(".class") else {
+                continue;
+            };
+            if class_name.ends_with("module-info") {
+                continue;
+            }
+            match self.check_version(entry, release, name, class_name) {
+                Ok(kinds) => conflicts.extend(kinds.into_iter().map(|kind| VersionConflict {
+                    entry: entry.name.clone(),
+                    release,
+                    kind,
+                })),
+                // A class that can't be parsed is reported, and the other classes are still checked.
+                Err(error @ ArchiveError::InvalidClass(..)) => conflicts.push(VersionConflict {
+                    entry: entry.name.clone(),
+                    release,
+                    kind: VersionConflictKind::InvalidClass {
+                        error: error.to_string(),
+                    },
+                }),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// The conflicts of the versioned class in an entry for a release, which overrides `name`.
+    fn check_version(
+        &self,
+        entry: &JarEntry,
+        release: u16,
+        name: &str,
+        class_name: &str,
+    ) -> Result<Vec<VersionConflictKind>, ArchiveError> {
+        let mut conflicts = Vec::new();
+        let class_file = self.read_class_entry(entry)?;
+        let invalid = |error: ClassFileError| ArchiveError::InvalidClass(entry.name.clone(), error);
+        // A class of release N has the major version N + 44.
+        if class_file.version.major > release.saturating_add(44) {
+            let major = class_file.version.major;
+            conflicts.push(VersionConflictKind::ClassVersionTooHigh { major });
+        }
+        let this_class_name = class_file.this_class_name().map_err(invalid)?;
+        if this_class_name != class_name {
+            let class_name = this_class_name.clone();
+            conflicts.push(VersionConflictKind::ClassNameMismatch { class_name });
+            return Ok(conflicts);
+        }
+
+        let overridden = match self.entry(name) {
+            Some(base) => Some(base),
+            None => self.versioned_indices[name]
+                .first()
+                .map(|(_, idx)| &self.entries[*idx])
+                .filter(|lowest| lowest.name != entry.name),
+        };
+        let Some(overridden) = overridden else {
+            if class_file.access_flags.contains(AccessFlags::ACC_PUBLIC) {
+                conflicts.push(VersionConflictKind::NewPublicClass);
+            }
+            return Ok(conflicts);
+        };
+
+        let overridden_class = self.read_class_entry(overridden)?;
+        let expected = public_api(&overridden_class)
+            .map_err(|error| ArchiveError::InvalidClass(overridden.name.clone(), error))?;
+        let actual = public_api(&class_file).map_err(invalid)?;
+        let mut differences: Vec<String> = expected
+            .difference(&actual)
+            .map(|item| format!("removed {item}"))
+            .collect();
+        differences.extend(
+            actual
+                .difference(&expected)
+                .map(|item| format!("added {item}")),
+        );
+        if !differences.is_empty() {
+            conflicts.push(VersionConflictKind::ApiChanged { differences });
+        }
+        Ok(conflicts)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::errors::ClassFileError;
+
+/// How the data of an archive entry is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Compression {
+    Stored,
+    Deflated,
+    /// A compression method that can't be read, with its number from the archive.
+    Unsupported(u16),
+}
+
+impl Compression {
+    pub fn from_method(method: u16) -> Compression {
+        match method {
+            0 => Compression::Stored,
+            8 => Compression::Deflated,
+            other => Compression::Unsupported(other),
+        }
+    }
+}
+
+/// An entry of the central directory of a jar or zip file.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct JarEntry {
+    /// The path of the entry within the archive, e.g. `java/lang/Object.class`.
+    pub name: String,
+    pub compression: Compression,
+    pub crc32: u32,
+    pub compressed_size: u64,
+    pub size: u64,
+    /// Whether the entry is encrypted, in which case it can't be read.
+    pub encrypted: bool,
+    /// The offset of the local file header of the entry.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) header_offset: u64,
+}
+
+impl JarEntry {
+    pub fn is_directory(&self) -> bool {
+        self.name.ends_with('/')
+    }
+
+    /// The binary name of the class in the entry in its internal form, e.g. `java/lang/Object` for
+    /// `java/lang/Object.class`, or `None` if it doesn't hold a class.
+    pub fn class_name(&self) -> Option<&str> {
+        self.name.strip_suffix(".class")
+    }
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(std::io::Error),
+    /// The archive is not a zip file, or its structure is damaged.
+    InvalidArchive,
+    /// The entry is encrypted or uses a compression method other than stored or deflated, or it is too large to be
+    /// written.
+    UnsupportedEntry(String),
+    /// The data of the entry doesn't match its size or checksum.
+    CorruptEntry(String),
+    EntryNotFound(String),
+    /// An entry with the same name was already written.
+    DuplicateEntry(String),
+    InvalidManifest,
+    /// A class in the archive could not be parsed.
+    InvalidClass(String, ClassFileError),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::Io(error) => write!(f, "{error}"),
+            ArchiveError::InvalidArchive => write!(f, "InvalidArchive"),
+            ArchiveError::UnsupportedEntry(name) => write!(f, "UnsupportedEntry({name})"),
+            ArchiveError::CorruptEntry(name) => write!(f, "CorruptEntry({name})"),
+            ArchiveError::EntryNotFound(name) => write!(f, "EntryNotFound({name})"),
+            ArchiveError::DuplicateEntry(name) => write!(f, "DuplicateEntry({name})"),
+            ArchiveError::InvalidManifest => write!(f, "InvalidManifest"),
+            ArchiveError::InvalidClass(name, error) => write!(f, "{error} in {name}"),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(error: std::io::Error) -> Self {
+        ArchiveError::Io(error)
+    }
+}
+
+/// A versioned entry of a multi-release jar that doesn't agree with the entry it overrides.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VersionConflict {
+    /// The name of the versioned entry, e.g. `META-INF/versions/11/com/example/Foo.class`.
+    pub entry: String,
+    /// The release of the directory the entry is in.
+    pub release: u16,
+    pub kind: VersionConflictKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VersionConflictKind {
+    /// The entry is in a directory for a release before 9, which is ignored.
+    UnsupportedRelease,
+    /// The class needs a later release than the directory it is in.
+    ClassVersionTooHigh { major: u16 },
+    /// The class is not the class its path names.
+    ClassNameMismatch { class_name: String },
+    /// The class is public and has no base entry, so that the API of the jar depends on the release.
+    NewPublicClass,
+    /// The public API of the class differs from the one of the class it overrides, described by each difference.
+    ApiChanged { differences: Vec<String> },
+    /// The class, or the class it overrides, can't be read, described by the error.
+    InvalidClass { error: String },
+}
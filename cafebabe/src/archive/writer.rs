@@ -0,0 +1,150 @@
+//! Writing of jar and zip files, without ZIP64 extensions, so that entries and archives are limited to 4 GiB and
+//! 65535 entries.
+
+use std::collections::HashSet;
+use std::result::Result;
+
+use super::types::{ArchiveError, Compression, JarEntry};
+use super::{CENTRAL_HEADER_SIGNATURE, END_SIGNATURE, LOCAL_HEADER_SIGNATURE, crc32};
+
+const VERSION: u16 = 20;
+/// The flag for names encoded in UTF-8.
+const UTF8_FLAG: u16 = 0x0800;
+/// The DOS time and date of all entries, midnight on 1980-01-01, so that the same entries give the same archive.
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+const DEFLATE_LEVEL: u8 = 6;
+
+fn put_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Builds a jar or zip file in memory, entry by entry.
+#[derive(Debug, Default)]
+pub struct JarWriter {
+    data: Vec<u8>,
+    entries: Vec<JarEntry>,
+    names: HashSet<String>,
+}
+
+impl JarWriter {
+    pub fn new() -> JarWriter {
+        JarWriter::default()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// Adds an entry, or a directory if its name ends with `/`. Deflated entries are stored instead if deflating
+    /// them doesn't make them smaller, and directories are always stored.
+    pub fn add(
+        &mut self,
+        name: &str,
+        data: &[u8],
+        compression: Compression,
+    ) -> Result<(), ArchiveError> {
+        if !self.names.insert(name.to_string()) {
+            return Err(ArchiveError::DuplicateEntry(name.to_string()));
+        }
+        let too_large = || ArchiveError::UnsupportedEntry(name.to_string());
+        let (compression, compressed) = match compression {
+            Compression::Deflated if !name.ends_with('/') => {
+                let deflated = miniz_oxide::deflate::compress_to_vec(data, DEFLATE_LEVEL);
+                match deflated.len() < data.len() {
+                    true => (Compression::Deflated, deflated),
+                    false => (Compression::Stored, data.to_vec()),
+                }
+            }
+            Compression::Unsupported(_) => return Err(too_large()),
+            _ => (Compression::Stored, data.to_vec()),
+        };
+        let method = match compression {
+            Compression::Deflated => 8,
+            _ => 0,
+        };
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let compressed_size = u32::try_from(compressed.len()).map_err(|_| too_large())?;
+        let header_offset = u32::try_from(self.data.len()).map_err(|_| too_large())?;
+        let name_length = u16::try_from(name.len()).map_err(|_| too_large())?;
+        if self.entries.len() == usize::from(u16::MAX) {
+            return Err(too_large());
+        }
+        let crc32 = crc32(data);
+
+        put_u32(&mut self.data, LOCAL_HEADER_SIGNATURE);
+        put_u16(&mut self.data, VERSION);
+        put_u16(&mut self.data, UTF8_FLAG);
+        put_u16(&mut self.data, method);
+        put_u16(&mut self.data, DOS_TIME);
+        put_u16(&mut self.data, DOS_DATE);
+        put_u32(&mut self.data, crc32);
+        put_u32(&mut self.data, compressed_size);
+        put_u32(&mut self.data, size);
+        put_u16(&mut self.data, name_length);
+        put_u16(&mut self.data, 0);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(&compressed);
+
+        self.entries.push(JarEntry {
+            name: name.to_string(),
+            compression,
+            crc32,
+            compressed_size: u64::from(compressed_size),
+            size: u64::from(size),
+            encrypted: false,
+            header_offset: u64::from(header_offset),
+        });
+        Ok(())
+    }
+
+    /// Writes the central directory after the entries and gives the bytes of the archive.
+    pub fn finish(mut self) -> Result<Vec<u8>, ArchiveError> {
+        let directory_offset =
+            u32::try_from(self.data.len()).map_err(|_| ArchiveError::InvalidArchive)?;
+        for entry in &self.entries {
+            let method = match entry.compression {
+                Compression::Deflated => 8,
+                _ => 0,
+            };
+            put_u32(&mut self.data, CENTRAL_HEADER_SIGNATURE);
+            put_u16(&mut self.data, VERSION);
+            put_u16(&mut self.data, VERSION);
+            put_u16(&mut self.data, UTF8_FLAG);
+            put_u16(&mut self.data, method);
+            put_u16(&mut self.data, DOS_TIME);
+            put_u16(&mut self.data, DOS_DATE);
+            put_u32(&mut self.data, entry.crc32);
+            // The sizes and offset were checked to fit when the entry was added.
+            put_u32(&mut self.data, entry.compressed_size as u32);
+            put_u32(&mut self.data, entry.size as u32);
+            put_u16(&mut self.data, entry.name.len() as u16);
+            // The lengths of the extra field and the comment, the disk number and the file attributes.
+            put_u16(&mut self.data, 0);
+            put_u16(&mut self.data, 0);
+            put_u16(&mut self.data, 0);
+            put_u16(&mut self.data, 0);
+            put_u32(&mut self.data, 0);
+            put_u32(&mut self.data, entry.header_offset as u32);
+            self.data.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = u32::try_from(self.data.len())
+            .map_err(|_| ArchiveError::InvalidArchive)?
+            - directory_offset;
+        let entry_count = self.entries.len() as u16;
+
+        put_u32(&mut self.data, END_SIGNATURE);
+        put_u16(&mut self.data, 0);
+        put_u16(&mut self.data, 0);
+        put_u16(&mut self.data, entry_count);
+        put_u16(&mut self.data, entry_count);
+        put_u32(&mut self.data, directory_size);
+        put_u32(&mut self.data, directory_offset);
+        put_u16(&mut self.data, 0);
+        Ok(self.data)
+    }
+}
@@ -0,0 +1,98 @@
+//! A textual assembly syntax for class files, for writing classes by hand that `javac` would never produce.
+//!
+//! A class is described by directives, one after the other:
+//!
+//! ```text
+//! .version 52 0
+//! .class public super examples/Hello
+//! .super java/lang/Object
+//! .implements java/lang/Runnable
+//! .sourcefile Hello.java
+//!
+//! .field private static final GREETING Ljava/lang/String;
+//!     .constantvalue String "Hello, world!"
+//! .end field
+//!
+//! .method public run ()V
+//!     .code stack 2 locals 1
+//!         getstatic java/lang/System out Ljava/io/PrintStream;
+//!         ldc String "Hello, world!"
+//!         invokevirtual java/io/PrintStream println (Ljava/lang/String;)V
+//!         return
+//!     .end code
+//! .end method
+//! ```
+//!
+//! * `.version` defaults to 52 (Java 8). Without `.super` the class has no superclass, as for `module-info`.
+//! * Access flags are written as the names of the `ACC_` flags in lower case.
+//! * Constant pool entries are written as `#n` or as a keyword followed by the contents of the entry:
+//!   `Utf8`, `Int`, `Float`, `Long`, `Double`, `Class`, `String`, `MethodType`, `Module`, `Package`, `Field`, `Method`,
+//!   `InterfaceMethod` (each followed by a class, a name and a descriptor), `NameAndType`,
+//!   `MethodHandle <kind> <reference>` (with kinds such as `invokeStatic`) and `Dynamic`/`InvokeDynamic` (followed by
+//!   the index of a bootstrap method, a name and a descriptor). Entries are added to the pool as they are needed.
+//!   Where a class, field or method is expected its keyword may be left out, as in the example above.
+//!   Floating point values may also be given by their bits in hexadecimal, e.g. `Float 0x7fc00000`, and strings by
+//!   their bytes in modified UTF-8, e.g. `Utf8 bytes "eda080"` for an unpaired surrogate.
+//! * `.const #n = <constant>` places an entry at a given index, so that the layout of a pool can be reproduced.
+//! * Fields may be followed by attributes, in which case they end with `.end field`. Methods end with `.end method`.
+//! * Attributes are `.code stack <n> locals <n>` up to `.end code`, `.sourcefile`, `.signature`, `.constantvalue`,
+//!   `.exceptions` (followed by the classes on the same line), `.deprecated`, `.synthetic`, `.bootstrapmethods` (one
+//!   method handle and its arguments per line, up to `.end bootstrapmethods`) and `.attribute <name> "<hex bytes>"`
+//!   for any other attribute.
+//! * In code, `name:` defines a label and instructions are written as their mnemonics followed by their operands.
+//!   Branch targets are labels or offsets. `wide` may prefix a local variable instruction, which is otherwise widened
+//!   when needed. Switches list their targets up to `default <target>`: `tableswitch <low> <targets>` and
+//!   `lookupswitch <key> <target> ...`. The argument count of `invokeinterface` may be left out.
+//! * Code may contain `.catch <class>|any from <label> to <label> using <label>`, `.linenumbertable` with
+//!   `<label> <line>` pairs, and `.localvariabletable`/`.localvariabletypetable` with
+//!   `<index> <name> <descriptor> from <label> to <label>` entries, each closed by `.end <directive name>`.
+//! * `//` starts a comment that runs to the end of the line.
+//!
+//! [`disassemble`] prints a class file in this syntax such that assembling the output yields the same class file.
+
+use std::fmt;
+use std::result::Result;
+
+use crate::types::ClassFile;
+
+mod builder;
+mod disassembler;
+mod lexer;
+mod syntax;
+
+pub use disassembler::disassemble;
+
+/// An error in assembly source, with the line it was found on.
+#[derive(Debug, PartialEq)]
+pub struct AssemblyError {
+    /// The line of the error, or 0 if it concerns the source as a whole.
+    pub line: usize,
+    pub message: String,
+}
+
+impl AssemblyError {
+    pub fn new(line: usize, message: impl Into<String>) -> AssemblyError {
+        AssemblyError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "{}", self.message),
+            line => write!(f, "line {line}: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+/// Assembles a class file from its assembly source. Use [`crate::write_class_data`] to turn it into bytes.
+pub fn assemble(source: &str) -> Result<ClassFile, AssemblyError> {
+    let tokens = lexer::tokenize(source)?;
+    let class = syntax::parse(&tokens)?;
+    builder::build(&class)
+}
@@ -0,0 +1,729 @@
+use std::collections::HashMap;
+use std::result::Result;
+
+use crate::assembler::AssemblyError;
+use crate::assembler::syntax::{
+    AttributeKind, AttributeSyntax, ClassSyntax, CodeItem, CodeSyntax, Constant,
+    LocalVariableSyntax, MemberSyntax, OperandSyntax, Position,
+};
+use crate::attributes::{
+    Attribute, BootstrapMethod, Code, ExceptionHandler, LineNumber, LocalVariable,
+};
+use crate::bytecode::{Instruction, Opcode, Operand, encode};
+use crate::constant_pool::ConstantPool;
+use crate::constant_pool::types::{
+    ConstantPoolEntry, DynamicRef, FieldRef, MethodHandle, MethodRef, NameTypeDescriptor, Version,
+};
+use crate::descriptor::MethodDescriptor;
+use crate::types::{ClassDefinition, ClassFile, FieldInfo, MethodInfo};
+
+/// Whether two entries are the same, comparing floating point values by their bits.
+fn same_entry(left: &ConstantPoolEntry, right: &ConstantPoolEntry) -> bool {
+    match (left, right) {
+        (ConstantPoolEntry::Float(left), ConstantPoolEntry::Float(right)) => {
+            left.to_bits() == right.to_bits()
+        }
+        (ConstantPoolEntry::Double(left), ConstantPoolEntry::Double(right)) => {
+            left.to_bits() == right.to_bits()
+        }
+        _ => left == right,
+    }
+}
+
+fn takes_two_slots(entry: &ConstantPoolEntry) -> bool {
+    matches!(
+        entry,
+        ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_)
+    )
+}
+
+/// Builds the constant pool, placing explicitly numbered entries first and the others in the lowest free slots.
+struct PoolBuilder {
+    /// The entry at position `n` has index `n + 1`.
+    slots: Vec<Option<ConstantPoolEntry>>,
+    /// Slots that are claimed by an explicitly numbered entry that is not placed yet.
+    reserved: Vec<bool>,
+    /// The bytes of the String entries that were given by their bytes, by position.
+    raw_strings: HashMap<usize, Vec<u8>>,
+}
+
+impl PoolBuilder {
+    fn is_free(&self, position: usize) -> bool {
+        let taken = self.slots.get(position).is_some_and(Option::is_some);
+        let reserved = self
+            .reserved
+            .get(position)
+            .is_some_and(|reserved| *reserved);
+        !taken && !reserved
+    }
+
+    fn set(&mut self, position: usize, entry: ConstantPoolEntry) {
+        if self.slots.len() <= position {
+            self.slots.resize(position + 1, None);
+        }
+        self.slots[position] = Some(entry);
+    }
+
+    fn reserve(&mut self, position: usize) {
+        if self.reserved.len() <= position {
+            self.reserved.resize(position + 1, false);
+        }
+        self.reserved[position] = true;
+    }
+
+    fn entry(&self, index: u16) -> Option<&ConstantPoolEntry> {
+        match index {
+            0 => None,
+            index => self.slots.get(usize::from(index - 1))?.as_ref(),
+        }
+    }
+
+    fn utf8(&self, index: u16) -> Option<&String> {
+        match self.entry(index) {
+            Some(ConstantPoolEntry::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn place(&mut self, entry: ConstantPoolEntry, line: usize) -> Result<u16, AssemblyError> {
+        if let Some(position) = self.slots.iter().enumerate().position(|(position, slot)| {
+            !self.raw_strings.contains_key(&position)
+                && slot.as_ref().is_some_and(|slot| same_entry(slot, &entry))
+        }) {
+            return Ok(position as u16 + 1);
+        }
+
+        let wide = takes_two_slots(&entry);
+        let mut position = 0;
+        while !self.is_free(position) || (wide && !self.is_free(position + 1)) {
+            position += 1;
+        }
+        if position + usize::from(wide) >= usize::from(u16::MAX - 1) {
+            return Err(AssemblyError::new(line, "the constant pool is full"));
+        }
+        self.set(position, entry);
+        if wide {
+            self.set(position + 1, ConstantPoolEntry::Empty());
+        }
+        Ok(position as u16 + 1)
+    }
+
+    /// The entry that a constant describes, with the entries it refers to resolved.
+    fn build_entry(
+        &mut self,
+        constant: &Constant,
+        line: usize,
+    ) -> Result<ConstantPoolEntry, AssemblyError> {
+        let entry = match constant {
+            Constant::Index(index) => {
+                return Err(AssemblyError::new(
+                    line,
+                    format!("#{index} does not describe an entry"),
+                ));
+            }
+            Constant::Utf8(value) => ConstantPoolEntry::String(value.clone()),
+            // The bytes are decoded by the constant pool when it is built.
+            Constant::Utf8Bytes(_) => ConstantPoolEntry::String(String::new()),
+            Constant::Integer(value) => ConstantPoolEntry::Integer(*value),
+            Constant::Float(value) => ConstantPoolEntry::Float(*value),
+            Constant::Long(value) => ConstantPoolEntry::Long(*value),
+            Constant::Double(value) => ConstantPoolEntry::Double(*value),
+            Constant::Class(name) => ConstantPoolEntry::ClassRef(self.resolve(name, line)?),
+            Constant::String(value) => ConstantPoolEntry::StringRef(self.resolve(value, line)?),
+            Constant::MethodType(descriptor) => {
+                ConstantPoolEntry::MethodType(self.resolve(descriptor, line)?)
+            }
+            Constant::Module(name) => ConstantPoolEntry::Module(self.resolve(name, line)?),
+            Constant::Package(name) => ConstantPoolEntry::Package(self.resolve(name, line)?),
+            Constant::Field(class, name_and_type) => ConstantPoolEntry::FieldRef(FieldRef {
+                class_ref: self.resolve(class, line)?,
+                name_type_ref: self.resolve(name_and_type, line)?,
+            }),
+            Constant::Method(class, name_and_type) => ConstantPoolEntry::MethodRef(MethodRef {
+                class_ref: self.resolve(class, line)?,
+                name_type_ref: self.resolve(name_and_type, line)?,
+            }),
+            Constant::InterfaceMethod(class, name_and_type) => {
+                ConstantPoolEntry::InterfaceMethodRef(MethodRef {
+                    class_ref: self.resolve(class, line)?,
+                    name_type_ref: self.resolve(name_and_type, line)?,
+                })
+            }
+            Constant::NameAndType(name, descriptor) => {
+                ConstantPoolEntry::NameTypeDescriptor(NameTypeDescriptor {
+                    name_ref: self.resolve(name, line)?,
+                    type_descriptor_ref: self.resolve(descriptor, line)?,
+                })
+            }
+            Constant::MethodHandle(reference_kind, reference) => {
+                ConstantPoolEntry::MethodHandle(MethodHandle {
+                    reference_kind: *reference_kind,
+                    reference_ref: self.resolve(reference, line)?,
+                })
+            }
+            Constant::Dynamic(bootstrap_method_attr_idx, name_and_type) => {
+                ConstantPoolEntry::Dynamic(DynamicRef {
+                    bootstrap_method_attr_idx: *bootstrap_method_attr_idx,
+                    name_type_ref: self.resolve(name_and_type, line)?,
+                })
+            }
+            Constant::InvokeDynamic(bootstrap_method_attr_idx, name_and_type) => {
+                ConstantPoolEntry::InvokeDynamic(DynamicRef {
+                    bootstrap_method_attr_idx: *bootstrap_method_attr_idx,
+                    name_type_ref: self.resolve(name_and_type, line)?,
+                })
+            }
+        };
+        Ok(entry)
+    }
+
+    /// The index of the entry a constant refers to, adding the entry to the pool if needed.
+    fn resolve(&mut self, constant: &Constant, line: usize) -> Result<u16, AssemblyError> {
+        match constant {
+            Constant::Index(index) => Ok(*index),
+            Constant::Utf8Bytes(bytes) => {
+                if let Some((position, _)) = self.raw_strings.iter().find(|(_, raw)| *raw == bytes)
+                {
+                    return Ok(*position as u16 + 1);
+                }
+                let mut position = 0;
+                while !self.is_free(position) {
+                    position += 1;
+                }
+                if position >= usize::from(u16::MAX - 1) {
+                    return Err(AssemblyError::new(line, "the constant pool is full"));
+                }
+                self.set(position, ConstantPoolEntry::String(String::new()));
+                self.raw_strings.insert(position, bytes.clone());
+                Ok(position as u16 + 1)
+            }
+            constant => {
+                let entry = self.build_entry(constant, line)?;
+                self.place(entry, line)
+            }
+        }
+    }
+
+    fn build(self) -> Result<ConstantPool, AssemblyError> {
+        let mut items = Vec::with_capacity(self.slots.len());
+        for (position, slot) in self.slots.into_iter().enumerate() {
+            match slot {
+                Some(entry) => items.push(entry),
+                None => {
+                    return Err(AssemblyError::new(
+                        0,
+                        format!("constant pool entry #{} is not defined", position + 1),
+                    ));
+                }
+            }
+        }
+        let mut pool = ConstantPool::new(items);
+        for (position, bytes) in &self.raw_strings {
+            pool.set_raw_string(*position as u16 + 1, bytes)
+                .map_err(|_| {
+                    AssemblyError::new(0, format!("#{} is not modified UTF-8", position + 1))
+                })?;
+        }
+        Ok(pool)
+    }
+}
+
+fn attribute_name(kind: &AttributeKind) -> Option<&'static str> {
+    match kind {
+        AttributeKind::Code(_) => Some("Code"),
+        AttributeKind::SourceFile(_) => Some("SourceFile"),
+        AttributeKind::Signature(_) => Some("Signature"),
+        AttributeKind::ConstantValue(_) => Some("ConstantValue"),
+        AttributeKind::Exceptions(_) => Some("Exceptions"),
+        AttributeKind::Deprecated => Some("Deprecated"),
+        AttributeKind::Synthetic => Some("Synthetic"),
+        AttributeKind::BootstrapMethods(_) => Some("BootstrapMethods"),
+        AttributeKind::LineNumberTable(_) => Some("LineNumberTable"),
+        AttributeKind::LocalVariableTable(_) => Some("LocalVariableTable"),
+        AttributeKind::LocalVariableTypeTable(_) => Some("LocalVariableTypeTable"),
+        AttributeKind::Raw { .. } => None,
+    }
+}
+
+/// The offsets of the labels of a Code attribute, and the length of its code.
+struct Labels {
+    offsets: HashMap<String, u32>,
+    code_length: u32,
+}
+
+impl Labels {
+    fn offset(&self, position: &Position, line: usize) -> Result<u32, AssemblyError> {
+        match position {
+            Position::Offset(offset) => Ok(u32::from(*offset)),
+            Position::Label(label) => self
+                .offsets
+                .get(label)
+                .copied()
+                .ok_or_else(|| AssemblyError::new(line, format!("undefined label {label:?}"))),
+        }
+    }
+
+    fn pc(&self, position: &Position, line: usize) -> Result<u16, AssemblyError> {
+        u16::try_from(self.offset(position, line)?)
+            .map_err(|_| AssemblyError::new(line, "offset does not fit in 16 bits"))
+    }
+}
+
+struct Builder {
+    pool: PoolBuilder,
+}
+
+impl Builder {
+    fn local_variables(
+        &mut self,
+        local_variables: &[LocalVariableSyntax],
+        labels: Option<&Labels>,
+    ) -> Result<Vec<LocalVariable>, AssemblyError> {
+        let Some(labels) = labels else {
+            return Err(AssemblyError::new(
+                local_variables.first().map_or(0, |variable| variable.line),
+                "local variable tables belong in .code",
+            ));
+        };
+        local_variables
+            .iter()
+            .map(|variable| {
+                let start_pc = labels.pc(&variable.start, variable.line)?;
+                let end_pc = labels.pc(&variable.end, variable.line)?;
+                Ok(LocalVariable {
+                    start_pc,
+                    length: end_pc.wrapping_sub(start_pc),
+                    name_idx: self.pool.resolve(&variable.name, variable.line)?,
+                    descriptor_idx: self.pool.resolve(&variable.descriptor, variable.line)?,
+                    index: variable.index,
+                })
+            })
+            .collect()
+    }
+
+    /// Builds an attribute; `labels` holds the labels of the enclosing Code attribute, if any.
+    fn attribute(
+        &mut self,
+        attribute: &AttributeSyntax,
+        labels: Option<&Labels>,
+    ) -> Result<Attribute, AssemblyError> {
+        let line = attribute.line;
+        if let Some(name) = attribute_name(&attribute.kind) {
+            self.pool.resolve(&Constant::Utf8(name.to_string()), line)?;
+        }
+
+        let attribute = match &attribute.kind {
+            AttributeKind::Code(code) => Attribute::Code(self.code(code, line)?),
+            AttributeKind::SourceFile(name) => {
+                Attribute::SourceFile(self.pool.resolve(name, line)?)
+            }
+            AttributeKind::Signature(signature) => {
+                Attribute::Signature(self.pool.resolve(signature, line)?)
+            }
+            AttributeKind::ConstantValue(value) => {
+                Attribute::ConstantValue(self.pool.resolve(value, line)?)
+            }
+            AttributeKind::Exceptions(exceptions) => Attribute::Exceptions(
+                exceptions
+                    .iter()
+                    .map(|exception| self.pool.resolve(exception, line))
+                    .collect::<Result<_, _>>()?,
+            ),
+            AttributeKind::Deprecated => Attribute::Deprecated,
+            AttributeKind::Synthetic => Attribute::Synthetic,
+            AttributeKind::BootstrapMethods(bootstrap_methods) => {
+                let mut result = Vec::with_capacity(bootstrap_methods.len());
+                for (method_handle, arguments) in bootstrap_methods {
+                    result.push(BootstrapMethod {
+                        method_ref: self.pool.resolve(method_handle, line)?,
+                        arguments: arguments
+                            .iter()
+                            .map(|argument| self.pool.resolve(argument, line))
+                            .collect::<Result<_, _>>()?,
+                    });
+                }
+                Attribute::BootstrapMethods(result)
+            }
+            AttributeKind::LineNumberTable(line_numbers) => {
+                let Some(labels) = labels else {
+                    return Err(AssemblyError::new(
+                        line,
+                        "line number tables belong in .code",
+                    ));
+                };
+                Attribute::LineNumberTable(
+                    line_numbers
+                        .iter()
+                        .map(|(start, line_number)| {
+                            Ok(LineNumber {
+                                start_pc: labels.pc(start, line)?,
+                                line_number: *line_number,
+                            })
+                        })
+                        .collect::<Result<_, AssemblyError>>()?,
+                )
+            }
+            AttributeKind::LocalVariableTable(local_variables) => {
+                Attribute::LocalVariableTable(self.local_variables(local_variables, labels)?)
+            }
+            AttributeKind::LocalVariableTypeTable(local_variables) => {
+                Attribute::LocalVariableTypeTable(self.local_variables(local_variables, labels)?)
+            }
+            AttributeKind::Raw { name, info } => Attribute::Unknown {
+                name_idx: self.pool.resolve(name, line)?,
+                info: info.clone(),
+            },
+        };
+        Ok(attribute)
+    }
+
+    fn attributes(
+        &mut self,
+        attributes: &[AttributeSyntax],
+        labels: Option<&Labels>,
+    ) -> Result<Vec<Attribute>, AssemblyError> {
+        attributes
+            .iter()
+            .map(|attribute| self.attribute(attribute, labels))
+            .collect()
+    }
+
+    /// The argument count of `invokeinterface`, derived from the descriptor of the method it invokes.
+    fn invoke_interface_count(&self, index: u16, line: usize) -> Result<u8, AssemblyError> {
+        let descriptor = match self.pool.entry(index) {
+            Some(ConstantPoolEntry::InterfaceMethodRef(method_ref)) => {
+                match self.pool.entry(method_ref.name_type_ref) {
+                    Some(ConstantPoolEntry::NameTypeDescriptor(name_type)) => {
+                        self.pool.utf8(name_type.type_descriptor_ref)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let Some(descriptor) = descriptor else {
+            return Err(AssemblyError::new(
+                line,
+                "the argument count of invokeinterface is needed when it does not refer to an interface method",
+            ));
+        };
+        let descriptor = MethodDescriptor::parse(descriptor)
+            .map_err(|_| AssemblyError::new(line, format!("invalid descriptor {descriptor:?}")))?;
+        u8::try_from(descriptor.parameter_slots() + 1)
+            .map_err(|_| AssemblyError::new(line, "too many arguments for invokeinterface"))
+    }
+
+    /// Builds an instruction whose branch targets are left at zero, to be filled in once all labels are known.
+    fn instruction(
+        &mut self,
+        opcode: Opcode,
+        wide: bool,
+        operand: &OperandSyntax,
+        line: usize,
+    ) -> Result<(Operand, bool), AssemblyError> {
+        let result = match operand {
+            OperandSyntax::None => (Operand::None, wide),
+            OperandSyntax::Byte(value) => (Operand::Byte(*value), wide),
+            OperandSyntax::Short(value) => (Operand::Short(*value), wide),
+            OperandSyntax::Constant(constant) => {
+                let index = self.pool.resolve(constant, line)?;
+                if opcode == Opcode::Ldc && index > u16::from(u8::MAX) {
+                    return Err(AssemblyError::new(
+                        line,
+                        format!("ldc cannot refer to #{index}, use ldc_w"),
+                    ));
+                }
+                (Operand::ConstantPool(index), wide)
+            }
+            // Local variables beyond 255 need the wide form.
+            OperandSyntax::Local(index) => (Operand::Local(*index), wide || *index > 255),
+            OperandSyntax::Iinc { index, value } => (
+                Operand::Iinc {
+                    index: *index,
+                    value: *value,
+                },
+                wide || *index > 255 || i8::try_from(*value).is_err(),
+            ),
+            OperandSyntax::Branch(_) => (Operand::Branch(0), wide),
+            OperandSyntax::ArrayType(array_type) => (Operand::ArrayType(*array_type), wide),
+            OperandSyntax::InvokeInterface { constant, count } => {
+                let index = self.pool.resolve(constant, line)?;
+                let count = match count {
+                    Some(count) => *count,
+                    None => self.invoke_interface_count(index, line)?,
+                };
+                (Operand::InvokeInterface { index, count }, wide)
+            }
+            OperandSyntax::MultiANewArray {
+                constant,
+                dimensions,
+            } => (
+                Operand::MultiANewArray {
+                    index: self.pool.resolve(constant, line)?,
+                    dimensions: *dimensions,
+                },
+                wide,
+            ),
+            OperandSyntax::TableSwitch { low, targets, .. } => {
+                let high = i64::from(*low) + targets.len() as i64 - 1;
+                let Ok(high) = i32::try_from(high) else {
+                    return Err(AssemblyError::new(line, "too many tableswitch targets"));
+                };
+                (
+                    Operand::TableSwitch {
+                        default: 0,
+                        low: *low,
+                        high,
+                        targets: vec![0; targets.len()],
+                    },
+                    wide,
+                )
+            }
+            OperandSyntax::LookupSwitch { pairs, .. } => (
+                Operand::LookupSwitch {
+                    default: 0,
+                    pairs: pairs.iter().map(|(key, _)| (*key, 0)).collect(),
+                },
+                wide,
+            ),
+        };
+        Ok(result)
+    }
+
+    fn resolve_branches(
+        operand: &mut Operand,
+        syntax: &OperandSyntax,
+        labels: &Labels,
+        line: usize,
+    ) -> Result<(), AssemblyError> {
+        match (operand, syntax) {
+            (Operand::Branch(target), OperandSyntax::Branch(position)) => {
+                *target = labels.offset(position, line)?;
+            }
+            (
+                Operand::TableSwitch {
+                    default, targets, ..
+                },
+                OperandSyntax::TableSwitch {
+                    targets: positions,
+                    default: default_position,
+                    ..
+                },
+            ) => {
+                *default = labels.offset(default_position, line)?;
+                for (target, position) in targets.iter_mut().zip(positions) {
+                    *target = labels.offset(position, line)?;
+                }
+            }
+            (
+                Operand::LookupSwitch { default, pairs },
+                OperandSyntax::LookupSwitch {
+                    pairs: positions,
+                    default: default_position,
+                },
+            ) => {
+                *default = labels.offset(default_position, line)?;
+                for ((_, target), (_, position)) in pairs.iter_mut().zip(positions) {
+                    *target = labels.offset(position, line)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn code(&mut self, code: &CodeSyntax, line: usize) -> Result<Code, AssemblyError> {
+        // Lay out the instructions first, so that the offsets of all labels are known.
+        let mut offsets = HashMap::new();
+        let mut instructions = Vec::new();
+        let mut offset = 0u32;
+        for item in &code.items {
+            match item {
+                CodeItem::Label(label, label_line) => {
+                    if offsets.insert(label.clone(), offset).is_some() {
+                        return Err(AssemblyError::new(
+                            *label_line,
+                            format!("label {label:?} is defined twice"),
+                        ));
+                    }
+                }
+                CodeItem::Instruction(syntax) => {
+                    let (operand, wide) =
+                        self.instruction(syntax.opcode, syntax.wide, &syntax.operand, syntax.line)?;
+                    let instruction = Instruction {
+                        offset,
+                        opcode: syntax.opcode,
+                        operand,
+                        wide,
+                    };
+                    offset += instruction.length() as u32;
+                    instructions.push((instruction, syntax));
+                }
+            }
+        }
+        let labels = Labels {
+            offsets,
+            code_length: offset,
+        };
+
+        let mut resolved = Vec::with_capacity(instructions.len());
+        for (mut instruction, syntax) in instructions {
+            Self::resolve_branches(
+                &mut instruction.operand,
+                &syntax.operand,
+                &labels,
+                syntax.line,
+            )?;
+            if let (Operand::Branch(target), false) = (
+                &instruction.operand,
+                matches!(instruction.opcode, Opcode::GotoW | Opcode::JsrW),
+            ) {
+                let relative = i64::from(*target) - i64::from(instruction.offset);
+                if i16::try_from(relative).is_err() {
+                    return Err(AssemblyError::new(
+                        syntax.line,
+                        format!("the branch of {} is too far", syntax.opcode.mnemonic()),
+                    ));
+                }
+            }
+            resolved.push(instruction);
+        }
+        let bytes = encode(&resolved)
+            .map_err(|_| AssemblyError::new(line, "the code cannot be encoded"))?;
+        debug_assert_eq!(bytes.len() as u32, labels.code_length);
+
+        let mut exception_table = Vec::with_capacity(code.catches.len());
+        for catch in &code.catches {
+            exception_table.push(ExceptionHandler {
+                start_pc: labels.pc(&catch.start, catch.line)?,
+                end_pc: labels.pc(&catch.end, catch.line)?,
+                handler_pc: labels.pc(&catch.handler, catch.line)?,
+                catch_type: match &catch.catch_type {
+                    Some(catch_type) => self.pool.resolve(catch_type, catch.line)?,
+                    None => 0,
+                },
+            });
+        }
+
+        Ok(Code {
+            max_stack: code.max_stack,
+            max_locals: code.max_locals,
+            code: bytes,
+            exception_table,
+            attributes: self.attributes(&code.attributes, Some(&labels))?,
+        })
+    }
+
+    fn member<F: Copy>(
+        &mut self,
+        member: &MemberSyntax<F>,
+    ) -> Result<(F, u16, u16, Vec<Attribute>), AssemblyError> {
+        let name_idx = self.pool.resolve(&member.name, member.line)?;
+        let descriptor_idx = self.pool.resolve(&member.descriptor, member.line)?;
+        let attributes = self.attributes(&member.attributes, None)?;
+        Ok((member.access_flags, name_idx, descriptor_idx, attributes))
+    }
+
+    /// The index of the name of the class a ClassRef entry refers to.
+    fn class_name_idx(&self, class_ref: u16, line: usize) -> Result<u16, AssemblyError> {
+        match self.pool.entry(class_ref) {
+            Some(ConstantPoolEntry::ClassRef(name_idx)) => Ok(*name_idx),
+            _ => Err(AssemblyError::new(
+                line,
+                format!("#{class_ref} is not a Class entry"),
+            )),
+        }
+    }
+}
+
+/// Builds a class file from its syntax tree, resolving constants and labels.
+pub fn build(class: &ClassSyntax) -> Result<ClassFile, AssemblyError> {
+    let mut pool = PoolBuilder {
+        slots: Vec::new(),
+        reserved: Vec::new(),
+        raw_strings: HashMap::new(),
+    };
+
+    // Explicitly numbered entries claim their slots before any other entry is placed.
+    for definition in &class.constants {
+        if definition.index == 0 {
+            return Err(AssemblyError::new(
+                definition.line,
+                "#0 is not a valid index",
+            ));
+        }
+        let position = usize::from(definition.index - 1);
+        if !pool.is_free(position) {
+            return Err(AssemblyError::new(
+                definition.line,
+                format!("#{} is defined twice", definition.index),
+            ));
+        }
+        pool.reserve(position);
+        if matches!(definition.constant, Constant::Long(_) | Constant::Double(_)) {
+            pool.reserve(position + 1);
+        }
+    }
+    for definition in &class.constants {
+        let entry = pool.build_entry(&definition.constant, definition.line)?;
+        let position = usize::from(definition.index - 1);
+        if let Constant::Utf8Bytes(bytes) = &definition.constant {
+            pool.raw_strings.insert(position, bytes.clone());
+        }
+        if takes_two_slots(&entry) {
+            pool.set(position + 1, ConstantPoolEntry::Empty());
+        }
+        pool.set(position, entry);
+    }
+
+    let mut builder = Builder { pool };
+    let this_idx = builder.pool.resolve(&class.this_class, 0)?;
+    let super_idx = match &class.super_class {
+        Some(super_class) => builder.pool.resolve(super_class, 0)?,
+        None => 0,
+    };
+    let mut interfaces = Vec::with_capacity(class.interfaces.len());
+    for interface in &class.interfaces {
+        let class_ref = builder.pool.resolve(interface, 0)?;
+        interfaces.push(usize::from(builder.class_name_idx(class_ref, 0)?));
+    }
+
+    let attributes = builder.attributes(&class.attributes, None)?;
+    let mut fields = Vec::with_capacity(class.fields.len());
+    for field in &class.fields {
+        let (access_flags, name_idx, descriptor_idx, attributes) = builder.member(field)?;
+        fields.push(FieldInfo {
+            access_flags,
+            name_idx,
+            descriptor_idx,
+            attributes,
+        });
+    }
+    let mut methods = Vec::with_capacity(class.methods.len());
+    for method in &class.methods {
+        let (access_flags, name_idx, descriptor_idx, attributes) = builder.member(method)?;
+        methods.push(MethodInfo {
+            access_flags,
+            name_idx,
+            descriptor_idx,
+            attributes,
+        });
+    }
+
+    Ok(ClassFile {
+        version: Version {
+            major: class.major_version,
+            minor: class.minor_version,
+        },
+        constant_pool: builder.pool.build()?,
+        access_flags: class.access_flags,
+        class: ClassDefinition {
+            this_idx: usize::from(this_idx),
+            super_idx: usize::from(super_idx),
+            interfaces,
+        },
+        fields,
+        methods,
+        attributes,
+    })
+}
@@ -0,0 +1,540 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::result::Result;
+
+use bitflags::Flags;
+
+use crate::assembler::syntax::{flag_keyword, reference_kind_keyword};
+use crate::attributes::{Attribute, Code, LocalVariable, encode_attribute};
+use crate::bytecode::{Instruction, Operand, array_type_name, decode, encode};
+use crate::constant_pool::ConstantPool;
+use crate::constant_pool::types::ConstantPoolEntry;
+use crate::errors::ClassFileError;
+use crate::types::ClassFile;
+
+/// Quotes a string so that the assembler reads it back unchanged.
+fn quote(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            c if c.is_control() => {
+                let _ = write!(result, "\\u{:04x}", c as u32);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn format_float(value: f32) -> String {
+    match value.is_finite() {
+        true => format!("{value:?}"),
+        false => format!("0x{:08x}", value.to_bits()),
+    }
+}
+
+fn format_double(value: f64) -> String {
+    match value.is_finite() {
+        true => format!("{value:?}"),
+        false => format!("0x{:016x}", value.to_bits()),
+    }
+}
+
+fn flags<F: Flags<Bits = u16>>(flags: &F) -> String {
+    F::FLAGS
+        .iter()
+        .filter(|flag| flags.contains(F::from_bits_retain(flag.value().bits())))
+        .map(|flag| flag_keyword(flag.name()) + " ")
+        .collect()
+}
+
+/// The constant of an entry in assembly syntax, referring to other entries by index.
+fn constant(entry: &ConstantPoolEntry) -> String {
+    match entry {
+        ConstantPoolEntry::String(value) => format!("Utf8 {}", quote(value)),
+        ConstantPoolEntry::Integer(value) => format!("Int {value}"),
+        ConstantPoolEntry::Float(value) => format!("Float {}", format_float(*value)),
+        ConstantPoolEntry::Long(value) => format!("Long {value}"),
+        ConstantPoolEntry::Double(value) => format!("Double {}", format_double(*value)),
+        ConstantPoolEntry::ClassRef(name_ref) => format!("Class #{name_ref}"),
+        ConstantPoolEntry::StringRef(value_ref) => format!("String #{value_ref}"),
+        ConstantPoolEntry::FieldRef(field_ref) => {
+            format!(
+                "Field #{} #{}",
+                field_ref.class_ref, field_ref.name_type_ref
+            )
+        }
+        ConstantPoolEntry::MethodRef(method_ref) => format!(
+            "Method #{} #{}",
+            method_ref.class_ref, method_ref.name_type_ref
+        ),
+        ConstantPoolEntry::InterfaceMethodRef(method_ref) => format!(
+            "InterfaceMethod #{} #{}",
+            method_ref.class_ref, method_ref.name_type_ref
+        ),
+        ConstantPoolEntry::NameTypeDescriptor(name_type) => format!(
+            "NameAndType #{} #{}",
+            name_type.name_ref, name_type.type_descriptor_ref
+        ),
+        ConstantPoolEntry::MethodHandle(method_handle) => format!(
+            "MethodHandle {} #{}",
+            reference_kind_keyword(method_handle.reference_kind),
+            method_handle.reference_ref
+        ),
+        ConstantPoolEntry::MethodType(descriptor_ref) => format!("MethodType #{descriptor_ref}"),
+        ConstantPoolEntry::Dynamic(dynamic) => format!(
+            "Dynamic {} #{}",
+            dynamic.bootstrap_method_attr_idx, dynamic.name_type_ref
+        ),
+        ConstantPoolEntry::InvokeDynamic(dynamic) => format!(
+            "InvokeDynamic {} #{}",
+            dynamic.bootstrap_method_attr_idx, dynamic.name_type_ref
+        ),
+        ConstantPoolEntry::Module(name_ref) => format!("Module #{name_ref}"),
+        ConstantPoolEntry::Package(name_ref) => format!("Package #{name_ref}"),
+        ConstantPoolEntry::Empty() => String::new(),
+    }
+}
+
+/// A readable description of the entry at `index`, used for comments.
+fn describe(pool: &ConstantPool, index: u16) -> Option<String> {
+    let entry = pool.checked_entry(index).ok()?;
+    let member = |index: u16| {
+        let (owner, name, descriptor) = pool.member_ref(usize::from(index)).ok()?;
+        Some(format!("{owner}.{name}:{descriptor}"))
+    };
+    match entry {
+        ConstantPoolEntry::String(value) => Some(value.clone()),
+        ConstantPoolEntry::ClassRef(name_ref)
+        | ConstantPoolEntry::MethodType(name_ref)
+        | ConstantPoolEntry::Module(name_ref)
+        | ConstantPoolEntry::Package(name_ref) => pool.string_entry(*name_ref).ok().cloned(),
+        ConstantPoolEntry::StringRef(value_ref) => Some(quote(pool.string_entry(*value_ref).ok()?)),
+        ConstantPoolEntry::FieldRef(_)
+        | ConstantPoolEntry::MethodRef(_)
+        | ConstantPoolEntry::InterfaceMethodRef(_) => member(index),
+        ConstantPoolEntry::NameTypeDescriptor(_) => {
+            let (name, descriptor) = pool.name_and_type(usize::from(index)).ok()?;
+            Some(format!("{name}:{descriptor}"))
+        }
+        ConstantPoolEntry::MethodHandle(method_handle) => Some(format!(
+            "{} {}",
+            reference_kind_keyword(method_handle.reference_kind),
+            member(method_handle.reference_ref)?
+        )),
+        ConstantPoolEntry::Dynamic(dynamic) | ConstantPoolEntry::InvokeDynamic(dynamic) => {
+            let (name, descriptor) = pool
+                .name_and_type(usize::from(dynamic.name_type_ref))
+                .ok()?;
+            Some(format!("{name}:{descriptor}"))
+        }
+        _ => None,
+    }
+}
+
+fn with_comment(text: String, pool: &ConstantPool, index: u16) -> String {
+    match describe(pool, index) {
+        Some(description) => format!("{text} // {description}"),
+        None => text,
+    }
+}
+
+struct Disassembler<'a> {
+    pool: &'a ConstantPool,
+    out: String,
+}
+
+/// The labels of a Code attribute: offsets that are referred to and start an instruction or end the code.
+struct Labels {
+    offsets: BTreeSet<u32>,
+}
+
+impl Labels {
+    fn position(&self, offset: u32) -> String {
+        match self.offsets.contains(&offset) {
+            true => format!("L{offset}"),
+            false => offset.to_string(),
+        }
+    }
+}
+
+impl Disassembler<'_> {
+    fn line(&mut self, indent: usize, text: &str) {
+        let _ = writeln!(self.out, "{:indent$}{text}", "");
+    }
+
+    fn raw_attribute(
+        &mut self,
+        indent: usize,
+        attribute: &Attribute,
+    ) -> Result<(), ClassFileError> {
+        let (name_idx, info) = encode_attribute(attribute, self.pool)?;
+        let hex: String = info.iter().map(|byte| format!("{byte:02x}")).collect();
+        let text = with_comment(
+            format!(".attribute #{name_idx} \"{hex}\""),
+            self.pool,
+            name_idx,
+        );
+        self.line(indent, &text);
+        Ok(())
+    }
+
+    fn local_variables(
+        &mut self,
+        indent: usize,
+        directive: &str,
+        local_variables: &[LocalVariable],
+        labels: &Labels,
+    ) {
+        self.line(indent, directive);
+        for variable in local_variables {
+            let end = u32::from(variable.start_pc) + u32::from(variable.length);
+            self.line(
+                indent + 4,
+                &format!(
+                    "{} #{} #{} from {} to {}",
+                    variable.index,
+                    variable.name_idx,
+                    variable.descriptor_idx,
+                    labels.position(u32::from(variable.start_pc)),
+                    labels.position(end)
+                ),
+            );
+        }
+        self.line(indent, &format!(".end {}", &directive[1..]));
+    }
+
+    fn attribute(
+        &mut self,
+        indent: usize,
+        attribute: &Attribute,
+        labels: Option<&Labels>,
+    ) -> Result<(), ClassFileError> {
+        match (attribute, labels) {
+            (Attribute::Code(code), None) => self.code(indent, attribute, code)?,
+            (Attribute::SourceFile(index), _) => {
+                let text = with_comment(format!(".sourcefile #{index}"), self.pool, *index);
+                self.line(indent, &text);
+            }
+            (Attribute::Signature(index), _) => {
+                let text = with_comment(format!(".signature #{index}"), self.pool, *index);
+                self.line(indent, &text);
+            }
+            (Attribute::ConstantValue(index), _) => {
+                let text = with_comment(format!(".constantvalue #{index}"), self.pool, *index);
+                self.line(indent, &text);
+            }
+            (Attribute::Exceptions(exceptions), _) => {
+                let indices: Vec<String> = exceptions
+                    .iter()
+                    .map(|index| format!(" #{index}"))
+                    .collect();
+                self.line(indent, &format!(".exceptions{}", indices.concat()));
+            }
+            (Attribute::Deprecated, _) => self.line(indent, ".deprecated"),
+            (Attribute::Synthetic, _) => self.line(indent, ".synthetic"),
+            (Attribute::BootstrapMethods(bootstrap_methods), _) => {
+                self.line(indent, ".bootstrapmethods");
+                for bootstrap_method in bootstrap_methods {
+                    let arguments: Vec<String> = bootstrap_method
+                        .arguments
+                        .iter()
+                        .map(|index| format!(" #{index}"))
+                        .collect();
+                    let text = with_comment(
+                        format!("#{}{}", bootstrap_method.method_ref, arguments.concat()),
+                        self.pool,
+                        bootstrap_method.method_ref,
+                    );
+                    self.line(indent + 4, &text);
+                }
+                self.line(indent, ".end bootstrapmethods");
+            }
+            (Attribute::LineNumberTable(line_numbers), Some(labels)) => {
+                self.line(indent, ".linenumbertable");
+                for line_number in line_numbers {
+                    let start = labels.position(u32::from(line_number.start_pc));
+                    self.line(indent + 4, &format!("{start} {}", line_number.line_number));
+                }
+                self.line(indent, ".end linenumbertable");
+            }
+            (Attribute::LocalVariableTable(local_variables), Some(labels))
+                if fits(local_variables) =>
+            {
+                self.local_variables(indent, ".localvariabletable", local_variables, labels)
+            }
+            (Attribute::LocalVariableTypeTable(local_variables), Some(labels))
+                if fits(local_variables) =>
+            {
+                self.local_variables(indent, ".localvariabletypetable", local_variables, labels)
+            }
+            (attribute, _) => self.raw_attribute(indent, attribute)?,
+        }
+        Ok(())
+    }
+
+    fn operand(&self, instruction: &Instruction, labels: &Labels) -> String {
+        let constant = |index: u16| with_comment(format!("#{index}"), self.pool, index);
+        match &instruction.operand {
+            Operand::None => String::new(),
+            Operand::Byte(value) => value.to_string(),
+            Operand::Short(value) => value.to_string(),
+            Operand::ConstantPool(index) => constant(*index),
+            Operand::Local(index) => index.to_string(),
+            Operand::Iinc { index, value } => format!("{index} {value}"),
+            Operand::Branch(target) => labels.position(*target),
+            Operand::ArrayType(array_type) => match array_type_name(*array_type) {
+                Some(name) => name.to_string(),
+                None => array_type.to_string(),
+            },
+            Operand::InvokeInterface { index, count } => {
+                with_comment(format!("#{index} {count}"), self.pool, *index)
+            }
+            Operand::MultiANewArray { index, dimensions } => {
+                with_comment(format!("#{index} {dimensions}"), self.pool, *index)
+            }
+            Operand::TableSwitch {
+                default,
+                low,
+                targets,
+                ..
+            } => {
+                let mut text = low.to_string();
+                for target in targets {
+                    let _ = write!(text, "\n            {}", labels.position(*target));
+                }
+                let _ = write!(text, "\n            default {}", labels.position(*default));
+                text
+            }
+            Operand::LookupSwitch { default, pairs } => {
+                let mut text = String::new();
+                for (key, target) in pairs {
+                    let _ = write!(text, "\n            {key} {}", labels.position(*target));
+                }
+                let _ = write!(text, "\n            default {}", labels.position(*default));
+                text
+            }
+        }
+    }
+
+    fn code(
+        &mut self,
+        indent: usize,
+        attribute: &Attribute,
+        code: &Code,
+    ) -> Result<(), ClassFileError> {
+        // Code that does not encode back to the same bytes, e.g. with odd padding, is kept as it is.
+        let instructions = match decode(&code.code) {
+            Ok(instructions) if encode(&instructions).is_ok_and(|bytes| bytes == code.code) => {
+                instructions
+            }
+            _ => return self.raw_attribute(indent, attribute),
+        };
+
+        let mut boundaries: BTreeSet<u32> = instructions
+            .iter()
+            .map(|instruction| instruction.offset)
+            .collect();
+        boundaries.insert(code.code.len() as u32);
+        let mut referenced: Vec<u32> = instructions
+            .iter()
+            .flat_map(Instruction::branch_targets)
+            .collect();
+        for handler in &code.exception_table {
+            referenced
+                .extend([handler.start_pc, handler.end_pc, handler.handler_pc].map(u32::from));
+        }
+        for attribute in &code.attributes {
+            match attribute {
+                Attribute::LineNumberTable(line_numbers) => {
+                    referenced.extend(
+                        line_numbers
+                            .iter()
+                            .map(|line_number| u32::from(line_number.start_pc)),
+                    );
+                }
+                Attribute::LocalVariableTable(local_variables)
+                | Attribute::LocalVariableTypeTable(local_variables) => {
+                    for variable in local_variables {
+                        referenced.push(u32::from(variable.start_pc));
+                        referenced.push(u32::from(variable.start_pc) + u32::from(variable.length));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let labels = Labels {
+            offsets: referenced
+                .into_iter()
+                .filter(|offset| boundaries.contains(offset))
+                .collect(),
+        };
+
+        self.line(
+            indent,
+            &format!(".code stack {} locals {}", code.max_stack, code.max_locals),
+        );
+        for instruction in &instructions {
+            if labels.offsets.contains(&instruction.offset) {
+                self.line(indent, &format!("L{}:", instruction.offset));
+            }
+            let mnemonic = match instruction.wide {
+                true => format!("wide {}", instruction.opcode.mnemonic()),
+                false => instruction.opcode.mnemonic().to_string(),
+            };
+            let operand = self.operand(instruction, &labels);
+            match operand.is_empty() {
+                true => self.line(indent + 4, &mnemonic),
+                false => self.line(indent + 4, &format!("{mnemonic} {operand}")),
+            }
+        }
+        let end = code.code.len() as u32;
+        if labels.offsets.contains(&end) {
+            self.line(indent, &format!("L{end}:"));
+        }
+
+        for handler in &code.exception_table {
+            let catch_type = match handler.catch_type {
+                0 => "any".to_string(),
+                catch_type => format!("#{catch_type}"),
+            };
+            let text = format!(
+                ".catch {catch_type} from {} to {} using {}",
+                labels.position(u32::from(handler.start_pc)),
+                labels.position(u32::from(handler.end_pc)),
+                labels.position(u32::from(handler.handler_pc))
+            );
+            let text = with_comment(text, self.pool, handler.catch_type);
+            self.line(indent + 4, &text);
+        }
+        for attribute in &code.attributes {
+            self.attribute(indent + 4, attribute, Some(&labels))?;
+        }
+        self.line(indent, ".end code");
+        Ok(())
+    }
+
+    fn class(&mut self, class_file: &ClassFile) -> Result<(), ClassFileError> {
+        let pool = self.pool;
+        self.line(
+            0,
+            &format!(
+                ".version {} {}",
+                class_file.version.major, class_file.version.minor
+            ),
+        );
+        let this_idx = class_file.class.this_idx as u16;
+        let text = format!(".class {}#{this_idx}", flags(&class_file.access_flags));
+        self.line(0, &with_comment(text, pool, this_idx));
+        if class_file.class.super_idx != 0 {
+            let super_idx = class_file.class.super_idx as u16;
+            self.line(
+                0,
+                &with_comment(format!(".super #{super_idx}"), pool, super_idx),
+            );
+        }
+        for name_idx in &class_file.class.interfaces {
+            let Some(class_ref) = pool.find_class_ref(*name_idx as u16) else {
+                return Err(ClassFileError::InvalidConstantPoolContent);
+            };
+            self.line(
+                0,
+                &with_comment(format!(".implements #{class_ref}"), pool, class_ref),
+            );
+        }
+
+        self.line(0, "");
+        for (position, entry) in pool.items().enumerate() {
+            if *entry == ConstantPoolEntry::Empty() {
+                continue;
+            }
+            let index = position as u16 + 1;
+            let text = match pool.raw_string(index) {
+                Some(bytes) => {
+                    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+                    format!(".const #{index} = Utf8 bytes \"{hex}\"")
+                }
+                None => format!(".const #{index} = {}", constant(entry)),
+            };
+            match entry {
+                ConstantPoolEntry::String(_) => self.line(0, &text),
+                _ => self.line(0, &with_comment(text, pool, index)),
+            }
+        }
+
+        // Class attributes come before the fields, which would otherwise claim them.
+        if !class_file.attributes.is_empty() {
+            self.line(0, "");
+        }
+        for attribute in &class_file.attributes {
+            self.attribute(0, attribute, None)?;
+        }
+
+        for field in &class_file.fields {
+            self.line(0, "");
+            let text = format!(
+                ".field {}#{} #{}",
+                flags(&field.access_flags),
+                field.name_idx,
+                field.descriptor_idx
+            );
+            self.line(0, &with_comment(text, pool, field.name_idx));
+            for attribute in &field.attributes {
+                self.attribute(4, attribute, None)?;
+            }
+            if !field.attributes.is_empty() {
+                self.line(0, ".end field");
+            }
+        }
+
+        for method in &class_file.methods {
+            self.line(0, "");
+            let text = format!(
+                ".method {}#{} #{}",
+                flags(&method.access_flags),
+                method.name_idx,
+                method.descriptor_idx
+            );
+            let description = match (
+                describe(pool, method.name_idx),
+                describe(pool, method.descriptor_idx),
+            ) {
+                (Some(name), Some(descriptor)) => format!("{text} // {name}{descriptor}"),
+                _ => text,
+            };
+            self.line(0, &description);
+            for attribute in &method.attributes {
+                self.attribute(4, attribute, None)?;
+            }
+            self.line(0, ".end method");
+        }
+        Ok(())
+    }
+}
+
+/// Whether the ends of all local variables fit in an offset, so that they can be written with labels.
+fn fits(local_variables: &[LocalVariable]) -> bool {
+    local_variables.iter().all(|variable| {
+        u32::from(variable.start_pc) + u32::from(variable.length) <= u32::from(u16::MAX)
+    })
+}
+
+/// Prints a class file in assembly syntax, such that assembling the output yields the same class file.
+///
+/// Every constant pool entry is given with its index and all references use indices, so that the constant pool keeps
+/// its layout. Comments describe what the indices refer to.
+pub fn disassemble(class_file: &ClassFile) -> Result<String, ClassFileError> {
+    let mut disassembler = Disassembler {
+        pool: &class_file.constant_pool,
+        out: String::new(),
+    };
+    disassembler.class(class_file)?;
+    Ok(disassembler.out)
+}
@@ -0,0 +1,95 @@
+use std::result::Result;
+
+use crate::assembler::AssemblyError;
+
+/// A word or a quoted string of the assembly source, together with the line it starts on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub text: String,
+    /// Whether the token was a quoted string, whose `text` holds the unescaped value.
+    pub quoted: bool,
+    pub line: usize,
+}
+
+fn read_escape(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    line: usize,
+) -> Result<char, AssemblyError> {
+    match chars.next() {
+        Some('n') => Ok('\n'),
+        Some('t') => Ok('\t'),
+        Some('r') => Ok('\r'),
+        Some('"') => Ok('"'),
+        Some('\\') => Ok('\\'),
+        Some('u') => {
+            let digits: String = (0..4).filter_map(|_| chars.next()).collect();
+            u32::from_str_radix(&digits, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| AssemblyError::new(line, format!("invalid escape \\u{digits}")))
+        }
+        other => Err(AssemblyError::new(
+            line,
+            format!("invalid escape \\{}", other.unwrap_or(' ')),
+        )),
+    }
+}
+
+/// Splits assembly source into tokens.
+///
+/// Tokens are separated by whitespace. A token starting with `"` is a string that runs to the next unescaped `"`, and
+/// a token starting with `//` is a comment that runs to the end of the line.
+pub fn tokenize(source: &str) -> Result<Vec<Token>, AssemblyError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+
+    while let Some(&c) = chars.peek() {
+        if c == '\n' {
+            line += 1;
+            chars.next();
+        } else if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => text.push(read_escape(&mut chars, line)?),
+                    Some('\n') | None => {
+                        return Err(AssemblyError::new(line, "unterminated string"));
+                    }
+                    Some(c) => text.push(c),
+                }
+            }
+            tokens.push(Token {
+                text,
+                quoted: true,
+                line,
+            });
+        } else {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            if text.starts_with("//") {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+                line,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
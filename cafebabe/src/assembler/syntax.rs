@@ -0,0 +1,917 @@
+use std::result::Result;
+
+use bitflags::Flags;
+
+use crate::assembler::AssemblyError;
+use crate::assembler::lexer::Token;
+use crate::bytecode::{Opcode, array_type_code};
+use crate::constant_pool::types::ReferenceKind;
+use crate::types::{AccessFlags, FieldAccessFlags, MethodAccessFlags};
+
+/// A reference to a constant pool entry, either by index or by its contents.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+    Index(u16),
+    Utf8(String),
+    /// A string by its bytes in modified UTF-8, for strings that a Rust string can't hold.
+    Utf8Bytes(Vec<u8>),
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    Class(Box<Constant>),
+    String(Box<Constant>),
+    MethodType(Box<Constant>),
+    Module(Box<Constant>),
+    Package(Box<Constant>),
+    Field(Box<Constant>, Box<Constant>),
+    Method(Box<Constant>, Box<Constant>),
+    InterfaceMethod(Box<Constant>, Box<Constant>),
+    NameAndType(Box<Constant>, Box<Constant>),
+    MethodHandle(ReferenceKind, Box<Constant>),
+    Dynamic(u16, Box<Constant>),
+    InvokeDynamic(u16, Box<Constant>),
+}
+
+/// An offset within the code, either given by a label or as a number.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Position {
+    Label(String),
+    Offset(u16),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OperandSyntax {
+    None,
+    Byte(i8),
+    Short(i16),
+    Constant(Constant),
+    Local(u16),
+    Iinc {
+        index: u16,
+        value: i16,
+    },
+    Branch(Position),
+    ArrayType(u8),
+    /// The count is derived from the method descriptor when it is omitted.
+    InvokeInterface {
+        constant: Constant,
+        count: Option<u8>,
+    },
+    MultiANewArray {
+        constant: Constant,
+        dimensions: u8,
+    },
+    TableSwitch {
+        low: i32,
+        targets: Vec<Position>,
+        default: Position,
+    },
+    LookupSwitch {
+        pairs: Vec<(i32, Position)>,
+        default: Position,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstructionSyntax {
+    pub line: usize,
+    pub opcode: Opcode,
+    pub wide: bool,
+    pub operand: OperandSyntax,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CodeItem {
+    Label(String, usize),
+    Instruction(InstructionSyntax),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CatchSyntax {
+    pub line: usize,
+    /// The caught class, or `None` for a handler that catches everything.
+    pub catch_type: Option<Constant>,
+    pub start: Position,
+    pub end: Position,
+    pub handler: Position,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalVariableSyntax {
+    pub line: usize,
+    pub index: u16,
+    pub name: Constant,
+    pub descriptor: Constant,
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeSyntax {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    pub items: Vec<CodeItem>,
+    pub catches: Vec<CatchSyntax>,
+    pub attributes: Vec<AttributeSyntax>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeKind {
+    Code(CodeSyntax),
+    SourceFile(Constant),
+    Signature(Constant),
+    ConstantValue(Constant),
+    Exceptions(Vec<Constant>),
+    Deprecated,
+    Synthetic,
+    BootstrapMethods(Vec<(Constant, Vec<Constant>)>),
+    LineNumberTable(Vec<(Position, u16)>),
+    LocalVariableTable(Vec<LocalVariableSyntax>),
+    LocalVariableTypeTable(Vec<LocalVariableSyntax>),
+    /// An attribute given as its name and the bytes of its `info`.
+    Raw {
+        name: Constant,
+        info: Vec<u8>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributeSyntax {
+    pub line: usize,
+    pub kind: AttributeKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemberSyntax<F> {
+    pub line: usize,
+    pub access_flags: F,
+    pub name: Constant,
+    pub descriptor: Constant,
+    pub attributes: Vec<AttributeSyntax>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstantDefinition {
+    pub line: usize,
+    pub index: u16,
+    pub constant: Constant,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassSyntax {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub access_flags: AccessFlags,
+    pub this_class: Constant,
+    pub super_class: Option<Constant>,
+    pub interfaces: Vec<Constant>,
+    pub constants: Vec<ConstantDefinition>,
+    pub fields: Vec<MemberSyntax<FieldAccessFlags>>,
+    pub methods: Vec<MemberSyntax<MethodAccessFlags>>,
+    pub attributes: Vec<AttributeSyntax>,
+}
+
+const ATTRIBUTE_DIRECTIVES: [&str; 12] = [
+    ".code",
+    ".sourcefile",
+    ".signature",
+    ".constantvalue",
+    ".exceptions",
+    ".deprecated",
+    ".synthetic",
+    ".bootstrapmethods",
+    ".linenumbertable",
+    ".localvariabletable",
+    ".localvariabletypetable",
+    ".attribute",
+];
+
+const CONSTANT_KEYWORDS: [&str; 17] = [
+    "Utf8",
+    "Int",
+    "Float",
+    "Long",
+    "Double",
+    "Class",
+    "String",
+    "MethodType",
+    "Module",
+    "Package",
+    "Field",
+    "Method",
+    "InterfaceMethod",
+    "NameAndType",
+    "MethodHandle",
+    "Dynamic",
+    "InvokeDynamic",
+];
+
+/// The name of a reference kind as written in assembly, e.g. `invokeStatic`.
+pub fn reference_kind_keyword(reference_kind: ReferenceKind) -> &'static str {
+    match reference_kind {
+        ReferenceKind::GetField => "getField",
+        ReferenceKind::GetStatic => "getStatic",
+        ReferenceKind::PutField => "putField",
+        ReferenceKind::PutStatic => "putStatic",
+        ReferenceKind::InvokeVirtual => "invokeVirtual",
+        ReferenceKind::InvokeStatic => "invokeStatic",
+        ReferenceKind::InvokeSpecial => "invokeSpecial",
+        ReferenceKind::NewInvokeSpecial => "newInvokeSpecial",
+        ReferenceKind::InvokeInterface => "invokeInterface",
+    }
+}
+
+/// The name of an access flag as written in assembly, e.g. `public` for `ACC_PUBLIC`.
+pub fn flag_keyword(name: &str) -> String {
+    name.trim_start_matches("ACC_").to_lowercase()
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    /// Whether the next token is the unquoted word `text`.
+    fn peek_is(&self, text: &str) -> bool {
+        self.peek()
+            .is_some_and(|token| !token.quoted && token.text == text)
+    }
+
+    /// Whether the next token exists and starts on `line`.
+    fn peek_on_line(&self, line: usize) -> bool {
+        self.peek().is_some_and(|token| token.line == line)
+    }
+
+    fn line(&self) -> usize {
+        match self.peek() {
+            Some(token) => token.line,
+            None => self.tokens.last().map_or(0, |token| token.line),
+        }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, AssemblyError> {
+        Err(AssemblyError::new(self.line(), message))
+    }
+
+    fn next(&mut self, expected: &str) -> Result<&'a Token, AssemblyError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token)
+            }
+            None => self.error(format!("expected {expected}, found the end of the input")),
+        }
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), AssemblyError> {
+        let token = self.next(text)?;
+        match !token.quoted && token.text == text {
+            true => Ok(()),
+            false => Err(AssemblyError::new(
+                token.line,
+                format!("expected {text}, found {:?}", token.text),
+            )),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self, expected: &str) -> Result<T, AssemblyError> {
+        let token = self.next(expected)?;
+        let parsed = match token.text.strip_prefix("0x") {
+            Some(hex) => i64::from_str_radix(hex, 16)
+                .ok()
+                .and_then(|value| value.to_string().parse().ok()),
+            None => token.text.parse().ok(),
+        };
+        match (token.quoted, parsed) {
+            (false, Some(value)) => Ok(value),
+            _ => Err(AssemblyError::new(
+                token.line,
+                format!("expected {expected}, found {:?}", token.text),
+            )),
+        }
+    }
+
+    fn float(&mut self) -> Result<f32, AssemblyError> {
+        let token = self.next("a float")?;
+        let parsed = match token.text.strip_prefix("0x") {
+            Some(bits) => u32::from_str_radix(bits, 16).ok().map(f32::from_bits),
+            None => token.text.parse().ok(),
+        };
+        parsed.ok_or_else(|| {
+            AssemblyError::new(
+                token.line,
+                format!("expected a float, found {:?}", token.text),
+            )
+        })
+    }
+
+    fn double(&mut self) -> Result<f64, AssemblyError> {
+        let token = self.next("a double")?;
+        let parsed = match token.text.strip_prefix("0x") {
+            Some(bits) => u64::from_str_radix(bits, 16).ok().map(f64::from_bits),
+            None => token.text.parse().ok(),
+        };
+        parsed.ok_or_else(|| {
+            AssemblyError::new(
+                token.line,
+                format!("expected a double, found {:?}", token.text),
+            )
+        })
+    }
+
+    /// Parses `#n` into an index, if the next token has that form.
+    fn index(&mut self) -> Result<Option<u16>, AssemblyError> {
+        let Some(token) = self.peek() else {
+            return Ok(None);
+        };
+        match (token.quoted, token.text.strip_prefix('#')) {
+            (false, Some(index)) => match index.parse() {
+                Ok(index) => {
+                    self.position += 1;
+                    Ok(Some(index))
+                }
+                Err(_) => self.error(format!("invalid constant pool index {:?}", token.text)),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses a String entry: `#n`, a word or a quoted string.
+    fn utf8(&mut self) -> Result<Constant, AssemblyError> {
+        if let Some(index) = self.index()? {
+            return Ok(Constant::Index(index));
+        }
+        Ok(Constant::Utf8(self.next("a name")?.text.clone()))
+    }
+
+    /// Parses a ClassRef entry: `#n`, `Class <name>` or just the name of the class.
+    fn class(&mut self) -> Result<Constant, AssemblyError> {
+        if let Some(index) = self.index()? {
+            return Ok(Constant::Index(index));
+        }
+        if self.peek_is("Class") {
+            self.position += 1;
+        }
+        Ok(Constant::Class(Box::new(self.utf8()?)))
+    }
+
+    /// Parses a NameTypeDescriptor entry: `#n` or a name followed by a descriptor.
+    fn name_and_type(&mut self) -> Result<Constant, AssemblyError> {
+        if let Some(index) = self.index()? {
+            return Ok(Constant::Index(index));
+        }
+        let name = self.utf8()?;
+        let descriptor = self.utf8()?;
+        Ok(Constant::NameAndType(Box::new(name), Box::new(descriptor)))
+    }
+
+    fn member(&mut self) -> Result<(Box<Constant>, Box<Constant>), AssemblyError> {
+        let class = self.class()?;
+        let name_and_type = self.name_and_type()?;
+        Ok((Box::new(class), Box::new(name_and_type)))
+    }
+
+    fn reference_kind(&mut self) -> Result<ReferenceKind, AssemblyError> {
+        let token = self.next("a reference kind")?;
+        (1..=9)
+            .filter_map(ReferenceKind::from_u8)
+            .find(|kind| reference_kind_keyword(*kind) == token.text)
+            .ok_or_else(|| {
+                AssemblyError::new(
+                    token.line,
+                    format!("unknown reference kind {:?}", token.text),
+                )
+            })
+    }
+
+    /// Parses any constant: `#n`, or a keyword such as `Int` or `Method` followed by the contents of the entry.
+    fn constant(&mut self) -> Result<Constant, AssemblyError> {
+        if let Some(index) = self.index()? {
+            return Ok(Constant::Index(index));
+        }
+        let token = self.next("a constant")?;
+        if token.quoted {
+            return Err(AssemblyError::new(
+                token.line,
+                format!("expected a constant, found the string {:?}", token.text),
+            ));
+        }
+        let constant = match token.text.as_str() {
+            "Utf8" if self.peek_is("bytes") => {
+                self.next("bytes")?;
+                Constant::Utf8Bytes(self.hex()?)
+            }
+            "Utf8" => Constant::Utf8(self.next("a string")?.text.clone()),
+            "Int" => Constant::Integer(self.number("an int")?),
+            "Float" => Constant::Float(self.float()?),
+            "Long" => Constant::Long(self.number("a long")?),
+            "Double" => Constant::Double(self.double()?),
+            "Class" => Constant::Class(Box::new(self.utf8()?)),
+            "String" => Constant::String(Box::new(self.utf8()?)),
+            "MethodType" => Constant::MethodType(Box::new(self.utf8()?)),
+            "Module" => Constant::Module(Box::new(self.utf8()?)),
+            "Package" => Constant::Package(Box::new(self.utf8()?)),
+            "Field" => {
+                let (class, name_and_type) = self.member()?;
+                Constant::Field(class, name_and_type)
+            }
+            "Method" => {
+                let (class, name_and_type) = self.member()?;
+                Constant::Method(class, name_and_type)
+            }
+            "InterfaceMethod" => {
+                let (class, name_and_type) = self.member()?;
+                Constant::InterfaceMethod(class, name_and_type)
+            }
+            "NameAndType" => {
+                let name = self.utf8()?;
+                let descriptor = self.utf8()?;
+                Constant::NameAndType(Box::new(name), Box::new(descriptor))
+            }
+            "MethodHandle" => {
+                let reference_kind = self.reference_kind()?;
+                Constant::MethodHandle(reference_kind, Box::new(self.constant()?))
+            }
+            "Dynamic" => {
+                let bootstrap_method = self.number("a bootstrap method index")?;
+                Constant::Dynamic(bootstrap_method, Box::new(self.name_and_type()?))
+            }
+            "InvokeDynamic" => {
+                let bootstrap_method = self.number("a bootstrap method index")?;
+                Constant::InvokeDynamic(bootstrap_method, Box::new(self.name_and_type()?))
+            }
+            other => {
+                return Err(AssemblyError::new(
+                    token.line,
+                    format!("expected a constant, found {other:?}"),
+                ));
+            }
+        };
+        Ok(constant)
+    }
+
+    /// Parses the operand of a field or method instruction, where the keyword may be left out.
+    fn member_constant(
+        &mut self,
+        build: fn(Box<Constant>, Box<Constant>) -> Constant,
+    ) -> Result<Constant, AssemblyError> {
+        let is_keyword = self
+            .peek()
+            .is_some_and(|token| !token.quoted && CONSTANT_KEYWORDS.contains(&token.text.as_str()));
+        if is_keyword || self.peek().is_some_and(|token| token.text.starts_with('#')) {
+            return self.constant();
+        }
+        let (class, name_and_type) = self.member()?;
+        Ok(build(class, name_and_type))
+    }
+
+    fn flags<F: Flags<Bits = u16>>(&mut self) -> F {
+        let mut flags = F::empty();
+        while let Some(token) = self.peek() {
+            let name = format!("ACC_{}", token.text.to_uppercase());
+            match (token.quoted, F::from_name(&name)) {
+                (false, Some(flag)) => {
+                    flags.insert(flag);
+                    self.position += 1;
+                }
+                _ => break,
+            }
+        }
+        flags
+    }
+
+    fn position(&mut self) -> Result<Position, AssemblyError> {
+        let token = self.next("a label or an offset")?;
+        match token.text.parse() {
+            Ok(offset) => Ok(Position::Offset(offset)),
+            Err(_) => Ok(Position::Label(token.text.clone())),
+        }
+    }
+
+    fn hex(&mut self) -> Result<Vec<u8>, AssemblyError> {
+        let token = self.next("a quoted hex string")?;
+        let digits: Vec<char> = token.text.chars().filter(|c| !c.is_whitespace()).collect();
+        if !token.quoted || !digits.len().is_multiple_of(2) {
+            return Err(AssemblyError::new(
+                token.line,
+                "expected a quoted hex string",
+            ));
+        }
+        digits
+            .chunks(2)
+            .map(|pair| {
+                let pair: String = pair.iter().collect();
+                u8::from_str_radix(&pair, 16).map_err(|_| {
+                    AssemblyError::new(token.line, format!("invalid hex byte {pair:?}"))
+                })
+            })
+            .collect()
+    }
+
+    fn local_variables(&mut self, end: &str) -> Result<Vec<LocalVariableSyntax>, AssemblyError> {
+        let mut local_variables = Vec::new();
+        while !self.peek_is(".end") {
+            let line = self.line();
+            let index = self.number("a local variable index")?;
+            let name = self.utf8()?;
+            let descriptor = self.utf8()?;
+            self.expect("from")?;
+            let start = self.position()?;
+            self.expect("to")?;
+            let end = self.position()?;
+            local_variables.push(LocalVariableSyntax {
+                line,
+                index,
+                name,
+                descriptor,
+                start,
+                end,
+            });
+        }
+        self.expect(".end")?;
+        self.expect(end)?;
+        Ok(local_variables)
+    }
+
+    /// Parses an attribute directive, or returns `None` if the next token does not start one.
+    fn attribute(&mut self) -> Result<Option<AttributeSyntax>, AssemblyError> {
+        let Some(token) = self.peek() else {
+            return Ok(None);
+        };
+        if token.quoted || !ATTRIBUTE_DIRECTIVES.contains(&token.text.as_str()) {
+            return Ok(None);
+        }
+        let line = token.line;
+        self.position += 1;
+
+        let kind = match token.text.as_str() {
+            ".code" => AttributeKind::Code(self.code()?),
+            ".sourcefile" => AttributeKind::SourceFile(self.utf8()?),
+            ".signature" => AttributeKind::Signature(self.utf8()?),
+            ".constantvalue" => AttributeKind::ConstantValue(self.constant()?),
+            ".exceptions" => {
+                let mut exceptions = Vec::new();
+                while self.peek_on_line(line) {
+                    exceptions.push(self.class()?);
+                }
+                AttributeKind::Exceptions(exceptions)
+            }
+            ".deprecated" => AttributeKind::Deprecated,
+            ".synthetic" => AttributeKind::Synthetic,
+            ".bootstrapmethods" => {
+                let mut bootstrap_methods = Vec::new();
+                while !self.peek_is(".end") {
+                    let method_line = self.line();
+                    let method_handle = self.constant()?;
+                    let mut arguments = Vec::new();
+                    while self.peek_on_line(method_line) {
+                        arguments.push(self.constant()?);
+                    }
+                    bootstrap_methods.push((method_handle, arguments));
+                }
+                self.expect(".end")?;
+                self.expect("bootstrapmethods")?;
+                AttributeKind::BootstrapMethods(bootstrap_methods)
+            }
+            ".linenumbertable" => {
+                let mut line_numbers = Vec::new();
+                while !self.peek_is(".end") {
+                    let start = self.position()?;
+                    line_numbers.push((start, self.number("a line number")?));
+                }
+                self.expect(".end")?;
+                self.expect("linenumbertable")?;
+                AttributeKind::LineNumberTable(line_numbers)
+            }
+            ".localvariabletable" => {
+                AttributeKind::LocalVariableTable(self.local_variables("localvariabletable")?)
+            }
+            ".localvariabletypetable" => AttributeKind::LocalVariableTypeTable(
+                self.local_variables("localvariabletypetable")?,
+            ),
+            _ => {
+                let name = self.utf8()?;
+                AttributeKind::Raw {
+                    name,
+                    info: self.hex()?,
+                }
+            }
+        };
+
+        Ok(Some(AttributeSyntax { line, kind }))
+    }
+
+    fn operand(&mut self, opcode: Opcode, line: usize) -> Result<OperandSyntax, AssemblyError> {
+        let operand = match opcode {
+            Opcode::Bipush => OperandSyntax::Byte(self.number("a byte")?),
+            Opcode::Sipush => OperandSyntax::Short(self.number("a short")?),
+            Opcode::Ldc | Opcode::LdcW | Opcode::Ldc2W | Opcode::Invokedynamic => {
+                OperandSyntax::Constant(self.constant()?)
+            }
+            Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield => {
+                OperandSyntax::Constant(self.member_constant(Constant::Field)?)
+            }
+            Opcode::Invokevirtual | Opcode::Invokespecial | Opcode::Invokestatic => {
+                OperandSyntax::Constant(self.member_constant(Constant::Method)?)
+            }
+            Opcode::Invokeinterface => {
+                let constant = self.member_constant(Constant::InterfaceMethod)?;
+                let count = match self.peek_on_line(line) {
+                    true => Some(self.number("an argument count")?),
+                    false => None,
+                };
+                OperandSyntax::InvokeInterface { constant, count }
+            }
+            Opcode::New | Opcode::Anewarray | Opcode::Checkcast | Opcode::Instanceof => {
+                OperandSyntax::Constant(self.class()?)
+            }
+            Opcode::Multianewarray => {
+                let constant = self.class()?;
+                OperandSyntax::MultiANewArray {
+                    constant,
+                    dimensions: self.number("a number of dimensions")?,
+                }
+            }
+            Opcode::Newarray => {
+                let token = self.next("an array type")?;
+                match array_type_code(&token.text).or_else(|| token.text.parse().ok()) {
+                    Some(array_type) => OperandSyntax::ArrayType(array_type),
+                    None => {
+                        return Err(AssemblyError::new(
+                            token.line,
+                            format!("unknown array type {:?}", token.text),
+                        ));
+                    }
+                }
+            }
+            Opcode::Iload
+            | Opcode::Lload
+            | Opcode::Fload
+            | Opcode::Dload
+            | Opcode::Aload
+            | Opcode::Istore
+            | Opcode::Lstore
+            | Opcode::Fstore
+            | Opcode::Dstore
+            | Opcode::Astore
+            | Opcode::Ret => OperandSyntax::Local(self.number("a local variable index")?),
+            Opcode::Iinc => OperandSyntax::Iinc {
+                index: self.number("a local variable index")?,
+                value: self.number("an increment")?,
+            },
+            Opcode::Ifeq
+            | Opcode::Ifne
+            | Opcode::Iflt
+            | Opcode::Ifge
+            | Opcode::Ifgt
+            | Opcode::Ifle
+            | Opcode::IfIcmpeq
+            | Opcode::IfIcmpne
+            | Opcode::IfIcmplt
+            | Opcode::IfIcmpge
+            | Opcode::IfIcmpgt
+            | Opcode::IfIcmple
+            | Opcode::IfAcmpeq
+            | Opcode::IfAcmpne
+            | Opcode::Goto
+            | Opcode::Jsr
+            | Opcode::Ifnull
+            | Opcode::Ifnonnull
+            | Opcode::GotoW
+            | Opcode::JsrW => OperandSyntax::Branch(self.position()?),
+            Opcode::Tableswitch => {
+                let low = self.number("the lowest key")?;
+                let mut targets = Vec::new();
+                while !self.peek_is("default") {
+                    targets.push(self.position()?);
+                }
+                self.expect("default")?;
+                if targets.is_empty() {
+                    return self.error("tableswitch needs at least one target");
+                }
+                OperandSyntax::TableSwitch {
+                    low,
+                    targets,
+                    default: self.position()?,
+                }
+            }
+            Opcode::Lookupswitch => {
+                let mut pairs = Vec::new();
+                while !self.peek_is("default") {
+                    let key = self.number("a key")?;
+                    pairs.push((key, self.position()?));
+                }
+                self.expect("default")?;
+                OperandSyntax::LookupSwitch {
+                    pairs,
+                    default: self.position()?,
+                }
+            }
+            _ => OperandSyntax::None,
+        };
+        Ok(operand)
+    }
+
+    fn code(&mut self) -> Result<CodeSyntax, AssemblyError> {
+        self.expect("stack")?;
+        let max_stack = self.number("the maximum stack size")?;
+        self.expect("locals")?;
+        let max_locals = self.number("the number of locals")?;
+
+        let mut items = Vec::new();
+        let mut catches = Vec::new();
+        let mut attributes = Vec::new();
+        loop {
+            if let Some(attribute) = self.attribute()? {
+                attributes.push(attribute);
+                continue;
+            }
+
+            let token = self.next(".end code")?;
+            let line = token.line;
+            match token.text.as_str() {
+                ".end" => {
+                    self.expect("code")?;
+                    break;
+                }
+                ".catch" => {
+                    let catch_type = match self.peek_is("any") {
+                        true => {
+                            self.position += 1;
+                            None
+                        }
+                        false => Some(self.class()?),
+                    };
+                    self.expect("from")?;
+                    let start = self.position()?;
+                    self.expect("to")?;
+                    let end = self.position()?;
+                    self.expect("using")?;
+                    catches.push(CatchSyntax {
+                        line,
+                        catch_type,
+                        start,
+                        end,
+                        handler: self.position()?,
+                    });
+                }
+                label if label.ends_with(':') && !token.quoted => {
+                    items.push(CodeItem::Label(
+                        label.trim_end_matches(':').to_string(),
+                        line,
+                    ));
+                }
+                mnemonic => {
+                    let wide = mnemonic == "wide";
+                    let mnemonic = match wide {
+                        true => self.next("an instruction")?.text.as_str(),
+                        false => mnemonic,
+                    };
+                    let Some(opcode) = Opcode::from_mnemonic(mnemonic) else {
+                        return Err(AssemblyError::new(
+                            line,
+                            format!("unknown instruction {mnemonic:?}"),
+                        ));
+                    };
+                    let operand = self.operand(opcode, line)?;
+                    items.push(CodeItem::Instruction(InstructionSyntax {
+                        line,
+                        opcode,
+                        wide,
+                        operand,
+                    }));
+                }
+            }
+        }
+
+        Ok(CodeSyntax {
+            max_stack,
+            max_locals,
+            items,
+            catches,
+            attributes,
+        })
+    }
+
+    fn field(&mut self, line: usize) -> Result<MemberSyntax<FieldAccessFlags>, AssemblyError> {
+        let access_flags = self.flags();
+        let name = self.utf8()?;
+        let descriptor = self.utf8()?;
+        let mut attributes = Vec::new();
+        while let Some(attribute) = self.attribute()? {
+            attributes.push(attribute);
+        }
+        // Only fields with attributes need to be closed.
+        if !attributes.is_empty() {
+            self.expect(".end")?;
+            self.expect("field")?;
+        }
+        Ok(MemberSyntax {
+            line,
+            access_flags,
+            name,
+            descriptor,
+            attributes,
+        })
+    }
+
+    fn method(&mut self, line: usize) -> Result<MemberSyntax<MethodAccessFlags>, AssemblyError> {
+        let access_flags = self.flags();
+        let name = self.utf8()?;
+        let descriptor = self.utf8()?;
+        let mut attributes = Vec::new();
+        while let Some(attribute) = self.attribute()? {
+            attributes.push(attribute);
+        }
+        self.expect(".end")?;
+        self.expect("method")?;
+        Ok(MemberSyntax {
+            line,
+            access_flags,
+            name,
+            descriptor,
+            attributes,
+        })
+    }
+
+    fn class_file(&mut self) -> Result<ClassSyntax, AssemblyError> {
+        let mut class = ClassSyntax {
+            major_version: 52,
+            minor_version: 0,
+            access_flags: AccessFlags::empty(),
+            this_class: Constant::Index(0),
+            super_class: None,
+            interfaces: Vec::new(),
+            constants: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: Vec::new(),
+        };
+        let mut has_class = false;
+
+        while self.peek().is_some() {
+            if let Some(attribute) = self.attribute()? {
+                class.attributes.push(attribute);
+                continue;
+            }
+
+            let token = self.next("a directive")?;
+            let line = token.line;
+            match token.text.as_str() {
+                ".version" => {
+                    class.major_version = self.number("a major version")?;
+                    class.minor_version = self.number("a minor version")?;
+                }
+                ".class" => {
+                    class.access_flags = self.flags();
+                    class.this_class = self.class()?;
+                    has_class = true;
+                }
+                ".super" => class.super_class = Some(self.class()?),
+                ".implements" => class.interfaces.push(self.class()?),
+                ".const" => {
+                    let Some(index) = self.index()? else {
+                        return self.error("expected a constant pool index such as #1");
+                    };
+                    self.expect("=")?;
+                    class.constants.push(ConstantDefinition {
+                        line,
+                        index,
+                        constant: self.constant()?,
+                    });
+                }
+                ".field" => {
+                    let field = self.field(line)?;
+                    class.fields.push(field);
+                }
+                ".method" => {
+                    let method = self.method(line)?;
+                    class.methods.push(method);
+                }
+                other => {
+                    return Err(AssemblyError::new(line, format!("unexpected {other:?}")));
+                }
+            }
+        }
+
+        match has_class {
+            true => Ok(class),
+            false => Err(AssemblyError::new(0, "missing .class directive")),
+        }
+    }
+}
+
+/// Parses the tokens of a class in assembly syntax.
+pub fn parse(tokens: &[Token]) -> Result<ClassSyntax, AssemblyError> {
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+    parser.class_file()
+}
@@ -0,0 +1,517 @@
+use std::result::Result;
+
+pub use crate::attributes::types::{
+    Attribute, BootstrapMethod, Code, ExceptionHandler, LineNumber, LocalVariable, MethodParameter,
+    Module, ModuleFlags, ModulePackage, ModuleProvides, ModuleRequires, PackageFlags,
+    ParameterFlags, RequiresFlags,
+};
+use crate::bytecode::{Instruction, decode};
+use crate::constant_pool::ConstantPool;
+use crate::errors::ClassFileError;
+use crate::shared::{to_bytes, to_u8, to_u16, to_u32, write_u16, write_u32};
+use log::{debug, error};
+
+pub mod types;
+
+const BOOTSTRAP_METHODS: &str = "BootstrapMethods";
+const MODULE: &str = "Module";
+const MODULE_PACKAGES: &str = "ModulePackages";
+const MODULE_MAIN_CLASS: &str = "ModuleMainClass";
+const EXCEPTIONS: &str = "Exceptions";
+const METHOD_PARAMETERS: &str = "MethodParameters";
+const DEPRECATED: &str = "Deprecated";
+const SYNTHETIC: &str = "Synthetic";
+const CODE: &str = "Code";
+const CONSTANT_VALUE: &str = "ConstantValue";
+const SOURCE_FILE: &str = "SourceFile";
+const SIGNATURE: &str = "Signature";
+const LINE_NUMBER_TABLE: &str = "LineNumberTable";
+const LOCAL_VARIABLE_TABLE: &str = "LocalVariableTable";
+const LOCAL_VARIABLE_TYPE_TABLE: &str = "LocalVariableTypeTable";
+
+fn read_u16_table(data: &[u8], from_idx: usize) -> Result<(Vec<u16>, usize), ClassFileError> {
+    let count = to_u16(data, from_idx, from_idx + 1)?;
+    let mut values = Vec::with_capacity(usize::from(count));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..count {
+        values.push(to_u16(data, current_idx, current_idx + 1)?);
+        current_idx += 2;
+    }
+
+    Ok((values, current_idx))
+}
+
+fn read_bootstrap_methods(data: &[u8], from_idx: usize) -> Result<Attribute, ClassFileError> {
+    let num_bootstrap_methods = to_u16(data, from_idx, from_idx + 1)?;
+    let mut bootstrap_methods = Vec::with_capacity(usize::from(num_bootstrap_methods));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..num_bootstrap_methods {
+        let method_ref = to_u16(data, current_idx, current_idx + 1)?;
+        let arguments = read_u16_table(data, current_idx + 2)?;
+        current_idx = arguments.1;
+        debug!(
+            "found bootstrap method; method_ref={method_ref}, arguments={:?}",
+            arguments.0
+        );
+
+        bootstrap_methods.push(BootstrapMethod {
+            method_ref,
+            arguments: arguments.0,
+        });
+    }
+
+    Ok(Attribute::BootstrapMethods(bootstrap_methods))
+}
+
+fn read_code(
+    data: &[u8],
+    from_idx: usize,
+    end_idx: usize,
+    constant_pool: &ConstantPool,
+) -> Result<Attribute, ClassFileError> {
+    let max_stack = to_u16(data, from_idx, from_idx + 1)?;
+    let max_locals = to_u16(data, from_idx + 2, from_idx + 3)?;
+    let code_length = to_u32(data, from_idx + 4, from_idx + 7)? as usize;
+    let code_idx = from_idx + 8;
+    if code_idx + code_length > end_idx {
+        dbg!(code_length);
+        return Err(ClassFileError::InvalidAttribute);
+    }
+    let code = to_bytes(data, code_idx, code_length)?.to_vec();
+    debug!("found code; max_stack={max_stack}, max_locals={max_locals}, code_length={code_length}");
+
+    let mut current_idx = code_idx + code_length;
+    let exception_table_length = to_u16(data, current_idx, current_idx + 1)?;
+    let mut exception_table = Vec::with_capacity(usize::from(exception_table_length));
+    current_idx += 2;
+    for _ in 0..exception_table_length {
+        exception_table.push(ExceptionHandler {
+            start_pc: to_u16(data, current_idx, current_idx + 1)?,
+            end_pc: to_u16(data, current_idx + 2, current_idx + 3)?,
+            handler_pc: to_u16(data, current_idx + 4, current_idx + 5)?,
+            catch_type: to_u16(data, current_idx + 6, current_idx + 7)?,
+        });
+        current_idx += 8;
+    }
+
+    let attributes = read_attributes(data, current_idx, constant_pool)?;
+    if attributes.1 != end_idx {
+        dbg!(attributes.1, end_idx);
+        return Err(ClassFileError::InvalidAttribute);
+    }
+
+    Ok(Attribute::Code(Code {
+        max_stack,
+        max_locals,
+        code,
+        exception_table,
+        attributes: attributes.0,
+    }))
+}
+
+fn read_line_number_table(data: &[u8], from_idx: usize) -> Result<Attribute, ClassFileError> {
+    let count = to_u16(data, from_idx, from_idx + 1)?;
+    let mut line_numbers = Vec::with_capacity(usize::from(count));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..count {
+        line_numbers.push(LineNumber {
+            start_pc: to_u16(data, current_idx, current_idx + 1)?,
+            line_number: to_u16(data, current_idx + 2, current_idx + 3)?,
+        });
+        current_idx += 4;
+    }
+
+    Ok(Attribute::LineNumberTable(line_numbers))
+}
+
+fn read_local_variables(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<Vec<LocalVariable>, ClassFileError> {
+    let count = to_u16(data, from_idx, from_idx + 1)?;
+    let mut local_variables = Vec::with_capacity(usize::from(count));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..count {
+        local_variables.push(LocalVariable {
+            start_pc: to_u16(data, current_idx, current_idx + 1)?,
+            length: to_u16(data, current_idx + 2, current_idx + 3)?,
+            name_idx: to_u16(data, current_idx + 4, current_idx + 5)?,
+            descriptor_idx: to_u16(data, current_idx + 6, current_idx + 7)?,
+            index: to_u16(data, current_idx + 8, current_idx + 9)?,
+        });
+        current_idx += 10;
+    }
+
+    Ok(local_variables)
+}
+
+fn read_method_parameters(data: &[u8], from_idx: usize) -> Result<Attribute, ClassFileError> {
+    // Unlike most tables, the number of parameters is a single byte.
+    let count = to_u8(data, from_idx)?;
+    let mut parameters = Vec::with_capacity(usize::from(count));
+    let mut current_idx = from_idx + 1;
+
+    for _ in 0..count {
+        parameters.push(MethodParameter {
+            name_idx: to_u16(data, current_idx, current_idx + 1)?,
+            flags: ParameterFlags::from_bits_retain(to_u16(
+                data,
+                current_idx + 2,
+                current_idx + 3,
+            )?),
+        });
+        current_idx += 4;
+    }
+
+    Ok(Attribute::MethodParameters(parameters))
+}
+
+fn read_module_packages(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(Vec<ModulePackage>, usize), ClassFileError> {
+    let count = to_u16(data, from_idx, from_idx + 1)?;
+    let mut packages = Vec::with_capacity(usize::from(count));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..count {
+        let package_idx = to_u16(data, current_idx, current_idx + 1)?;
+        let flags = PackageFlags::from_bits_retain(to_u16(data, current_idx + 2, current_idx + 3)?);
+        let to = read_u16_table(data, current_idx + 4)?;
+        current_idx = to.1;
+
+        packages.push(ModulePackage {
+            package_idx,
+            flags,
+            to: to.0,
+        });
+    }
+
+    Ok((packages, current_idx))
+}
+
+fn read_module(data: &[u8], from_idx: usize) -> Result<Attribute, ClassFileError> {
+    let name_idx = to_u16(data, from_idx, from_idx + 1)?;
+    let flags = ModuleFlags::from_bits_retain(to_u16(data, from_idx + 2, from_idx + 3)?);
+    let version_idx = to_u16(data, from_idx + 4, from_idx + 5)?;
+    debug!("found module; name_idx={name_idx}, flags={flags:?}, version_idx={version_idx}");
+
+    let requires_count = to_u16(data, from_idx + 6, from_idx + 7)?;
+    let mut requires = Vec::with_capacity(usize::from(requires_count));
+    let mut current_idx = from_idx + 8;
+    for _ in 0..requires_count {
+        requires.push(ModuleRequires {
+            requires_idx: to_u16(data, current_idx, current_idx + 1)?,
+            flags: RequiresFlags::from_bits_retain(to_u16(data, current_idx + 2, current_idx + 3)?),
+            version_idx: to_u16(data, current_idx + 4, current_idx + 5)?,
+        });
+        current_idx += 6;
+    }
+
+    let exports = read_module_packages(data, current_idx)?;
+    let opens = read_module_packages(data, exports.1)?;
+    let uses = read_u16_table(data, opens.1)?;
+
+    let provides_count = to_u16(data, uses.1, uses.1 + 1)?;
+    let mut provides = Vec::with_capacity(usize::from(provides_count));
+    current_idx = uses.1 + 2;
+    for _ in 0..provides_count {
+        let provides_idx = to_u16(data, current_idx, current_idx + 1)?;
+        let with = read_u16_table(data, current_idx + 2)?;
+        current_idx = with.1;
+
+        provides.push(ModuleProvides {
+            provides_idx,
+            with: with.0,
+        });
+    }
+
+    Ok(Attribute::Module(Module {
+        name_idx,
+        flags,
+        version_idx,
+        requires,
+        exports: exports.0,
+        opens: opens.0,
+        uses: uses.0,
+        provides,
+    }))
+}
+
+fn read_attribute(
+    data: &[u8],
+    from_idx: usize,
+    constant_pool: &ConstantPool,
+) -> Result<(Attribute, usize), ClassFileError> {
+    let name_idx = to_u16(data, from_idx, from_idx + 1)?;
+    let length = to_u32(data, from_idx + 2, from_idx + 5)? as usize;
+    let info_idx = from_idx + 6;
+    let end_idx = info_idx + length;
+    if end_idx > data.len() {
+        dbg!(end_idx);
+        return Err(ClassFileError::Truncated);
+    }
+    // The readers of the attributes can't read beyond it.
+    let data = &data[..end_idx];
+
+    let name = constant_pool.string_entry(name_idx)?;
+    debug!("found attribute; name={name}, length={length}");
+
+    let attribute = match name.as_str() {
+        BOOTSTRAP_METHODS => read_bootstrap_methods(data, info_idx)?,
+        MODULE => read_module(data, info_idx)?,
+        MODULE_PACKAGES => Attribute::ModulePackages(read_u16_table(data, info_idx)?.0),
+        MODULE_MAIN_CLASS => Attribute::ModuleMainClass(to_u16(data, info_idx, info_idx + 1)?),
+        EXCEPTIONS => Attribute::Exceptions(read_u16_table(data, info_idx)?.0),
+        METHOD_PARAMETERS => read_method_parameters(data, info_idx)?,
+        DEPRECATED => Attribute::Deprecated,
+        SYNTHETIC => Attribute::Synthetic,
+        CODE => read_code(data, info_idx, end_idx, constant_pool)?,
+        CONSTANT_VALUE => Attribute::ConstantValue(to_u16(data, info_idx, info_idx + 1)?),
+        SOURCE_FILE => Attribute::SourceFile(to_u16(data, info_idx, info_idx + 1)?),
+        SIGNATURE => Attribute::Signature(to_u16(data, info_idx, info_idx + 1)?),
+        LINE_NUMBER_TABLE => read_line_number_table(data, info_idx)?,
+        LOCAL_VARIABLE_TABLE => {
+            Attribute::LocalVariableTable(read_local_variables(data, info_idx)?)
+        }
+        LOCAL_VARIABLE_TYPE_TABLE => {
+            Attribute::LocalVariableTypeTable(read_local_variables(data, info_idx)?)
+        }
+        _ => Attribute::Unknown {
+            name_idx,
+            info: data[info_idx..].to_vec(),
+        },
+    };
+
+    Ok((attribute, end_idx))
+}
+
+/// Reads an `attributes_count` followed by that many attributes, as found at the end of a class, field or method.
+pub fn read_attributes(
+    data: &[u8],
+    from_idx: usize,
+    constant_pool: &ConstantPool,
+) -> Result<(Vec<Attribute>, usize), ClassFileError> {
+    let attributes_count = to_u16(data, from_idx, from_idx + 1)?;
+    let mut attributes = Vec::with_capacity(usize::from(attributes_count));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..attributes_count {
+        let attribute = read_attribute(data, current_idx, constant_pool)?;
+        attributes.push(attribute.0);
+        current_idx = attribute.1;
+    }
+
+    Ok((attributes, current_idx))
+}
+
+fn write_u16_table(values: &[u16], out: &mut Vec<u8>) {
+    write_u16(out, values.len() as u16);
+    for value in values {
+        write_u16(out, *value);
+    }
+}
+
+fn write_local_variables(local_variables: &[LocalVariable], out: &mut Vec<u8>) {
+    write_u16(out, local_variables.len() as u16);
+    for local_variable in local_variables {
+        write_u16(out, local_variable.start_pc);
+        write_u16(out, local_variable.length);
+        write_u16(out, local_variable.name_idx);
+        write_u16(out, local_variable.descriptor_idx);
+        write_u16(out, local_variable.index);
+    }
+}
+
+fn write_module_packages(packages: &[ModulePackage], out: &mut Vec<u8>) {
+    write_u16(out, packages.len() as u16);
+    for package in packages {
+        write_u16(out, package.package_idx);
+        write_u16(out, package.flags.bits());
+        write_u16_table(&package.to, out);
+    }
+}
+
+fn write_module(module: &Module, out: &mut Vec<u8>) {
+    write_u16(out, module.name_idx);
+    write_u16(out, module.flags.bits());
+    write_u16(out, module.version_idx);
+
+    write_u16(out, module.requires.len() as u16);
+    for requires in &module.requires {
+        write_u16(out, requires.requires_idx);
+        write_u16(out, requires.flags.bits());
+        write_u16(out, requires.version_idx);
+    }
+
+    write_module_packages(&module.exports, out);
+    write_module_packages(&module.opens, out);
+    write_u16_table(&module.uses, out);
+
+    write_u16(out, module.provides.len() as u16);
+    for provides in &module.provides {
+        write_u16(out, provides.provides_idx);
+        write_u16_table(&provides.with, out);
+    }
+}
+
+fn write_code(
+    code: &Code,
+    constant_pool: &ConstantPool,
+    out: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    write_u16(out, code.max_stack);
+    write_u16(out, code.max_locals);
+    write_u32(out, code.code.len() as u32);
+    out.extend_from_slice(&code.code);
+
+    write_u16(out, code.exception_table.len() as u16);
+    for handler in &code.exception_table {
+        write_u16(out, handler.start_pc);
+        write_u16(out, handler.end_pc);
+        write_u16(out, handler.handler_pc);
+        write_u16(out, handler.catch_type);
+    }
+
+    write_attributes(&code.attributes, constant_pool, out)
+}
+
+/// The name of an attribute as stored in the class file, or `None` for an unknown attribute.
+pub(crate) fn attribute_name(attribute: &Attribute) -> Option<&'static str> {
+    match attribute {
+        Attribute::BootstrapMethods(_) => Some(BOOTSTRAP_METHODS),
+        Attribute::Module(_) => Some(MODULE),
+        Attribute::ModulePackages(_) => Some(MODULE_PACKAGES),
+        Attribute::ModuleMainClass(_) => Some(MODULE_MAIN_CLASS),
+        Attribute::Exceptions(_) => Some(EXCEPTIONS),
+        Attribute::MethodParameters(_) => Some(METHOD_PARAMETERS),
+        Attribute::Deprecated => Some(DEPRECATED),
+        Attribute::Synthetic => Some(SYNTHETIC),
+        Attribute::Code(_) => Some(CODE),
+        Attribute::ConstantValue(_) => Some(CONSTANT_VALUE),
+        Attribute::SourceFile(_) => Some(SOURCE_FILE),
+        Attribute::Signature(_) => Some(SIGNATURE),
+        Attribute::LineNumberTable(_) => Some(LINE_NUMBER_TABLE),
+        Attribute::LocalVariableTable(_) => Some(LOCAL_VARIABLE_TABLE),
+        Attribute::LocalVariableTypeTable(_) => Some(LOCAL_VARIABLE_TYPE_TABLE),
+        Attribute::Unknown { .. } => None,
+    }
+}
+
+/// Encodes an attribute into the index of its name in the constant pool and its `info` bytes.
+///
+/// The name of a known attribute is looked up in the constant pool, so it must contain a String entry holding it.
+pub fn encode_attribute(
+    attribute: &Attribute,
+    constant_pool: &ConstantPool,
+) -> Result<(u16, Vec<u8>), ClassFileError> {
+    let name_idx = match (attribute, attribute_name(attribute)) {
+        (Attribute::Unknown { name_idx, .. }, _) => *name_idx,
+        (_, Some(name)) => match constant_pool.find_string(name) {
+            Some(name_idx) => name_idx,
+            None => {
+                error!("The constant pool lacks the name of the {name} attribute");
+                return Err(ClassFileError::InvalidAttribute);
+            }
+        },
+        (_, None) => unreachable!("only unknown attributes have no name"),
+    };
+
+    let mut info = Vec::new();
+    match attribute {
+        Attribute::BootstrapMethods(bootstrap_methods) => {
+            write_u16(&mut info, bootstrap_methods.len() as u16);
+            for bootstrap_method in bootstrap_methods {
+                write_u16(&mut info, bootstrap_method.method_ref);
+                write_u16_table(&bootstrap_method.arguments, &mut info);
+            }
+        }
+        Attribute::Module(module) => write_module(module, &mut info),
+        Attribute::ModulePackages(packages) => write_u16_table(packages, &mut info),
+        Attribute::Exceptions(exceptions) => write_u16_table(exceptions, &mut info),
+        Attribute::MethodParameters(parameters) => {
+            info.push(parameters.len() as u8);
+            for parameter in parameters {
+                write_u16(&mut info, parameter.name_idx);
+                write_u16(&mut info, parameter.flags.bits());
+            }
+        }
+        Attribute::Deprecated | Attribute::Synthetic => {}
+        Attribute::Code(code) => write_code(code, constant_pool, &mut info)?,
+        Attribute::ModuleMainClass(index)
+        | Attribute::ConstantValue(index)
+        | Attribute::SourceFile(index)
+        | Attribute::Signature(index) => write_u16(&mut info, *index),
+        Attribute::LineNumberTable(line_numbers) => {
+            write_u16(&mut info, line_numbers.len() as u16);
+            for line_number in line_numbers {
+                write_u16(&mut info, line_number.start_pc);
+                write_u16(&mut info, line_number.line_number);
+            }
+        }
+        Attribute::LocalVariableTable(local_variables)
+        | Attribute::LocalVariableTypeTable(local_variables) => {
+            write_local_variables(local_variables, &mut info)
+        }
+        Attribute::Unknown { info: bytes, .. } => info.extend_from_slice(bytes),
+    }
+
+    Ok((name_idx, info))
+}
+
+/// Writes an `attributes_count` followed by the attributes, the counterpart of [`read_attributes`].
+pub fn write_attributes(
+    attributes: &[Attribute],
+    constant_pool: &ConstantPool,
+    out: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    write_u16(out, attributes.len() as u16);
+    for attribute in attributes {
+        let (name_idx, info) = encode_attribute(attribute, constant_pool)?;
+        write_u16(out, name_idx);
+        write_u32(out, info.len() as u32);
+        out.extend_from_slice(&info);
+    }
+    Ok(())
+}
+
+impl Code {
+    /// Decodes the bytecode of this Code attribute into instructions.
+    pub fn instructions(&self) -> Result<Vec<Instruction>, ClassFileError> {
+        decode(&self.code)
+    }
+
+    /// The line number table(s) of this Code attribute, combined.
+    pub fn line_numbers(&self) -> Vec<LineNumber> {
+        let mut line_numbers = Vec::new();
+        for attribute in &self.attributes {
+            if let Attribute::LineNumberTable(table) = attribute {
+                line_numbers.extend(table);
+            }
+        }
+        line_numbers
+    }
+
+    /// The local variable table(s) of this Code attribute, combined.
+    pub fn local_variables(&self) -> Vec<LocalVariable> {
+        let mut local_variables = Vec::new();
+        for attribute in &self.attributes {
+            if let Attribute::LocalVariableTable(table) = attribute {
+                local_variables.extend(table);
+            }
+        }
+        local_variables
+    }
+}
+
+/// Finds the Signature attribute in a list of attributes.
+pub(crate) fn signature_idx(attributes: &[Attribute]) -> Option<u16> {
+    attributes.iter().find_map(|attribute| match attribute {
+        Attribute::Signature(signature_idx) => Some(*signature_idx),
+        _ => None,
+    })
+}
@@ -0,0 +1,201 @@
+use bitflags::bitflags;
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Attribute {
+    /// Bootstrap method specifiers, referenced by `invokedynamic` instructions and by dynamically computed constants.
+    BootstrapMethods(Vec<BootstrapMethod>),
+    /// The module that is described by a `module-info` class.
+    Module(Module),
+    /// Point to Package entries for all packages of a module.
+    ModulePackages(Vec<u16>),
+    /// Points to a ClassRef entry holding the main class of a module.
+    ModuleMainClass(u16),
+    /// Point to ClassRef entries for the checked exceptions that a method may throw.
+    Exceptions(Vec<u16>),
+    /// The formal parameters of a method.
+    MethodParameters(Vec<MethodParameter>),
+    /// Marks a class, field or method as deprecated.
+    Deprecated,
+    /// Marks a class, field or method as not present in the source code.
+    Synthetic,
+    /// The bytecode and auxiliary information of a method.
+    Code(Code),
+    /// Points to the constant pool entry holding the value of a constant field.
+    ConstantValue(u16),
+    /// Points to a String entry holding the name of the source file from which the class was compiled.
+    SourceFile(u16),
+    /// Points to a String entry holding the generic signature of a class, field or method.
+    Signature(u16),
+    /// Maps offsets in the code to line numbers in the source file.
+    LineNumberTable(Vec<LineNumber>),
+    /// Describes the local variables of a method.
+    LocalVariableTable(Vec<LocalVariable>),
+    /// Describes the generic signatures of the local variables of a method.
+    LocalVariableTypeTable(Vec<LocalVariable>),
+
+    /// An attribute that is not (yet) understood. Its content is kept as-is.
+    Unknown { name_idx: u16, info: Vec<u8> },
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BootstrapMethod {
+    /// Points to a MethodHandle entry in the constant pool.
+    pub method_ref: u16,
+    /// Point to loadable entries in the constant pool (static arguments to the bootstrap method).
+    pub arguments: Vec<u16>,
+}
+
+bitflags! {
+    /// Denote properties of a module.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct ModuleFlags: u16 {
+        // Indicates that this module is open.
+        const ACC_OPEN = 0x0020;
+        // Indicates that this module was not explicitly or implicitly declared.
+        const ACC_SYNTHETIC = 0x1000;
+        // Indicates that this module was implicitly declared.
+        const ACC_MANDATED = 0x8000;
+    }
+}
+
+bitflags! {
+    /// Denote properties of a dependence on a module.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct RequiresFlags: u16 {
+        // Indicates that any module which depends on the current module, implicitly declares a dependence on this module.
+        const ACC_TRANSITIVE = 0x0020;
+        // Indicates that this dependence is mandatory in the static phase, but optional in the dynamic phase.
+        const ACC_STATIC_PHASE = 0x0040;
+        // Indicates that this dependence was not explicitly or implicitly declared.
+        const ACC_SYNTHETIC = 0x1000;
+        // Indicates that this dependence was implicitly declared.
+        const ACC_MANDATED = 0x8000;
+    }
+}
+
+bitflags! {
+    /// Denote properties of an exported or opened package.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct PackageFlags: u16 {
+        // Indicates that this export or opening was not explicitly or implicitly declared.
+        const ACC_SYNTHETIC = 0x1000;
+        // Indicates that this export or opening was implicitly declared.
+        const ACC_MANDATED = 0x8000;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Module {
+    /// Points to a Module entry in the constant pool.
+    pub name_idx: u16,
+    pub flags: ModuleFlags,
+    /// Points to a String entry holding the version of the module, or 0 if there is no version.
+    pub version_idx: u16,
+    pub requires: Vec<ModuleRequires>,
+    pub exports: Vec<ModulePackage>,
+    pub opens: Vec<ModulePackage>,
+    /// Point to ClassRef entries for service interfaces that this module uses.
+    pub uses: Vec<u16>,
+    pub provides: Vec<ModuleProvides>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ModuleRequires {
+    /// Points to a Module entry in the constant pool.
+    pub requires_idx: u16,
+    pub flags: RequiresFlags,
+    /// Points to a String entry holding the version of the required module, or 0 if there is no version.
+    pub version_idx: u16,
+}
+
+/// An exported or opened package.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ModulePackage {
+    /// Points to a Package entry in the constant pool.
+    pub package_idx: u16,
+    pub flags: PackageFlags,
+    /// Point to Module entries; empty if the package is exported or opened to all modules.
+    pub to: Vec<u16>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ModuleProvides {
+    /// Points to a ClassRef entry for the service interface.
+    pub provides_idx: u16,
+    /// Point to ClassRef entries for the service implementations.
+    pub with: Vec<u16>,
+}
+
+bitflags! {
+    /// Denote properties of a formal parameter.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct ParameterFlags: u16 {
+        // Indicates that the formal parameter was declared final.
+        const ACC_FINAL = 0x0010;
+        // Indicates that the formal parameter was not explicitly or implicitly declared in source code.
+        const ACC_SYNTHETIC = 0x1000;
+        // Indicates that the formal parameter was implicitly declared in source code.
+        const ACC_MANDATED = 0x8000;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MethodParameter {
+    /// Points to a String entry holding the name of the parameter, or 0 if the parameter has no name.
+    pub name_idx: u16,
+    pub flags: ParameterFlags,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Code {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    /// The raw bytecode; see [`crate::bytecode::decode`] to turn it into instructions.
+    pub code: Vec<u8>,
+    pub exception_table: Vec<ExceptionHandler>,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExceptionHandler {
+    /// The offset (inclusive) in the code from which the handler is active.
+    pub start_pc: u16,
+    /// The offset (exclusive) in the code until which the handler is active.
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    /// Points to a ClassRef entry for the caught exception, or 0 if the handler catches everything.
+    pub catch_type: u16,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LineNumber {
+    pub start_pc: u16,
+    pub line_number: u16,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LocalVariable {
+    pub start_pc: u16,
+    pub length: u16,
+    /// Points to a String entry holding the name of the local variable.
+    pub name_idx: u16,
+    /// Points to a String entry holding the field descriptor, or the signature in a LocalVariableTypeTable.
+    pub descriptor_idx: u16,
+    /// The index of the local variable in the local variable array.
+    pub index: u16,
+}
@@ -0,0 +1,176 @@
+use std::result::Result;
+
+use crate::ClassFile;
+use crate::attributes::{Attribute, BootstrapMethod};
+use crate::constant_pool::ConstantPool;
+use crate::constant_pool::types::{ConstantPoolEntry, ReferenceKind};
+use crate::errors::ClassFileError;
+use log::error;
+
+const LAMBDA_METAFACTORY: &str = "java/lang/invoke/LambdaMetafactory";
+const STRING_CONCAT_FACTORY: &str = "java/lang/invoke/StringConcatFactory";
+const OBJECT_METHODS: &str = "java/lang/runtime/ObjectMethods";
+
+/// A method handle constant with its member reference resolved.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MethodHandleInfo {
+    pub reference_kind: ReferenceKind,
+    pub owner: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+/// A static argument that is passed to a bootstrap method.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BootstrapArgument {
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    String(String),
+    Class(String),
+    MethodType(String),
+    MethodHandle(MethodHandleInfo),
+    /// A dynamically computed constant; points to the Dynamic entry in the constant pool.
+    Dynamic(u16),
+}
+
+/// Well-known bootstrap methods emitted by `javac`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BootstrapKind {
+    /// A lambda expression or method reference.
+    LambdaMetafactory,
+    /// String concatenation (Java 9 and later).
+    StringConcatFactory,
+    /// The `equals`, `hashCode` and `toString` methods of a record.
+    ObjectMethods,
+    Other,
+}
+
+/// An `invokedynamic` call site or dynamically computed constant, together with its bootstrap method.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CallSite {
+    pub bootstrap_method: MethodHandleInfo,
+    pub arguments: Vec<BootstrapArgument>,
+    /// The name that is passed to the bootstrap method, e.g. the name of the functional interface method.
+    pub name: String,
+    /// The descriptor of the call site (`invokedynamic`) or the field descriptor of the constant (condy).
+    pub descriptor: String,
+}
+
+impl CallSite {
+    pub fn kind(&self) -> BootstrapKind {
+        match self.bootstrap_method.owner.as_str() {
+            LAMBDA_METAFACTORY => BootstrapKind::LambdaMetafactory,
+            STRING_CONCAT_FACTORY => BootstrapKind::StringConcatFactory,
+            OBJECT_METHODS => BootstrapKind::ObjectMethods,
+            _ => BootstrapKind::Other,
+        }
+    }
+}
+
+/// Resolves a MethodHandle entry in the constant pool to the member it refers to.
+pub fn method_handle_info(
+    constant_pool: &ConstantPool,
+    index: usize,
+) -> Result<MethodHandleInfo, ClassFileError> {
+    let method_handle = constant_pool.method_handle_entry(index)?;
+    let (owner, name, descriptor) =
+        constant_pool.member_ref(method_handle.reference_ref as usize)?;
+
+    Ok(MethodHandleInfo {
+        reference_kind: method_handle.reference_kind,
+        owner: owner.clone(),
+        name: name.clone(),
+        descriptor: descriptor.clone(),
+    })
+}
+
+fn bootstrap_argument(
+    constant_pool: &ConstantPool,
+    index: u16,
+) -> Result<BootstrapArgument, ClassFileError> {
+    match constant_pool.checked_entry(index)? {
+        ConstantPoolEntry::Integer(value) => Ok(BootstrapArgument::Integer(*value)),
+        ConstantPoolEntry::Float(value) => Ok(BootstrapArgument::Float(*value)),
+        ConstantPoolEntry::Long(value) => Ok(BootstrapArgument::Long(*value)),
+        ConstantPoolEntry::Double(value) => Ok(BootstrapArgument::Double(*value)),
+        ConstantPoolEntry::StringRef(value) => Ok(BootstrapArgument::String(
+            constant_pool.string_entry(*value)?.clone(),
+        )),
+        ConstantPoolEntry::ClassRef(value) => Ok(BootstrapArgument::Class(
+            constant_pool.string_entry(*value)?.clone(),
+        )),
+        ConstantPoolEntry::MethodType(value) => Ok(BootstrapArgument::MethodType(
+            constant_pool.string_entry(*value)?.clone(),
+        )),
+        ConstantPoolEntry::MethodHandle(_) => Ok(BootstrapArgument::MethodHandle(
+            method_handle_info(constant_pool, index as usize)?,
+        )),
+        ConstantPoolEntry::Dynamic(_) => Ok(BootstrapArgument::Dynamic(index)),
+        other => {
+            error!("Expected loadable constant at index {index:?}, found {other:?}");
+            Err(ClassFileError::UnexpectedConstantPoolType)
+        }
+    }
+}
+
+impl ClassFile {
+    /// The bootstrap methods of this class, or an empty slice if it has no BootstrapMethods attribute.
+    pub fn bootstrap_methods(&self) -> &[BootstrapMethod] {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::BootstrapMethods(methods) => Some(methods.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+
+    /// Resolves the InvokeDynamic or Dynamic entry at `index` in the constant pool to its bootstrap method,
+    /// the static arguments for that bootstrap method, and the name and type of the call site.
+    pub fn call_site(&self, index: u16) -> Result<CallSite, ClassFileError> {
+        let dynamic_ref = match self.constant_pool.checked_entry(index)? {
+            ConstantPoolEntry::InvokeDynamic(value) | ConstantPoolEntry::Dynamic(value) => *value,
+            other => {
+                error!("Expected InvokeDynamic or Dynamic at index {index:?}, found {other:?}");
+                return Err(ClassFileError::UnexpectedConstantPoolType);
+            }
+        };
+
+        let bootstrap_methods = self.bootstrap_methods();
+        let Some(bootstrap_method) =
+            bootstrap_methods.get(usize::from(dynamic_ref.bootstrap_method_attr_idx))
+        else {
+            error!(
+                "Bootstrap method {} not found, class has {} bootstrap methods",
+                dynamic_ref.bootstrap_method_attr_idx,
+                bootstrap_methods.len()
+            );
+            return Err(ClassFileError::MissingBootstrapMethod);
+        };
+
+        let arguments = bootstrap_method
+            .arguments
+            .iter()
+            .map(|argument| bootstrap_argument(&self.constant_pool, *argument))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (name, descriptor) = self
+            .constant_pool
+            .name_and_type(dynamic_ref.name_type_ref as usize)?;
+
+        Ok(CallSite {
+            bootstrap_method: method_handle_info(
+                &self.constant_pool,
+                bootstrap_method.method_ref as usize,
+            )?,
+            arguments,
+            name: name.clone(),
+            descriptor: descriptor.clone(),
+        })
+    }
+}
@@ -0,0 +1,430 @@
+use std::result::Result;
+
+pub use crate::bytecode::types::{Instruction, Opcode, Operand, array_type_code, array_type_name};
+use crate::errors::ClassFileError;
+use crate::shared::{to_u16, to_u32, write_u16, write_u32};
+use log::error;
+
+pub mod types;
+
+fn read_u8(code: &[u8], idx: usize) -> Result<u8, ClassFileError> {
+    match code.get(idx) {
+        Some(value) => Ok(*value),
+        None => {
+            error!("Unexpected end of code at offset {idx}");
+            Err(ClassFileError::InvalidCode)
+        }
+    }
+}
+
+fn read_u16(code: &[u8], idx: usize) -> Result<u16, ClassFileError> {
+    if idx + 2 > code.len() {
+        error!("Unexpected end of code at offset {idx}");
+        return Err(ClassFileError::InvalidCode);
+    }
+    to_u16(code, idx, idx + 1)
+}
+
+fn read_i32(code: &[u8], idx: usize) -> Result<i32, ClassFileError> {
+    if idx + 4 > code.len() {
+        error!("Unexpected end of code at offset {idx}");
+        return Err(ClassFileError::InvalidCode);
+    }
+    Ok(to_u32(code, idx, idx + 3)? as i32)
+}
+
+fn branch_target(offset: usize, relative: i32, code_length: usize) -> Result<u32, ClassFileError> {
+    let target = offset as i64 + i64::from(relative);
+    if target < 0 || target >= code_length as i64 {
+        error!("Branch at offset {offset} jumps outside the code to {target}");
+        return Err(ClassFileError::InvalidCode);
+    }
+    Ok(target as u32)
+}
+
+fn read_instruction(code: &[u8], offset: usize) -> Result<(Instruction, usize), ClassFileError> {
+    let value = read_u8(code, offset)?;
+    let Some(mut opcode) = Opcode::from_u8(value) else {
+        error!("Unknown opcode {value} at offset {offset}");
+        return Err(ClassFileError::InvalidCode);
+    };
+
+    let wide = opcode == Opcode::Wide;
+    let operand_idx = if wide {
+        let value = read_u8(code, offset + 1)?;
+        opcode = match Opcode::from_u8(value) {
+            Some(
+                modified @ (Opcode::Iload
+                | Opcode::Lload
+                | Opcode::Fload
+                | Opcode::Dload
+                | Opcode::Aload
+                | Opcode::Istore
+                | Opcode::Lstore
+                | Opcode::Fstore
+                | Opcode::Dstore
+                | Opcode::Astore
+                | Opcode::Ret
+                | Opcode::Iinc),
+            ) => modified,
+            _ => {
+                error!("Opcode {value} at offset {offset} cannot be modified by wide");
+                return Err(ClassFileError::InvalidCode);
+            }
+        };
+        offset + 2
+    } else {
+        offset + 1
+    };
+
+    let (operand, next_idx) = match opcode {
+        Opcode::Bipush => (
+            Operand::Byte(read_u8(code, operand_idx)? as i8),
+            operand_idx + 1,
+        ),
+        Opcode::Sipush => (
+            Operand::Short(read_u16(code, operand_idx)? as i16),
+            operand_idx + 2,
+        ),
+        Opcode::Ldc => (
+            Operand::ConstantPool(u16::from(read_u8(code, operand_idx)?)),
+            operand_idx + 1,
+        ),
+        Opcode::LdcW
+        | Opcode::Ldc2W
+        | Opcode::Getstatic
+        | Opcode::Putstatic
+        | Opcode::Getfield
+        | Opcode::Putfield
+        | Opcode::Invokevirtual
+        | Opcode::Invokespecial
+        | Opcode::Invokestatic
+        | Opcode::New
+        | Opcode::Anewarray
+        | Opcode::Checkcast
+        | Opcode::Instanceof => (
+            Operand::ConstantPool(read_u16(code, operand_idx)?),
+            operand_idx + 2,
+        ),
+        Opcode::Invokedynamic => (
+            Operand::ConstantPool(read_u16(code, operand_idx)?),
+            operand_idx + 4,
+        ),
+        Opcode::Invokeinterface => (
+            Operand::InvokeInterface {
+                index: read_u16(code, operand_idx)?,
+                count: read_u8(code, operand_idx + 2)?,
+            },
+            operand_idx + 4,
+        ),
+        Opcode::Multianewarray => (
+            Operand::MultiANewArray {
+                index: read_u16(code, operand_idx)?,
+                dimensions: read_u8(code, operand_idx + 2)?,
+            },
+            operand_idx + 3,
+        ),
+        Opcode::Newarray => (
+            Operand::ArrayType(read_u8(code, operand_idx)?),
+            operand_idx + 1,
+        ),
+        Opcode::Iload
+        | Opcode::Lload
+        | Opcode::Fload
+        | Opcode::Dload
+        | Opcode::Aload
+        | Opcode::Istore
+        | Opcode::Lstore
+        | Opcode::Fstore
+        | Opcode::Dstore
+        | Opcode::Astore
+        | Opcode::Ret => match wide {
+            true => (
+                Operand::Local(read_u16(code, operand_idx)?),
+                operand_idx + 2,
+            ),
+            false => (
+                Operand::Local(u16::from(read_u8(code, operand_idx)?)),
+                operand_idx + 1,
+            ),
+        },
+        Opcode::Iinc => match wide {
+            true => (
+                Operand::Iinc {
+                    index: read_u16(code, operand_idx)?,
+                    value: read_u16(code, operand_idx + 2)? as i16,
+                },
+                operand_idx + 4,
+            ),
+            false => (
+                Operand::Iinc {
+                    index: u16::from(read_u8(code, operand_idx)?),
+                    value: i16::from(read_u8(code, operand_idx + 1)? as i8),
+                },
+                operand_idx + 2,
+            ),
+        },
+        Opcode::Ifeq
+        | Opcode::Ifne
+        | Opcode::Iflt
+        | Opcode::Ifge
+        | Opcode::Ifgt
+        | Opcode::Ifle
+        | Opcode::IfIcmpeq
+        | Opcode::IfIcmpne
+        | Opcode::IfIcmplt
+        | Opcode::IfIcmpge
+        | Opcode::IfIcmpgt
+        | Opcode::IfIcmple
+        | Opcode::IfAcmpeq
+        | Opcode::IfAcmpne
+        | Opcode::Goto
+        | Opcode::Jsr
+        | Opcode::Ifnull
+        | Opcode::Ifnonnull => {
+            let relative = i32::from(read_u16(code, operand_idx)? as i16);
+            (
+                Operand::Branch(branch_target(offset, relative, code.len())?),
+                operand_idx + 2,
+            )
+        }
+        Opcode::GotoW | Opcode::JsrW => {
+            let relative = read_i32(code, operand_idx)?;
+            (
+                Operand::Branch(branch_target(offset, relative, code.len())?),
+                operand_idx + 4,
+            )
+        }
+        Opcode::Tableswitch => {
+            // The operands start at the next offset that is a multiple of four.
+            let aligned_idx = (operand_idx + 3) & !3;
+            let default = branch_target(offset, read_i32(code, aligned_idx)?, code.len())?;
+            let low = read_i32(code, aligned_idx + 4)?;
+            let high = read_i32(code, aligned_idx + 8)?;
+            if low > high {
+                error!("Tableswitch at offset {offset} has low {low} > high {high}");
+                return Err(ClassFileError::InvalidCode);
+            }
+
+            let count = (i64::from(high) - i64::from(low) + 1) as usize;
+            let mut targets = Vec::with_capacity(count.min(code.len()));
+            let mut current_idx = aligned_idx + 12;
+            for _ in 0..count {
+                let relative = read_i32(code, current_idx)?;
+                targets.push(branch_target(offset, relative, code.len())?);
+                current_idx += 4;
+            }
+            (
+                Operand::TableSwitch {
+                    default,
+                    low,
+                    high,
+                    targets,
+                },
+                current_idx,
+            )
+        }
+        Opcode::Lookupswitch => {
+            let aligned_idx = (operand_idx + 3) & !3;
+            let default = branch_target(offset, read_i32(code, aligned_idx)?, code.len())?;
+            let npairs = read_i32(code, aligned_idx + 4)?;
+            if npairs < 0 {
+                error!("Lookupswitch at offset {offset} has {npairs} pairs");
+                return Err(ClassFileError::InvalidCode);
+            }
+
+            let mut pairs = Vec::with_capacity((npairs as usize).min(code.len()));
+            let mut current_idx = aligned_idx + 8;
+            for _ in 0..npairs {
+                let key = read_i32(code, current_idx)?;
+                let relative = read_i32(code, current_idx + 4)?;
+                pairs.push((key, branch_target(offset, relative, code.len())?));
+                current_idx += 8;
+            }
+            (Operand::LookupSwitch { default, pairs }, current_idx)
+        }
+        _ => (Operand::None, operand_idx),
+    };
+
+    if next_idx > code.len() {
+        error!("Instruction at offset {offset} extends past the end of the code");
+        return Err(ClassFileError::InvalidCode);
+    }
+
+    let instruction = Instruction {
+        offset: offset as u32,
+        opcode,
+        operand,
+        wide,
+    };
+    Ok((instruction, next_idx))
+}
+
+/// Decodes the `code` array of a Code attribute into instructions.
+pub fn decode(code: &[u8]) -> Result<Vec<Instruction>, ClassFileError> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+
+    while offset < code.len() {
+        let instruction = read_instruction(code, offset)?;
+        instructions.push(instruction.0);
+        offset = instruction.1;
+    }
+
+    Ok(instructions)
+}
+
+fn relative_offset(instruction: &Instruction, target: u32) -> i32 {
+    (i64::from(target) - i64::from(instruction.offset)) as i32
+}
+
+fn write_short_branch(
+    instruction: &Instruction,
+    target: u32,
+    code: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    let relative = relative_offset(instruction, target);
+    match i16::try_from(relative) {
+        Ok(relative) => {
+            write_u16(code, relative as u16);
+            Ok(())
+        }
+        Err(_) => {
+            error!(
+                "Branch at offset {} is too far for {}",
+                instruction.offset,
+                instruction.opcode.mnemonic()
+            );
+            Err(ClassFileError::InvalidCode)
+        }
+    }
+}
+
+fn write_u8_operand(
+    instruction: &Instruction,
+    value: u16,
+    code: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    match u8::try_from(value) {
+        Ok(value) => {
+            code.push(value);
+            Ok(())
+        }
+        Err(_) => {
+            error!(
+                "Operand {value} at offset {} does not fit in a byte",
+                instruction.offset
+            );
+            Err(ClassFileError::InvalidCode)
+        }
+    }
+}
+
+fn write_instruction(instruction: &Instruction, code: &mut Vec<u8>) -> Result<(), ClassFileError> {
+    if instruction.wide {
+        code.push(Opcode::Wide as u8);
+    }
+    code.push(instruction.opcode as u8);
+
+    match &instruction.operand {
+        Operand::None => {}
+        Operand::Byte(value) => code.push(*value as u8),
+        Operand::Short(value) => write_u16(code, *value as u16),
+        Operand::ConstantPool(index) => match instruction.opcode {
+            Opcode::Ldc => write_u8_operand(instruction, *index, code)?,
+            Opcode::Invokedynamic => {
+                write_u16(code, *index);
+                write_u16(code, 0);
+            }
+            _ => write_u16(code, *index),
+        },
+        Operand::Local(index) => match instruction.wide {
+            true => write_u16(code, *index),
+            false => write_u8_operand(instruction, *index, code)?,
+        },
+        Operand::Iinc { index, value } => match instruction.wide {
+            true => {
+                write_u16(code, *index);
+                write_u16(code, *value as u16);
+            }
+            false => {
+                write_u8_operand(instruction, *index, code)?;
+                match i8::try_from(*value) {
+                    Ok(value) => code.push(value as u8),
+                    Err(_) => {
+                        error!(
+                            "Increment {value} at offset {} needs wide",
+                            instruction.offset
+                        );
+                        return Err(ClassFileError::InvalidCode);
+                    }
+                }
+            }
+        },
+        Operand::Branch(target) => match instruction.opcode {
+            Opcode::GotoW | Opcode::JsrW => {
+                write_u32(code, relative_offset(instruction, *target) as u32)
+            }
+            _ => write_short_branch(instruction, *target, code)?,
+        },
+        Operand::ArrayType(array_type) => code.push(*array_type),
+        Operand::InvokeInterface { index, count } => {
+            write_u16(code, *index);
+            code.push(*count);
+            code.push(0);
+        }
+        Operand::MultiANewArray { index, dimensions } => {
+            write_u16(code, *index);
+            code.push(*dimensions);
+        }
+        Operand::TableSwitch {
+            default,
+            low,
+            high,
+            targets,
+        } => {
+            while !code.len().is_multiple_of(4) {
+                code.push(0);
+            }
+            write_u32(code, relative_offset(instruction, *default) as u32);
+            write_u32(code, *low as u32);
+            write_u32(code, *high as u32);
+            for target in targets {
+                write_u32(code, relative_offset(instruction, *target) as u32);
+            }
+        }
+        Operand::LookupSwitch { default, pairs } => {
+            while !code.len().is_multiple_of(4) {
+                code.push(0);
+            }
+            write_u32(code, relative_offset(instruction, *default) as u32);
+            write_u32(code, pairs.len() as u32);
+            for (key, target) in pairs {
+                write_u32(code, *key as u32);
+                write_u32(code, relative_offset(instruction, *target) as u32);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Encodes instructions into the `code` array of a Code attribute, the counterpart of [`decode`].
+///
+/// The offset of each instruction must be where the previous one ends, see [`Instruction::length`].
+pub fn encode(instructions: &[Instruction]) -> Result<Vec<u8>, ClassFileError> {
+    let mut code = Vec::new();
+
+    for instruction in instructions {
+        if instruction.offset as usize != code.len() {
+            error!(
+                "Instruction at offset {} was expected at offset {}",
+                instruction.offset,
+                code.len()
+            );
+            return Err(ClassFileError::InvalidCode);
+        }
+        write_instruction(instruction, &mut code)?;
+    }
+
+    Ok(code)
+}
@@ -0,0 +1,366 @@
+macro_rules! opcodes {
+    ($($name:ident = $value:literal => $mnemonic:literal,)*) => {
+        /// The operation codes of the Java Virtual Machine instruction set.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Opcode {
+            $($name = $value,)*
+        }
+
+        impl Opcode {
+            pub fn from_u8(value: u8) -> Option<Opcode> {
+                match value {
+                    $($value => Some(Opcode::$name),)*
+                    _ => None,
+                }
+            }
+
+            pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+                match mnemonic {
+                    $($mnemonic => Some(Opcode::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// The name of this operation code as used in the Java Virtual Machine Specification.
+            pub fn mnemonic(&self) -> &'static str {
+                match self {
+                    $(Opcode::$name => $mnemonic,)*
+                }
+            }
+        }
+
+        /// Opcodes are serialized as their mnemonic, e.g. `"invokespecial"`.
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for Opcode {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.mnemonic())
+            }
+        }
+    };
+}
+
+opcodes! {
+    Nop = 0 => "nop",
+    AconstNull = 1 => "aconst_null",
+    IconstM1 = 2 => "iconst_m1",
+    Iconst0 = 3 => "iconst_0",
+    Iconst1 = 4 => "iconst_1",
+    Iconst2 = 5 => "iconst_2",
+    Iconst3 = 6 => "iconst_3",
+    Iconst4 = 7 => "iconst_4",
+    Iconst5 = 8 => "iconst_5",
+    Lconst0 = 9 => "lconst_0",
+    Lconst1 = 10 => "lconst_1",
+    Fconst0 = 11 => "fconst_0",
+    Fconst1 = 12 => "fconst_1",
+    Fconst2 = 13 => "fconst_2",
+    Dconst0 = 14 => "dconst_0",
+    Dconst1 = 15 => "dconst_1",
+    Bipush = 16 => "bipush",
+    Sipush = 17 => "sipush",
+    Ldc = 18 => "ldc",
+    LdcW = 19 => "ldc_w",
+    Ldc2W = 20 => "ldc2_w",
+    Iload = 21 => "iload",
+    Lload = 22 => "lload",
+    Fload = 23 => "fload",
+    Dload = 24 => "dload",
+    Aload = 25 => "aload",
+    Iload0 = 26 => "iload_0",
+    Iload1 = 27 => "iload_1",
+    Iload2 = 28 => "iload_2",
+    Iload3 = 29 => "iload_3",
+    Lload0 = 30 => "lload_0",
+    Lload1 = 31 => "lload_1",
+    Lload2 = 32 => "lload_2",
+    Lload3 = 33 => "lload_3",
+    Fload0 = 34 => "fload_0",
+    Fload1 = 35 => "fload_1",
+    Fload2 = 36 => "fload_2",
+    Fload3 = 37 => "fload_3",
+    Dload0 = 38 => "dload_0",
+    Dload1 = 39 => "dload_1",
+    Dload2 = 40 => "dload_2",
+    Dload3 = 41 => "dload_3",
+    Aload0 = 42 => "aload_0",
+    Aload1 = 43 => "aload_1",
+    Aload2 = 44 => "aload_2",
+    Aload3 = 45 => "aload_3",
+    Iaload = 46 => "iaload",
+    Laload = 47 => "laload",
+    Faload = 48 => "faload",
+    Daload = 49 => "daload",
+    Aaload = 50 => "aaload",
+    Baload = 51 => "baload",
+    Caload = 52 => "caload",
+    Saload = 53 => "saload",
+    Istore = 54 => "istore",
+    Lstore = 55 => "lstore",
+    Fstore = 56 => "fstore",
+    Dstore = 57 => "dstore",
+    Astore = 58 => "astore",
+    Istore0 = 59 => "istore_0",
+    Istore1 = 60 => "istore_1",
+    Istore2 = 61 => "istore_2",
+    Istore3 = 62 => "istore_3",
+    Lstore0 = 63 => "lstore_0",
+    Lstore1 = 64 => "lstore_1",
+    Lstore2 = 65 => "lstore_2",
+    Lstore3 = 66 => "lstore_3",
+    Fstore0 = 67 => "fstore_0",
+    Fstore1 = 68 => "fstore_1",
+    Fstore2 = 69 => "fstore_2",
+    Fstore3 = 70 => "fstore_3",
+    Dstore0 = 71 => "dstore_0",
+    Dstore1 = 72 => "dstore_1",
+    Dstore2 = 73 => "dstore_2",
+    Dstore3 = 74 => "dstore_3",
+    Astore0 = 75 => "astore_0",
+    Astore1 = 76 => "astore_1",
+    Astore2 = 77 => "astore_2",
+    Astore3 = 78 => "astore_3",
+    Iastore = 79 => "iastore",
+    Lastore = 80 => "lastore",
+    Fastore = 81 => "fastore",
+    Dastore = 82 => "dastore",
+    Aastore = 83 => "aastore",
+    Bastore = 84 => "bastore",
+    Castore = 85 => "castore",
+    Sastore = 86 => "sastore",
+    Pop = 87 => "pop",
+    Pop2 = 88 => "pop2",
+    Dup = 89 => "dup",
+    DupX1 = 90 => "dup_x1",
+    DupX2 = 91 => "dup_x2",
+    Dup2 = 92 => "dup2",
+    Dup2X1 = 93 => "dup2_x1",
+    Dup2X2 = 94 => "dup2_x2",
+    Swap = 95 => "swap",
+    Iadd = 96 => "iadd",
+    Ladd = 97 => "ladd",
+    Fadd = 98 => "fadd",
+    Dadd = 99 => "dadd",
+    Isub = 100 => "isub",
+    Lsub = 101 => "lsub",
+    Fsub = 102 => "fsub",
+    Dsub = 103 => "dsub",
+    Imul = 104 => "imul",
+    Lmul = 105 => "lmul",
+    Fmul = 106 => "fmul",
+    Dmul = 107 => "dmul",
+    Idiv = 108 => "idiv",
+    Ldiv = 109 => "ldiv",
+    Fdiv = 110 => "fdiv",
+    Ddiv = 111 => "ddiv",
+    Irem = 112 => "irem",
+    Lrem = 113 => "lrem",
+    Frem = 114 => "frem",
+    Drem = 115 => "drem",
+    Ineg = 116 => "ineg",
+    Lneg = 117 => "lneg",
+    Fneg = 118 => "fneg",
+    Dneg = 119 => "dneg",
+    Ishl = 120 => "ishl",
+    Lshl = 121 => "lshl",
+    Ishr = 122 => "ishr",
+    Lshr = 123 => "lshr",
+    Iushr = 124 => "iushr",
+    Lushr = 125 => "lushr",
+    Iand = 126 => "iand",
+    Land = 127 => "land",
+    Ior = 128 => "ior",
+    Lor = 129 => "lor",
+    Ixor = 130 => "ixor",
+    Lxor = 131 => "lxor",
+    Iinc = 132 => "iinc",
+    I2l = 133 => "i2l",
+    I2f = 134 => "i2f",
+    I2d = 135 => "i2d",
+    L2i = 136 => "l2i",
+    L2f = 137 => "l2f",
+    L2d = 138 => "l2d",
+    F2i = 139 => "f2i",
+    F2l = 140 => "f2l",
+    F2d = 141 => "f2d",
+    D2i = 142 => "d2i",
+    D2l = 143 => "d2l",
+    D2f = 144 => "d2f",
+    I2b = 145 => "i2b",
+    I2c = 146 => "i2c",
+    I2s = 147 => "i2s",
+    Lcmp = 148 => "lcmp",
+    Fcmpl = 149 => "fcmpl",
+    Fcmpg = 150 => "fcmpg",
+    Dcmpl = 151 => "dcmpl",
+    Dcmpg = 152 => "dcmpg",
+    Ifeq = 153 => "ifeq",
+    Ifne = 154 => "ifne",
+    Iflt = 155 => "iflt",
+    Ifge = 156 => "ifge",
+    Ifgt = 157 => "ifgt",
+    Ifle = 158 => "ifle",
+    IfIcmpeq = 159 => "if_icmpeq",
+    IfIcmpne = 160 => "if_icmpne",
+    IfIcmplt = 161 => "if_icmplt",
+    IfIcmpge = 162 => "if_icmpge",
+    IfIcmpgt = 163 => "if_icmpgt",
+    IfIcmple = 164 => "if_icmple",
+    IfAcmpeq = 165 => "if_acmpeq",
+    IfAcmpne = 166 => "if_acmpne",
+    Goto = 167 => "goto",
+    Jsr = 168 => "jsr",
+    Ret = 169 => "ret",
+    Tableswitch = 170 => "tableswitch",
+    Lookupswitch = 171 => "lookupswitch",
+    Ireturn = 172 => "ireturn",
+    Lreturn = 173 => "lreturn",
+    Freturn = 174 => "freturn",
+    Dreturn = 175 => "dreturn",
+    Areturn = 176 => "areturn",
+    Return = 177 => "return",
+    Getstatic = 178 => "getstatic",
+    Putstatic = 179 => "putstatic",
+    Getfield = 180 => "getfield",
+    Putfield = 181 => "putfield",
+    Invokevirtual = 182 => "invokevirtual",
+    Invokespecial = 183 => "invokespecial",
+    Invokestatic = 184 => "invokestatic",
+    Invokeinterface = 185 => "invokeinterface",
+    Invokedynamic = 186 => "invokedynamic",
+    New = 187 => "new",
+    Newarray = 188 => "newarray",
+    Anewarray = 189 => "anewarray",
+    Arraylength = 190 => "arraylength",
+    Athrow = 191 => "athrow",
+    Checkcast = 192 => "checkcast",
+    Instanceof = 193 => "instanceof",
+    Monitorenter = 194 => "monitorenter",
+    Monitorexit = 195 => "monitorexit",
+    Wide = 196 => "wide",
+    Multianewarray = 197 => "multianewarray",
+    Ifnull = 198 => "ifnull",
+    Ifnonnull = 199 => "ifnonnull",
+    GotoW = 200 => "goto_w",
+    JsrW = 201 => "jsr_w",
+}
+
+/// The operand(s) of an instruction.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Operand {
+    None,
+    /// The immediate value of `bipush`.
+    Byte(i8),
+    /// The immediate value of `sipush`.
+    Short(i16),
+    /// An index into the constant pool.
+    ConstantPool(u16),
+    /// An index into the local variable array.
+    Local(u16),
+    Iinc {
+        index: u16,
+        value: i16,
+    },
+    /// The absolute offset of a branch target within the code.
+    Branch(u32),
+    /// The array type code of `newarray`.
+    ArrayType(u8),
+    InvokeInterface {
+        index: u16,
+        count: u8,
+    },
+    MultiANewArray {
+        index: u16,
+        dimensions: u8,
+    },
+    TableSwitch {
+        default: u32,
+        low: i32,
+        high: i32,
+        targets: Vec<u32>,
+    },
+    LookupSwitch {
+        default: u32,
+        pairs: Vec<(i32, u32)>,
+    },
+}
+
+/// A single decoded instruction.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Instruction {
+    /// The offset of the instruction within the code.
+    pub offset: u32,
+    pub opcode: Opcode,
+    pub operand: Operand,
+    /// Whether the instruction was prefixed with `wide`, giving it 16-bit operands.
+    pub wide: bool,
+}
+
+impl Instruction {
+    /// The number of bytes this instruction takes in the code, which for switches depends on its offset.
+    pub fn length(&self) -> usize {
+        // Switch operands start at the next offset that is a multiple of four.
+        let padding = (4 - (self.offset as usize + 1) % 4) % 4;
+        match (&self.operand, self.wide) {
+            (Operand::Iinc { .. }, true) => 6,
+            (_, true) => 4,
+            (Operand::TableSwitch { targets, .. }, false) => 1 + padding + 12 + 4 * targets.len(),
+            (Operand::LookupSwitch { pairs, .. }, false) => 1 + padding + 8 + 8 * pairs.len(),
+            (Operand::None, false) => 1,
+            (Operand::Byte(_) | Operand::Local(_) | Operand::ArrayType(_), false) => 2,
+            (Operand::ConstantPool(_), false) => match self.opcode {
+                Opcode::Ldc => 2,
+                Opcode::Invokedynamic => 5,
+                _ => 3,
+            },
+            (Operand::Short(_) | Operand::Iinc { .. }, false) => 3,
+            (Operand::Branch(_), false) => match self.opcode {
+                Opcode::GotoW | Opcode::JsrW => 5,
+                _ => 3,
+            },
+            (Operand::MultiANewArray { .. }, false) => 4,
+            (Operand::InvokeInterface { .. }, false) => 5,
+        }
+    }
+
+    /// The offsets of all branch targets of this instruction, including switch defaults.
+    pub fn branch_targets(&self) -> Vec<u32> {
+        match &self.operand {
+            Operand::Branch(target) => vec![*target],
+            Operand::TableSwitch {
+                default, targets, ..
+            } => {
+                let mut result = vec![*default];
+                result.extend(targets);
+                result
+            }
+            Operand::LookupSwitch { default, pairs } => {
+                let mut result = vec![*default];
+                result.extend(pairs.iter().map(|(_, target)| *target));
+                result
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// The array type code of `newarray` for an element type, the counterpart of [`array_type_name`].
+pub fn array_type_code(name: &str) -> Option<u8> {
+    (4..=11).find(|array_type| array_type_name(*array_type) == Some(name))
+}
+
+/// The element type of an array created with `newarray`.
+pub fn array_type_name(array_type: u8) -> Option<&'static str> {
+    match array_type {
+        4 => Some("boolean"),
+        5 => Some("char"),
+        6 => Some("float"),
+        7 => Some("double"),
+        8 => Some("byte"),
+        9 => Some("short"),
+        10 => Some("int"),
+        11 => Some("long"),
+        _ => None,
+    }
+}
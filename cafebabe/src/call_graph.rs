@@ -0,0 +1,956 @@
+//! Whole-program call graphs over a class path: the methods that are reachable from entry points such as `main`
+//! methods, and the calls between them, by class hierarchy analysis or rapid type analysis.
+//!
+//! The classes of jimages and jmod files count as the JDK, a library whose code isn't analysed. Calls into the JDK
+//! and into classes that aren't on the class path end at the method they resolve to. The library may in turn call
+//! back the methods of instantiated classes that override its methods, so these are reachable too. For a supertype
+//! that isn't on the class path, it isn't known which methods it has, and all instance methods count as overriding
+//! it, except for `java/lang/Object` whose methods are known: put the JDK on the class path for a precise graph.
+//!
+//! Lambda expressions and method references call their implementation method, which `invokedynamic` passes to
+//! `LambdaMetafactory`. Other bootstrap methods are called from their call sites, except for those of the JDK.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::result::Result;
+use std::sync::Arc;
+
+use log::{debug, error};
+
+use crate::archive::ArchiveError;
+use crate::bootstrap::{BootstrapArgument, BootstrapKind, CallSite, MethodHandleInfo};
+use crate::bytecode::{Opcode, Operand, decode};
+use crate::class_path::ClassPath;
+use crate::constant_pool::ConstantPoolEntry;
+use crate::constant_pool::types::ReferenceKind;
+use crate::hierarchy::ClassHierarchy;
+use crate::types::{AccessFlags, ClassFile, MethodAccessFlags, MethodInfo};
+
+mod types;
+
+pub use types::{Algorithm, CallEdge, CallKind, MethodId};
+
+const OBJECT: &str = "java/lang/Object";
+const CONSTRUCTOR: &str = "<init>";
+const STATIC_INITIALIZER: &str = "<clinit>";
+
+/// The methods of `java/lang/Object` that subclasses may override, for when it isn't on the class path.
+const OBJECT_METHODS: [(&str, &str); 5] = [
+    ("equals", "(Ljava/lang/Object;)Z"),
+    ("hashCode", "()I"),
+    ("toString", "()Ljava/lang/String;"),
+    ("clone", "()Ljava/lang/Object;"),
+    ("finalize", "()V"),
+];
+
+/// A class as the call graph sees it.
+#[derive(Clone)]
+enum Class {
+    /// A class whose code is analysed.
+    Application(Arc<ClassFile>),
+    /// A class of the JDK.
+    Library(Arc<ClassFile>),
+    /// A class that isn't on the class path or can't be parsed.
+    Missing,
+}
+
+impl Class {
+    fn class_file(&self) -> Option<&ClassFile> {
+        match self {
+            Class::Application(class_file) | Class::Library(class_file) => Some(class_file),
+            Class::Missing => None,
+        }
+    }
+}
+
+/// A call between the methods of a call graph, by their positions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Call {
+    caller: u32,
+    callee: u32,
+    kind: CallKind,
+    offset: u32,
+}
+
+/// The methods that a virtual call to a method may dispatch to, and the call sites that make it.
+#[derive(Default)]
+struct Dispatch {
+    targets: BTreeSet<u32>,
+    /// The callers, offsets and kinds of the call sites, kept for rapid type analysis to add the targets of classes
+    /// that are instantiated later.
+    sites: Vec<(u32, u32, CallKind)>,
+}
+
+struct Builder<'a> {
+    class_path: &'a ClassPath,
+    hierarchy: &'a ClassHierarchy,
+    algorithm: Algorithm,
+    /// The reachable methods in the order they were reached, which gives their positions.
+    methods: Vec<MethodId>,
+    positions: HashMap<MethodId, u32>,
+    external: Vec<bool>,
+    calls: Vec<Call>,
+    known_calls: HashSet<Call>,
+    pending: Vec<u32>,
+    classes: HashMap<String, Class>,
+    /// Whether classes are concrete classes of the application, whose objects virtual calls dispatch on.
+    receiver_classes: HashMap<String, bool>,
+    initialized: HashSet<String>,
+    instantiated_classes: BTreeSet<String>,
+    missing_classes: BTreeSet<String>,
+    invalid_classes: BTreeSet<String>,
+    /// The methods that the methods named by instructions resolve to, with their access flags.
+    resolutions: HashMap<MethodId, Option<(MethodId, MethodAccessFlags)>>,
+    /// The virtual calls that reachable code makes, by the method they name.
+    dispatches: HashMap<MethodId, Dispatch>,
+    /// The methods of `dispatches` by the class they are named with.
+    dispatched_classes: HashMap<String, Vec<MethodId>>,
+    /// The classes whose objects virtual calls to the methods of a class dispatch on, by the class: all concrete
+    /// subclasses for class hierarchy analysis, as they are asked for, and the instantiated ones for rapid type
+    /// analysis, as they are instantiated.
+    receivers: HashMap<String, Vec<String>>,
+}
+
+impl Builder<'_> {
+    fn class(&mut self, class_name: &str) -> Result<Class, ArchiveError> {
+        if let Some(class) = self.classes.get(class_name) {
+            return Ok(class.clone());
+        }
+        let class = match self.class_path.find_class(class_name) {
+            Ok(Some(class_file)) => match self.class_path.locate(class_name)? {
+                Some(entry) if entry.source.is_jdk() => Class::Library(class_file),
+                _ => Class::Application(class_file),
+            },
+            Ok(None) => {
+                self.missing_classes.insert(class_name.to_string());
+                Class::Missing
+            }
+            Err(ArchiveError::InvalidClass(_, error)) => {
+                error!("can't parse class; class_name={class_name}, error={error}");
+                self.invalid_classes.insert(class_name.to_string());
+                Class::Missing
+            }
+            Err(error) => return Err(error),
+        };
+        self.classes.insert(class_name.to_string(), class.clone());
+        Ok(class)
+    }
+
+    fn is_application(&mut self, class_name: &str) -> Result<bool, ArchiveError> {
+        Ok(matches!(self.class(class_name)?, Class::Application(_)))
+    }
+
+    /// Whether a class is a concrete class of the application. Tells from the hierarchy and the class path entry,
+    /// without parsing the class, as class hierarchy analysis asks for all subclasses of `java/lang/Object`.
+    fn is_receiver(&mut self, class_name: &str) -> Result<bool, ArchiveError> {
+        if let Some(is_receiver) = self.receiver_classes.get(class_name) {
+            return Ok(*is_receiver);
+        }
+        let is_concrete = self.hierarchy.get(class_name).is_some_and(|node| {
+            !node
+                .access_flags
+                .intersects(AccessFlags::ACC_INTERFACE | AccessFlags::ACC_ABSTRACT)
+        });
+        let is_receiver = is_concrete
+            && self
+                .class_path
+                .locate(class_name)?
+                .is_some_and(|entry| !entry.source.is_jdk());
+        self.receiver_classes
+            .insert(class_name.to_string(), is_receiver);
+        Ok(is_receiver)
+    }
+
+    /// A class followed by its superclasses, as far as they are indexed.
+    fn class_and_superclasses(&self, class_name: &str) -> Vec<String> {
+        std::iter::once(class_name)
+            .chain(self.hierarchy.superclasses(class_name))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The supertypes of a class, always with `java/lang/Object`.
+    fn supertypes(&self, class_name: &str) -> Vec<String> {
+        let mut supertypes: Vec<String> = self
+            .hierarchy
+            .supertypes(class_name)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        if !supertypes.iter().any(|supertype| supertype == OBJECT) {
+            supertypes.push(OBJECT.to_string());
+        }
+        supertypes
+    }
+
+    /// Marks a method reachable, and queues it to be analysed if its class is. Gives its position.
+    fn reach(&mut self, method: MethodId) -> Result<u32, ArchiveError> {
+        if let Some(position) = self.positions.get(&method) {
+            return Ok(*position);
+        }
+        let position = self.methods.len() as u32;
+        let is_application = self.is_application(&method.class_name)?;
+        if is_application {
+            self.pending.push(position);
+        }
+        self.external.push(!is_application);
+        self.positions.insert(method.clone(), position);
+        self.methods.push(method);
+        Ok(position)
+    }
+
+    fn add_call(&mut self, caller: u32, callee: u32, kind: CallKind, offset: u32) {
+        let call = Call {
+            caller,
+            callee,
+            kind,
+            offset,
+        };
+        if self.known_calls.insert(call) {
+            self.calls.push(call);
+        }
+    }
+
+    /// Resolves a method that an instruction names like the Java Virtual Machine: in the class and its superclasses,
+    /// and then in its superinterfaces, preferring default methods. A missing class ends the search with the method
+    /// named in it, as it may declare the method.
+    fn resolve(
+        &mut self,
+        method: &MethodId,
+    ) -> Result<Option<(MethodId, MethodAccessFlags)>, ArchiveError> {
+        if let Some(resolution) = self.resolutions.get(method) {
+            return Ok(resolution.clone());
+        }
+        let resolution =
+            self.resolve_uncached(&method.class_name, &method.name, &method.descriptor)?;
+        self.resolutions.insert(method.clone(), resolution.clone());
+        Ok(resolution)
+    }
+
+    fn resolve_uncached(
+        &mut self,
+        class_name: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Option<(MethodId, MethodAccessFlags)>, ArchiveError> {
+        for current in self.class_and_superclasses(class_name) {
+            let class = self.class(&current)?;
+            let Some(class_file) = class.class_file() else {
+                let method = MethodId::new(current, name, descriptor);
+                return Ok(Some((method, MethodAccessFlags::empty())));
+            };
+            if let Some(declared) = class_file.declared_method(name, descriptor) {
+                let method = MethodId::new(current, name, descriptor);
+                return Ok(Some((method, declared.access_flags)));
+            }
+        }
+        let mut found = None;
+        for interface in self.supertypes(class_name) {
+            if self.hierarchy.is_interface(&interface) == Some(false) {
+                continue;
+            }
+            let class = self.class(&interface)?;
+            let Some(class_file) = class.class_file() else {
+                found.get_or_insert((
+                    MethodId::new(interface, name, descriptor),
+                    MethodAccessFlags::empty(),
+                ));
+                continue;
+            };
+            if let Some(declared) = class_file.declared_method(name, descriptor) {
+                let flags = declared.access_flags;
+                let method = MethodId::new(interface, name, descriptor);
+                if !flags.contains(MethodAccessFlags::ACC_ABSTRACT) {
+                    return Ok(Some((method, flags)));
+                }
+                found.get_or_insert((method, flags));
+            }
+        }
+        Ok(found)
+    }
+
+    /// The method that a virtual call dispatches to for an object of a class: the nearest implementation in the
+    /// class and its superclasses, or else a default method of its superinterfaces.
+    fn select(
+        &mut self,
+        class_name: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Option<MethodId>, ArchiveError> {
+        for current in self.class_and_superclasses(class_name) {
+            let class = self.class(&current)?;
+            let Some(class_file) = class.class_file() else {
+                return Ok(Some(MethodId::new(current, name, descriptor)));
+            };
+            let Some(method) = class_file.declared_method(name, descriptor) else {
+                continue;
+            };
+            let flags = method.access_flags;
+            let inherited =
+                current == class_name || !flags.contains(MethodAccessFlags::ACC_PRIVATE);
+            if inherited
+                && !flags
+                    .intersects(MethodAccessFlags::ACC_STATIC | MethodAccessFlags::ACC_ABSTRACT)
+            {
+                return Ok(Some(MethodId::new(current, name, descriptor)));
+            }
+        }
+        for interface in self.supertypes(class_name) {
+            if self.hierarchy.is_interface(&interface) != Some(true) {
+                continue;
+            }
+            let class = self.class(&interface)?;
+            let is_default = class
+                .class_file()
+                .and_then(|class_file| class_file.declared_method(name, descriptor))
+                .is_some_and(|method| {
+                    !method.access_flags.intersects(
+                        MethodAccessFlags::ACC_STATIC
+                            | MethodAccessFlags::ACC_ABSTRACT
+                            | MethodAccessFlags::ACC_PRIVATE,
+                    )
+                });
+            if is_default {
+                return Ok(Some(MethodId::new(interface, name, descriptor)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The classes whose objects a virtual call to a method of a class may currently dispatch on.
+    fn receivers(&mut self, class_name: &str) -> Result<Vec<String>, ArchiveError> {
+        if self.algorithm == Algorithm::ClassHierarchy && !self.receivers.contains_key(class_name) {
+            let mut receivers = Vec::new();
+            for candidate in std::iter::once(class_name).chain(self.hierarchy.subtypes(class_name))
+            {
+                if self.is_receiver(candidate)? {
+                    receivers.push(candidate.to_string());
+                }
+            }
+            self.receivers.insert(class_name.to_string(), receivers);
+        }
+        Ok(self.receivers.get(class_name).cloned().unwrap_or_default())
+    }
+
+    fn call_virtual(
+        &mut self,
+        caller: u32,
+        offset: u32,
+        kind: CallKind,
+        method: MethodId,
+    ) -> Result<(), ArchiveError> {
+        match self.resolve(&method)? {
+            // Private methods are called directly, like by `invokespecial`.
+            Some((resolved, flags)) if flags.contains(MethodAccessFlags::ACC_PRIVATE) => {
+                let callee = self.reach(resolved)?;
+                self.add_call(caller, callee, kind, offset);
+                return Ok(());
+            }
+            // The implementations of the library aren't known, but the method it resolves to stands for them.
+            Some((resolved, _)) if !self.is_application(&resolved.class_name)? => {
+                let callee = self.reach(resolved)?;
+                self.add_call(caller, callee, kind, offset);
+            }
+            Some(_) => {}
+            None => debug!("can't resolve method; method={method}"),
+        }
+
+        if !self.dispatches.contains_key(&method) {
+            let mut dispatch = Dispatch::default();
+            for receiver in self.receivers(&method.class_name)? {
+                if let Some(target) = self.select(&receiver, &method.name, &method.descriptor)? {
+                    dispatch.targets.insert(self.reach(target)?);
+                }
+            }
+            self.dispatches.insert(method.clone(), dispatch);
+            self.dispatched_classes
+                .entry(method.class_name.clone())
+                .or_default()
+                .push(method.clone());
+        }
+        let dispatch = self
+            .dispatches
+            .get_mut(&method)
+            .expect("dispatch was just added");
+        if self.algorithm == Algorithm::RapidType {
+            dispatch.sites.push((caller, offset, kind));
+        }
+        let targets: Vec<u32> = dispatch.targets.iter().copied().collect();
+        for target in targets {
+            self.add_call(caller, target, kind, offset);
+        }
+        Ok(())
+    }
+
+    /// Adds a call that doesn't dispatch, to the method it resolves to.
+    fn call_direct(
+        &mut self,
+        caller: u32,
+        offset: u32,
+        kind: CallKind,
+        method: MethodId,
+    ) -> Result<(), ArchiveError> {
+        let callee = match self.resolve(&method)? {
+            Some((resolved, _)) => resolved,
+            None if !self.is_application(&method.class_name)? => method,
+            None => {
+                debug!("can't resolve method; method={method}");
+                return Ok(());
+            }
+        };
+        let callee = self.reach(callee)?;
+        self.add_call(caller, callee, kind, offset);
+        Ok(())
+    }
+
+    /// Runs the static initializers of a class and its superclasses, if they haven't run yet.
+    fn initialize(
+        &mut self,
+        caller: u32,
+        offset: u32,
+        class_name: &str,
+    ) -> Result<(), ArchiveError> {
+        if !self.initialized.insert(class_name.to_string()) {
+            return Ok(());
+        }
+        for current in self.class_and_superclasses(class_name) {
+            if let Class::Application(class_file) = self.class(&current)?
+                && class_file
+                    .declared_method(STATIC_INITIALIZER, "()V")
+                    .is_some()
+            {
+                let initializer = self.reach(MethodId::new(current, STATIC_INITIALIZER, "()V"))?;
+                self.add_call(caller, initializer, CallKind::Initialization, offset);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a method overrides a method of the JDK or of a missing class, among the supertypes of its class.
+    fn overrides_library(
+        &mut self,
+        supertypes: &[String],
+        name: &str,
+        descriptor: &str,
+    ) -> Result<bool, ArchiveError> {
+        for supertype in supertypes {
+            let overrides = match self.class(supertype)? {
+                Class::Application(_) => false,
+                Class::Library(class_file) => class_file
+                    .declared_method(name, descriptor)
+                    .is_some_and(|method| {
+                        !method.access_flags.intersects(
+                            MethodAccessFlags::ACC_STATIC | MethodAccessFlags::ACC_PRIVATE,
+                        )
+                    }),
+                Class::Missing if supertype == OBJECT => {
+                    OBJECT_METHODS.contains(&(name, descriptor))
+                }
+                Class::Missing => true,
+            };
+            if overrides {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Records that reachable code creates objects of a class: virtual calls may now dispatch on them, and the
+    /// library may call back the methods that override its own.
+    fn instantiate(
+        &mut self,
+        caller: u32,
+        offset: u32,
+        class_name: &str,
+    ) -> Result<(), ArchiveError> {
+        if !self.instantiated_classes.insert(class_name.to_string())
+            || !self.is_receiver(class_name)?
+        {
+            return Ok(());
+        }
+        let supertypes = self.supertypes(class_name);
+
+        if self.algorithm == Algorithm::RapidType {
+            for supertype in
+                std::iter::once(class_name).chain(supertypes.iter().map(String::as_str))
+            {
+                self.receivers
+                    .entry(supertype.to_string())
+                    .or_default()
+                    .push(class_name.to_string());
+                let methods = self
+                    .dispatched_classes
+                    .get(supertype)
+                    .cloned()
+                    .unwrap_or_default();
+                for method in methods {
+                    let Some(target) = self.select(class_name, &method.name, &method.descriptor)?
+                    else {
+                        continue;
+                    };
+                    let target = self.reach(target)?;
+                    let dispatch = self
+                        .dispatches
+                        .get_mut(&method)
+                        .expect("dispatched methods are indexed by class");
+                    if dispatch.targets.insert(target) {
+                        for (site_caller, site_offset, kind) in dispatch.sites.clone() {
+                            self.add_call(site_caller, target, kind, site_offset);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut overridden = HashSet::new();
+        for current in self.class_and_superclasses(class_name) {
+            let Class::Application(class_file) = self.class(&current)? else {
+                break;
+            };
+            let constant_pool = &class_file.constant_pool;
+            for method in &class_file.methods {
+                let (Ok(name), Ok(descriptor)) =
+                    (method.name(constant_pool), method.descriptor(constant_pool))
+                else {
+                    continue;
+                };
+                if method
+                    .access_flags
+                    .intersects(MethodAccessFlags::ACC_STATIC | MethodAccessFlags::ACC_PRIVATE)
+                    || name.starts_with('<')
+                    || !overridden.insert((name.clone(), descriptor.clone()))
+                    || !self.overrides_library(&supertypes, name, descriptor)?
+                {
+                    continue;
+                }
+                if let Some(target) = self.select(class_name, name, descriptor)? {
+                    let callee = self.reach(target)?;
+                    self.add_call(caller, callee, CallKind::Callback, offset);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Follows a method handle that a call site passes to its bootstrap method or that is a bootstrap method.
+    fn method_handle(
+        &mut self,
+        caller: u32,
+        offset: u32,
+        kind: CallKind,
+        handle: &MethodHandleInfo,
+    ) -> Result<(), ArchiveError> {
+        let method = MethodId::new(&handle.owner, &handle.name, &handle.descriptor);
+        match handle.reference_kind {
+            ReferenceKind::InvokeVirtual | ReferenceKind::InvokeInterface => {
+                return self.call_virtual(caller, offset, kind, method);
+            }
+            ReferenceKind::NewInvokeSpecial => {
+                self.instantiate(caller, offset, &handle.owner)?;
+                self.initialize(caller, offset, &handle.owner)?;
+            }
+            ReferenceKind::InvokeStatic => self.initialize(caller, offset, &handle.owner)?,
+            ReferenceKind::InvokeSpecial => {}
+            ReferenceKind::GetStatic | ReferenceKind::PutStatic => {
+                return self.initialize(caller, offset, &handle.owner);
+            }
+            ReferenceKind::GetField | ReferenceKind::PutField => return Ok(()),
+        }
+        self.call_direct(caller, offset, kind, method)
+    }
+
+    fn call_site(
+        &mut self,
+        caller: u32,
+        offset: u32,
+        call_site: &CallSite,
+    ) -> Result<(), ArchiveError> {
+        match call_site.kind() {
+            BootstrapKind::LambdaMetafactory => match call_site.arguments.get(1) {
+                Some(BootstrapArgument::MethodHandle(handle)) => {
+                    self.method_handle(caller, offset, CallKind::Lambda, handle)
+                }
+                _ => Ok(()),
+            },
+            BootstrapKind::StringConcatFactory | BootstrapKind::ObjectMethods => Ok(()),
+            BootstrapKind::Other => self.method_handle(
+                caller,
+                offset,
+                CallKind::Bootstrap,
+                &call_site.bootstrap_method,
+            ),
+        }
+    }
+
+    /// Adds the calls that the code of a method makes.
+    fn analyse(&mut self, position: u32) -> Result<(), ArchiveError> {
+        let method = self.methods[position as usize].clone();
+        let Class::Application(class_file) = self.class(&method.class_name)? else {
+            return Ok(());
+        };
+        let Some(code) = class_file
+            .declared_method(&method.name, &method.descriptor)
+            .and_then(MethodInfo::code)
+        else {
+            return Ok(());
+        };
+        let invalid =
+            |error| ArchiveError::InvalidClass(format!("{}.class", method.class_name), error);
+        let constant_pool = &class_file.constant_pool;
+        for instruction in decode(&code.code).map_err(invalid)? {
+            let offset = instruction.offset;
+            let index = match instruction.operand {
+                Operand::ConstantPool(index) | Operand::InvokeInterface { index, .. } => index,
+                _ => continue,
+            };
+            let member = || {
+                constant_pool
+                    .member_ref(usize::from(index))
+                    .map(|(owner, name, descriptor)| {
+                        // Arrays have the methods of `java/lang/Object`.
+                        let owner = match owner.starts_with('[') {
+                            true => OBJECT,
+                            false => owner,
+                        };
+                        MethodId::new(owner, name, descriptor)
+                    })
+                    .map_err(invalid)
+            };
+            match instruction.opcode {
+                Opcode::New => {
+                    let class_name = constant_pool
+                        .class_name(usize::from(index))
+                        .map_err(invalid)?;
+                    self.instantiate(position, offset, class_name)?;
+                    self.initialize(position, offset, class_name)?;
+                }
+                Opcode::Getstatic | Opcode::Putstatic => {
+                    self.initialize(position, offset, &member()?.class_name)?;
+                }
+                Opcode::Invokestatic => {
+                    let callee = member()?;
+                    self.initialize(position, offset, &callee.class_name)?;
+                    self.call_direct(position, offset, CallKind::Static, callee)?;
+                }
+                Opcode::Invokespecial => {
+                    self.call_direct(position, offset, CallKind::Special, member()?)?;
+                }
+                Opcode::Invokevirtual => {
+                    self.call_virtual(position, offset, CallKind::Virtual, member()?)?;
+                }
+                Opcode::Invokeinterface => {
+                    self.call_virtual(position, offset, CallKind::Interface, member()?)?;
+                }
+                Opcode::Invokedynamic => {
+                    let call_site = class_file.call_site(index).map_err(invalid)?;
+                    self.call_site(position, offset, &call_site)?;
+                }
+                Opcode::Ldc | Opcode::LdcW | Opcode::Ldc2W => {
+                    let entry = constant_pool.checked_entry(index).map_err(invalid)?;
+                    if matches!(entry, ConstantPoolEntry::Dynamic(_)) {
+                        let call_site = class_file.call_site(index).map_err(invalid)?;
+                        self.call_site(position, offset, &call_site)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn build(mut self, entry_points: &[MethodId]) -> Result<CallGraph, ArchiveError> {
+        for entry_point in entry_points {
+            let position = self.reach(entry_point.clone())?;
+            // The object of a constructor is created before it is called, and the class of an entry point is
+            // initialized.
+            if entry_point.name == CONSTRUCTOR {
+                self.instantiate(position, 0, &entry_point.class_name)?;
+            }
+            self.initialize(position, 0, &entry_point.class_name)?;
+        }
+        while let Some(position) = self.pending.pop() {
+            match self.analyse(position) {
+                Err(ArchiveError::InvalidClass(_, error)) => {
+                    let method = &self.methods[position as usize];
+                    error!("can't analyse method; method={method}, error={error}");
+                    self.invalid_classes.insert(method.class_name.clone());
+                }
+                result => result?,
+            }
+        }
+
+        // Sort the methods, and renumber the calls to match.
+        let mut order: Vec<u32> = (0..self.methods.len() as u32).collect();
+        order.sort_by(|first, second| {
+            self.methods[*first as usize].cmp(&self.methods[*second as usize])
+        });
+        let mut renumbered = vec![0; order.len()];
+        for (position, previous) in order.iter().enumerate() {
+            renumbered[*previous as usize] = position as u32;
+        }
+        let mut methods: Vec<Option<MethodId>> = self.methods.into_iter().map(Some).collect();
+        Ok(CallGraph {
+            entry_points: entry_points.to_vec(),
+            external: order
+                .iter()
+                .map(|previous| self.external[*previous as usize])
+                .collect(),
+            methods: order
+                .iter()
+                .filter_map(|previous| methods[*previous as usize].take())
+                .collect(),
+            calls: self
+                .calls
+                .into_iter()
+                .map(|call| Call {
+                    caller: renumbered[call.caller as usize],
+                    callee: renumbered[call.callee as usize],
+                    ..call
+                })
+                .collect(),
+            instantiated_classes: self.instantiated_classes,
+            missing_classes: self.missing_classes,
+            invalid_classes: self.invalid_classes,
+        })
+    }
+}
+
+/// The entry points of a class to start a program: `public static void main(String[])`, or an instance or
+/// parameterless `main` method that the launcher of Java 25 and later accepts.
+fn main_method(class_file: &ClassFile, class_name: &str) -> Vec<MethodId> {
+    for descriptor in ["([Ljava/lang/String;)V", "()V"] {
+        let Some(method) = class_file.declared_method("main", descriptor) else {
+            continue;
+        };
+        let flags = method.access_flags;
+        if flags.intersects(MethodAccessFlags::ACC_PRIVATE | MethodAccessFlags::ACC_ABSTRACT) {
+            continue;
+        }
+        let main = MethodId::new(class_name, "main", descriptor);
+        if flags.contains(MethodAccessFlags::ACC_STATIC) {
+            return vec![main];
+        }
+        // An instance `main` method is called on an object that the constructor without parameters creates.
+        return match class_file.declared_method(CONSTRUCTOR, "()V") {
+            Some(_) => vec![MethodId::new(class_name, CONSTRUCTOR, "()V"), main],
+            None => Vec::new(),
+        };
+    }
+    Vec::new()
+}
+
+/// The methods that are reachable from a set of entry points, and the calls between them.
+#[derive(Debug)]
+pub struct CallGraph {
+    entry_points: Vec<MethodId>,
+    /// All reachable methods, sorted.
+    methods: Vec<MethodId>,
+    /// Whether the code of each method isn't analysed, as it belongs to the JDK or to a missing class.
+    external: Vec<bool>,
+    calls: Vec<Call>,
+    instantiated_classes: BTreeSet<String>,
+    missing_classes: BTreeSet<String>,
+    invalid_classes: BTreeSet<String>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of the methods reachable from entry points, with the classes of a class path and their
+    /// hierarchy, which must index the same class path. Classes that fail to parse are logged and count as missing,
+    /// see [`CallGraph::invalid_classes`].
+    pub fn build(
+        class_path: &ClassPath,
+        hierarchy: &ClassHierarchy,
+        entry_points: &[MethodId],
+        algorithm: Algorithm,
+    ) -> Result<CallGraph, ArchiveError> {
+        let builder = Builder {
+            class_path,
+            hierarchy,
+            algorithm,
+            methods: Vec::new(),
+            positions: HashMap::new(),
+            external: Vec::new(),
+            calls: Vec::new(),
+            known_calls: HashSet::new(),
+            pending: Vec::new(),
+            classes: HashMap::new(),
+            receiver_classes: HashMap::new(),
+            initialized: HashSet::new(),
+            instantiated_classes: BTreeSet::new(),
+            missing_classes: BTreeSet::new(),
+            invalid_classes: BTreeSet::new(),
+            resolutions: HashMap::new(),
+            dispatches: HashMap::new(),
+            dispatched_classes: HashMap::new(),
+            receivers: HashMap::new(),
+        };
+        builder.build(entry_points)
+    }
+
+    /// The `main` methods of the classes of a class path other than the JDK, sorted by class, to use as entry
+    /// points. An instance `main` method comes after the constructor that creates its object. Classes that fail to
+    /// parse are logged and left out.
+    pub fn main_methods(class_path: &ClassPath) -> Result<Vec<MethodId>, ArchiveError> {
+        let mut main_methods = Vec::new();
+        for class_name in class_path.class_names() {
+            if class_path
+                .locate(class_name)?
+                .is_some_and(|entry| entry.source.is_jdk())
+            {
+                continue;
+            }
+            match class_path.find_class(class_name) {
+                Ok(Some(class_file)) => main_methods.extend(main_method(&class_file, class_name)),
+                Ok(None) => {}
+                Err(ArchiveError::InvalidClass(_, error)) => {
+                    error!("can't parse class; class_name={class_name}, error={error}");
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(main_methods)
+    }
+
+    pub fn entry_points(&self) -> &[MethodId] {
+        &self.entry_points
+    }
+
+    /// All reachable methods, including those of the JDK and of missing classes, sorted.
+    pub fn methods(&self) -> &[MethodId] {
+        &self.methods
+    }
+
+    fn position(&self, method: &MethodId) -> Option<u32> {
+        self.methods
+            .binary_search(method)
+            .ok()
+            .map(|position| position as u32)
+    }
+
+    pub fn is_reachable(&self, method: &MethodId) -> bool {
+        self.position(method).is_some()
+    }
+
+    /// The reachable methods whose code isn't analysed: those of the JDK and of classes that aren't on the class
+    /// path, sorted.
+    pub fn external_methods(&self) -> impl Iterator<Item = &MethodId> {
+        self.methods
+            .iter()
+            .zip(&self.external)
+            .filter(|(_, external)| **external)
+            .map(|(method, _)| method)
+    }
+
+    /// The classes that reachable code instantiates, with `new` or a constructor reference, sorted.
+    pub fn instantiated_classes(&self) -> &BTreeSet<String> {
+        &self.instantiated_classes
+    }
+
+    /// The classes that reachable code names but that aren't on the class path, sorted.
+    pub fn missing_classes(&self) -> &BTreeSet<String> {
+        &self.missing_classes
+    }
+
+    /// The classes that couldn't be parsed or analysed, sorted.
+    pub fn invalid_classes(&self) -> &BTreeSet<String> {
+        &self.invalid_classes
+    }
+
+    fn edge(&self, call: &Call) -> CallEdge<'_> {
+        CallEdge {
+            caller: &self.methods[call.caller as usize],
+            callee: &self.methods[call.callee as usize],
+            kind: call.kind,
+            offset: call.offset,
+        }
+    }
+
+    /// All calls, in the order they were found.
+    pub fn edges(&self) -> impl Iterator<Item = CallEdge<'_>> {
+        self.calls.iter().map(|call| self.edge(call))
+    }
+
+    /// The calls that a method makes.
+    pub fn callees(&self, method: &MethodId) -> impl Iterator<Item = CallEdge<'_>> {
+        let position = self.position(method);
+        self.calls
+            .iter()
+            .filter(move |call| Some(call.caller) == position)
+            .map(|call| self.edge(call))
+    }
+
+    /// The calls of a method.
+    pub fn callers(&self, method: &MethodId) -> impl Iterator<Item = CallEdge<'_>> {
+        let position = self.position(method);
+        self.calls
+            .iter()
+            .filter(move |call| Some(call.callee) == position)
+            .map(|call| self.edge(call))
+    }
+
+    /// The calls of each method, by the position of the caller.
+    fn outgoing_calls(&self) -> Vec<Vec<&Call>> {
+        let mut outgoing = vec![Vec::new(); self.methods.len()];
+        for call in &self.calls {
+            outgoing[call.caller as usize].push(call);
+        }
+        outgoing
+    }
+
+    /// The methods that a method calls directly or through others, including itself, sorted.
+    pub fn reachable_from(&self, method: &MethodId) -> Vec<&MethodId> {
+        let Some(position) = self.position(method) else {
+            return Vec::new();
+        };
+        let outgoing = self.outgoing_calls();
+        let mut reached = vec![false; self.methods.len()];
+        let mut pending = vec![position];
+        while let Some(current) = pending.pop() {
+            if !std::mem::replace(&mut reached[current as usize], true) {
+                pending.extend(outgoing[current as usize].iter().map(|call| call.callee));
+            }
+        }
+        self.methods
+            .iter()
+            .zip(reached)
+            .filter(|(_, reached)| *reached)
+            .map(|(method, _)| method)
+            .collect()
+    }
+
+    /// The shortest chain of calls from an entry point to a method, which shows why the method is reachable. It is
+    /// empty for an entry point, and `None` if the method isn't reachable.
+    pub fn path_to(&self, method: &MethodId) -> Option<Vec<CallEdge<'_>>> {
+        let target = self.position(method)?;
+        let outgoing = self.outgoing_calls();
+        // The call by which each method was first reached, or `None` for the entry points.
+        let mut reached: HashMap<u32, Option<&Call>> = HashMap::new();
+        let mut queue = VecDeque::new();
+        for entry_point in &self.entry_points {
+            if let Some(position) = self.position(entry_point)
+                && reached.insert(position, None).is_none()
+            {
+                queue.push_back(position);
+            }
+        }
+        while let Some(current) = queue.pop_front() {
+            if current == target {
+                let mut path = Vec::new();
+                let mut current = current;
+                while let Some(Some(call)) = reached.get(&current) {
+                    path.push(self.edge(call));
+                    current = call.caller;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for call in &outgoing[current as usize] {
+                if let Entry::Vacant(entry) = reached.entry(call.callee) {
+                    entry.insert(Some(call));
+                    queue.push_back(call.callee);
+                }
+            }
+        }
+        None
+    }
+}
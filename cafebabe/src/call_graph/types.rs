@@ -0,0 +1,74 @@
+use std::fmt;
+
+/// A method by its class, name and descriptor, e.g. `com/acme/Main.main:([Ljava/lang/String;)V`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MethodId {
+    pub class_name: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+impl MethodId {
+    pub fn new(
+        class_name: impl Into<String>,
+        name: impl Into<String>,
+        descriptor: impl Into<String>,
+    ) -> MethodId {
+        MethodId {
+            class_name: class_name.into(),
+            name: name.into(),
+            descriptor: descriptor.into(),
+        }
+    }
+}
+
+impl fmt::Display for MethodId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}:{}", self.class_name, self.name, self.descriptor)
+    }
+}
+
+/// How the methods that a virtual or interface call may dispatch to are found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Algorithm {
+    /// Class hierarchy analysis: the implementations of all concrete subclasses of the class that the call names.
+    ClassHierarchy,
+    /// Rapid type analysis: like class hierarchy analysis, but only for the classes that reachable code instantiates.
+    #[default]
+    RapidType,
+}
+
+/// Why a method calls another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum CallKind {
+    Static,
+    /// Constructors, private methods and methods of a superclass, called by `invokespecial`.
+    Special,
+    Virtual,
+    Interface,
+    /// The implementation of a lambda expression or method reference, bound by `invokedynamic`.
+    Lambda,
+    /// The bootstrap method of an `invokedynamic` call site or a dynamically computed constant, other than the
+    /// bootstrap methods of the JDK for lambdas, string concatenation and records.
+    Bootstrap,
+    /// The static initializer of a class that an instruction may initialize.
+    Initialization,
+    /// A method of an instantiated class that overrides a method of the JDK or of a missing class, which the library
+    /// may call back. The call comes from the method that instantiates the class.
+    Callback,
+}
+
+/// A call from one method to another, at the offset of the instruction that makes it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CallEdge<'a> {
+    pub caller: &'a MethodId,
+    pub callee: &'a MethodId,
+    pub kind: CallKind,
+    pub offset: u32,
+}
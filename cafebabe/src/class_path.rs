@@ -0,0 +1,444 @@
+//! A class path: an ordered list of directories, jars, jmods and jimages in which classes are looked up.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::result::Result;
+use std::sync::{Arc, Mutex};
+
+use log::debug;
+
+use crate::archive::{ArchiveError, JImage, JarFile, JmodFile, ResourceLookup};
+use crate::read_class_data_with_options;
+use crate::types::ClassFile;
+use crate::version::ParseOptions;
+
+#[cfg(feature = "parallel")]
+mod parallel;
+
+/// A directory of classes, such as the output directory of `javac`.
+#[derive(Debug)]
+pub struct Directory {
+    root: PathBuf,
+    /// The names of all files below the root, separated by `/`, as found when the directory was opened.
+    names: Vec<String>,
+}
+
+fn list_files(root: &Path, directory: &Path, names: &mut Vec<String>) -> Result<(), ArchiveError> {
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(root, &path, names)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let components: Vec<String> = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            names.push(components.join("/"));
+        }
+    }
+    Ok(())
+}
+
+impl Directory {
+    pub fn open(root: impl Into<PathBuf>) -> Result<Directory, ArchiveError> {
+        let root = root.into();
+        let mut names = Vec::new();
+        list_files(&root, &root, &mut names)?;
+        names.sort();
+        Ok(Directory { root, names })
+    }
+
+    /// The path of a resource below the root, or `None` for names that could point outside of it, such as absolute
+    /// names or names with `..`.
+    fn resource_path(&self, name: &str) -> Option<PathBuf> {
+        let relative = Path::new(name);
+        relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+            .then(|| self.root.join(relative))
+    }
+}
+
+impl ResourceLookup for Directory {
+    fn resource_names(&self) -> Vec<&str> {
+        self.names.iter().map(String::as_str).collect()
+    }
+
+    fn find_resource(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        let Some(path) = self.resource_path(name) else {
+            return Ok(None);
+        };
+        match fs::read(path) {
+            Ok(data) => Ok(Some(data)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(ArchiveError::Io(error)),
+        }
+    }
+
+    fn contains_resource(&self, name: &str) -> bool {
+        self.resource_path(name).is_some_and(|path| path.is_file())
+    }
+}
+
+/// Where the classes of a class path entry come from.
+#[derive(Debug)]
+pub enum ClassPathSource {
+    Directory(Directory),
+    Jar(JarFile),
+    Jmod(JmodFile),
+    Image(JImage),
+}
+
+impl ClassPathSource {
+    /// Opens a directory, a jar or zip file, a jmod file or a jimage, which is told apart by its extension or, for
+    /// the `lib/modules` file of a JDK, its magic number.
+    pub fn open(path: &Path) -> Result<ClassPathSource, ArchiveError> {
+        if path.is_dir() {
+            return Ok(ClassPathSource::Directory(Directory::open(path)?));
+        }
+        let data = fs::read(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("jmod") => Ok(ClassPathSource::Jmod(JmodFile::from_bytes(data)?)),
+            Some("jar" | "zip") => Ok(ClassPathSource::Jar(JarFile::from_bytes(data)?)),
+            _ if data.starts_with(&[0xda, 0xda, 0xfe, 0xca])
+                || data.starts_with(&[0xca, 0xfe, 0xda, 0xda]) =>
+            {
+                Ok(ClassPathSource::Image(JImage::from_bytes(data)?))
+            }
+            _ => Ok(ClassPathSource::Jar(JarFile::from_bytes(data)?)),
+        }
+    }
+
+    /// Whether the source belongs to the JDK: a jimage or a jmod file.
+    pub fn is_jdk(&self) -> bool {
+        matches!(self, ClassPathSource::Image(_) | ClassPathSource::Jmod(_))
+    }
+
+    fn find_resource(
+        &self,
+        name: &str,
+        release: Option<u16>,
+    ) -> Result<Option<Vec<u8>>, ArchiveError> {
+        match (self, release) {
+            (ClassPathSource::Jar(jar_file), Some(release)) => jar_file
+                .resolve_entry(name, release)
+                .map(|entry| jar_file.read(entry))
+                .transpose(),
+            (ClassPathSource::Directory(directory), _) => directory.find_resource(name),
+            (ClassPathSource::Jar(jar_file), None) => jar_file.find_resource(name),
+            (ClassPathSource::Jmod(jmod_file), _) => jmod_file.find_resource(name),
+            (ClassPathSource::Image(image), _) => image.find_resource(name),
+        }
+    }
+
+    fn contains_resource(&self, name: &str, release: Option<u16>) -> bool {
+        match (self, release) {
+            (ClassPathSource::Jar(jar_file), Some(release)) => {
+                jar_file.resolve_entry(name, release).is_some()
+            }
+            (ClassPathSource::Directory(directory), _) => directory.contains_resource(name),
+            (ClassPathSource::Jar(jar_file), None) => jar_file.contains_resource(name),
+            (ClassPathSource::Jmod(jmod_file), _) => jmod_file.contains_resource(name),
+            (ClassPathSource::Image(image), _) => ResourceLookup::contains_resource(image, name),
+        }
+    }
+
+    /// The binary names of the classes of the source, without `module-info` and the versioned classes of
+    /// multi-release jars that are not visible to the release.
+    fn class_names(&self, release: Option<u16>) -> Vec<&str> {
+        let names = match (self, release) {
+            (ClassPathSource::Jar(jar_file), Some(release)) => {
+                return jar_file.class_names_for_release(release);
+            }
+            (ClassPathSource::Jar(jar_file), None) => ResourceLookup::resource_names(jar_file),
+            (ClassPathSource::Directory(directory), _) => directory.resource_names(),
+            (ClassPathSource::Jmod(jmod_file), _) => ResourceLookup::resource_names(jmod_file),
+            (ClassPathSource::Image(image), _) => ResourceLookup::resource_names(image),
+        };
+        names
+            .into_iter()
+            .filter(|name| !name.starts_with("META-INF/"))
+            .filter_map(|name| name.strip_suffix(".class"))
+            .filter(|class_name| !class_name.ends_with("module-info"))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub struct ClassPathEntry {
+    pub location: PathBuf,
+    pub source: ClassPathSource,
+}
+
+impl ClassPathEntry {
+    fn scan<'a>(
+        &'a self,
+        class_name: &'a str,
+        release: Option<u16>,
+        options: &ParseOptions,
+    ) -> ScannedClass<'a> {
+        let name = format!("{class_name}.class");
+        let class_file = match self.source.find_resource(&name, release) {
+            Ok(Some(data)) => read_class_data_with_options(&data, options)
+                .map_err(|error| ArchiveError::InvalidClass(name, error)),
+            Ok(None) => Err(ArchiveError::EntryNotFound(name)),
+            Err(error) => Err(error),
+        };
+        ScannedClass {
+            entry: self,
+            class_name,
+            class_file,
+        }
+    }
+}
+
+/// A class read from one of the entries of a class path by a scan of all classes.
+pub struct ScannedClass<'a> {
+    /// The entry the class comes from.
+    pub entry: &'a ClassPathEntry,
+    /// The binary name of the class in its internal form, e.g. `com/acme/Foo`.
+    pub class_name: &'a str,
+    /// The parsed class, or why it could not be read or parsed.
+    pub class_file: Result<ClassFile, ArchiveError>,
+}
+
+/// A class that is found in more than one entry of a class path, of which only the first is used.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ShadowedClass {
+    pub class_name: String,
+    /// The location of the entry that provides the class.
+    pub location: PathBuf,
+    /// The locations of the later entries whose class is hidden.
+    pub shadowed: Vec<PathBuf>,
+}
+
+/// An ordered list of places to look classes up in, like the class path of `java` preceded by the runtime image.
+///
+/// Classes are found in the first entry that has them. Parsed classes are cached, so that each class is parsed once
+/// however often it is asked for, also from several threads.
+#[derive(Default)]
+pub struct ClassPath {
+    entries: Vec<ClassPathEntry>,
+    /// The release for which the versioned entries of multi-release jars are resolved, or `None` to use only their
+    /// base entries.
+    release: Option<u16>,
+    /// How the classes of all entries are parsed.
+    options: ParseOptions,
+    cache: Mutex<HashMap<String, Arc<ClassFile>>>,
+}
+
+impl std::fmt::Debug for ClassPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClassPath")
+            .field("entries", &self.entries)
+            .field("release", &self.release)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClassPath {
+    pub fn new() -> ClassPath {
+        ClassPath::default()
+    }
+
+    /// A class path of the paths in a string separated like the `-classpath` option of `java`, e.g. `lib/a.jar:classes`
+    /// (with `;` on Windows).
+    pub fn parse(class_path: &str) -> Result<ClassPath, ArchiveError> {
+        let mut result = ClassPath::new();
+        for path in std::env::split_paths(class_path) {
+            if !path.as_os_str().is_empty() {
+                result.add(path)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// A class path of the runtime image of a JDK: its `lib/modules` jimage or, without one, its `jmods` directory.
+    pub fn from_java_home(java_home: impl AsRef<Path>) -> Result<ClassPath, ArchiveError> {
+        let java_home = java_home.as_ref();
+        let mut result = ClassPath::new();
+        let image = java_home.join("lib").join("modules");
+        if image.is_file() {
+            result.add(image)?;
+            return Ok(result);
+        }
+        let mut jmods: Vec<PathBuf> = fs::read_dir(java_home.join("jmods"))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        jmods.sort();
+        for jmod in jmods {
+            if jmod
+                .extension()
+                .is_some_and(|extension| extension == "jmod")
+            {
+                result.add(jmod)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Resolves the versioned entries of multi-release jars for a release, such as 17.
+    pub fn with_release(mut self, release: u16) -> ClassPath {
+        self.release = Some(release);
+        self.clear_cache();
+        self
+    }
+
+    /// Parses the classes with the options instead of the default ones, e.g. to accept preview class files.
+    pub fn with_options(mut self, options: ParseOptions) -> ClassPath {
+        self.options = options;
+        self.clear_cache();
+        self
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Appends a directory, jar, jmod or jimage to the end of the class path.
+    pub fn add(&mut self, path: impl Into<PathBuf>) -> Result<(), ArchiveError> {
+        let location = path.into();
+        let source = ClassPathSource::open(&location)?;
+        self.push(location, source);
+        Ok(())
+    }
+
+    /// Appends a source that has already been opened, under the location by which it is reported.
+    pub fn push(&mut self, location: impl Into<PathBuf>, source: ClassPathSource) {
+        let location = location.into();
+        debug!("added class path entry; location={}", location.display());
+        self.entries.push(ClassPathEntry { location, source });
+        self.clear_cache();
+    }
+
+    pub fn entries(&self) -> &[ClassPathEntry] {
+        &self.entries
+    }
+
+    /// The binary names of the classes of one of the entries, including the classes that earlier entries shadow.
+    pub fn entry_class_names<'a>(&self, entry: &'a ClassPathEntry) -> Vec<&'a str> {
+        entry.source.class_names(self.release)
+    }
+
+    fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+
+    /// Reads a resource from the first entry that has it, e.g. `com/acme/Foo.class` or `META-INF/MANIFEST.MF`.
+    pub fn find_resource(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        for entry in &self.entries {
+            if let Some(data) = entry.source.find_resource(name, self.release)? {
+                return Ok(Some(data));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The entry that provides a class, given by its binary name in its internal form, e.g. `com/acme/Foo`, without
+    /// reading the class.
+    pub fn locate(&self, class_name: &str) -> Result<Option<&ClassPathEntry>, ArchiveError> {
+        let name = format!("{class_name}.class");
+        Ok(self
+            .entries
+            .iter()
+            .find(|entry| entry.source.contains_resource(&name, self.release)))
+    }
+
+    /// Reads the bytes of a class, given by its binary name in its internal form, e.g. `com/acme/Foo`.
+    pub fn find_class_data(&self, class_name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        self.find_resource(&format!("{class_name}.class"))
+    }
+
+    /// Reads the bytes of a class like [`find_class_data`](ClassPath::find_class_data), together with the entry that
+    /// provides it.
+    pub fn locate_class_data(
+        &self,
+        class_name: &str,
+    ) -> Result<Option<(&ClassPathEntry, Vec<u8>)>, ArchiveError> {
+        let name = format!("{class_name}.class");
+        for entry in &self.entries {
+            if let Some(data) = entry.source.find_resource(&name, self.release)? {
+                return Ok(Some((entry, data)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Reads and parses a class, or takes it from the cache if it has been parsed before.
+    pub fn find_class(&self, class_name: &str) -> Result<Option<Arc<ClassFile>>, ArchiveError> {
+        if let Some(class_file) = self
+            .cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(class_name).cloned())
+        {
+            return Ok(Some(class_file));
+        }
+        let Some(data) = self.find_class_data(class_name)? else {
+            return Ok(None);
+        };
+        let class_file = read_class_data_with_options(&data, &self.options)
+            .map_err(|error| ArchiveError::InvalidClass(format!("{class_name}.class"), error))?;
+        let class_file = Arc::new(class_file);
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(class_name.to_string(), Arc::clone(&class_file));
+        }
+        Ok(Some(class_file))
+    }
+
+    /// Reads and parses all classes of all entries, one entry after the other, including the classes that are
+    /// shadowed by earlier entries. Unlike [`find_class`](ClassPath::find_class), this doesn't cache the classes.
+    pub fn classes(&self) -> impl Iterator<Item = ScannedClass<'_>> {
+        self.entries.iter().flat_map(|entry| {
+            let class_names = entry.source.class_names(self.release);
+            class_names
+                .into_iter()
+                .map(|class_name| entry.scan(class_name, self.release, &self.options))
+        })
+    }
+
+    /// The binary names of all classes on the class path, sorted and each once.
+    pub fn class_names(&self) -> Vec<&str> {
+        let mut class_names: Vec<&str> = self
+            .entries
+            .iter()
+            .flat_map(|entry| entry.source.class_names(self.release))
+            .collect();
+        class_names.sort();
+        class_names.dedup();
+        class_names
+    }
+
+    /// The classes that more than one entry provides, sorted by name, with the entry that wins and the ones it
+    /// shadows.
+    pub fn shadowed_classes(&self) -> Vec<ShadowedClass> {
+        let mut locations: HashMap<&str, Vec<&Path>> = HashMap::new();
+        for entry in &self.entries {
+            for class_name in entry.source.class_names(self.release) {
+                locations
+                    .entry(class_name)
+                    .or_default()
+                    .push(&entry.location);
+            }
+        }
+        let mut shadowed: Vec<ShadowedClass> = locations
+            .into_iter()
+            .filter(|(_, locations)| locations.len() > 1)
+            .map(|(class_name, locations)| ShadowedClass {
+                class_name: class_name.to_string(),
+                location: locations[0].to_path_buf(),
+                shadowed: locations[1..]
+                    .iter()
+                    .map(|location| location.to_path_buf())
+                    .collect(),
+            })
+            .collect();
+        shadowed.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+        shadowed
+    }
+}
@@ -0,0 +1,37 @@
+//! Opening and scanning class paths on all cores, with the global thread pool of rayon.
+
+use std::path::Path;
+use std::result::Result;
+
+use rayon::prelude::*;
+
+use crate::archive::ArchiveError;
+use crate::class_path::{ClassPath, ClassPathSource, ScannedClass};
+
+impl ClassPath {
+    /// Opens directories, jars, jmods and jimages concurrently into a class path, keeping their order.
+    pub fn par_open<P: AsRef<Path> + Sync>(paths: &[P]) -> Result<ClassPath, ArchiveError> {
+        let sources: Vec<ClassPathSource> = paths
+            .par_iter()
+            .map(|path| ClassPathSource::open(path.as_ref()))
+            .collect::<Result<_, _>>()?;
+        let mut class_path = ClassPath::new();
+        for (path, source) in paths.iter().zip(sources) {
+            class_path.push(path.as_ref(), source);
+        }
+        Ok(class_path)
+    }
+
+    /// Reads and parses all classes of all entries like [`classes`](ClassPath::classes), but spread over the
+    /// threads of rayon, both across entries and within each entry. The classes come in no particular order.
+    pub fn par_classes(&self) -> impl ParallelIterator<Item = ScannedClass<'_>> {
+        let release = self.release;
+        let options = &self.options;
+        self.entries.par_iter().flat_map(move |entry| {
+            let class_names = entry.source.class_names(release);
+            class_names
+                .into_par_iter()
+                .map(move |class_name| entry.scan(class_name, release, options))
+        })
+    }
+}
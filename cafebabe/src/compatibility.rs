@@ -0,0 +1,486 @@
+//! Compares two versions of a library to find the changes that break binary compatibility with the classes that were
+//! compiled against the old version, following chapter 13 of the Java Language Specification.
+
+mod types;
+
+use std::collections::BTreeSet;
+use std::result::Result;
+use std::sync::Arc;
+
+use log::{debug, error};
+
+pub use self::types::{Change, ChangeKind, CompatibilityReport, Member, Severity, section_title};
+use crate::archive::ArchiveError;
+use crate::class_path::ClassPath;
+use crate::constant_pool::{ConstantPool, ConstantPoolEntry};
+use crate::errors::ClassFileError;
+use crate::types::{AccessFlags, ClassFile, FieldAccessFlags, MethodAccessFlags};
+
+const PRIVATE: u8 = 0;
+const PACKAGE: u8 = 1;
+const PROTECTED: u8 = 2;
+const PUBLIC: u8 = 3;
+
+/// A field or method, reduced to what matters for linking against it.
+struct MemberInfo<'a> {
+    name: &'a str,
+    descriptor: &'a str,
+    access: u8,
+    is_static: bool,
+    is_final: bool,
+    is_abstract: bool,
+    is_synthetic: bool,
+    constant: Option<String>,
+}
+
+impl MemberInfo<'_> {
+    fn member(&self) -> Member {
+        Member {
+            name: self.name.to_string(),
+            descriptor: self.descriptor.to_string(),
+        }
+    }
+
+    /// Whether classes in other packages can use the member: it is public, or protected in a class that can be
+    /// extended.
+    fn is_api(&self, class_file: &ClassFile) -> bool {
+        !self.is_synthetic
+            && (self.access == PUBLIC
+                || (self.access == PROTECTED
+                    && !class_file.access_flags.contains(AccessFlags::ACC_FINAL)))
+    }
+}
+
+fn access(public: bool, protected: bool, private: bool) -> u8 {
+    match (public, protected, private) {
+        (true, _, _) => PUBLIC,
+        (_, true, _) => PROTECTED,
+        (_, _, true) => PRIVATE,
+        _ => PACKAGE,
+    }
+}
+
+/// The value of a constant as it would appear in Java source, so that values of different types don't compare equal.
+fn constant_value(
+    constant_pool: &ConstantPool,
+    value_idx: u16,
+    descriptor: &str,
+) -> Result<String, ClassFileError> {
+    match (constant_pool.checked_entry(value_idx)?, descriptor) {
+        (ConstantPoolEntry::Integer(value), "Z") => Ok((*value != 0).to_string()),
+        (ConstantPoolEntry::Integer(value), "C") => match char::from_u32(*value as u32) {
+            Some(value) => Ok(format!("{value:?}")),
+            None => Ok(value.to_string()),
+        },
+        (ConstantPoolEntry::Integer(value), _) => Ok(value.to_string()),
+        (ConstantPoolEntry::Long(value), _) => Ok(format!("{value}L")),
+        (ConstantPoolEntry::Float(value), _) => Ok(format!("{value:?}f")),
+        (ConstantPoolEntry::Double(value), _) => Ok(format!("{value:?}")),
+        (ConstantPoolEntry::StringRef(string_idx), _) => {
+            Ok(format!("{:?}", constant_pool.string_entry(*string_idx)?))
+        }
+        _ => Err(ClassFileError::UnexpectedConstantPoolType),
+    }
+}
+
+fn fields(class_file: &ClassFile) -> Result<Vec<MemberInfo<'_>>, ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    let mut fields = Vec::with_capacity(class_file.fields.len());
+    for field in &class_file.fields {
+        let flags = field.access_flags;
+        let descriptor = field.descriptor(constant_pool)?;
+        let constant = field
+            .constant_value_idx()
+            .map(|value_idx| constant_value(constant_pool, value_idx, descriptor))
+            .transpose()?;
+        fields.push(MemberInfo {
+            name: field.name(constant_pool)?,
+            descriptor,
+            access: access(
+                flags.contains(FieldAccessFlags::ACC_PUBLIC),
+                flags.contains(FieldAccessFlags::ACC_PROTECTED),
+                flags.contains(FieldAccessFlags::ACC_PRIVATE),
+            ),
+            is_static: flags.contains(FieldAccessFlags::ACC_STATIC),
+            is_final: flags.contains(FieldAccessFlags::ACC_FINAL),
+            is_abstract: false,
+            is_synthetic: field.is_synthetic(),
+            constant,
+        });
+    }
+    Ok(fields)
+}
+
+fn methods(class_file: &ClassFile) -> Result<Vec<MemberInfo<'_>>, ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    let mut methods = Vec::with_capacity(class_file.methods.len());
+    for method in &class_file.methods {
+        let flags = method.access_flags;
+        methods.push(MemberInfo {
+            name: method.name(constant_pool)?,
+            descriptor: method.descriptor(constant_pool)?,
+            access: access(
+                flags.contains(MethodAccessFlags::ACC_PUBLIC),
+                flags.contains(MethodAccessFlags::ACC_PROTECTED),
+                flags.contains(MethodAccessFlags::ACC_PRIVATE),
+            ),
+            is_static: flags.contains(MethodAccessFlags::ACC_STATIC),
+            is_final: flags.contains(MethodAccessFlags::ACC_FINAL),
+            is_abstract: flags.contains(MethodAccessFlags::ACC_ABSTRACT),
+            is_synthetic: method.is_synthetic(),
+            constant: None,
+        });
+    }
+    Ok(methods)
+}
+
+fn find<'a, 'b>(
+    members: &'a [MemberInfo<'b>],
+    name: &str,
+    descriptor: &str,
+) -> Option<&'a MemberInfo<'b>> {
+    members
+        .iter()
+        .find(|member| member.name == name && member.descriptor == descriptor)
+}
+
+fn is_interface(class_file: &ClassFile) -> bool {
+    class_file.access_flags.contains(AccessFlags::ACC_INTERFACE)
+}
+
+/// Whether classes in other packages can use the class. Nested classes that are public or protected in the source
+/// are public in their class file too.
+fn is_api_class(class_file: &ClassFile) -> bool {
+    let flags = class_file.access_flags;
+    flags.contains(AccessFlags::ACC_PUBLIC)
+        && !flags.intersects(AccessFlags::ACC_SYNTHETIC | AccessFlags::ACC_MODULE)
+}
+
+fn find_class(class_path: &ClassPath, class_name: &str) -> Option<Arc<ClassFile>> {
+    match class_path.find_class(class_name) {
+        Ok(class_file) => class_file,
+        Err(error) => {
+            debug!("can't read supertype; class_name={class_name}, error={error}");
+            None
+        }
+    }
+}
+
+fn direct_supertypes(class_file: &ClassFile) -> Result<Vec<String>, ClassFileError> {
+    let mut supertypes: Vec<String> = class_file
+        .super_class_name()?
+        .into_iter()
+        .cloned()
+        .collect();
+    supertypes.extend(class_file.interface_names()?.into_iter().cloned());
+    Ok(supertypes)
+}
+
+/// All superclasses and superinterfaces of a class, as far as they can be found on the class path.
+fn supertypes(
+    class_path: &ClassPath,
+    class_file: &ClassFile,
+) -> Result<BTreeSet<String>, ClassFileError> {
+    let mut supertypes = BTreeSet::new();
+    let mut pending = direct_supertypes(class_file)?;
+    while let Some(class_name) = pending.pop() {
+        if !supertypes.insert(class_name.clone()) {
+            continue;
+        }
+        if let Some(supertype) = find_class(class_path, &class_name) {
+            pending.extend(direct_supertypes(&supertype)?);
+        }
+    }
+    Ok(supertypes)
+}
+
+/// Whether one of the supertypes declares a field or method that classes in other packages can use in place of the
+/// given one. Constructors are not inherited, nor are the static methods of interfaces.
+fn is_inherited(
+    class_path: &ClassPath,
+    supertypes: &BTreeSet<String>,
+    member: &MemberInfo,
+    is_field: bool,
+) -> Result<bool, ClassFileError> {
+    if member.name == "<init>" {
+        return Ok(false);
+    }
+    for class_name in supertypes {
+        let Some(supertype) = find_class(class_path, class_name) else {
+            continue;
+        };
+        let members = match is_field {
+            true => fields(&supertype)?,
+            false => methods(&supertype)?,
+        };
+        let inherited = find(&members, member.name, member.descriptor).is_some_and(|inherited| {
+            inherited.is_api(&supertype)
+                && inherited.is_static == member.is_static
+                && (is_field || !inherited.is_static || !is_interface(&supertype))
+        });
+        if inherited {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Compares the two versions of a class, resolving their supertypes on the class paths of the respective versions.
+struct ClassComparison<'a> {
+    class_name: &'a str,
+    old: &'a ClassFile,
+    new: &'a ClassFile,
+    old_path: &'a ClassPath,
+    new_path: &'a ClassPath,
+    changes: Vec<Change>,
+}
+
+impl ClassComparison<'_> {
+    fn push(&mut self, member: Option<&MemberInfo>, kind: ChangeKind) {
+        let change = Change::new(
+            self.class_name,
+            member.map(MemberInfo::member),
+            kind,
+            is_interface(self.old),
+        );
+        self.changes.push(change);
+    }
+
+    fn compare(mut self) -> Result<Vec<Change>, ClassFileError> {
+        let (old_flags, new_flags) = (self.old.access_flags, self.new.access_flags);
+        if !new_flags.contains(AccessFlags::ACC_PUBLIC) {
+            self.push(None, ChangeKind::LessAccessible);
+            return Ok(self.changes);
+        }
+        if is_interface(self.old) != is_interface(self.new) {
+            let interface = is_interface(self.new);
+            self.push(None, ChangeKind::KindChanged { interface });
+            return Ok(self.changes);
+        }
+        if !is_interface(self.old)
+            && !old_flags.contains(AccessFlags::ACC_ABSTRACT)
+            && new_flags.contains(AccessFlags::ACC_ABSTRACT)
+        {
+            self.push(None, ChangeKind::ClassMadeAbstract);
+        }
+        if !old_flags.contains(AccessFlags::ACC_FINAL) && new_flags.contains(AccessFlags::ACC_FINAL)
+        {
+            self.push(None, ChangeKind::ClassMadeFinal);
+        }
+
+        let old_supertypes = supertypes(self.old_path, self.old)?;
+        let new_supertypes = supertypes(self.new_path, self.new)?;
+        for supertype in old_supertypes.difference(&new_supertypes) {
+            let supertype = supertype.clone();
+            self.push(None, ChangeKind::SupertypeRemoved { supertype });
+        }
+
+        self.compare_fields(&new_supertypes)?;
+        self.compare_methods(&old_supertypes, &new_supertypes)?;
+        Ok(self.changes)
+    }
+
+    fn compare_fields(&mut self, new_supertypes: &BTreeSet<String>) -> Result<(), ClassFileError> {
+        let (old_fields, new_fields) = (fields(self.old)?, fields(self.new)?);
+        for old_field in old_fields.iter().filter(|field| field.is_api(self.old)) {
+            let Some(new_field) = find(&new_fields, old_field.name, old_field.descriptor) else {
+                if is_inherited(self.new_path, new_supertypes, old_field, true)? {
+                    continue;
+                }
+                let kind = match new_fields.iter().find(|field| field.name == old_field.name) {
+                    Some(new_field) => ChangeKind::FieldTypeChanged {
+                        descriptor: new_field.descriptor.to_string(),
+                    },
+                    None => ChangeKind::FieldRemoved,
+                };
+                self.push(Some(old_field), kind);
+                continue;
+            };
+
+            if new_field.access < old_field.access {
+                self.push(Some(old_field), ChangeKind::LessAccessible);
+            }
+            if new_field.is_static != old_field.is_static {
+                let is_static = new_field.is_static;
+                self.push(
+                    Some(old_field),
+                    ChangeKind::FieldStaticChanged { is_static },
+                );
+            }
+            if !old_field.is_final && new_field.is_final {
+                self.push(Some(old_field), ChangeKind::FieldMadeFinal);
+            }
+            if let Some(old) = &old_field.constant
+                && new_field.constant.as_ref() != Some(old)
+            {
+                let kind = ChangeKind::ConstantValueChanged {
+                    old: old.clone(),
+                    new: new_field.constant.clone(),
+                };
+                self.push(Some(old_field), kind);
+            }
+        }
+        Ok(())
+    }
+
+    fn compare_methods(
+        &mut self,
+        old_supertypes: &BTreeSet<String>,
+        new_supertypes: &BTreeSet<String>,
+    ) -> Result<(), ClassFileError> {
+        let (old_methods, new_methods) = (methods(self.old)?, methods(self.new)?);
+        for old_method in old_methods.iter().filter(|method| method.is_api(self.old)) {
+            let Some(new_method) = find(&new_methods, old_method.name, old_method.descriptor)
+            else {
+                if is_inherited(self.new_path, new_supertypes, old_method, false)? {
+                    continue;
+                }
+                let kind = self.replacement(old_method, &old_methods, &new_methods);
+                self.push(Some(old_method), kind);
+                continue;
+            };
+
+            if new_method.access < old_method.access {
+                self.push(Some(old_method), ChangeKind::LessAccessible);
+            }
+            if new_method.is_static != old_method.is_static {
+                let is_static = new_method.is_static;
+                self.push(
+                    Some(old_method),
+                    ChangeKind::MethodStaticChanged { is_static },
+                );
+            }
+            if !old_method.is_abstract && new_method.is_abstract {
+                self.push(Some(old_method), ChangeKind::MethodMadeAbstract);
+            }
+            if !old_method.is_final
+                && new_method.is_final
+                && !self.old.access_flags.contains(AccessFlags::ACC_FINAL)
+            {
+                self.push(Some(old_method), ChangeKind::MethodMadeFinal);
+            }
+        }
+
+        for new_method in new_methods.iter().filter(|method| method.is_api(self.new)) {
+            if new_method.is_abstract
+                && find(&old_methods, new_method.name, new_method.descriptor).is_none()
+                && !is_inherited(self.old_path, old_supertypes, new_method, false)?
+            {
+                self.push(Some(new_method), ChangeKind::AbstractMethodAdded);
+            }
+        }
+        Ok(())
+    }
+
+    /// Tells a method that was removed from one whose descriptor changed, by a new method with the same name that the
+    /// old version didn't have.
+    fn replacement(
+        &self,
+        old_method: &MemberInfo,
+        old_methods: &[MemberInfo],
+        new_methods: &[MemberInfo],
+    ) -> ChangeKind {
+        let candidates: Vec<&MemberInfo> = new_methods
+            .iter()
+            .filter(|method| method.name == old_method.name && method.is_api(self.new))
+            .filter(|method| find(old_methods, method.name, method.descriptor).is_none())
+            .collect();
+        let parameters = |descriptor: &str| {
+            descriptor
+                .rfind(')')
+                .map(|end| descriptor[..end].to_string())
+        };
+        let same_parameters = candidates
+            .iter()
+            .find(|method| parameters(method.descriptor) == parameters(old_method.descriptor));
+        match (same_parameters, candidates.first()) {
+            (Some(method), _) => ChangeKind::MethodReturnTypeChanged {
+                descriptor: method.descriptor.to_string(),
+            },
+            (None, Some(method)) => ChangeKind::MethodParametersChanged {
+                descriptor: method.descriptor.to_string(),
+            },
+            (None, None) => ChangeKind::MethodRemoved,
+        }
+    }
+}
+
+/// Compares two versions of a class on their own. Members that move to a supertype count as removed, as the
+/// supertypes can't be found; use [`compare`] to compare whole libraries.
+pub fn compare_classes(old: &ClassFile, new: &ClassFile) -> Result<Vec<Change>, ClassFileError> {
+    let class_path = ClassPath::new();
+    compare_class(old, new, &class_path, &class_path)
+}
+
+fn compare_class(
+    old: &ClassFile,
+    new: &ClassFile,
+    old_path: &ClassPath,
+    new_path: &ClassPath,
+) -> Result<Vec<Change>, ClassFileError> {
+    let class_name = old.this_class_name()?;
+    if !is_api_class(old) {
+        return Ok(Vec::new());
+    }
+    let comparison = ClassComparison {
+        class_name,
+        old,
+        new,
+        old_path,
+        new_path,
+        changes: Vec::new(),
+    };
+    comparison.compare()
+}
+
+/// Reads a class to compare, recording it in the report if it can't be parsed.
+fn read_class(
+    class_path: &ClassPath,
+    class_name: &str,
+    report: &mut CompatibilityReport,
+) -> Result<Option<Arc<ClassFile>>, ArchiveError> {
+    match class_path.find_class(class_name) {
+        Ok(class_file) => Ok(class_file),
+        Err(ArchiveError::InvalidClass(_, error)) => {
+            error!("can't compare class; class_name={class_name}, error={error}");
+            report.invalid_classes.push(class_name.to_string());
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Compares the public classes of the old version of a library with those of the new one. Classes that were added
+/// can't break existing binaries, so they are not reported.
+pub fn compare(old: &ClassPath, new: &ClassPath) -> Result<CompatibilityReport, ArchiveError> {
+    let mut report = CompatibilityReport::default();
+    for class_name in old.class_names() {
+        let Some(old_class) = read_class(old, class_name, &mut report)? else {
+            continue;
+        };
+        if !is_api_class(&old_class) {
+            continue;
+        }
+        if new.find_class_data(class_name)?.is_none() {
+            let change = Change::new(
+                class_name,
+                None,
+                ChangeKind::ClassRemoved,
+                is_interface(&old_class),
+            );
+            report.changes.push(change);
+            continue;
+        }
+        let Some(new_class) = read_class(new, class_name, &mut report)? else {
+            continue;
+        };
+        match compare_class(&old_class, &new_class, old, new) {
+            Ok(changes) => report.changes.extend(changes),
+            Err(error) => {
+                error!("can't compare class; class_name={class_name}, error={error}");
+                report.invalid_classes.push(class_name.to_string());
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,305 @@
+use std::fmt::{Display, Formatter};
+
+/// How a change affects the classes that were compiled against the old version of a library.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// Existing binaries may fail to link, e.g. with a `NoSuchMethodError` or an `IncompatibleClassChangeError`.
+    Breaking,
+    /// Existing binaries still link, but may behave differently, e.g. because they inlined the old value of a
+    /// constant or don't implement a new abstract method.
+    Behavioral,
+}
+
+/// What changed about a class or one of its members.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum ChangeKind {
+    ClassRemoved,
+    /// A public class or member is no longer public, or a protected member is no longer protected.
+    LessAccessible,
+    /// A class became an interface, or the other way round.
+    KindChanged {
+        interface: bool,
+    },
+    ClassMadeAbstract,
+    ClassMadeFinal,
+    /// A class or interface is no longer a subtype of the supertype, directly or indirectly.
+    SupertypeRemoved {
+        supertype: String,
+    },
+    FieldRemoved,
+    /// The field is still there, but with another type.
+    FieldTypeChanged {
+        descriptor: String,
+    },
+    FieldMadeFinal,
+    /// A static field became an instance field, or the other way round.
+    FieldStaticChanged {
+        is_static: bool,
+    },
+    /// The value of a constant changed, or the field is no longer a constant. Binaries that use the constant keep
+    /// the old value, as the compiler inlined it.
+    ConstantValueChanged {
+        old: String,
+        new: Option<String>,
+    },
+    MethodRemoved,
+    /// The method is still there, but with other parameters.
+    MethodParametersChanged {
+        descriptor: String,
+    },
+    /// The method is still there, but with another return type.
+    MethodReturnTypeChanged {
+        descriptor: String,
+    },
+    MethodMadeAbstract,
+    MethodMadeFinal,
+    /// A static method became an instance method, or the other way round.
+    MethodStaticChanged {
+        is_static: bool,
+    },
+    /// A new abstract method, which subclasses and implementations that were compiled before don't implement.
+    AbstractMethodAdded,
+}
+
+impl ChangeKind {
+    pub fn severity(&self) -> Severity {
+        match self {
+            ChangeKind::ConstantValueChanged { .. } | ChangeKind::AbstractMethodAdded => {
+                Severity::Behavioral
+            }
+            _ => Severity::Breaking,
+        }
+    }
+
+    /// The section of chapter 13 of the Java Language Specification (Java SE 17) with the rule for the change, which
+    /// differs between changes to classes and to interfaces, and between changes to types and to their members.
+    pub(super) fn section(&self, interface: bool, member: bool) -> &'static str {
+        match (self, interface) {
+            (ChangeKind::ClassRemoved, _) => "13.3",
+            (ChangeKind::LessAccessible, _) if member => "13.4.7",
+            (ChangeKind::LessAccessible, false) => "13.4.3",
+            (ChangeKind::LessAccessible, true) => "13.5.1",
+            (ChangeKind::KindChanged { .. }, false) => "13.4",
+            (ChangeKind::KindChanged { .. }, true) => "13.5",
+            (ChangeKind::ClassMadeAbstract, _) => "13.4.1",
+            (ChangeKind::ClassMadeFinal, _) => "13.4.2.3",
+            (ChangeKind::SupertypeRemoved { .. }, false) => "13.4.4",
+            (ChangeKind::SupertypeRemoved { .. }, true) => "13.5.3",
+            (ChangeKind::FieldRemoved | ChangeKind::FieldTypeChanged { .. }, false) => "13.4.8",
+            (ChangeKind::FieldRemoved | ChangeKind::FieldTypeChanged { .. }, true) => "13.5.6",
+            (ChangeKind::FieldMadeFinal | ChangeKind::ConstantValueChanged { .. }, _) => "13.4.9",
+            (ChangeKind::FieldStaticChanged { .. }, _) => "13.4.10",
+            (ChangeKind::MethodRemoved, false) => "13.4.12",
+            (ChangeKind::MethodParametersChanged { .. }, false) => "13.4.14",
+            (ChangeKind::MethodReturnTypeChanged { .. }, false) => "13.4.15",
+            (ChangeKind::MethodMadeAbstract | ChangeKind::AbstractMethodAdded, false) => "13.4.16",
+            (ChangeKind::MethodMadeFinal, _) => "13.4.17",
+            (ChangeKind::MethodStaticChanged { .. }, false) => "13.4.19",
+            (ChangeKind::AbstractMethodAdded, true) => "13.5.4",
+            (
+                ChangeKind::MethodRemoved
+                | ChangeKind::MethodParametersChanged { .. }
+                | ChangeKind::MethodReturnTypeChanged { .. }
+                | ChangeKind::MethodMadeAbstract
+                | ChangeKind::MethodStaticChanged { .. },
+                true,
+            ) => "13.5.7",
+        }
+    }
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::ClassRemoved => write!(f, "removed"),
+            ChangeKind::LessAccessible => write!(f, "less accessible"),
+            ChangeKind::KindChanged { interface: true } => write!(f, "changed into an interface"),
+            ChangeKind::KindChanged { interface: false } => write!(f, "changed into a class"),
+            ChangeKind::ClassMadeAbstract | ChangeKind::MethodMadeAbstract => {
+                write!(f, "made abstract")
+            }
+            ChangeKind::ClassMadeFinal
+            | ChangeKind::FieldMadeFinal
+            | ChangeKind::MethodMadeFinal => write!(f, "made final"),
+            ChangeKind::SupertypeRemoved { supertype } => {
+                write!(f, "no longer a subtype of {}", supertype.replace('/', "."))
+            }
+            ChangeKind::FieldRemoved | ChangeKind::MethodRemoved => write!(f, "removed"),
+            ChangeKind::FieldTypeChanged { descriptor } => {
+                write!(f, "type changed to {descriptor}")
+            }
+            ChangeKind::FieldStaticChanged { is_static: true }
+            | ChangeKind::MethodStaticChanged { is_static: true } => write!(f, "made static"),
+            ChangeKind::FieldStaticChanged { is_static: false }
+            | ChangeKind::MethodStaticChanged { is_static: false } => {
+                write!(f, "no longer static")
+            }
+            ChangeKind::ConstantValueChanged {
+                old,
+                new: Some(new),
+            } => {
+                write!(f, "constant value changed from {old} to {new}")
+            }
+            ChangeKind::ConstantValueChanged { old, new: None } => {
+                write!(f, "no longer a constant, was {old}")
+            }
+            ChangeKind::MethodParametersChanged { descriptor } => {
+                write!(f, "parameters changed to {descriptor}")
+            }
+            ChangeKind::MethodReturnTypeChanged { descriptor } => {
+                write!(f, "return type changed to {descriptor}")
+            }
+            ChangeKind::AbstractMethodAdded => write!(f, "added as abstract method"),
+        }
+    }
+}
+
+/// The title of a section of chapter 13 of the Java Language Specification (Java SE 17).
+pub fn section_title(section: &str) -> &'static str {
+    match section {
+        "13.3" => "Evolution of Packages",
+        "13.4" => "Evolution of Classes",
+        "13.4.1" => "abstract Classes",
+        "13.4.2.3" => "final Classes",
+        "13.4.3" => "public Classes",
+        "13.4.4" => "Superclasses and Superinterfaces",
+        "13.4.7" => "Access to Members and Constructors",
+        "13.4.8" => "Field Declarations",
+        "13.4.9" => "final Fields and static Constant Variables",
+        "13.4.10" => "static Fields",
+        "13.4.12" => "Method and Constructor Declarations",
+        "13.4.14" => "Method and Constructor Formal Parameters",
+        "13.4.15" => "Method Result Type",
+        "13.4.16" => "abstract Methods",
+        "13.4.17" => "final Methods",
+        "13.4.19" => "static Methods",
+        "13.5" => "Evolution of Interfaces",
+        "13.5.1" => "public Interfaces",
+        "13.5.3" => "Superinterfaces",
+        "13.5.4" => "Interface Members",
+        "13.5.6" => "Interface Field Declarations",
+        "13.5.7" => "Interface Method Declarations",
+        _ => "",
+    }
+}
+
+/// A field or method, by its name and descriptor.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Member {
+    pub name: String,
+    pub descriptor: String,
+}
+
+/// A change between the old and the new version of a class.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Change {
+    pub class_name: String,
+    /// The field or method that changed, or `None` if the class itself changed.
+    pub member: Option<Member>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub kind: ChangeKind,
+    pub severity: Severity,
+    /// The section of chapter 13 of the Java Language Specification that covers the change, e.g. `13.4.12`.
+    pub section: &'static str,
+}
+
+impl Change {
+    pub(super) fn new(
+        class_name: &str,
+        member: Option<Member>,
+        kind: ChangeKind,
+        interface: bool,
+    ) -> Change {
+        Change {
+            class_name: class_name.to_string(),
+            severity: kind.severity(),
+            section: kind.section(interface, member.is_some()),
+            member,
+            kind,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let class_name = self.class_name.replace('/', ".");
+        match &self.member {
+            Some(member) if member.descriptor.starts_with('(') => write!(
+                f,
+                "{class_name}.{}{}: {}",
+                member.name, member.descriptor, self.kind
+            ),
+            Some(member) => write!(
+                f,
+                "{class_name}.{}:{}: {}",
+                member.name, member.descriptor, self.kind
+            ),
+            None => write!(f, "{class_name}: {}", self.kind),
+        }
+    }
+}
+
+/// The changes between two versions of a library that affect binary compatibility.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CompatibilityReport {
+    /// The changes, ordered by class.
+    pub changes: Vec<Change>,
+    /// The classes of either version that could not be parsed, and so were not compared.
+    pub invalid_classes: Vec<String>,
+}
+
+fn section_key(section: &str) -> Vec<u32> {
+    section
+        .split('.')
+        .map(|number| number.parse().unwrap_or_default())
+        .collect()
+}
+
+impl CompatibilityReport {
+    /// Whether none of the changes break existing binaries.
+    pub fn is_compatible(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|change| change.severity != Severity::Breaking)
+    }
+
+    /// The changes grouped by the section of the Java Language Specification that covers them, in the order of the
+    /// sections.
+    pub fn by_section(&self) -> Vec<(&'static str, Vec<&Change>)> {
+        let mut sections: Vec<(&'static str, Vec<&Change>)> = Vec::new();
+        for change in &self.changes {
+            match sections
+                .iter_mut()
+                .find(|(section, _)| *section == change.section)
+            {
+                Some((_, changes)) => changes.push(change),
+                None => sections.push((change.section, vec![change])),
+            }
+        }
+        sections.sort_by_key(|(section, _)| section_key(section));
+        sections
+    }
+}
+
+impl Display for CompatibilityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (section, changes) in self.by_section() {
+            writeln!(f, "JLS {section} {}", section_title(section))?;
+            for change in changes {
+                let severity = match change.severity {
+                    Severity::Breaking => "BREAKING",
+                    Severity::Behavioral => "BEHAVIORAL",
+                };
+                writeln!(f, "  {severity:<10} {change}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::result::Result;
 use std::slice::Iter;
 use std::str;
@@ -5,39 +7,151 @@ use std::str;
 pub use crate::constant_pool::types::ConstantPoolEntry;
 use crate::errors::ClassFileError;
 
-use crate::constant_pool::types::{MethodRef, NameTypeDescriptor};
-use crate::shared::to_u16;
+use crate::constant_pool::types::{
+    DynamicRef, FieldRef, MethodHandle, MethodRef, NameTypeDescriptor, ReferenceKind,
+};
+use crate::shared::{to_bytes, to_u8, to_u16, to_u32, write_u16, write_u32};
 use log::{debug, error};
 
 pub mod types;
 
 const TAG_STRING: usize = 1;
-// const TAG_INTEGER: usize = 3;
-// const TAG_FLOAT: usize = 4;
-// const TAG_LONG: usize = 5;
-// const TAG_DOUBLE: usize = 6;
+const TAG_INTEGER: usize = 3;
+const TAG_FLOAT: usize = 4;
+const TAG_LONG: usize = 5;
+const TAG_DOUBLE: usize = 6;
 const TAG_CLASS_REF: usize = 7;
-// const TAG_STRING_REF: usize = 8;
-// const TAG_FIELD_REF: usize = 9;
+const TAG_STRING_REF: usize = 8;
+const TAG_FIELD_REF: usize = 9;
 const TAG_METHOD_REF: usize = 10;
-// const TAG_INTERFACE_METHOD_REF: usize = 11;
+const TAG_INTERFACE_METHOD_REF: usize = 11;
 const TAG_NAME_TYPE_DESCRIPTOR: usize = 12;
-// const TAG_METHOD_HANDLE: usize = 15;
-// const TAG_METHOD_TYPE: usize = 16;
-// const TAG_DYNAMIC: usize = 17;
-// const TAG_INVOKE_DYNAMIC: usize = 18;
-// const TAG_MODULE: usize = 19;
-// const TAG_PACKAGE: usize = 20;
+const TAG_METHOD_HANDLE: usize = 15;
+const TAG_METHOD_TYPE: usize = 16;
+const TAG_DYNAMIC: usize = 17;
+const TAG_INVOKE_DYNAMIC: usize = 18;
+const TAG_MODULE: usize = 19;
+const TAG_PACKAGE: usize = 20;
 
+/// Serialized as the list of its entries, where the entry at position `n` has index `n + 1`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct ConstantPool {
     items: Vec<ConstantPoolEntry>,
+    /// The bytes of the String entries that were read from a class file but can't be held in a Rust string, such as
+    /// unpaired surrogates, by index. They are written again as long as the entry keeps the value they decode to.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raw_strings: HashMap<u16, Vec<u8>>,
 }
 
 impl ConstantPool {
+    /// Creates a constant pool from its entries, where the entry at position `n` gets index `n + 1`.
+    /// A `long` or `double` entry must be followed by an `Empty()` entry for its unusable second slot.
+    pub fn new(items: Vec<ConstantPoolEntry>) -> ConstantPool {
+        ConstantPool {
+            items,
+            raw_strings: HashMap::new(),
+        }
+    }
+
+    /// Appends an entry (and the unusable slot after a `long` or `double`) and returns its index.
+    pub fn add(&mut self, entry: ConstantPoolEntry) -> u16 {
+        let takes_two_slots = matches!(
+            entry,
+            ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_)
+        );
+        self.items.push(entry);
+        let index = self.len();
+        if takes_two_slots {
+            self.items.push(ConstantPoolEntry::Empty());
+        }
+        index
+    }
+
+    /// The index of the first String entry holding `value`.
+    pub fn find_string(&self, value: &str) -> Option<u16> {
+        self.items
+            .iter()
+            .position(|entry| matches!(entry, ConstantPoolEntry::String(item) if item == value))
+            .map(|position| position as u16 + 1)
+    }
+
+    /// The index of the first ClassRef entry that points to the String entry at `name_idx`.
+    pub fn find_class_ref(&self, name_idx: u16) -> Option<u16> {
+        self.items
+            .iter()
+            .position(|entry| *entry == ConstantPoolEntry::ClassRef(name_idx))
+            .map(|position| position as u16 + 1)
+    }
+
     pub fn get_entry(&self, index: u16) -> &ConstantPoolEntry {
         &self.items[(index - 1) as usize]
     }
 
+    /// The entry at `index`, or an error if the index is outside of the constant pool.
+    pub(crate) fn checked_entry(&self, index: u16) -> Result<&ConstantPoolEntry, ClassFileError> {
+        match index
+            .checked_sub(1)
+            .and_then(|position| self.items.get(usize::from(position)))
+        {
+            Some(entry) => Ok(entry),
+            None => {
+                error!("Constant pool index {index} out of bounds");
+                Err(ClassFileError::InvalidConstantPoolIndex)
+            }
+        }
+    }
+
+    /// Replaces the entry at an index, which must neither be nor become a `long` or `double` entry.
+    pub fn set_entry(&mut self, index: u16, entry: ConstantPoolEntry) {
+        self.items[(index - 1) as usize] = entry;
+    }
+
+    /// The bytes that the String entry at `index` was read from if they can't be written from its value, e.g. because
+    /// they hold unpaired surrogates, which the value holds as replacement characters.
+    pub fn raw_string(&self, index: u16) -> Option<&[u8]> {
+        let ConstantPoolEntry::String(value) = self.get_entry(index) else {
+            return None;
+        };
+        self.raw_strings
+            .get(&index)
+            .filter(|bytes| decode_modified_utf8(bytes).as_ref() == Some(value))
+            .map(Vec::as_slice)
+    }
+
+    /// Replaces the entry at `index` by a String entry with the value of bytes in modified UTF-8, which are kept to be
+    /// written as they are if the value can't hold them.
+    pub fn set_raw_string(&mut self, index: u16, bytes: &[u8]) -> Result<(), ClassFileError> {
+        let Some(value) = decode_modified_utf8(bytes) else {
+            error!("invalid modified UTF-8 in string; content={bytes:?}");
+            return Err(ClassFileError::InvalidConstantPoolContent);
+        };
+        if *encode_modified_utf8(&value) != *bytes {
+            self.raw_strings.insert(index, bytes.to_vec());
+        }
+        self.set_entry(index, ConstantPoolEntry::String(value));
+        Ok(())
+    }
+
+    /// Removes the entries whose index `keep` doesn't hold. The other entries keep their order, so their indices only
+    /// get smaller, but the references between them are left as they are.
+    pub(crate) fn retain(&mut self, keep: &[bool]) {
+        let mut items = Vec::with_capacity(self.items.len());
+        let mut raw_strings = HashMap::new();
+        for (position, entry) in self.items.drain(..).enumerate() {
+            let index = position + 1;
+            if !keep.get(index).copied().unwrap_or(false) {
+                continue;
+            }
+            items.push(entry);
+            if let Some(bytes) = self.raw_strings.remove(&(index as u16)) {
+                raw_strings.insert(items.len() as u16, bytes);
+            }
+        }
+        self.items = items;
+        self.raw_strings = raw_strings;
+    }
+
     pub fn items(&self) -> Iter<'_, ConstantPoolEntry> {
         self.items.iter()
     }
@@ -51,7 +165,7 @@ impl ConstantPool {
     }
 
     pub fn string_entry(&self, index: u16) -> Result<&String, ClassFileError> {
-        match self.get_entry(index) {
+        match self.checked_entry(index)? {
             ConstantPoolEntry::String(value) => Ok(value),
             other => {
                 error!("Expected String at index {index:?}, found {other:?}");
@@ -61,7 +175,7 @@ impl ConstantPool {
     }
 
     pub fn class_ref_entry(&self, index: usize) -> Result<u16, ClassFileError> {
-        match self.get_entry(index as u16) {
+        match self.checked_entry(index as u16)? {
             ConstantPoolEntry::ClassRef(value) => Ok(*value),
             other => {
                 error!("Expected Class at index {index:?}, found {other:?}");
@@ -70,8 +184,28 @@ impl ConstantPool {
         }
     }
 
+    pub fn module_entry(&self, index: usize) -> Result<u16, ClassFileError> {
+        match self.checked_entry(index as u16)? {
+            ConstantPoolEntry::Module(value) => Ok(*value),
+            other => {
+                error!("Expected Module at index {index:?}, found {other:?}");
+                Err(ClassFileError::UnexpectedConstantPoolType)
+            }
+        }
+    }
+
+    pub fn package_entry(&self, index: usize) -> Result<u16, ClassFileError> {
+        match self.checked_entry(index as u16)? {
+            ConstantPoolEntry::Package(value) => Ok(*value),
+            other => {
+                error!("Expected Package at index {index:?}, found {other:?}");
+                Err(ClassFileError::UnexpectedConstantPoolType)
+            }
+        }
+    }
+
     pub fn name_type_entry(&self, index: usize) -> Result<NameTypeDescriptor, ClassFileError> {
-        match self.get_entry(index as u16) {
+        match self.checked_entry(index as u16)? {
             ConstantPoolEntry::NameTypeDescriptor(value) => Ok(*value),
             other => {
                 error!("Expected NameTypeDescriptor at index {index:?}, found {other:?}");
@@ -79,13 +213,54 @@ impl ConstantPool {
             }
         }
     }
+
+    pub fn method_handle_entry(&self, index: usize) -> Result<MethodHandle, ClassFileError> {
+        match self.checked_entry(index as u16)? {
+            ConstantPoolEntry::MethodHandle(value) => Ok(*value),
+            other => {
+                error!("Expected MethodHandle at index {index:?}, found {other:?}");
+                Err(ClassFileError::UnexpectedConstantPoolType)
+            }
+        }
+    }
+
+    /// Looks up the name of the class that a ClassRef entry points to.
+    pub fn class_name(&self, index: usize) -> Result<&String, ClassFileError> {
+        let name_idx = self.class_ref_entry(index)?;
+        self.string_entry(name_idx)
+    }
+
+    /// Looks up the name and the type descriptor of a NameTypeDescriptor entry.
+    pub fn name_and_type(&self, index: usize) -> Result<(&String, &String), ClassFileError> {
+        let name_type = self.name_type_entry(index)?;
+        let name = self.string_entry(name_type.name_ref)?;
+        let descriptor = self.string_entry(name_type.type_descriptor_ref)?;
+        Ok((name, descriptor))
+    }
+
+    /// Looks up the owning class, name and type descriptor of a FieldRef, MethodRef or
+    /// InterfaceMethodRef entry.
+    pub fn member_ref(&self, index: usize) -> Result<(&String, &String, &String), ClassFileError> {
+        let (class_ref, name_type_ref) = match self.checked_entry(index as u16)? {
+            ConstantPoolEntry::FieldRef(value) => (value.class_ref, value.name_type_ref),
+            ConstantPoolEntry::MethodRef(value) => (value.class_ref, value.name_type_ref),
+            ConstantPoolEntry::InterfaceMethodRef(value) => (value.class_ref, value.name_type_ref),
+            other => {
+                error!("Expected member reference at index {index:?}, found {other:?}");
+                return Err(ClassFileError::UnexpectedConstantPoolType);
+            }
+        };
+        let class_name = self.class_name(class_ref as usize)?;
+        let (name, descriptor) = self.name_and_type(name_type_ref as usize)?;
+        Ok((class_name, name, descriptor))
+    }
 }
 
 fn read_constant_pool_entry_class_ref(
     data: &[u8],
     from_idx: usize,
 ) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
-    let class_ref = to_u16(data, from_idx, from_idx + 1);
+    let class_ref = to_u16(data, from_idx, from_idx + 1)?;
     debug!("found class ref; class_ref={class_ref}");
 
     Ok((ConstantPoolEntry::ClassRef(class_ref), from_idx + 2))
@@ -95,8 +270,8 @@ fn read_constant_pool_entry_name_type_descriptor(
     data: &[u8],
     from_idx: usize,
 ) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
-    let name_ref = to_u16(data, from_idx, from_idx + 1);
-    let type_descriptor_ref = to_u16(data, from_idx + 2, from_idx + 3);
+    let name_ref = to_u16(data, from_idx, from_idx + 1)?;
+    let type_descriptor_ref = to_u16(data, from_idx + 2, from_idx + 3)?;
     debug!(
         "found name and type descriptor; class_name_ref={name_ref}, type_descriptor_ref={type_descriptor_ref}"
     );
@@ -115,8 +290,8 @@ fn read_constant_pool_entry_method_ref(
     data: &[u8],
     from_idx: usize,
 ) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
-    let class_ref = to_u16(data, from_idx, from_idx + 1);
-    let name_type_ref = to_u16(data, from_idx + 2, from_idx + 3);
+    let class_ref = to_u16(data, from_idx, from_idx + 1)?;
+    let name_type_ref = to_u16(data, from_idx + 2, from_idx + 3)?;
     debug!("found method ref; class_ref={class_ref}, name_type_ref={name_type_ref}");
 
     let method_ref = MethodRef {
@@ -126,36 +301,233 @@ fn read_constant_pool_entry_method_ref(
     Ok((ConstantPoolEntry::MethodRef(method_ref), from_idx + 4))
 }
 
-// fn read_constant_pool_entry_float(
-//     data: &[u8],
-//     from_idx: usize,
-// ) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
-//     // 4 bytes with a 32-bit single-precision IEEE 754 floating-point number
-//     let bytes: [u8; 4] = data[from_idx..(from_idx + 4)]
-//         .try_into()
-//         .expect("incorrect length of slice");
-//     let value = f32::from_be_bytes(bytes);
-//     debug!("found float; value={value}");
-//
-//     Ok((ConstantPoolEntry::Empty {}, from_idx + 4))
-// }
+fn read_constant_pool_entry_field_ref(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let class_ref = to_u16(data, from_idx, from_idx + 1)?;
+    let name_type_ref = to_u16(data, from_idx + 2, from_idx + 3)?;
+    debug!("found field ref; class_ref={class_ref}, name_type_ref={name_type_ref}");
+
+    let field_ref = FieldRef {
+        class_ref,
+        name_type_ref,
+    };
+    Ok((ConstantPoolEntry::FieldRef(field_ref), from_idx + 4))
+}
+
+fn read_constant_pool_entry_interface_method_ref(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let class_ref = to_u16(data, from_idx, from_idx + 1)?;
+    let name_type_ref = to_u16(data, from_idx + 2, from_idx + 3)?;
+    debug!("found interface method ref; class_ref={class_ref}, name_type_ref={name_type_ref}");
+
+    let method_ref = MethodRef {
+        class_ref,
+        name_type_ref,
+    };
+    Ok((
+        ConstantPoolEntry::InterfaceMethodRef(method_ref),
+        from_idx + 4,
+    ))
+}
+
+fn read_constant_pool_entry_string_ref(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let string_ref = to_u16(data, from_idx, from_idx + 1)?;
+    debug!("found string ref; string_ref={string_ref}");
+
+    Ok((ConstantPoolEntry::StringRef(string_ref), from_idx + 2))
+}
+
+fn read_constant_pool_entry_integer(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let value = to_u32(data, from_idx, from_idx + 3)? as i32;
+    debug!("found integer; value={value}");
+
+    Ok((ConstantPoolEntry::Integer(value), from_idx + 4))
+}
+
+fn read_constant_pool_entry_float(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    // 4 bytes with a 32-bit single-precision IEEE 754 floating-point number
+    let value = f32::from_bits(to_u32(data, from_idx, from_idx + 3)?);
+    debug!("found float; value={value}");
+
+    Ok((ConstantPoolEntry::Float(value), from_idx + 4))
+}
+
+fn read_constant_pool_entry_long(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let high_bytes = u64::from(to_u32(data, from_idx, from_idx + 3)?);
+    let low_bytes = u64::from(to_u32(data, from_idx + 4, from_idx + 7)?);
+    let value = ((high_bytes << 32) | low_bytes) as i64;
+    debug!("found long; value={value}");
+
+    Ok((ConstantPoolEntry::Long(value), from_idx + 8))
+}
+
+fn read_constant_pool_entry_double(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    // 8 bytes with a 64-bit double-precision IEEE 754 floating-point number
+    let high_bytes = u64::from(to_u32(data, from_idx, from_idx + 3)?);
+    let low_bytes = u64::from(to_u32(data, from_idx + 4, from_idx + 7)?);
+    let value = f64::from_bits((high_bytes << 32) | low_bytes);
+    debug!("found double; value={value}");
+
+    Ok((ConstantPoolEntry::Double(value), from_idx + 8))
+}
+
+fn read_constant_pool_entry_method_handle(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let reference_kind = to_u8(data, from_idx)?;
+    let reference_ref = to_u16(data, from_idx + 1, from_idx + 2)?;
+    debug!("found method handle; reference_kind={reference_kind}, reference_ref={reference_ref}");
+
+    match ReferenceKind::from_u8(reference_kind) {
+        Some(reference_kind) => {
+            let method_handle = MethodHandle {
+                reference_kind,
+                reference_ref,
+            };
+            Ok((ConstantPoolEntry::MethodHandle(method_handle), from_idx + 3))
+        }
+        None => {
+            dbg!(reference_kind);
+            Err(ClassFileError::InvalidConstantPoolContent)
+        }
+    }
+}
+
+fn read_constant_pool_entry_method_type(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let descriptor_ref = to_u16(data, from_idx, from_idx + 1)?;
+    debug!("found method type; descriptor_ref={descriptor_ref}");
+
+    Ok((ConstantPoolEntry::MethodType(descriptor_ref), from_idx + 2))
+}
+
+fn read_constant_pool_entry_module(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let name_ref = to_u16(data, from_idx, from_idx + 1)?;
+    debug!("found module; name_ref={name_ref}");
+
+    Ok((ConstantPoolEntry::Module(name_ref), from_idx + 2))
+}
+
+fn read_constant_pool_entry_package(
+    data: &[u8],
+    from_idx: usize,
+) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
+    let name_ref = to_u16(data, from_idx, from_idx + 1)?;
+    debug!("found package; name_ref={name_ref}");
+
+    Ok((ConstantPoolEntry::Package(name_ref), from_idx + 2))
+}
+
+fn read_dynamic_ref(data: &[u8], from_idx: usize) -> Result<DynamicRef, ClassFileError> {
+    let bootstrap_method_attr_idx = to_u16(data, from_idx, from_idx + 1)?;
+    let name_type_ref = to_u16(data, from_idx + 2, from_idx + 3)?;
+    debug!(
+        "found dynamic ref; bootstrap_method_attr_idx={bootstrap_method_attr_idx}, name_type_ref={name_type_ref}"
+    );
+
+    Ok(DynamicRef {
+        bootstrap_method_attr_idx,
+        name_type_ref,
+    })
+}
+
+/// Decodes the modified UTF-8 of the constant pool (JVMS 4.4.7), which writes the null character in two bytes and
+/// characters outside the Basic Multilingual Plane as surrogate pairs of three bytes each. Unpaired surrogates, which
+/// can't be held in a Rust string, become replacement characters.
+fn decode_modified_utf8(content: &[u8]) -> Option<String> {
+    if let Ok(value) = str::from_utf8(content) {
+        return Some(value.to_string());
+    }
+    let continuation = |idx: usize| {
+        content
+            .get(idx)
+            .filter(|byte| *byte & 0xc0 == 0x80)
+            .map(|byte| u16::from(byte & 0x3f))
+    };
+    let mut units = Vec::with_capacity(content.len());
+    let mut idx = 0;
+    while idx < content.len() {
+        let byte = u16::from(content[idx]);
+        let (unit, length) = match content[idx] {
+            0x00..=0x7f => (byte, 1),
+            0xc0..=0xdf => ((byte & 0x1f) << 6 | continuation(idx + 1)?, 2),
+            0xe0..=0xef => (
+                (byte & 0x0f) << 12 | continuation(idx + 1)? << 6 | continuation(idx + 2)?,
+                3,
+            ),
+            _ => return None,
+        };
+        units.push(unit);
+        idx += length;
+    }
+    Some(String::from_utf16_lossy(&units))
+}
+
+/// Encodes a string in modified UTF-8, which only differs from UTF-8 for the null character and characters outside
+/// the Basic Multilingual Plane.
+fn encode_modified_utf8(value: &str) -> Cow<'_, [u8]> {
+    if !value.chars().any(|c| c == '\0' || c.len_utf8() == 4) {
+        return Cow::Borrowed(value.as_bytes());
+    }
+    let mut bytes = Vec::with_capacity(value.len() + 2);
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7f => bytes.push(unit as u8),
+            0x00 | 0x80..=0x7ff => {
+                bytes.push(0xc0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                bytes.push(0xe0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    Cow::Owned(bytes)
+}
 
 fn read_constant_pool_entry_string(
     data: &[u8],
     from_idx: usize,
 ) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
-    let size = to_u16(data, from_idx, from_idx + 1);
-    let content = &data[from_idx + 2..from_idx + 2 + usize::from(size)];
-    match str::from_utf8(content) {
-        Ok(value) => {
+    let size = to_u16(data, from_idx, from_idx + 1)?;
+    let content = to_bytes(data, from_idx + 2, usize::from(size))?;
+    match decode_modified_utf8(content) {
+        Some(value) => {
             debug!("found string; value={value}");
             Ok((
-                ConstantPoolEntry::String(value.to_string()),
+                ConstantPoolEntry::String(value),
                 from_idx + 2 + usize::from(size),
             ))
         }
-        Err(error) => {
-            dbg!(error);
+        None => {
+            error!("invalid modified UTF-8 in string; content={content:?}");
             Err(ClassFileError::InvalidConstantPoolContent)
         }
     }
@@ -165,21 +537,36 @@ fn read_constant_pool_entry(
     data: &[u8],
     from_idx: usize,
 ) -> Result<(ConstantPoolEntry, usize), ClassFileError> {
-    let tag = usize::from(data[from_idx]);
+    let tag = usize::from(to_u8(data, from_idx)?);
     // debug!("next constant pool entry; tag={tag}, from_idx={from_idx}");
     match tag {
         TAG_STRING => read_constant_pool_entry_string(data, from_idx + 1),
-        // TAG_INTEGER => Ok((ConstantPoolEntry {}, from_idx + 4)),
-        // TAG_FLOAT => read_constant_pool_entry_float(data, from_idx + 1),
-        // TAG_LONG => Ok((ConstantPoolEntry {}, from_idx + 8)),
-        // TAG_DOUBLE => Ok((ConstantPoolEntry {}, from_idx + 8)),
+        TAG_INTEGER => read_constant_pool_entry_integer(data, from_idx + 1),
+        TAG_FLOAT => read_constant_pool_entry_float(data, from_idx + 1),
+        TAG_LONG => read_constant_pool_entry_long(data, from_idx + 1),
+        TAG_DOUBLE => read_constant_pool_entry_double(data, from_idx + 1),
         TAG_CLASS_REF => read_constant_pool_entry_class_ref(data, from_idx + 1),
-        // TAG_STRING_REF => Ok((ConstantPoolEntry {}, from_idx + 2)),
-        // TAG_FIELD_REF => Ok((ConstantPoolEntry {}, from_idx + 4)),
+        TAG_STRING_REF => read_constant_pool_entry_string_ref(data, from_idx + 1),
+        TAG_FIELD_REF => read_constant_pool_entry_field_ref(data, from_idx + 1),
         TAG_METHOD_REF => read_constant_pool_entry_method_ref(data, from_idx + 1),
+        TAG_INTERFACE_METHOD_REF => {
+            read_constant_pool_entry_interface_method_ref(data, from_idx + 1)
+        }
         TAG_NAME_TYPE_DESCRIPTOR => {
             read_constant_pool_entry_name_type_descriptor(data, from_idx + 1)
         }
+        TAG_METHOD_HANDLE => read_constant_pool_entry_method_handle(data, from_idx + 1),
+        TAG_METHOD_TYPE => read_constant_pool_entry_method_type(data, from_idx + 1),
+        TAG_DYNAMIC => Ok((
+            ConstantPoolEntry::Dynamic(read_dynamic_ref(data, from_idx + 1)?),
+            from_idx + 5,
+        )),
+        TAG_INVOKE_DYNAMIC => Ok((
+            ConstantPoolEntry::InvokeDynamic(read_dynamic_ref(data, from_idx + 1)?),
+            from_idx + 5,
+        )),
+        TAG_MODULE => read_constant_pool_entry_module(data, from_idx + 1),
+        TAG_PACKAGE => read_constant_pool_entry_package(data, from_idx + 1),
         _ => {
             debug!("unknown constant pool entry; tag={tag}");
             Err(ClassFileError::UnknownConstantPoolEntryTag)
@@ -188,19 +575,154 @@ fn read_constant_pool_entry(
 }
 
 pub fn read_constant_pool(data: &[u8]) -> Result<(ConstantPool, usize), ClassFileError> {
-    let pool_size = u16::from_be_bytes([data[8], data[9]]);
+    let pool_size = to_u16(data, 8, 9)?;
+    if pool_size == 0 {
+        error!("constant pool count must be at least 1");
+        return Err(ClassFileError::InvalidConstantPoolContent);
+    }
     debug!("start reading constant pool; expected_size={pool_size}");
 
     let mut items: Vec<ConstantPoolEntry> = Vec::with_capacity(usize::from(pool_size - 1));
+    let mut raw_strings = HashMap::new();
     let mut from_idx = 10;
 
-    for _ in 1..pool_size {
+    while items.len() < usize::from(pool_size - 1) {
         // nah, nasty one: reading a variable number of bytes here
         // what is going to be the starting point for the next item?!
         let item = read_constant_pool_entry(data, from_idx)?;
+        let takes_two_slots = matches!(
+            item.0,
+            ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_)
+        );
+        if let ConstantPoolEntry::String(value) = &item.0 {
+            let content = to_bytes(data, from_idx + 3, item.1 - from_idx - 3)?;
+            if *encode_modified_utf8(value) != *content {
+                raw_strings.insert(items.len() as u16 + 1, content.to_vec());
+            }
+        }
         items.push(item.0);
+        if takes_two_slots {
+            // "In retrospect, making 8-byte constants take two constant pool entries was a poor choice."
+            items.push(ConstantPoolEntry::Empty());
+        }
         from_idx = item.1;
     }
 
-    Ok((ConstantPool { items }, from_idx))
+    Ok((ConstantPool { items, raw_strings }, from_idx))
+}
+
+/// Writes an entry, with the bytes it was read from if it is a String entry that can't be written otherwise.
+fn write_constant_pool_entry(
+    entry: &ConstantPoolEntry,
+    raw_string: Option<&[u8]>,
+    out: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    match entry {
+        ConstantPoolEntry::String(value) => {
+            let bytes = match raw_string {
+                Some(raw_string) => Cow::Borrowed(raw_string),
+                None => encode_modified_utf8(value),
+            };
+            let Ok(length) = u16::try_from(bytes.len()) else {
+                error!(
+                    "String of {} bytes is too long for the constant pool",
+                    bytes.len()
+                );
+                return Err(ClassFileError::InvalidConstantPoolContent);
+            };
+            out.push(TAG_STRING as u8);
+            write_u16(out, length);
+            out.extend_from_slice(&bytes);
+        }
+        ConstantPoolEntry::Integer(value) => {
+            out.push(TAG_INTEGER as u8);
+            write_u32(out, *value as u32);
+        }
+        ConstantPoolEntry::Float(value) => {
+            out.push(TAG_FLOAT as u8);
+            write_u32(out, value.to_bits());
+        }
+        ConstantPoolEntry::Long(value) => {
+            out.push(TAG_LONG as u8);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+        ConstantPoolEntry::Double(value) => {
+            out.push(TAG_DOUBLE as u8);
+            out.extend_from_slice(&value.to_bits().to_be_bytes());
+        }
+        ConstantPoolEntry::ClassRef(name_ref) => {
+            out.push(TAG_CLASS_REF as u8);
+            write_u16(out, *name_ref);
+        }
+        ConstantPoolEntry::StringRef(string_ref) => {
+            out.push(TAG_STRING_REF as u8);
+            write_u16(out, *string_ref);
+        }
+        ConstantPoolEntry::FieldRef(field_ref) => {
+            out.push(TAG_FIELD_REF as u8);
+            write_u16(out, field_ref.class_ref);
+            write_u16(out, field_ref.name_type_ref);
+        }
+        ConstantPoolEntry::MethodRef(method_ref) => {
+            out.push(TAG_METHOD_REF as u8);
+            write_u16(out, method_ref.class_ref);
+            write_u16(out, method_ref.name_type_ref);
+        }
+        ConstantPoolEntry::InterfaceMethodRef(method_ref) => {
+            out.push(TAG_INTERFACE_METHOD_REF as u8);
+            write_u16(out, method_ref.class_ref);
+            write_u16(out, method_ref.name_type_ref);
+        }
+        ConstantPoolEntry::NameTypeDescriptor(name_type) => {
+            out.push(TAG_NAME_TYPE_DESCRIPTOR as u8);
+            write_u16(out, name_type.name_ref);
+            write_u16(out, name_type.type_descriptor_ref);
+        }
+        ConstantPoolEntry::MethodHandle(method_handle) => {
+            out.push(TAG_METHOD_HANDLE as u8);
+            out.push(method_handle.reference_kind as u8);
+            write_u16(out, method_handle.reference_ref);
+        }
+        ConstantPoolEntry::MethodType(descriptor_ref) => {
+            out.push(TAG_METHOD_TYPE as u8);
+            write_u16(out, *descriptor_ref);
+        }
+        ConstantPoolEntry::Dynamic(dynamic) | ConstantPoolEntry::InvokeDynamic(dynamic) => {
+            let tag = match entry {
+                ConstantPoolEntry::Dynamic(_) => TAG_DYNAMIC,
+                _ => TAG_INVOKE_DYNAMIC,
+            };
+            out.push(tag as u8);
+            write_u16(out, dynamic.bootstrap_method_attr_idx);
+            write_u16(out, dynamic.name_type_ref);
+        }
+        ConstantPoolEntry::Module(name_ref) => {
+            out.push(TAG_MODULE as u8);
+            write_u16(out, *name_ref);
+        }
+        ConstantPoolEntry::Package(name_ref) => {
+            out.push(TAG_PACKAGE as u8);
+            write_u16(out, *name_ref);
+        }
+        // The slot after a long or double is not written.
+        ConstantPoolEntry::Empty() => {}
+    }
+    Ok(())
+}
+
+/// Writes `constant_pool_count` followed by the entries of the constant pool.
+pub fn write_constant_pool(
+    constant_pool: &ConstantPool,
+    out: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    let Ok(pool_size) = u16::try_from(constant_pool.items.len() + 1) else {
+        error!("Constant pool has too many entries");
+        return Err(ClassFileError::InvalidConstantPoolContent);
+    };
+    write_u16(out, pool_size);
+    for (position, entry) in constant_pool.items.iter().enumerate() {
+        let raw_string = constant_pool.raw_string(position as u16 + 1);
+        write_constant_pool_entry(entry, raw_string, out)?;
+    }
+    Ok(())
 }
@@ -1,58 +1,118 @@
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ConstantPoolEntry {
     /// An entry holding a `String`.
     String(String),
     /// An entry holding an `int`.
-    Integer(),
+    Integer(i32),
     /// An entry holding a `float`.
-    Float(),
+    Float(f32),
     /// An entry holding a `long`.
-    Long(),
+    Long(i64),
     /// An entry holding a `double`.
-    Double(),
+    Double(f64),
     /// An entry holding a reference to a class. Points to a String entry holding the name of the class.
     ClassRef(u16),
     /// An entry holding a reference to a [`String`] entry.
-    StringRef(),
+    StringRef(u16),
     /// An entry holding a reference to a field in a class.
-    FieldRef(),
+    FieldRef(FieldRef),
     /// An entry holding a reference to a method in a class.
     MethodRef(MethodRef),
     /// An entry holding a reference to a method in an interface.
-    InterfaceMethodRef(),
+    InterfaceMethodRef(MethodRef),
     /// An entry describing a name and a type.
     NameTypeDescriptor(NameTypeDescriptor),
     /// An entry holding a method handle.
-    MethodHandle(),
-    /// An entry holding a type description of a method.
-    MethodType(),
+    MethodHandle(MethodHandle),
+    /// An entry holding a type description of a method. Points to a String entry holding the descriptor.
+    MethodType(u16),
     /// An entry holding a dynamically computed constant (produced by invocation of a bootstrap method).
-    Dynamic(),
+    Dynamic(DynamicRef),
     /// An entry holding a bootstrap method that can be used by the `invokedynamic` instruction.
-    InvokeDynamic(),
-    /// An entry holding a JPMS module identification.
-    Module(),
-    /// An entry holding a package identification.
-    Package(),
+    InvokeDynamic(DynamicRef),
+    /// An entry holding a JPMS module identification. Points to a String entry holding the name of the module.
+    Module(u16),
+    /// An entry holding a package identification. Points to a String entry holding the name of the package.
+    Package(u16),
 
     /// Placeholder for empty or unimplemented constant pool entries.
+    /// Also used for the unusable slot that follows a `long` or `double` entry.
     Empty(),
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Version {
     pub major: u16,
     pub minor: u16,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldRef {
+    pub class_ref: u16,
+    pub name_type_ref: u16,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MethodRef {
     pub class_ref: u16,
     pub name_type_ref: u16,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct NameTypeDescriptor {
     pub name_ref: u16,
     pub type_descriptor_ref: u16,
 }
+
+/// The kind of a method handle, which characterises its bytecode behaviour.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ReferenceKind {
+    GetField = 1,
+    GetStatic = 2,
+    PutField = 3,
+    PutStatic = 4,
+    InvokeVirtual = 5,
+    InvokeStatic = 6,
+    InvokeSpecial = 7,
+    NewInvokeSpecial = 8,
+    InvokeInterface = 9,
+}
+
+impl ReferenceKind {
+    pub fn from_u8(value: u8) -> Option<ReferenceKind> {
+        match value {
+            1 => Some(ReferenceKind::GetField),
+            2 => Some(ReferenceKind::GetStatic),
+            3 => Some(ReferenceKind::PutField),
+            4 => Some(ReferenceKind::PutStatic),
+            5 => Some(ReferenceKind::InvokeVirtual),
+            6 => Some(ReferenceKind::InvokeStatic),
+            7 => Some(ReferenceKind::InvokeSpecial),
+            8 => Some(ReferenceKind::NewInvokeSpecial),
+            9 => Some(ReferenceKind::InvokeInterface),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MethodHandle {
+    pub reference_kind: ReferenceKind,
+    /// Points to a FieldRef, MethodRef or InterfaceMethodRef entry, depending on the reference kind.
+    pub reference_ref: u16,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DynamicRef {
+    /// Index into the `bootstrap_methods` array of the BootstrapMethods attribute.
+    pub bootstrap_method_attr_idx: u16,
+    pub name_type_ref: u16,
+}
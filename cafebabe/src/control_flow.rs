@@ -0,0 +1,398 @@
+//! Control flow graphs of method bodies: the basic blocks of decoded bytecode and the edges between them, including
+//! the switch targets, the exception handlers that cover a block and the subroutines of `jsr` and `ret`.
+//!
+//! On top of the graph, [`ControlFlowGraph::dominators`] gives the dominator tree and [`ControlFlowGraph::loops`] the
+//! natural loops, and [`ControlFlowGraph::to_dot`] draws the graph with Graphviz.
+
+pub(crate) mod dominators;
+mod types;
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::result::Result;
+
+use log::error;
+
+pub use self::types::{BasicBlock, ControlFlowGraph, DominatorTree, Edge, EdgeKind, Loop};
+use crate::attributes::{Code, ExceptionHandler};
+use crate::bytecode::{Instruction, Opcode, Operand, array_type_name, decode};
+use crate::constant_pool::{ConstantPool, ConstantPoolEntry};
+use crate::errors::ClassFileError;
+
+/// Whether control never goes on to the next instruction after this one.
+fn ends_flow(opcode: Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::Goto
+            | Opcode::GotoW
+            | Opcode::Jsr
+            | Opcode::JsrW
+            | Opcode::Ret
+            | Opcode::Tableswitch
+            | Opcode::Lookupswitch
+            | Opcode::Ireturn
+            | Opcode::Lreturn
+            | Opcode::Freturn
+            | Opcode::Dreturn
+            | Opcode::Areturn
+            | Opcode::Return
+            | Opcode::Athrow
+    )
+}
+
+/// Whether the instruction after this one starts a block.
+fn ends_block(instruction: &Instruction) -> bool {
+    ends_flow(instruction.opcode) || matches!(instruction.operand, Operand::Branch(_))
+}
+
+/// The position of the block that starts at an offset.
+fn block_at(blocks: &[BasicBlock], offset: u32) -> Result<usize, ClassFileError> {
+    match blocks.binary_search_by_key(&offset, |block| block.start) {
+        Ok(position) => Ok(position),
+        Err(_) => {
+            error!("no instruction starts at the target offset; offset={offset}");
+            Err(ClassFileError::InvalidCode)
+        }
+    }
+}
+
+/// The edges that leave a block by its last instruction, apart from those of `ret`.
+fn flow_edges(
+    blocks: &[BasicBlock],
+    source: usize,
+    instruction: &Instruction,
+) -> Result<Vec<Edge>, ClassFileError> {
+    let edge = |target: u32, kind: EdgeKind| -> Result<Edge, ClassFileError> {
+        Ok(Edge {
+            source,
+            target: block_at(blocks, target)?,
+            kind,
+        })
+    };
+    let mut edges = Vec::new();
+    match (&instruction.operand, instruction.opcode) {
+        (Operand::Branch(target), Opcode::Jsr | Opcode::JsrW) => {
+            edges.push(edge(*target, EdgeKind::Jsr)?);
+        }
+        (Operand::Branch(target), _) => edges.push(edge(*target, EdgeKind::Branch)?),
+        (
+            Operand::TableSwitch {
+                default,
+                low,
+                targets,
+                ..
+            },
+            _,
+        ) => {
+            for (key, target) in (*low..).zip(targets) {
+                edges.push(edge(*target, EdgeKind::Switch(Some(key)))?);
+            }
+            edges.push(edge(*default, EdgeKind::Switch(None))?);
+        }
+        (Operand::LookupSwitch { default, pairs }, _) => {
+            for (key, target) in pairs {
+                edges.push(edge(*target, EdgeKind::Switch(Some(*key)))?);
+            }
+            edges.push(edge(*default, EdgeKind::Switch(None))?);
+        }
+        _ => {}
+    }
+    if !ends_flow(instruction.opcode) && source + 1 < blocks.len() {
+        edges.push(Edge {
+            source,
+            target: source + 1,
+            kind: EdgeKind::FallThrough,
+        });
+    }
+    Ok(edges)
+}
+
+/// The blocks ending in `ret` that return from the subroutine starting at a block: those reached from it without
+/// returning, where a nested `jsr` goes on after its call.
+fn subroutine_returns(graph: &ControlFlowGraph, subroutine: usize) -> Vec<usize> {
+    let mut visited = vec![false; graph.blocks.len()];
+    let mut pending = vec![subroutine];
+    let mut returns = Vec::new();
+    while let Some(block) = pending.pop() {
+        if std::mem::replace(&mut visited[block], true) {
+            continue;
+        }
+        if graph.last_instruction(block).opcode == Opcode::Ret {
+            returns.push(block);
+        }
+        for edge in graph.edges.iter().filter(|edge| edge.source == block) {
+            match edge.kind {
+                EdgeKind::FallThrough | EdgeKind::Branch | EdgeKind::Switch(_) => {
+                    pending.push(edge.target)
+                }
+                EdgeKind::Jsr if block + 1 < graph.blocks.len() => pending.push(block + 1),
+                _ => {}
+            }
+        }
+    }
+    returns.sort_unstable();
+    returns
+}
+
+/// A short text for an operand that points to the constant pool: the name of a class, the owner, name and descriptor
+/// of a member, or the value of a constant.
+fn constant_label(constant_pool: &ConstantPool, index: u16) -> String {
+    let Ok(entry) = constant_pool.checked_entry(index) else {
+        return format!("#{index}");
+    };
+    let text = match entry {
+        ConstantPoolEntry::ClassRef(_) => constant_pool.class_name(usize::from(index)).cloned(),
+        ConstantPoolEntry::FieldRef(_)
+        | ConstantPoolEntry::MethodRef(_)
+        | ConstantPoolEntry::InterfaceMethodRef(_) => constant_pool
+            .member_ref(usize::from(index))
+            .map(|(class_name, name, descriptor)| format!("{class_name}.{name}:{descriptor}")),
+        ConstantPoolEntry::StringRef(value_idx) => constant_pool
+            .string_entry(*value_idx)
+            .map(|value| format!("{value:?}")),
+        ConstantPoolEntry::Integer(value) => Ok(value.to_string()),
+        ConstantPoolEntry::Float(value) => Ok(format!("{value}f")),
+        ConstantPoolEntry::Long(value) => Ok(format!("{value}l")),
+        ConstantPoolEntry::Double(value) => Ok(format!("{value}d")),
+        ConstantPoolEntry::MethodType(descriptor_idx) => {
+            constant_pool.string_entry(*descriptor_idx).cloned()
+        }
+        ConstantPoolEntry::Dynamic(dynamic_ref) | ConstantPoolEntry::InvokeDynamic(dynamic_ref) => {
+            constant_pool
+                .name_and_type(usize::from(dynamic_ref.name_type_ref))
+                .map(|(name, descriptor)| format!("{name}:{descriptor}"))
+        }
+        _ => Err(ClassFileError::UnexpectedConstantPoolType),
+    };
+    text.unwrap_or_else(|_| format!("#{index}"))
+}
+
+fn instruction_label(instruction: &Instruction, constant_pool: &ConstantPool) -> String {
+    let mnemonic = instruction.opcode.mnemonic();
+    let operand = match &instruction.operand {
+        Operand::None => String::new(),
+        Operand::Byte(value) => value.to_string(),
+        Operand::Short(value) => value.to_string(),
+        Operand::ConstantPool(index)
+        | Operand::InvokeInterface { index, .. }
+        | Operand::MultiANewArray { index, .. } => constant_label(constant_pool, *index),
+        Operand::Local(index) => index.to_string(),
+        Operand::Iinc { index, value } => format!("{index} {value}"),
+        Operand::Branch(target) => target.to_string(),
+        Operand::ArrayType(array_type) => array_type_name(*array_type)
+            .map(str::to_string)
+            .unwrap_or_else(|| array_type.to_string()),
+        // The targets are drawn as edges.
+        Operand::TableSwitch { .. } | Operand::LookupSwitch { .. } => String::new(),
+    };
+    match operand.is_empty() {
+        true => format!("{}: {mnemonic}", instruction.offset),
+        false => format!("{}: {mnemonic} {operand}", instruction.offset),
+    }
+}
+
+/// Escapes a text for a quoted string of the DOT language.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl ControlFlowGraph {
+    /// Builds the control flow graph of the code of a method.
+    pub fn build(code: &Code) -> Result<ControlFlowGraph, ClassFileError> {
+        Self::from_instructions(decode(&code.code)?, &code.exception_table)
+    }
+
+    /// Builds a control flow graph from decoded instructions and the exception table of their code. Blocks start at
+    /// the targets of branches, after instructions that branch, return or throw, and where the range of an exception
+    /// handler starts or ends, so that a handler covers either all instructions of a block or none.
+    pub fn from_instructions(
+        instructions: Vec<Instruction>,
+        exception_table: &[ExceptionHandler],
+    ) -> Result<ControlFlowGraph, ClassFileError> {
+        let code_length = instructions.last().map_or(0, |instruction| {
+            instruction.offset + instruction.length() as u32
+        });
+        let mut leaders = BTreeSet::new();
+        if !instructions.is_empty() {
+            leaders.insert(0);
+        }
+        for handler in exception_table {
+            leaders.insert(u32::from(handler.start_pc));
+            leaders.insert(u32::from(handler.handler_pc));
+            if u32::from(handler.end_pc) < code_length {
+                leaders.insert(u32::from(handler.end_pc));
+            }
+        }
+        for instruction in &instructions {
+            leaders.extend(instruction.branch_targets());
+            let next_offset = instruction.offset + instruction.length() as u32;
+            if ends_block(instruction) && next_offset < code_length {
+                leaders.insert(next_offset);
+            }
+        }
+
+        let mut blocks: Vec<BasicBlock> = Vec::with_capacity(leaders.len());
+        for (position, instruction) in instructions.iter().enumerate() {
+            if leaders.remove(&instruction.offset) {
+                if let Some(block) = blocks.last_mut() {
+                    block.end = instruction.offset;
+                }
+                blocks.push(BasicBlock {
+                    start: instruction.offset,
+                    end: code_length,
+                    instructions: position..position,
+                });
+            }
+            if let Some(block) = blocks.last_mut() {
+                block.instructions.end = position + 1;
+            }
+        }
+        if let Some(offset) = leaders.first() {
+            error!("no instruction starts at the target offset; offset={offset}");
+            return Err(ClassFileError::InvalidCode);
+        }
+
+        let mut edges = Vec::new();
+        for (source, block) in blocks.iter().enumerate() {
+            let last_instruction = &instructions[block.instructions.end - 1];
+            edges.extend(flow_edges(&blocks, source, last_instruction)?);
+            for handler in exception_table {
+                let covered = u32::from(handler.start_pc)..u32::from(handler.end_pc);
+                if covered.contains(&block.start) {
+                    edges.push(Edge {
+                        source,
+                        target: block_at(&blocks, u32::from(handler.handler_pc))?,
+                        kind: EdgeKind::Exception(handler.catch_type),
+                    });
+                }
+            }
+        }
+        let mut graph = ControlFlowGraph {
+            instructions,
+            blocks,
+            edges,
+        };
+        graph.add_ret_edges();
+        Ok(graph)
+    }
+
+    /// Adds the edges from each `ret` to the instructions after the calls of its subroutine.
+    fn add_ret_edges(&mut self) {
+        let calls: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Jsr)
+            .map(|edge| (edge.source, edge.target))
+            .collect();
+        let mut ret_edges = Vec::new();
+        for (call, subroutine) in calls {
+            if call + 1 >= self.blocks.len() {
+                continue;
+            }
+            for ret in subroutine_returns(self, subroutine) {
+                ret_edges.push(Edge {
+                    source: ret,
+                    target: call + 1,
+                    kind: EdgeKind::Ret,
+                });
+            }
+        }
+        for edge in ret_edges {
+            // After the other edges of the block and before its exception edges.
+            let position = self
+                .edges
+                .iter()
+                .position(|other| {
+                    other.source > edge.source
+                        || (other.source == edge.source
+                            && matches!(other.kind, EdgeKind::Exception(_)))
+                })
+                .unwrap_or(self.edges.len());
+            if !self.edges[..position].contains(&edge) {
+                self.edges.insert(position, edge);
+            }
+        }
+    }
+
+    /// The instructions of a block.
+    pub fn block_instructions(&self, block: usize) -> &[Instruction] {
+        &self.instructions[self.blocks[block].instructions.clone()]
+    }
+
+    fn last_instruction(&self, block: usize) -> &Instruction {
+        &self.instructions[self.blocks[block].instructions.end - 1]
+    }
+
+    /// The position of the block that contains the instruction at an offset.
+    pub fn block_of(&self, offset: u32) -> Option<usize> {
+        let position = self.blocks.partition_point(|block| block.start <= offset);
+        position
+            .checked_sub(1)
+            .filter(|position| offset < self.blocks[*position].end)
+    }
+
+    /// The blocks that an edge from a block leads to, in the order of the edges and without duplicates.
+    pub fn successors(&self, block: usize) -> Vec<usize> {
+        let mut successors = Vec::new();
+        for edge in self.edges.iter().filter(|edge| edge.source == block) {
+            if !successors.contains(&edge.target) {
+                successors.push(edge.target);
+            }
+        }
+        successors
+    }
+
+    /// The blocks with an edge to a block, sorted.
+    pub fn predecessors(&self, block: usize) -> Vec<usize> {
+        let predecessors: BTreeSet<usize> = self
+            .edges
+            .iter()
+            .filter(|edge| edge.target == block)
+            .map(|edge| edge.source)
+            .collect();
+        predecessors.into_iter().collect()
+    }
+
+    pub fn dominators(&self) -> DominatorTree {
+        dominators::dominator_tree(self)
+    }
+
+    /// The natural loops, one for each block that edges jump back to, sorted by their header. Irreducible loops, which
+    /// are entered at more than one block, are not found, but `javac` doesn't produce them.
+    pub fn loops(&self) -> Vec<Loop> {
+        dominators::natural_loops(self, &self.dominators())
+    }
+
+    /// The graph in the DOT language of Graphviz, with a box listing the instructions of each block, where the
+    /// operands that point to the constant pool are shown by their content. Exception edges are dashed and are
+    /// labelled with the class they catch.
+    pub fn to_dot(&self, name: &str, constant_pool: &ConstantPool) -> String {
+        let mut out = format!("digraph \"{}\" {{\n", escape(name));
+        out.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for block in 0..self.blocks.len() {
+            let mut label = String::new();
+            for instruction in self.block_instructions(block) {
+                label.push_str(&escape(&instruction_label(instruction, constant_pool)));
+                label.push_str("\\l");
+            }
+            let _ = writeln!(out, "    b{block} [label=\"{label}\"];");
+        }
+        for edge in &self.edges {
+            let attributes = match edge.kind {
+                EdgeKind::FallThrough | EdgeKind::Branch => String::new(),
+                EdgeKind::Switch(Some(key)) => format!(" [label=\"{key}\"]"),
+                EdgeKind::Switch(None) => " [label=\"default\"]".to_string(),
+                EdgeKind::Exception(0) => " [style=dashed, label=\"any\"]".to_string(),
+                EdgeKind::Exception(catch_type) => format!(
+                    " [style=dashed, label=\"{}\"]",
+                    escape(&constant_label(constant_pool, catch_type))
+                ),
+                EdgeKind::Jsr => " [label=\"jsr\"]".to_string(),
+                EdgeKind::Ret => " [style=dotted, label=\"ret\"]".to_string(),
+            };
+            let _ = writeln!(out, "    b{} -> b{}{attributes};", edge.source, edge.target);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
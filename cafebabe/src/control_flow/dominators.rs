@@ -0,0 +1,202 @@
+//! Dominators with the iterative algorithm of Cooper, Harvey and Kennedy ("A Simple, Fast Dominance Algorithm"), and
+//! the natural loops that follow from them.
+
+use std::collections::BTreeMap;
+
+use super::types::{ControlFlowGraph, DominatorTree, Loop};
+
+/// The successors and the predecessors of each block, without duplicates.
+fn adjacency(graph: &ControlFlowGraph) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    let mut successors = vec![Vec::new(); graph.blocks.len()];
+    let mut predecessors = vec![Vec::new(); graph.blocks.len()];
+    for edge in &graph.edges {
+        if !successors[edge.source].contains(&edge.target) {
+            successors[edge.source].push(edge.target);
+            predecessors[edge.target].push(edge.source);
+        }
+    }
+    (successors, predecessors)
+}
+
+/// The blocks that can be reached from the entry in reverse postorder, visited depth first without recursion.
+pub(crate) fn reverse_postorder(successors: &[Vec<usize>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(successors.len());
+    if successors.is_empty() {
+        return order;
+    }
+    let mut visited = vec![false; successors.len()];
+    visited[0] = true;
+    let mut path = vec![(0, 0)];
+    while let Some((block, position)) = path.last_mut() {
+        if let Some(&successor) = successors[*block].get(*position) {
+            *position += 1;
+            if !visited[successor] {
+                visited[successor] = true;
+                path.push((successor, 0));
+            }
+            continue;
+        }
+        order.push(*block);
+        path.pop();
+    }
+    order.reverse();
+    order
+}
+
+pub(super) fn dominator_tree(graph: &ControlFlowGraph) -> DominatorTree {
+    let (successors, predecessors) = adjacency(graph);
+    dominator_tree_of(&successors, &predecessors)
+}
+
+/// The dominator tree of a graph given by the successors and the predecessors of its nodes, entered at the first node.
+pub(crate) fn dominator_tree_of(
+    successors: &[Vec<usize>],
+    predecessors: &[Vec<usize>],
+) -> DominatorTree {
+    let order = reverse_postorder(successors);
+    // The position of each reachable block in reverse postorder.
+    let mut rank = vec![usize::MAX; successors.len()];
+    for (position, block) in order.iter().enumerate() {
+        rank[*block] = position;
+    }
+
+    let mut idoms: Vec<Option<usize>> = vec![None; successors.len()];
+    if let Some(entry) = order.first() {
+        idoms[*entry] = Some(*entry);
+    }
+    let intersect = |idoms: &[Option<usize>], mut first: usize, mut second: usize| {
+        while first != second {
+            while rank[first] > rank[second] {
+                first = idoms[first].expect("processed blocks have a dominator");
+            }
+            while rank[second] > rank[first] {
+                second = idoms[second].expect("processed blocks have a dominator");
+            }
+        }
+        first
+    };
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for block in order.iter().skip(1) {
+            let mut new_idom = None;
+            for predecessor in &predecessors[*block] {
+                if idoms[*predecessor].is_none() {
+                    continue;
+                }
+                new_idom = match new_idom {
+                    None => Some(*predecessor),
+                    Some(idom) => Some(intersect(&idoms, *predecessor, idom)),
+                };
+            }
+            if new_idom.is_some() && idoms[*block] != new_idom {
+                idoms[*block] = new_idom;
+                changed = true;
+            }
+        }
+    }
+
+    let mut reachable = vec![false; successors.len()];
+    for block in &order {
+        reachable[*block] = true;
+    }
+    if let Some(entry) = order.first() {
+        idoms[*entry] = None;
+    }
+    DominatorTree {
+        immediate_dominators: idoms,
+        reachable,
+    }
+}
+
+impl DominatorTree {
+    pub fn immediate_dominator(&self, block: usize) -> Option<usize> {
+        self.immediate_dominators[block]
+    }
+
+    /// Whether every path from the entry to `block` goes through `dominator`. A reachable block dominates itself, and
+    /// an unreachable one is dominated by nothing.
+    pub fn dominates(&self, dominator: usize, block: usize) -> bool {
+        if !self.reachable[block] {
+            return false;
+        }
+        let mut current = Some(block);
+        while let Some(ancestor) = current {
+            if ancestor == dominator {
+                return true;
+            }
+            current = self.immediate_dominators[ancestor];
+        }
+        false
+    }
+
+    /// The blocks whose immediate dominator is `block`, sorted.
+    pub fn children(&self, block: usize) -> Vec<usize> {
+        (0..self.immediate_dominators.len())
+            .filter(|child| self.immediate_dominators[*child] == Some(block))
+            .collect()
+    }
+}
+
+pub(super) fn natural_loops(graph: &ControlFlowGraph, dominators: &DominatorTree) -> Vec<Loop> {
+    let (successors, predecessors) = adjacency(graph);
+    natural_loops_of(&successors, &predecessors, dominators)
+}
+
+/// The natural loops of a graph given by the successors and the predecessors of its nodes, with its dominator tree.
+pub(crate) fn natural_loops_of(
+    successors: &[Vec<usize>],
+    predecessors: &[Vec<usize>],
+    dominators: &DominatorTree,
+) -> Vec<Loop> {
+    // The latches of each header, from the edges whose target dominates their source.
+    let mut headers: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (source, targets) in successors.iter().enumerate() {
+        for target in targets {
+            if dominators.dominates(*target, source) {
+                let latches = headers.entry(*target).or_default();
+                if !latches.contains(&source) {
+                    latches.push(source);
+                }
+            }
+        }
+    }
+
+    let mut loops = Vec::with_capacity(headers.len());
+    for (header, mut latches) in headers {
+        // The blocks that reach a latch without going through the header, which all are dominated by it.
+        let mut in_loop = vec![false; successors.len()];
+        in_loop[header] = true;
+        let mut pending = latches.clone();
+        while let Some(block) = pending.pop() {
+            if !in_loop[block] {
+                in_loop[block] = true;
+                pending.extend(
+                    predecessors[block]
+                        .iter()
+                        .filter(|predecessor| dominators.dominates(header, **predecessor)),
+                );
+            }
+        }
+        latches.sort_unstable();
+        loops.push(Loop {
+            header,
+            latches,
+            blocks: (0..successors.len())
+                .filter(|block| in_loop[*block])
+                .collect(),
+            parent: None,
+        });
+    }
+
+    // The innermost enclosing loop is the smallest other loop that contains the header.
+    for position in 0..loops.len() {
+        let header = loops[position].header;
+        loops[position].parent = (0..loops.len())
+            .filter(|other| {
+                *other != position && loops[*other].blocks.binary_search(&header).is_ok()
+            })
+            .min_by_key(|other| loops[*other].blocks.len());
+    }
+    loops
+}
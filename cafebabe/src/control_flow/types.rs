@@ -0,0 +1,80 @@
+use std::ops::Range;
+
+use crate::bytecode::Instruction;
+
+/// A sequence of instructions that is only entered at its first instruction and only left after its last one, apart
+/// from exceptions.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BasicBlock {
+    /// The offset of the first instruction in the code.
+    pub start: u32,
+    /// The offset just after the last instruction in the code.
+    pub end: u32,
+    /// The positions of the instructions of the block in [`ControlFlowGraph::instructions`].
+    pub instructions: Range<usize>,
+}
+
+/// How control gets from one block to another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EdgeKind {
+    /// To the next block in the code, after an instruction that doesn't branch or a conditional branch that isn't taken.
+    FallThrough,
+    /// A `goto` or a conditional branch that is taken.
+    Branch,
+    /// A case of a `tableswitch` or `lookupswitch` by its key, or its default without a key.
+    Switch(Option<i32>),
+    /// To an exception handler that covers the block, which catches the class of a ClassRef entry, or all exceptions
+    /// for 0.
+    Exception(u16),
+    /// From a `jsr` to the subroutine it calls.
+    Jsr,
+    /// From a `ret` to the instruction after a `jsr` that called its subroutine.
+    Ret,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Edge {
+    /// The position of the block the edge leaves in [`ControlFlowGraph::blocks`].
+    pub source: usize,
+    /// The position of the block the edge enters in [`ControlFlowGraph::blocks`].
+    pub target: usize,
+    pub kind: EdgeKind,
+}
+
+/// The basic blocks of the code of a method and the edges between them. The first block is the entry.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ControlFlowGraph {
+    pub instructions: Vec<Instruction>,
+    /// The blocks in the order of their offsets.
+    pub blocks: Vec<BasicBlock>,
+    /// The edges, grouped by the block they leave, with the exception edges of a block after its other edges.
+    pub edges: Vec<Edge>,
+}
+
+/// The immediate dominator of each block of a control flow graph: the last block that every path from the entry to the
+/// block goes through.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DominatorTree {
+    /// The immediate dominator by the position of each block, or `None` for the entry and for unreachable blocks.
+    pub immediate_dominators: Vec<Option<usize>>,
+    /// Whether each block can be reached from the entry.
+    pub reachable: Vec<bool>,
+}
+
+/// A natural loop: a header block that dominates the other blocks of the loop, and the blocks that jump back to it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Loop {
+    pub header: usize,
+    /// The blocks with an edge back to the header, sorted.
+    pub latches: Vec<usize>,
+    /// All blocks of the loop, including the header and the blocks of nested loops, sorted.
+    pub blocks: Vec<usize>,
+    /// The position of the innermost loop that contains this loop, in the list of loops.
+    pub parent: Option<usize>,
+}
@@ -0,0 +1,238 @@
+//! Data-flow analyses over the control flow graph of a method: a generic engine that iterates an [`Analysis`] over the
+//! blocks of the graph until its states no longer change, forward or backward, and analyses built on it.
+//!
+//! [`Frames`] simulates the local variables and the operand stack for an [`Interpreter`] that gives meaning to the
+//! values, like [`ConstantPropagation`] and [`NullnessAnalysis`]. [`ReachingDefinitions`] and [`Liveness`] only
+//! follow the local variables.
+//!
+//! An exception handler joins the states before each instruction of the blocks it covers, as any of them may throw.
+//! The edges of `jsr` and `ret` are followed like the others, without telling the calls of a subroutine apart.
+
+mod constants;
+mod frames;
+mod locals;
+mod nullness;
+mod types;
+
+use std::collections::BTreeSet;
+use std::result::Result;
+
+pub use self::constants::ConstantPropagation;
+pub use self::frames::{Frames, Interpreter};
+pub use self::locals::{Liveness, ReachingDefinitions};
+pub use self::nullness::NullnessAnalysis;
+pub use self::types::{Constant, Definition, Direction, Frame, Nullness, Solution, ValueKind};
+use crate::bytecode::Instruction;
+use crate::control_flow::{ControlFlowGraph, Edge, EdgeKind};
+use crate::errors::ClassFileError;
+
+/// A data-flow analysis: a lattice of states with a join, and how instructions and edges change states.
+pub trait Analysis {
+    type State: Clone + PartialEq;
+
+    fn direction(&self) -> Direction;
+
+    /// The state of points that the analysis hasn't reached, which joining with another state gives the other state.
+    fn bottom(&self) -> Self::State;
+
+    /// The state at the entry of the method for a forward analysis, or after the instructions that leave the method
+    /// for a backward analysis.
+    fn boundary(&self) -> Self::State;
+
+    /// Joins another state into a state where control flow meets, and tells whether the state changed.
+    fn join(&self, state: &mut Self::State, other: &Self::State) -> Result<bool, ClassFileError>;
+
+    /// Changes a state by an instruction, in the direction of the analysis.
+    fn transfer(
+        &self,
+        instruction: &Instruction,
+        state: &mut Self::State,
+    ) -> Result<(), ClassFileError>;
+
+    /// Changes a state along an edge, in the direction of the analysis, e.g. to learn from the condition of a
+    /// branch. Does nothing by default.
+    fn transfer_edge(&self, _graph: &ControlFlowGraph, _edge: &Edge, _state: &mut Self::State) {}
+}
+
+/// The positions of the edges that leave each block.
+fn outgoing_edges(graph: &ControlFlowGraph) -> Vec<Vec<usize>> {
+    let mut outgoing = vec![Vec::new(); graph.blocks.len()];
+    for (position, edge) in graph.edges.iter().enumerate() {
+        outgoing[edge.source].push(position);
+    }
+    outgoing
+}
+
+/// Runs an analysis over a control flow graph until its states no longer change.
+pub fn solve<A: Analysis>(
+    graph: &ControlFlowGraph,
+    analysis: &A,
+) -> Result<Solution<A::State>, ClassFileError> {
+    let mut solution = Solution {
+        entry: vec![analysis.bottom(); graph.blocks.len()],
+        exit: vec![analysis.bottom(); graph.blocks.len()],
+    };
+    if graph.blocks.is_empty() {
+        return Ok(solution);
+    }
+    let outgoing = outgoing_edges(graph);
+    match analysis.direction() {
+        Direction::Forward => solve_forward(graph, analysis, &outgoing, &mut solution)?,
+        Direction::Backward => solve_backward(graph, analysis, &outgoing, &mut solution)?,
+    }
+    Ok(solution)
+}
+
+/// The blocks of a forward analysis that wait to be visited, or visited again as their entry changed.
+struct Worklist {
+    visited: Vec<bool>,
+    pending: BTreeSet<usize>,
+}
+
+impl Worklist {
+    /// Joins the state that leaves a block along an edge into the entry of the block the edge enters.
+    fn enter<A: Analysis>(
+        &mut self,
+        graph: &ControlFlowGraph,
+        analysis: &A,
+        edge: &Edge,
+        state: &A::State,
+        solution: &mut Solution<A::State>,
+    ) -> Result<(), ClassFileError> {
+        let mut leaving = state.clone();
+        analysis.transfer_edge(graph, edge, &mut leaving);
+        if analysis.join(&mut solution.entry[edge.target], &leaving)? || !self.visited[edge.target]
+        {
+            self.pending.insert(edge.target);
+        }
+        Ok(())
+    }
+}
+
+fn solve_forward<A: Analysis>(
+    graph: &ControlFlowGraph,
+    analysis: &A,
+    outgoing: &[Vec<usize>],
+    solution: &mut Solution<A::State>,
+) -> Result<(), ClassFileError> {
+    solution.entry[0] = analysis.boundary();
+    let mut worklist = Worklist {
+        visited: vec![false; graph.blocks.len()],
+        pending: BTreeSet::from([0]),
+    };
+    while let Some(block) = worklist.pending.pop_first() {
+        worklist.visited[block] = true;
+        let (thrown, edges): (Vec<&Edge>, Vec<&Edge>) = outgoing[block]
+            .iter()
+            .map(|position| &graph.edges[*position])
+            .partition(|edge| matches!(edge.kind, EdgeKind::Exception(_)));
+        let mut state = solution.entry[block].clone();
+        for instruction in graph.block_instructions(block) {
+            for edge in &thrown {
+                worklist.enter(graph, analysis, edge, &state, solution)?;
+            }
+            analysis.transfer(instruction, &mut state)?;
+        }
+        for edge in &edges {
+            worklist.enter(graph, analysis, edge, &state, solution)?;
+        }
+        solution.exit[block] = state;
+    }
+    Ok(())
+}
+
+/// The joined states that enter a block from its successors, without and with exceptions, in a backward analysis.
+/// Blocks without successors other than exception handlers start from the boundary.
+fn backward_exit<A: Analysis>(
+    graph: &ControlFlowGraph,
+    analysis: &A,
+    outgoing: &[Vec<usize>],
+    solution: &Solution<A::State>,
+    block: usize,
+) -> Result<(A::State, A::State), ClassFileError> {
+    let mut state = analysis.bottom();
+    let mut thrown = analysis.bottom();
+    let mut leaves = true;
+    for edge in outgoing[block]
+        .iter()
+        .map(|position| &graph.edges[*position])
+    {
+        let mut entering = solution.entry[edge.target].clone();
+        analysis.transfer_edge(graph, edge, &mut entering);
+        match edge.kind {
+            EdgeKind::Exception(_) => analysis.join(&mut thrown, &entering)?,
+            _ => {
+                leaves = false;
+                analysis.join(&mut state, &entering)?
+            }
+        };
+    }
+    if leaves {
+        state = analysis.boundary();
+    }
+    Ok((state, thrown))
+}
+
+fn solve_backward<A: Analysis>(
+    graph: &ControlFlowGraph,
+    analysis: &A,
+    outgoing: &[Vec<usize>],
+    solution: &mut Solution<A::State>,
+) -> Result<(), ClassFileError> {
+    let mut incoming = vec![Vec::new(); graph.blocks.len()];
+    for edge in &graph.edges {
+        incoming[edge.target].push(edge.source);
+    }
+    let mut visited = vec![false; graph.blocks.len()];
+    let mut pending: BTreeSet<usize> = (0..graph.blocks.len()).collect();
+    while let Some(block) = pending.pop_last() {
+        let (mut state, thrown) = backward_exit(graph, analysis, outgoing, solution, block)?;
+        solution.exit[block] = state.clone();
+        for instruction in graph.block_instructions(block).iter().rev() {
+            analysis.transfer(instruction, &mut state)?;
+            analysis.join(&mut state, &thrown)?;
+        }
+        if state != solution.entry[block] || !visited[block] {
+            visited[block] = true;
+            pending.extend(&incoming[block]);
+            solution.entry[block] = state;
+        }
+    }
+    Ok(())
+}
+
+impl<S: Clone + PartialEq> Solution<S> {
+    /// The states at every point of a block, from before its first instruction to after its last one, for the
+    /// analysis that gave this solution.
+    pub fn block_states<A: Analysis<State = S>>(
+        &self,
+        graph: &ControlFlowGraph,
+        analysis: &A,
+        block: usize,
+    ) -> Result<Vec<S>, ClassFileError> {
+        let instructions = graph.block_instructions(block);
+        let mut states = Vec::with_capacity(instructions.len() + 1);
+        match analysis.direction() {
+            Direction::Forward => {
+                let mut state = self.entry[block].clone();
+                for instruction in instructions {
+                    states.push(state.clone());
+                    analysis.transfer(instruction, &mut state)?;
+                }
+                states.push(state);
+            }
+            Direction::Backward => {
+                let outgoing = outgoing_edges(graph);
+                let (mut state, thrown) = backward_exit(graph, analysis, &outgoing, self, block)?;
+                for instruction in instructions.iter().rev() {
+                    states.push(state.clone());
+                    analysis.transfer(instruction, &mut state)?;
+                    analysis.join(&mut state, &thrown)?;
+                }
+                states.push(state);
+                states.reverse();
+            }
+        }
+        Ok(states)
+    }
+}
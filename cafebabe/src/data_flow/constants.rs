@@ -0,0 +1,229 @@
+use std::cmp::Ordering;
+
+use super::frames::Interpreter;
+use super::types::{Constant, ValueKind};
+use crate::bytecode::{Instruction, Opcode, Operand};
+use crate::constant_pool::{ConstantPool, ConstantPoolEntry};
+
+impl PartialEq for Constant {
+    fn eq(&self, other: &Constant) -> bool {
+        match (self, other) {
+            (Constant::Unknown, Constant::Unknown) | (Constant::Null, Constant::Null) => true,
+            (Constant::Int(value), Constant::Int(other)) => value == other,
+            (Constant::Long(value), Constant::Long(other)) => value == other,
+            (Constant::Float(value), Constant::Float(other)) => value.to_bits() == other.to_bits(),
+            (Constant::Double(value), Constant::Double(other)) => {
+                value.to_bits() == other.to_bits()
+            }
+            (Constant::String(value), Constant::String(other)) => value == other,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Constant {}
+
+/// Constant propagation: which values are the same constant whenever control reaches an instruction. Arithmetic,
+/// conversions and comparisons of constants are folded like the Java Virtual Machine computes them.
+pub struct ConstantPropagation<'a> {
+    constant_pool: &'a ConstantPool,
+}
+
+impl<'a> ConstantPropagation<'a> {
+    /// The analysis of methods of a class with the constant pool, for the constants that `ldc` loads.
+    pub fn new(constant_pool: &'a ConstantPool) -> ConstantPropagation<'a> {
+        ConstantPropagation { constant_pool }
+    }
+
+    fn loaded_constant(&self, index: u16) -> Option<Constant> {
+        match self.constant_pool.checked_entry(index).ok()? {
+            ConstantPoolEntry::Integer(value) => Some(Constant::Int(*value)),
+            ConstantPoolEntry::Float(value) => Some(Constant::Float(*value)),
+            ConstantPoolEntry::Long(value) => Some(Constant::Long(*value)),
+            ConstantPoolEntry::Double(value) => Some(Constant::Double(*value)),
+            ConstantPoolEntry::StringRef(value_idx) => self
+                .constant_pool
+                .string_entry(*value_idx)
+                .ok()
+                .map(|value| Constant::String(value.clone())),
+            _ => None,
+        }
+    }
+}
+
+fn compare<T: PartialOrd>(value: T, other: T, nan: i32) -> Constant {
+    Constant::Int(match value.partial_cmp(&other) {
+        Some(Ordering::Less) => -1,
+        Some(Ordering::Equal) => 0,
+        Some(Ordering::Greater) => 1,
+        None => nan,
+    })
+}
+
+/// The constant an instruction pushes, or `None` if it isn't one.
+fn fold(instruction: &Instruction, operands: &[Constant]) -> Option<Constant> {
+    use Constant::{Double, Float, Int, Long, Null};
+
+    let opcode = instruction.opcode;
+    let constant = match (opcode, operands) {
+        (Opcode::AconstNull, _) => Null,
+        (
+            Opcode::IconstM1
+            | Opcode::Iconst0
+            | Opcode::Iconst1
+            | Opcode::Iconst2
+            | Opcode::Iconst3
+            | Opcode::Iconst4
+            | Opcode::Iconst5,
+            _,
+        ) => Int(opcode as i32 - Opcode::Iconst0 as i32),
+        (Opcode::Lconst0 | Opcode::Lconst1, _) => Long(opcode as i64 - Opcode::Lconst0 as i64),
+        (Opcode::Fconst0 | Opcode::Fconst1 | Opcode::Fconst2, _) => {
+            Float((opcode as u8 - Opcode::Fconst0 as u8).into())
+        }
+        (Opcode::Dconst0 | Opcode::Dconst1, _) => {
+            Double((opcode as u8 - Opcode::Dconst0 as u8).into())
+        }
+        (Opcode::Bipush, _) => match instruction.operand {
+            Operand::Byte(value) => Int(value.into()),
+            _ => return None,
+        },
+        (Opcode::Sipush, _) => match instruction.operand {
+            Operand::Short(value) => Int(value.into()),
+            _ => return None,
+        },
+        (Opcode::Iinc, [Int(value)]) => match instruction.operand {
+            Operand::Iinc {
+                value: increment, ..
+            } => Int(value.wrapping_add(increment.into())),
+            _ => return None,
+        },
+        (_, [Int(value), Int(other)]) => Int(match opcode {
+            Opcode::Iadd => value.wrapping_add(*other),
+            Opcode::Isub => value.wrapping_sub(*other),
+            Opcode::Imul => value.wrapping_mul(*other),
+            Opcode::Idiv if *other != 0 => value.wrapping_div(*other),
+            Opcode::Irem if *other != 0 => value.wrapping_rem(*other),
+            Opcode::Ishl => value.wrapping_shl(*other as u32),
+            Opcode::Ishr => value.wrapping_shr(*other as u32),
+            Opcode::Iushr => (*value as u32).wrapping_shr(*other as u32) as i32,
+            Opcode::Iand => value & other,
+            Opcode::Ior => value | other,
+            Opcode::Ixor => value ^ other,
+            _ => return None,
+        }),
+        (_, [Long(value), Int(other)]) => Long(match opcode {
+            Opcode::Lshl => value.wrapping_shl(*other as u32),
+            Opcode::Lshr => value.wrapping_shr(*other as u32),
+            Opcode::Lushr => (*value as u64).wrapping_shr(*other as u32) as i64,
+            _ => return None,
+        }),
+        (Opcode::Lcmp, [Long(value), Long(other)]) => compare(value, other, 0),
+        (_, [Long(value), Long(other)]) => Long(match opcode {
+            Opcode::Ladd => value.wrapping_add(*other),
+            Opcode::Lsub => value.wrapping_sub(*other),
+            Opcode::Lmul => value.wrapping_mul(*other),
+            Opcode::Ldiv if *other != 0 => value.wrapping_div(*other),
+            Opcode::Lrem if *other != 0 => value.wrapping_rem(*other),
+            Opcode::Land => value & other,
+            Opcode::Lor => value | other,
+            Opcode::Lxor => value ^ other,
+            _ => return None,
+        }),
+        (Opcode::Fcmpl, [Float(value), Float(other)]) => compare(value, other, -1),
+        (Opcode::Fcmpg, [Float(value), Float(other)]) => compare(value, other, 1),
+        (_, [Float(value), Float(other)]) => Float(match opcode {
+            Opcode::Fadd => value + other,
+            Opcode::Fsub => value - other,
+            Opcode::Fmul => value * other,
+            Opcode::Fdiv => value / other,
+            Opcode::Frem => value % other,
+            _ => return None,
+        }),
+        (Opcode::Dcmpl, [Double(value), Double(other)]) => compare(value, other, -1),
+        (Opcode::Dcmpg, [Double(value), Double(other)]) => compare(value, other, 1),
+        (_, [Double(value), Double(other)]) => Double(match opcode {
+            Opcode::Dadd => value + other,
+            Opcode::Dsub => value - other,
+            Opcode::Dmul => value * other,
+            Opcode::Ddiv => value / other,
+            Opcode::Drem => value % other,
+            _ => return None,
+        }),
+        // Conversions to integers saturate and turn NaN into 0, in Rust as in Java.
+        (_, [Int(value)]) => match opcode {
+            Opcode::Ineg => Int(value.wrapping_neg()),
+            Opcode::I2l => Long((*value).into()),
+            Opcode::I2f => Float(*value as f32),
+            Opcode::I2d => Double((*value).into()),
+            Opcode::I2b => Int((*value as i8).into()),
+            Opcode::I2c => Int((*value as u16).into()),
+            Opcode::I2s => Int((*value as i16).into()),
+            _ => return None,
+        },
+        (_, [Long(value)]) => match opcode {
+            Opcode::Lneg => Long(value.wrapping_neg()),
+            Opcode::L2i => Int(*value as i32),
+            Opcode::L2f => Float(*value as f32),
+            Opcode::L2d => Double(*value as f64),
+            _ => return None,
+        },
+        (_, [Float(value)]) => match opcode {
+            Opcode::Fneg => Float(-value),
+            Opcode::F2i => Int(*value as i32),
+            Opcode::F2l => Long(*value as i64),
+            Opcode::F2d => Double((*value).into()),
+            _ => return None,
+        },
+        (_, [Double(value)]) => match opcode {
+            Opcode::Dneg => Double(-value),
+            Opcode::D2i => Int(*value as i32),
+            Opcode::D2l => Long(*value as i64),
+            Opcode::D2f => Float(*value as f32),
+            _ => return None,
+        },
+        // A cast that doesn't throw gives the same value.
+        (Opcode::Checkcast, [value]) => value.clone(),
+        (Opcode::Instanceof, [Null]) => Int(0),
+        _ => return None,
+    };
+    Some(constant)
+}
+
+impl Interpreter for ConstantPropagation<'_> {
+    type Value = Constant;
+
+    fn unset(&self) -> Constant {
+        Constant::Unknown
+    }
+
+    fn parameter(&self, _kind: ValueKind) -> Constant {
+        Constant::Unknown
+    }
+
+    fn operation(
+        &self,
+        instruction: &Instruction,
+        operands: &[Constant],
+        _kind: ValueKind,
+    ) -> Constant {
+        let constant = match (instruction.opcode, &instruction.operand) {
+            (Opcode::Ldc | Opcode::LdcW | Opcode::Ldc2W, Operand::ConstantPool(index)) => {
+                self.loaded_constant(*index)
+            }
+            _ => fold(instruction, operands),
+        };
+        constant.unwrap_or(Constant::Unknown)
+    }
+
+    fn exception(&self, _catch_type: u16) -> Constant {
+        Constant::Unknown
+    }
+
+    fn join(&self, first: &Constant, second: &Constant) -> Constant {
+        match first == second {
+            true => first.clone(),
+            false => Constant::Unknown,
+        }
+    }
+}
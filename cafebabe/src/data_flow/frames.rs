@@ -0,0 +1,654 @@
+use std::result::Result;
+
+use log::error;
+
+use super::Analysis;
+use super::types::{Direction, Frame, ValueKind};
+use crate::bytecode::{Instruction, Opcode, Operand};
+use crate::constant_pool::{ConstantPool, ConstantPoolEntry};
+use crate::control_flow::{ControlFlowGraph, Edge, EdgeKind};
+use crate::descriptor::{FieldType, MethodDescriptor};
+use crate::errors::ClassFileError;
+use crate::types::{MethodAccessFlags, MethodInfo};
+
+/// Gives meaning to the values that [`Frames`] moves between the local variables and the operand stack, by creating
+/// the values that instructions push and joining values where control flow meets.
+pub trait Interpreter {
+    type Value: Clone + PartialEq;
+
+    /// The value of local variables that are not set, and of the second entry of `long` and `double` values.
+    fn unset(&self) -> Self::Value;
+
+    /// The value of a parameter when the method is called.
+    fn parameter(&self, kind: ValueKind) -> Self::Value;
+
+    /// The value of `this` when an instance method is called, a parameter by default.
+    fn this(&self) -> Self::Value {
+        self.parameter(ValueKind::Reference)
+    }
+
+    /// The value of a kind that an instruction pushes, from the values it pops in the order they were pushed, of which
+    /// `long` and `double` values only give their first entry. For `iinc`, the new value of its local variable from
+    /// the old one. Loads, stores and the instructions that duplicate and swap values copy them instead.
+    fn operation(
+        &self,
+        instruction: &Instruction,
+        operands: &[Self::Value],
+        kind: ValueKind,
+    ) -> Self::Value;
+
+    /// The exception that a handler starts with, which it catches by a ClassRef entry, or all exceptions for 0.
+    fn exception(&self, catch_type: u16) -> Self::Value;
+
+    fn join(&self, first: &Self::Value, second: &Self::Value) -> Self::Value;
+
+    /// Changes the frame after the instructions of a block along an edge that leaves it, other than an exception
+    /// edge, e.g. to learn from the condition of a branch. Does nothing by default.
+    fn refine(
+        &self,
+        _instructions: &[Instruction],
+        _kind: EdgeKind,
+        _frame: &mut Frame<Self::Value>,
+    ) {
+    }
+}
+
+/// The local variables and the operand stack before and after each instruction of a method, as a forward analysis
+/// whose state is `None` for code that it doesn't reach.
+pub struct Frames<'a, I: Interpreter> {
+    interpreter: I,
+    constant_pool: &'a ConstantPool,
+    entry: Frame<I::Value>,
+}
+
+/// A load or store of a local variable, by its index and the kind of its value.
+pub(super) enum LocalAccess {
+    Load(u16, ValueKind),
+    Store(u16, ValueKind),
+}
+
+const KINDS: [ValueKind; 5] = [
+    ValueKind::Int,
+    ValueKind::Long,
+    ValueKind::Float,
+    ValueKind::Double,
+    ValueKind::Reference,
+];
+
+/// The kinds of the elements of arrays, in the order of the array loads and stores.
+const ELEMENT_KINDS: [ValueKind; 8] = [
+    ValueKind::Int,
+    ValueKind::Long,
+    ValueKind::Float,
+    ValueKind::Double,
+    ValueKind::Reference,
+    ValueKind::Int,
+    ValueKind::Int,
+    ValueKind::Int,
+];
+
+pub(super) fn local_access(instruction: &Instruction) -> Option<LocalAccess> {
+    let opcode = instruction.opcode as u8;
+    let index = match instruction.operand {
+        Operand::Local(index) => index,
+        _ => 0,
+    };
+    match instruction.opcode {
+        Opcode::Iload | Opcode::Lload | Opcode::Fload | Opcode::Dload | Opcode::Aload => Some(
+            LocalAccess::Load(index, KINDS[usize::from(opcode - Opcode::Iload as u8)]),
+        ),
+        Opcode::Istore | Opcode::Lstore | Opcode::Fstore | Opcode::Dstore | Opcode::Astore => Some(
+            LocalAccess::Store(index, KINDS[usize::from(opcode - Opcode::Istore as u8)]),
+        ),
+        _ if (Opcode::Iload0 as u8..=Opcode::Aload3 as u8).contains(&opcode) => {
+            let position = opcode - Opcode::Iload0 as u8;
+            Some(LocalAccess::Load(
+                u16::from(position % 4),
+                KINDS[usize::from(position / 4)],
+            ))
+        }
+        _ if (Opcode::Istore0 as u8..=Opcode::Astore3 as u8).contains(&opcode) => {
+            let position = opcode - Opcode::Istore0 as u8;
+            Some(LocalAccess::Store(
+                u16::from(position % 4),
+                KINDS[usize::from(position / 4)],
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The number of entries that a value of a kind takes in the local variables or on the operand stack.
+pub(super) fn size(kind: ValueKind) -> usize {
+    match kind {
+        ValueKind::Long | ValueKind::Double => 2,
+        _ => 1,
+    }
+}
+
+fn kind_of(field_type: &FieldType) -> ValueKind {
+    match field_type {
+        FieldType::Long => ValueKind::Long,
+        FieldType::Float => ValueKind::Float,
+        FieldType::Double => ValueKind::Double,
+        FieldType::Object(_) | FieldType::Array(_) => ValueKind::Reference,
+        _ => ValueKind::Int,
+    }
+}
+
+/// The number of local variables of a method and the kinds of the values it is called with, including `this`.
+pub(super) fn parameter_kinds(
+    constant_pool: &ConstantPool,
+    method: &MethodInfo,
+) -> Result<(u16, Vec<ValueKind>), ClassFileError> {
+    let Some(code) = method.code() else {
+        error!("method has no code; name_idx={}", method.name_idx);
+        return Err(ClassFileError::InvalidCode);
+    };
+    let descriptor = MethodDescriptor::parse(method.descriptor(constant_pool)?)?;
+    let mut kinds = Vec::with_capacity(descriptor.parameters.len() + 1);
+    if !method.access_flags.contains(MethodAccessFlags::ACC_STATIC) {
+        kinds.push(ValueKind::Reference);
+    }
+    kinds.extend(descriptor.parameters.iter().map(kind_of));
+    if kinds.iter().map(|kind| size(*kind)).sum::<usize>() > usize::from(code.max_locals) {
+        error!(
+            "parameters exceed the local variables; max_locals={}",
+            code.max_locals
+        );
+        return Err(ClassFileError::InvalidCode);
+    }
+    Ok((code.max_locals, kinds))
+}
+
+pub(super) fn invalid(instruction: &Instruction, problem: &str) -> ClassFileError {
+    error!(
+        "{problem}; offset={}, opcode={}",
+        instruction.offset,
+        instruction.opcode.mnemonic()
+    );
+    ClassFileError::InvalidCode
+}
+
+impl<V: Clone> Frame<V> {
+    /// Pops entries off the operand stack, in the order they were pushed.
+    fn pop_entries(
+        &mut self,
+        count: usize,
+        instruction: &Instruction,
+    ) -> Result<Vec<V>, ClassFileError> {
+        match self.stack.len().checked_sub(count) {
+            Some(length) => Ok(self.stack.split_off(length)),
+            None => Err(invalid(instruction, "operand stack underflow")),
+        }
+    }
+
+    /// Pops values of some kinds, the last of them at the top of the stack, and gives the first entry of each.
+    fn pop_values(
+        &mut self,
+        kinds: &[ValueKind],
+        instruction: &Instruction,
+    ) -> Result<Vec<V>, ClassFileError> {
+        let entries = self.pop_entries(kinds.iter().map(|kind| size(*kind)).sum(), instruction)?;
+        let mut position = 0;
+        let mut values = Vec::with_capacity(kinds.len());
+        for kind in kinds {
+            values.push(entries[position].clone());
+            position += size(*kind);
+        }
+        Ok(values)
+    }
+
+    fn push_value(&mut self, value: V, kind: ValueKind, unset: V) {
+        self.stack.push(value);
+        if size(kind) == 2 {
+            self.stack.push(unset);
+        }
+    }
+
+    /// The local variable at an index, checking that the index is in range for a value of a kind.
+    fn local_slot(
+        &mut self,
+        index: u16,
+        kind: ValueKind,
+        instruction: &Instruction,
+    ) -> Result<usize, ClassFileError> {
+        let index = usize::from(index);
+        match index + size(kind) <= self.locals.len() {
+            true => Ok(index),
+            false => Err(invalid(instruction, "local variable out of range")),
+        }
+    }
+}
+
+impl<'a, I: Interpreter> Frames<'a, I> {
+    /// The analysis of a method of a class with the constant pool, which must have code.
+    pub fn new(
+        interpreter: I,
+        constant_pool: &'a ConstantPool,
+        method: &MethodInfo,
+    ) -> Result<Frames<'a, I>, ClassFileError> {
+        let (max_locals, kinds) = parameter_kinds(constant_pool, method)?;
+        let mut locals = Vec::with_capacity(usize::from(max_locals));
+        for (position, kind) in kinds.iter().enumerate() {
+            let is_this =
+                position == 0 && !method.access_flags.contains(MethodAccessFlags::ACC_STATIC);
+            locals.push(match is_this {
+                true => interpreter.this(),
+                false => interpreter.parameter(*kind),
+            });
+            if size(*kind) == 2 {
+                locals.push(interpreter.unset());
+            }
+        }
+        locals.resize(usize::from(max_locals), interpreter.unset());
+        Ok(Frames {
+            interpreter,
+            constant_pool,
+            entry: Frame {
+                locals,
+                stack: Vec::new(),
+            },
+        })
+    }
+
+    pub fn interpreter(&self) -> &I {
+        &self.interpreter
+    }
+
+    /// The kind of the value that an `ldc` instruction pushes.
+    fn constant_kind(
+        &self,
+        instruction: &Instruction,
+        index: u16,
+    ) -> Result<ValueKind, ClassFileError> {
+        let entry = self
+            .constant_pool
+            .checked_entry(index)
+            .map_err(|_| invalid(instruction, "constant pool index out of range"))?;
+        match entry {
+            ConstantPoolEntry::Integer(_) => Ok(ValueKind::Int),
+            ConstantPoolEntry::Float(_) => Ok(ValueKind::Float),
+            ConstantPoolEntry::Long(_) => Ok(ValueKind::Long),
+            ConstantPoolEntry::Double(_) => Ok(ValueKind::Double),
+            ConstantPoolEntry::StringRef(_)
+            | ConstantPoolEntry::ClassRef(_)
+            | ConstantPoolEntry::MethodType(_)
+            | ConstantPoolEntry::MethodHandle(_) => Ok(ValueKind::Reference),
+            ConstantPoolEntry::Dynamic(dynamic_ref) => {
+                let (_, descriptor) = self
+                    .constant_pool
+                    .name_and_type(usize::from(dynamic_ref.name_type_ref))?;
+                Ok(kind_of(&FieldType::parse(descriptor)?))
+            }
+            _ => Err(ClassFileError::UnexpectedConstantPoolType),
+        }
+    }
+
+    /// The descriptor of the method that an invoke instruction calls.
+    fn invoked_method(
+        &self,
+        instruction: &Instruction,
+        index: u16,
+    ) -> Result<MethodDescriptor, ClassFileError> {
+        let entry = self
+            .constant_pool
+            .checked_entry(index)
+            .map_err(|_| invalid(instruction, "constant pool index out of range"))?;
+        let descriptor = match entry {
+            ConstantPoolEntry::InvokeDynamic(dynamic_ref) => {
+                self.constant_pool
+                    .name_and_type(usize::from(dynamic_ref.name_type_ref))?
+                    .1
+            }
+            _ => self.constant_pool.member_ref(usize::from(index))?.2,
+        };
+        MethodDescriptor::parse(descriptor)
+    }
+
+    /// Pops the operands of an instruction and pushes the value the interpreter gives for them.
+    fn apply(
+        &self,
+        frame: &mut Frame<I::Value>,
+        instruction: &Instruction,
+        operands: &[ValueKind],
+        result: ValueKind,
+    ) -> Result<(), ClassFileError> {
+        let values = frame.pop_values(operands, instruction)?;
+        let value = self.interpreter.operation(instruction, &values, result);
+        frame.push_value(value, result, self.interpreter.unset());
+        Ok(())
+    }
+
+    fn execute(
+        &self,
+        instruction: &Instruction,
+        frame: &mut Frame<I::Value>,
+    ) -> Result<(), ClassFileError> {
+        use ValueKind::{Double, Float, Int, Long, Reference, ReturnAddress};
+
+        if let Some(access) = local_access(instruction) {
+            match access {
+                LocalAccess::Load(index, kind) => {
+                    let slot = frame.local_slot(index, kind, instruction)?;
+                    frame.stack.push(frame.locals[slot].clone());
+                    if size(kind) == 2 {
+                        frame.stack.push(self.interpreter.unset());
+                    }
+                }
+                LocalAccess::Store(index, kind) => {
+                    let slot = frame.local_slot(index, kind, instruction)?;
+                    let entries = frame.pop_entries(size(kind), instruction)?;
+                    frame.locals.splice(slot..slot + entries.len(), entries);
+                }
+            }
+            return Ok(());
+        }
+
+        let opcode = instruction.opcode as u8;
+        match instruction.opcode {
+            Opcode::Nop | Opcode::Goto | Opcode::GotoW | Opcode::Ret | Opcode::Return => {}
+            Opcode::AconstNull => self.apply(frame, instruction, &[], Reference)?,
+            Opcode::IconstM1
+            | Opcode::Iconst0
+            | Opcode::Iconst1
+            | Opcode::Iconst2
+            | Opcode::Iconst3
+            | Opcode::Iconst4
+            | Opcode::Iconst5
+            | Opcode::Bipush
+            | Opcode::Sipush => self.apply(frame, instruction, &[], Int)?,
+            Opcode::Lconst0 | Opcode::Lconst1 => self.apply(frame, instruction, &[], Long)?,
+            Opcode::Fconst0 | Opcode::Fconst1 | Opcode::Fconst2 => {
+                self.apply(frame, instruction, &[], Float)?
+            }
+            Opcode::Dconst0 | Opcode::Dconst1 => self.apply(frame, instruction, &[], Double)?,
+            Opcode::Ldc | Opcode::LdcW | Opcode::Ldc2W => {
+                let Operand::ConstantPool(index) = instruction.operand else {
+                    return Err(invalid(instruction, "missing constant pool index"));
+                };
+                let kind = self.constant_kind(instruction, index)?;
+                self.apply(frame, instruction, &[], kind)?
+            }
+            Opcode::Iaload
+            | Opcode::Laload
+            | Opcode::Faload
+            | Opcode::Daload
+            | Opcode::Aaload
+            | Opcode::Baload
+            | Opcode::Caload
+            | Opcode::Saload => {
+                let kind = ELEMENT_KINDS[usize::from(opcode - Opcode::Iaload as u8)];
+                self.apply(frame, instruction, &[Reference, Int], kind)?
+            }
+            Opcode::Iastore
+            | Opcode::Lastore
+            | Opcode::Fastore
+            | Opcode::Dastore
+            | Opcode::Aastore
+            | Opcode::Bastore
+            | Opcode::Castore
+            | Opcode::Sastore => {
+                let kind = ELEMENT_KINDS[usize::from(opcode - Opcode::Iastore as u8)];
+                frame.pop_values(&[Reference, Int, kind], instruction)?;
+            }
+            Opcode::Pop => {
+                frame.pop_entries(1, instruction)?;
+            }
+            Opcode::Pop2 => {
+                frame.pop_entries(2, instruction)?;
+            }
+            Opcode::Dup
+            | Opcode::DupX1
+            | Opcode::DupX2
+            | Opcode::Dup2
+            | Opcode::Dup2X1
+            | Opcode::Dup2X2
+            | Opcode::Swap => {
+                // The entries that are copied, and those that they are copied below.
+                let (copied, skipped) = match instruction.opcode {
+                    Opcode::Dup => (1, 0),
+                    Opcode::DupX1 => (1, 1),
+                    Opcode::DupX2 => (1, 2),
+                    Opcode::Dup2 => (2, 0),
+                    Opcode::Dup2X1 => (2, 1),
+                    Opcode::Dup2X2 => (2, 2),
+                    _ => (1, 1),
+                };
+                let entries = frame.pop_entries(copied + skipped, instruction)?;
+                let (below, top) = entries.split_at(skipped);
+                frame.stack.extend_from_slice(top);
+                frame.stack.extend_from_slice(below);
+                if instruction.opcode != Opcode::Swap {
+                    frame.stack.extend_from_slice(top);
+                }
+            }
+            _ if (Opcode::Iadd as u8..=Opcode::Drem as u8).contains(&opcode) => {
+                let kind = KINDS[usize::from(opcode - Opcode::Iadd as u8) % 4];
+                self.apply(frame, instruction, &[kind, kind], kind)?
+            }
+            Opcode::Ineg | Opcode::Lneg | Opcode::Fneg | Opcode::Dneg => {
+                let kind = KINDS[usize::from(opcode - Opcode::Ineg as u8)];
+                self.apply(frame, instruction, &[kind], kind)?
+            }
+            Opcode::Ishl | Opcode::Ishr | Opcode::Iushr => {
+                self.apply(frame, instruction, &[Int, Int], Int)?
+            }
+            Opcode::Lshl | Opcode::Lshr | Opcode::Lushr => {
+                self.apply(frame, instruction, &[Long, Int], Long)?
+            }
+            Opcode::Iand | Opcode::Ior | Opcode::Ixor => {
+                self.apply(frame, instruction, &[Int, Int], Int)?
+            }
+            Opcode::Land | Opcode::Lor | Opcode::Lxor => {
+                self.apply(frame, instruction, &[Long, Long], Long)?
+            }
+            Opcode::Iinc => {
+                let Operand::Iinc { index, .. } = instruction.operand else {
+                    return Err(invalid(instruction, "missing local variable"));
+                };
+                let slot = frame.local_slot(index, Int, instruction)?;
+                let value =
+                    self.interpreter
+                        .operation(instruction, &frame.locals[slot..=slot], Int);
+                frame.locals[slot] = value;
+            }
+            _ if (Opcode::I2l as u8..=Opcode::I2s as u8).contains(&opcode) => {
+                let (from, to) = match instruction.opcode {
+                    Opcode::I2l => (Int, Long),
+                    Opcode::I2f => (Int, Float),
+                    Opcode::I2d => (Int, Double),
+                    Opcode::L2i => (Long, Int),
+                    Opcode::L2f => (Long, Float),
+                    Opcode::L2d => (Long, Double),
+                    Opcode::F2i => (Float, Int),
+                    Opcode::F2l => (Float, Long),
+                    Opcode::F2d => (Float, Double),
+                    Opcode::D2i => (Double, Int),
+                    Opcode::D2l => (Double, Long),
+                    Opcode::D2f => (Double, Float),
+                    _ => (Int, Int),
+                };
+                self.apply(frame, instruction, &[from], to)?
+            }
+            Opcode::Lcmp => self.apply(frame, instruction, &[Long, Long], Int)?,
+            Opcode::Fcmpl | Opcode::Fcmpg => {
+                self.apply(frame, instruction, &[Float, Float], Int)?
+            }
+            Opcode::Dcmpl | Opcode::Dcmpg => {
+                self.apply(frame, instruction, &[Double, Double], Int)?
+            }
+            Opcode::Ifeq
+            | Opcode::Ifne
+            | Opcode::Iflt
+            | Opcode::Ifge
+            | Opcode::Ifgt
+            | Opcode::Ifle
+            | Opcode::Tableswitch
+            | Opcode::Lookupswitch
+            | Opcode::Ireturn => {
+                frame.pop_values(&[Int], instruction)?;
+            }
+            Opcode::IfIcmpeq
+            | Opcode::IfIcmpne
+            | Opcode::IfIcmplt
+            | Opcode::IfIcmpge
+            | Opcode::IfIcmpgt
+            | Opcode::IfIcmple => {
+                frame.pop_values(&[Int, Int], instruction)?;
+            }
+            Opcode::IfAcmpeq | Opcode::IfAcmpne => {
+                frame.pop_values(&[Reference, Reference], instruction)?;
+            }
+            Opcode::Jsr | Opcode::JsrW => self.apply(frame, instruction, &[], ReturnAddress)?,
+            Opcode::Lreturn => {
+                frame.pop_values(&[Long], instruction)?;
+            }
+            Opcode::Freturn => {
+                frame.pop_values(&[Float], instruction)?;
+            }
+            Opcode::Dreturn => {
+                frame.pop_values(&[Double], instruction)?;
+            }
+            Opcode::Areturn
+            | Opcode::Athrow
+            | Opcode::Monitorenter
+            | Opcode::Monitorexit
+            | Opcode::Ifnull
+            | Opcode::Ifnonnull => {
+                frame.pop_values(&[Reference], instruction)?;
+            }
+            Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield => {
+                let Operand::ConstantPool(index) = instruction.operand else {
+                    return Err(invalid(instruction, "missing constant pool index"));
+                };
+                let (_, _, descriptor) = self.constant_pool.member_ref(usize::from(index))?;
+                let kind = kind_of(&FieldType::parse(descriptor)?);
+                match instruction.opcode {
+                    Opcode::Getstatic => self.apply(frame, instruction, &[], kind)?,
+                    Opcode::Putstatic => {
+                        frame.pop_values(&[kind], instruction)?;
+                    }
+                    Opcode::Getfield => self.apply(frame, instruction, &[Reference], kind)?,
+                    _ => {
+                        frame.pop_values(&[Reference, kind], instruction)?;
+                    }
+                }
+            }
+            Opcode::Invokevirtual
+            | Opcode::Invokespecial
+            | Opcode::Invokestatic
+            | Opcode::Invokeinterface
+            | Opcode::Invokedynamic => {
+                let (Operand::ConstantPool(index) | Operand::InvokeInterface { index, .. }) =
+                    instruction.operand
+                else {
+                    return Err(invalid(instruction, "missing constant pool index"));
+                };
+                let descriptor = self.invoked_method(instruction, index)?;
+                let mut kinds = Vec::with_capacity(descriptor.parameters.len() + 1);
+                if !matches!(
+                    instruction.opcode,
+                    Opcode::Invokestatic | Opcode::Invokedynamic
+                ) {
+                    kinds.push(Reference);
+                }
+                kinds.extend(descriptor.parameters.iter().map(kind_of));
+                match &descriptor.return_type {
+                    Some(return_type) => {
+                        self.apply(frame, instruction, &kinds, kind_of(return_type))?
+                    }
+                    None => {
+                        frame.pop_values(&kinds, instruction)?;
+                    }
+                }
+            }
+            Opcode::New => self.apply(frame, instruction, &[], Reference)?,
+            Opcode::Newarray | Opcode::Anewarray => {
+                self.apply(frame, instruction, &[Int], Reference)?
+            }
+            Opcode::Arraylength | Opcode::Instanceof => {
+                self.apply(frame, instruction, &[Reference], Int)?
+            }
+            Opcode::Checkcast => self.apply(frame, instruction, &[Reference], Reference)?,
+            Opcode::Multianewarray => {
+                let Operand::MultiANewArray { dimensions, .. } = instruction.operand else {
+                    return Err(invalid(instruction, "missing dimensions"));
+                };
+                let kinds = vec![Int; usize::from(dimensions)];
+                self.apply(frame, instruction, &kinds, Reference)?
+            }
+            _ => return Err(invalid(instruction, "unexpected instruction")),
+        }
+        Ok(())
+    }
+}
+
+impl<I: Interpreter> Analysis for Frames<'_, I> {
+    type State = Option<Frame<I::Value>>;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn bottom(&self) -> Self::State {
+        None
+    }
+
+    fn boundary(&self) -> Self::State {
+        Some(self.entry.clone())
+    }
+
+    fn join(&self, state: &mut Self::State, other: &Self::State) -> Result<bool, ClassFileError> {
+        let (state, Some(other)) = (state, other) else {
+            return Ok(false);
+        };
+        let Some(frame) = state else {
+            *state = Some(other.clone());
+            return Ok(true);
+        };
+        if frame.stack.len() != other.stack.len() || frame.locals.len() != other.locals.len() {
+            error!(
+                "operand stacks of different heights meet; height={}, other_height={}",
+                frame.stack.len(),
+                other.stack.len()
+            );
+            return Err(ClassFileError::InvalidCode);
+        }
+        let mut changed = false;
+        let values = frame.locals.iter_mut().chain(frame.stack.iter_mut());
+        for (value, other) in values.zip(other.locals.iter().chain(&other.stack)) {
+            let joined = self.interpreter.join(value, other);
+            if joined != *value {
+                *value = joined;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    fn transfer(
+        &self,
+        instruction: &Instruction,
+        state: &mut Self::State,
+    ) -> Result<(), ClassFileError> {
+        match state {
+            Some(frame) => self.execute(instruction, frame),
+            None => Ok(()),
+        }
+    }
+
+    fn transfer_edge(&self, graph: &ControlFlowGraph, edge: &Edge, state: &mut Self::State) {
+        let Some(frame) = state else {
+            return;
+        };
+        match edge.kind {
+            EdgeKind::Exception(catch_type) => {
+                frame.stack = vec![self.interpreter.exception(catch_type)];
+            }
+            kind => self
+                .interpreter
+                .refine(graph.block_instructions(edge.source), kind, frame),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Analyses of the local variables alone, by slot: the stores that reach each point, and the slots that are read
+//! later.
+
+use std::collections::BTreeSet;
+use std::result::Result;
+
+use super::Analysis;
+use super::frames::{LocalAccess, invalid, local_access, parameter_kinds, size};
+use super::types::{Definition, Direction};
+use crate::bytecode::{Instruction, Opcode, Operand};
+use crate::constant_pool::ConstantPool;
+use crate::errors::ClassFileError;
+use crate::types::MethodInfo;
+
+/// Whether an instruction sets or reads local variables, the first slot it accesses and the number of slots.
+fn accessed_slots(instruction: &Instruction) -> Option<(bool, usize, usize)> {
+    match (local_access(instruction), &instruction.operand) {
+        (Some(LocalAccess::Store(index, kind)), _) => Some((true, usize::from(index), size(kind))),
+        (Some(LocalAccess::Load(index, kind)), _) => Some((false, usize::from(index), size(kind))),
+        (None, Operand::Iinc { index, .. }) => Some((true, usize::from(*index), 1)),
+        (None, Operand::Local(index)) if instruction.opcode == Opcode::Ret => {
+            Some((false, usize::from(*index), 1))
+        }
+        _ => None,
+    }
+}
+
+/// The definitions that reach each point, for each slot of the local variables: the stores whose value the slot may
+/// still hold, or the parameters of the method. The state is empty for slots that may not be set yet.
+pub struct ReachingDefinitions {
+    max_locals: u16,
+    /// The number of slots that `this` and the parameters take.
+    parameter_slots: usize,
+}
+
+impl ReachingDefinitions {
+    /// The analysis of a method of a class with the constant pool, which must have code.
+    pub fn new(
+        constant_pool: &ConstantPool,
+        method: &MethodInfo,
+    ) -> Result<ReachingDefinitions, ClassFileError> {
+        let (max_locals, kinds) = parameter_kinds(constant_pool, method)?;
+        Ok(ReachingDefinitions {
+            max_locals,
+            parameter_slots: kinds.iter().map(|kind| size(*kind)).sum(),
+        })
+    }
+}
+
+impl Analysis for ReachingDefinitions {
+    type State = Vec<BTreeSet<Definition>>;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn bottom(&self) -> Self::State {
+        vec![BTreeSet::new(); usize::from(self.max_locals)]
+    }
+
+    fn boundary(&self) -> Self::State {
+        let mut state = self.bottom();
+        for definitions in &mut state[..self.parameter_slots] {
+            definitions.insert(Definition::Parameter);
+        }
+        state
+    }
+
+    fn join(&self, state: &mut Self::State, other: &Self::State) -> Result<bool, ClassFileError> {
+        let mut changed = false;
+        for (definitions, other) in state.iter_mut().zip(other) {
+            let count = definitions.len();
+            definitions.extend(other);
+            changed |= definitions.len() != count;
+        }
+        Ok(changed)
+    }
+
+    fn transfer(
+        &self,
+        instruction: &Instruction,
+        state: &mut Self::State,
+    ) -> Result<(), ClassFileError> {
+        let Some((true, index, slots)) = accessed_slots(instruction) else {
+            return Ok(());
+        };
+        if index + slots > state.len() {
+            return Err(invalid(instruction, "local variable out of range"));
+        }
+        state[index] = BTreeSet::from([Definition::Instruction(instruction.offset)]);
+        // The second slot of a `long` or `double` holds no value of its own.
+        if slots == 2 {
+            state[index + 1].clear();
+        }
+        Ok(())
+    }
+}
+
+/// The slots of the local variables that are live at each point: those that some path from the point reads before
+/// it sets them. A store to a slot that isn't live after it is dead.
+pub struct Liveness {
+    max_locals: u16,
+}
+
+impl Liveness {
+    pub fn new(max_locals: u16) -> Liveness {
+        Liveness { max_locals }
+    }
+}
+
+impl Analysis for Liveness {
+    /// Whether each slot is live.
+    type State = Vec<bool>;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn bottom(&self) -> Self::State {
+        vec![false; usize::from(self.max_locals)]
+    }
+
+    fn boundary(&self) -> Self::State {
+        self.bottom()
+    }
+
+    fn join(&self, state: &mut Self::State, other: &Self::State) -> Result<bool, ClassFileError> {
+        let mut changed = false;
+        for (live, other) in state.iter_mut().zip(other) {
+            changed |= !*live && *other;
+            *live |= *other;
+        }
+        Ok(changed)
+    }
+
+    fn transfer(
+        &self,
+        instruction: &Instruction,
+        state: &mut Self::State,
+    ) -> Result<(), ClassFileError> {
+        let Some((sets, index, slots)) = accessed_slots(instruction) else {
+            return Ok(());
+        };
+        if index + slots > state.len() {
+            return Err(invalid(instruction, "local variable out of range"));
+        }
+        // `iinc` reads the slot before it sets it, so it stays live.
+        let live = !sets || instruction.opcode == Opcode::Iinc;
+        state[index..index + slots].fill(live);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use super::frames::{Interpreter, LocalAccess, local_access};
+use super::types::{Frame, Nullness, ValueKind};
+use crate::bytecode::{Instruction, Opcode, Operand};
+use crate::constant_pool::{ConstantPool, ConstantPoolEntry};
+use crate::control_flow::EdgeKind;
+
+/// Nullness analysis: which references are `null`, which are not, and which may be either. New objects, constants and
+/// caught exceptions are not `null`, and a comparison of a local variable with `null` tells which it is on each edge
+/// of the branch.
+pub struct NullnessAnalysis<'a> {
+    constant_pool: &'a ConstantPool,
+}
+
+impl<'a> NullnessAnalysis<'a> {
+    /// The analysis of methods of a class with the constant pool, for the constants that `ldc` loads.
+    pub fn new(constant_pool: &'a ConstantPool) -> NullnessAnalysis<'a> {
+        NullnessAnalysis { constant_pool }
+    }
+}
+
+/// The local variable that an instruction loads, if it loads a reference.
+fn loaded_reference(instruction: &Instruction) -> Option<usize> {
+    match local_access(instruction) {
+        Some(LocalAccess::Load(index, ValueKind::Reference)) => Some(usize::from(index)),
+        _ => None,
+    }
+}
+
+/// The local variable that a branch compares with `null`, and whether the branch is taken if it is `null`.
+fn null_check(instructions: &[Instruction]) -> Option<(usize, bool)> {
+    match instructions {
+        [.., load, branch] if matches!(branch.opcode, Opcode::Ifnull | Opcode::Ifnonnull) => {
+            Some((loaded_reference(load)?, branch.opcode == Opcode::Ifnull))
+        }
+        [.., first, second, branch]
+            if matches!(branch.opcode, Opcode::IfAcmpeq | Opcode::IfAcmpne) =>
+        {
+            let local = match (first.opcode, second.opcode) {
+                (Opcode::AconstNull, _) => loaded_reference(second)?,
+                (_, Opcode::AconstNull) => loaded_reference(first)?,
+                _ => return None,
+            };
+            Some((local, branch.opcode == Opcode::IfAcmpeq))
+        }
+        _ => None,
+    }
+}
+
+impl Interpreter for NullnessAnalysis<'_> {
+    type Value = Nullness;
+
+    fn unset(&self) -> Nullness {
+        Nullness::Unknown
+    }
+
+    fn parameter(&self, _kind: ValueKind) -> Nullness {
+        Nullness::Unknown
+    }
+
+    fn this(&self) -> Nullness {
+        Nullness::NotNull
+    }
+
+    fn operation(
+        &self,
+        instruction: &Instruction,
+        operands: &[Nullness],
+        kind: ValueKind,
+    ) -> Nullness {
+        match (instruction.opcode, &instruction.operand) {
+            (Opcode::AconstNull, _) => Nullness::Null,
+            (Opcode::New | Opcode::Newarray | Opcode::Anewarray | Opcode::Multianewarray, _) => {
+                Nullness::NotNull
+            }
+            // Dynamically computed constants may be `null`, the others are not.
+            (Opcode::Ldc | Opcode::LdcW, Operand::ConstantPool(index))
+                if kind == ValueKind::Reference =>
+            {
+                match self.constant_pool.checked_entry(*index) {
+                    Ok(ConstantPoolEntry::Dynamic(_)) => Nullness::Unknown,
+                    _ => Nullness::NotNull,
+                }
+            }
+            (Opcode::Checkcast, _) => operands[0],
+            _ => Nullness::Unknown,
+        }
+    }
+
+    fn exception(&self, _catch_type: u16) -> Nullness {
+        Nullness::NotNull
+    }
+
+    fn join(&self, first: &Nullness, second: &Nullness) -> Nullness {
+        match first == second {
+            true => *first,
+            false => Nullness::Unknown,
+        }
+    }
+
+    fn refine(&self, instructions: &[Instruction], kind: EdgeKind, frame: &mut Frame<Nullness>) {
+        let Some((local, taken_if_null)) = null_check(instructions) else {
+            return;
+        };
+        let is_null = match kind {
+            EdgeKind::Branch => taken_if_null,
+            EdgeKind::FallThrough => !taken_if_null,
+            _ => return,
+        };
+        if let Some(value) = frame.locals.get_mut(local) {
+            *value = match is_null {
+                true => Nullness::Null,
+                false => Nullness::NotNull,
+            };
+        }
+    }
+}
@@ -0,0 +1,77 @@
+/// The direction in which an analysis follows the control flow.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// From the entry of a method to its exits, computing the state after an instruction from the state before it.
+    Forward,
+    /// From the exits of a method to its entry, computing the state before an instruction from the state after it.
+    Backward,
+}
+
+/// The states of an analysis at the borders of each block, in the order of the code for both directions.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Solution<S> {
+    /// The state before the first instruction of each block.
+    pub entry: Vec<S>,
+    /// The state after the last instruction of each block.
+    pub exit: Vec<S>,
+}
+
+/// The kinds of values the Java Virtual Machine distinguishes on the operand stack and in local variables. Values of
+/// `boolean`, `byte`, `char` and `short` are `int` values there.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ValueKind {
+    Int,
+    Long,
+    Float,
+    Double,
+    Reference,
+    /// The address that `jsr` pushes.
+    ReturnAddress,
+}
+
+/// The local variables and the operand stack at a point in a method. Like in the Java Virtual Machine, values of `long`
+/// and `double` take two entries, the value and a second one that holds the unset value of the interpreter.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Frame<V> {
+    pub locals: Vec<V>,
+    /// The operand stack, with its top as the last value.
+    pub stack: Vec<V>,
+}
+
+/// Where the value of a local variable was set.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Definition {
+    /// The value is `this` or a parameter, set when the method is called.
+    Parameter,
+    /// The value was set by the instruction at an offset: a store, `iinc`, or `astore` of the return address of `jsr`.
+    Instruction(u32),
+}
+
+/// A value as far as constant propagation knows it. Floating-point constants are equal if their bits are, so that
+/// `NaN` is a constant like any other.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Constant {
+    /// A value that may differ between executions, or that isn't a constant that the analysis tracks.
+    Unknown,
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Null,
+}
+
+/// Whether a value can be `null`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Nullness {
+    Null,
+    NotNull,
+    /// A reference that may be `null` or not, or a value that isn't a reference.
+    Unknown,
+}
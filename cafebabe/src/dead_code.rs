@@ -0,0 +1,378 @@
+//! Dead code analysis: the classes, methods and fields of a program that are never used when it runs from its entry
+//! points, and the jars and directories of its class path that it doesn't need, from its [`CallGraph`].
+//!
+//! A class is used when reachable code names it: as the class of an instruction or of a member it accesses, in a
+//! descriptor, a caught exception, a constant or a bootstrap argument, or as a supertype of a used class. Arrays count
+//! as their element types. Fields are used when reachable code reads or writes them, or passes a method handle to them.
+//!
+//! Only bytecode is followed: code that is only called by reflection, serialization, service loaders or a framework
+//! looks dead, and needs to be kept as entry points. Constants that the compiler inlines count as unused fields. The
+//! JDK is never reported.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::result::Result;
+use std::sync::Arc;
+
+use log::{debug, error};
+
+use crate::archive::ArchiveError;
+use crate::bootstrap::{BootstrapArgument, MethodHandleInfo, method_handle_info};
+use crate::bytecode::{Operand, decode};
+use crate::call_graph::{Algorithm, CallGraph, MethodId};
+use crate::class_path::ClassPath;
+use crate::constant_pool::ConstantPoolEntry;
+use crate::descriptor::{add_class_name, add_descriptor};
+use crate::errors::ClassFileError;
+use crate::hierarchy::ClassHierarchy;
+use crate::types::{ClassFile, MethodInfo};
+
+mod types;
+
+pub use types::{DeadCodeReport, EntryPoints, FieldId};
+
+/// Parses a method pattern of [`EntryPoints::methods`] into its class name, method name and optional descriptor.
+fn parse_method_pattern(pattern: &str) -> Option<(&str, &str, Option<&str>)> {
+    let (member, descriptor) = match pattern.find('(') {
+        Some(position) => (&pattern[..position], Some(&pattern[position..])),
+        None => (pattern, None),
+    };
+    let (class_name, name) = member.rsplit_once('.')?;
+    (!class_name.is_empty() && !name.is_empty()).then_some((class_name, name, descriptor))
+}
+
+/// The methods of a class that are entry points because of a pattern, or all of its methods with code without one.
+fn class_entry_points(
+    class_file: &ClassFile,
+    class_name: &str,
+    pattern: Option<(&str, Option<&str>)>,
+) -> Vec<MethodId> {
+    let constant_pool = &class_file.constant_pool;
+    let mut methods = Vec::new();
+    for method in &class_file.methods {
+        let (Ok(name), Ok(descriptor)) =
+            (method.name(constant_pool), method.descriptor(constant_pool))
+        else {
+            continue;
+        };
+        let matches = match pattern {
+            Some((pattern_name, pattern_descriptor)) => {
+                name == pattern_name
+                    && pattern_descriptor
+                        .is_none_or(|pattern_descriptor| descriptor == pattern_descriptor)
+            }
+            None => method.code().is_some(),
+        };
+        if matches {
+            methods.push(MethodId::new(class_name, name, descriptor));
+        }
+    }
+    methods
+}
+
+/// What reachable code refers to.
+struct Usage<'a> {
+    class_path: &'a ClassPath,
+    hierarchy: &'a ClassHierarchy,
+    classes: BTreeSet<String>,
+    /// The fields that instructions and method handles name, which may be declared by a supertype.
+    named_fields: BTreeSet<FieldId>,
+    invalid_classes: BTreeSet<String>,
+}
+
+impl Usage<'_> {
+    /// Reads a class of the class path, logging and recording the classes that fail to parse.
+    fn find_class(&mut self, class_name: &str) -> Result<Option<Arc<ClassFile>>, ArchiveError> {
+        match self.class_path.find_class(class_name) {
+            Err(ArchiveError::InvalidClass(_, error)) => {
+                error!("can't parse class; class_name={class_name}, error={error}");
+                self.invalid_classes.insert(class_name.to_string());
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+
+    fn add_method_handle(&mut self, handle: &MethodHandleInfo) -> Result<(), ClassFileError> {
+        add_class_name(&mut self.classes, &handle.owner)?;
+        add_descriptor(&mut self.classes, &handle.descriptor)?;
+        if !handle.descriptor.starts_with('(') {
+            self.named_fields.insert(FieldId::new(
+                &handle.owner,
+                &handle.name,
+                &handle.descriptor,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Adds what a constant that an instruction refers to names.
+    fn add_constant(&mut self, class_file: &ClassFile, index: u16) -> Result<(), ClassFileError> {
+        let constant_pool = &class_file.constant_pool;
+        match constant_pool.checked_entry(index)? {
+            ConstantPoolEntry::ClassRef(name_idx) => {
+                add_class_name(&mut self.classes, constant_pool.string_entry(*name_idx)?)?;
+            }
+            ConstantPoolEntry::FieldRef(_) => {
+                let (owner, name, descriptor) = constant_pool.member_ref(usize::from(index))?;
+                add_class_name(&mut self.classes, owner)?;
+                add_descriptor(&mut self.classes, descriptor)?;
+                self.named_fields
+                    .insert(FieldId::new(owner, name, descriptor));
+            }
+            ConstantPoolEntry::MethodRef(_) | ConstantPoolEntry::InterfaceMethodRef(_) => {
+                let (owner, _, descriptor) = constant_pool.member_ref(usize::from(index))?;
+                add_class_name(&mut self.classes, owner)?;
+                add_descriptor(&mut self.classes, descriptor)?;
+            }
+            ConstantPoolEntry::MethodType(descriptor_idx) => {
+                add_descriptor(
+                    &mut self.classes,
+                    constant_pool.string_entry(*descriptor_idx)?,
+                )?;
+            }
+            ConstantPoolEntry::MethodHandle(_) => {
+                self.add_method_handle(&method_handle_info(constant_pool, usize::from(index))?)?;
+            }
+            ConstantPoolEntry::InvokeDynamic(_) | ConstantPoolEntry::Dynamic(_) => {
+                let call_site = class_file.call_site(index)?;
+                add_descriptor(&mut self.classes, &call_site.descriptor)?;
+                self.add_method_handle(&call_site.bootstrap_method)?;
+                for argument in &call_site.arguments {
+                    match argument {
+                        BootstrapArgument::Class(class_name) => {
+                            add_class_name(&mut self.classes, class_name)?;
+                        }
+                        BootstrapArgument::MethodType(descriptor) => {
+                            add_descriptor(&mut self.classes, descriptor)?;
+                        }
+                        BootstrapArgument::MethodHandle(handle) => {
+                            self.add_method_handle(handle)?
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Adds what a reachable method names in its descriptor and its code.
+    fn add_method(
+        &mut self,
+        class_file: &ClassFile,
+        method: &MethodInfo,
+    ) -> Result<(), ClassFileError> {
+        let constant_pool = &class_file.constant_pool;
+        add_descriptor(&mut self.classes, method.descriptor(constant_pool)?)?;
+        let Some(code) = method.code() else {
+            return Ok(());
+        };
+        for instruction in decode(&code.code)? {
+            match instruction.operand {
+                Operand::ConstantPool(index)
+                | Operand::InvokeInterface { index, .. }
+                | Operand::MultiANewArray { index, .. } => self.add_constant(class_file, index)?,
+                _ => {}
+            }
+        }
+        for handler in &code.exception_table {
+            if handler.catch_type != 0 {
+                add_class_name(
+                    &mut self.classes,
+                    constant_pool.class_name(usize::from(handler.catch_type))?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The field that a named field resolves to: declared by its class, a superclass or a superinterface.
+    fn resolve_field(&mut self, field: &FieldId) -> Result<Option<FieldId>, ArchiveError> {
+        let candidates: Vec<String> = std::iter::once(field.class_name.as_str())
+            .chain(self.hierarchy.superclasses(&field.class_name))
+            .chain(self.hierarchy.supertypes(&field.class_name))
+            .map(str::to_string)
+            .collect();
+        for class_name in candidates {
+            let Some(class_file) = self.find_class(&class_name)? else {
+                continue;
+            };
+            let constant_pool = &class_file.constant_pool;
+            let declares = class_file.fields.iter().any(|declared| {
+                declared
+                    .name(constant_pool)
+                    .is_ok_and(|name| *name == field.name)
+                    && declared
+                        .descriptor(constant_pool)
+                        .is_ok_and(|descriptor| *descriptor == field.descriptor)
+            });
+            if declares {
+                return Ok(Some(FieldId::new(
+                    class_name,
+                    &field.name,
+                    &field.descriptor,
+                )));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl DeadCodeReport {
+    /// Finds the classes, methods, fields and class path entries other than the JDK that aren't used from the entry
+    /// points, with the call graph built by an algorithm over a class path and its hierarchy, which must index the same
+    /// class path. Classes that fail to parse are logged and reported, see [`DeadCodeReport::invalid_classes`].
+    pub fn analyze(
+        class_path: &ClassPath,
+        hierarchy: &ClassHierarchy,
+        entry_points: &EntryPoints,
+        algorithm: Algorithm,
+    ) -> Result<DeadCodeReport, ArchiveError> {
+        let mut usage = Usage {
+            class_path,
+            hierarchy,
+            classes: BTreeSet::new(),
+            named_fields: BTreeSet::new(),
+            invalid_classes: BTreeSet::new(),
+        };
+        let mut report = DeadCodeReport::default();
+
+        // The classes other than the JDK, with the entry that provides them, by position.
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        for (position, entry) in class_path.entries().iter().enumerate() {
+            if entry.source.is_jdk() {
+                continue;
+            }
+            for class_name in class_path.entry_class_names(entry) {
+                owners.entry(class_name).or_insert(position);
+            }
+        }
+        let mut application_classes: Vec<&str> = owners.keys().copied().collect();
+        application_classes.sort();
+
+        let mut methods = Vec::new();
+        if entry_points.main_methods {
+            methods.extend(CallGraph::main_methods(class_path)?);
+        }
+        for pattern in &entry_points.methods {
+            let mut found = Vec::new();
+            if let Some((class_name, name, descriptor)) = parse_method_pattern(pattern)
+                && let Some(class_file) = usage.find_class(class_name)?
+            {
+                found = class_entry_points(&class_file, class_name, Some((name, descriptor)));
+            }
+            if found.is_empty() {
+                debug!("unknown entry point; pattern={pattern}");
+                report.unknown_entry_points.push(pattern.clone());
+            }
+            methods.extend(found);
+        }
+        for prefix in &entry_points.keep {
+            let mut kept = false;
+            for class_name in &application_classes {
+                if !class_name.starts_with(prefix.as_str()) {
+                    continue;
+                }
+                kept = true;
+                usage.classes.insert(class_name.to_string());
+                if let Some(class_file) = usage.find_class(class_name)? {
+                    methods.extend(class_entry_points(&class_file, class_name, None));
+                }
+            }
+            if !kept {
+                debug!("unknown entry point; prefix={prefix}");
+                report.unknown_entry_points.push(prefix.clone());
+            }
+        }
+
+        let graph = CallGraph::build(class_path, hierarchy, &methods, algorithm)?;
+        let external: HashSet<&MethodId> = graph.external_methods().collect();
+        for method in graph.methods() {
+            if external.contains(method) {
+                continue;
+            }
+            usage.classes.insert(method.class_name.clone());
+            let Some(class_file) = usage.find_class(&method.class_name)? else {
+                continue;
+            };
+            let Some(declared) = class_file.declared_method(&method.name, &method.descriptor)
+            else {
+                continue;
+            };
+            if let Err(error) = usage.add_method(&class_file, declared) {
+                error!("can't analyse method; method={method}, error={error}");
+                usage.invalid_classes.insert(method.class_name.clone());
+            }
+        }
+        usage
+            .classes
+            .extend(graph.instantiated_classes().iter().cloned());
+
+        let mut used_fields = HashSet::new();
+        for field in std::mem::take(&mut usage.named_fields) {
+            if let Some(resolved) = usage.resolve_field(&field)? {
+                usage.classes.insert(resolved.class_name.clone());
+                used_fields.insert(resolved);
+            }
+        }
+        let supertypes: Vec<String> = usage
+            .classes
+            .iter()
+            .flat_map(|class_name| hierarchy.supertypes(class_name))
+            .map(str::to_string)
+            .collect();
+        usage.classes.extend(supertypes);
+
+        let mut used_entries = HashSet::new();
+        for class_name in application_classes {
+            if !usage.classes.contains(class_name) {
+                report.unused_classes.push(class_name.to_string());
+                continue;
+            }
+            used_entries.insert(owners[class_name]);
+            let Some(class_file) = usage.find_class(class_name)? else {
+                continue;
+            };
+            let constant_pool = &class_file.constant_pool;
+            for method in &class_file.methods {
+                let (Ok(name), Ok(descriptor)) =
+                    (method.name(constant_pool), method.descriptor(constant_pool))
+                else {
+                    continue;
+                };
+                let method_id = MethodId::new(class_name, name, descriptor);
+                if method.code().is_some() && !graph.is_reachable(&method_id) {
+                    report.unreachable_methods.push(method_id);
+                }
+            }
+            for field in &class_file.fields {
+                let (Ok(name), Ok(descriptor)) =
+                    (field.name(constant_pool), field.descriptor(constant_pool))
+                else {
+                    continue;
+                };
+                let field_id = FieldId::new(class_name, name, descriptor);
+                if !used_fields.contains(&field_id) {
+                    report.unused_fields.push(field_id);
+                }
+            }
+        }
+        report.unreachable_methods.sort();
+        report.unused_fields.sort();
+
+        for (position, entry) in class_path.entries().iter().enumerate() {
+            if !entry.source.is_jdk()
+                && !used_entries.contains(&position)
+                && !class_path.entry_class_names(entry).is_empty()
+            {
+                report.unused_archives.push(entry.location.clone());
+            }
+        }
+        usage
+            .invalid_classes
+            .extend(graph.invalid_classes().iter().cloned());
+        report.invalid_classes = usage.invalid_classes.into_iter().collect();
+        Ok(report)
+    }
+}
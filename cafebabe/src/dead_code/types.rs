@@ -0,0 +1,75 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::call_graph::MethodId;
+
+/// A field by its class, name and descriptor, e.g. `com/acme/Config.VALUE:I`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldId {
+    pub class_name: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+impl FieldId {
+    pub fn new(
+        class_name: impl Into<String>,
+        name: impl Into<String>,
+        descriptor: impl Into<String>,
+    ) -> FieldId {
+        FieldId {
+            class_name: class_name.into(),
+            name: name.into(),
+            descriptor: descriptor.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}:{}", self.class_name, self.name, self.descriptor)
+    }
+}
+
+/// Where the code of a program starts, for the dead code analysis.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryPoints {
+    /// Whether the `main` methods of all classes other than the JDK are entry points.
+    pub main_methods: bool,
+    /// Methods that are entry points, by the class name in its internal form and the method name, e.g.
+    /// `com/acme/Handler.handle` for all its overloads, optionally followed by a descriptor, e.g.
+    /// `com/acme/Handler.handle(Ljava/lang/String;)V`.
+    pub methods: Vec<String>,
+    /// Prefixes of class names in their internal form, e.g. `com/acme/api/`, whose classes are kept with all their
+    /// methods as entry points, for code that is only called by reflection, serialization or a framework.
+    pub keep: Vec<String>,
+}
+
+impl EntryPoints {
+    /// The `main` methods of the program.
+    pub fn main_methods() -> EntryPoints {
+        EntryPoints {
+            main_methods: true,
+            ..EntryPoints::default()
+        }
+    }
+}
+
+/// What is never used when a program runs from its entry points, for the classes other than the JDK.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DeadCodeReport {
+    /// The classes that reachable code doesn't refer to, sorted.
+    pub unused_classes: Vec<String>,
+    /// The methods with code of the used classes that aren't reachable, sorted.
+    pub unreachable_methods: Vec<MethodId>,
+    /// The fields of the used classes that reachable code doesn't read or write, sorted.
+    pub unused_fields: Vec<FieldId>,
+    /// The locations of the class path entries that have classes but none used, in class path order.
+    pub unused_archives: Vec<PathBuf>,
+    /// The methods and prefixes of the entry points that match nothing on the class path.
+    pub unknown_entry_points: Vec<String>,
+    /// The classes that couldn't be parsed or analysed, sorted.
+    pub invalid_classes: Vec<String>,
+}
@@ -0,0 +1,339 @@
+//! Decompiling classes to Java-like source code for reading: the instructions of each method are lifted to statements
+//! and expressions by simulating the operand stack, and its control flow graph is structured into `if`, `switch`,
+//! loops and `try` statements. Calls of `invokedynamic` are recognised as lambdas, method references and string
+//! concatenation where they use the bootstrap methods of the JDK for them.
+//!
+//! The source code isn't meant to compile: names of local variables come from the local variable table where there is
+//! one, generic types and annotations are left out, and control flow that has no structured form is marked with a
+//! `goto` comment.
+
+mod ast;
+mod lift;
+mod source;
+mod structure;
+
+use std::result::Result;
+
+use self::ast::{Expr, Literal, Stmt};
+use self::lift::{LocalNames, Method};
+use self::source::Printer;
+use crate::bytecode::Opcode;
+use crate::constant_pool::types::ConstantPoolEntry;
+use crate::control_flow::ControlFlowGraph;
+use crate::descriptor::{FieldType, MethodDescriptor};
+use crate::errors::ClassFileError;
+use crate::{AccessFlags, ClassFile, FieldAccessFlags, MethodAccessFlags, MethodInfo};
+
+/// The parameters and statements of a decompiled method.
+struct Body {
+    parameters: Vec<(String, FieldType)>,
+    statements: Vec<Stmt>,
+}
+
+/// Decompiles a method, naming its first parameters by `names` where it has a name for them, as lambdas name the
+/// variables that they capture. `depth` counts the lambdas that the method is decompiled in.
+fn method_body(
+    class_file: &ClassFile,
+    method: &MethodInfo,
+    names: &[Option<String>],
+    depth: usize,
+) -> Result<Body, ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    let descriptor = MethodDescriptor::parse(method.descriptor(constant_pool)?)?;
+    let locals = LocalNames::new(class_file, method, &descriptor, names)?;
+    let parameters = locals
+        .parameters()
+        .iter()
+        .map(|(_, name, field_type)| (name.clone(), field_type.clone()))
+        .collect();
+    let Some(code) = method.code() else {
+        return Ok(Body {
+            parameters,
+            statements: Vec::new(),
+        });
+    };
+    let instructions = code.instructions()?;
+    if instructions
+        .iter()
+        .any(|instruction| matches!(instruction.opcode, Opcode::Jsr | Opcode::JsrW | Opcode::Ret))
+    {
+        return Ok(Body {
+            parameters,
+            statements: vec![Stmt::Comment(
+                "subroutines of jsr and ret aren't decompiled".to_string(),
+            )],
+        });
+    }
+    let graph = ControlFlowGraph::from_instructions(instructions, &code.exception_table)?;
+    let is_void = descriptor.return_type.is_none();
+    let is_constructor = method.name(constant_pool)? == "<init>";
+    let method = Method {
+        class_file,
+        class_name: class_file.this_class_name()?,
+        descriptor,
+        is_static: method.access_flags.contains(MethodAccessFlags::ACC_STATIC),
+        locals,
+        depth,
+    };
+    let nodes = lift::lift(&method, &graph)?;
+    let mut statements = structure::structure(nodes, &graph, &code.exception_table, constant_pool)?;
+
+    // `return;` at the end of a method and `super();` at the start of a constructor go without saying.
+    if is_void && statements.last() == Some(&Stmt::Return(None)) {
+        statements.pop();
+    }
+    if is_constructor
+        && let Some(Stmt::Expr(Expr::ConstructorCall {
+            this: false,
+            arguments,
+        })) = statements.first()
+        && arguments.is_empty()
+    {
+        statements.remove(0);
+    }
+    Ok(Body {
+        parameters,
+        statements,
+    })
+}
+
+fn field_constant(class_file: &ClassFile, value_idx: u16) -> Result<Option<Expr>, ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    let literal = match constant_pool.checked_entry(value_idx)? {
+        ConstantPoolEntry::Integer(value) => Literal::Int(*value),
+        ConstantPoolEntry::Long(value) => Literal::Long(*value),
+        ConstantPoolEntry::Float(value) => Literal::Float(*value),
+        ConstantPoolEntry::Double(value) => Literal::Double(*value),
+        ConstantPoolEntry::StringRef(string_idx) => {
+            Literal::String(constant_pool.string_entry(*string_idx)?.clone())
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(Expr::Literal(literal)))
+}
+
+fn method_modifiers(access_flags: MethodAccessFlags, is_interface: bool) -> String {
+    let mut modifiers = String::new();
+    for (flag, modifier) in [
+        (MethodAccessFlags::ACC_PUBLIC, "public "),
+        (MethodAccessFlags::ACC_PRIVATE, "private "),
+        (MethodAccessFlags::ACC_PROTECTED, "protected "),
+        (MethodAccessFlags::ACC_STATIC, "static "),
+        (MethodAccessFlags::ACC_FINAL, "final "),
+        (MethodAccessFlags::ACC_SYNCHRONIZED, "synchronized "),
+        (MethodAccessFlags::ACC_NATIVE, "native "),
+    ] {
+        if access_flags.contains(flag) {
+            modifiers.push_str(modifier);
+        }
+    }
+    if access_flags.contains(MethodAccessFlags::ACC_ABSTRACT) && !is_interface {
+        modifiers.push_str("abstract ");
+    }
+    let is_default = is_interface
+        && !access_flags.intersects(
+            MethodAccessFlags::ACC_ABSTRACT
+                | MethodAccessFlags::ACC_STATIC
+                | MethodAccessFlags::ACC_PRIVATE,
+        );
+    if is_default {
+        modifiers.push_str("default ");
+    }
+    modifiers
+}
+
+fn print_method(
+    printer: &mut Printer,
+    class_file: &ClassFile,
+    method: &MethodInfo,
+) -> Result<(), ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    let name = method.name(constant_pool)?;
+    let descriptor = MethodDescriptor::parse(method.descriptor(constant_pool)?)?;
+    let is_interface = class_file.access_flags.contains(AccessFlags::ACC_INTERFACE);
+    let access_flags = method.access_flags;
+    let body = method_body(class_file, method, &[], 0);
+    let parameters = match &body {
+        Ok(body) => body.parameters.clone(),
+        Err(_) => LocalNames::new(class_file, method, &descriptor, &[])?
+            .parameters()
+            .iter()
+            .map(|(_, name, field_type)| (name.clone(), field_type.clone()))
+            .collect(),
+    };
+
+    let mut header = method_modifiers(access_flags, is_interface);
+    match name.as_str() {
+        "<clinit>" => header = "static".to_string(),
+        "<init>" => {
+            let class_name = class_file.this_class_name()?;
+            let simple_name = class_name.rsplit(['/', '$']).next().unwrap_or(class_name);
+            header.push_str(simple_name);
+        }
+        _ => {
+            let return_type = match &descriptor.return_type {
+                Some(return_type) => printer.field_type(return_type),
+                None => "void".to_string(),
+            };
+            header.push_str(&format!("{return_type} {name}"));
+        }
+    }
+    if name != "<clinit>" {
+        let count = parameters.len();
+        let parameters: Vec<String> = parameters
+            .iter()
+            .enumerate()
+            .map(|(position, (name, field_type))| match field_type {
+                FieldType::Array(element_type)
+                    if position + 1 == count
+                        && access_flags.contains(MethodAccessFlags::ACC_VARARGS) =>
+                {
+                    format!("{}... {name}", printer.field_type(element_type))
+                }
+                field_type => format!("{} {name}", printer.field_type(field_type)),
+            })
+            .collect();
+        header.push_str(&format!("({})", parameters.join(", ")));
+        let exceptions: Vec<String> = method
+            .exceptions(constant_pool)?
+            .into_iter()
+            .map(|exception| printer.type_name(exception))
+            .collect();
+        if !exceptions.is_empty() {
+            header.push_str(&format!(" throws {}", exceptions.join(", ")));
+        }
+    }
+
+    if method.code().is_none() {
+        printer.line(&format!("{header};"));
+        return Ok(());
+    }
+    match body {
+        Ok(body) => {
+            let names: Vec<String> = body
+                .parameters
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect();
+            printer.body(&header, &body.statements, &names, descriptor.return_type);
+        }
+        Err(error) => {
+            printer.line(&format!("{header} {{"));
+            printer.indent();
+            printer.line(&format!("// can't decompile: {error}"));
+            printer.dedent();
+            printer.line("}");
+        }
+    }
+    Ok(())
+}
+
+/// Decompiles a class to Java-like source code.
+pub fn decompile(class_file: &ClassFile) -> Result<String, ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    let class_name = class_file.this_class_name()?;
+    let mut printer = Printer::new(class_name);
+    if let Some((package, _)) = class_name.rsplit_once('/') {
+        printer.line(&format!("package {};", package.replace('/', ".")));
+        printer.line("");
+    }
+
+    let access_flags = class_file.access_flags;
+    let mut header = String::new();
+    if access_flags.contains(AccessFlags::ACC_PUBLIC) {
+        header.push_str("public ");
+    }
+    let kind = if access_flags.contains(AccessFlags::ACC_ANNOTATION) {
+        "@interface"
+    } else if access_flags.contains(AccessFlags::ACC_INTERFACE) {
+        "interface"
+    } else if access_flags.contains(AccessFlags::ACC_ENUM) {
+        "enum"
+    } else {
+        if access_flags.contains(AccessFlags::ACC_ABSTRACT) {
+            header.push_str("abstract ");
+        }
+        if access_flags.contains(AccessFlags::ACC_FINAL) {
+            header.push_str("final ");
+        }
+        "class"
+    };
+    let simple_name = class_name.rsplit('/').next().unwrap_or(class_name);
+    header.push_str(&format!("{kind} {simple_name}"));
+    let is_interface = access_flags.contains(AccessFlags::ACC_INTERFACE);
+    if let Some(super_class_name) = class_file.super_class_name()?
+        && super_class_name != "java/lang/Object"
+        && super_class_name != "java/lang/Enum"
+    {
+        header.push_str(&format!(" extends {}", printer.type_name(super_class_name)));
+    }
+    let interfaces: Vec<String> = class_file
+        .interface_names()?
+        .into_iter()
+        .filter(|interface| *interface != "java/lang/annotation/Annotation")
+        .map(|interface| printer.type_name(interface))
+        .collect();
+    if !interfaces.is_empty() {
+        let keyword = match is_interface {
+            true => "extends",
+            false => "implements",
+        };
+        header.push_str(&format!(" {keyword} {}", interfaces.join(", ")));
+    }
+    printer.line(&format!("{header} {{"));
+    printer.indent();
+
+    let mut fields = 0;
+    for field in &class_file.fields {
+        if field.is_synthetic() {
+            continue;
+        }
+        let mut line = String::new();
+        for (flag, modifier) in [
+            (FieldAccessFlags::ACC_PUBLIC, "public "),
+            (FieldAccessFlags::ACC_PRIVATE, "private "),
+            (FieldAccessFlags::ACC_PROTECTED, "protected "),
+            (FieldAccessFlags::ACC_STATIC, "static "),
+            (FieldAccessFlags::ACC_FINAL, "final "),
+            (FieldAccessFlags::ACC_VOLATILE, "volatile "),
+            (FieldAccessFlags::ACC_TRANSIENT, "transient "),
+        ] {
+            if field.access_flags.contains(flag) {
+                line.push_str(modifier);
+            }
+        }
+        let field_type = FieldType::parse(field.descriptor(constant_pool)?)?;
+        line.push_str(&format!(
+            "{} {}",
+            printer.field_type(&field_type),
+            field.name(constant_pool)?
+        ));
+        if let Some(value_idx) = field.constant_value_idx()
+            && let Some(value) = field_constant(class_file, value_idx)?
+        {
+            let value = printer.value(&value, &field_type);
+            line.push_str(&format!(" = {value}"));
+        }
+        printer.line(&format!("{line};"));
+        fields += 1;
+    }
+
+    let mut first = fields == 0;
+    for method in &class_file.methods {
+        let name = method.name(constant_pool)?;
+        if method.is_synthetic()
+            || method.access_flags.contains(MethodAccessFlags::ACC_BRIDGE)
+            || name.starts_with("lambda$")
+        {
+            continue;
+        }
+        if !first {
+            printer.line("");
+        }
+        first = false;
+        print_method(&mut printer, class_file, method)?;
+    }
+    printer.dedent();
+    printer.line("}");
+    Ok(printer.out)
+}
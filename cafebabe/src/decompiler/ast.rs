@@ -0,0 +1,550 @@
+//! The Java-like syntax tree that methods are decompiled into.
+
+use crate::descriptor::{FieldType, MethodDescriptor};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Literal {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Boolean(bool),
+    Char(u16),
+    String(String),
+    Null,
+    /// A class literal, e.g. `String.class`.
+    Class(FieldType),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    ShiftLeft,
+    ShiftRight,
+    UnsignedShiftRight,
+    And,
+    Or,
+    Xor,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    GreaterOrEqual,
+    Greater,
+    LessOrEqual,
+}
+
+impl Comparison {
+    pub(super) fn negate(self) -> Comparison {
+        match self {
+            Comparison::Equal => Comparison::NotEqual,
+            Comparison::NotEqual => Comparison::Equal,
+            Comparison::Less => Comparison::GreaterOrEqual,
+            Comparison::GreaterOrEqual => Comparison::Less,
+            Comparison::Greater => Comparison::LessOrEqual,
+            Comparison::LessOrEqual => Comparison::Greater,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum Logical {
+    And,
+    Or,
+}
+
+/// What a method is called on.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Receiver {
+    Static,
+    Object(Box<Expr>),
+    /// The method of the superclass that an `invokespecial` on `this` calls.
+    Super,
+}
+
+/// What a method reference binds, before the `::`.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Qualifier {
+    Type(String),
+    Object(Box<Expr>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Expr {
+    Literal(Literal),
+    Local {
+        name: String,
+        field_type: Option<FieldType>,
+    },
+    This,
+    /// The exception that a handler catches, until it is stored in the variable of the catch clause.
+    CaughtException,
+    /// A value left on the operand stack where control flow meets, by its depth.
+    StackSlot(usize),
+    /// A value that is kept aside so that it is evaluated once and in order.
+    Temporary(usize),
+    StaticField {
+        class_name: String,
+        name: String,
+        descriptor: String,
+    },
+    Field {
+        object: Box<Expr>,
+        name: String,
+        descriptor: String,
+    },
+    ArrayElement {
+        array: Box<Expr>,
+        index: Box<Expr>,
+    },
+    ArrayLength(Box<Expr>),
+    Invoke {
+        receiver: Receiver,
+        class_name: String,
+        name: String,
+        descriptor: String,
+        arguments: Vec<Expr>,
+    },
+    /// A call of another constructor at the start of a constructor: `super(...)` or `this(...)`.
+    ConstructorCall {
+        this: bool,
+        arguments: Vec<Expr>,
+    },
+    New {
+        class_name: String,
+        arguments: Vec<Expr>,
+    },
+    /// An object that `new` created and whose constructor hasn't been called yet.
+    Uninitialized {
+        id: usize,
+        class_name: String,
+    },
+    /// An array with the lengths of its first dimensions, of an element type that may have more dimensions.
+    NewArray {
+        element_type: FieldType,
+        lengths: Vec<Expr>,
+    },
+    /// An array created with its elements, which are stored right after it is created.
+    ArrayInitializer {
+        id: usize,
+        element_type: FieldType,
+        length: i32,
+        elements: Vec<Expr>,
+    },
+    Binary(BinaryOperator, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparison, Box<Expr>, Box<Expr>),
+    /// The -1, 0 or 1 of `lcmp`, `fcmpl`, `fcmpg`, `dcmpl` and `dcmpg`, until a branch compares it with zero.
+    CompareResult(Box<Expr>, Box<Expr>),
+    Logical(Logical, Box<Expr>, Box<Expr>),
+    Conditional {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+    Cast(FieldType, Box<Expr>),
+    InstanceOf(Box<Expr>, FieldType),
+    Assign(Box<Expr>, Box<Expr>),
+    /// `i++` or `i--` of a local variable that is read before `iinc` changes it.
+    PostIncrement(Box<Expr>, i16),
+    Lambda {
+        parameters: Vec<String>,
+        body: Vec<Stmt>,
+    },
+    MethodReference {
+        qualifier: Qualifier,
+        name: String,
+    },
+    /// String concatenation, of string literals and other values.
+    Concat(Vec<Expr>),
+    /// A call site or constant of `invokedynamic` or `ldc` with a bootstrap method that isn't recognised.
+    Dynamic {
+        bootstrap: String,
+        name: String,
+        arguments: Vec<Expr>,
+    },
+    /// A constant that Java has no syntax for, such as a method handle, printed as it is.
+    Raw(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum LoopKind {
+    Infinite,
+    While(Expr),
+    DoWhile(Expr),
+    For {
+        init: Box<Stmt>,
+        condition: Expr,
+        update: Box<Stmt>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Catch {
+    /// The classes of the caught exceptions, or none for all of them.
+    pub class_names: Vec<String>,
+    pub variable: String,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Stmt {
+    Expr(Expr),
+    Return(Option<Expr>),
+    Throw(Expr),
+    If {
+        condition: Expr,
+        then: Vec<Stmt>,
+        otherwise: Vec<Stmt>,
+    },
+    Loop {
+        label: usize,
+        kind: LoopKind,
+        body: Vec<Stmt>,
+    },
+    Switch {
+        label: usize,
+        value: Expr,
+        /// The keys of each case, `None` for the default, with the statements of the case.
+        cases: Vec<(Vec<Option<i32>>, Vec<Stmt>)>,
+    },
+    Try {
+        body: Vec<Stmt>,
+        catches: Vec<Catch>,
+        finally: Vec<Stmt>,
+    },
+    /// Leaves the loop or switch with the label.
+    Break(usize),
+    /// Continues the loop with the label.
+    Continue(usize),
+    MonitorEnter(Expr),
+    MonitorExit(Expr),
+    Comment(String),
+}
+
+impl Stmt {
+    /// Whether control never goes on to the next statement.
+    pub(super) fn is_jump(&self) -> bool {
+        match self {
+            Stmt::Return(_) | Stmt::Throw(_) | Stmt::Break(_) | Stmt::Continue(_) => true,
+            Stmt::If {
+                then, otherwise, ..
+            } => ends_with_jump(then) && ends_with_jump(otherwise),
+            _ => false,
+        }
+    }
+}
+
+pub(super) fn ends_with_jump(statements: &[Stmt]) -> bool {
+    statements.last().is_some_and(Stmt::is_jump)
+}
+
+impl Expr {
+    pub(super) fn int(value: i32) -> Expr {
+        Expr::Literal(Literal::Int(value))
+    }
+
+    /// Whether evaluating the expression has no effects and gives the same value wherever it is evaluated, so that it
+    /// can be copied.
+    pub(super) fn is_simple(&self) -> bool {
+        matches!(
+            self,
+            Expr::Literal(_)
+                | Expr::Local { .. }
+                | Expr::This
+                | Expr::CaughtException
+                | Expr::StackSlot(_)
+                | Expr::Temporary(_)
+        )
+    }
+
+    /// Whether the value of the expression can't be changed by a statement that doesn't assign a local variable: it
+    /// reads no fields or arrays and calls nothing.
+    pub(super) fn is_stable(&self) -> bool {
+        match self {
+            Expr::Literal(_)
+            | Expr::Local { .. }
+            | Expr::This
+            | Expr::CaughtException
+            | Expr::StackSlot(_)
+            | Expr::Temporary(_)
+            | Expr::Uninitialized { .. }
+            | Expr::Raw(_)
+            | Expr::MethodReference {
+                qualifier: Qualifier::Type(_),
+                ..
+            } => true,
+            Expr::Binary(_, left, right)
+            | Expr::Compare(_, left, right)
+            | Expr::CompareResult(left, right)
+            | Expr::Logical(_, left, right) => left.is_stable() && right.is_stable(),
+            Expr::Negate(operand)
+            | Expr::Not(operand)
+            | Expr::Cast(_, operand)
+            | Expr::InstanceOf(operand, _) => operand.is_stable(),
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => condition.is_stable() && then.is_stable() && otherwise.is_stable(),
+            Expr::ArrayInitializer { elements, .. } | Expr::Concat(elements) => {
+                elements.iter().all(Expr::is_stable)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the expression reads the local variable with a name.
+    pub(super) fn reads_local(&self, local: &str) -> bool {
+        let mut found = false;
+        self.visit(&mut |expr| {
+            if let Expr::Local { name, .. } = expr {
+                found |= name == local;
+            }
+        });
+        found
+    }
+
+    /// Calls a function on the expression and all expressions in it, but not in the bodies of lambdas.
+    pub(super) fn visit(&self, visitor: &mut dyn FnMut(&Expr)) {
+        visitor(self);
+        match self {
+            Expr::Field { object: inner, .. }
+            | Expr::ArrayLength(inner)
+            | Expr::Negate(inner)
+            | Expr::Not(inner)
+            | Expr::Cast(_, inner)
+            | Expr::InstanceOf(inner, _)
+            | Expr::PostIncrement(inner, _)
+            | Expr::MethodReference {
+                qualifier: Qualifier::Object(inner),
+                ..
+            } => inner.visit(visitor),
+            Expr::ArrayElement { array, index } => {
+                array.visit(visitor);
+                index.visit(visitor);
+            }
+            Expr::Invoke {
+                receiver,
+                arguments,
+                ..
+            } => {
+                if let Receiver::Object(object) = receiver {
+                    object.visit(visitor);
+                }
+                arguments
+                    .iter()
+                    .for_each(|argument| argument.visit(visitor));
+            }
+            Expr::ConstructorCall { arguments, .. }
+            | Expr::New { arguments, .. }
+            | Expr::Dynamic { arguments, .. }
+            | Expr::Concat(arguments)
+            | Expr::NewArray {
+                lengths: arguments, ..
+            }
+            | Expr::ArrayInitializer {
+                elements: arguments,
+                ..
+            } => arguments
+                .iter()
+                .for_each(|argument| argument.visit(visitor)),
+            Expr::Binary(_, left, right)
+            | Expr::Compare(_, left, right)
+            | Expr::CompareResult(left, right)
+            | Expr::Logical(_, left, right)
+            | Expr::Assign(left, right) => {
+                left.visit(visitor);
+                right.visit(visitor);
+            }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                condition.visit(visitor);
+                then.visit(visitor);
+                otherwise.visit(visitor);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the expression has the type `boolean`, as far as it can be told.
+    pub(super) fn is_condition(&self) -> bool {
+        match self {
+            Expr::Literal(Literal::Boolean(_))
+            | Expr::Not(_)
+            | Expr::Compare(..)
+            | Expr::Logical(..)
+            | Expr::InstanceOf(..) => true,
+            Expr::Local { field_type, .. } => field_type == &Some(FieldType::Boolean),
+            Expr::StaticField { descriptor, .. } | Expr::Field { descriptor, .. } => {
+                descriptor == "Z"
+            }
+            Expr::Invoke { descriptor, .. } => descriptor.ends_with(")Z"),
+            Expr::Conditional {
+                then, otherwise, ..
+            } => then.is_condition() && otherwise.is_condition(),
+            Expr::Assign(_, value) => value.is_condition(),
+            _ => false,
+        }
+    }
+
+    /// Gives a literal the type of where it is used, so that `1` is printed as `true` where a `boolean` is expected.
+    pub(super) fn coerce(self, field_type: &FieldType) -> Expr {
+        match (self, field_type) {
+            (Expr::Literal(Literal::Int(value)), FieldType::Boolean)
+                if value == 0 || value == 1 =>
+            {
+                Expr::Literal(Literal::Boolean(value == 1))
+            }
+            (Expr::Literal(Literal::Int(value)), FieldType::Char)
+                if (0..=0xffff).contains(&value) =>
+            {
+                Expr::Literal(Literal::Char(value as u16))
+            }
+            (
+                Expr::Conditional {
+                    condition,
+                    then,
+                    otherwise,
+                },
+                FieldType::Boolean | FieldType::Char,
+            ) => conditional(
+                *condition,
+                then.coerce(field_type),
+                otherwise.coerce(field_type),
+            ),
+            (expr, _) => expr,
+        }
+    }
+}
+
+/// The negation of a condition, pushing `!` into comparisons and `&&` and `||`.
+pub(super) fn negate(condition: Expr) -> Expr {
+    match condition {
+        Expr::Not(inner) => *inner,
+        Expr::Compare(comparison, left, right) => Expr::Compare(comparison.negate(), left, right),
+        Expr::Logical(Logical::And, left, right) => Expr::Logical(
+            Logical::Or,
+            Box::new(negate(*left)),
+            Box::new(negate(*right)),
+        ),
+        Expr::Logical(Logical::Or, left, right) => Expr::Logical(
+            Logical::And,
+            Box::new(negate(*left)),
+            Box::new(negate(*right)),
+        ),
+        Expr::Literal(Literal::Boolean(value)) => Expr::Literal(Literal::Boolean(!value)),
+        other => Expr::Not(Box::new(other)),
+    }
+}
+
+pub(super) fn logical(operator: Logical, left: Expr, right: Expr) -> Expr {
+    Expr::Logical(operator, Box::new(left), Box::new(right))
+}
+
+/// `condition ? then : otherwise`, simplified to `&&`, `||` or the condition itself where a branch is a boolean
+/// literal, which is how `javac` compiles boolean expressions.
+pub(super) fn conditional(condition: Expr, then: Expr, otherwise: Expr) -> Expr {
+    let boolean = |expr: &Expr| match expr {
+        Expr::Literal(Literal::Boolean(value)) => Some(*value),
+        Expr::Literal(Literal::Int(0)) => Some(false),
+        Expr::Literal(Literal::Int(1)) => Some(true),
+        _ => None,
+    };
+    match (boolean(&then), boolean(&otherwise)) {
+        (Some(true), Some(false)) => return condition,
+        (Some(false), Some(true)) => return negate(condition),
+        (Some(false), None) if otherwise.is_condition() => {
+            return logical(Logical::And, negate(condition), otherwise);
+        }
+        (Some(true), None) if otherwise.is_condition() => {
+            return logical(Logical::Or, condition, otherwise);
+        }
+        (None, Some(false)) if then.is_condition() => {
+            return logical(Logical::And, condition, then);
+        }
+        (None, Some(true)) if then.is_condition() => {
+            return logical(Logical::Or, negate(condition), then);
+        }
+        _ => {}
+    }
+    // `a ? (b ? x : y) : y` is `a && b ? x : y`, and `a ? x : (b ? x : y)` is `a || b ? x : y`.
+    match (then, otherwise) {
+        (
+            Expr::Conditional {
+                condition: inner,
+                then: inner_then,
+                otherwise: inner_otherwise,
+            },
+            otherwise,
+        ) if *inner_otherwise == otherwise => conditional(
+            logical(Logical::And, condition, *inner),
+            *inner_then,
+            otherwise,
+        ),
+        (
+            then,
+            Expr::Conditional {
+                condition: inner,
+                then: inner_then,
+                otherwise: inner_otherwise,
+            },
+        ) if *inner_then == then => conditional(
+            logical(Logical::Or, condition, *inner),
+            then,
+            *inner_otherwise,
+        ),
+        (then, otherwise) => Expr::Conditional {
+            condition: Box::new(condition),
+            then: Box::new(then),
+            otherwise: Box::new(otherwise),
+        },
+    }
+}
+
+/// The type of the value of an expression, where it can be told from descriptors and the local variable table.
+pub(super) fn static_type(expr: &Expr) -> Option<FieldType> {
+    match expr {
+        Expr::Literal(literal) => match literal {
+            Literal::Int(_) => Some(FieldType::Int),
+            Literal::Long(_) => Some(FieldType::Long),
+            Literal::Float(_) => Some(FieldType::Float),
+            Literal::Double(_) => Some(FieldType::Double),
+            Literal::Boolean(_) => Some(FieldType::Boolean),
+            Literal::Char(_) => Some(FieldType::Char),
+            Literal::String(_) => Some(FieldType::Object("java/lang/String".to_string())),
+            Literal::Class(_) => Some(FieldType::Object("java/lang/Class".to_string())),
+            Literal::Null => None,
+        },
+        Expr::Local { field_type, .. } => field_type.clone(),
+        Expr::StaticField { descriptor, .. } | Expr::Field { descriptor, .. } => {
+            FieldType::parse(descriptor).ok()
+        }
+        Expr::Invoke { descriptor, .. } => MethodDescriptor::parse(descriptor)
+            .ok()
+            .and_then(|descriptor| descriptor.return_type),
+        Expr::ArrayElement { array, .. } => match static_type(array) {
+            Some(FieldType::Array(element_type)) => Some(*element_type),
+            _ => None,
+        },
+        Expr::ArrayLength(_) => Some(FieldType::Int),
+        Expr::New { class_name, .. } => Some(FieldType::Object(class_name.clone())),
+        Expr::Cast(field_type, _) => Some(field_type.clone()),
+        Expr::Concat(_) => Some(FieldType::Object("java/lang/String".to_string())),
+        Expr::Assign(target, _) => static_type(target),
+        Expr::PostIncrement(operand, _) | Expr::Negate(operand) => static_type(operand),
+        expr if expr.is_condition() => Some(FieldType::Boolean),
+        _ => None,
+    }
+}
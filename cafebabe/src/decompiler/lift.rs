@@ -0,0 +1,1653 @@
+//! Lifting the instructions of a method to statements and expressions by simulating the operand stack with
+//! expressions instead of values. Each block of the control flow graph becomes a node that ends by going to other nodes,
+//! returning or throwing.
+//!
+//! Blocks that only compute a value for the stack of another block, as `javac` compiles `?:`, `&&` and `||` to, are
+//! folded into conditional expressions of the node that branches to them. Values that are still on the stack where
+//! control flow meets otherwise are kept in stack variables.
+
+use log::error;
+
+use super::Body;
+use super::ast::{
+    BinaryOperator, Comparison, Expr, Literal, Qualifier, Receiver, Stmt, conditional, negate,
+    static_type,
+};
+use crate::bootstrap::{BootstrapArgument, BootstrapKind, CallSite};
+use crate::bytecode::{Instruction, Opcode, Operand};
+use crate::constant_pool::types::{ConstantPoolEntry, ReferenceKind};
+use crate::control_flow::dominators::reverse_postorder;
+use crate::control_flow::{ControlFlowGraph, EdgeKind};
+use crate::descriptor::{FieldType, MethodDescriptor};
+use crate::errors::ClassFileError;
+use crate::{ClassFile, MethodAccessFlags, MethodInfo};
+
+/// How deeply the bodies of lambdas are decompiled into the lambda expressions that create them.
+const MAX_LAMBDA_DEPTH: usize = 8;
+
+/// How many blocks deep a conditional expression is looked for.
+const MAX_VALUE_DEPTH: usize = 16;
+
+/// The names and types of the local variables of a method, from its local variable table and its parameters.
+pub(super) struct LocalNames {
+    variables: Vec<Variable>,
+    /// The slot, name and type of each parameter, not counting `this`.
+    parameters: Vec<(u16, String, FieldType)>,
+}
+
+struct Variable {
+    index: u16,
+    start: u32,
+    end: u32,
+    name: String,
+    field_type: Option<FieldType>,
+}
+
+impl LocalNames {
+    /// Names the parameters by `names` where it has a name for them, and else by the local variable table, the
+    /// MethodParameters attribute or their position.
+    pub(super) fn new(
+        class_file: &ClassFile,
+        method: &MethodInfo,
+        descriptor: &MethodDescriptor,
+        names: &[Option<String>],
+    ) -> Result<LocalNames, ClassFileError> {
+        let constant_pool = &class_file.constant_pool;
+        let mut variables = Vec::new();
+        if let Some(code) = method.code() {
+            for variable in code.local_variables() {
+                let start = u32::from(variable.start_pc);
+                variables.push(Variable {
+                    index: variable.index,
+                    start,
+                    end: start + u32::from(variable.length),
+                    name: constant_pool.string_entry(variable.name_idx)?.clone(),
+                    field_type: FieldType::parse(
+                        constant_pool.string_entry(variable.descriptor_idx)?,
+                    )
+                    .ok(),
+                });
+            }
+        }
+        let declared = method.parameters(constant_pool)?;
+        let mut slot = match method.access_flags.contains(MethodAccessFlags::ACC_STATIC) {
+            true => 0,
+            false => 1,
+        };
+        let mut parameters = Vec::new();
+        for (position, field_type) in descriptor.parameters.iter().enumerate() {
+            let name = names
+                .get(position)
+                .cloned()
+                .flatten()
+                .or_else(|| {
+                    variables
+                        .iter()
+                        .find(|variable| variable.index == slot && variable.start == 0)
+                        .map(|variable| variable.name.clone())
+                })
+                .or_else(|| {
+                    declared
+                        .get(position)
+                        .and_then(|parameter| parameter.name.clone())
+                })
+                .unwrap_or_else(|| format!("arg{position}"));
+            parameters.push((slot, name, field_type.clone()));
+            slot += field_type.slots();
+        }
+        Ok(LocalNames {
+            variables,
+            parameters,
+        })
+    }
+
+    pub(super) fn parameters(&self) -> &[(u16, String, FieldType)] {
+        &self.parameters
+    }
+
+    fn find(&self, slot: u16, offset: u32) -> Option<Expr> {
+        let variable = self.variables.iter().find(|variable| {
+            variable.index == slot && variable.start <= offset && offset < variable.end
+        })?;
+        Some(Expr::Local {
+            name: variable.name.clone(),
+            field_type: variable.field_type.clone(),
+        })
+    }
+
+    fn fallback(&self, slot: u16) -> Expr {
+        match self.parameters.iter().find(|parameter| parameter.0 == slot) {
+            Some((_, name, field_type)) => Expr::Local {
+                name: name.clone(),
+                field_type: Some(field_type.clone()),
+            },
+            None => Expr::Local {
+                name: format!("var{slot}"),
+                field_type: None,
+            },
+        }
+    }
+
+    /// The variable in a slot that an instruction at an offset reads.
+    fn load(&self, slot: u16, offset: u32) -> Expr {
+        self.find(slot, offset)
+            .unwrap_or_else(|| self.fallback(slot))
+    }
+
+    /// The variable in a slot that an instruction at an offset writes, whose scope usually starts at the next
+    /// instruction.
+    fn store(&self, slot: u16, offset: u32, next_offset: u32) -> Expr {
+        self.find(slot, next_offset)
+            .or_else(|| self.find(slot, offset))
+            .unwrap_or_else(|| self.fallback(slot))
+    }
+}
+
+/// A method with what lifting its instructions needs to know about it.
+pub(super) struct Method<'a> {
+    pub class_file: &'a ClassFile,
+    pub class_name: &'a str,
+    pub descriptor: MethodDescriptor,
+    pub is_static: bool,
+    pub locals: LocalNames,
+    /// How deeply the lambda that this method implements is nested in another method.
+    pub depth: usize,
+}
+
+/// How a node is left.
+#[derive(Clone, Debug)]
+pub(super) enum Exit {
+    Goto(usize),
+    /// Goes to `target` if the condition holds, and else to `next`.
+    Branch {
+        condition: Expr,
+        target: usize,
+        next: usize,
+    },
+    Switch {
+        value: Expr,
+        cases: Vec<(i32, usize)>,
+        default: usize,
+    },
+    Return(Option<Expr>),
+    Throw(Expr),
+}
+
+/// The statements that one or more blocks of the control flow graph were lifted to, at the position of its first
+/// block.
+#[derive(Debug)]
+pub(super) struct Node {
+    pub start: u32,
+    /// The blocks that the node was lifted from, starting with its own.
+    pub blocks: Vec<usize>,
+    pub statements: Vec<Stmt>,
+    pub exit: Exit,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Value {
+    expr: Expr,
+    /// Whether the value is a `long` or a `double`, which take two words of the stack.
+    wide: bool,
+}
+
+impl Value {
+    fn new(expr: Expr, wide: bool) -> Value {
+        Value { expr, wide }
+    }
+}
+
+/// Where control goes after the instructions of a block.
+enum Terminator {
+    Next(usize),
+    Branch {
+        condition: Expr,
+        target: usize,
+        next: usize,
+    },
+    Switch {
+        value: Expr,
+        cases: Vec<(i32, usize)>,
+        default: usize,
+    },
+    Return(Option<Expr>),
+    Throw(Expr),
+}
+
+fn is_wide(field_type: &FieldType) -> bool {
+    field_type.slots() == 2
+}
+
+fn class_type(class_name: &str) -> Result<FieldType, ClassFileError> {
+    match class_name.starts_with('[') {
+        true => FieldType::parse(class_name),
+        false => Ok(FieldType::Object(class_name.to_string())),
+    }
+}
+
+fn array_element_type(array_type: u8) -> Option<FieldType> {
+    match array_type {
+        4 => Some(FieldType::Boolean),
+        5 => Some(FieldType::Char),
+        6 => Some(FieldType::Float),
+        7 => Some(FieldType::Double),
+        8 => Some(FieldType::Byte),
+        9 => Some(FieldType::Short),
+        10 => Some(FieldType::Int),
+        11 => Some(FieldType::Long),
+        _ => None,
+    }
+}
+
+/// The slot of a load or store instruction, with whether it holds a `long` or a `double`.
+fn local_slot(instruction: &Instruction) -> Option<(u16, bool)> {
+    use Opcode::*;
+    let implicit = |first: Opcode| (instruction.opcode as u8 - first as u8) as u16;
+    let slot = |wide: bool| match instruction.operand {
+        Operand::Local(index) => Some((index, wide)),
+        _ => None,
+    };
+    match instruction.opcode {
+        Iload | Fload | Aload | Istore | Fstore | Astore => slot(false),
+        Lload | Dload | Lstore | Dstore => slot(true),
+        Iload0 | Iload1 | Iload2 | Iload3 => Some((implicit(Iload0), false)),
+        Lload0 | Lload1 | Lload2 | Lload3 => Some((implicit(Lload0), true)),
+        Fload0 | Fload1 | Fload2 | Fload3 => Some((implicit(Fload0), false)),
+        Dload0 | Dload1 | Dload2 | Dload3 => Some((implicit(Dload0), true)),
+        Aload0 | Aload1 | Aload2 | Aload3 => Some((implicit(Aload0), false)),
+        Istore0 | Istore1 | Istore2 | Istore3 => Some((implicit(Istore0), false)),
+        Lstore0 | Lstore1 | Lstore2 | Lstore3 => Some((implicit(Lstore0), true)),
+        Fstore0 | Fstore1 | Fstore2 | Fstore3 => Some((implicit(Fstore0), false)),
+        Dstore0 | Dstore1 | Dstore2 | Dstore3 => Some((implicit(Dstore0), true)),
+        Astore0 | Astore1 | Astore2 | Astore3 => Some((implicit(Astore0), false)),
+        _ => None,
+    }
+}
+
+fn is_load(opcode: Opcode) -> bool {
+    (Opcode::Iload as u8..=Opcode::Aload3 as u8).contains(&(opcode as u8))
+}
+
+fn is_store(opcode: Opcode) -> bool {
+    (Opcode::Istore as u8..=Opcode::Astore3 as u8).contains(&(opcode as u8))
+}
+
+fn is_array_store(opcode: Opcode) -> bool {
+    (Opcode::Iastore as u8..=Opcode::Sastore as u8).contains(&(opcode as u8))
+}
+
+fn binary_operator(opcode: Opcode) -> Option<(BinaryOperator, bool)> {
+    use Opcode::*;
+    let operator = match opcode {
+        Iadd | Ladd | Fadd | Dadd => BinaryOperator::Add,
+        Isub | Lsub | Fsub | Dsub => BinaryOperator::Subtract,
+        Imul | Lmul | Fmul | Dmul => BinaryOperator::Multiply,
+        Idiv | Ldiv | Fdiv | Ddiv => BinaryOperator::Divide,
+        Irem | Lrem | Frem | Drem => BinaryOperator::Remainder,
+        Ishl | Lshl => BinaryOperator::ShiftLeft,
+        Ishr | Lshr => BinaryOperator::ShiftRight,
+        Iushr | Lushr => BinaryOperator::UnsignedShiftRight,
+        Iand | Land => BinaryOperator::And,
+        Ior | Lor => BinaryOperator::Or,
+        Ixor | Lxor => BinaryOperator::Xor,
+        _ => return None,
+    };
+    let wide = matches!(
+        opcode,
+        Ladd | Dadd
+            | Lsub
+            | Dsub
+            | Lmul
+            | Dmul
+            | Ldiv
+            | Ddiv
+            | Lrem
+            | Drem
+            | Lshl
+            | Lshr
+            | Lushr
+            | Land
+            | Lor
+            | Lxor
+    );
+    Some((operator, wide))
+}
+
+fn conversion(opcode: Opcode) -> Option<FieldType> {
+    use Opcode::*;
+    match opcode {
+        L2i | F2i | D2i => Some(FieldType::Int),
+        I2l | F2l | D2l => Some(FieldType::Long),
+        I2f | L2f | D2f => Some(FieldType::Float),
+        I2d | L2d | F2d => Some(FieldType::Double),
+        I2b => Some(FieldType::Byte),
+        I2c => Some(FieldType::Char),
+        I2s => Some(FieldType::Short),
+        _ => None,
+    }
+}
+
+fn comparison(opcode: Opcode) -> Option<Comparison> {
+    use Opcode::*;
+    match opcode {
+        Ifeq | IfIcmpeq | IfAcmpeq | Ifnull => Some(Comparison::Equal),
+        Ifne | IfIcmpne | IfAcmpne | Ifnonnull => Some(Comparison::NotEqual),
+        Iflt | IfIcmplt => Some(Comparison::Less),
+        Ifge | IfIcmpge => Some(Comparison::GreaterOrEqual),
+        Ifgt | IfIcmpgt => Some(Comparison::Greater),
+        Ifle | IfIcmple => Some(Comparison::LessOrEqual),
+        _ => None,
+    }
+}
+
+/// Gives a literal on one side of a comparison the type of the other side, so that `c == 97` is `c == 'a'`.
+fn compare(comparison: Comparison, left: Expr, right: Expr) -> Expr {
+    let (left, right) = match (static_type(&left), static_type(&right)) {
+        (Some(field_type @ (FieldType::Char | FieldType::Boolean)), _) => {
+            let right = right.coerce(&field_type);
+            (left, right)
+        }
+        (_, Some(field_type @ (FieldType::Char | FieldType::Boolean))) => {
+            (left.coerce(&field_type), right)
+        }
+        _ => (left, right),
+    };
+    Expr::Compare(comparison, Box::new(left), Box::new(right))
+}
+
+/// The condition of `ifeq` and the other branches that compare a value with zero.
+fn compare_with_zero(comparison: Comparison, value: Expr) -> Expr {
+    match value {
+        Expr::CompareResult(left, right) => Expr::Compare(comparison, left, right),
+        value if value.is_condition() && comparison == Comparison::NotEqual => value,
+        value if value.is_condition() && comparison == Comparison::Equal => negate(value),
+        value => compare(comparison, value, Expr::int(0)),
+    }
+}
+
+/// The parts of `new StringBuilder().append(a).append(b)`, which `javac` compiled string concatenation to before
+/// Java 9.
+fn builder_parts(expr: &Expr) -> Option<Vec<Expr>> {
+    const STRING_BUILDER: &str = "java/lang/StringBuilder";
+    match expr {
+        Expr::New {
+            class_name,
+            arguments,
+        } if class_name == STRING_BUILDER => match arguments.as_slice() {
+            [] => Some(Vec::new()),
+            [
+                Expr::Invoke {
+                    class_name,
+                    name,
+                    arguments,
+                    ..
+                },
+            ] if class_name == "java/lang/String" && name == "valueOf" && arguments.len() == 1 => {
+                Some(arguments.clone())
+            }
+            [argument] if matches!(argument, Expr::Literal(Literal::String(_))) => {
+                Some(vec![argument.clone()])
+            }
+            _ => None,
+        },
+        Expr::Invoke {
+            receiver: Receiver::Object(builder),
+            class_name,
+            name,
+            arguments,
+            ..
+        } if class_name == STRING_BUILDER && name == "append" && arguments.len() == 1 => {
+            let mut parts = builder_parts(builder)?;
+            parts.push(arguments[0].clone());
+            Some(parts)
+        }
+        _ => None,
+    }
+}
+
+/// The string concatenation of a call site of `StringConcatFactory`, where the recipe marks the arguments with `\1`
+/// and the constants with `\2`.
+fn concatenation(call_site: &CallSite, arguments: Vec<Expr>) -> Expr {
+    let recipe = match (
+        call_site.bootstrap_method.name.as_str(),
+        call_site.arguments.first(),
+    ) {
+        ("makeConcatWithConstants", Some(BootstrapArgument::String(recipe))) => recipe,
+        _ => return Expr::Concat(arguments),
+    };
+    let mut arguments = arguments.into_iter();
+    let mut constants = call_site.arguments.iter().skip(1);
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let flush = |literal: &mut String, parts: &mut Vec<Expr>| {
+        if !literal.is_empty() {
+            parts.push(Expr::Literal(Literal::String(std::mem::take(literal))));
+        }
+    };
+    for c in recipe.chars() {
+        match c {
+            '\u{1}' => {
+                flush(&mut literal, &mut parts);
+                parts.extend(arguments.next());
+            }
+            '\u{2}' => match constants.next() {
+                Some(BootstrapArgument::String(value)) => literal.push_str(value),
+                Some(BootstrapArgument::Integer(value)) => literal.push_str(&value.to_string()),
+                Some(BootstrapArgument::Long(value)) => literal.push_str(&value.to_string()),
+                Some(BootstrapArgument::Float(value)) => literal.push_str(&value.to_string()),
+                Some(BootstrapArgument::Double(value)) => literal.push_str(&value.to_string()),
+                Some(BootstrapArgument::Class(value)) => literal.push_str(value),
+                _ => {}
+            },
+            c => literal.push(c),
+        }
+    }
+    flush(&mut literal, &mut parts);
+    Expr::Concat(parts)
+}
+
+struct Lifter<'a> {
+    method: &'a Method<'a>,
+    graph: &'a ControlFlowGraph,
+    /// Whether each block is the entry of an exception handler.
+    handlers: Vec<bool>,
+    next_id: usize,
+    next_temporary: usize,
+}
+
+impl Lifter<'_> {
+    fn invalid(&self, offset: u32, problem: &str) -> ClassFileError {
+        error!("can't decompile the instruction; offset={offset}, problem={problem}");
+        ClassFileError::InvalidCode
+    }
+
+    fn block_at(&self, offset: u32) -> Result<usize, ClassFileError> {
+        match self.graph.block_of(offset) {
+            Some(block) if self.graph.blocks[block].start == offset => Ok(block),
+            _ => Err(self.invalid(offset, "branch target")),
+        }
+    }
+
+    fn pop(&self, stack: &mut Vec<Value>, offset: u32) -> Result<Value, ClassFileError> {
+        stack
+            .pop()
+            .ok_or_else(|| self.invalid(offset, "operand stack underflow"))
+    }
+
+    /// Pops the arguments of a method, giving literals the types of the parameters.
+    fn pop_arguments(
+        &self,
+        stack: &mut Vec<Value>,
+        descriptor: &MethodDescriptor,
+        offset: u32,
+    ) -> Result<Vec<Expr>, ClassFileError> {
+        let mut arguments = Vec::with_capacity(descriptor.parameters.len());
+        for parameter in descriptor.parameters.iter().rev() {
+            arguments.push(self.pop(stack, offset)?.expr.coerce(parameter));
+        }
+        arguments.reverse();
+        Ok(arguments)
+    }
+
+    fn load(&self, slot: u16, offset: u32) -> Expr {
+        match (self.method.is_static, slot) {
+            (false, 0) => Expr::This,
+            _ => self.method.locals.load(slot, offset),
+        }
+    }
+
+    fn spill(&mut self, statements: &mut Vec<Stmt>, value: &mut Value) {
+        let temporary = Expr::Temporary(self.next_temporary);
+        self.next_temporary += 1;
+        let expr = std::mem::replace(&mut value.expr, temporary.clone());
+        statements.push(Stmt::Expr(Expr::Assign(
+            Box::new(temporary),
+            Box::new(expr),
+        )));
+    }
+
+    /// Adds a statement, first keeping aside the values on the stack that it could change, so that they are still
+    /// evaluated before it.
+    fn emit(&mut self, statements: &mut Vec<Stmt>, stack: &mut [Value], statement: Stmt) {
+        let (local, stable) = match &statement {
+            Stmt::Expr(Expr::Assign(target, value)) => match target.as_ref() {
+                Expr::Local { name, .. } => (Some(name.clone()), value.is_stable()),
+                _ => (None, false),
+            },
+            Stmt::Expr(Expr::PostIncrement(target, _)) => match target.as_ref() {
+                Expr::Local { name, .. } => (Some(name.clone()), true),
+                _ => (None, false),
+            },
+            _ => (None, false),
+        };
+        for value in stack.iter_mut() {
+            let reads_local = local
+                .as_ref()
+                .is_some_and(|local| value.expr.reads_local(local));
+            if reads_local || (!stable && !value.expr.is_stable()) {
+                self.spill(statements, value);
+            }
+        }
+        statements.push(statement);
+    }
+
+    /// Keeps the values on the stack that read a local variable aside, before the variable is assigned.
+    fn spill_readers(&mut self, statements: &mut Vec<Stmt>, stack: &mut [Value], local: &Expr) {
+        let Expr::Local { name, .. } = local else {
+            return;
+        };
+        for value in stack.iter_mut() {
+            if value.expr.reads_local(name) {
+                self.spill(statements, value);
+            }
+        }
+    }
+
+    /// Drops a value that is popped, keeping it as a statement if evaluating it has effects.
+    fn discard(&mut self, statements: &mut Vec<Stmt>, stack: &mut [Value], value: Value) {
+        if !value.expr.is_stable() {
+            self.emit(statements, stack, Stmt::Expr(value.expr));
+        }
+    }
+
+    /// The number of values at the top of the stack, above `skip` values, that make up a number of words.
+    fn values_for_words(&self, stack: &[Value], skip: usize, words: usize) -> Option<usize> {
+        let mut count = 0;
+        let mut total = 0;
+        for value in stack.iter().rev().skip(skip) {
+            if total == words {
+                break;
+            }
+            total += if value.wide { 2 } else { 1 };
+            count += 1;
+        }
+        (total == words).then_some(count)
+    }
+
+    /// `dup` and its variants, which copy `words` words at the top of the stack below the `under` words beneath them.
+    fn duplicate(
+        &mut self,
+        statements: &mut Vec<Stmt>,
+        stack: &mut Vec<Value>,
+        words: usize,
+        under: usize,
+        offset: u32,
+    ) -> Result<(), ClassFileError> {
+        let copied = self
+            .values_for_words(stack, 0, words)
+            .ok_or_else(|| self.invalid(offset, "operand stack underflow"))?;
+        let skipped = self
+            .values_for_words(stack, copied, under)
+            .ok_or_else(|| self.invalid(offset, "operand stack underflow"))?;
+        let first = stack.len() - copied;
+        for value in stack[first..].iter_mut() {
+            match &value.expr {
+                Expr::NewArray {
+                    element_type,
+                    lengths,
+                } if lengths.len() == 1 && words == 1 => {
+                    if let Expr::Literal(Literal::Int(length)) = lengths[0] {
+                        value.expr = Expr::ArrayInitializer {
+                            id: self.next_id,
+                            element_type: element_type.clone(),
+                            length,
+                            elements: Vec::new(),
+                        };
+                        self.next_id += 1;
+                    }
+                }
+                Expr::Uninitialized { .. } | Expr::ArrayInitializer { .. } => {}
+                expr if expr.is_simple() => {}
+                _ => self.spill(statements, value),
+            }
+        }
+        let copies: Vec<Value> = stack[first..].to_vec();
+        let position = first - skipped;
+        stack.splice(position..position, copies);
+        Ok(())
+    }
+
+    /// Folds `dup` followed by a store of the copy into an assignment that is used as a value, as `javac` compiles
+    /// `a = b = c` to. Returns whether the store was folded.
+    fn assign_duplicate(
+        &mut self,
+        statements: &mut Vec<Stmt>,
+        stack: &mut Vec<Value>,
+        instruction: &Instruction,
+        store: &Instruction,
+    ) -> Result<bool, ClassFileError> {
+        use Opcode::*;
+        let constant_pool = &self.method.class_file.constant_pool;
+        let under = match instruction.opcode {
+            Dup | Dup2 => 0,
+            DupX1 | Dup2X1 => 1,
+            DupX2 | Dup2X2 => 2,
+            _ => return Ok(false),
+        };
+        let offset = instruction.offset;
+        if stack
+            .last()
+            .is_none_or(|value| matches!(value.expr, Expr::Uninitialized { .. }))
+        {
+            return Ok(false);
+        }
+        let value = match (under, store.opcode, &store.operand) {
+            (0, opcode, _) if is_store(opcode) => {
+                let Some((slot, _)) = local_slot(store) else {
+                    return Ok(false);
+                };
+                let next_offset = store.offset + store.length() as u32;
+                let local = self.method.locals.store(slot, store.offset, next_offset);
+                let value = self.pop(stack, offset)?;
+                self.spill_readers(statements, stack, &local);
+                let expr = match &local {
+                    Expr::Local {
+                        field_type: Some(field_type),
+                        ..
+                    } => value.expr.coerce(field_type),
+                    _ => value.expr,
+                };
+                Value::new(Expr::Assign(Box::new(local), Box::new(expr)), value.wide)
+            }
+            (0, Putstatic, Operand::ConstantPool(index)) => {
+                let (class_name, name, descriptor) = constant_pool.member_ref(*index as usize)?;
+                let value = self.pop(stack, offset)?;
+                let target = Expr::StaticField {
+                    class_name: class_name.clone(),
+                    name: name.clone(),
+                    descriptor: descriptor.clone(),
+                };
+                let expr = value.expr.coerce(&FieldType::parse(descriptor)?);
+                Value::new(Expr::Assign(Box::new(target), Box::new(expr)), value.wide)
+            }
+            (1, Putfield, Operand::ConstantPool(index)) => {
+                let (_, name, descriptor) = constant_pool.member_ref(*index as usize)?;
+                let value = self.pop(stack, offset)?;
+                let object = self.pop(stack, offset)?;
+                let target = Expr::Field {
+                    object: Box::new(object.expr),
+                    name: name.clone(),
+                    descriptor: descriptor.clone(),
+                };
+                let expr = value.expr.coerce(&FieldType::parse(descriptor)?);
+                Value::new(Expr::Assign(Box::new(target), Box::new(expr)), value.wide)
+            }
+            (2, opcode, _) if is_array_store(opcode) => {
+                let value = self.pop(stack, offset)?;
+                let index = self.pop(stack, offset)?;
+                let array = self.pop(stack, offset)?;
+                let target = Expr::ArrayElement {
+                    array: Box::new(array.expr),
+                    index: Box::new(index.expr),
+                };
+                Value::new(
+                    Expr::Assign(Box::new(target), Box::new(value.expr)),
+                    value.wide,
+                )
+            }
+            _ => return Ok(false),
+        };
+        stack.push(value);
+        Ok(true)
+    }
+
+    fn constant(&self, index: u16, offset: u32) -> Result<Value, ClassFileError> {
+        let class_file = self.method.class_file;
+        let constant_pool = &class_file.constant_pool;
+        let value = match constant_pool.checked_entry(index)? {
+            ConstantPoolEntry::Integer(value) => Value::new(Expr::int(*value), false),
+            ConstantPoolEntry::Float(value) => {
+                Value::new(Expr::Literal(Literal::Float(*value)), false)
+            }
+            ConstantPoolEntry::Long(value) => {
+                Value::new(Expr::Literal(Literal::Long(*value)), true)
+            }
+            ConstantPoolEntry::Double(value) => {
+                Value::new(Expr::Literal(Literal::Double(*value)), true)
+            }
+            ConstantPoolEntry::StringRef(value_idx) => Value::new(
+                Expr::Literal(Literal::String(
+                    constant_pool.string_entry(*value_idx)?.clone(),
+                )),
+                false,
+            ),
+            ConstantPoolEntry::ClassRef(_) => {
+                let class_name = constant_pool.class_name(usize::from(index))?;
+                Value::new(
+                    Expr::Literal(Literal::Class(class_type(class_name)?)),
+                    false,
+                )
+            }
+            ConstantPoolEntry::MethodType(descriptor_idx) => Value::new(
+                Expr::Raw(format!(
+                    "MethodType({:?})",
+                    constant_pool.string_entry(*descriptor_idx)?
+                )),
+                false,
+            ),
+            ConstantPoolEntry::MethodHandle(_) => {
+                let handle =
+                    crate::bootstrap::method_handle_info(constant_pool, usize::from(index))?;
+                Value::new(
+                    Expr::Raw(format!(
+                        "MethodHandle({:?}, {}.{}{})",
+                        handle.reference_kind, handle.owner, handle.name, handle.descriptor
+                    )),
+                    false,
+                )
+            }
+            ConstantPoolEntry::Dynamic(_) => {
+                let call_site = class_file.call_site(index)?;
+                let wide = is_wide(&FieldType::parse(&call_site.descriptor)?);
+                Value::new(
+                    Expr::Dynamic {
+                        bootstrap: format!(
+                            "{}.{}",
+                            call_site.bootstrap_method.owner, call_site.bootstrap_method.name
+                        ),
+                        name: call_site.name,
+                        arguments: Vec::new(),
+                    },
+                    wide,
+                )
+            }
+            _ => return Err(self.invalid(offset, "constant")),
+        };
+        Ok(value)
+    }
+
+    fn invoke(
+        &mut self,
+        opcode: Opcode,
+        index: u16,
+        statements: &mut Vec<Stmt>,
+        stack: &mut Vec<Value>,
+        offset: u32,
+    ) -> Result<(), ClassFileError> {
+        let constant_pool = &self.method.class_file.constant_pool;
+        let (class_name, name, descriptor) = constant_pool.member_ref(usize::from(index))?;
+        let parsed = MethodDescriptor::parse(descriptor)?;
+        let arguments = self.pop_arguments(stack, &parsed, offset)?;
+        let receiver = match opcode {
+            Opcode::Invokestatic => None,
+            _ => Some(self.pop(stack, offset)?.expr),
+        };
+
+        if opcode == Opcode::Invokespecial && name == "<init>" {
+            match receiver {
+                Some(Expr::Uninitialized { id, class_name }) => {
+                    let created = Expr::New {
+                        class_name,
+                        arguments,
+                    };
+                    let mut used = false;
+                    for value in stack.iter_mut() {
+                        if matches!(value.expr, Expr::Uninitialized { id: other, .. } if other == id)
+                        {
+                            value.expr = created.clone();
+                            used = true;
+                        }
+                    }
+                    if !used {
+                        self.emit(statements, stack, Stmt::Expr(created));
+                    }
+                }
+                Some(Expr::This) => {
+                    let call = Expr::ConstructorCall {
+                        this: class_name == self.method.class_name,
+                        arguments,
+                    };
+                    self.emit(statements, stack, Stmt::Expr(call));
+                }
+                receiver => {
+                    let call = Expr::Invoke {
+                        receiver: Receiver::Object(Box::new(receiver.unwrap_or(Expr::This))),
+                        class_name: class_name.clone(),
+                        name: name.clone(),
+                        descriptor: descriptor.clone(),
+                        arguments,
+                    };
+                    self.emit(statements, stack, Stmt::Expr(call));
+                }
+            }
+            return Ok(());
+        }
+
+        let receiver = match receiver {
+            None => Receiver::Static,
+            Some(Expr::This)
+                if opcode == Opcode::Invokespecial && class_name != self.method.class_name =>
+            {
+                Receiver::Super
+            }
+            Some(object) => Receiver::Object(Box::new(object)),
+        };
+        let call = Expr::Invoke {
+            receiver,
+            class_name: class_name.clone(),
+            name: name.clone(),
+            descriptor: descriptor.clone(),
+            arguments,
+        };
+        let call = match (class_name.as_str(), name.as_str(), &call) {
+            (
+                "java/lang/StringBuilder",
+                "toString",
+                Expr::Invoke {
+                    receiver: Receiver::Object(builder),
+                    ..
+                },
+            ) => match builder_parts(builder) {
+                Some(parts) if !parts.is_empty() => Expr::Concat(parts),
+                _ => call,
+            },
+            _ => call,
+        };
+        match parsed.return_type {
+            Some(return_type) => stack.push(Value::new(call, is_wide(&return_type))),
+            None => self.emit(statements, stack, Stmt::Expr(call)),
+        }
+        Ok(())
+    }
+
+    /// A lambda expression for a lambda implemented by a method of the class, or a method reference.
+    fn lambda(&self, call_site: &CallSite, captured: Vec<Expr>) -> Expr {
+        let Some(BootstrapArgument::MethodHandle(handle)) = call_site.arguments.get(1) else {
+            return Expr::Dynamic {
+                bootstrap: "LambdaMetafactory".to_string(),
+                name: call_site.name.clone(),
+                arguments: captured,
+            };
+        };
+        let class_file = self.method.class_file;
+        if handle.owner == self.method.class_name
+            && handle.name.starts_with("lambda$")
+            && self.method.depth < MAX_LAMBDA_DEPTH
+        {
+            let constant_pool = &class_file.constant_pool;
+            let implementation = class_file.methods.iter().find(|method| {
+                method
+                    .name(constant_pool)
+                    .is_ok_and(|name| *name == handle.name)
+                    && method
+                        .descriptor(constant_pool)
+                        .is_ok_and(|descriptor| *descriptor == handle.descriptor)
+            });
+            if let Some(implementation) = implementation {
+                let receiver = match implementation
+                    .access_flags
+                    .contains(MethodAccessFlags::ACC_STATIC)
+                {
+                    true => 0,
+                    false => 1,
+                };
+                let names: Vec<Option<String>> = captured
+                    .iter()
+                    .skip(receiver)
+                    .map(|expr| match expr {
+                        Expr::Local { name, .. } => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                if let Ok(Body {
+                    parameters,
+                    statements,
+                }) =
+                    super::method_body(class_file, implementation, &names, self.method.depth + 1)
+                {
+                    return Expr::Lambda {
+                        parameters: parameters
+                            .into_iter()
+                            .skip(names.len())
+                            .map(|(name, _)| name)
+                            .collect(),
+                        body: statements,
+                    };
+                }
+            }
+        }
+
+        let qualifier = match (handle.reference_kind, captured.into_iter().next()) {
+            (ReferenceKind::NewInvokeSpecial | ReferenceKind::InvokeStatic, _) | (_, None) => {
+                Qualifier::Type(handle.owner.clone())
+            }
+            (_, Some(object)) => Qualifier::Object(Box::new(object)),
+        };
+        let name = match handle.reference_kind {
+            ReferenceKind::NewInvokeSpecial => "new".to_string(),
+            _ => handle.name.clone(),
+        };
+        Expr::MethodReference { qualifier, name }
+    }
+
+    fn invoke_dynamic(
+        &mut self,
+        index: u16,
+        statements: &mut Vec<Stmt>,
+        stack: &mut Vec<Value>,
+        offset: u32,
+    ) -> Result<(), ClassFileError> {
+        let call_site = self.method.class_file.call_site(index)?;
+        let descriptor = MethodDescriptor::parse(&call_site.descriptor)?;
+        let arguments = self.pop_arguments(stack, &descriptor, offset)?;
+        let expr = match call_site.kind() {
+            BootstrapKind::StringConcatFactory => concatenation(&call_site, arguments),
+            BootstrapKind::LambdaMetafactory => self.lambda(&call_site, arguments),
+            BootstrapKind::ObjectMethods | BootstrapKind::Other => Expr::Dynamic {
+                bootstrap: format!(
+                    "{}.{}",
+                    call_site.bootstrap_method.owner, call_site.bootstrap_method.name
+                ),
+                name: call_site.name.clone(),
+                arguments,
+            },
+        };
+        match descriptor.return_type {
+            Some(return_type) => stack.push(Value::new(expr, is_wide(&return_type))),
+            None => self.emit(statements, stack, Stmt::Expr(expr)),
+        }
+        Ok(())
+    }
+
+    /// Adds an element to an array that is being initialised, or stores it in the array.
+    fn store_element(
+        &mut self,
+        statements: &mut Vec<Stmt>,
+        stack: &mut [Value],
+        array: Expr,
+        index: Expr,
+        value: Expr,
+    ) {
+        if let Expr::ArrayInitializer { id, elements, .. } = &array
+            && index == Expr::int(elements.len() as i32)
+        {
+            let id = *id;
+            for entry in stack.iter_mut() {
+                if let Expr::ArrayInitializer {
+                    id: other,
+                    element_type,
+                    elements,
+                    ..
+                } = &mut entry.expr
+                    && *other == id
+                {
+                    elements.push(value.clone().coerce(element_type));
+                }
+            }
+            return;
+        }
+        let value = match static_type(&array) {
+            Some(FieldType::Array(element_type)) => value.coerce(&element_type),
+            _ => value,
+        };
+        let target = Expr::ArrayElement {
+            array: Box::new(array),
+            index: Box::new(index),
+        };
+        self.emit(
+            statements,
+            stack,
+            Stmt::Expr(Expr::Assign(Box::new(target), Box::new(value))),
+        );
+    }
+
+    /// Simulates one instruction. Returns the terminator if the instruction leaves the block, and whether it used up
+    /// the instruction after it.
+    fn step(
+        &mut self,
+        instruction: &Instruction,
+        following: &[Instruction],
+        statements: &mut Vec<Stmt>,
+        stack: &mut Vec<Value>,
+    ) -> Result<(Option<Terminator>, usize), ClassFileError> {
+        use Opcode::*;
+        let offset = instruction.offset;
+        let next_offset = offset + instruction.length() as u32;
+        let constant_pool = &self.method.class_file.constant_pool;
+        let literal = |literal: Literal| Value::new(Expr::Literal(literal), false);
+        let pool_index = || match instruction.operand {
+            Operand::ConstantPool(index)
+            | Operand::InvokeInterface { index, .. }
+            | Operand::MultiANewArray { index, .. } => Ok(index),
+            _ => Err(self.invalid(offset, "operand")),
+        };
+
+        match instruction.opcode {
+            Nop => {}
+            AconstNull => stack.push(literal(Literal::Null)),
+            IconstM1 | Iconst0 | Iconst1 | Iconst2 | Iconst3 | Iconst4 | Iconst5 => {
+                let value = instruction.opcode as i32 - Iconst0 as i32;
+                stack.push(Value::new(Expr::int(value), false));
+            }
+            Lconst0 | Lconst1 => {
+                let value = (instruction.opcode as u8 - Lconst0 as u8) as i64;
+                stack.push(Value::new(Expr::Literal(Literal::Long(value)), true));
+            }
+            Fconst0 | Fconst1 | Fconst2 => {
+                let value = (instruction.opcode as u8 - Fconst0 as u8) as f32;
+                stack.push(literal(Literal::Float(value)));
+            }
+            Dconst0 | Dconst1 => {
+                let value = (instruction.opcode as u8 - Dconst0 as u8) as f64;
+                stack.push(Value::new(Expr::Literal(Literal::Double(value)), true));
+            }
+            Bipush | Sipush => {
+                let value = match instruction.operand {
+                    Operand::Byte(value) => i32::from(value),
+                    Operand::Short(value) => i32::from(value),
+                    _ => return Err(self.invalid(offset, "operand")),
+                };
+                stack.push(Value::new(Expr::int(value), false));
+            }
+            Ldc | LdcW | Ldc2W => stack.push(self.constant(pool_index()?, offset)?),
+            opcode
+                if is_load(opcode)
+                    && !is_array_store(opcode)
+                    && local_slot(instruction).is_some() =>
+            {
+                let (slot, wide) = local_slot(instruction).expect("loads have a slot");
+                stack.push(Value::new(self.load(slot, offset), wide));
+            }
+            Iaload | Laload | Faload | Daload | Aaload | Baload | Caload | Saload => {
+                let index = self.pop(stack, offset)?.expr;
+                let array = self.pop(stack, offset)?.expr;
+                let wide = matches!(instruction.opcode, Laload | Daload);
+                let element = Expr::ArrayElement {
+                    array: Box::new(array),
+                    index: Box::new(index),
+                };
+                stack.push(Value::new(element, wide));
+            }
+            opcode if is_store(opcode) => {
+                let (slot, _) =
+                    local_slot(instruction).ok_or_else(|| self.invalid(offset, "operand"))?;
+                let value = self.pop(stack, offset)?.expr;
+                let local = self.method.locals.store(slot, offset, next_offset);
+                let value = match &local {
+                    Expr::Local {
+                        field_type: Some(field_type),
+                        ..
+                    } => value.coerce(field_type),
+                    _ => value,
+                };
+                self.spill_readers(statements, stack, &local);
+                let assignment = Expr::Assign(Box::new(local), Box::new(value));
+                self.emit(statements, stack, Stmt::Expr(assignment));
+            }
+            opcode if is_array_store(opcode) => {
+                let value = self.pop(stack, offset)?.expr;
+                let index = self.pop(stack, offset)?.expr;
+                let array = self.pop(stack, offset)?.expr;
+                let value = match opcode {
+                    Castore => value.coerce(&FieldType::Char),
+                    _ => value,
+                };
+                self.store_element(statements, stack, array, index, value);
+            }
+            Pop => {
+                let value = self.pop(stack, offset)?;
+                self.discard(statements, stack, value);
+            }
+            Pop2 => {
+                let value = self.pop(stack, offset)?;
+                let wide = value.wide;
+                self.discard(statements, stack, value);
+                if !wide {
+                    let value = self.pop(stack, offset)?;
+                    self.discard(statements, stack, value);
+                }
+            }
+            Dup | DupX1 | DupX2 | Dup2 | Dup2X1 | Dup2X2 => {
+                // `Objects.requireNonNull` and `getClass` check for null where a method reference is bound to an
+                // object, and are left out with their copy.
+                if let [
+                    Instruction {
+                        opcode: Invokestatic | Invokevirtual,
+                        operand: Operand::ConstantPool(index),
+                        ..
+                    },
+                    Instruction { opcode: Pop, .. },
+                    ..,
+                ] = following
+                    && instruction.opcode == Dup
+                    && let Ok((class_name, name, _)) = constant_pool.member_ref(usize::from(*index))
+                    && matches!(
+                        (class_name.as_str(), name.as_str()),
+                        ("java/util/Objects", "requireNonNull") | ("java/lang/Object", "getClass")
+                    )
+                {
+                    return Ok((None, 2));
+                }
+                if let Some(store) = following.first()
+                    && self.assign_duplicate(statements, stack, instruction, store)?
+                {
+                    return Ok((None, 1));
+                }
+                let (words, under) = match instruction.opcode {
+                    Dup => (1, 0),
+                    DupX1 => (1, 1),
+                    DupX2 => (1, 2),
+                    Dup2 => (2, 0),
+                    Dup2X1 => (2, 1),
+                    _ => (2, 2),
+                };
+                self.duplicate(statements, stack, words, under, offset)?;
+            }
+            Swap => {
+                let first = self.pop(stack, offset)?;
+                let second = self.pop(stack, offset)?;
+                stack.push(first);
+                stack.push(second);
+            }
+            opcode if binary_operator(opcode).is_some() => {
+                let (operator, wide) = binary_operator(opcode).expect("checked above");
+                let right = self.pop(stack, offset)?.expr;
+                let left = self.pop(stack, offset)?.expr;
+                let expr = Expr::Binary(operator, Box::new(left), Box::new(right));
+                stack.push(Value::new(expr, wide));
+            }
+            Ineg | Lneg | Fneg | Dneg => {
+                let value = self.pop(stack, offset)?;
+                stack.push(Value::new(Expr::Negate(Box::new(value.expr)), value.wide));
+            }
+            Iinc => {
+                let Operand::Iinc { index, value } = instruction.operand else {
+                    return Err(self.invalid(offset, "operand"));
+                };
+                let local = self.load(index, offset);
+                let position = stack.iter().rposition(|entry| entry.expr == local);
+                match position {
+                    Some(position) if value == 1 || value == -1 => {
+                        stack[position].expr = Expr::PostIncrement(Box::new(local.clone()), value);
+                        let (before, after) = stack.split_at_mut(position);
+                        self.spill_readers(statements, before, &local);
+                        self.spill_readers(statements, &mut after[1..], &local);
+                    }
+                    _ => {
+                        let increment = Expr::PostIncrement(Box::new(local), value);
+                        self.emit(statements, stack, Stmt::Expr(increment));
+                    }
+                }
+            }
+            opcode if conversion(opcode).is_some() => {
+                let field_type = conversion(opcode).expect("checked above");
+                let value = self.pop(stack, offset)?.expr;
+                let wide = is_wide(&field_type);
+                stack.push(Value::new(Expr::Cast(field_type, Box::new(value)), wide));
+            }
+            Lcmp | Fcmpl | Fcmpg | Dcmpl | Dcmpg => {
+                let right = self.pop(stack, offset)?.expr;
+                let left = self.pop(stack, offset)?.expr;
+                stack.push(Value::new(
+                    Expr::CompareResult(Box::new(left), Box::new(right)),
+                    false,
+                ));
+            }
+            Ifeq | Ifne | Iflt | Ifge | Ifgt | Ifle | Ifnull | Ifnonnull | IfIcmpeq | IfIcmpne
+            | IfIcmplt | IfIcmpge | IfIcmpgt | IfIcmple | IfAcmpeq | IfAcmpne => {
+                let Operand::Branch(target) = instruction.operand else {
+                    return Err(self.invalid(offset, "operand"));
+                };
+                let operator = comparison(instruction.opcode).expect("branches compare");
+                let condition = match instruction.opcode {
+                    Ifnull | Ifnonnull => compare(
+                        operator,
+                        self.pop(stack, offset)?.expr,
+                        Expr::Literal(Literal::Null),
+                    ),
+                    Ifeq | Ifne | Iflt | Ifge | Ifgt | Ifle => {
+                        compare_with_zero(operator, self.pop(stack, offset)?.expr)
+                    }
+                    _ => {
+                        let right = self.pop(stack, offset)?.expr;
+                        let left = self.pop(stack, offset)?.expr;
+                        compare(operator, left, right)
+                    }
+                };
+                let block = self
+                    .graph
+                    .block_of(offset)
+                    .ok_or_else(|| self.invalid(offset, "block"))?;
+                let terminator = Terminator::Branch {
+                    condition,
+                    target: self.block_at(target)?,
+                    next: self.next_block(block, offset)?,
+                };
+                return Ok((Some(terminator), 0));
+            }
+            Goto | GotoW => {
+                let Operand::Branch(target) = instruction.operand else {
+                    return Err(self.invalid(offset, "operand"));
+                };
+                return Ok((Some(Terminator::Next(self.block_at(target)?)), 0));
+            }
+            Tableswitch | Lookupswitch => {
+                let value = self.pop(stack, offset)?.expr;
+                let (default, keys): (u32, Vec<(i32, u32)>) = match &instruction.operand {
+                    Operand::TableSwitch {
+                        default,
+                        low,
+                        targets,
+                        ..
+                    } => (*default, (*low..).zip(targets.iter().copied()).collect()),
+                    Operand::LookupSwitch { default, pairs } => (*default, pairs.clone()),
+                    _ => return Err(self.invalid(offset, "operand")),
+                };
+                let mut cases = Vec::with_capacity(keys.len());
+                for (key, target) in keys {
+                    cases.push((key, self.block_at(target)?));
+                }
+                let terminator = Terminator::Switch {
+                    value,
+                    cases,
+                    default: self.block_at(default)?,
+                };
+                return Ok((Some(terminator), 0));
+            }
+            Ireturn | Lreturn | Freturn | Dreturn | Areturn => {
+                let value = self.pop(stack, offset)?.expr;
+                let value = match &self.method.descriptor.return_type {
+                    Some(return_type) => value.coerce(return_type),
+                    None => value,
+                };
+                return Ok((Some(Terminator::Return(Some(value))), 0));
+            }
+            Return => return Ok((Some(Terminator::Return(None)), 0)),
+            Athrow => {
+                let value = self.pop(stack, offset)?.expr;
+                return Ok((Some(Terminator::Throw(value)), 0));
+            }
+            Getstatic | Getfield => {
+                let (class_name, name, descriptor) =
+                    constant_pool.member_ref(usize::from(pool_index()?))?;
+                let wide = is_wide(&FieldType::parse(descriptor)?);
+                let expr = match instruction.opcode {
+                    Getstatic => Expr::StaticField {
+                        class_name: class_name.clone(),
+                        name: name.clone(),
+                        descriptor: descriptor.clone(),
+                    },
+                    _ => Expr::Field {
+                        object: Box::new(self.pop(stack, offset)?.expr),
+                        name: name.clone(),
+                        descriptor: descriptor.clone(),
+                    },
+                };
+                stack.push(Value::new(expr, wide));
+            }
+            Putstatic | Putfield => {
+                let (class_name, name, descriptor) =
+                    constant_pool.member_ref(usize::from(pool_index()?))?;
+                let value = self
+                    .pop(stack, offset)?
+                    .expr
+                    .coerce(&FieldType::parse(descriptor)?);
+                let target = match instruction.opcode {
+                    Putstatic => Expr::StaticField {
+                        class_name: class_name.clone(),
+                        name: name.clone(),
+                        descriptor: descriptor.clone(),
+                    },
+                    _ => Expr::Field {
+                        object: Box::new(self.pop(stack, offset)?.expr),
+                        name: name.clone(),
+                        descriptor: descriptor.clone(),
+                    },
+                };
+                let assignment = Expr::Assign(Box::new(target), Box::new(value));
+                self.emit(statements, stack, Stmt::Expr(assignment));
+            }
+            Invokevirtual | Invokespecial | Invokestatic | Invokeinterface => {
+                self.invoke(instruction.opcode, pool_index()?, statements, stack, offset)?;
+            }
+            Invokedynamic => self.invoke_dynamic(pool_index()?, statements, stack, offset)?,
+            New => {
+                let class_name = constant_pool
+                    .class_name(usize::from(pool_index()?))?
+                    .clone();
+                stack.push(Value::new(
+                    Expr::Uninitialized {
+                        id: self.next_id,
+                        class_name,
+                    },
+                    false,
+                ));
+                self.next_id += 1;
+            }
+            Newarray => {
+                let Operand::ArrayType(array_type) = instruction.operand else {
+                    return Err(self.invalid(offset, "operand"));
+                };
+                let element_type = array_element_type(array_type)
+                    .ok_or_else(|| self.invalid(offset, "array type"))?;
+                let length = self.pop(stack, offset)?.expr;
+                stack.push(Value::new(
+                    Expr::NewArray {
+                        element_type,
+                        lengths: vec![length],
+                    },
+                    false,
+                ));
+            }
+            Anewarray => {
+                let class_name = constant_pool.class_name(usize::from(pool_index()?))?;
+                let length = self.pop(stack, offset)?.expr;
+                stack.push(Value::new(
+                    Expr::NewArray {
+                        element_type: class_type(class_name)?,
+                        lengths: vec![length],
+                    },
+                    false,
+                ));
+            }
+            Multianewarray => {
+                let Operand::MultiANewArray { index, dimensions } = instruction.operand else {
+                    return Err(self.invalid(offset, "operand"));
+                };
+                let mut element_type = class_type(constant_pool.class_name(usize::from(index))?)?;
+                let mut lengths = Vec::with_capacity(usize::from(dimensions));
+                for _ in 0..dimensions {
+                    lengths.push(self.pop(stack, offset)?.expr);
+                    element_type = match element_type {
+                        FieldType::Array(component) => *component,
+                        _ => return Err(self.invalid(offset, "array dimensions")),
+                    };
+                }
+                lengths.reverse();
+                stack.push(Value::new(
+                    Expr::NewArray {
+                        element_type,
+                        lengths,
+                    },
+                    false,
+                ));
+            }
+            Arraylength => {
+                let array = self.pop(stack, offset)?.expr;
+                stack.push(Value::new(Expr::ArrayLength(Box::new(array)), false));
+            }
+            Checkcast | Instanceof => {
+                let field_type = class_type(constant_pool.class_name(usize::from(pool_index()?))?)?;
+                let value = self.pop(stack, offset)?.expr;
+                let expr = match instruction.opcode {
+                    Checkcast => Expr::Cast(field_type, Box::new(value)),
+                    _ => Expr::InstanceOf(Box::new(value), field_type),
+                };
+                stack.push(Value::new(expr, false));
+            }
+            Monitorenter | Monitorexit => {
+                let value = self.pop(stack, offset)?.expr;
+                let statement = match instruction.opcode {
+                    Monitorenter => Stmt::MonitorEnter(value),
+                    _ => Stmt::MonitorExit(value),
+                };
+                self.emit(statements, stack, statement);
+            }
+            _ => return Err(self.invalid(offset, instruction.opcode.mnemonic())),
+        }
+        Ok((None, 0))
+    }
+
+    fn next_block(&self, block: usize, offset: u32) -> Result<usize, ClassFileError> {
+        match block + 1 < self.graph.blocks.len() {
+            true => Ok(block + 1),
+            false => Err(self.invalid(offset, "falls off the end of the code")),
+        }
+    }
+
+    /// Simulates the instructions of a block on a stack.
+    fn simulate(
+        &mut self,
+        block: usize,
+        mut stack: Vec<Value>,
+    ) -> Result<(Vec<Stmt>, Vec<Value>, Terminator), ClassFileError> {
+        let instructions = self.graph.block_instructions(block);
+        let mut statements = Vec::new();
+        let mut position = 0;
+        while position < instructions.len() {
+            let (terminator, used) = self.step(
+                &instructions[position],
+                &instructions[position + 1..],
+                &mut statements,
+                &mut stack,
+            )?;
+            if let Some(terminator) = terminator {
+                return Ok((statements, stack, terminator));
+            }
+            position += 1 + used;
+        }
+        let offset = self.graph.blocks[block].start;
+        let next = self.next_block(block, offset)?;
+        Ok((statements, stack, Terminator::Next(next)))
+    }
+
+    /// The value that a block, and the blocks it branches to, leave on top of a stack without any statements, with the
+    /// block that they all go on to.
+    fn value(
+        &mut self,
+        block: usize,
+        stack: &[Value],
+        region: &mut Vec<usize>,
+        depth: usize,
+    ) -> Option<(Value, usize)> {
+        if depth > MAX_VALUE_DEPTH || block == 0 || self.handlers[block] {
+            return None;
+        }
+        let (statements, after, terminator) = self.simulate(block, stack.to_vec()).ok()?;
+        if !statements.is_empty() {
+            return None;
+        }
+        if !region.contains(&block) {
+            region.push(block);
+        }
+        match terminator {
+            Terminator::Next(next)
+                if after.len() == stack.len() + 1 && after[..stack.len()] == *stack =>
+            {
+                Some((after.last().cloned()?, next))
+            }
+            Terminator::Branch {
+                condition,
+                target,
+                next,
+            } if after == stack => {
+                let (then, join) = self.value(target, stack, region, depth + 1)?;
+                let (otherwise, other_join) = self.value(next, stack, region, depth + 1)?;
+                if join != other_join || then.wide != otherwise.wide {
+                    return None;
+                }
+                let wide = then.wide;
+                let value = conditional(negate(condition), otherwise.expr, then.expr);
+                Some((Value::new(value, wide), join))
+            }
+            _ => None,
+        }
+    }
+
+    /// The conditional value that the blocks a branch goes to leave on the stack, with the block where they meet, if
+    /// only the node enters them. The blocks are marked as absorbed into the node.
+    fn fold_value(
+        &mut self,
+        condition: &Expr,
+        target: usize,
+        next: usize,
+        stack: &[Value],
+        blocks: &[usize],
+        absorbed: &mut [bool],
+    ) -> Option<(Value, usize)> {
+        let mut region = Vec::new();
+        let (then, join) = self.value(target, stack, &mut region, 0)?;
+        let (otherwise, other_join) = self.value(next, stack, &mut region, 0)?;
+        let inside = |candidate: &usize| blocks.contains(candidate) || region.contains(candidate);
+        if join != other_join
+            || then.wide != otherwise.wide
+            || self.handlers[join]
+            || absorbed[join]
+            || region
+                .iter()
+                .chain([&join])
+                .any(|member| !self.graph.predecessors(*member).iter().all(inside))
+        {
+            return None;
+        }
+        for member in region.iter().chain([&join]) {
+            absorbed[*member] = true;
+        }
+        // The block that the branch falls through to comes first, as in the source code.
+        let value = conditional(negate(condition.clone()), otherwise.expr, then.expr);
+        Some((Value::new(value, then.wide), join))
+    }
+
+    /// Whether a block only follows another one, within no more exception handlers than it.
+    fn is_continuation(&self, block: usize, next: usize, absorbed: &[bool]) -> bool {
+        let handlers = |source: usize| -> Vec<usize> {
+            self.graph
+                .edges
+                .iter()
+                .filter(|edge| edge.source == source && matches!(edge.kind, EdgeKind::Exception(_)))
+                .map(|edge| edge.target)
+                .collect()
+        };
+        let outer = handlers(block);
+        !absorbed[next]
+            && !self.handlers[next]
+            && self.graph.predecessors(next) == [block]
+            && handlers(next).iter().all(|handler| outer.contains(handler))
+    }
+
+    /// Lifts a block, and the blocks after it whose only purpose is to compute a value for it, to a node.
+    fn lift_node(
+        &mut self,
+        block: usize,
+        stack: Vec<Value>,
+        absorbed: &mut [bool],
+    ) -> Result<(Node, Vec<Value>), ClassFileError> {
+        let (mut statements, mut stack, mut terminator) = self.simulate(block, stack)?;
+        let mut blocks = vec![block];
+        loop {
+            if let Terminator::Branch {
+                condition,
+                target,
+                next,
+            } = &terminator
+                && let Some((value, join)) =
+                    self.fold_value(condition, *target, *next, &stack, &blocks, absorbed)
+            {
+                stack.push(value);
+                blocks.push(join);
+                let (more, after, next_terminator) = self.simulate(join, stack)?;
+                statements.extend(more);
+                stack = after;
+                terminator = next_terminator;
+                continue;
+            }
+            // A value that is left on the stack for the next block, such as for a return after the range of an
+            // exception handler, is used there if nothing else goes to it.
+            if let Terminator::Next(next) = terminator
+                && !stack.is_empty()
+                && self.is_continuation(blocks[blocks.len() - 1], next, absorbed)
+            {
+                absorbed[next] = true;
+                blocks.push(next);
+                let (more, after, next_terminator) = self.simulate(next, stack)?;
+                statements.extend(more);
+                stack = after;
+                terminator = next_terminator;
+                continue;
+            }
+            break;
+        }
+
+        // Values that are left on the stack are passed on in stack variables.
+        for (depth, value) in stack.iter().enumerate() {
+            if value.expr != Expr::StackSlot(depth) {
+                statements.push(Stmt::Expr(Expr::Assign(
+                    Box::new(Expr::StackSlot(depth)),
+                    Box::new(value.expr.clone()),
+                )));
+            }
+        }
+        let exit = match terminator {
+            Terminator::Next(next) => Exit::Goto(next),
+            Terminator::Branch {
+                condition,
+                target,
+                next,
+            } => Exit::Branch {
+                condition,
+                target,
+                next,
+            },
+            Terminator::Switch {
+                value,
+                cases,
+                default,
+            } => Exit::Switch {
+                value,
+                cases,
+                default,
+            },
+            Terminator::Return(value) => Exit::Return(value),
+            Terminator::Throw(value) => Exit::Throw(value),
+        };
+        let node = Node {
+            start: self.graph.blocks[block].start,
+            blocks,
+            statements,
+            exit,
+        };
+        Ok((node, stack))
+    }
+}
+
+/// Lifts the blocks of a method that can be reached to nodes, by the position of their first block. The positions of
+/// the blocks that were folded into others and of those that can't be reached have no node.
+pub(super) fn lift(
+    method: &Method,
+    graph: &ControlFlowGraph,
+) -> Result<Vec<Option<Node>>, ClassFileError> {
+    let count = graph.blocks.len();
+    let mut handlers = vec![false; count];
+    for edge in &graph.edges {
+        if let EdgeKind::Exception(_) = edge.kind {
+            handlers[edge.target] = true;
+        }
+    }
+    let mut lifter = Lifter {
+        method,
+        graph,
+        handlers,
+        next_id: 0,
+        next_temporary: 0,
+    };
+
+    let successors: Vec<Vec<usize>> = (0..count).map(|block| graph.successors(block)).collect();
+    let mut nodes: Vec<Option<Node>> = (0..count).map(|_| None).collect();
+    let mut absorbed = vec![false; count];
+    // Whether the stack variables that each block starts with hold `long` or `double` values.
+    let mut entry_stacks: Vec<Option<Vec<bool>>> = vec![None; count];
+    for block in reverse_postorder(&successors) {
+        if absorbed[block] {
+            continue;
+        }
+        let stack = match lifter.handlers[block] {
+            true => vec![Value::new(Expr::CaughtException, false)],
+            false => entry_stacks[block]
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(depth, wide)| Value::new(Expr::StackSlot(depth), *wide))
+                .collect(),
+        };
+        let (node, stack) = lifter.lift_node(block, stack, &mut absorbed)?;
+        if !stack.is_empty() {
+            let wides: Vec<bool> = stack.iter().map(|value| value.wide).collect();
+            let last = node.blocks.last().copied().unwrap_or(block);
+            for successor in &successors[last] {
+                entry_stacks[*successor] = Some(wides.clone());
+            }
+        }
+        nodes[block] = Some(node);
+    }
+    Ok(nodes)
+}
@@ -0,0 +1,1000 @@
+//! Printing the syntax tree as Java-like source code: expressions get only the parentheses that their precedence
+//! needs, loops and switches get labels only where a jump leaves more than the innermost of them, and local variables
+//! are declared in the innermost block that holds all their uses, at their first assignment where it is in that block.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::ast::{
+    BinaryOperator, Catch, Comparison, Expr, Literal, Logical, LoopKind, Qualifier, Receiver, Stmt,
+    static_type,
+};
+use crate::descriptor::FieldType;
+
+const ASSIGNMENT: u8 = 1;
+const CONDITIONAL: u8 = 2;
+const OR: u8 = 3;
+const AND: u8 = 4;
+const BIT_OR: u8 = 5;
+const XOR: u8 = 6;
+const BIT_AND: u8 = 7;
+const EQUALITY: u8 = 8;
+const RELATIONAL: u8 = 9;
+const SHIFT: u8 = 10;
+const ADDITIVE: u8 = 11;
+const MULTIPLICATIVE: u8 = 12;
+const UNARY: u8 = 13;
+const POSTFIX: u8 = 14;
+const PRIMARY: u8 = 15;
+
+fn binary_operator(operator: BinaryOperator) -> (&'static str, u8) {
+    match operator {
+        BinaryOperator::Add => ("+", ADDITIVE),
+        BinaryOperator::Subtract => ("-", ADDITIVE),
+        BinaryOperator::Multiply => ("*", MULTIPLICATIVE),
+        BinaryOperator::Divide => ("/", MULTIPLICATIVE),
+        BinaryOperator::Remainder => ("%", MULTIPLICATIVE),
+        BinaryOperator::ShiftLeft => ("<<", SHIFT),
+        BinaryOperator::ShiftRight => (">>", SHIFT),
+        BinaryOperator::UnsignedShiftRight => (">>>", SHIFT),
+        BinaryOperator::And => ("&", BIT_AND),
+        BinaryOperator::Or => ("|", BIT_OR),
+        BinaryOperator::Xor => ("^", XOR),
+    }
+}
+
+fn comparison_operator(comparison: Comparison) -> (&'static str, u8) {
+    match comparison {
+        Comparison::Equal => ("==", EQUALITY),
+        Comparison::NotEqual => ("!=", EQUALITY),
+        Comparison::Less => ("<", RELATIONAL),
+        Comparison::GreaterOrEqual => (">=", RELATIONAL),
+        Comparison::Greater => (">", RELATIONAL),
+        Comparison::LessOrEqual => ("<=", RELATIONAL),
+    }
+}
+
+fn escape(character: u16, quote: char) -> String {
+    match char::from_u32(u32::from(character)) {
+        Some('\n') => "\\n".to_string(),
+        Some('\t') => "\\t".to_string(),
+        Some('\r') => "\\r".to_string(),
+        Some('\u{8}') => "\\b".to_string(),
+        Some('\u{c}') => "\\f".to_string(),
+        Some('\\') => "\\\\".to_string(),
+        Some(character) if character == quote => format!("\\{character}"),
+        Some(character @ ' '..='~') => character.to_string(),
+        _ => format!("\\u{character:04x}"),
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    let escaped: String = value
+        .encode_utf16()
+        .map(|character| escape(character, '"'))
+        .collect();
+    format!("\"{escaped}\"")
+}
+
+/// The name that a local variable, kept value or stack variable is printed with.
+fn variable_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Local { name, .. } => Some(name.clone()),
+        Expr::Temporary(index) => Some(format!("tmp{index}")),
+        Expr::StackSlot(depth) => Some(format!("stack{depth}")),
+        _ => None,
+    }
+}
+
+fn is_string(expr: &Expr) -> bool {
+    static_type(expr) == Some(FieldType::Object("java/lang/String".to_string()))
+}
+
+/// The blocks of statements in a statement, numbered as the locations of declarations count them.
+fn blocks(statement: &Stmt) -> Vec<&[Stmt]> {
+    match statement {
+        Stmt::If {
+            then, otherwise, ..
+        } => vec![then, otherwise],
+        Stmt::Loop { body, .. } => vec![body],
+        Stmt::Switch { cases, .. } => cases.iter().map(|(_, body)| body.as_slice()).collect(),
+        Stmt::Try {
+            body,
+            catches,
+            finally,
+        } => {
+            let mut blocks = vec![body.as_slice()];
+            blocks.extend(catches.iter().map(|catch| catch.body.as_slice()));
+            blocks.push(finally);
+            blocks
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The expressions of a statement, not counting those in its blocks.
+fn expressions(statement: &Stmt) -> Vec<&Expr> {
+    match statement {
+        Stmt::Expr(expr)
+        | Stmt::Return(Some(expr))
+        | Stmt::Throw(expr)
+        | Stmt::If {
+            condition: expr, ..
+        }
+        | Stmt::Switch { value: expr, .. }
+        | Stmt::MonitorEnter(expr)
+        | Stmt::MonitorExit(expr) => vec![expr],
+        Stmt::Loop { kind, .. } => match kind {
+            LoopKind::Infinite => Vec::new(),
+            LoopKind::While(condition) | LoopKind::DoWhile(condition) => vec![condition],
+            LoopKind::For {
+                init,
+                condition,
+                update,
+            } => {
+                let mut found = expressions(init);
+                found.push(condition);
+                found.extend(expressions(update));
+                found
+            }
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// The local variable that a statement assigns, if it is an assignment.
+fn assigned(statement: &Stmt) -> Option<(String, &Expr)> {
+    match statement {
+        Stmt::Expr(Expr::Assign(target, value)) => Some((variable_name(target)?, value)),
+        _ => None,
+    }
+}
+
+/// A statement by the path of its block and its position in the block.
+type Location = (Vec<usize>, usize);
+
+/// Where the variables of a body are declared, with their types: the variables declared before a statement and the
+/// variable that the statement declares itself.
+#[derive(Default)]
+struct Declarations {
+    before: HashMap<Location, Vec<(String, String)>>,
+    inline: HashMap<Location, (String, String)>,
+}
+
+#[derive(Default)]
+struct Uses {
+    /// The locations of the statements that use each variable: the position of the statement and of the block in it
+    /// for each statement that it is nested in, and the position of the statement that uses it.
+    locations: BTreeMap<String, Vec<Vec<usize>>>,
+    types: BTreeMap<String, FieldType>,
+    excluded: BTreeSet<String>,
+}
+
+impl Uses {
+    fn scan(&mut self, statements: &[Stmt], path: &mut Vec<usize>) {
+        for (position, statement) in statements.iter().enumerate() {
+            path.push(position);
+            if let Stmt::Try { catches, .. } = statement {
+                self.excluded
+                    .extend(catches.iter().map(|catch| catch.variable.clone()));
+            }
+            for expr in expressions(statement) {
+                expr.visit(&mut |expr| {
+                    if let Some(name) = variable_name(expr) {
+                        let locations = self.locations.entry(name.clone()).or_default();
+                        if locations.last() != Some(path) {
+                            locations.push(path.clone());
+                        }
+                        if let Expr::Local {
+                            field_type: Some(field_type),
+                            ..
+                        } = expr
+                        {
+                            self.types.insert(name, field_type.clone());
+                        }
+                    } else if let Expr::Assign(target, value) = expr
+                        && let Some(name) = variable_name(target)
+                        && let Some(field_type) = static_type(value)
+                    {
+                        self.types.entry(name).or_insert(field_type);
+                    }
+                });
+            }
+            for (index, block) in blocks(statement).into_iter().enumerate() {
+                path.push(index);
+                self.scan(block, path);
+                path.pop();
+            }
+            path.pop();
+        }
+    }
+}
+
+fn statement_at<'a>(statements: &'a [Stmt], block: &[usize], position: usize) -> Option<&'a Stmt> {
+    match block {
+        [] => statements.get(position),
+        [statement, index, rest @ ..] => {
+            let inner = *blocks(statements.get(*statement)?).get(*index)?;
+            statement_at(inner, rest, position)
+        }
+        [_] => None,
+    }
+}
+
+/// Whether a `break` or `continue` of a label is inside another loop or switch, so that it needs the label.
+fn label_used(
+    statements: &[Stmt],
+    label: usize,
+    nested_breakable: bool,
+    nested_loop: bool,
+) -> bool {
+    statements.iter().any(|statement| match statement {
+        Stmt::Break(other) => *other == label && nested_breakable,
+        Stmt::Continue(other) => *other == label && nested_loop,
+        Stmt::Loop { body, .. } => label_used(body, label, true, true),
+        Stmt::Switch { cases, .. } => cases
+            .iter()
+            .any(|(_, body)| label_used(body, label, true, nested_loop)),
+        statement => blocks(statement)
+            .into_iter()
+            .any(|block| label_used(block, label, nested_breakable, nested_loop)),
+    })
+}
+
+pub(super) struct Printer<'a> {
+    class_name: &'a str,
+    package: &'a str,
+    pub(super) out: String,
+    indent: usize,
+    return_type: Option<FieldType>,
+    declarations: Declarations,
+    location: Vec<usize>,
+    /// The labels of the loops and switches around the statement that is printed, and whether they are loops.
+    breakables: Vec<(usize, bool)>,
+}
+
+impl<'a> Printer<'a> {
+    pub(super) fn new(class_name: &'a str) -> Printer<'a> {
+        let package = class_name
+            .rsplit_once('/')
+            .map_or("", |(package, _)| package);
+        Printer {
+            class_name,
+            package,
+            out: String::new(),
+            indent: 0,
+            return_type: None,
+            declarations: Declarations::default(),
+            location: Vec::new(),
+            breakables: Vec::new(),
+        }
+    }
+
+    /// The name of a class in source code, without the package for classes of `java.lang` and of the same package.
+    pub(super) fn type_name(&self, class_name: &str) -> String {
+        match class_name.rsplit_once('/') {
+            Some((package, name)) if package == "java/lang" || package == self.package => {
+                name.to_string()
+            }
+            Some(_) => class_name.replace('/', "."),
+            None => class_name.to_string(),
+        }
+    }
+
+    pub(super) fn field_type(&self, field_type: &FieldType) -> String {
+        match field_type {
+            FieldType::Object(class_name) => self.type_name(class_name),
+            FieldType::Array(element_type) => format!("{}[]", self.field_type(element_type)),
+            primitive => primitive.java_name(),
+        }
+    }
+
+    pub(super) fn line(&mut self, line: &str) {
+        if line.is_empty() {
+            self.out.push('\n');
+            return;
+        }
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    pub(super) fn indent(&mut self) {
+        self.indent += 1;
+    }
+
+    pub(super) fn dedent(&mut self) {
+        self.indent -= 1;
+    }
+
+    /// Prints the statements of a body in braces, after `header` on its first line.
+    pub(super) fn body(
+        &mut self,
+        header: &str,
+        statements: &[Stmt],
+        parameters: &[String],
+        return_type: Option<FieldType>,
+    ) {
+        let declarations = self.declare(statements, parameters);
+        let declarations = std::mem::replace(&mut self.declarations, declarations);
+        let location = std::mem::take(&mut self.location);
+        let return_type = std::mem::replace(&mut self.return_type, return_type);
+        self.line(&format!("{header} {{"));
+        self.indent();
+        self.statements(statements);
+        self.dedent();
+        self.line("}");
+        self.declarations = declarations;
+        self.location = location;
+        self.return_type = return_type;
+    }
+
+    fn declare(&self, statements: &[Stmt], parameters: &[String]) -> Declarations {
+        let mut uses = Uses::default();
+        uses.excluded.extend(parameters.iter().cloned());
+        uses.scan(statements, &mut Vec::new());
+        let mut declarations = Declarations::default();
+        for (name, locations) in &uses.locations {
+            if uses.excluded.contains(name) {
+                continue;
+            }
+            let mut common = locations[0].len();
+            for location in &locations[1..] {
+                common = common.min(
+                    location
+                        .iter()
+                        .zip(&locations[0])
+                        .take_while(|(left, right)| left == right)
+                        .count(),
+                );
+            }
+            let block = locations[0][..common - common % 2].to_vec();
+            let positions: Vec<usize> = locations
+                .iter()
+                .map(|location| location[block.len()])
+                .collect();
+            let first = positions
+                .iter()
+                .copied()
+                .min()
+                .expect("a variable has a use");
+            let type_name = uses.types.get(name).map_or_else(
+                || "var".to_string(),
+                |field_type| self.field_type(field_type),
+            );
+            let inline = match statement_at(statements, &block, first) {
+                Some(statement @ Stmt::Expr(_)) => assigned(statement)
+                    .is_some_and(|(assigned, value)| assigned == *name && !value.reads_local(name)),
+                Some(Stmt::Loop {
+                    kind: LoopKind::For { init, .. },
+                    ..
+                }) => {
+                    positions.iter().all(|position| *position == first)
+                        && assigned(init).is_some_and(|(assigned, value)| {
+                            assigned == *name && !value.reads_local(name)
+                        })
+                }
+                _ => false,
+            };
+            let key = (block, first);
+            if inline {
+                declarations.inline.insert(key, (type_name, name.clone()));
+            } else {
+                declarations
+                    .before
+                    .entry(key)
+                    .or_default()
+                    .push((type_name, name.clone()));
+            }
+        }
+        declarations
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        for (position, statement) in statements.iter().enumerate() {
+            let key = (self.location.clone(), position);
+            if let Some(before) = self.declarations.before.get(&key).cloned() {
+                for (type_name, name) in before {
+                    self.line(&format!("{type_name} {name};"));
+                }
+            }
+            self.location.push(position);
+            self.statement(statement, self.declarations.inline.get(&key).cloned());
+            self.location.pop();
+        }
+    }
+
+    fn block(&mut self, index: usize, statements: &[Stmt]) {
+        self.location.push(index);
+        self.indent();
+        self.statements(statements);
+        self.dedent();
+        self.location.pop();
+    }
+
+    fn label(&self, label: usize, body: &[Stmt]) -> String {
+        match label_used(body, label, false, false) {
+            true => format!("label{label}: "),
+            false => String::new(),
+        }
+    }
+
+    /// A statement that fits on a line, without the semicolon, declaring the variable that it assigns if it is given.
+    fn simple_statement(
+        &mut self,
+        statement: &Stmt,
+        declared: Option<&(String, String)>,
+    ) -> String {
+        match statement {
+            Stmt::Expr(Expr::Assign(target, value)) => {
+                if let Some((type_name, name)) = declared
+                    && variable_name(target).as_ref() == Some(name)
+                {
+                    return format!(
+                        "{type_name} {name} = {}",
+                        self.assigned_value(target, value)
+                    );
+                }
+                if let Some(increment) = self.increment(target, value, true) {
+                    return increment;
+                }
+                self.expr(&Expr::Assign(target.clone(), value.clone()), ASSIGNMENT)
+            }
+            Stmt::Expr(Expr::PostIncrement(target, amount)) => {
+                let target = self.expr(target, POSTFIX);
+                match amount {
+                    1 => format!("{target}++"),
+                    -1 => format!("{target}--"),
+                    amount if *amount < 0 => format!("{target} -= {}", -i32::from(*amount)),
+                    amount => format!("{target} += {amount}"),
+                }
+            }
+            Stmt::Expr(expr) => self.expr(expr, ASSIGNMENT),
+            _ => String::new(),
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt, declared: Option<(String, String)>) {
+        match statement {
+            Stmt::Expr(_) => {
+                let line = self.simple_statement(statement, declared.as_ref());
+                self.line(&format!("{line};"));
+            }
+            Stmt::Return(None) => self.line("return;"),
+            Stmt::Return(Some(value)) => {
+                let value = match &self.return_type {
+                    Some(field_type) => value.clone().coerce(field_type),
+                    None => value.clone(),
+                };
+                let value = self.expr(&value, ASSIGNMENT);
+                self.line(&format!("return {value};"));
+            }
+            Stmt::Throw(value) => {
+                let value = self.expr(value, ASSIGNMENT);
+                self.line(&format!("throw {value};"));
+            }
+            Stmt::If { .. } => self.if_statement(statement, ""),
+            Stmt::Loop { label, kind, body } => {
+                let label_text = self.label(*label, body);
+                match kind {
+                    LoopKind::Infinite => self.line(&format!("{label_text}while (true) {{")),
+                    LoopKind::While(condition) => {
+                        let condition = self.expr(condition, ASSIGNMENT);
+                        self.line(&format!("{label_text}while ({condition}) {{"));
+                    }
+                    LoopKind::DoWhile(_) => self.line(&format!("{label_text}do {{")),
+                    LoopKind::For {
+                        init,
+                        condition,
+                        update,
+                    } => {
+                        let init = self.simple_statement(init, declared.as_ref());
+                        let condition = self.expr(condition, ASSIGNMENT);
+                        let update = self.simple_statement(update, None);
+                        self.line(&format!(
+                            "{label_text}for ({init}; {condition}; {update}) {{"
+                        ));
+                    }
+                }
+                self.breakables.push((*label, true));
+                self.block(0, body);
+                self.breakables.pop();
+                match kind {
+                    LoopKind::DoWhile(condition) => {
+                        let condition = self.expr(condition, ASSIGNMENT);
+                        self.line(&format!("}} while ({condition});"));
+                    }
+                    _ => self.line("}"),
+                }
+            }
+            Stmt::Switch {
+                label,
+                value,
+                cases,
+            } => {
+                let all: Vec<Stmt> = cases.iter().flat_map(|(_, body)| body.clone()).collect();
+                let label_text = self.label(*label, &all);
+                let is_char = static_type(value) == Some(FieldType::Char);
+                let value = self.expr(value, ASSIGNMENT);
+                self.line(&format!("{label_text}switch ({value}) {{"));
+                self.indent();
+                self.breakables.push((*label, false));
+                for (index, (keys, body)) in cases.iter().enumerate() {
+                    for key in keys {
+                        match key {
+                            Some(key) if is_char && (0..=0xffff).contains(key) => {
+                                let key = self.literal(&Literal::Char(*key as u16));
+                                self.line(&format!("case {key}:"));
+                            }
+                            Some(key) => self.line(&format!("case {key}:")),
+                            None => self.line("default:"),
+                        }
+                    }
+                    self.block(index, body);
+                }
+                self.breakables.pop();
+                self.dedent();
+                self.line("}");
+            }
+            Stmt::Try {
+                body,
+                catches,
+                finally,
+            } => {
+                self.line("try {");
+                self.block(0, body);
+                for (
+                    index,
+                    Catch {
+                        class_names,
+                        variable,
+                        body,
+                    },
+                ) in catches.iter().enumerate()
+                {
+                    let class_names: Vec<String> = match class_names.as_slice() {
+                        [] => vec!["Throwable".to_string()],
+                        class_names => class_names
+                            .iter()
+                            .map(|class_name| self.type_name(class_name))
+                            .collect(),
+                    };
+                    let class_names = class_names.join(" | ");
+                    self.line(&format!("}} catch ({class_names} {variable}) {{"));
+                    self.block(index + 1, body);
+                }
+                if !finally.is_empty() || catches.is_empty() {
+                    self.line("} finally {");
+                    self.block(catches.len() + 1, finally);
+                }
+                self.line("}");
+            }
+            Stmt::Break(label) => {
+                let line = match self.jump_needs_label(*label, false) {
+                    true => format!("break label{label};"),
+                    false => "break;".to_string(),
+                };
+                self.line(&line);
+            }
+            Stmt::Continue(label) => {
+                let line = match self.jump_needs_label(*label, true) {
+                    true => format!("continue label{label};"),
+                    false => "continue;".to_string(),
+                };
+                self.line(&line);
+            }
+            Stmt::MonitorEnter(value) => {
+                let value = self.expr(value, ASSIGNMENT);
+                self.line(&format!("// monitorenter {value}"));
+            }
+            Stmt::MonitorExit(value) => {
+                let value = self.expr(value, ASSIGNMENT);
+                self.line(&format!("// monitorexit {value}"));
+            }
+            Stmt::Comment(comment) => self.line(&format!("// {comment}")),
+        }
+    }
+
+    /// Whether a jump needs its label, because the loop or switch that it leaves isn't the innermost one.
+    fn jump_needs_label(&self, label: usize, is_continue: bool) -> bool {
+        self.breakables
+            .iter()
+            .rev()
+            .find(|(_, is_loop)| *is_loop || !is_continue)
+            .is_none_or(|(innermost, _)| *innermost != label)
+    }
+
+    fn if_statement(&mut self, statement: &Stmt, prefix: &str) {
+        let Stmt::If {
+            condition,
+            then,
+            otherwise,
+        } = statement
+        else {
+            return;
+        };
+        let condition = self.expr(condition, ASSIGNMENT);
+        self.line(&format!("{prefix}if ({condition}) {{"));
+        self.block(0, then);
+        match otherwise.as_slice() {
+            [] => self.line("}"),
+            [nested @ Stmt::If { .. }]
+                if !self
+                    .declarations
+                    .before
+                    .contains_key(&([self.location.as_slice(), &[1]].concat(), 0)) =>
+            {
+                self.location.extend([1, 0]);
+                self.if_statement(nested, "} else ");
+                self.location.truncate(self.location.len() - 2);
+            }
+            _ => {
+                self.line("} else {");
+                self.block(1, otherwise);
+                self.line("}");
+            }
+        }
+    }
+}
+
+impl Printer<'_> {
+    /// A value of a type, such as the constant value of a field.
+    pub(super) fn value(&mut self, value: &Expr, field_type: &FieldType) -> String {
+        self.expr(&value.clone().coerce(field_type), ASSIGNMENT)
+    }
+
+    fn literal(&self, literal: &Literal) -> String {
+        match literal {
+            Literal::Int(value) => value.to_string(),
+            Literal::Long(value) => format!("{value}L"),
+            Literal::Float(value) if value.is_nan() => "Float.NaN".to_string(),
+            Literal::Float(value) if value.is_infinite() => match value.is_sign_positive() {
+                true => "Float.POSITIVE_INFINITY".to_string(),
+                false => "Float.NEGATIVE_INFINITY".to_string(),
+            },
+            Literal::Float(value) => format!("{value:?}f"),
+            Literal::Double(value) if value.is_nan() => "Double.NaN".to_string(),
+            Literal::Double(value) if value.is_infinite() => match value.is_sign_positive() {
+                true => "Double.POSITIVE_INFINITY".to_string(),
+                false => "Double.NEGATIVE_INFINITY".to_string(),
+            },
+            Literal::Double(value) => format!("{value:?}"),
+            Literal::Boolean(value) => value.to_string(),
+            Literal::Char(value) => format!("'{}'", escape(*value, '\'')),
+            Literal::String(value) => string_literal(value),
+            Literal::Null => "null".to_string(),
+            Literal::Class(field_type) => format!("{}.class", self.field_type(field_type)),
+        }
+    }
+
+    fn literal_precedence(literal: &Literal) -> u8 {
+        let negative = match literal {
+            Literal::Int(value) => *value < 0,
+            Literal::Long(value) => *value < 0,
+            Literal::Float(value) => value.is_sign_negative() && !value.is_nan(),
+            Literal::Double(value) => value.is_sign_negative() && !value.is_nan(),
+            _ => false,
+        };
+        match negative {
+            true => UNARY,
+            false => PRIMARY,
+        }
+    }
+
+    fn arguments(&mut self, arguments: &[Expr]) -> String {
+        let arguments: Vec<String> = arguments
+            .iter()
+            .map(|argument| self.expr(argument, ASSIGNMENT))
+            .collect();
+        arguments.join(", ")
+    }
+
+    /// A value assigned to a target, as a literal of the type of the target where it is one.
+    fn assigned_value(&mut self, target: &Expr, value: &Expr) -> String {
+        let value = match static_type(target) {
+            Some(field_type) => value.clone().coerce(&field_type),
+            None => value.clone(),
+        };
+        self.expr(&value, ASSIGNMENT)
+    }
+
+    /// `x++`, `x += y` and the like for an assignment of an operation on its own target.
+    fn increment(&mut self, target: &Expr, value: &Expr, statement: bool) -> Option<String> {
+        if !matches!(
+            target,
+            Expr::Local { .. }
+                | Expr::Field { .. }
+                | Expr::StaticField { .. }
+                | Expr::ArrayElement { .. }
+        ) {
+            return None;
+        }
+        match value {
+            Expr::Binary(operator, left, right) if **left == *target => {
+                let target = self.expr(target, POSTFIX);
+                let (symbol, _) = binary_operator(*operator);
+                match (operator, right.as_ref()) {
+                    (BinaryOperator::Add, Expr::Literal(Literal::Int(1))) if statement => {
+                        Some(format!("{target}++"))
+                    }
+                    (BinaryOperator::Add, Expr::Literal(Literal::Int(1))) => {
+                        Some(format!("++{target}"))
+                    }
+                    (BinaryOperator::Subtract, Expr::Literal(Literal::Int(1))) if statement => {
+                        Some(format!("{target}--"))
+                    }
+                    (BinaryOperator::Subtract, Expr::Literal(Literal::Int(1))) => {
+                        Some(format!("--{target}"))
+                    }
+                    _ => {
+                        let right = self.expr(right, ASSIGNMENT);
+                        Some(format!("{target} {symbol}= {right}"))
+                    }
+                }
+            }
+            Expr::Concat(parts)
+                if parts.len() >= 2
+                    && parts[0] == *target
+                    && (parts.len() == 2 || is_string(&parts[1])) =>
+            {
+                let target = self.expr(target, POSTFIX);
+                let rest = match parts.len() {
+                    2 => self.expr(&parts[1], ASSIGNMENT),
+                    _ => self.expr(&Expr::Concat(parts[1..].to_vec()), ASSIGNMENT),
+                };
+                Some(format!("{target} += {rest}"))
+            }
+            _ => None,
+        }
+    }
+
+    fn lambda(&mut self, parameters: &[String], body: &[Stmt]) -> String {
+        let parameters = match parameters {
+            [parameter] => parameter.clone(),
+            parameters => format!("({})", parameters.join(", ")),
+        };
+        match body {
+            [Stmt::Return(Some(value))] | [Stmt::Expr(value)] => {
+                let value = self.expr(value, ASSIGNMENT);
+                format!("{parameters} -> {value}")
+            }
+            _ => {
+                // The body is printed on its own lines, indented from the line of the lambda.
+                let out = std::mem::take(&mut self.out);
+                let breakables = std::mem::take(&mut self.breakables);
+                self.body(&format!("{parameters} ->"), body, &[], None);
+                let printed = std::mem::replace(&mut self.out, out);
+                self.breakables = breakables;
+                printed.trim_start().trim_end_matches('\n').to_string()
+            }
+        }
+    }
+
+    fn type_prefix(&self, class_name: &str) -> String {
+        match class_name == self.class_name {
+            true => String::new(),
+            false => format!("{}.", self.type_name(class_name)),
+        }
+    }
+
+    /// An expression, in parentheses if it binds less tightly than `precedence`.
+    fn expr(&mut self, expr: &Expr, precedence: u8) -> String {
+        let (text, own) = self.expr_with_precedence(expr);
+        match own < precedence {
+            true => format!("({text})"),
+            false => text,
+        }
+    }
+
+    fn expr_with_precedence(&mut self, expr: &Expr) -> (String, u8) {
+        match expr {
+            Expr::Literal(literal) => (self.literal(literal), Self::literal_precedence(literal)),
+            Expr::Local { .. } | Expr::Temporary(_) | Expr::StackSlot(_) => {
+                (variable_name(expr).expect("a variable"), PRIMARY)
+            }
+            Expr::This => ("this".to_string(), PRIMARY),
+            Expr::CaughtException => ("exception".to_string(), PRIMARY),
+            Expr::StaticField {
+                class_name, name, ..
+            } => (format!("{}{name}", self.type_prefix(class_name)), PRIMARY),
+            Expr::Field { object, name, .. } => {
+                (format!("{}.{name}", self.expr(object, POSTFIX)), PRIMARY)
+            }
+            Expr::ArrayElement { array, index } => {
+                let array = self.expr(array, POSTFIX);
+                let index = self.expr(index, ASSIGNMENT);
+                (format!("{array}[{index}]"), PRIMARY)
+            }
+            Expr::ArrayLength(array) => (format!("{}.length", self.expr(array, POSTFIX)), PRIMARY),
+            Expr::Invoke {
+                receiver,
+                class_name,
+                name,
+                arguments,
+                ..
+            } => {
+                let receiver = match receiver {
+                    Receiver::Static => self.type_prefix(class_name),
+                    Receiver::Object(object) => format!("{}.", self.expr(object, POSTFIX)),
+                    Receiver::Super => "super.".to_string(),
+                };
+                let arguments = self.arguments(arguments);
+                (format!("{receiver}{name}({arguments})"), PRIMARY)
+            }
+            Expr::ConstructorCall { this, arguments } => {
+                let callee = match this {
+                    true => "this",
+                    false => "super",
+                };
+                (format!("{callee}({})", self.arguments(arguments)), PRIMARY)
+            }
+            Expr::New {
+                class_name,
+                arguments,
+            } => {
+                let arguments = self.arguments(arguments);
+                (
+                    format!("new {}({arguments})", self.type_name(class_name)),
+                    PRIMARY,
+                )
+            }
+            Expr::Uninitialized { class_name, .. } => {
+                (format!("new {}()", self.type_name(class_name)), PRIMARY)
+            }
+            Expr::NewArray {
+                element_type,
+                lengths,
+            } => {
+                let mut base = element_type;
+                let mut dimensions = 0;
+                while let FieldType::Array(inner) = base {
+                    base = inner;
+                    dimensions += 1;
+                }
+                let mut text = format!("new {}", self.field_type(base));
+                for length in lengths {
+                    let length = self.expr(length, ASSIGNMENT);
+                    text.push_str(&format!("[{length}]"));
+                }
+                for _ in 0..dimensions {
+                    text.push_str("[]");
+                }
+                (text, PRIMARY)
+            }
+            Expr::ArrayInitializer {
+                element_type,
+                elements,
+                ..
+            } => {
+                let elements: Vec<String> = elements
+                    .iter()
+                    .map(|element| {
+                        let element = element.clone().coerce(element_type);
+                        self.expr(&element, ASSIGNMENT)
+                    })
+                    .collect();
+                let element_type = self.field_type(element_type);
+                (
+                    format!("new {element_type}[]{{{}}}", elements.join(", ")),
+                    PRIMARY,
+                )
+            }
+            Expr::Binary(operator, left, right) => {
+                let (symbol, precedence) = binary_operator(*operator);
+                let left = self.expr(left, precedence);
+                let right = self.expr(right, precedence + 1);
+                (format!("{left} {symbol} {right}"), precedence)
+            }
+            Expr::Negate(operand) => {
+                let operand = self.expr(operand, UNARY);
+                match operand.starts_with('-') {
+                    true => (format!("-({operand})"), UNARY),
+                    false => (format!("-{operand}"), UNARY),
+                }
+            }
+            Expr::Not(operand) => (format!("!{}", self.expr(operand, UNARY)), UNARY),
+            Expr::Compare(comparison, left, right) => {
+                let (symbol, precedence) = comparison_operator(*comparison);
+                let field_type = static_type(left).or_else(|| static_type(right));
+                let (left, right) = match &field_type {
+                    Some(field_type @ (FieldType::Char | FieldType::Boolean)) => (
+                        left.as_ref().clone().coerce(field_type),
+                        right.as_ref().clone().coerce(field_type),
+                    ),
+                    _ => (left.as_ref().clone(), right.as_ref().clone()),
+                };
+                let left = self.expr(&left, precedence);
+                let right = self.expr(&right, precedence + 1);
+                (format!("{left} {symbol} {right}"), precedence)
+            }
+            Expr::CompareResult(left, right) => {
+                let class_name = match static_type(left).or_else(|| static_type(right)) {
+                    Some(FieldType::Long) => "Long",
+                    Some(FieldType::Float) => "Float",
+                    _ => "Double",
+                };
+                let left = self.expr(left, ASSIGNMENT);
+                let right = self.expr(right, ASSIGNMENT);
+                (format!("{class_name}.compare({left}, {right})"), PRIMARY)
+            }
+            Expr::Logical(operator, left, right) => {
+                let (symbol, precedence) = match operator {
+                    Logical::And => ("&&", AND),
+                    Logical::Or => ("||", OR),
+                };
+                let left = self.expr(left, precedence);
+                let right = self.expr(right, precedence + 1);
+                (format!("{left} {symbol} {right}"), precedence)
+            }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let condition = self.expr(condition, OR);
+                let then = self.expr(then, ASSIGNMENT);
+                let otherwise = self.expr(otherwise, CONDITIONAL);
+                (format!("{condition} ? {then} : {otherwise}"), CONDITIONAL)
+            }
+            Expr::Cast(field_type, operand) => {
+                let operand = self.expr(operand, UNARY);
+                (
+                    format!("({}) {operand}", self.field_type(field_type)),
+                    UNARY,
+                )
+            }
+            Expr::InstanceOf(operand, field_type) => {
+                let operand = self.expr(operand, RELATIONAL);
+                let field_type = self.field_type(field_type);
+                (format!("{operand} instanceof {field_type}"), RELATIONAL)
+            }
+            Expr::Assign(target, value) => {
+                if let Some(increment) = self.increment(target, value, false) {
+                    return (increment, ASSIGNMENT);
+                }
+                let value = self.assigned_value(target, value);
+                (
+                    format!("{} = {value}", self.expr(target, POSTFIX)),
+                    ASSIGNMENT,
+                )
+            }
+            Expr::PostIncrement(target, amount) => {
+                let target = self.expr(target, POSTFIX);
+                match amount {
+                    1 => (format!("{target}++"), POSTFIX),
+                    -1 => (format!("{target}--"), POSTFIX),
+                    amount => (format!("({target} += {amount}) - {amount}"), ADDITIVE),
+                }
+            }
+            Expr::Lambda { parameters, body } => (self.lambda(parameters, body), ASSIGNMENT),
+            Expr::MethodReference { qualifier, name } => {
+                let qualifier = match qualifier {
+                    Qualifier::Type(class_name) => self.type_name(class_name),
+                    Qualifier::Object(object) => self.expr(object, POSTFIX),
+                };
+                (format!("{qualifier}::{name}"), PRIMARY)
+            }
+            Expr::Concat(parts) => {
+                let mut texts: Vec<String> = Vec::with_capacity(parts.len() + 1);
+                // Without a string among the first two operands, `+` would add numbers.
+                if !parts.iter().take(2).any(is_string) {
+                    texts.push("\"\"".to_string());
+                }
+                for part in parts {
+                    texts.push(self.expr(part, MULTIPLICATIVE));
+                }
+                (texts.join(" + "), ADDITIVE)
+            }
+            Expr::Dynamic {
+                bootstrap,
+                name,
+                arguments,
+            } => {
+                let arguments = self.arguments(arguments);
+                (format!("/* {bootstrap} */ {name}({arguments})"), PRIMARY)
+            }
+            Expr::Raw(text) => (text.clone(), PRIMARY),
+        }
+    }
+}
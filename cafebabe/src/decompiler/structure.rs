@@ -0,0 +1,1075 @@
+//! Structuring the nodes of a method into nested statements: the branches of conditions and switches end where they
+//! meet again, which is found with the dominator tree, and the natural loops are written as `while (true)` loops with
+//! labelled breaks and continues. Afterwards the loops whose condition is at their start or their end are rewritten
+//! to `while`, `do`-`while` and `for` loops, and `else` branches after jumps are moved out of the `if`.
+//!
+//! Exception handlers that start at the same offset and cover the same range become the catch clauses of one `try`
+//! statement, and a handler of all exceptions that ends by throwing what it caught becomes a `finally` clause. Control
+//! flow that doesn't fit is left as a `goto` comment.
+
+use std::collections::BTreeMap;
+
+use super::ast::{Catch, Expr, Logical, LoopKind, Stmt, ends_with_jump, logical, negate};
+use super::lift::{Exit, Node};
+use crate::attributes::ExceptionHandler;
+use crate::constant_pool::ConstantPool;
+use crate::control_flow::dominators::{dominator_tree_of, natural_loops_of};
+use crate::control_flow::{ControlFlowGraph, DominatorTree, Loop};
+use crate::errors::ClassFileError;
+
+/// How many statements a node that leaves the method may have to be copied to each branch that goes to it.
+const MAX_COPIED_STATEMENTS: usize = 4;
+
+/// The nodes that a node goes to when it is left normally, without duplicates.
+fn exit_targets(exit: &Exit) -> Vec<usize> {
+    let mut targets = match exit {
+        Exit::Goto(target) => vec![*target],
+        Exit::Branch { target, next, .. } => vec![*next, *target],
+        Exit::Switch { cases, default, .. } => {
+            let mut targets: Vec<usize> = cases.iter().map(|(_, target)| *target).collect();
+            targets.push(*default);
+            targets
+        }
+        Exit::Return(_) | Exit::Throw(_) => Vec::new(),
+    };
+    let mut seen = Vec::with_capacity(targets.len());
+    targets.retain(|target| {
+        let new = !seen.contains(target);
+        seen.push(*target);
+        new
+    });
+    targets
+}
+
+fn exit_targets_mut(exit: &mut Exit) -> Vec<&mut usize> {
+    match exit {
+        Exit::Goto(target) => vec![target],
+        Exit::Branch { target, next, .. } => vec![target, next],
+        Exit::Switch { cases, default, .. } => {
+            let mut targets: Vec<&mut usize> = cases.iter_mut().map(|(_, target)| target).collect();
+            targets.push(default);
+            targets
+        }
+        Exit::Return(_) | Exit::Throw(_) => Vec::new(),
+    }
+}
+
+/// The handlers of the exception table that start and end at the same offsets.
+struct TryRegion {
+    start: u32,
+    end: u32,
+    /// The class that each handler catches, or `None` for all exceptions, with its node.
+    handlers: Vec<(Option<String>, usize)>,
+    entered: bool,
+}
+
+/// Where `break` and `continue` go from inside a loop or switch.
+#[derive(Clone)]
+struct Target {
+    label: usize,
+    break_to: Option<usize>,
+    continue_to: Option<usize>,
+}
+
+#[derive(Clone, Default)]
+struct Context {
+    /// The node after the statement that is being structured, where its branches end.
+    follow: Option<usize>,
+    targets: Vec<Target>,
+}
+
+struct Structurer {
+    nodes: Vec<Option<Node>>,
+    /// The nodes that go to each node when they are left normally.
+    predecessors: Vec<Vec<usize>>,
+    dominators: DominatorTree,
+    /// The loop with each node as its header.
+    loops: Vec<Option<Loop>>,
+    regions: Vec<TryRegion>,
+    handlers: Vec<bool>,
+    claimed: Vec<bool>,
+    emitted: Vec<bool>,
+    /// The statements of the nodes that leave the method and are small enough to be copied.
+    terminal_copies: Vec<Option<Vec<Stmt>>>,
+    active_loops: Vec<usize>,
+    next_label: usize,
+}
+
+impl Structurer {
+    fn start(&self, node: usize) -> u32 {
+        self.nodes[node]
+            .as_ref()
+            .map_or(u32::MAX, |node| node.start)
+    }
+
+    fn is_jump_target(&self, node: usize, context: &Context) -> bool {
+        context
+            .targets
+            .iter()
+            .any(|target| target.break_to == Some(node) || target.continue_to == Some(node))
+    }
+
+    fn jump(&self, node: usize, context: &Context) -> Option<Stmt> {
+        for target in context.targets.iter().rev() {
+            if target.continue_to == Some(node) {
+                return Some(Stmt::Continue(target.label));
+            }
+            if target.break_to == Some(node) {
+                return Some(Stmt::Break(target.label));
+            }
+        }
+        None
+    }
+
+    /// The number of nodes that go to a node, apart from those that it dominates, which jump back to it.
+    fn forward_predecessors(&self, node: usize) -> usize {
+        self.predecessors[node]
+            .iter()
+            .filter(|predecessor| !self.dominators.dominates(node, **predecessor))
+            .count()
+    }
+
+    /// Whether a node can be reached from others without going through where breaks and continues go.
+    fn reaches(&self, starts: &[usize], node: usize, context: &Context) -> bool {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut pending = starts.to_vec();
+        while let Some(current) = pending.pop() {
+            if current == node {
+                return true;
+            }
+            if visited[current] || self.is_jump_target(current, context) {
+                continue;
+            }
+            visited[current] = true;
+            if let Some(lifted) = &self.nodes[current] {
+                pending.extend(exit_targets(&lifted.exit));
+            }
+        }
+        false
+    }
+
+    /// Where the branches of a node meet: the first node that it immediately dominates, that more than one node goes
+    /// to and that the branches from `starts` reach.
+    fn merge(
+        &self,
+        node: usize,
+        starts: &[usize],
+        context: &Context,
+        excluded: &[usize],
+    ) -> Option<usize> {
+        self.dominators
+            .children(node)
+            .into_iter()
+            .filter(|child| {
+                self.nodes[*child].is_some()
+                    && !self.handlers[*child]
+                    && !excluded.contains(child)
+                    && !self.is_jump_target(*child, context)
+                    && self.forward_predecessors(*child) >= 2
+                    && self.reaches(starts, *child, context)
+            })
+            .min_by_key(|child| self.start(*child))
+    }
+
+    fn new_label(&mut self) -> usize {
+        self.next_label += 1;
+        self.next_label - 1
+    }
+
+    /// Structures the nodes from a node until the follow of the context, a jump or the end of the method. When
+    /// `entering` the first node is structured even if it is the follow or a jump target, which it is for loops.
+    fn sequence(&mut self, start: usize, context: &Context, mut entering: bool) -> Vec<Stmt> {
+        let mut statements = Vec::new();
+        let mut current = Some(start);
+        while let Some(node) = current {
+            if !entering {
+                if Some(node) == context.follow {
+                    break;
+                }
+                if let Some(jump) = self.jump(node, context) {
+                    statements.push(jump);
+                    break;
+                }
+            }
+            entering = false;
+            if let Some(copy) = &self.terminal_copies[node] {
+                statements.extend(copy.iter().cloned());
+                break;
+            }
+            if self.emitted[node] || self.nodes[node].is_none() {
+                statements.push(Stmt::Comment(format!("goto {}", self.start(node))));
+                break;
+            }
+
+            let region = self.region_at(node);
+            if let Some(header_loop) = &self.loops[node]
+                && !self.active_loops.contains(&node)
+                && region.is_none_or(|region| {
+                    self.regions[region]
+                        .handlers
+                        .iter()
+                        .all(|(_, handler)| header_loop.blocks.binary_search(handler).is_ok())
+                })
+            {
+                let (statement, next) = self.structure_loop(node, context);
+                statements.push(statement);
+                current = next;
+                continue;
+            }
+            if let Some(region) = region
+                && let Some((statement, next)) = self.structure_try(node, region, context)
+            {
+                statements.push(statement);
+                current = next;
+                continue;
+            }
+
+            self.emitted[node] = true;
+            let lifted = self.nodes[node].as_mut().expect("checked above");
+            let terminal = match &lifted.exit {
+                Exit::Return(value) => Some(Stmt::Return(value.clone())),
+                Exit::Throw(value) => Some(Stmt::Throw(value.clone())),
+                _ => None,
+            };
+            if let Some(terminal) = terminal {
+                // Small blocks that leave the method are copied where other branches go to them too.
+                if lifted.statements.len() < MAX_COPIED_STATEMENTS {
+                    let mut copy = lifted.statements.clone();
+                    copy.push(terminal.clone());
+                    self.terminal_copies[node] = Some(copy);
+                }
+                statements.append(&mut lifted.statements);
+                statements.push(terminal);
+                break;
+            }
+            statements.append(&mut lifted.statements);
+            current = match lifted.exit.clone() {
+                Exit::Goto(next) => Some(next),
+                Exit::Return(_) | Exit::Throw(_) => None,
+                Exit::Branch {
+                    condition,
+                    target,
+                    next,
+                } => {
+                    let follow = self.merge(node, &[target, next], context, &[]);
+                    let inner = Context {
+                        follow: follow.or(context.follow),
+                        targets: context.targets.clone(),
+                    };
+                    // The branch that isn't taken comes first, as in the source code.
+                    let then = self.sequence(next, &inner, false);
+                    let otherwise = self.sequence(target, &inner, false);
+                    statements.push(Stmt::If {
+                        condition: negate(condition),
+                        then,
+                        otherwise,
+                    });
+                    follow
+                }
+                Exit::Switch {
+                    value,
+                    cases,
+                    default,
+                } => {
+                    let (statement, follow) =
+                        self.structure_switch(node, value, cases, default, context);
+                    statements.push(statement);
+                    follow
+                }
+            };
+        }
+        statements
+    }
+
+    fn structure_switch(
+        &mut self,
+        node: usize,
+        value: Expr,
+        cases: Vec<(i32, usize)>,
+        default: usize,
+        context: &Context,
+    ) -> (Stmt, Option<usize>) {
+        let mut targets: Vec<usize> = cases.iter().map(|(_, target)| *target).collect();
+        targets.sort_by_key(|target| self.start(*target));
+        targets.dedup();
+        // Cases that go to where the statement around the switch goes on break out of it. Else, without a default
+        // clause, the default goes to the statement after the switch.
+        let mut starts = targets.clone();
+        starts.push(default);
+        let follow = self
+            .merge(node, &starts, context, &targets)
+            .or(context.follow)
+            .or_else(|| {
+                (!targets.contains(&default)
+                    && targets
+                        .iter()
+                        .all(|target| self.start(*target) < self.start(default)))
+                .then_some(default)
+            });
+        if Some(default) != follow && !targets.contains(&default) {
+            targets.push(default);
+            targets.sort_by_key(|target| self.start(*target));
+        }
+
+        let label = self.new_label();
+        let mut targets_context = context.targets.clone();
+        targets_context.push(Target {
+            label,
+            break_to: follow,
+            continue_to: None,
+        });
+        let mut clauses = Vec::with_capacity(targets.len());
+        for (position, target) in targets.iter().enumerate() {
+            let inner = Context {
+                // A case that doesn't break falls through to the next one.
+                follow: targets.get(position + 1).copied().or(follow),
+                targets: targets_context.clone(),
+            };
+            let mut keys: Vec<Option<i32>> = cases
+                .iter()
+                .filter(|(_, case_target)| case_target == target)
+                .map(|(key, _)| Some(*key))
+                .collect();
+            if default == *target {
+                keys.push(None);
+            }
+            clauses.push((keys, self.sequence(*target, &inner, false)));
+        }
+        let statement = Stmt::Switch {
+            label,
+            value,
+            cases: clauses,
+        };
+        (statement, follow)
+    }
+
+    /// Where a loop is left: the exit of its header or of a latch that checks a condition, or else the node that the
+    /// most nodes of the loop leave it for.
+    fn loop_follow(&self, header_loop: &Loop) -> Option<usize> {
+        let outside = |node: &usize| header_loop.blocks.binary_search(node).is_err();
+        let conditional_exit = |node: usize| match &self.nodes[node].as_ref()?.exit {
+            Exit::Branch { target, next, .. } if outside(target) != outside(next) => {
+                Some(if outside(target) { *target } else { *next })
+            }
+            _ => None,
+        };
+        if let Some(exit) = conditional_exit(header_loop.header) {
+            return Some(exit);
+        }
+        if let Some(exit) = header_loop
+            .latches
+            .iter()
+            .find_map(|latch| conditional_exit(*latch))
+        {
+            return Some(exit);
+        }
+        let mut exits: BTreeMap<usize, usize> = BTreeMap::new();
+        for block in &header_loop.blocks {
+            if let Some(node) = &self.nodes[*block] {
+                for target in exit_targets(&node.exit).into_iter().filter(outside) {
+                    *exits.entry(target).or_default() += 1;
+                }
+            }
+        }
+        exits
+            .into_iter()
+            .max_by_key(|(target, count)| (*count, std::cmp::Reverse(self.start(*target))))
+            .map(|(target, _)| target)
+    }
+
+    fn structure_loop(&mut self, header: usize, context: &Context) -> (Stmt, Option<usize>) {
+        let header_loop = self.loops[header]
+            .clone()
+            .expect("the node is a loop header");
+        let follow = self.loop_follow(&header_loop);
+        let label = self.new_label();
+        let mut targets = context.targets.clone();
+        targets.push(Target {
+            label,
+            break_to: follow,
+            continue_to: Some(header),
+        });
+        let inner = Context {
+            follow: None,
+            targets,
+        };
+        self.active_loops.push(header);
+        let body = self.sequence(header, &inner, true);
+        self.active_loops.pop();
+        let statement = Stmt::Loop {
+            label,
+            kind: LoopKind::Infinite,
+            body,
+        };
+        (statement, follow)
+    }
+
+    fn region_at(&self, node: usize) -> Option<usize> {
+        let start = self.start(node);
+        self.regions
+            .iter()
+            .position(|region| !region.entered && region.start == start)
+    }
+
+    /// Where a `try` statement goes on: where its body and its handlers meet, or else the first node after its range
+    /// that the body goes to.
+    fn try_follow(&self, node: usize, region: &TryRegion, context: &Context) -> Option<usize> {
+        let inside = |node: usize| (region.start..region.end).contains(&self.start(node));
+        let excluded: Vec<usize> = (0..self.nodes.len()).filter(|node| inside(*node)).collect();
+        if let Some(merge) = self.merge(node, &[node], context, &excluded) {
+            return Some(merge);
+        }
+        (0..self.nodes.len())
+            .filter(|node| inside(*node))
+            .filter_map(|node| self.nodes[node].as_ref())
+            .flat_map(|node| exit_targets(&node.exit))
+            .filter(|target| {
+                !inside(*target)
+                    && !self.handlers[*target]
+                    && self.reaches(&[node], *target, context)
+            })
+            .min_by_key(|target| self.start(*target))
+    }
+
+    fn structure_try(
+        &mut self,
+        node: usize,
+        region: usize,
+        context: &Context,
+    ) -> Option<(Stmt, Option<usize>)> {
+        self.regions[region].entered = true;
+        // Handlers of more than one class are multi-catch clauses.
+        let mut handlers: Vec<(Vec<String>, usize)> = Vec::new();
+        for (class_name, handler) in &self.regions[region].handlers {
+            if self.claimed[*handler] {
+                continue;
+            }
+            let position = match handlers.iter().position(|(_, other)| other == handler) {
+                Some(position) => position,
+                None => {
+                    handlers.push((Vec::new(), *handler));
+                    handlers.len() - 1
+                }
+            };
+            handlers[position].0.extend(class_name.iter().cloned());
+        }
+        if handlers.is_empty() {
+            return None;
+        }
+        for (_, handler) in &handlers {
+            self.claimed[*handler] = true;
+        }
+        let follow = self.try_follow(node, &self.regions[region], context);
+        let inner = Context {
+            follow: follow.or(context.follow),
+            targets: context.targets.clone(),
+        };
+        let mut body = self.sequence(node, &inner, true);
+
+        let mut catches = Vec::with_capacity(handlers.len());
+        for (class_names, handler) in handlers {
+            // The handler starts by storing the exception in the variable of the catch clause.
+            let mut variable = "exception".to_string();
+            if let Some(lifted) = self.nodes[handler].as_mut()
+                && let Some(Stmt::Expr(Expr::Assign(target, value))) = lifted.statements.first()
+                && let (Expr::Local { name, .. }, Expr::CaughtException) =
+                    (target.as_ref(), value.as_ref())
+            {
+                variable = name.clone();
+                lifted.statements.remove(0);
+            }
+            let body = self.sequence(handler, &inner, false);
+            catches.push(Catch {
+                class_names,
+                variable,
+                body,
+            });
+        }
+
+        // A handler of all exceptions that rethrows them after its statements is a finally clause.
+        let mut finally = Vec::new();
+        if let Some(last) = catches.last()
+            && last.class_names.is_empty()
+            && let Some(Stmt::Throw(thrown)) = last.body.last()
+            && match thrown {
+                Expr::Local { name, .. } => *name == last.variable,
+                Expr::CaughtException => true,
+                _ => false,
+            }
+        {
+            let mut last = catches.pop().expect("checked above");
+            last.body.pop();
+            finally = last.body;
+            // The statements of the finally clause are copied to the end of the body and of each catch clause.
+            remove_finally_copy(&mut body, &finally);
+            for catch in &mut catches {
+                remove_finally_copy(&mut catch.body, &finally);
+            }
+        }
+        let statement = Stmt::Try {
+            body,
+            catches,
+            finally,
+        };
+        Some((statement, follow))
+    }
+}
+
+/// Removes the copy of the statements of a finally clause at the end of a block, or before the jump that ends it.
+fn remove_finally_copy(statements: &mut Vec<Stmt>, finally: &[Stmt]) {
+    if finally.is_empty() {
+        return;
+    }
+    if let Some(Stmt::Try {
+        body,
+        catches,
+        finally: inner,
+    }) = statements.last_mut()
+        && inner.is_empty()
+    {
+        remove_finally_copy(body, finally);
+        for catch in catches {
+            remove_finally_copy(&mut catch.body, finally);
+        }
+        return;
+    }
+    let end = match statements.last() {
+        // The value of a `return` in the `try` is stored to a local before the copy of the `finally` statements.
+        Some(
+            Stmt::Return(None | Some(Expr::Local { .. })) | Stmt::Break(_) | Stmt::Continue(_),
+        ) => statements.len() - 1,
+        _ => statements.len(),
+    };
+    if end >= finally.len() && statements[end - finally.len()..end] == *finally {
+        statements.drain(end - finally.len()..end);
+    }
+}
+
+/// Merges the branches of a node into the node, where they test a second condition that goes to one of the same nodes,
+/// as `javac` compiles `&&` and `||` to.
+fn fold_conditions(nodes: &mut [Option<Node>], protected: &[bool]) {
+    loop {
+        let mut predecessors = vec![0; nodes.len()];
+        for node in nodes.iter().flatten() {
+            for target in exit_targets(&node.exit) {
+                predecessors[target] += 1;
+            }
+        }
+        let mut changed = false;
+        for first in 0..nodes.len() {
+            let Some(Node {
+                exit:
+                    Exit::Branch {
+                        condition,
+                        target,
+                        next,
+                    },
+                ..
+            }) = &nodes[first]
+            else {
+                continue;
+            };
+            let (condition, target, next) = (condition.clone(), *target, *next);
+            for (second, through_next) in [(next, true), (target, false)] {
+                let Some(Node {
+                    statements,
+                    exit:
+                        Exit::Branch {
+                            condition: second_condition,
+                            target: second_target,
+                            next: second_next,
+                        },
+                    ..
+                }) = &nodes[second]
+                else {
+                    continue;
+                };
+                if second == first
+                    || second == 0
+                    || protected[second]
+                    || predecessors[second] != 1
+                    || !statements.is_empty()
+                {
+                    continue;
+                }
+                let second_condition = second_condition.clone();
+                let (second_target, second_next) = (*second_target, *second_next);
+                let folded = match through_next {
+                    true if second_target == target => Some((
+                        logical(Logical::Or, condition.clone(), second_condition),
+                        target,
+                        second_next,
+                    )),
+                    true if second_next == target => Some((
+                        logical(Logical::Or, condition.clone(), negate(second_condition)),
+                        target,
+                        second_target,
+                    )),
+                    false if second_next == next => Some((
+                        logical(Logical::And, condition.clone(), second_condition),
+                        second_target,
+                        next,
+                    )),
+                    false if second_target == next => Some((
+                        logical(Logical::And, condition.clone(), negate(second_condition)),
+                        second_next,
+                        next,
+                    )),
+                    _ => None,
+                };
+                if let Some((condition, target, next)) = folded {
+                    let removed = nodes[second].take().expect("checked above");
+                    let node = nodes[first].as_mut().expect("checked above");
+                    node.blocks.extend(removed.blocks);
+                    node.exit = Exit::Branch {
+                        condition,
+                        target,
+                        next,
+                    };
+                    changed = true;
+                    break;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// Lets the nodes that go to a node without statements go where it goes instead.
+fn thread_jumps(nodes: &mut [Option<Node>], protected: &[bool]) {
+    let resolve = |nodes: &[Option<Node>], mut node: usize| {
+        for _ in 0..nodes.len() {
+            match &nodes[node] {
+                Some(Node {
+                    statements,
+                    exit: Exit::Goto(next),
+                    ..
+                }) if statements.is_empty() && node != 0 && !protected[node] && *next != node => {
+                    node = *next;
+                }
+                _ => break,
+            }
+        }
+        node
+    };
+    for position in 0..nodes.len() {
+        let Some(node) = &nodes[position] else {
+            continue;
+        };
+        let resolved: Vec<usize> = exit_targets_mut(&mut node.exit.clone())
+            .into_iter()
+            .map(|target| resolve(nodes, *target))
+            .collect();
+        let node = nodes[position].as_mut().expect("checked above");
+        for (target, resolved) in exit_targets_mut(&mut node.exit).into_iter().zip(resolved) {
+            *target = resolved;
+        }
+    }
+}
+
+/// Structures the nodes of a method into statements.
+pub(super) fn structure(
+    mut nodes: Vec<Option<Node>>,
+    graph: &ControlFlowGraph,
+    exception_table: &[ExceptionHandler],
+    constant_pool: &ConstantPool,
+) -> Result<Vec<Stmt>, ClassFileError> {
+    let count = nodes.len();
+    let node_at = |offset: u16| {
+        graph
+            .block_of(u32::from(offset))
+            .filter(|block| graph.blocks[*block].start == u32::from(offset))
+    };
+    // Handlers and the starts of their ranges keep their nodes.
+    let mut handlers = vec![false; count];
+    let mut protected = vec![false; count];
+    for handler in exception_table {
+        if let Some(node) = node_at(handler.handler_pc) {
+            handlers[node] = true;
+            protected[node] = true;
+        }
+        if let Some(node) = node_at(handler.start_pc) {
+            protected[node] = true;
+        }
+    }
+    fold_conditions(&mut nodes, &protected);
+    thread_jumps(&mut nodes, &protected);
+
+    let mut successors = vec![Vec::new(); count];
+    let mut all_successors = vec![Vec::new(); count];
+    let mut predecessors = vec![Vec::new(); count];
+    let mut all_predecessors = vec![Vec::new(); count];
+    for (position, node) in nodes.iter().enumerate() {
+        let Some(node) = node else {
+            continue;
+        };
+        successors[position] = exit_targets(&node.exit);
+        all_successors[position] = successors[position].clone();
+        for handler in exception_table {
+            let covered = u32::from(handler.start_pc)..u32::from(handler.end_pc);
+            if let Some(target) = node_at(handler.handler_pc)
+                && node
+                    .blocks
+                    .iter()
+                    .any(|block| covered.contains(&graph.blocks[*block].start))
+                && !all_successors[position].contains(&target)
+            {
+                all_successors[position].push(target);
+            }
+        }
+    }
+    for (position, targets) in all_successors.iter().enumerate() {
+        for target in targets {
+            all_predecessors[*target].push(position);
+            if successors[position].contains(target) {
+                predecessors[*target].push(position);
+            }
+        }
+    }
+    let dominators = dominator_tree_of(&all_successors, &all_predecessors);
+    // Only normal edges jump back to a loop header, but handlers in a loop belong to it.
+    let mut loops: Vec<Option<Loop>> = (0..count).map(|_| None).collect();
+    for found in natural_loops_of(&successors, &all_predecessors, &dominators) {
+        let header = found.header;
+        loops[header] = Some(found);
+    }
+
+    // The handlers of each range, in the order of the exception table.
+    let mut ranges: BTreeMap<(u16, usize), (u16, u16)> = BTreeMap::new();
+    for (position, handler) in exception_table.iter().enumerate() {
+        let first = exception_table[..position]
+            .iter()
+            .position(|other| {
+                other.handler_pc == handler.handler_pc && other.catch_type == handler.catch_type
+            })
+            .unwrap_or(position);
+        let range = ranges
+            .entry((handler.handler_pc, first))
+            .or_insert((handler.start_pc, handler.end_pc));
+        range.0 = range.0.min(handler.start_pc);
+        range.1 = range.1.max(handler.end_pc);
+    }
+    let mut regions: Vec<TryRegion> = Vec::new();
+    let mut ordered: Vec<(usize, u16, (u16, u16))> = ranges
+        .into_iter()
+        .map(|((handler_pc, first), range)| (first, handler_pc, range))
+        .collect();
+    ordered.sort();
+    for (first, handler_pc, (start, end)) in ordered {
+        let Some(handler) = node_at(handler_pc) else {
+            continue;
+        };
+        let class_name = match exception_table[first].catch_type {
+            0 => None,
+            catch_type => Some(constant_pool.class_name(usize::from(catch_type))?.clone()),
+        };
+        let (start, end) = (u32::from(start), u32::from(end));
+        match regions
+            .iter_mut()
+            .find(|region| region.start == start && region.end == end)
+        {
+            Some(region) => region.handlers.push((class_name, handler)),
+            None => regions.push(TryRegion {
+                start,
+                end,
+                handlers: vec![(class_name, handler)],
+                entered: false,
+            }),
+        }
+    }
+    // Outer ranges first.
+    regions.sort_by_key(|region| (region.start, std::cmp::Reverse(region.end)));
+
+    let mut structurer = Structurer {
+        nodes,
+        predecessors,
+        dominators,
+        loops,
+        regions,
+        handlers,
+        claimed: vec![false; count],
+        emitted: vec![false; count],
+        terminal_copies: vec![None; count],
+        active_loops: Vec::new(),
+        next_label: 0,
+    };
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let statements = structurer.sequence(0, &Context::default(), true);
+    Ok(simplify(statements))
+}
+
+fn contains_continue(statements: &[Stmt], label: usize) -> bool {
+    statements.iter().any(|statement| match statement {
+        Stmt::Continue(other) => *other == label,
+        Stmt::If {
+            then, otherwise, ..
+        } => contains_continue(then, label) || contains_continue(otherwise, label),
+        Stmt::Loop { body, .. } => contains_continue(body, label),
+        Stmt::Switch { cases, .. } => cases.iter().any(|(_, body)| contains_continue(body, label)),
+        Stmt::Try {
+            body,
+            catches,
+            finally,
+        } => {
+            contains_continue(body, label)
+                || catches
+                    .iter()
+                    .any(|catch| contains_continue(&catch.body, label))
+                || contains_continue(finally, label)
+        }
+        _ => false,
+    })
+}
+
+fn is_plain_jump(statements: &[Stmt]) -> bool {
+    matches!(
+        statements,
+        [Stmt::Return(_) | Stmt::Throw(_) | Stmt::Break(_) | Stmt::Continue(_)]
+    )
+}
+
+fn push_if(statements: &mut Vec<Stmt>, condition: Expr, then: Vec<Stmt>, otherwise: Vec<Stmt>) {
+    let loop_jump = matches!(otherwise.as_slice(), [Stmt::Break(_) | Stmt::Continue(_)]);
+    if loop_jump || (is_plain_jump(&otherwise) && !ends_with_jump(&then)) {
+        statements.push(Stmt::If {
+            condition: negate(condition),
+            then: otherwise,
+            otherwise: Vec::new(),
+        });
+        statements.extend(then);
+    } else if !otherwise.is_empty() && ends_with_jump(&then) {
+        statements.push(Stmt::If {
+            condition,
+            then,
+            otherwise: Vec::new(),
+        });
+        statements.extend(otherwise);
+    } else if then.is_empty() && !otherwise.is_empty() {
+        statements.push(Stmt::If {
+            condition: negate(condition),
+            then: otherwise,
+            otherwise: Vec::new(),
+        });
+    } else {
+        statements.push(Stmt::If {
+            condition,
+            then,
+            otherwise,
+        });
+    }
+}
+
+/// The local variable that a statement updates, for the last statement of a `for` loop.
+fn updated_local(statement: &Stmt) -> Option<&str> {
+    match statement {
+        Stmt::Expr(Expr::Assign(target, _) | Expr::PostIncrement(target, _)) => {
+            match target.as_ref() {
+                Expr::Local { name, .. } => Some(name),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn push_loop(statements: &mut Vec<Stmt>, label: usize, mut body: Vec<Stmt>) {
+    if body.last() == Some(&Stmt::Continue(label)) {
+        body.pop();
+    }
+    let kind = match body.as_slice() {
+        [
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            },
+            ..,
+        ] if *then == [Stmt::Break(label)] && otherwise.is_empty() => {
+            let condition = negate(condition.clone());
+            body.remove(0);
+            LoopKind::While(condition)
+        }
+        [
+            rest @ ..,
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            },
+            Stmt::Break(other),
+        ] if *other == label
+            && *then == [Stmt::Continue(label)]
+            && otherwise.is_empty()
+            && !contains_continue(rest, label) =>
+        {
+            let condition = condition.clone();
+            body.truncate(body.len() - 2);
+            LoopKind::DoWhile(condition)
+        }
+        [
+            rest @ ..,
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            },
+        ] if *then == [Stmt::Break(label)]
+            && otherwise.is_empty()
+            && !contains_continue(rest, label) =>
+        {
+            let condition = negate(condition.clone());
+            body.pop();
+            LoopKind::DoWhile(condition)
+        }
+        [
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            },
+            Stmt::Break(other),
+        ] if *other == label
+            && then.last() == Some(&Stmt::Continue(label))
+            && otherwise.is_empty() =>
+        {
+            let condition = condition.clone();
+            let mut then = then.clone();
+            then.pop();
+            body = then;
+            LoopKind::While(condition)
+        }
+        _ => LoopKind::Infinite,
+    };
+
+    // `i = 0; while (i < n) { ...; i++; }` is a for loop, unless it continues, which would skip the update.
+    if let LoopKind::While(condition) = &kind
+        && let Some(init) = statements.last()
+        && let Some(local) = updated_local(init)
+        && matches!(init, Stmt::Expr(Expr::Assign(..)))
+        && condition.reads_local(local)
+        && body.last().and_then(updated_local) == Some(local)
+        && !contains_continue(&body, label)
+    {
+        let init = statements.pop().expect("checked above");
+        let update = body.pop().expect("checked above");
+        statements.push(Stmt::Loop {
+            label,
+            kind: LoopKind::For {
+                init: Box::new(init),
+                condition: condition.clone(),
+                update: Box::new(update),
+            },
+            body,
+        });
+        return;
+    }
+    statements.push(Stmt::Loop { label, kind, body });
+}
+
+/// Rewrites the structured statements to read like source code.
+fn simplify(statements: Vec<Stmt>) -> Vec<Stmt> {
+    let mut simplified = Vec::with_capacity(statements.len());
+    for statement in statements {
+        match statement {
+            Stmt::If {
+                condition,
+                then,
+                otherwise,
+            } => push_if(
+                &mut simplified,
+                condition,
+                simplify(then),
+                simplify(otherwise),
+            ),
+            Stmt::Loop {
+                label,
+                kind: LoopKind::Infinite,
+                body,
+            } => push_loop(&mut simplified, label, simplify(body)),
+            Stmt::Loop { label, kind, body } => simplified.push(Stmt::Loop {
+                label,
+                kind,
+                body: simplify(body),
+            }),
+            Stmt::Switch {
+                label,
+                value,
+                cases,
+            } => simplified.push(Stmt::Switch {
+                label,
+                value,
+                cases: cases
+                    .into_iter()
+                    .map(|(keys, body)| (keys, simplify(body)))
+                    .collect(),
+            }),
+            Stmt::Try {
+                body,
+                catches,
+                finally,
+            } => simplified.push(Stmt::Try {
+                body: simplify(body),
+                catches: catches
+                    .into_iter()
+                    .map(|catch| Catch {
+                        body: simplify(catch.body),
+                        ..catch
+                    })
+                    .collect(),
+                finally: simplify(finally),
+            }),
+            statement => simplified.push(statement),
+        }
+    }
+    remove_finally_exits(&mut simplified);
+    simplified
+}
+
+/// Removes the copy of the `finally` statements that `javac` puts after the protected range for leaving a `try`
+/// normally, and moves a `return` of the value that the `try` stores before it back into the `try`.
+fn remove_finally_exits(statements: &mut Vec<Stmt>) {
+    let mut index = 0;
+    while index < statements.len() {
+        let after = index + 1;
+        let (copy, returns) = match &statements[index] {
+            Stmt::Try {
+                body,
+                catches,
+                finally,
+            } if !finally.is_empty()
+                && !ends_with_jump(body)
+                && statements.get(after..after + finally.len()) == Some(finally.as_slice()) =>
+            {
+                let returned = match statements.get(after + finally.len()) {
+                    Some(Stmt::Return(Some(Expr::Local { name, .. }))) => Some(name),
+                    _ => None,
+                };
+                let returns = catches.iter().all(|catch| ends_with_jump(&catch.body))
+                    && matches!(
+                        body.last(),
+                        Some(Stmt::Expr(Expr::Assign(target, _)))
+                            if matches!(&**target, Expr::Local { name, .. } if Some(name) == returned)
+                    );
+                (finally.len(), returns)
+            }
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+        statements.drain(after..after + copy);
+        if returns {
+            statements.remove(after);
+            if let Stmt::Try { body, .. } = &mut statements[index]
+                && let Some(Stmt::Expr(Expr::Assign(_, value))) = body.pop()
+            {
+                body.push(Stmt::Return(Some(*value)));
+            }
+        }
+        index += 1;
+    }
+}
@@ -0,0 +1,361 @@
+//! Dependency analysis in the manner of `jdeps`: the classes that each class mentions, from its constant pool,
+//! descriptors, signatures and annotations, grouped into graphs of classes, packages or archives.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::result::Result;
+
+use log::error;
+
+use crate::archive::{ArchiveError, JmodSection};
+use crate::attributes::Attribute;
+use crate::class_path::{ClassPath, ClassPathEntry, ClassPathSource};
+use crate::constant_pool::{ConstantPool, ConstantPoolEntry};
+use crate::descriptor::{add_class_name, add_descriptor, signature_class_names};
+use crate::errors::ClassFileError;
+use crate::read_class_data_with_options;
+use crate::types::ClassFile;
+
+mod annotations;
+mod graph;
+mod types;
+
+pub use graph::DependencyGraph;
+pub use types::{ClassDependencies, DependencyFilter, JdkInternalDependency, Level, NOT_FOUND};
+
+/// Packages that are internal to the JDK whether or not the JDK is on the class path.
+const INTERNAL_PACKAGE_PREFIXES: [&str; 2] = ["sun/", "jdk/internal/"];
+
+fn add_attributes(
+    class_names: &mut BTreeSet<String>,
+    attributes: &[Attribute],
+    constant_pool: &ConstantPool,
+) -> Result<(), ClassFileError> {
+    for attribute in attributes {
+        match attribute {
+            Attribute::Signature(idx) => {
+                class_names.extend(signature_class_names(constant_pool.string_entry(*idx)?)?);
+            }
+            Attribute::LocalVariableTable(variables) => {
+                for variable in variables {
+                    add_descriptor(
+                        class_names,
+                        constant_pool.string_entry(variable.descriptor_idx)?,
+                    )?;
+                }
+            }
+            Attribute::LocalVariableTypeTable(variables) => {
+                for variable in variables {
+                    let signature = constant_pool.string_entry(variable.descriptor_idx)?;
+                    class_names.extend(signature_class_names(signature)?);
+                }
+            }
+            Attribute::Code(code) => add_attributes(class_names, &code.attributes, constant_pool)?,
+            Attribute::Unknown { name_idx, info } => {
+                let name = constant_pool.string_entry(*name_idx)?;
+                for descriptor in annotations::annotation_descriptors(name, info, constant_pool)? {
+                    // Class values may be `V` for `void.class`.
+                    if descriptor != "V" {
+                        add_descriptor(class_names, descriptor)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The names of all classes that a class refers to, other than itself, sorted: the classes of its constant pool, the
+/// types in the descriptors and signatures of its members and local variables, and the types of its annotations and
+/// their enum and class values. Arrays count as their element types.
+pub fn class_dependencies(class_file: &ClassFile) -> Result<BTreeSet<String>, ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    let mut class_names = BTreeSet::new();
+    for entry in constant_pool.items() {
+        match entry {
+            ConstantPoolEntry::ClassRef(name_idx) => {
+                add_class_name(&mut class_names, constant_pool.string_entry(*name_idx)?)?;
+            }
+            ConstantPoolEntry::NameTypeDescriptor(name_and_type) => {
+                let descriptor = constant_pool.string_entry(name_and_type.type_descriptor_ref)?;
+                add_descriptor(&mut class_names, descriptor)?;
+            }
+            ConstantPoolEntry::MethodType(descriptor_idx) => {
+                add_descriptor(
+                    &mut class_names,
+                    constant_pool.string_entry(*descriptor_idx)?,
+                )?;
+            }
+            _ => {}
+        }
+    }
+    for field in &class_file.fields {
+        add_descriptor(
+            &mut class_names,
+            constant_pool.string_entry(field.descriptor_idx)?,
+        )?;
+        add_attributes(&mut class_names, &field.attributes, constant_pool)?;
+    }
+    for method in &class_file.methods {
+        add_descriptor(
+            &mut class_names,
+            constant_pool.string_entry(method.descriptor_idx)?,
+        )?;
+        add_attributes(&mut class_names, &method.attributes, constant_pool)?;
+    }
+    add_attributes(&mut class_names, &class_file.attributes, constant_pool)?;
+    class_names.remove(class_file.this_class_name()?);
+    Ok(class_names)
+}
+
+/// The package of a class in its internal form, e.g. `java/lang` for `java/lang/String`.
+pub fn package_name(class_name: &str) -> &str {
+    class_name
+        .rsplit_once('/')
+        .map_or("", |(package, _)| package)
+}
+
+/// Where a class was found on a class path.
+#[derive(Clone, Debug)]
+struct Origin {
+    archive: String,
+    /// The module of the class if it comes from the JDK.
+    module: Option<String>,
+}
+
+fn file_name(location: &Path) -> String {
+    location.file_name().map_or_else(
+        || location.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// The origin of a class in a class path entry: the module for the JDK and the file name for jars and directories.
+fn origin(entry: &ClassPathEntry, class_name: &str) -> Result<Origin, ArchiveError> {
+    let module = match &entry.source {
+        ClassPathSource::Image(image) => image.module_of(&format!("{class_name}.class"))?,
+        ClassPathSource::Jmod(_) => entry
+            .location
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned()),
+        ClassPathSource::Directory(_) | ClassPathSource::Jar(_) => None,
+    };
+    Ok(Origin {
+        archive: module.clone().unwrap_or_else(|| file_name(&entry.location)),
+        module,
+    })
+}
+
+/// Whether the packages of the JDK modules on a class path are exported to all modules, by package.
+fn jdk_exports(class_path: &ClassPath) -> Result<HashMap<String, bool>, ArchiveError> {
+    let mut module_infos = Vec::new();
+    for entry in class_path.entries() {
+        match &entry.source {
+            ClassPathSource::Image(image) => {
+                for module in image.modules()? {
+                    if let Some(location) =
+                        image.find_location(&format!("/{module}/module-info.class"))?
+                    {
+                        module_infos.push(image.read(&location)?);
+                    }
+                }
+            }
+            ClassPathSource::Jmod(jmod_file) => {
+                module_infos.push(jmod_file.read(JmodSection::Classes, "module-info.class")?);
+            }
+            ClassPathSource::Directory(_) | ClassPathSource::Jar(_) => {}
+        }
+    }
+
+    let mut exports = HashMap::new();
+    for data in module_infos {
+        let descriptor = read_class_data_with_options(&data, class_path.options())
+            .and_then(|class_file| class_file.module_descriptor())
+            .map_err(|error| ArchiveError::InvalidClass("module-info.class".to_string(), error))?;
+        let Some(descriptor) = descriptor else {
+            continue;
+        };
+        for package in &descriptor.packages {
+            exports.entry(package.clone()).or_insert(false);
+        }
+        for export in descriptor
+            .exports
+            .iter()
+            .filter(|export| export.targets.is_empty())
+        {
+            exports.insert(export.package.clone(), true);
+        }
+    }
+    Ok(exports)
+}
+
+/// The dependencies of the classes of one class path, resolved against another one.
+#[derive(Debug)]
+pub struct DependencyAnalysis {
+    classes: Vec<ClassDependencies>,
+    /// Where each dependency was found, or `None` if it is on neither class path.
+    origins: BTreeMap<String, Option<Origin>>,
+    /// Whether the packages of the JDK are exported to all modules, by package.
+    exports: HashMap<String, bool>,
+    invalid_classes: Vec<String>,
+}
+
+impl DependencyAnalysis {
+    /// Analyses all classes on the class path `roots`. Their dependencies are looked up on `roots` and then on
+    /// `class_path`, which usually holds the libraries and the JDK. Classes of jimages and jmod files count as the
+    /// JDK. Classes that fail to parse are logged and left out, see [`DependencyAnalysis::invalid_classes`].
+    pub fn analyze(
+        roots: &ClassPath,
+        class_path: &ClassPath,
+    ) -> Result<DependencyAnalysis, ArchiveError> {
+        let mut exports = jdk_exports(class_path)?;
+        exports.extend(jdk_exports(roots)?);
+        let mut analysis = DependencyAnalysis {
+            classes: Vec::new(),
+            origins: BTreeMap::new(),
+            exports,
+            invalid_classes: Vec::new(),
+        };
+
+        for class_name in roots.class_names() {
+            let Some((entry, data)) = roots.locate_class_data(class_name)? else {
+                continue;
+            };
+            let dependencies = match read_class_data_with_options(&data, roots.options())
+                .and_then(|class_file| class_dependencies(&class_file))
+            {
+                Ok(dependencies) => dependencies,
+                Err(error) => {
+                    error!("can't analyse class; class_name={class_name}, error={error}");
+                    analysis.invalid_classes.push(class_name.to_string());
+                    continue;
+                }
+            };
+            // Each dependency is located once, however many classes depend on it.
+            for dependency in &dependencies {
+                if analysis.origins.contains_key(dependency) {
+                    continue;
+                }
+                let entry = match roots.locate(dependency)? {
+                    Some(entry) => Some(entry),
+                    None => class_path.locate(dependency)?,
+                };
+                let origin = entry.map(|entry| origin(entry, dependency)).transpose()?;
+                analysis.origins.insert(dependency.clone(), origin);
+            }
+            analysis.classes.push(ClassDependencies {
+                class_name: class_name.to_string(),
+                archive: origin(entry, class_name)?.archive,
+                dependencies,
+            });
+        }
+        Ok(analysis)
+    }
+
+    /// The analysed classes with their dependencies, sorted by name.
+    pub fn classes(&self) -> &[ClassDependencies] {
+        &self.classes
+    }
+
+    /// The classes of the roots that couldn't be parsed.
+    pub fn invalid_classes(&self) -> &[String] {
+        &self.invalid_classes
+    }
+
+    /// The jar, directory or module a dependency was found in, or `None` if it wasn't found.
+    pub fn archive_of(&self, class_name: &str) -> Option<&str> {
+        self.origins
+            .get(class_name)?
+            .as_ref()
+            .map(|origin| origin.archive.as_str())
+    }
+
+    /// The dependencies that aren't on either class path, sorted.
+    pub fn missing_classes(&self) -> Vec<&str> {
+        self.origins
+            .iter()
+            .filter(|(_, origin)| origin.is_none())
+            .map(|(class_name, _)| class_name.as_str())
+            .collect()
+    }
+
+    fn is_jdk_internal(&self, class_name: &str) -> bool {
+        let package = package_name(class_name);
+        match self.origins.get(class_name) {
+            Some(Some(Origin {
+                module: Some(_), ..
+            })) => self.exports.get(package) == Some(&false),
+            Some(None) => INTERNAL_PACKAGE_PREFIXES
+                .iter()
+                .any(|prefix| class_name.starts_with(prefix)),
+            _ => false,
+        }
+    }
+
+    /// The dependencies of the included classes on classes of the JDK that their modules don't export, or that are in
+    /// packages that are always internal, such as `sun/misc`.
+    pub fn jdk_internal_dependencies(
+        &self,
+        filter: &DependencyFilter,
+    ) -> Vec<JdkInternalDependency> {
+        let mut dependencies = Vec::new();
+        for class in self
+            .classes
+            .iter()
+            .filter(|class| filter.includes_source(&class.class_name))
+        {
+            for target in class
+                .dependencies
+                .iter()
+                .filter(|target| self.is_jdk_internal(target))
+            {
+                dependencies.push(JdkInternalDependency {
+                    class_name: class.class_name.clone(),
+                    archive: class.archive.clone(),
+                    target: target.clone(),
+                    module: self
+                        .origins
+                        .get(target)
+                        .and_then(|origin| origin.as_ref()?.module.clone()),
+                });
+            }
+        }
+        dependencies
+    }
+
+    fn node<'a>(level: Level, class_name: &'a str, archive: &'a str) -> &'a str {
+        match level {
+            Level::Class => class_name,
+            Level::Package => package_name(class_name),
+            Level::Archive => archive,
+        }
+    }
+
+    /// The graph of the dependencies of the included classes at a level. Dependencies that aren't found belong to the
+    /// archive [`NOT_FOUND`].
+    pub fn graph(&self, level: Level, filter: &DependencyFilter) -> DependencyGraph {
+        let mut graph = DependencyGraph::new(level);
+        for class in self
+            .classes
+            .iter()
+            .filter(|class| filter.includes_source(&class.class_name))
+        {
+            let source = DependencyAnalysis::node(level, &class.class_name, &class.archive);
+            graph.add_node(source, &class.archive);
+            for target in &class.dependencies {
+                let origin = self.origins.get(target).and_then(Option::as_ref);
+                if filter.excludes_target(target)
+                    || (filter.exclude_jdk && origin.is_some_and(|origin| origin.module.is_some()))
+                {
+                    continue;
+                }
+                let archive = origin.map_or(NOT_FOUND, |origin| origin.archive.as_str());
+                let target = DependencyAnalysis::node(level, target, archive);
+                graph.add_node(target, archive);
+                graph.add_edge(source, target);
+            }
+        }
+        graph
+    }
+}
@@ -0,0 +1,30 @@
+//! The types that annotations mention, read from the attributes that hold them (JVMS 4.7.16 to 4.7.22), which are kept
+//! as unknown attributes.
+
+use std::result::Result;
+
+use crate::constant_pool::ConstantPool;
+use crate::errors::ClassFileError;
+use crate::shared::annotations::{AnnotationIndex, annotation_indices};
+
+/// The field descriptors of the annotation types and of the enum and class values in an annotation attribute, e.g.
+/// `Ljava/lang/Deprecated;`, or none if the attribute doesn't hold annotations.
+pub(super) fn annotation_descriptors<'a>(
+    name: &str,
+    info: &[u8],
+    constant_pool: &'a ConstantPool,
+) -> Result<Vec<&'a str>, ClassFileError> {
+    let mut indices = Vec::new();
+    annotation_indices(name, &mut info.to_vec(), &mut |kind, index| {
+        if matches!(
+            kind,
+            AnnotationIndex::Type | AnnotationIndex::EnumType | AnnotationIndex::Class
+        ) {
+            indices.push(*index);
+        }
+    })?;
+    indices
+        .into_iter()
+        .map(|index| constant_pool.string_entry(index).map(String::as_str))
+        .collect()
+}
@@ -0,0 +1,179 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::dependencies::Level;
+
+/// A directed graph of classes, packages or archives and the ones they depend on.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DependencyGraph {
+    pub level: Level,
+    /// All nodes with the archive they come from; for archive graphs, the archive itself.
+    pub nodes: BTreeMap<String, String>,
+    /// The nodes each node depends on. Nodes without dependencies have no entry.
+    pub edges: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Finds the strongly connected components with Tarjan's algorithm.
+struct Components<'a> {
+    graph: &'a DependencyGraph,
+    index: BTreeMap<&'a str, usize>,
+    low_link: BTreeMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: BTreeSet<&'a str>,
+    components: Vec<Vec<String>>,
+}
+
+impl<'a> Components<'a> {
+    fn enter(&mut self, node: &'a str) -> (&'a str, Vec<&'a str>, usize) {
+        let index = self.index.len();
+        self.index.insert(node, index);
+        self.low_link.insert(node, index);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+        (node, self.graph.dependencies(node).collect(), 0)
+    }
+
+    /// Visits the nodes reachable from a node depth first, keeping the path on a stack of its own rather than
+    /// recursing, as paths through class graphs can be long.
+    fn visit(&mut self, root: &'a str) {
+        let mut path = vec![self.enter(root)];
+        while let Some((node, targets, position)) = path.last_mut() {
+            let node = *node;
+            if let Some(&target) = targets.get(*position) {
+                *position += 1;
+                if !self.index.contains_key(target) {
+                    let next = self.enter(target);
+                    path.push(next);
+                } else if self.on_stack.contains(target) {
+                    let low_link = self.low_link[node].min(self.index[target]);
+                    self.low_link.insert(node, low_link);
+                }
+                continue;
+            }
+
+            path.pop();
+            if let Some((parent, _, _)) = path.last() {
+                let low_link = self.low_link[parent].min(self.low_link[node]);
+                self.low_link.insert(parent, low_link);
+            }
+            if self.low_link[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort();
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('/', ".").replace('"', "\\\""))
+}
+
+impl DependencyGraph {
+    pub fn new(level: Level) -> DependencyGraph {
+        DependencyGraph {
+            level,
+            nodes: BTreeMap::new(),
+            edges: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a node, unless there is one already.
+    pub fn add_node(&mut self, node: &str, archive: &str) {
+        if !self.nodes.contains_key(node) {
+            self.nodes.insert(node.to_string(), archive.to_string());
+        }
+    }
+
+    /// Adds a dependency between two nodes, which must have been added. Dependencies of a node on itself are left out.
+    pub fn add_edge(&mut self, source: &str, target: &str) {
+        if source != target {
+            self.edges
+                .entry(source.to_string())
+                .or_default()
+                .insert(target.to_string());
+        }
+    }
+
+    /// The nodes that a node depends on, sorted.
+    pub fn dependencies(&self, node: &str) -> impl Iterator<Item = &str> {
+        self.edges
+            .get(node)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// The nodes that depend on a node, sorted.
+    pub fn dependents(&self, node: &str) -> Vec<&str> {
+        self.edges
+            .iter()
+            .filter(|(_, targets)| targets.contains(node))
+            .map(|(source, _)| source.as_str())
+            .collect()
+    }
+
+    /// The groups of nodes that depend on each other, directly or through other nodes, each sorted and the groups
+    /// sorted by their first node.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut components = Components {
+            graph: self,
+            index: BTreeMap::new(),
+            low_link: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        };
+        for node in self.nodes.keys() {
+            if !components.index.contains_key(node.as_str()) {
+                components.visit(node);
+            }
+        }
+        let mut cycles: Vec<Vec<String>> = components
+            .components
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// The graph in the DOT language of Graphviz, with the names of classes and packages in their Java form. Below the
+    /// archive level, the nodes of each archive are grouped in a cluster.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+        if self.level != Level::Archive {
+            let mut archives: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            for (node, archive) in &self.nodes {
+                archives.entry(archive).or_default().push(node);
+            }
+            for (idx, (archive, nodes)) in archives.into_iter().enumerate() {
+                let _ = writeln!(out, "    subgraph cluster_{idx} {{");
+                let _ = writeln!(out, "        label = {};", quote(archive));
+                for node in nodes {
+                    let _ = writeln!(out, "        {};", quote(node));
+                }
+                let _ = writeln!(out, "    }}");
+            }
+        } else {
+            for node in self.nodes.keys() {
+                let _ = writeln!(out, "    {};", quote(node));
+            }
+        }
+        for (source, targets) in &self.edges {
+            for target in targets {
+                let _ = writeln!(out, "    {} -> {};", quote(source), quote(target));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeSet;
+
+/// The node a dependency graph has for a class that isn't on the class path, at the archive level.
+pub const NOT_FOUND: &str = "not found";
+
+/// What the nodes of a dependency graph stand for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Level {
+    Class,
+    Package,
+    /// Jars, directories and modules, like the summary of `jdeps`.
+    Archive,
+}
+
+/// Which dependencies a graph includes. By default it has all of them.
+#[derive(Clone, Debug, Default)]
+pub struct DependencyFilter {
+    /// Prefixes of the classes whose dependencies are included, e.g. `com/acme/`, or all classes if empty.
+    pub include: Vec<String>,
+    /// Prefixes of the classes that are left out as dependencies, e.g. `java/`.
+    pub exclude: Vec<String>,
+    /// Leaves out dependencies on the classes of the JDK, i.e. the classes of jimages and jmod files.
+    pub exclude_jdk: bool,
+}
+
+impl DependencyFilter {
+    pub(super) fn includes_source(&self, class_name: &str) -> bool {
+        self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|prefix| class_name.starts_with(prefix))
+    }
+
+    pub(super) fn excludes_target(&self, class_name: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|prefix| class_name.starts_with(prefix))
+    }
+}
+
+/// The dependencies of a class that was analysed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ClassDependencies {
+    pub class_name: String,
+    /// The jar, directory or module the class comes from.
+    pub archive: String,
+    /// The names of the classes that the class mentions, sorted.
+    pub dependencies: BTreeSet<String>,
+}
+
+/// A dependency on a class of the JDK that its module doesn't export, which may change or go away in any release.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct JdkInternalDependency {
+    pub class_name: String,
+    pub archive: String,
+    pub target: String,
+    /// The module of the target, or `None` if the JDK isn't on the class path and the target is known to be
+    /// internal by its package, such as `sun/misc`.
+    pub module: Option<String>,
+}
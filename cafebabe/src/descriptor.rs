@@ -0,0 +1,403 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::result::Result;
+use std::str::Chars;
+
+use crate::errors::ClassFileError;
+use log::error;
+
+/// The type of a field, parameter, local variable or value, as described by a field descriptor.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FieldType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+    /// An instance of a class, holding the class name in its internal form.
+    Object(String),
+    Array(Box<FieldType>),
+}
+
+/// The parameter types and return type of a method, as described by a method descriptor.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MethodDescriptor {
+    pub parameters: Vec<FieldType>,
+    /// The return type, or `None` if the method returns `void`.
+    pub return_type: Option<FieldType>,
+}
+
+fn read_field_type(descriptor: &str, chars: &mut Chars) -> Result<FieldType, ClassFileError> {
+    match chars.next() {
+        Some('B') => Ok(FieldType::Byte),
+        Some('C') => Ok(FieldType::Char),
+        Some('D') => Ok(FieldType::Double),
+        Some('F') => Ok(FieldType::Float),
+        Some('I') => Ok(FieldType::Int),
+        Some('J') => Ok(FieldType::Long),
+        Some('S') => Ok(FieldType::Short),
+        Some('Z') => Ok(FieldType::Boolean),
+        Some('L') => {
+            let Some((class_name, _)) = chars.as_str().split_once(';') else {
+                error!("Unterminated class name in descriptor {descriptor:?}");
+                return Err(ClassFileError::InvalidDescriptor);
+            };
+            if class_name.is_empty() {
+                error!("Missing class name in descriptor {descriptor:?}");
+                return Err(ClassFileError::InvalidDescriptor);
+            }
+            let class_name = class_name.to_string();
+            // Skip the class name and the terminating ';'.
+            for _ in 0..=class_name.chars().count() {
+                chars.next();
+            }
+            Ok(FieldType::Object(class_name))
+        }
+        Some('[') => Ok(FieldType::Array(Box::new(read_field_type(
+            descriptor, chars,
+        )?))),
+        other => {
+            error!("Unexpected {other:?} in descriptor {descriptor:?}");
+            Err(ClassFileError::InvalidDescriptor)
+        }
+    }
+}
+
+impl FieldType {
+    /// Parses a field descriptor, e.g. `[Ljava/lang/String;`.
+    pub fn parse(descriptor: &str) -> Result<FieldType, ClassFileError> {
+        let mut chars = descriptor.chars();
+        let field_type = read_field_type(descriptor, &mut chars)?;
+        match chars.next() {
+            None => Ok(field_type),
+            Some(_) => {
+                error!("Trailing characters in field descriptor {descriptor:?}");
+                Err(ClassFileError::InvalidDescriptor)
+            }
+        }
+    }
+
+    /// The name of this type as it would appear in Java source code, e.g. `java.lang.String[]`.
+    pub fn java_name(&self) -> String {
+        match self {
+            FieldType::Byte => "byte".to_string(),
+            FieldType::Char => "char".to_string(),
+            FieldType::Double => "double".to_string(),
+            FieldType::Float => "float".to_string(),
+            FieldType::Int => "int".to_string(),
+            FieldType::Long => "long".to_string(),
+            FieldType::Short => "short".to_string(),
+            FieldType::Boolean => "boolean".to_string(),
+            FieldType::Object(class_name) => class_name.replace('/', "."),
+            FieldType::Array(component) => format!("{}[]", component.java_name()),
+        }
+    }
+
+    /// The number of local variable or operand stack slots that a value of this type occupies.
+    pub fn slots(&self) -> u16 {
+        match self {
+            FieldType::Long | FieldType::Double => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    /// Formats this type as a field descriptor.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Byte => write!(f, "B"),
+            FieldType::Char => write!(f, "C"),
+            FieldType::Double => write!(f, "D"),
+            FieldType::Float => write!(f, "F"),
+            FieldType::Int => write!(f, "I"),
+            FieldType::Long => write!(f, "J"),
+            FieldType::Short => write!(f, "S"),
+            FieldType::Boolean => write!(f, "Z"),
+            FieldType::Object(class_name) => write!(f, "L{class_name};"),
+            FieldType::Array(component) => write!(f, "[{component}"),
+        }
+    }
+}
+
+impl MethodDescriptor {
+    /// Parses a method descriptor, e.g. `(ILjava/lang/String;)V`.
+    pub fn parse(descriptor: &str) -> Result<MethodDescriptor, ClassFileError> {
+        let Some(parameters_and_return) = descriptor.strip_prefix('(') else {
+            error!("Method descriptor {descriptor:?} does not start with '('");
+            return Err(ClassFileError::InvalidDescriptor);
+        };
+        let Some((parameters, return_type)) = parameters_and_return.split_once(')') else {
+            error!("Method descriptor {descriptor:?} does not contain ')'");
+            return Err(ClassFileError::InvalidDescriptor);
+        };
+
+        let mut chars = parameters.chars();
+        let mut parameter_types = Vec::new();
+        while !chars.as_str().is_empty() {
+            parameter_types.push(read_field_type(descriptor, &mut chars)?);
+        }
+
+        let return_type = match return_type {
+            "V" => None,
+            _ => Some(FieldType::parse(return_type)?),
+        };
+
+        Ok(MethodDescriptor {
+            parameters: parameter_types,
+            return_type,
+        })
+    }
+
+    /// The number of local variable slots taken by the parameters, not counting `this`.
+    pub fn parameter_slots(&self) -> u16 {
+        self.parameters.iter().map(FieldType::slots).sum()
+    }
+}
+
+impl fmt::Display for MethodDescriptor {
+    /// Formats this method as a method descriptor.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for parameter in &self.parameters {
+            write!(f, "{parameter}")?;
+        }
+        match &self.return_type {
+            Some(return_type) => write!(f, "){return_type}"),
+            None => write!(f, ")V"),
+        }
+    }
+}
+
+/// Walks a generic signature (JVMS 4.7.9.1) and copies it, with the name of each class replaced by the callback.
+struct SignatureWalker<'a, 'b> {
+    signature: &'a str,
+    rest: &'a str,
+    out: String,
+    class_name: &'b mut dyn FnMut(&str) -> String,
+}
+
+impl<'a> SignatureWalker<'a, '_> {
+    fn invalid(&self) -> ClassFileError {
+        error!(
+            "Unexpected {:?} in signature {:?}",
+            self.rest.chars().next(),
+            self.signature
+        );
+        ClassFileError::InvalidSignature
+    }
+
+    fn next_is(&self, c: char) -> bool {
+        self.rest.starts_with(c)
+    }
+
+    /// Copies the next character, which must be `c`.
+    fn expect(&mut self, c: char) -> Result<(), ClassFileError> {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                self.out.push(c);
+                Ok(())
+            }
+            None => Err(self.invalid()),
+        }
+    }
+
+    /// Reads up to, but not including, the first of the delimiters, without copying it.
+    fn identifier(&mut self, delimiters: &[char]) -> Result<&'a str, ClassFileError> {
+        match self.rest.find(delimiters) {
+            Some(0) | None => Err(self.invalid()),
+            Some(end) => {
+                let (identifier, rest) = self.rest.split_at(end);
+                self.rest = rest;
+                Ok(identifier)
+            }
+        }
+    }
+
+    fn type_parameters(&mut self) -> Result<(), ClassFileError> {
+        if !self.next_is('<') {
+            return Ok(());
+        }
+        self.expect('<')?;
+        while !self.next_is('>') {
+            let name = self.identifier(&[':'])?;
+            self.out.push_str(name);
+            self.expect(':')?;
+            if !self.next_is(':') {
+                self.reference_type()?;
+            }
+            while self.next_is(':') {
+                self.expect(':')?;
+                self.reference_type()?;
+            }
+        }
+        self.expect('>')
+    }
+
+    fn type_arguments(&mut self) -> Result<(), ClassFileError> {
+        self.expect('<')?;
+        while !self.next_is('>') {
+            if self.next_is('*') {
+                self.expect('*')?;
+                continue;
+            }
+            if self.next_is('+') {
+                self.expect('+')?;
+            } else if self.next_is('-') {
+                self.expect('-')?;
+            }
+            self.reference_type()?;
+        }
+        self.expect('>')
+    }
+
+    /// Hands the class name to the callback once the class type ends, e.g. `p/Outer$Inner` for
+    /// `Lp/Outer<TT;>.Inner;`. The new name is written with the same nesting as far as it still has inner classes,
+    /// and the type arguments of outer classes that it no longer nests in are left out.
+    fn class_type(&mut self) -> Result<(), ClassFileError> {
+        self.expect('L')?;
+        let mut class_name = self.identifier(&['<', '.', ';'])?.to_string();
+        let mut type_arguments = Vec::new();
+        loop {
+            let start = self.out.len();
+            if self.next_is('<') {
+                self.type_arguments()?;
+            }
+            type_arguments.push(self.out.split_off(start));
+            if !self.next_is('.') {
+                break;
+            }
+            self.rest = &self.rest[1..];
+            let inner_name = self.identifier(&['<', '.', ';'])?;
+            class_name = format!("{class_name}${inner_name}");
+        }
+        let new_name = (self.class_name)(&class_name);
+        let mut names: Vec<&str> = new_name.rsplitn(type_arguments.len(), '$').collect();
+        names.reverse();
+        let nested_arguments = &type_arguments[type_arguments.len() - names.len()..];
+        for (position, (name, arguments)) in names.iter().zip(nested_arguments).enumerate() {
+            if position > 0 {
+                self.out.push('.');
+            }
+            self.out.push_str(name);
+            self.out.push_str(arguments);
+        }
+        self.expect(';')
+    }
+
+    fn reference_type(&mut self) -> Result<(), ClassFileError> {
+        match self.rest.chars().next() {
+            Some('L') => self.class_type(),
+            Some('T') => {
+                self.expect('T')?;
+                let name = self.identifier(&[';'])?;
+                self.out.push_str(name);
+                self.expect(';')
+            }
+            Some('[') => {
+                self.expect('[')?;
+                self.java_type()
+            }
+            _ => Err(self.invalid()),
+        }
+    }
+
+    fn java_type(&mut self) -> Result<(), ClassFileError> {
+        match self.rest.chars().next() {
+            Some(c @ ('B' | 'C' | 'D' | 'F' | 'I' | 'J' | 'S' | 'Z' | 'V')) => self.expect(c),
+            _ => self.reference_type(),
+        }
+    }
+}
+
+/// Copies a class, method or field signature with the name of each class that it mentions replaced by `class_name`,
+/// which is called with the internal names in the order the classes end in the signature.
+pub(crate) fn map_signature_class_names(
+    signature: &str,
+    class_name: &mut dyn FnMut(&str) -> String,
+) -> Result<String, ClassFileError> {
+    let mut walker = SignatureWalker {
+        signature,
+        rest: signature,
+        out: String::with_capacity(signature.len()),
+        class_name,
+    };
+    walker.type_parameters()?;
+    if walker.next_is('(') {
+        walker.expect('(')?;
+        while !walker.next_is(')') {
+            walker.java_type()?;
+        }
+        walker.expect(')')?;
+        walker.java_type()?;
+        while walker.next_is('^') {
+            walker.expect('^')?;
+            walker.reference_type()?;
+        }
+    }
+    while !walker.rest.is_empty() {
+        walker.reference_type()?;
+    }
+    Ok(walker.out)
+}
+
+/// The names of the classes that a class, method or field signature mentions, in their internal form and in the order
+/// they appear, e.g. `java/util/List` and `java/lang/String` for `Ljava/util/List<Ljava/lang/String;>;`. Nested classes
+/// of parameterized classes are named with a `$`, e.g. `java/util/Map$Entry`.
+pub fn signature_class_names(signature: &str) -> Result<Vec<String>, ClassFileError> {
+    let mut class_names = Vec::new();
+    map_signature_class_names(signature, &mut |class_name| {
+        class_names.push(class_name.to_string());
+        class_name.to_string()
+    })?;
+    Ok(class_names)
+}
+
+fn add_field_type(class_names: &mut BTreeSet<String>, field_type: &FieldType) {
+    match field_type {
+        FieldType::Object(class_name) => {
+            class_names.insert(class_name.clone());
+        }
+        FieldType::Array(component) => add_field_type(class_names, component),
+        _ => {}
+    }
+}
+
+/// Adds the classes of a field or method descriptor.
+pub(crate) fn add_descriptor(
+    class_names: &mut BTreeSet<String>,
+    descriptor: &str,
+) -> Result<(), ClassFileError> {
+    if descriptor.starts_with('(') {
+        let method_descriptor = MethodDescriptor::parse(descriptor)?;
+        for field_type in method_descriptor
+            .parameters
+            .iter()
+            .chain(&method_descriptor.return_type)
+        {
+            add_field_type(class_names, field_type);
+        }
+    } else {
+        add_field_type(class_names, &FieldType::parse(descriptor)?);
+    }
+    Ok(())
+}
+
+/// Adds the class of a Class entry, which holds the descriptor of an array class for arrays.
+pub(crate) fn add_class_name(
+    class_names: &mut BTreeSet<String>,
+    class_name: &str,
+) -> Result<(), ClassFileError> {
+    if class_name.starts_with('[') {
+        add_descriptor(class_names, class_name)
+    } else {
+        class_names.insert(class_name.to_string());
+        Ok(())
+    }
+}
@@ -1,13 +1,47 @@
+use crate::format_check::Diagnostic;
+
 #[derive(Debug)]
 pub enum ClassFileError {
     Unknown,
 
     InvalidMagicNumber,
+    /// The class file ends before a structure that it declares, or a structure overruns the attribute it is in.
+    Truncated,
     MajorVersionTooLow,
     InvalidMinorVersion,
+    /// The class file depends on preview features, which the parse options don't allow.
+    PreviewNotAllowed,
     MajorVersionTooHigh,
     UnknownConstantPoolEntryTag,
     InvalidConstantPoolContent,
     UnexpectedConstantPoolType,
+    /// An index into the constant pool is 0 or beyond its end.
+    InvalidConstantPoolIndex,
     InvalidAccessFlags,
+    InvalidAttribute,
+    MissingBootstrapMethod,
+    InvalidDescriptor,
+    InvalidSignature,
+    InvalidCode,
+    /// The data of a `@kotlin.Metadata` annotation can't be decoded.
+    InvalidKotlinMetadata,
+    /// The class file breaks rules of JVMS 4.8, which are only checked in strict mode.
+    FormatViolations(Vec<Diagnostic>),
+}
+
+impl std::fmt::Display for ClassFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassFileError::FormatViolations(diagnostics) => {
+                write!(f, "FormatViolations:")?;
+                for diagnostic in diagnostics {
+                    write!(f, "\n  {diagnostic}")?;
+                }
+                Ok(())
+            }
+            _ => write!(f, "{self:?}"),
+        }
+    }
 }
+
+impl std::error::Error for ClassFileError {}
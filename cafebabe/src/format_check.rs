@@ -0,0 +1,1010 @@
+//! Format checking of class files, following section 4.8 of the JVMS: the rules about names, descriptors, access
+//! flags, constant pool entries and attributes that the JVM enforces when it loads a class, before it verifies the
+//! bytecode.
+
+mod types;
+
+use std::collections::HashSet;
+use std::result::Result;
+
+pub use self::types::{Diagnostic, Location};
+use crate::attributes::{self, Attribute, Code};
+use crate::constant_pool::ConstantPool;
+use crate::constant_pool::types::{ConstantPoolEntry, ReferenceKind};
+use crate::descriptor::{FieldType, MethodDescriptor};
+use crate::errors::ClassFileError;
+use crate::types::{AccessFlags, ClassFile, FieldAccessFlags, MethodAccessFlags};
+use crate::version::ParseOptions;
+
+const OBJECT: &str = "java/lang/Object";
+const STRING: &str = "java/lang/String";
+const MODULE_INFO: &str = "module-info";
+const INIT: &str = "<init>";
+const CLINIT: &str = "<clinit>";
+
+/// The attributes that may appear at most once in a class, field, method or Code attribute, see section 4.7.
+const UNIQUE_CLASS_ATTRIBUTES: [&str; 17] = [
+    "SourceFile",
+    "InnerClasses",
+    "EnclosingMethod",
+    "SourceDebugExtension",
+    "BootstrapMethods",
+    "Module",
+    "ModulePackages",
+    "ModuleMainClass",
+    "NestHost",
+    "NestMembers",
+    "Record",
+    "PermittedSubclasses",
+    "Signature",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
+const UNIQUE_FIELD_ATTRIBUTES: [&str; 6] = [
+    "ConstantValue",
+    "Signature",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
+const UNIQUE_METHOD_ATTRIBUTES: [&str; 11] = [
+    "Code",
+    "Exceptions",
+    "Signature",
+    "MethodParameters",
+    "AnnotationDefault",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleParameterAnnotations",
+    "RuntimeInvisibleParameterAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
+const UNIQUE_CODE_ATTRIBUTES: [&str; 3] = [
+    "StackMapTable",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+];
+
+/// A broken rule, as the section of the JVMS that states it and a message.
+type Problem = (&'static str, String);
+
+/// Checks a class file that was already read, or built in memory, against the rules of JVMS 4.8.
+pub fn check_class_file(class_file: &ClassFile) -> Vec<Diagnostic> {
+    let mut checker = Checker::new(class_file);
+    checker.check_constant_pool();
+    checker.check_class();
+    checker.check_fields();
+    checker.check_methods();
+    checker.diagnostics
+}
+
+/// Reads a class file and checks it against the rules of JVMS 4.8, including that no bytes follow its attributes.
+///
+/// Only the problems that keep the class file from being read at all are errors; the others are diagnostics.
+pub fn check_class_data(
+    data: &[u8],
+    options: &ParseOptions,
+) -> Result<Vec<Diagnostic>, ClassFileError> {
+    let (class_file, end_idx) = crate::read_class(data, options)?;
+    Ok(check(&class_file, data, end_idx))
+}
+
+/// Checks a class file that was read from `data`, where its attributes end at `end_idx`.
+pub(crate) fn check(class_file: &ClassFile, data: &[u8], end_idx: usize) -> Vec<Diagnostic> {
+    let mut diagnostics = check_class_file(class_file);
+    if end_idx < data.len() {
+        diagnostics.push(Diagnostic {
+            location: Location::Offset { offset: end_idx },
+            section: "4.8",
+            message: format!(
+                "{} trailing bytes after the attributes of the class",
+                data.len() - end_idx
+            ),
+        });
+    }
+    diagnostics
+}
+
+/// Whether a name is a valid unqualified name of a field, method or local variable, see JVMS 4.2.2.
+fn is_unqualified_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['.', ';', '[', '/'])
+}
+
+fn is_method_name(name: &str) -> bool {
+    name == INIT || name == CLINIT || (is_unqualified_name(name) && !name.contains(['<', '>']))
+}
+
+/// Whether a name is a valid binary name of a class or interface in its internal form, see JVMS 4.2.1.
+fn is_class_name(name: &str) -> bool {
+    name.split('/').all(is_unqualified_name)
+}
+
+/// Parses a field descriptor and checks what its grammar doesn't: the class names in it must be valid and arrays
+/// may have at most 255 dimensions.
+fn parse_field_descriptor(descriptor: &str) -> Result<FieldType, String> {
+    let Ok(field_type) = FieldType::parse(descriptor) else {
+        return Err(format!("invalid field descriptor {descriptor:?}"));
+    };
+    check_field_type(&field_type)?;
+    Ok(field_type)
+}
+
+fn check_field_type(field_type: &FieldType) -> Result<(), String> {
+    let mut dimensions = 0;
+    let mut element = field_type;
+    while let FieldType::Array(component) = element {
+        dimensions += 1;
+        element = component;
+    }
+    if dimensions > 255 {
+        return Err(format!("{dimensions} array dimensions, more than 255"));
+    }
+    match element {
+        FieldType::Object(class_name) if !is_class_name(class_name) => {
+            Err(format!("invalid class name {class_name:?} in descriptor"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn parse_method_descriptor(descriptor: &str) -> Result<MethodDescriptor, String> {
+    let Ok(method_descriptor) = MethodDescriptor::parse(descriptor) else {
+        return Err(format!("invalid method descriptor {descriptor:?}"));
+    };
+    for field_type in method_descriptor
+        .parameters
+        .iter()
+        .chain(&method_descriptor.return_type)
+    {
+        check_field_type(field_type)?;
+    }
+    Ok(method_descriptor)
+}
+
+/// The names of a set of access flags, e.g. `ACC_FINAL ACC_ABSTRACT`.
+fn flag_names<T>(names: impl Iterator<Item = (&'static str, T)>) -> String {
+    names.map(|(name, _)| name).collect::<Vec<_>>().join(" ")
+}
+
+struct Checker<'a> {
+    class_file: &'a ClassFile,
+    constant_pool: &'a ConstantPool,
+    major: u16,
+    is_interface: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(class_file: &'a ClassFile) -> Checker<'a> {
+        Checker {
+            class_file,
+            constant_pool: &class_file.constant_pool,
+            major: class_file.version.major,
+            is_interface: class_file.access_flags.contains(AccessFlags::ACC_INTERFACE),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, location: &Location, section: &'static str, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            location: location.clone(),
+            section,
+            message: message.into(),
+        });
+    }
+
+    /// The entry at an index, or `None` if the index is out of range.
+    fn entry(&self, index: u16) -> Option<&'a ConstantPoolEntry> {
+        self.constant_pool.checked_entry(index).ok()
+    }
+
+    fn utf8(&self, index: u16) -> Option<&'a str> {
+        match self.entry(index) {
+            Some(ConstantPoolEntry::String(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn class_name(&self, index: u16) -> Option<&'a str> {
+        match self.entry(index) {
+            Some(ConstantPoolEntry::ClassRef(name_idx)) => self.utf8(*name_idx),
+            _ => None,
+        }
+    }
+
+    fn attribute_name(&self, attribute: &Attribute) -> Option<&'a str> {
+        match attribute {
+            Attribute::Unknown { name_idx, .. } => self.utf8(*name_idx),
+            _ => attributes::attribute_name(attribute),
+        }
+    }
+
+    fn check_attributes(&mut self, location: &Location, attributes: &[Attribute], unique: &[&str]) {
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        for attribute in attributes {
+            match self.attribute_name(attribute) {
+                Some(name) if unique.contains(&name) && !seen.insert(name) => {
+                    if !duplicates.contains(&name) {
+                        duplicates.push(name);
+                    }
+                }
+                Some(_) => {}
+                None => self.report(
+                    location,
+                    "4.7",
+                    "attribute_name_index must point to a Utf8 entry",
+                ),
+            }
+        }
+        for name in duplicates {
+            self.report(location, "4.7", format!("more than one {name} attribute"));
+        }
+    }
+
+    fn check_constant_pool(&mut self) {
+        let bootstrap_methods =
+            self.class_file
+                .attributes
+                .iter()
+                .find_map(|attribute| match attribute {
+                    Attribute::BootstrapMethods(methods) => Some(methods.len()),
+                    _ => None,
+                });
+        for (position, entry) in self.constant_pool.items().enumerate() {
+            if let Err((section, message)) = self.check_entry(entry, bootstrap_methods) {
+                let location = Location::ConstantPool {
+                    index: position as u16 + 1,
+                };
+                self.report(&location, section, message);
+            }
+        }
+    }
+
+    fn check_entry(
+        &self,
+        entry: &ConstantPoolEntry,
+        bootstrap_methods: Option<usize>,
+    ) -> Result<(), Problem> {
+        let (first_major, kind) = match entry {
+            ConstantPoolEntry::MethodHandle(_) => (51, "MethodHandle"),
+            ConstantPoolEntry::MethodType(_) => (51, "MethodType"),
+            ConstantPoolEntry::InvokeDynamic(_) => (51, "InvokeDynamic"),
+            ConstantPoolEntry::Module(_) => (53, "Module"),
+            ConstantPoolEntry::Package(_) => (53, "Package"),
+            ConstantPoolEntry::Dynamic(_) => (55, "Dynamic"),
+            _ => (0, ""),
+        };
+        if self.major < first_major {
+            return Err((
+                "4.4",
+                format!("{kind} entries require major version {first_major}"),
+            ));
+        }
+
+        match entry {
+            ConstantPoolEntry::ClassRef(name_idx) => {
+                let Some(name) = self.utf8(*name_idx) else {
+                    return Err(("4.4.1", "name_index must point to a Utf8 entry".into()));
+                };
+                if name.starts_with('[') {
+                    parse_field_descriptor(name).map_err(|message| ("4.4.1", message))?;
+                } else if !is_class_name(name) {
+                    return Err(("4.4.1", format!("invalid class name {name:?}")));
+                }
+            }
+            ConstantPoolEntry::StringRef(value_idx) if self.utf8(*value_idx).is_none() => {
+                return Err(("4.4.3", "string_index must point to a Utf8 entry".into()));
+            }
+            ConstantPoolEntry::FieldRef(field_ref) => {
+                let (name, descriptor) =
+                    self.member_ref(field_ref.class_ref, field_ref.name_type_ref)?;
+                if self
+                    .class_name(field_ref.class_ref)
+                    .is_some_and(|class_name| class_name.starts_with('['))
+                {
+                    return Err(("4.4.2", "a field can't belong to an array type".into()));
+                }
+                if !is_unqualified_name(name) {
+                    return Err(("4.2.2", format!("invalid field name {name:?}")));
+                }
+                parse_field_descriptor(descriptor).map_err(|message| ("4.3.2", message))?;
+            }
+            ConstantPoolEntry::MethodRef(method_ref) => {
+                let (name, descriptor) =
+                    self.member_ref(method_ref.class_ref, method_ref.name_type_ref)?;
+                let method_descriptor =
+                    parse_method_descriptor(descriptor).map_err(|message| ("4.3.3", message))?;
+                if name.starts_with('<') {
+                    if name != INIT {
+                        return Err((
+                            "4.4.2",
+                            format!("method name {name:?} starts with '<' but is not <init>"),
+                        ));
+                    }
+                    if method_descriptor.return_type.is_some() {
+                        return Err(("4.4.2", "<init> must return void".into()));
+                    }
+                } else if !is_method_name(name) {
+                    return Err(("4.2.2", format!("invalid method name {name:?}")));
+                }
+            }
+            ConstantPoolEntry::InterfaceMethodRef(method_ref) => {
+                let (name, descriptor) =
+                    self.member_ref(method_ref.class_ref, method_ref.name_type_ref)?;
+                parse_method_descriptor(descriptor).map_err(|message| ("4.3.3", message))?;
+                if !is_method_name(name) || name.starts_with('<') {
+                    return Err(("4.2.2", format!("invalid interface method name {name:?}")));
+                }
+            }
+            ConstantPoolEntry::NameTypeDescriptor(name_type)
+                if self.utf8(name_type.name_ref).is_none()
+                    || self.utf8(name_type.type_descriptor_ref).is_none() =>
+            {
+                return Err((
+                    "4.4.6",
+                    "name_index and descriptor_index must point to Utf8 entries".into(),
+                ));
+            }
+            ConstantPoolEntry::MethodHandle(method_handle) => {
+                self.check_method_handle(
+                    method_handle.reference_kind,
+                    method_handle.reference_ref,
+                )?;
+            }
+            ConstantPoolEntry::MethodType(descriptor_idx) => {
+                let Some(descriptor) = self.utf8(*descriptor_idx) else {
+                    return Err((
+                        "4.4.9",
+                        "descriptor_index must point to a Utf8 entry".into(),
+                    ));
+                };
+                parse_method_descriptor(descriptor).map_err(|message| ("4.3.3", message))?;
+            }
+            ConstantPoolEntry::Dynamic(dynamic) | ConstantPoolEntry::InvokeDynamic(dynamic) => {
+                match bootstrap_methods {
+                    None => {
+                        return Err((
+                            "4.7.23",
+                            "the class has no BootstrapMethods attribute".into(),
+                        ));
+                    }
+                    Some(count) if usize::from(dynamic.bootstrap_method_attr_idx) >= count => {
+                        return Err((
+                            "4.4.10",
+                            format!(
+                                "bootstrap method {} is out of range, the class has {count}",
+                                dynamic.bootstrap_method_attr_idx
+                            ),
+                        ));
+                    }
+                    Some(_) => {}
+                }
+                let (name, descriptor) = self.name_and_type(dynamic.name_type_ref, "4.4.10")?;
+                if !is_unqualified_name(name) {
+                    return Err(("4.2.2", format!("invalid name {name:?}")));
+                }
+                if matches!(entry, ConstantPoolEntry::Dynamic(_)) {
+                    parse_field_descriptor(descriptor).map_err(|message| ("4.3.2", message))?;
+                } else {
+                    parse_method_descriptor(descriptor).map_err(|message| ("4.3.3", message))?;
+                }
+            }
+            ConstantPoolEntry::Module(name_idx) | ConstantPoolEntry::Package(name_idx) => {
+                let section = match entry {
+                    ConstantPoolEntry::Module(_) => "4.4.11",
+                    _ => "4.4.12",
+                };
+                let Some(name) = self.utf8(*name_idx) else {
+                    return Err((section, "name_index must point to a Utf8 entry".into()));
+                };
+                if !self
+                    .class_file
+                    .access_flags
+                    .contains(AccessFlags::ACC_MODULE)
+                {
+                    return Err((
+                        section,
+                        "only a module may refer to modules and packages".into(),
+                    ));
+                }
+                if matches!(entry, ConstantPoolEntry::Package(_)) && !is_class_name(name) {
+                    return Err(("4.2.3", format!("invalid package name {name:?}")));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Looks up the name and descriptor of a field or method reference, checking the entries it points to.
+    fn member_ref(
+        &self,
+        class_ref: u16,
+        name_type_ref: u16,
+    ) -> Result<(&'a str, &'a str), Problem> {
+        if self.class_name(class_ref).is_none() {
+            return Err((
+                "4.4.2",
+                format!("class_index #{class_ref} must point to a Class entry"),
+            ));
+        }
+        self.name_and_type(name_type_ref, "4.4.2")
+    }
+
+    fn name_and_type(
+        &self,
+        index: u16,
+        section: &'static str,
+    ) -> Result<(&'a str, &'a str), Problem> {
+        let Some(ConstantPoolEntry::NameTypeDescriptor(name_type)) = self.entry(index) else {
+            return Err((
+                section,
+                format!("name_and_type_index #{index} must point to a NameAndType entry"),
+            ));
+        };
+        match (
+            self.utf8(name_type.name_ref),
+            self.utf8(name_type.type_descriptor_ref),
+        ) {
+            (Some(name), Some(descriptor)) => Ok((name, descriptor)),
+            _ => Err((
+                "4.4.6",
+                format!("the NameAndType entry #{index} must point to Utf8 entries"),
+            )),
+        }
+    }
+
+    fn check_method_handle(&self, kind: ReferenceKind, reference_ref: u16) -> Result<(), Problem> {
+        let target = self.entry(reference_ref);
+        let (valid, name_type_ref) = match target {
+            Some(ConstantPoolEntry::FieldRef(field_ref)) => (
+                matches!(
+                    kind,
+                    ReferenceKind::GetField
+                        | ReferenceKind::GetStatic
+                        | ReferenceKind::PutField
+                        | ReferenceKind::PutStatic
+                ),
+                field_ref.name_type_ref,
+            ),
+            Some(ConstantPoolEntry::MethodRef(method_ref)) => (
+                matches!(
+                    kind,
+                    ReferenceKind::InvokeVirtual
+                        | ReferenceKind::NewInvokeSpecial
+                        | ReferenceKind::InvokeStatic
+                        | ReferenceKind::InvokeSpecial
+                ),
+                method_ref.name_type_ref,
+            ),
+            Some(ConstantPoolEntry::InterfaceMethodRef(method_ref)) => (
+                kind == ReferenceKind::InvokeInterface
+                    || (self.major >= 52
+                        && matches!(
+                            kind,
+                            ReferenceKind::InvokeStatic | ReferenceKind::InvokeSpecial
+                        )),
+                method_ref.name_type_ref,
+            ),
+            _ => (false, 0),
+        };
+        if !valid {
+            return Err((
+                "4.4.8",
+                format!("reference kind {kind:?} can't refer to #{reference_ref}"),
+            ));
+        }
+
+        // An invalid reference is reported for its own entry.
+        let Ok((name, _)) = self.name_and_type(name_type_ref, "4.4.8") else {
+            return Ok(());
+        };
+        match kind {
+            ReferenceKind::NewInvokeSpecial if name != INIT => Err((
+                "4.4.8",
+                format!("NewInvokeSpecial must refer to <init>, not {name:?}"),
+            )),
+            ReferenceKind::InvokeVirtual
+            | ReferenceKind::InvokeStatic
+            | ReferenceKind::InvokeSpecial
+            | ReferenceKind::InvokeInterface
+                if name == INIT || name == CLINIT =>
+            {
+                Err((
+                    "4.4.8",
+                    format!("reference kind {kind:?} can't refer to {name}"),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_class(&mut self) {
+        let location = Location::Class;
+        let flags = self.class_file.access_flags;
+        let class = &self.class_file.class;
+        let this_name = self.class_name(class.this_idx as u16);
+
+        if flags.contains(AccessFlags::ACC_MODULE) {
+            if flags != AccessFlags::ACC_MODULE {
+                self.report(
+                    &location,
+                    "4.1",
+                    format!(
+                        "a module must not also be {}",
+                        flag_names((flags - AccessFlags::ACC_MODULE).iter_names())
+                    ),
+                );
+            }
+            if self.major < 53 {
+                self.report(&location, "4.1", "ACC_MODULE requires major version 53");
+            }
+            if this_name != Some(MODULE_INFO) {
+                self.report(
+                    &location,
+                    "4.1",
+                    "the class of a module must be module-info",
+                );
+            }
+            if class.super_idx != 0
+                || !class.interfaces.is_empty()
+                || !self.class_file.fields.is_empty()
+                || !self.class_file.methods.is_empty()
+            {
+                self.report(
+                    &location,
+                    "4.1",
+                    "a module must not have a super class, interfaces, fields or methods",
+                );
+            }
+            self.check_attributes(
+                &location,
+                &self.class_file.attributes,
+                &UNIQUE_CLASS_ATTRIBUTES,
+            );
+            return;
+        }
+
+        if self.is_interface {
+            if !flags.contains(AccessFlags::ACC_ABSTRACT) {
+                self.report(&location, "4.1", "ACC_INTERFACE requires ACC_ABSTRACT");
+            }
+            let forbidden =
+                flags & (AccessFlags::ACC_FINAL | AccessFlags::ACC_SUPER | AccessFlags::ACC_ENUM);
+            if !forbidden.is_empty() {
+                self.report(
+                    &location,
+                    "4.1",
+                    format!(
+                        "an interface must not be {}",
+                        flag_names(forbidden.iter_names())
+                    ),
+                );
+            }
+        } else {
+            if flags.contains(AccessFlags::ACC_ANNOTATION) {
+                self.report(&location, "4.1", "ACC_ANNOTATION requires ACC_INTERFACE");
+            }
+            if flags.contains(AccessFlags::ACC_FINAL | AccessFlags::ACC_ABSTRACT) {
+                self.report(
+                    &location,
+                    "4.1",
+                    "a class must not be both ACC_FINAL and ACC_ABSTRACT",
+                );
+            }
+        }
+
+        match this_name {
+            Some(name) if !name.starts_with('[') => {}
+            _ => self.report(
+                &location,
+                "4.1",
+                "this_class must point to a Class entry for a class or interface",
+            ),
+        }
+
+        if class.super_idx == 0 {
+            if this_name != Some(OBJECT) {
+                self.report(&location, "4.1", "only java/lang/Object has no super class");
+            }
+        } else {
+            match self.class_name(class.super_idx as u16) {
+                None => self.report(&location, "4.1", "super_class must point to a Class entry"),
+                Some(name) if name.starts_with('[') => self.report(
+                    &location,
+                    "4.1",
+                    "the super class must not be an array type",
+                ),
+                Some(name) if self.is_interface && name != OBJECT => self.report(
+                    &location,
+                    "4.1",
+                    format!("the super class of an interface must be java/lang/Object, not {name}"),
+                ),
+                Some(_) => {}
+            }
+        }
+
+        let mut interfaces = HashSet::new();
+        for name_idx in &class.interfaces {
+            match self.utf8(*name_idx as u16) {
+                Some(name) if name.starts_with('[') => self.report(
+                    &location,
+                    "4.1",
+                    format!("the interface {name} must not be an array type"),
+                ),
+                Some(name) if !interfaces.insert(name) => {
+                    self.report(&location, "4.1", format!("duplicate interface {name}"))
+                }
+                _ => {}
+            }
+        }
+
+        self.check_attributes(
+            &location,
+            &self.class_file.attributes,
+            &UNIQUE_CLASS_ATTRIBUTES,
+        );
+    }
+
+    fn check_fields(&mut self) {
+        let mut fields = HashSet::new();
+        for field in &self.class_file.fields {
+            let name = self.utf8(field.name_idx);
+            let descriptor = self.utf8(field.descriptor_idx);
+            let location = Location::Field {
+                name: name.unwrap_or("?").to_string(),
+                descriptor: descriptor.unwrap_or("?").to_string(),
+            };
+
+            match name {
+                None => self.report(&location, "4.5", "name_index must point to a Utf8 entry"),
+                Some(name) if !is_unqualified_name(name) => {
+                    self.report(&location, "4.2.2", format!("invalid field name {name:?}"))
+                }
+                Some(_) => {}
+            }
+            let field_type = match descriptor.map(parse_field_descriptor) {
+                None => {
+                    self.report(
+                        &location,
+                        "4.5",
+                        "descriptor_index must point to a Utf8 entry",
+                    );
+                    None
+                }
+                Some(Err(message)) => {
+                    self.report(&location, "4.3.2", message);
+                    None
+                }
+                Some(Ok(field_type)) => Some(field_type),
+            };
+            if let (Some(name), Some(descriptor)) = (name, descriptor)
+                && !fields.insert((name, descriptor))
+            {
+                self.report(&location, "4.5", "duplicate field");
+            }
+
+            let flags = field.access_flags;
+            let visibility = flags
+                & (FieldAccessFlags::ACC_PUBLIC
+                    | FieldAccessFlags::ACC_PRIVATE
+                    | FieldAccessFlags::ACC_PROTECTED);
+            if visibility.bits().count_ones() > 1 {
+                self.report(
+                    &location,
+                    "4.5",
+                    format!(
+                        "a field must not be {}",
+                        flag_names(visibility.iter_names())
+                    ),
+                );
+            }
+            if flags.contains(FieldAccessFlags::ACC_FINAL | FieldAccessFlags::ACC_VOLATILE) {
+                self.report(
+                    &location,
+                    "4.5",
+                    "a field must not be both ACC_FINAL and ACC_VOLATILE",
+                );
+            }
+            let constant = FieldAccessFlags::ACC_PUBLIC
+                | FieldAccessFlags::ACC_STATIC
+                | FieldAccessFlags::ACC_FINAL;
+            if self.is_interface
+                && (!flags.contains(constant)
+                    || !(constant | FieldAccessFlags::ACC_SYNTHETIC).contains(flags))
+            {
+                self.report(
+                    &location,
+                    "4.5",
+                    "a field of an interface must be ACC_PUBLIC ACC_STATIC ACC_FINAL, and may only also be ACC_SYNTHETIC",
+                );
+            }
+
+            if let Some(field_type) = &field_type
+                && flags.contains(FieldAccessFlags::ACC_STATIC)
+            {
+                for attribute in &field.attributes {
+                    if let Attribute::ConstantValue(value_idx) = attribute
+                        && !self.matches_constant(field_type, *value_idx)
+                    {
+                        self.report(
+                            &location,
+                            "4.7.2",
+                            format!(
+                                "the constant #{value_idx} does not match the type of the field"
+                            ),
+                        );
+                    }
+                }
+            }
+
+            self.check_attributes(&location, &field.attributes, &UNIQUE_FIELD_ATTRIBUTES);
+        }
+    }
+
+    /// Whether a ConstantValue attribute may point to the entry at `value_idx` for a field of a type, see table
+    /// 4.7.2-A of the JVMS.
+    fn matches_constant(&self, field_type: &FieldType, value_idx: u16) -> bool {
+        match (field_type, self.entry(value_idx)) {
+            (FieldType::Long, Some(ConstantPoolEntry::Long(_)))
+            | (FieldType::Float, Some(ConstantPoolEntry::Float(_)))
+            | (FieldType::Double, Some(ConstantPoolEntry::Double(_))) => true,
+            (
+                FieldType::Int
+                | FieldType::Short
+                | FieldType::Char
+                | FieldType::Byte
+                | FieldType::Boolean,
+                Some(ConstantPoolEntry::Integer(_)),
+            ) => true,
+            (FieldType::Object(class_name), Some(ConstantPoolEntry::StringRef(_))) => {
+                class_name == STRING
+            }
+            _ => false,
+        }
+    }
+
+    fn check_methods(&mut self) {
+        let mut methods = HashSet::new();
+        for method in &self.class_file.methods {
+            let name = self.utf8(method.name_idx);
+            let descriptor = self.utf8(method.descriptor_idx);
+            let location = Location::Method {
+                name: name.unwrap_or("?").to_string(),
+                descriptor: descriptor.unwrap_or("?").to_string(),
+            };
+            let flags = method.access_flags;
+
+            match name {
+                None => self.report(&location, "4.6", "name_index must point to a Utf8 entry"),
+                Some(name) if !is_method_name(name) => {
+                    self.report(&location, "4.2.2", format!("invalid method name {name:?}"))
+                }
+                Some(_) => {}
+            }
+            let method_descriptor = match descriptor.map(parse_method_descriptor) {
+                None => {
+                    self.report(
+                        &location,
+                        "4.6",
+                        "descriptor_index must point to a Utf8 entry",
+                    );
+                    None
+                }
+                Some(Err(message)) => {
+                    self.report(&location, "4.3.3", message);
+                    None
+                }
+                Some(Ok(method_descriptor)) => Some(method_descriptor),
+            };
+            if let Some(method_descriptor) = &method_descriptor {
+                let this_slots = u16::from(!flags.contains(MethodAccessFlags::ACC_STATIC));
+                let slots = method_descriptor.parameter_slots() + this_slots;
+                if slots > 255 {
+                    self.report(
+                        &location,
+                        "4.3.3",
+                        format!("the parameters take {slots} slots, more than 255"),
+                    );
+                }
+            }
+            if let (Some(name), Some(descriptor)) = (name, descriptor)
+                && !methods.insert((name, descriptor))
+            {
+                self.report(&location, "4.6", "duplicate method");
+            }
+
+            if name == Some(CLINIT) && self.major >= 51 {
+                // The other access flags of a class initialization method are ignored.
+                if !flags.contains(MethodAccessFlags::ACC_STATIC) {
+                    self.report(&location, "2.9.2", "<clinit> must be ACC_STATIC");
+                }
+                if descriptor != Some("()V") {
+                    self.report(
+                        &location,
+                        "2.9.2",
+                        "<clinit> must take no parameters and return void",
+                    );
+                }
+            } else if name != Some(CLINIT) {
+                self.check_method_flags(&location, name == Some(INIT), flags);
+            }
+            if name == Some(INIT)
+                && method_descriptor
+                    .is_some_and(|method_descriptor| method_descriptor.return_type.is_some())
+            {
+                self.report(&location, "2.9.1", "<init> must return void");
+            }
+
+            let has_code = method
+                .attributes
+                .iter()
+                .any(|attribute| matches!(attribute, Attribute::Code(_)));
+            if flags.intersects(MethodAccessFlags::ACC_NATIVE | MethodAccessFlags::ACC_ABSTRACT) {
+                if has_code {
+                    self.report(
+                        &location,
+                        "4.7.3",
+                        "a native or abstract method must not have code",
+                    );
+                }
+            } else if !has_code {
+                self.report(
+                    &location,
+                    "4.7.3",
+                    "a method that is neither native nor abstract must have code",
+                );
+            }
+            for attribute in &method.attributes {
+                if let Attribute::Code(code) = attribute {
+                    self.check_code(&location, code);
+                }
+            }
+
+            self.check_attributes(&location, &method.attributes, &UNIQUE_METHOD_ATTRIBUTES);
+        }
+    }
+
+    fn check_method_flags(&mut self, location: &Location, is_init: bool, flags: MethodAccessFlags) {
+        let visibility = flags
+            & (MethodAccessFlags::ACC_PUBLIC
+                | MethodAccessFlags::ACC_PRIVATE
+                | MethodAccessFlags::ACC_PROTECTED);
+        if visibility.bits().count_ones() > 1 {
+            self.report(
+                location,
+                "4.6",
+                format!(
+                    "a method must not be {}",
+                    flag_names(visibility.iter_names())
+                ),
+            );
+        }
+
+        if self.is_interface {
+            if is_init {
+                self.report(
+                    location,
+                    "4.6",
+                    "an interface must not have an <init> method",
+                );
+            }
+            if self.major < 52 {
+                if !flags.contains(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_ABSTRACT)
+                {
+                    self.report(
+                        location,
+                        "4.6",
+                        "before major version 52, a method of an interface must be ACC_PUBLIC ACC_ABSTRACT",
+                    );
+                }
+            } else {
+                let forbidden = flags
+                    & (MethodAccessFlags::ACC_PROTECTED
+                        | MethodAccessFlags::ACC_FINAL
+                        | MethodAccessFlags::ACC_SYNCHRONIZED
+                        | MethodAccessFlags::ACC_NATIVE);
+                if !forbidden.is_empty() {
+                    self.report(
+                        location,
+                        "4.6",
+                        format!(
+                            "a method of an interface must not be {}",
+                            flag_names(forbidden.iter_names())
+                        ),
+                    );
+                }
+                let visibility =
+                    flags & (MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_PRIVATE);
+                if visibility.bits().count_ones() != 1 {
+                    self.report(
+                        location,
+                        "4.6",
+                        "a method of an interface must be either ACC_PUBLIC or ACC_PRIVATE",
+                    );
+                }
+            }
+        }
+
+        if flags.contains(MethodAccessFlags::ACC_ABSTRACT) {
+            let mut incompatible = MethodAccessFlags::ACC_PRIVATE
+                | MethodAccessFlags::ACC_STATIC
+                | MethodAccessFlags::ACC_FINAL
+                | MethodAccessFlags::ACC_SYNCHRONIZED
+                | MethodAccessFlags::ACC_NATIVE;
+            // ACC_STRICT only means strictfp from major version 46 to 60.
+            if (46..=60).contains(&self.major) {
+                incompatible |= MethodAccessFlags::ACC_STRICT;
+            }
+            let forbidden = flags & incompatible;
+            if !forbidden.is_empty() {
+                self.report(
+                    location,
+                    "4.6",
+                    format!(
+                        "an abstract method must not be {}",
+                        flag_names(forbidden.iter_names())
+                    ),
+                );
+            }
+        }
+
+        if is_init {
+            let allowed = MethodAccessFlags::ACC_PUBLIC
+                | MethodAccessFlags::ACC_PRIVATE
+                | MethodAccessFlags::ACC_PROTECTED
+                | MethodAccessFlags::ACC_VARARGS
+                | MethodAccessFlags::ACC_STRICT
+                | MethodAccessFlags::ACC_SYNTHETIC;
+            let forbidden = flags - allowed;
+            if !forbidden.is_empty() {
+                self.report(
+                    location,
+                    "4.6",
+                    format!("<init> must not be {}", flag_names(forbidden.iter_names())),
+                );
+            }
+        }
+    }
+
+    fn check_code(&mut self, location: &Location, code: &Code) {
+        let code_length = code.code.len();
+        if code_length == 0 || code_length >= 65536 {
+            self.report(
+                location,
+                "4.7.3",
+                format!("code_length is {code_length}, it must be between 1 and 65535"),
+            );
+        }
+        for (position, handler) in code.exception_table.iter().enumerate() {
+            if handler.start_pc >= handler.end_pc
+                || usize::from(handler.end_pc) > code_length
+                || usize::from(handler.handler_pc) >= code_length
+            {
+                self.report(
+                    location,
+                    "4.7.3",
+                    format!(
+                        "exception handler {position} covers {}..{} and starts at {}, outside the code",
+                        handler.start_pc, handler.end_pc, handler.handler_pc
+                    ),
+                );
+            }
+            if handler.catch_type != 0 && self.class_name(handler.catch_type).is_none() {
+                self.report(
+                    location,
+                    "4.7.3",
+                    format!(
+                        "catch_type of exception handler {position} must point to a Class entry"
+                    ),
+                );
+            }
+        }
+        self.check_attributes(location, &code.attributes, &UNIQUE_CODE_ATTRIBUTES);
+    }
+}
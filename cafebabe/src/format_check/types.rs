@@ -0,0 +1,50 @@
+use std::fmt::{Display, Formatter};
+
+/// Where in a class file a format check failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum Location {
+    /// A byte offset in the class file, for problems with its layout such as trailing bytes.
+    Offset { offset: usize },
+    /// An entry of the constant pool, by its index.
+    ConstantPool { index: u16 },
+    /// The access flags, super class, interfaces or attributes of the class itself.
+    Class,
+    /// A field, by its name and descriptor as far as they could be looked up.
+    Field { name: String, descriptor: String },
+    /// A method, by its name and descriptor as far as they could be looked up.
+    Method { name: String, descriptor: String },
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Offset { offset } => write!(f, "offset {offset}"),
+            Location::ConstantPool { index } => write!(f, "constant pool #{index}"),
+            Location::Class => write!(f, "class"),
+            Location::Field { name, descriptor } => write!(f, "field {name}:{descriptor}"),
+            Location::Method { name, descriptor } => write!(f, "method {name}{descriptor}"),
+        }
+    }
+}
+
+/// A violation of one of the rules that the JVM checks when it loads a class file, see section 4.8 of the JVMS.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnostic {
+    pub location: Location,
+    /// The section of the JVMS that states the rule, e.g. `4.6`.
+    pub section: &'static str,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} (JVMS {})",
+            self.location, self.message, self.section
+        )
+    }
+}
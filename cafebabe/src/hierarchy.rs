@@ -0,0 +1,279 @@
+//! An index of the superclasses and superinterfaces of the classes on a class path, to answer questions about
+//! subtyping across all of them.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::result::Result;
+
+use log::error;
+
+use crate::archive::ArchiveError;
+use crate::class_path::ClassPath;
+use crate::errors::ClassFileError;
+use crate::read_class_data_with_options;
+use crate::types::{AccessFlags, ClassFile};
+
+const OBJECT: &str = "java/lang/Object";
+
+/// The direct supertypes of a class, as declared by its `super_class` and `interfaces` items.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HierarchyNode {
+    pub class_name: String,
+    pub access_flags: AccessFlags,
+    pub super_class: Option<String>,
+    pub interfaces: Vec<String>,
+}
+
+impl HierarchyNode {
+    pub fn is_interface(&self) -> bool {
+        self.access_flags.contains(AccessFlags::ACC_INTERFACE)
+    }
+
+    fn direct_supertypes(&self) -> impl Iterator<Item = &str> {
+        self.super_class
+            .iter()
+            .chain(&self.interfaces)
+            .map(String::as_str)
+    }
+}
+
+/// A supertype that is named by classes in the index but isn't in the index itself.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MissingSupertype {
+    pub class_name: String,
+    /// The classes that extend or implement the missing class, sorted by name.
+    pub referenced_by: Vec<String>,
+}
+
+/// The class hierarchy of a set of classes, with the direct supertypes of each class and, in reverse, its direct
+/// subtypes.
+#[derive(Debug, Default)]
+pub struct ClassHierarchy {
+    nodes: HashMap<String, HierarchyNode>,
+    /// The classes and interfaces that directly extend or implement a class, by the name of the class.
+    direct_subtypes: HashMap<String, BTreeSet<String>>,
+    /// The classes of the class path that couldn't be parsed and are left out.
+    invalid_classes: Vec<String>,
+}
+
+impl ClassHierarchy {
+    pub fn new() -> ClassHierarchy {
+        ClassHierarchy::default()
+    }
+
+    /// Indexes all classes of a class path, except the ones shadowed by earlier entries.
+    ///
+    /// Classes that fail to parse are logged and left out, see [`ClassHierarchy::invalid_classes`].
+    pub fn from_class_path(class_path: &ClassPath) -> Result<ClassHierarchy, ArchiveError> {
+        let mut hierarchy = ClassHierarchy::new();
+        for class_name in class_path.class_names() {
+            let Some(data) = class_path.find_class_data(class_name)? else {
+                continue;
+            };
+            let added = read_class_data_with_options(&data, class_path.options())
+                .and_then(|class_file| hierarchy.add_class(&class_file));
+            if let Err(error) = added {
+                error!("can't index class; class_name={class_name}, error={error}");
+                hierarchy.invalid_classes.push(class_name.to_string());
+            }
+        }
+        Ok(hierarchy)
+    }
+
+    /// Adds a class to the index, replacing a class of the same name that was added before.
+    pub fn add_class(&mut self, class_file: &ClassFile) -> Result<(), ClassFileError> {
+        let node = HierarchyNode {
+            class_name: class_file.this_class_name()?.clone(),
+            access_flags: class_file.access_flags,
+            super_class: class_file.super_class_name()?.cloned(),
+            interfaces: class_file.interface_names()?.into_iter().cloned().collect(),
+        };
+        self.add_node(node);
+        Ok(())
+    }
+
+    pub fn add_node(&mut self, node: HierarchyNode) {
+        if let Some(previous) = self.nodes.remove(&node.class_name) {
+            for supertype in previous.direct_supertypes() {
+                if let Some(subtypes) = self.direct_subtypes.get_mut(supertype) {
+                    subtypes.remove(&previous.class_name);
+                }
+            }
+        }
+        for supertype in node.direct_supertypes() {
+            self.direct_subtypes
+                .entry(supertype.to_string())
+                .or_default()
+                .insert(node.class_name.clone());
+        }
+        self.nodes.insert(node.class_name.clone(), node);
+    }
+
+    pub fn get(&self, class_name: &str) -> Option<&HierarchyNode> {
+        self.nodes.get(class_name)
+    }
+
+    pub fn contains(&self, class_name: &str) -> bool {
+        self.nodes.contains_key(class_name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The names of all indexed classes, sorted.
+    pub fn class_names(&self) -> Vec<&str> {
+        let mut class_names: Vec<&str> = self.nodes.keys().map(String::as_str).collect();
+        class_names.sort();
+        class_names
+    }
+
+    /// The classes of the class path that couldn't be parsed when the index was built.
+    pub fn invalid_classes(&self) -> &[String] {
+        &self.invalid_classes
+    }
+
+    /// Whether a class is an interface, or `None` if it isn't indexed.
+    pub fn is_interface(&self, class_name: &str) -> Option<bool> {
+        self.nodes.get(class_name).map(HierarchyNode::is_interface)
+    }
+
+    /// The superclasses of a class from its direct superclass up to `java/lang/Object`, as far as they are indexed.
+    pub fn superclasses(&self, class_name: &str) -> Vec<&str> {
+        let mut superclasses = Vec::new();
+        let mut current = self.nodes.get(class_name);
+        while let Some(super_class) = current.and_then(|node| node.super_class.as_deref()) {
+            if super_class == class_name || superclasses.contains(&super_class) {
+                break;
+            }
+            superclasses.push(super_class);
+            current = self.nodes.get(super_class);
+        }
+        superclasses
+    }
+
+    /// All supertypes of a class, nearest first: its superclasses and then the interfaces they implement, in the
+    /// order they are declared. Supertypes that aren't indexed are included, but not their own supertypes.
+    pub fn supertypes(&self, class_name: &str) -> Vec<&str> {
+        let mut supertypes = Vec::new();
+        let mut seen = HashSet::from([class_name]);
+        let mut queue: VecDeque<&str> = self.superclasses(class_name).into();
+        if let Some(node) = self.nodes.get(class_name) {
+            queue.extend(node.interfaces.iter().map(String::as_str));
+        }
+        let mut interfaces: VecDeque<&str> = VecDeque::new();
+        while let Some(supertype) = queue.pop_front().or_else(|| interfaces.pop_front()) {
+            if !seen.insert(supertype) {
+                continue;
+            }
+            supertypes.push(supertype);
+            if let Some(node) = self.nodes.get(supertype) {
+                interfaces.extend(node.interfaces.iter().map(String::as_str));
+            }
+        }
+        supertypes
+    }
+
+    /// The classes and interfaces that directly extend or implement a class, sorted by name.
+    pub fn direct_subtypes(&self, class_name: &str) -> Vec<&str> {
+        self.direct_subtypes
+            .get(class_name)
+            .map(|subtypes| subtypes.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// All classes and interfaces that extend or implement a class, directly or through others, sorted by name.
+    pub fn subtypes(&self, class_name: &str) -> Vec<&str> {
+        let mut subtypes = BTreeSet::new();
+        let mut queue = vec![class_name];
+        while let Some(current) = queue.pop() {
+            for subtype in self.direct_subtypes(current) {
+                if subtype != class_name && subtypes.insert(subtype) {
+                    queue.push(subtype);
+                }
+            }
+        }
+        subtypes.into_iter().collect()
+    }
+
+    /// The classes that implement an interface, declared by themselves, by a superclass or through a subinterface,
+    /// sorted by name. For a class, these are its subclasses.
+    pub fn implementors(&self, class_name: &str) -> Vec<&str> {
+        self.subtypes(class_name)
+            .into_iter()
+            .filter(|subtype| self.is_interface(subtype) == Some(false))
+            .collect()
+    }
+
+    /// The supertypes that indexed classes name but that aren't indexed themselves, sorted by name.
+    pub fn missing_supertypes(&self) -> Vec<MissingSupertype> {
+        let mut missing: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for node in self.nodes.values() {
+            for supertype in node.direct_supertypes() {
+                if !self.nodes.contains_key(supertype) {
+                    missing
+                        .entry(supertype)
+                        .or_default()
+                        .insert(&node.class_name);
+                }
+            }
+        }
+        missing
+            .into_iter()
+            .map(|(class_name, referenced_by)| MissingSupertype {
+                class_name: class_name.to_string(),
+                referenced_by: referenced_by.into_iter().map(str::to_string).collect(),
+            })
+            .collect()
+    }
+
+    /// Whether a value of one class can be assigned to a variable of another, i.e. the other is the same class,
+    /// `java/lang/Object` or one of its supertypes. Array types aren't supported.
+    pub fn is_assignable(&self, from: &str, to: &str) -> bool {
+        from == to || to == OBJECT || self.supertypes(from).contains(&to)
+    }
+
+    /// The most specific class that both classes extend, as needed for merging types when computing a
+    /// `StackMapTable`. Interfaces merge to `java/lang/Object`, as the verifier treats them like it. Gives `None` when
+    /// a class or one of the superclasses to compare with isn't indexed.
+    pub fn common_super_class(&self, first: &str, second: &str) -> Option<&str> {
+        for class_name in [first, second] {
+            if class_name != OBJECT && !self.nodes.contains_key(class_name) {
+                return None;
+            }
+        }
+        if self.is_assignable(first, second) {
+            return Some(
+                self.nodes
+                    .get_key_value(second)
+                    .map_or(OBJECT, |(name, _)| name),
+            );
+        }
+        if self.is_assignable(second, first) {
+            return Some(
+                self.nodes
+                    .get_key_value(first)
+                    .map_or(OBJECT, |(name, _)| name),
+            );
+        }
+        if self.is_interface(first) == Some(true) || self.is_interface(second) == Some(true) {
+            return Some(OBJECT);
+        }
+        let mut node = self.nodes.get(first)?;
+        loop {
+            let super_class = node.super_class.as_deref()?;
+            if super_class == OBJECT {
+                return Some(OBJECT);
+            }
+            if self.is_assignable(second, super_class) {
+                return Some(super_class);
+            }
+            node = self.nodes.get(super_class)?;
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Kotlin metadata: the declarations that the Kotlin compiler writes to the `@kotlin.Metadata` annotation of every
+//! class it compiles, with what the JVM doesn't know about them, such as nullability, properties, extension
+//! receivers, data classes and top-level functions.
+//!
+//! The declarations are serialized as protocol buffers (`metadata.proto` and `jvm_metadata.proto` of the Kotlin
+//! compiler) and stored in the strings of `d1`, and refer to the strings of `d2` through a string table. Only the
+//! parts that describe declarations are decoded: contracts, version requirements, annotations on types and the
+//! declarations of local variables are skipped.
+
+mod annotation;
+mod decode;
+mod names;
+mod protobuf;
+mod source;
+mod types;
+
+use std::result::Result;
+
+use log::error;
+
+use self::decode::Decoder;
+use self::names::NameResolver;
+use crate::errors::ClassFileError;
+use crate::types::ClassFile;
+
+pub use types::{
+    Class, ClassKind, Classifier, Constructor, Declarations, Function, JvmSignature, MemberKind,
+    Metadata, Modality, Package, Property, Type, TypeAlias, TypeParameter, TypeProjection,
+    ValueParameter, Variance, Visibility,
+};
+
+/// Reads the `@kotlin.Metadata` annotation of a class, or `None` if it wasn't compiled by Kotlin.
+pub fn read_metadata(class_file: &ClassFile) -> Result<Option<Metadata>, ClassFileError> {
+    annotation::read_metadata(class_file)
+}
+
+impl Metadata {
+    /// Decodes the declarations of `d1` and `d2`.
+    pub fn declarations(&self) -> Result<Declarations, ClassFileError> {
+        if self.kind == 4 {
+            return Ok(Declarations::MultiFileClassFacade(self.data1.clone()));
+        }
+        let bytes = names::decode_bytes(&self.data1);
+        // Synthetic classes other than lambdas have no declarations.
+        if self.kind == 3 && bytes.is_empty() {
+            return Ok(Declarations::SyntheticClass(None));
+        }
+        let (string_table, message) = protobuf::parse_delimited(&bytes)?;
+        let message = protobuf::parse(message)?;
+        let mut decoder = Decoder::new(NameResolver::new(&string_table, &self.data2)?);
+        let declarations = match self.kind {
+            1 => Declarations::Class(decoder.class(&message)?),
+            2 => Declarations::File(decoder.package(&message)?),
+            3 => Declarations::SyntheticClass(Some(decoder.function(&message)?)),
+            5 => Declarations::MultiFileClassPart {
+                facade: self.extra_string.clone().unwrap_or_default(),
+                package: decoder.package(&message)?,
+            },
+            kind => {
+                error!("unknown kind of kotlin metadata; kind={kind}");
+                return Err(ClassFileError::InvalidKotlinMetadata);
+            }
+        };
+        Ok(declarations)
+    }
+}
+
+/// Decodes the Kotlin declarations of a class, or `None` if it wasn't compiled by Kotlin.
+pub fn kotlin_declarations(class_file: &ClassFile) -> Result<Option<Declarations>, ClassFileError> {
+    read_metadata(class_file)?
+        .map(|metadata| metadata.declarations())
+        .transpose()
+}
+
+/// Prints the Kotlin declarations of a class as Kotlin-like source without bodies, or `None` if it wasn't compiled by
+/// Kotlin. With `jvm_signatures`, each member is followed by the JVM methods and fields that it compiles to.
+pub fn print_kotlin(
+    class_file: &ClassFile,
+    jvm_signatures: bool,
+) -> Result<Option<String>, ClassFileError> {
+    let class_name = class_file.this_class_name()?;
+    Ok(kotlin_declarations(class_file)?
+        .map(|declarations| source::print(class_name, &declarations, jvm_signatures)))
+}
@@ -0,0 +1,131 @@
+//! Reading the values of the `@kotlin.Metadata` annotation from the `RuntimeVisibleAnnotations` attribute (JVMS
+//! 4.7.16), which is kept as an unknown attribute.
+
+use std::result::Result;
+
+use log::error;
+
+use super::types::Metadata;
+use crate::attributes::Attribute;
+use crate::constant_pool::ConstantPool;
+use crate::constant_pool::types::ConstantPoolEntry;
+use crate::errors::ClassFileError;
+use crate::shared::annotations::{AnnotationIndex, annotation_indices};
+use crate::types::ClassFile;
+
+const METADATA_DESCRIPTOR: &str = "Lkotlin/Metadata;";
+
+/// The constant values of an element of the metadata, which are integers and strings, alone or in arrays.
+enum ElementValue {
+    Int(i32),
+    String(String),
+}
+
+/// The elements of an annotation by name, with their constant values.
+type Elements = Vec<(String, Vec<ElementValue>)>;
+
+/// The constant values of the elements of the `@kotlin.Metadata` annotation among the indices of an annotation
+/// attribute, or `None` if it doesn't hold the metadata.
+fn metadata_elements(
+    indices: &[(AnnotationIndex, u16)],
+    constant_pool: &ConstantPool,
+) -> Result<Option<Elements>, ClassFileError> {
+    let mut elements: Option<Elements> = None;
+    for (kind, index) in indices {
+        match (kind, &mut elements) {
+            (AnnotationIndex::Type, None)
+                if constant_pool.string_entry(*index)? == METADATA_DESCRIPTOR =>
+            {
+                elements = Some(Vec::new());
+            }
+            // The metadata holds no annotations, so the next type starts another annotation.
+            (AnnotationIndex::Type, Some(_)) => break,
+            (AnnotationIndex::ElementName, Some(elements)) => {
+                let name = constant_pool.string_entry(*index)?.clone();
+                elements.push((name, Vec::new()));
+            }
+            (AnnotationIndex::Constant(tag), Some(elements)) => {
+                let Some((_, values)) = elements.last_mut() else {
+                    continue;
+                };
+                match (tag, constant_pool.checked_entry(*index)?) {
+                    (b'I', ConstantPoolEntry::Integer(value)) => {
+                        values.push(ElementValue::Int(*value))
+                    }
+                    (b'I', _) => {
+                        error!("integer element value isn't an integer; index={index}");
+                        return Err(ClassFileError::InvalidAttribute);
+                    }
+                    (b's', _) => values.push(ElementValue::String(
+                        constant_pool.string_entry(*index)?.clone(),
+                    )),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(elements)
+}
+
+fn ints(values: &[ElementValue]) -> Vec<i32> {
+    values
+        .iter()
+        .filter_map(|value| match value {
+            ElementValue::Int(value) => Some(*value),
+            _ => None,
+        })
+        .collect()
+}
+
+fn strings(values: &[ElementValue]) -> Vec<String> {
+    values
+        .iter()
+        .filter_map(|value| match value {
+            ElementValue::String(value) => Some(value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The values of the `@kotlin.Metadata` annotation of a class, with the defaults of the elements that it leaves out.
+pub(super) fn read_metadata(class_file: &ClassFile) -> Result<Option<Metadata>, ClassFileError> {
+    let constant_pool = &class_file.constant_pool;
+    for attribute in &class_file.attributes {
+        let Attribute::Unknown { name_idx, info } = attribute else {
+            continue;
+        };
+        let name = constant_pool.string_entry(*name_idx)?;
+        if name != "RuntimeVisibleAnnotations" {
+            continue;
+        }
+        let mut indices = Vec::new();
+        annotation_indices(name, &mut info.to_vec(), &mut |kind, index| {
+            indices.push((kind, *index))
+        })?;
+        if let Some(elements) = metadata_elements(&indices, constant_pool)? {
+            let mut metadata = Metadata {
+                kind: 1,
+                ..Metadata::default()
+            };
+            for (name, values) in elements {
+                match (name.as_str(), values.as_slice()) {
+                    ("k", [ElementValue::Int(kind)]) => metadata.kind = *kind,
+                    ("mv", values) => metadata.version = ints(values),
+                    ("d1", values) => metadata.data1 = strings(values),
+                    ("d2", values) => metadata.data2 = strings(values),
+                    ("xs", [ElementValue::String(value)]) if !value.is_empty() => {
+                        metadata.extra_string = Some(value.clone())
+                    }
+                    ("pn", [ElementValue::String(value)]) if !value.is_empty() => {
+                        metadata.package_name = Some(value.clone())
+                    }
+                    ("xi", [ElementValue::Int(value)]) => metadata.extra_int = *value,
+                    _ => {}
+                }
+            }
+            return Ok(Some(metadata));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,498 @@
+//! Decoding the messages of `metadata.proto` and the JVM extensions of `jvm_metadata.proto`, with the names they refer
+//! to and the types they share through type tables.
+
+use std::result::Result;
+
+use log::error;
+
+use super::names::NameResolver;
+use super::protobuf::Message;
+use super::types::{
+    Class, ClassKind, Classifier, Constructor, Function, JvmSignature, MemberKind, Modality,
+    Package, Property, Type, TypeAlias, TypeParameter, TypeProjection, ValueParameter, Variance,
+    Visibility,
+};
+use crate::errors::ClassFileError;
+
+/// The field number of the JVM signatures of constructors, functions and properties.
+const JVM_SIGNATURE: u32 = 100;
+/// The field number of the type table of classes, packages and functions.
+const TYPE_TABLE: u32 = 30;
+
+/// The flags that members and classes share, after the flag of whether they have annotations in the lowest bit.
+const VISIBILITY_SHIFT: i32 = 1;
+const MODALITY_SHIFT: i32 = 4;
+const MEMBER_KIND_SHIFT: i32 = 6;
+
+fn flag(flags: i32, bit: i32) -> bool {
+    flags & (1 << bit) != 0
+}
+
+fn invalid(what: &str, value: i32) -> ClassFileError {
+    error!("invalid {what} in kotlin metadata; value={value}");
+    ClassFileError::InvalidKotlinMetadata
+}
+
+fn visibility(flags: i32) -> Result<Visibility, ClassFileError> {
+    match (flags >> VISIBILITY_SHIFT) & 7 {
+        0 => Ok(Visibility::Internal),
+        1 => Ok(Visibility::Private),
+        2 => Ok(Visibility::Protected),
+        3 => Ok(Visibility::Public),
+        4 => Ok(Visibility::PrivateToThis),
+        5 => Ok(Visibility::Local),
+        value => Err(invalid("visibility", value)),
+    }
+}
+
+fn modality(flags: i32) -> Modality {
+    match (flags >> MODALITY_SHIFT) & 3 {
+        0 => Modality::Final,
+        1 => Modality::Open,
+        2 => Modality::Abstract,
+        _ => Modality::Sealed,
+    }
+}
+
+fn member_kind(flags: i32) -> MemberKind {
+    match (flags >> MEMBER_KIND_SHIFT) & 3 {
+        0 => MemberKind::Declaration,
+        1 => MemberKind::FakeOverride,
+        2 => MemberKind::Delegation,
+        _ => MemberKind::Synthesized,
+    }
+}
+
+fn class_kind(flags: i32) -> Result<ClassKind, ClassFileError> {
+    match (flags >> 6) & 7 {
+        0 => Ok(ClassKind::Class),
+        1 => Ok(ClassKind::Interface),
+        2 => Ok(ClassKind::EnumClass),
+        3 => Ok(ClassKind::EnumEntry),
+        4 => Ok(ClassKind::AnnotationClass),
+        5 => Ok(ClassKind::Object),
+        6 => Ok(ClassKind::CompanionObject),
+        value => Err(invalid("class kind", value)),
+    }
+}
+
+/// The variance of type parameters and of type projections, which also have `STAR` as 3.
+fn variance(value: Option<i32>) -> Result<Variance, ClassFileError> {
+    match value.unwrap_or(2) {
+        0 => Ok(Variance::In),
+        1 => Ok(Variance::Out),
+        2 => Ok(Variance::Invariant),
+        value => Err(invalid("variance", value)),
+    }
+}
+
+/// The types that are referred to by their index, with the index of the first one that is nullable.
+struct TypeTable<'a> {
+    types: Vec<Message<'a>>,
+    first_nullable: i32,
+}
+
+pub(super) struct Decoder<'a> {
+    names: NameResolver<'a>,
+    /// The type parameters in scope by their id, innermost last.
+    type_parameters: Vec<(i32, String)>,
+    /// The type tables in scope, innermost last.
+    type_tables: Vec<TypeTable<'a>>,
+}
+
+impl<'a> Decoder<'a> {
+    pub(super) fn new(names: NameResolver<'a>) -> Decoder<'a> {
+        Decoder {
+            names,
+            type_parameters: Vec::new(),
+            type_tables: Vec::new(),
+        }
+    }
+
+    fn name(&self, message: &Message, number: u32) -> Result<String, ClassFileError> {
+        match message.int(number) {
+            Some(index) => self.names.string(index),
+            None => {
+                error!("missing name in kotlin metadata; field={number}");
+                Err(ClassFileError::InvalidKotlinMetadata)
+            }
+        }
+    }
+
+    fn names(&self, message: &Message, number: u32) -> Result<Vec<String>, ClassFileError> {
+        message
+            .ints(number)?
+            .into_iter()
+            .map(|index| self.names.string(index))
+            .collect()
+    }
+
+    /// Enters the type table of a declaration, if it has one, and returns whether it did.
+    fn enter_type_table(&mut self, message: &Message<'a>) -> Result<bool, ClassFileError> {
+        let Some(table) = message.message(TYPE_TABLE)? else {
+            return Ok(false);
+        };
+        self.type_tables.push(TypeTable {
+            types: table.messages(1)?,
+            first_nullable: table.int(2).unwrap_or(-1),
+        });
+        Ok(true)
+    }
+
+    fn leave_type_table(&mut self, entered: bool) {
+        if entered {
+            self.type_tables.pop();
+        }
+    }
+
+    /// A type that is written in the message or referred to by its index in the type table.
+    fn type_field(
+        &self,
+        message: &Message,
+        number: u32,
+        id_number: u32,
+    ) -> Result<Option<Type>, ClassFileError> {
+        if let Some(type_message) = message.message(number)? {
+            return self.decode_type(&type_message).map(Some);
+        }
+        match message.int(id_number) {
+            Some(id) => self.type_at(id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The type at an index of the innermost type table.
+    fn type_at(&self, id: i32) -> Result<Type, ClassFileError> {
+        let Some(table) = self.type_tables.last() else {
+            error!("type id without a type table; id={id}");
+            return Err(ClassFileError::InvalidKotlinMetadata);
+        };
+        let Some(type_message) = usize::try_from(id)
+            .ok()
+            .and_then(|idx| table.types.get(idx))
+        else {
+            error!("type id out of bounds; id={id}, size={}", table.types.len());
+            return Err(ClassFileError::InvalidKotlinMetadata);
+        };
+        let is_nullable = table.first_nullable >= 0 && id >= table.first_nullable;
+        let mut decoded = self.decode_type(type_message)?;
+        decoded.is_nullable |= is_nullable;
+        Ok(decoded)
+    }
+
+    fn required_type(
+        &self,
+        message: &Message,
+        number: u32,
+        id_number: u32,
+    ) -> Result<Type, ClassFileError> {
+        match self.type_field(message, number, id_number)? {
+            Some(decoded) => Ok(decoded),
+            None => {
+                error!("missing type in kotlin metadata; field={number}");
+                Err(ClassFileError::InvalidKotlinMetadata)
+            }
+        }
+    }
+
+    fn decode_type(&self, message: &Message) -> Result<Type, ClassFileError> {
+        let classifier = if let Some(index) = message.int(6) {
+            Classifier::Class(self.names.string(index)?)
+        } else if let Some(id) = message.int(7) {
+            let name = match self
+                .type_parameters
+                .iter()
+                .rev()
+                .find(|(other, _)| *other == id)
+            {
+                Some((_, name)) => name.clone(),
+                // Inner classes and lambdas refer to the type parameters of the declarations they are in.
+                None => format!("T#{id}"),
+            };
+            Classifier::TypeParameter(name)
+        } else if let Some(index) = message.int(9) {
+            Classifier::TypeParameter(self.names.string(index)?)
+        } else if let Some(index) = message.int(12) {
+            Classifier::TypeAlias(self.names.string(index)?)
+        } else {
+            error!("type without a classifier in kotlin metadata");
+            return Err(ClassFileError::InvalidKotlinMetadata);
+        };
+        let mut arguments = Vec::new();
+        for argument in message.messages(2)? {
+            arguments.push(match argument.int(1) {
+                Some(3) => TypeProjection::Star,
+                projection => {
+                    let projected = self.required_type(&argument, 2, 3)?;
+                    TypeProjection::Type(variance(projection)?, projected)
+                }
+            });
+        }
+        let flags = message.int(1).unwrap_or(0);
+        Ok(Type {
+            classifier,
+            arguments,
+            is_nullable: message.bool(3),
+            is_suspend: flag(flags, 0),
+            is_definitely_non_null: flag(flags, 1),
+            flexible_upper_bound: self.type_field(message, 5, 8)?.map(Box::new),
+            abbreviated_type: self.type_field(message, 13, 14)?.map(Box::new),
+        })
+    }
+
+    /// Declares the type parameters of a declaration, whose bounds may refer to each other, and returns them with the
+    /// number of type parameters that were in scope before.
+    fn enter_type_parameters(
+        &mut self,
+        message: &Message,
+        number: u32,
+    ) -> Result<(Vec<TypeParameter>, usize), ClassFileError> {
+        let scope = self.type_parameters.len();
+        let messages = message.messages(number)?;
+        for type_parameter in &messages {
+            let id = type_parameter.int(1).unwrap_or_default();
+            let name = self.name(type_parameter, 2)?;
+            self.type_parameters.push((id, name));
+        }
+        let mut type_parameters = Vec::new();
+        for type_parameter in &messages {
+            let mut upper_bounds = Vec::new();
+            for bound in type_parameter.messages(5)? {
+                upper_bounds.push(self.decode_type(&bound)?);
+            }
+            for id in type_parameter.ints(6)? {
+                upper_bounds.push(self.type_at(id)?);
+            }
+            type_parameters.push(TypeParameter {
+                id: type_parameter.int(1).unwrap_or_default(),
+                name: self.name(type_parameter, 2)?,
+                is_reified: type_parameter.bool(3),
+                variance: variance(type_parameter.int(4))?,
+                upper_bounds,
+            });
+        }
+        Ok((type_parameters, scope))
+    }
+
+    fn leave_type_parameters(&mut self, scope: usize) {
+        self.type_parameters.truncate(scope);
+    }
+
+    fn value_parameter(&self, message: &Message) -> Result<ValueParameter, ClassFileError> {
+        let flags = message.int(1).unwrap_or(0);
+        Ok(ValueParameter {
+            name: self.name(message, 2)?,
+            parameter_type: self.required_type(message, 3, 5)?,
+            vararg_element_type: self.type_field(message, 4, 6)?,
+            declares_default_value: flag(flags, 1),
+            is_crossinline: flag(flags, 2),
+            is_noinline: flag(flags, 3),
+        })
+    }
+
+    fn value_parameters(
+        &self,
+        message: &Message,
+        number: u32,
+    ) -> Result<Vec<ValueParameter>, ClassFileError> {
+        message
+            .messages(number)?
+            .iter()
+            .map(|parameter| self.value_parameter(parameter))
+            .collect()
+    }
+
+    /// A `JvmMethodSignature` or `JvmFieldSignature`, whose name defaults to the name of the declaration.
+    fn jvm_signature(
+        &self,
+        message: Option<Message>,
+        default_name: &str,
+    ) -> Result<Option<JvmSignature>, ClassFileError> {
+        let Some(message) = message else {
+            return Ok(None);
+        };
+        Ok(Some(JvmSignature {
+            name: match message.int(1) {
+                Some(index) => self.names.string(index)?,
+                None => default_name.to_string(),
+            },
+            descriptor: message
+                .int(2)
+                .map(|index| self.names.string(index))
+                .transpose()?,
+        }))
+    }
+
+    fn constructor(&self, message: &Message) -> Result<Constructor, ClassFileError> {
+        let flags = message.int(1).unwrap_or(6);
+        Ok(Constructor {
+            visibility: visibility(flags)?,
+            is_secondary: flag(flags, 4),
+            parameters: self.value_parameters(message, 2)?,
+            signature: self.jvm_signature(message.message(JVM_SIGNATURE)?, "<init>")?,
+        })
+    }
+
+    pub(super) fn function(&mut self, message: &Message<'a>) -> Result<Function, ClassFileError> {
+        let flags = message.int(9).unwrap_or(6);
+        let name = self.name(message, 2)?;
+        let entered = self.enter_type_table(message)?;
+        let (type_parameters, scope) = self.enter_type_parameters(message, 4)?;
+        let receiver = self.type_field(message, 5, 8)?;
+        let parameters = self.value_parameters(message, 6)?;
+        let return_type = self.required_type(message, 3, 7)?;
+        self.leave_type_parameters(scope);
+        self.leave_type_table(entered);
+        Ok(Function {
+            signature: self.jvm_signature(message.message(JVM_SIGNATURE)?, &name)?,
+            name,
+            visibility: visibility(flags)?,
+            modality: modality(flags),
+            member_kind: member_kind(flags),
+            is_operator: flag(flags, 8),
+            is_infix: flag(flags, 9),
+            is_inline: flag(flags, 10),
+            is_tailrec: flag(flags, 11),
+            is_external: flag(flags, 12),
+            is_suspend: flag(flags, 13),
+            is_expect: flag(flags, 14),
+            type_parameters,
+            receiver,
+            parameters,
+            return_type,
+        })
+    }
+
+    fn property(&mut self, message: &Message) -> Result<Property, ClassFileError> {
+        let flags = message.int(11).unwrap_or(518);
+        let name = self.name(message, 2)?;
+        let (type_parameters, scope) = self.enter_type_parameters(message, 4)?;
+        let receiver = self.type_field(message, 5, 10)?;
+        let return_type = self.required_type(message, 3, 9)?;
+        let setter_parameter = message
+            .message(6)?
+            .map(|parameter| self.value_parameter(&parameter))
+            .transpose()?;
+        self.leave_type_parameters(scope);
+        let (field, getter, setter) = match message.message(JVM_SIGNATURE)? {
+            Some(signature) => (
+                self.jvm_signature(signature.message(1)?, &name)?,
+                self.jvm_signature(signature.message(3)?, &name)?,
+                self.jvm_signature(signature.message(4)?, &name)?,
+            ),
+            None => (None, None, None),
+        };
+        Ok(Property {
+            name,
+            visibility: visibility(flags)?,
+            modality: modality(flags),
+            member_kind: member_kind(flags),
+            is_var: flag(flags, 8),
+            is_const: flag(flags, 11),
+            is_lateinit: flag(flags, 12),
+            is_external: flag(flags, 14),
+            is_delegated: flag(flags, 15),
+            is_expect: flag(flags, 16),
+            type_parameters,
+            receiver,
+            return_type,
+            setter_parameter,
+            field,
+            getter,
+            setter,
+        })
+    }
+
+    fn type_alias(&mut self, message: &Message) -> Result<TypeAlias, ClassFileError> {
+        let flags = message.int(1).unwrap_or(6);
+        let name = self.name(message, 2)?;
+        let (type_parameters, scope) = self.enter_type_parameters(message, 3)?;
+        let underlying_type = self.required_type(message, 4, 5)?;
+        let expanded_type = self.required_type(message, 6, 7)?;
+        self.leave_type_parameters(scope);
+        Ok(TypeAlias {
+            name,
+            visibility: visibility(flags)?,
+            type_parameters,
+            underlying_type,
+            expanded_type,
+        })
+    }
+
+    /// The functions, properties and type aliases at the given field numbers.
+    fn members(
+        &mut self,
+        message: &Message<'a>,
+        numbers: [u32; 3],
+    ) -> Result<Package, ClassFileError> {
+        let [functions, properties, type_aliases] = numbers;
+        let mut package = Package::default();
+        for function in message.messages(functions)? {
+            package.functions.push(self.function(&function)?);
+        }
+        for property in message.messages(properties)? {
+            package.properties.push(self.property(&property)?);
+        }
+        for type_alias in message.messages(type_aliases)? {
+            package.type_aliases.push(self.type_alias(&type_alias)?);
+        }
+        Ok(package)
+    }
+
+    pub(super) fn package(&mut self, message: &Message<'a>) -> Result<Package, ClassFileError> {
+        let entered = self.enter_type_table(message)?;
+        let package = self.members(message, [3, 4, 5]);
+        self.leave_type_table(entered);
+        package
+    }
+
+    pub(super) fn class(&mut self, message: &Message<'a>) -> Result<Class, ClassFileError> {
+        let flags = message.int(1).unwrap_or(6);
+        let entered = self.enter_type_table(message)?;
+        let (type_parameters, scope) = self.enter_type_parameters(message, 5)?;
+        let mut supertypes = Vec::new();
+        for supertype in message.messages(6)? {
+            supertypes.push(self.decode_type(&supertype)?);
+        }
+        for id in message.ints(2)? {
+            supertypes.push(self.type_at(id)?);
+        }
+        let mut constructors = Vec::new();
+        for constructor in message.messages(8)? {
+            constructors.push(self.constructor(&constructor)?);
+        }
+        let members = self.members(message, [9, 10, 11])?;
+        self.leave_type_parameters(scope);
+        self.leave_type_table(entered);
+
+        let mut enum_entries = Vec::new();
+        for entry in message.messages(13)? {
+            enum_entries.push(self.name(&entry, 1)?);
+        }
+        Ok(Class {
+            name: self.name(message, 3)?,
+            visibility: visibility(flags)?,
+            modality: modality(flags),
+            kind: class_kind(flags)?,
+            is_inner: flag(flags, 9),
+            is_data: flag(flags, 10),
+            is_external: flag(flags, 11),
+            is_expect: flag(flags, 12),
+            is_value: flag(flags, 13),
+            is_fun: flag(flags, 14),
+            type_parameters,
+            supertypes,
+            constructors,
+            functions: members.functions,
+            properties: members.properties,
+            type_aliases: members.type_aliases,
+            nested_classes: self.names(message, 7)?,
+            enum_entries,
+            sealed_subclasses: self.names(message, 16)?,
+            companion_object: message
+                .int(4)
+                .map(|index| self.names.string(index))
+                .transpose()?,
+        })
+    }
+}
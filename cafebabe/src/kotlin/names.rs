@@ -0,0 +1,186 @@
+//! The strings of Kotlin metadata: the bytes of the protocol buffers, which `d1` stores as strings, and the names that
+//! the messages refer to by their index in the string table, which resolves them from `d2`.
+
+use std::result::Result;
+
+use log::error;
+
+use super::protobuf::Message;
+use crate::errors::ClassFileError;
+
+/// The first character of `d1` when each character holds one byte. Otherwise, the bytes are stored in 7-bit form.
+const UTF8_MODE_MARKER: char = '\0';
+
+/// The strings that a record of the string table can refer to by `predefined_index` instead of storing them in `d2`.
+const PREDEFINED_STRINGS: [&str; 44] = [
+    "kotlin/Any",
+    "kotlin/Nothing",
+    "kotlin/Unit",
+    "kotlin/Throwable",
+    "kotlin/Number",
+    "kotlin/Byte",
+    "kotlin/Double",
+    "kotlin/Float",
+    "kotlin/Int",
+    "kotlin/Long",
+    "kotlin/Short",
+    "kotlin/Boolean",
+    "kotlin/Char",
+    "kotlin/CharSequence",
+    "kotlin/String",
+    "kotlin/Comparable",
+    "kotlin/Enum",
+    "kotlin/Array",
+    "kotlin/ByteArray",
+    "kotlin/DoubleArray",
+    "kotlin/FloatArray",
+    "kotlin/IntArray",
+    "kotlin/LongArray",
+    "kotlin/ShortArray",
+    "kotlin/BooleanArray",
+    "kotlin/CharArray",
+    "kotlin/Cloneable",
+    "kotlin/Annotation",
+    "kotlin/collections/Iterable",
+    "kotlin/collections/MutableIterable",
+    "kotlin/collections/Collection",
+    "kotlin/collections/MutableCollection",
+    "kotlin/collections/List",
+    "kotlin/collections/MutableList",
+    "kotlin/collections/Set",
+    "kotlin/collections/MutableSet",
+    "kotlin/collections/Map",
+    "kotlin/collections/MutableMap",
+    "kotlin/collections/Map.Entry",
+    "kotlin/collections/MutableMap.MutableEntry",
+    "kotlin/collections/Iterator",
+    "kotlin/collections/MutableIterator",
+    "kotlin/collections/ListIterator",
+    "kotlin/collections/MutableListIterator",
+];
+
+/// Decodes the bytes of the protocol buffers from the strings of `d1`.
+pub(super) fn decode_bytes(strings: &[String]) -> Vec<u8> {
+    let chars = strings.iter().flat_map(|string| string.chars());
+    if strings.first().and_then(|string| string.chars().next()) == Some(UTF8_MODE_MARKER) {
+        return chars.skip(1).map(|c| c as u8).collect();
+    }
+
+    // Older compilers add 0x7f to each 7-bit group so that the strings have no null characters, and split the bytes
+    // into groups of 7 bits starting at the least significant bit.
+    let groups: Vec<u8> = chars.map(|c| (c as u8).wrapping_add(0x7f) & 0x7f).collect();
+    let mut bytes = Vec::with_capacity(groups.len() * 7 / 8);
+    let mut idx = 0;
+    let mut bit = 0;
+    while bytes.len() < groups.len() * 7 / 8 {
+        let low = groups[idx] >> bit;
+        idx += 1;
+        let high = (groups[idx] & ((1 << (bit + 1)) - 1)) << (7 - bit);
+        bytes.push(low | high);
+        if bit == 6 {
+            idx += 1;
+            bit = 0;
+        } else {
+            bit += 1;
+        }
+    }
+    bytes
+}
+
+/// A record of the string table, for a range of consecutive indexes.
+#[derive(Clone, Debug)]
+struct Record {
+    string: Option<String>,
+    predefined_index: Option<usize>,
+    /// 1 to replace `$` by `.`, 2 to drop the first and last character of a descriptor and replace `$` by `.`.
+    operation: i32,
+    substring_index: Vec<i32>,
+    replace_char: Vec<i32>,
+}
+
+/// Resolves the names that the messages refer to by index, with the string table of the metadata and the strings of
+/// `d2`.
+pub(super) struct NameResolver<'a> {
+    strings: &'a [String],
+    /// The record of each index, after expanding their ranges.
+    records: Vec<Record>,
+}
+
+impl<'a> NameResolver<'a> {
+    /// Reads a `StringTableTypes` message.
+    pub(super) fn new(
+        string_table: &Message,
+        strings: &'a [String],
+    ) -> Result<NameResolver<'a>, ClassFileError> {
+        let mut records = Vec::new();
+        for record in string_table.messages(1)? {
+            let range = record.int(1).unwrap_or(1).max(0) as usize;
+            let record = Record {
+                string: record.string(6)?,
+                predefined_index: record.int(2).map(|index| index as usize),
+                operation: record.int(3).unwrap_or(0),
+                substring_index: record.ints(4)?,
+                replace_char: record.ints(5)?,
+            };
+            records.extend(std::iter::repeat_n(record, range));
+        }
+        Ok(NameResolver { strings, records })
+    }
+
+    pub(super) fn string(&self, index: i32) -> Result<String, ClassFileError> {
+        let Some(position) = usize::try_from(index).ok() else {
+            error!("negative string index; index={index}");
+            return Err(ClassFileError::InvalidKotlinMetadata);
+        };
+        let in_strings = || match self.strings.get(position) {
+            Some(string) => Ok(string.clone()),
+            None => {
+                error!(
+                    "string index out of bounds; index={index}, size={}",
+                    self.strings.len()
+                );
+                Err(ClassFileError::InvalidKotlinMetadata)
+            }
+        };
+        let Some(record) = self.records.get(position) else {
+            return in_strings();
+        };
+        let mut string = match (&record.string, record.predefined_index) {
+            (Some(string), _) => string.clone(),
+            (None, Some(predefined)) if predefined < PREDEFINED_STRINGS.len() => {
+                PREDEFINED_STRINGS[predefined].to_string()
+            }
+            _ => in_strings()?,
+        };
+        if let [begin, end, ..] = record.substring_index[..]
+            && 0 <= begin
+            && begin <= end
+            && end as usize <= string.chars().count()
+        {
+            string = string
+                .chars()
+                .skip(begin as usize)
+                .take((end - begin) as usize)
+                .collect();
+        }
+        if let [from, to, ..] = record.replace_char[..]
+            && let (Some(from), Some(to)) = (char::from_u32(from as u32), char::from_u32(to as u32))
+        {
+            string = string.replace(from, &to.to_string());
+        }
+        match record.operation {
+            1 => string = string.replace('$', "."),
+            2 => {
+                if string.chars().count() >= 2 {
+                    let mut chars = string.chars();
+                    chars.next();
+                    chars.next_back();
+                    string = chars.as_str().to_string();
+                }
+                string = string.replace('$', ".");
+            }
+            _ => {}
+        }
+        Ok(string)
+    }
+}
@@ -0,0 +1,205 @@
+//! The wire format of protocol buffers, which Kotlin metadata is serialized in: a message is read into its fields, so
+//! that they can be looked up by number in whatever order they were written.
+
+use std::result::Result;
+
+use log::error;
+
+use crate::errors::ClassFileError;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    /// A `fixed32`, `fixed64`, `float` or `double`, which Kotlin metadata only has in fields that aren't decoded.
+    Fixed,
+}
+
+/// The fields of a message in the order they were read. Fields that are repeated appear once per value.
+#[derive(Clone, Debug)]
+pub(super) struct Message<'a> {
+    fields: Vec<(u32, Value<'a>)>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    idx: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], ClassFileError> {
+        let end = self.idx.checked_add(count);
+        match end.and_then(|end| self.data.get(self.idx..end)) {
+            Some(bytes) => {
+                self.idx += count;
+                Ok(bytes)
+            }
+            None => {
+                error!(
+                    "kotlin metadata ends unexpectedly; offset={}, length={count}",
+                    self.idx
+                );
+                Err(ClassFileError::InvalidKotlinMetadata)
+            }
+        }
+    }
+
+    fn varint(&mut self) -> Result<u64, ClassFileError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.bytes(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        error!("varint is too long; offset={}", self.idx);
+        Err(ClassFileError::InvalidKotlinMetadata)
+    }
+
+    fn length(&mut self) -> Result<usize, ClassFileError> {
+        let length = self.varint()?;
+        usize::try_from(length).map_err(|_| {
+            error!("length is too large; length={length}");
+            ClassFileError::InvalidKotlinMetadata
+        })
+    }
+
+    fn message(&mut self) -> Result<Message<'a>, ClassFileError> {
+        let mut fields = Vec::new();
+        while self.idx < self.data.len() {
+            let key = self.varint()?;
+            let number = u32::try_from(key >> 3).map_err(|_| {
+                error!("field number is too large; key={key}");
+                ClassFileError::InvalidKotlinMetadata
+            })?;
+            let value = match key & 7 {
+                0 => Value::Varint(self.varint()?),
+                1 => {
+                    self.bytes(8)?;
+                    Value::Fixed
+                }
+                2 => {
+                    let length = self.length()?;
+                    Value::Bytes(self.bytes(length)?)
+                }
+                5 => {
+                    self.bytes(4)?;
+                    Value::Fixed
+                }
+                wire_type => {
+                    error!("unsupported wire type; wire_type={wire_type}, field={number}");
+                    return Err(ClassFileError::InvalidKotlinMetadata);
+                }
+            };
+            fields.push((number, value));
+        }
+        Ok(Message { fields })
+    }
+}
+
+/// Reads a message that is preceded by its length, as Kotlin writes the string table before the declarations, and
+/// returns it with the bytes that follow it.
+pub(super) fn parse_delimited(data: &[u8]) -> Result<(Message<'_>, &[u8]), ClassFileError> {
+    let mut reader = Reader { data, idx: 0 };
+    let length = reader.length()?;
+    let message = reader.bytes(length)?;
+    Ok((parse(message)?, &data[reader.idx..]))
+}
+
+pub(super) fn parse(data: &[u8]) -> Result<Message<'_>, ClassFileError> {
+    Reader { data, idx: 0 }.message()
+}
+
+impl<'a> Message<'a> {
+    fn values(&self, number: u32) -> impl Iterator<Item = Value<'a>> + '_ {
+        self.fields
+            .iter()
+            .filter(move |(field, _)| *field == number)
+            .map(|(_, value)| *value)
+    }
+
+    /// The last value of a field of a scalar type, as later values replace earlier ones.
+    fn varint(&self, number: u32) -> Option<u64> {
+        self.values(number)
+            .filter_map(|value| match value {
+                Value::Varint(value) => Some(value),
+                _ => None,
+            })
+            .last()
+    }
+
+    /// An `int32` or enum field, which negative values are written to as 64 bits.
+    pub(super) fn int(&self, number: u32) -> Option<i32> {
+        self.varint(number).map(|value| value as i32)
+    }
+
+    pub(super) fn bool(&self, number: u32) -> bool {
+        self.varint(number).is_some_and(|value| value != 0)
+    }
+
+    /// The values of a repeated `int32` field, which may be packed into one value or written one by one.
+    pub(super) fn ints(&self, number: u32) -> Result<Vec<i32>, ClassFileError> {
+        let mut ints = Vec::new();
+        for value in self.values(number) {
+            match value {
+                Value::Varint(value) => ints.push(value as i32),
+                Value::Bytes(bytes) => {
+                    let mut reader = Reader {
+                        data: bytes,
+                        idx: 0,
+                    };
+                    while reader.idx < bytes.len() {
+                        ints.push(reader.varint()? as i32);
+                    }
+                }
+                _ => {
+                    error!("unexpected wire type of integers; field={number}");
+                    return Err(ClassFileError::InvalidKotlinMetadata);
+                }
+            }
+        }
+        Ok(ints)
+    }
+
+    pub(super) fn string(&self, number: u32) -> Result<Option<String>, ClassFileError> {
+        match self.bytes(number)? {
+            Some(bytes) => match String::from_utf8(bytes.to_vec()) {
+                Ok(string) => Ok(Some(string)),
+                Err(_) => {
+                    error!("invalid UTF-8 in string; field={number}");
+                    Err(ClassFileError::InvalidKotlinMetadata)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn bytes(&self, number: u32) -> Result<Option<&'a [u8]>, ClassFileError> {
+        match self.values(number).last() {
+            Some(Value::Bytes(bytes)) => Ok(Some(bytes)),
+            Some(_) => {
+                error!("unexpected wire type of bytes; field={number}");
+                Err(ClassFileError::InvalidKotlinMetadata)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// A field of a message type. Kotlin never writes one twice, so they aren't merged.
+    pub(super) fn message(&self, number: u32) -> Result<Option<Message<'a>>, ClassFileError> {
+        self.bytes(number)?.map(parse).transpose()
+    }
+
+    pub(super) fn messages(&self, number: u32) -> Result<Vec<Message<'a>>, ClassFileError> {
+        self.values(number)
+            .map(|value| match value {
+                Value::Bytes(bytes) => parse(bytes),
+                _ => {
+                    error!("unexpected wire type of message; field={number}");
+                    Err(ClassFileError::InvalidKotlinMetadata)
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,397 @@
+//! Printing Kotlin declarations as Kotlin-like source without bodies, with the types qualified by their package.
+
+use super::types::{
+    Class, ClassKind, Constructor, Declarations, Function, JvmSignature, Modality, Package,
+    Property, TypeAlias, TypeParameter, ValueParameter,
+};
+
+const INDENT: &str = "    ";
+
+/// The simple name of a class in its package, e.g. `Map.Entry` for `kotlin/collections/Map.Entry`.
+fn simple_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+fn package_of(name: &str) -> Option<String> {
+    name.rsplit_once('/')
+        .map(|(package, _)| package.replace('/', "."))
+}
+
+/// The type parameters between angle brackets, with a single upper bound, and the `where` clause of those with more.
+fn type_parameters(type_parameters: &[TypeParameter]) -> (String, String) {
+    if type_parameters.is_empty() {
+        return (String::new(), String::new());
+    }
+    let mut constraints = Vec::new();
+    let declared: Vec<String> = type_parameters
+        .iter()
+        .map(|type_parameter| {
+            let mut declared = String::new();
+            if type_parameter.is_reified {
+                declared.push_str("reified ");
+            }
+            declared.push_str(&format!(
+                "{}{}",
+                type_parameter.variance, type_parameter.name
+            ));
+            match &type_parameter.upper_bounds[..] {
+                [] => {}
+                [bound] => declared.push_str(&format!(" : {bound}")),
+                bounds => constraints.extend(
+                    bounds
+                        .iter()
+                        .map(|bound| format!("{} : {bound}", type_parameter.name)),
+                ),
+            }
+            declared
+        })
+        .collect();
+    let clause = match constraints.is_empty() {
+        true => String::new(),
+        false => format!(" where {}", constraints.join(", ")),
+    };
+    (format!("<{}>", declared.join(", ")), clause)
+}
+
+fn value_parameters(parameters: &[ValueParameter]) -> String {
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|parameter| {
+            let mut printed = String::new();
+            if parameter.is_crossinline {
+                printed.push_str("crossinline ");
+            }
+            if parameter.is_noinline {
+                printed.push_str("noinline ");
+            }
+            match &parameter.vararg_element_type {
+                Some(element_type) => {
+                    printed.push_str(&format!("vararg {}: {element_type}", parameter.name))
+                }
+                None => {
+                    printed.push_str(&format!("{}: {}", parameter.name, parameter.parameter_type))
+                }
+            }
+            if parameter.declares_default_value {
+                printed.push_str(" = ...");
+            }
+            printed
+        })
+        .collect();
+    format!("({})", parameters.join(", "))
+}
+
+/// The modality of a member, which goes without saying when it is final.
+fn modality(modality: Modality) -> String {
+    match modality {
+        Modality::Final => String::new(),
+        modality => format!("{modality} "),
+    }
+}
+
+struct Printer {
+    out: String,
+    indent: usize,
+    /// Whether to follow declarations by the JVM methods and fields that they compile to.
+    jvm_signatures: bool,
+}
+
+impl Printer {
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            self.out.push_str(&INDENT.repeat(self.indent));
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    /// Prints a declaration with the JVM members that it compiles to as a comment.
+    fn declaration(&mut self, declaration: String, signatures: &[(&str, &Option<JvmSignature>)]) {
+        let signatures: Vec<String> = signatures
+            .iter()
+            .filter_map(|(kind, signature)| {
+                signature
+                    .as_ref()
+                    .map(|signature| format!("{kind}{signature}"))
+            })
+            .collect();
+        match self.jvm_signatures && !signatures.is_empty() {
+            true => self.line(&format!("{declaration} // {}", signatures.join(", "))),
+            false => self.line(&declaration),
+        }
+    }
+
+    fn constructor(&mut self, constructor: &Constructor) {
+        let declaration = format!(
+            "{} constructor{}",
+            constructor.visibility,
+            value_parameters(&constructor.parameters)
+        );
+        self.declaration(declaration, &[("", &constructor.signature)]);
+    }
+
+    fn function(&mut self, function: &Function) {
+        let mut declaration = format!("{} ", function.visibility);
+        for (set, modifier) in [
+            (function.is_expect, "expect "),
+            (true, modality(function.modality).as_str()),
+            (function.is_external, "external "),
+            (function.is_tailrec, "tailrec "),
+            (function.is_suspend, "suspend "),
+            (function.is_inline, "inline "),
+            (function.is_infix, "infix "),
+            (function.is_operator, "operator "),
+        ] {
+            if set {
+                declaration.push_str(modifier);
+            }
+        }
+        declaration.push_str("fun ");
+        let (type_parameters, constraints) = type_parameters(&function.type_parameters);
+        if !type_parameters.is_empty() {
+            declaration.push_str(&format!("{type_parameters} "));
+        }
+        if let Some(receiver) = &function.receiver {
+            declaration.push_str(&format!("{receiver}."));
+        }
+        declaration.push_str(&function.name);
+        declaration.push_str(&value_parameters(&function.parameters));
+        let return_type = function.return_type.to_string();
+        if return_type != "kotlin.Unit" {
+            declaration.push_str(&format!(": {return_type}"));
+        }
+        declaration.push_str(&constraints);
+        self.declaration(declaration, &[("", &function.signature)]);
+    }
+
+    fn property(&mut self, property: &Property) {
+        let mut declaration = format!("{} ", property.visibility);
+        for (set, modifier) in [
+            (property.is_expect, "expect "),
+            (true, modality(property.modality).as_str()),
+            (property.is_const, "const "),
+            (property.is_external, "external "),
+            (property.is_lateinit, "lateinit "),
+        ] {
+            if set {
+                declaration.push_str(modifier);
+            }
+        }
+        declaration.push_str(match property.is_var {
+            true => "var ",
+            false => "val ",
+        });
+        let (type_parameters, constraints) = type_parameters(&property.type_parameters);
+        if !type_parameters.is_empty() {
+            declaration.push_str(&format!("{type_parameters} "));
+        }
+        if let Some(receiver) = &property.receiver {
+            declaration.push_str(&format!("{receiver}."));
+        }
+        declaration.push_str(&format!("{}: {}", property.name, property.return_type));
+        declaration.push_str(&constraints);
+        if property.is_delegated {
+            declaration.push_str(" by ...");
+        }
+        self.declaration(
+            declaration,
+            &[
+                ("field ", &property.field),
+                ("getter ", &property.getter),
+                ("setter ", &property.setter),
+            ],
+        );
+    }
+
+    fn type_alias(&mut self, type_alias: &TypeAlias) {
+        let (type_parameters, _) = type_parameters(&type_alias.type_parameters);
+        self.line(&format!(
+            "{} typealias {}{type_parameters} = {}",
+            type_alias.visibility, type_alias.name, type_alias.underlying_type
+        ));
+    }
+
+    /// Prints the members of a package or class, with a blank line between kinds of members.
+    fn members(
+        &mut self,
+        functions: &[Function],
+        properties: &[Property],
+        type_aliases: &[TypeAlias],
+    ) {
+        let mut first = true;
+        let mut separate = |printer: &mut Printer, empty: bool| {
+            if !empty && !first {
+                printer.line("");
+            }
+            first &= empty;
+        };
+        separate(self, type_aliases.is_empty());
+        for type_alias in type_aliases {
+            self.type_alias(type_alias);
+        }
+        separate(self, properties.is_empty());
+        for property in properties {
+            self.property(property);
+        }
+        separate(self, functions.is_empty());
+        for function in functions {
+            self.function(function);
+        }
+    }
+
+    fn package(&mut self, package: &Package) {
+        self.members(
+            &package.functions,
+            &package.properties,
+            &package.type_aliases,
+        );
+    }
+
+    fn class(&mut self, class: &Class) {
+        let mut header = format!("{} ", class.visibility);
+        let implicit_modality = match class.kind {
+            ClassKind::Interface => Modality::Abstract,
+            _ => Modality::Final,
+        };
+        for (set, modifier) in [
+            (class.is_expect, "expect ".to_string()),
+            (
+                class.modality != implicit_modality,
+                format!("{} ", class.modality),
+            ),
+            (class.is_external, "external ".to_string()),
+            (class.is_inner, "inner ".to_string()),
+            (class.is_data, "data ".to_string()),
+            (class.is_value, "value ".to_string()),
+            (class.is_fun, "fun ".to_string()),
+        ] {
+            if set {
+                header.push_str(&modifier);
+            }
+        }
+        header.push_str(match class.kind {
+            ClassKind::Class => "class",
+            ClassKind::Interface => "interface",
+            ClassKind::EnumClass => "enum class",
+            ClassKind::EnumEntry => "enum entry",
+            ClassKind::AnnotationClass => "annotation class",
+            ClassKind::Object => "object",
+            ClassKind::CompanionObject => "companion object",
+        });
+        header.push_str(&format!(" {}", simple_name(&class.name)));
+        let (type_parameters, constraints) = type_parameters(&class.type_parameters);
+        header.push_str(&type_parameters);
+        // The supertypes that every class, enum class or annotation class has go without saying.
+        let implicit_supertype = match class.kind {
+            ClassKind::EnumClass => format!("kotlin.Enum<{}>", class.name.replace('/', ".")),
+            ClassKind::AnnotationClass => "kotlin.Annotation".to_string(),
+            _ => "kotlin.Any".to_string(),
+        };
+        let supertypes: Vec<String> = class
+            .supertypes
+            .iter()
+            .map(ToString::to_string)
+            .filter(|supertype| *supertype != implicit_supertype)
+            .collect();
+        if !supertypes.is_empty() {
+            header.push_str(&format!(" : {}", supertypes.join(", ")));
+        }
+        header.push_str(&constraints);
+        self.line(&format!("{header} {{"));
+        self.indent += 1;
+
+        let mut sections = 0;
+        if !class.enum_entries.is_empty() {
+            self.line(&format!("{};", class.enum_entries.join(", ")));
+            sections += 1;
+        }
+        if !class.constructors.is_empty() {
+            if sections > 0 {
+                self.line("");
+            }
+            for constructor in &class.constructors {
+                self.constructor(constructor);
+            }
+            sections += 1;
+        }
+        let has_members = !(class.functions.is_empty()
+            && class.properties.is_empty()
+            && class.type_aliases.is_empty());
+        if has_members {
+            if sections > 0 {
+                self.line("");
+            }
+            self.members(&class.functions, &class.properties, &class.type_aliases);
+            sections += 1;
+        }
+        let mut nested = Vec::new();
+        if let Some(companion_object) = &class.companion_object {
+            nested.push(format!("// companion object {companion_object}"));
+        }
+        for (names, kind) in [
+            (&class.nested_classes, "nested classes"),
+            (&class.sealed_subclasses, "sealed subclasses"),
+        ] {
+            if !names.is_empty() {
+                let names: Vec<String> = names.iter().map(|name| name.replace('/', ".")).collect();
+                nested.push(format!("// {kind}: {}", names.join(", ")));
+            }
+        }
+        if !nested.is_empty() {
+            if sections > 0 {
+                self.line("");
+            }
+            for line in nested {
+                self.line(&line);
+            }
+        }
+        self.indent -= 1;
+        self.line("}");
+    }
+}
+
+/// Prints the declarations of a class, whose name in its internal form gives the package of top-level declarations.
+pub(super) fn print(class_name: &str, declarations: &Declarations, jvm_signatures: bool) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        indent: 0,
+        jvm_signatures,
+    };
+    let name = match declarations {
+        Declarations::Class(class) => &class.name,
+        _ => class_name,
+    };
+    if let Some(package) = package_of(name) {
+        printer.line(&format!("package {package}"));
+        printer.line("");
+    }
+    let simple_class_name = simple_name(class_name);
+    match declarations {
+        Declarations::Class(class) => printer.class(class),
+        Declarations::File(package) => {
+            printer.line(&format!("// file facade {simple_class_name}"));
+            printer.package(package);
+        }
+        Declarations::SyntheticClass(lambda) => {
+            printer.line(&format!("// synthetic class {simple_class_name}"));
+            if let Some(lambda) = lambda {
+                printer.function(lambda);
+            }
+        }
+        Declarations::MultiFileClassFacade(parts) => {
+            let parts: Vec<&str> = parts.iter().map(|part| simple_name(part)).collect();
+            printer.line(&format!(
+                "// multi-file class facade {simple_class_name} of {}",
+                parts.join(", ")
+            ));
+        }
+        Declarations::MultiFileClassPart { facade, package } => {
+            printer.line(&format!(
+                "// multi-file class part {simple_class_name} of {}",
+                simple_name(facade)
+            ));
+            printer.package(package);
+        }
+    }
+    printer.out
+}
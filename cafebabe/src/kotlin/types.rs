@@ -0,0 +1,347 @@
+use std::fmt;
+
+/// The values of a `@kotlin.Metadata` annotation, as the Kotlin compiler writes them to every class it compiles.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Metadata {
+    /// `k`: 1 for a class, 2 for a file, 3 for a synthetic class, 4 for the facade of a multi-file class and 5 for one
+    /// of its parts.
+    pub kind: i32,
+    /// `mv`: the version of the metadata format, e.g. `[1, 8, 0]`.
+    pub version: Vec<i32>,
+    /// `d1`: the declarations serialized as protocol buffers and stored in strings, or the names of the parts of a
+    /// multi-file class facade.
+    pub data1: Vec<String>,
+    /// `d2`: the strings that the declarations refer to.
+    pub data2: Vec<String>,
+    /// `xs`: the name of the facade of a multi-file class part.
+    pub extra_string: Option<String>,
+    /// `pn`: the Kotlin package of the declarations, where it differs from the package of the class.
+    pub package_name: Option<String>,
+    /// `xi`: the flags of the class, e.g. 2 for a class that was compiled with pre-release features.
+    pub extra_int: i32,
+}
+
+/// The Kotlin declarations of a class, by the kind of the class in its [`Metadata`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Declarations {
+    Class(Class),
+    /// The top-level declarations of a source file, e.g. of `Strings.kt` in `StringsKt`.
+    File(Package),
+    /// A class that the compiler generated, with the function that it implements if it is a lambda.
+    SyntheticClass(Option<Function>),
+    /// The class that joins the top-level declarations of files with the same `@JvmName`, by the names of the classes
+    /// of its parts.
+    MultiFileClassFacade(Vec<String>),
+    /// The top-level declarations of one of the files of a multi-file class, with the name of its facade.
+    MultiFileClassPart {
+        facade: String,
+        package: Package,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Visibility {
+    Internal,
+    Private,
+    Protected,
+    Public,
+    /// Private to the instance, for members of classes with type parameters that are only `in` or `out`.
+    PrivateToThis,
+    Local,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Modality {
+    Final,
+    Open,
+    Abstract,
+    Sealed,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ClassKind {
+    Class,
+    Interface,
+    EnumClass,
+    EnumEntry,
+    AnnotationClass,
+    Object,
+    CompanionObject,
+}
+
+/// Where a member comes from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MemberKind {
+    Declaration,
+    FakeOverride,
+    Delegation,
+    /// Generated by the compiler, e.g. `copy` and the `componentN` functions of data classes.
+    Synthesized,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Variance {
+    In,
+    Out,
+    Invariant,
+}
+
+/// The name and descriptor of the JVM method or field that a Kotlin declaration compiles to. Without a descriptor,
+/// the compiler leaves it to be derived from the Kotlin types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct JvmSignature {
+    pub name: String,
+    pub descriptor: Option<String>,
+}
+
+impl fmt::Display for JvmSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        match &self.descriptor {
+            Some(descriptor) if descriptor.starts_with('(') => write!(f, "{descriptor}"),
+            Some(descriptor) => write!(f, ":{descriptor}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Class {
+    /// The name in the form `kotlin/collections/Map.Entry`, with dots between nested classes.
+    pub name: String,
+    pub visibility: Visibility,
+    pub modality: Modality,
+    pub kind: ClassKind,
+    pub is_inner: bool,
+    pub is_data: bool,
+    pub is_external: bool,
+    pub is_expect: bool,
+    pub is_value: bool,
+    pub is_fun: bool,
+    pub type_parameters: Vec<TypeParameter>,
+    pub supertypes: Vec<Type>,
+    pub constructors: Vec<Constructor>,
+    pub functions: Vec<Function>,
+    pub properties: Vec<Property>,
+    pub type_aliases: Vec<TypeAlias>,
+    /// The simple names of the nested classes.
+    pub nested_classes: Vec<String>,
+    pub enum_entries: Vec<String>,
+    pub sealed_subclasses: Vec<String>,
+    /// The simple name of the companion object.
+    pub companion_object: Option<String>,
+}
+
+/// The top-level declarations of a file or multi-file class part.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Package {
+    pub functions: Vec<Function>,
+    pub properties: Vec<Property>,
+    pub type_aliases: Vec<TypeAlias>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Constructor {
+    pub visibility: Visibility,
+    pub is_secondary: bool,
+    pub parameters: Vec<ValueParameter>,
+    pub signature: Option<JvmSignature>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Function {
+    pub name: String,
+    pub visibility: Visibility,
+    pub modality: Modality,
+    pub member_kind: MemberKind,
+    pub is_operator: bool,
+    pub is_infix: bool,
+    pub is_inline: bool,
+    pub is_tailrec: bool,
+    pub is_external: bool,
+    pub is_suspend: bool,
+    pub is_expect: bool,
+    pub type_parameters: Vec<TypeParameter>,
+    /// The receiver type of an extension function.
+    pub receiver: Option<Type>,
+    pub parameters: Vec<ValueParameter>,
+    pub return_type: Type,
+    pub signature: Option<JvmSignature>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Property {
+    pub name: String,
+    pub visibility: Visibility,
+    pub modality: Modality,
+    pub member_kind: MemberKind,
+    pub is_var: bool,
+    pub is_const: bool,
+    pub is_lateinit: bool,
+    pub is_delegated: bool,
+    pub is_external: bool,
+    pub is_expect: bool,
+    pub type_parameters: Vec<TypeParameter>,
+    /// The receiver type of an extension property.
+    pub receiver: Option<Type>,
+    pub return_type: Type,
+    /// The parameter of a setter that names it.
+    pub setter_parameter: Option<ValueParameter>,
+    /// The backing field, if it has one.
+    pub field: Option<JvmSignature>,
+    pub getter: Option<JvmSignature>,
+    pub setter: Option<JvmSignature>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ValueParameter {
+    pub name: String,
+    pub parameter_type: Type,
+    /// The type of the elements of a `vararg` parameter, whose type is an array of them.
+    pub vararg_element_type: Option<Type>,
+    pub declares_default_value: bool,
+    pub is_crossinline: bool,
+    pub is_noinline: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TypeParameter {
+    /// The number that types refer to the type parameter by, unique within the declarations of a class.
+    pub id: i32,
+    pub name: String,
+    pub is_reified: bool,
+    pub variance: Variance,
+    pub upper_bounds: Vec<Type>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TypeAlias {
+    pub name: String,
+    pub visibility: Visibility,
+    pub type_parameters: Vec<TypeParameter>,
+    pub underlying_type: Type,
+    pub expanded_type: Type,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Classifier {
+    /// A class by its name in the form `kotlin/collections/Map.Entry`.
+    Class(String),
+    TypeParameter(String),
+    TypeAlias(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TypeProjection {
+    /// `*`
+    Star,
+    Type(Variance, Type),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Type {
+    pub classifier: Classifier,
+    pub arguments: Vec<TypeProjection>,
+    pub is_nullable: bool,
+    pub is_suspend: bool,
+    /// `T & Any`
+    pub is_definitely_non_null: bool,
+    /// The nullable upper bound of a type from Java, whose nullability isn't known, e.g. `String!`.
+    pub flexible_upper_bound: Option<Box<Type>>,
+    /// The type alias that the type was written as.
+    pub abbreviated_type: Option<Box<Type>>,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let visibility = match self {
+            Visibility::Internal => "internal",
+            Visibility::Private | Visibility::PrivateToThis => "private",
+            Visibility::Protected => "protected",
+            Visibility::Public => "public",
+            Visibility::Local => "local",
+        };
+        write!(f, "{visibility}")
+    }
+}
+
+impl fmt::Display for Modality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modality = match self {
+            Modality::Final => "final",
+            Modality::Open => "open",
+            Modality::Abstract => "abstract",
+            Modality::Sealed => "sealed",
+        };
+        write!(f, "{modality}")
+    }
+}
+
+impl fmt::Display for Variance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variance::In => write!(f, "in "),
+            Variance::Out => write!(f, "out "),
+            Variance::Invariant => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for TypeProjection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeProjection::Star => write!(f, "*"),
+            TypeProjection::Type(variance, projected) => write!(f, "{variance}{projected}"),
+        }
+    }
+}
+
+/// Types are written as in Kotlin, with the names of classes qualified by their package, e.g.
+/// `kotlin.collections.List<kotlin.String?>`.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(abbreviated_type) = &self.abbreviated_type {
+            return write!(f, "{abbreviated_type}");
+        }
+        if self.is_suspend {
+            write!(f, "suspend ")?;
+        }
+        match &self.classifier {
+            Classifier::Class(name) | Classifier::TypeAlias(name) => {
+                write!(f, "{}", name.replace('/', "."))?
+            }
+            Classifier::TypeParameter(name) => write!(f, "{name}")?,
+        }
+        if !self.arguments.is_empty() {
+            let arguments: Vec<String> = self.arguments.iter().map(ToString::to_string).collect();
+            write!(f, "<{}>", arguments.join(", "))?;
+        }
+        if self.is_nullable {
+            write!(f, "?")?;
+        } else if self.flexible_upper_bound.is_some() {
+            write!(f, "!")?;
+        } else if self.is_definitely_non_null {
+            write!(f, " & Any")?;
+        }
+        Ok(())
+    }
+}
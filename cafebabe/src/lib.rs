@@ -1,26 +1,52 @@
+pub mod archive;
+pub mod assembler;
+pub mod attributes;
+pub mod bootstrap;
+pub mod bytecode;
+pub mod call_graph;
+pub mod class_path;
+pub mod compatibility;
 pub mod constant_pool;
+pub mod control_flow;
+pub mod data_flow;
+pub mod dead_code;
+pub mod decompiler;
+pub mod dependencies;
+pub mod descriptor;
 mod errors;
+pub mod format_check;
+pub mod hierarchy;
+pub mod kotlin;
+mod members;
+pub mod module;
+pub mod remapper;
+pub mod shade;
 mod shared;
+pub mod strip;
 mod types;
 mod version;
 
 use std::result::Result;
 
+use crate::attributes::{Attribute, signature_idx};
 use crate::constant_pool::ConstantPool;
-use crate::shared::to_u16;
-use errors::ClassFileError;
-pub use types::{AccessFlags, ClassDefinition, ClassFile};
+use crate::shared::{to_u16, to_u32, write_u16, write_u32};
+pub use errors::ClassFileError;
+pub use types::{
+    AccessFlags, ClassDefinition, ClassFile, FieldAccessFlags, FieldInfo, MethodAccessFlags,
+    MethodInfo, Parameter,
+};
+pub use version::{JavaRelease, PREVIEW_MINOR_VERSION, ParseOptions, Strictness};
 
 const CAFEBABE: u32 = u32::from_be_bytes([0xca, 0xfe, 0xba, 0xbe]);
 
 fn read_magic_number(data: &[u8]) -> Result<(), ClassFileError> {
-    let bytes: [u8; 4] = data[0..4].try_into().expect("slice with incorrect length");
-    let magic_number = u32::from_be_bytes(bytes);
+    let magic_number = to_u32(data, 0, 3)?;
 
     match magic_number {
         CAFEBABE => Ok(()),
         _ => {
-            dbg!(data[0..4].to_vec());
+            dbg!(magic_number);
             Err(ClassFileError::InvalidMagicNumber)
         }
     }
@@ -30,7 +56,7 @@ fn read_access_flags(
     data: &[u8],
     start_idx: usize,
 ) -> Result<(AccessFlags, usize), ClassFileError> {
-    let access_flags = to_u16(data, start_idx, start_idx + 1);
+    let access_flags = to_u16(data, start_idx, start_idx + 1)?;
     match AccessFlags::from_bits(access_flags) {
         Some(access_flags) => Ok((access_flags, start_idx + 2)),
         None => Err(ClassFileError::InvalidAccessFlags),
@@ -47,7 +73,7 @@ fn read_interfaces(
     let mut current_idx = start_idx;
 
     while current_idx < end_idx {
-        let iface_name_idx = u16::from_be_bytes([data[current_idx], data[current_idx + 1]]);
+        let iface_name_idx = to_u16(data, current_idx, current_idx + 1)?;
         let class_name_idx = constant_pool.class_ref_entry(iface_name_idx as usize)? as usize;
 
         result.push(class_name_idx);
@@ -63,9 +89,9 @@ fn read_class_definition(
     start_idx: usize,
     constant_pool: &ConstantPool,
 ) -> Result<(ClassDefinition, usize), ClassFileError> {
-    let this_idx = u16::from_be_bytes([data[start_idx], data[start_idx + 1]]);
-    let super_idx = u16::from_be_bytes([data[start_idx + 2], data[start_idx + 3]]);
-    let interface_count = u16::from_be_bytes([data[start_idx + 4], data[start_idx + 5]]);
+    let this_idx = to_u16(data, start_idx, start_idx + 1)?;
+    let super_idx = to_u16(data, start_idx + 2, start_idx + 3)?;
+    let interface_count = to_u16(data, start_idx + 4, start_idx + 5)?;
     let interface_end_idx = start_idx + 6usize + (2usize * interface_count as usize);
     let interfaces = read_interfaces(data, start_idx + 6, interface_end_idx, constant_pool);
 
@@ -74,19 +100,154 @@ fn read_class_definition(
         super_idx: super_idx as usize,
         interfaces: interfaces?,
     };
-    Ok((class_definition, start_idx + 4))
+    Ok((class_definition, interface_end_idx))
 }
 
+/// Reads a class file with the default [`ParseOptions`], which accept all releases up to [`JavaRelease::LATEST`]
+/// but no preview class files.
 pub fn read_class_data(data: &[u8]) -> Result<ClassFile, ClassFileError> {
+    read_class_data_with_options(data, &ParseOptions::default())
+}
+
+/// Reads a class file, checking its version against the options. With [`Strictness::Strict`], it also checks the
+/// format of the class file, see [`format_check`], and fails with all diagnostics.
+pub fn read_class_data_with_options(
+    data: &[u8],
+    options: &ParseOptions,
+) -> Result<ClassFile, ClassFileError> {
+    let (class_file, end_idx) = read_class(data, options)?;
+    if options.strictness == Strictness::Strict {
+        let diagnostics = format_check::check(&class_file, data, end_idx);
+        if !diagnostics.is_empty() {
+            return Err(ClassFileError::FormatViolations(diagnostics));
+        }
+    }
+    Ok(class_file)
+}
+
+/// Reads a class file, returning it with the index at which its attributes end.
+fn read_class(data: &[u8], options: &ParseOptions) -> Result<(ClassFile, usize), ClassFileError> {
     read_magic_number(data)?;
+    let version = version::read_version(data, options)?;
     let constant_pool = constant_pool::read_constant_pool(data)?;
     let access_flags = read_access_flags(data, constant_pool.1)?;
     let class_definition = read_class_definition(data, access_flags.1, &constant_pool.0)?;
+    let fields = members::read_fields(data, class_definition.1, &constant_pool.0)?;
+    let methods = members::read_methods(data, fields.1, &constant_pool.0)?;
+    let attributes = attributes::read_attributes(data, methods.1, &constant_pool.0)?;
 
-    Ok(ClassFile {
-        version: version::read_version(data)?,
+    let class_file = ClassFile {
+        version,
         constant_pool: constant_pool.0,
         access_flags: access_flags.0,
         class: class_definition.0,
-    })
+        fields: fields.0,
+        methods: methods.0,
+        attributes: attributes.0,
+    };
+    Ok((class_file, attributes.1))
+}
+
+fn write_class_definition(
+    class_definition: &ClassDefinition,
+    constant_pool: &ConstantPool,
+    out: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    write_u16(out, class_definition.this_idx as u16);
+    write_u16(out, class_definition.super_idx as u16);
+    write_u16(out, class_definition.interfaces.len() as u16);
+    for name_idx in &class_definition.interfaces {
+        // The class definition holds the names of the interfaces, while the class file refers to their ClassRef entries.
+        match constant_pool.find_class_ref(*name_idx as u16) {
+            Some(class_ref) => write_u16(out, class_ref),
+            None => {
+                dbg!(name_idx);
+                return Err(ClassFileError::InvalidConstantPoolContent);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes a class file, the counterpart of [`read_class_data`].
+///
+/// Reading a class file and writing it again yields the same bytes, as long as its constant pool holds each attribute
+/// name and each ClassRef of an interface only once.
+pub fn write_class_data(class_file: &ClassFile) -> Result<Vec<u8>, ClassFileError> {
+    let mut out = Vec::new();
+    write_u32(&mut out, CAFEBABE);
+    write_u16(&mut out, class_file.version.minor);
+    write_u16(&mut out, class_file.version.major);
+    constant_pool::write_constant_pool(&class_file.constant_pool, &mut out)?;
+    write_u16(&mut out, class_file.access_flags.bits());
+    write_class_definition(&class_file.class, &class_file.constant_pool, &mut out)?;
+    members::write_fields(&class_file.fields, &class_file.constant_pool, &mut out)?;
+    members::write_methods(&class_file.methods, &class_file.constant_pool, &mut out)?;
+    attributes::write_attributes(&class_file.attributes, &class_file.constant_pool, &mut out)?;
+    Ok(out)
+}
+
+impl ClassFile {
+    /// Whether the class file depends on the preview features of its release.
+    pub fn is_preview(&self) -> bool {
+        self.version.major >= version::FIRST_PREVIEW_MAJOR_VERSION
+            && self.version.minor == PREVIEW_MINOR_VERSION
+    }
+
+    /// The release of the Java platform that the class file was compiled for, if it is known.
+    pub fn release(&self) -> Option<JavaRelease> {
+        JavaRelease::from_major(self.version.major)
+    }
+
+    /// The name of the class that is described by this class file.
+    pub fn this_class_name(&self) -> Result<&String, ClassFileError> {
+        self.constant_pool.class_name(self.class.this_idx)
+    }
+
+    /// The name of the direct superclass, or `None` for `java/lang/Object` and `module-info`.
+    pub fn super_class_name(&self) -> Result<Option<&String>, ClassFileError> {
+        match self.class.super_idx {
+            0 => Ok(None),
+            super_idx => Ok(Some(self.constant_pool.class_name(super_idx)?)),
+        }
+    }
+
+    /// The names of the direct superinterfaces.
+    pub fn interface_names(&self) -> Result<Vec<&String>, ClassFileError> {
+        self.class
+            .interfaces
+            .iter()
+            .map(|name_idx| self.constant_pool.string_entry(*name_idx as u16))
+            .collect()
+    }
+
+    /// The method the class declares with a name and descriptor.
+    pub fn declared_method(&self, name: &str, descriptor: &str) -> Option<&MethodInfo> {
+        self.methods.iter().find(|method| {
+            method
+                .name(&self.constant_pool)
+                .is_ok_and(|method_name| method_name == name)
+                && method
+                    .descriptor(&self.constant_pool)
+                    .is_ok_and(|method_descriptor| method_descriptor == descriptor)
+        })
+    }
+
+    /// The name of the source file from which this class was compiled, if recorded.
+    pub fn source_file(&self) -> Result<Option<&String>, ClassFileError> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::SourceFile(name_idx) => Some(self.constant_pool.string_entry(*name_idx)),
+                _ => None,
+            })
+            .transpose()
+    }
+
+    /// The generic signature of the class, if it has one.
+    pub fn signature(&self) -> Result<Option<&String>, ClassFileError> {
+        signature_idx(&self.attributes)
+            .map(|idx| self.constant_pool.string_entry(idx))
+            .transpose()
+    }
 }
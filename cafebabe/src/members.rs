@@ -0,0 +1,267 @@
+use std::result::Result;
+
+use crate::attributes::{Attribute, Code, read_attributes, signature_idx, write_attributes};
+use crate::constant_pool::ConstantPool;
+use crate::errors::ClassFileError;
+use crate::shared::{to_u16, write_u16};
+use crate::types::{FieldAccessFlags, FieldInfo, MethodAccessFlags, MethodInfo, Parameter};
+use log::debug;
+
+fn read_field(
+    data: &[u8],
+    from_idx: usize,
+    constant_pool: &ConstantPool,
+) -> Result<(FieldInfo, usize), ClassFileError> {
+    let access_flags = to_u16(data, from_idx, from_idx + 1)?;
+    let name_idx = to_u16(data, from_idx + 2, from_idx + 3)?;
+    let descriptor_idx = to_u16(data, from_idx + 4, from_idx + 5)?;
+    debug!("found field; name_idx={name_idx}, descriptor_idx={descriptor_idx}");
+
+    let access_flags = match FieldAccessFlags::from_bits(access_flags) {
+        Some(access_flags) => access_flags,
+        None => {
+            dbg!(access_flags);
+            return Err(ClassFileError::InvalidAccessFlags);
+        }
+    };
+    let attributes = read_attributes(data, from_idx + 6, constant_pool)?;
+
+    let field = FieldInfo {
+        access_flags,
+        name_idx,
+        descriptor_idx,
+        attributes: attributes.0,
+    };
+    Ok((field, attributes.1))
+}
+
+fn read_method(
+    data: &[u8],
+    from_idx: usize,
+    constant_pool: &ConstantPool,
+) -> Result<(MethodInfo, usize), ClassFileError> {
+    let access_flags = to_u16(data, from_idx, from_idx + 1)?;
+    let name_idx = to_u16(data, from_idx + 2, from_idx + 3)?;
+    let descriptor_idx = to_u16(data, from_idx + 4, from_idx + 5)?;
+    debug!("found method; name_idx={name_idx}, descriptor_idx={descriptor_idx}");
+
+    let access_flags = match MethodAccessFlags::from_bits(access_flags) {
+        Some(access_flags) => access_flags,
+        None => {
+            dbg!(access_flags);
+            return Err(ClassFileError::InvalidAccessFlags);
+        }
+    };
+    let attributes = read_attributes(data, from_idx + 6, constant_pool)?;
+
+    let method = MethodInfo {
+        access_flags,
+        name_idx,
+        descriptor_idx,
+        attributes: attributes.0,
+    };
+    Ok((method, attributes.1))
+}
+
+pub fn read_fields(
+    data: &[u8],
+    from_idx: usize,
+    constant_pool: &ConstantPool,
+) -> Result<(Vec<FieldInfo>, usize), ClassFileError> {
+    let fields_count = to_u16(data, from_idx, from_idx + 1)?;
+    let mut fields = Vec::with_capacity(usize::from(fields_count));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..fields_count {
+        let field = read_field(data, current_idx, constant_pool)?;
+        fields.push(field.0);
+        current_idx = field.1;
+    }
+
+    Ok((fields, current_idx))
+}
+
+pub fn read_methods(
+    data: &[u8],
+    from_idx: usize,
+    constant_pool: &ConstantPool,
+) -> Result<(Vec<MethodInfo>, usize), ClassFileError> {
+    let methods_count = to_u16(data, from_idx, from_idx + 1)?;
+    let mut methods = Vec::with_capacity(usize::from(methods_count));
+    let mut current_idx = from_idx + 2;
+
+    for _ in 0..methods_count {
+        let method = read_method(data, current_idx, constant_pool)?;
+        methods.push(method.0);
+        current_idx = method.1;
+    }
+
+    Ok((methods, current_idx))
+}
+
+pub fn write_fields(
+    fields: &[FieldInfo],
+    constant_pool: &ConstantPool,
+    out: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    write_u16(out, fields.len() as u16);
+    for field in fields {
+        write_u16(out, field.access_flags.bits());
+        write_u16(out, field.name_idx);
+        write_u16(out, field.descriptor_idx);
+        write_attributes(&field.attributes, constant_pool, out)?;
+    }
+    Ok(())
+}
+
+pub fn write_methods(
+    methods: &[MethodInfo],
+    constant_pool: &ConstantPool,
+    out: &mut Vec<u8>,
+) -> Result<(), ClassFileError> {
+    write_u16(out, methods.len() as u16);
+    for method in methods {
+        write_u16(out, method.access_flags.bits());
+        write_u16(out, method.name_idx);
+        write_u16(out, method.descriptor_idx);
+        write_attributes(&method.attributes, constant_pool, out)?;
+    }
+    Ok(())
+}
+
+fn is_deprecated(attributes: &[Attribute]) -> bool {
+    attributes
+        .iter()
+        .any(|attribute| matches!(attribute, Attribute::Deprecated))
+}
+
+fn has_synthetic_attribute(attributes: &[Attribute]) -> bool {
+    attributes
+        .iter()
+        .any(|attribute| matches!(attribute, Attribute::Synthetic))
+}
+
+impl FieldInfo {
+    pub fn name<'a>(&self, constant_pool: &'a ConstantPool) -> Result<&'a String, ClassFileError> {
+        constant_pool.string_entry(self.name_idx)
+    }
+
+    pub fn descriptor<'a>(
+        &self,
+        constant_pool: &'a ConstantPool,
+    ) -> Result<&'a String, ClassFileError> {
+        constant_pool.string_entry(self.descriptor_idx)
+    }
+
+    /// Points to the constant pool entry holding the value of the field, if it is a constant.
+    pub fn constant_value_idx(&self) -> Option<u16> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::ConstantValue(value_idx) => Some(*value_idx),
+                _ => None,
+            })
+    }
+
+    /// The generic signature of the field, if it has one.
+    pub fn signature<'a>(
+        &self,
+        constant_pool: &'a ConstantPool,
+    ) -> Result<Option<&'a String>, ClassFileError> {
+        signature_idx(&self.attributes)
+            .map(|idx| constant_pool.string_entry(idx))
+            .transpose()
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        is_deprecated(&self.attributes)
+    }
+
+    /// Whether the field is not present in the source code, either by its access flags or by a Synthetic attribute.
+    pub fn is_synthetic(&self) -> bool {
+        self.access_flags.contains(FieldAccessFlags::ACC_SYNTHETIC)
+            || has_synthetic_attribute(&self.attributes)
+    }
+}
+
+impl MethodInfo {
+    pub fn name<'a>(&self, constant_pool: &'a ConstantPool) -> Result<&'a String, ClassFileError> {
+        constant_pool.string_entry(self.name_idx)
+    }
+
+    pub fn descriptor<'a>(
+        &self,
+        constant_pool: &'a ConstantPool,
+    ) -> Result<&'a String, ClassFileError> {
+        constant_pool.string_entry(self.descriptor_idx)
+    }
+
+    /// The Code attribute of the method, or `None` if the method is abstract or native.
+    pub fn code(&self) -> Option<&Code> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Code(code) => Some(code),
+                _ => None,
+            })
+    }
+
+    /// The generic signature of the method, if it has one.
+    pub fn signature<'a>(
+        &self,
+        constant_pool: &'a ConstantPool,
+    ) -> Result<Option<&'a String>, ClassFileError> {
+        signature_idx(&self.attributes)
+            .map(|idx| constant_pool.string_entry(idx))
+            .transpose()
+    }
+
+    /// The class names of the checked exceptions in the `throws` clause of the method.
+    pub fn exceptions<'a>(
+        &self,
+        constant_pool: &'a ConstantPool,
+    ) -> Result<Vec<&'a String>, ClassFileError> {
+        let mut exceptions = Vec::new();
+        for attribute in &self.attributes {
+            if let Attribute::Exceptions(class_idxs) = attribute {
+                for class_idx in class_idxs {
+                    exceptions.push(constant_pool.class_name(*class_idx as usize)?);
+                }
+            }
+        }
+        Ok(exceptions)
+    }
+
+    /// The formal parameters of the method, or an empty list if it has no MethodParameters attribute.
+    pub fn parameters(
+        &self,
+        constant_pool: &ConstantPool,
+    ) -> Result<Vec<Parameter>, ClassFileError> {
+        let mut parameters = Vec::new();
+        for attribute in &self.attributes {
+            if let Attribute::MethodParameters(method_parameters) = attribute {
+                for parameter in method_parameters {
+                    let name = match parameter.name_idx {
+                        0 => None,
+                        name_idx => Some(constant_pool.string_entry(name_idx)?.clone()),
+                    };
+                    parameters.push(Parameter {
+                        name,
+                        flags: parameter.flags,
+                    });
+                }
+            }
+        }
+        Ok(parameters)
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        is_deprecated(&self.attributes)
+    }
+
+    /// Whether the method is not present in the source code, either by its access flags or by a Synthetic attribute.
+    pub fn is_synthetic(&self) -> bool {
+        self.access_flags.contains(MethodAccessFlags::ACC_SYNTHETIC)
+            || has_synthetic_attribute(&self.attributes)
+    }
+}
@@ -0,0 +1,174 @@
+use std::result::Result;
+
+use crate::ClassFile;
+use crate::attributes::{Attribute, ModuleFlags, ModulePackage, PackageFlags, RequiresFlags};
+use crate::constant_pool::ConstantPool;
+use crate::errors::ClassFileError;
+use crate::types::AccessFlags;
+
+/// The module declaration of a `module-info` class, with all constant pool references resolved.
+///
+/// Package and class names are in their internal form, e.g. `java/util` and `java/sql/Driver`.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ModuleDescriptor {
+    pub name: String,
+    pub flags: ModuleFlags,
+    pub version: Option<String>,
+    pub requires: Vec<Requires>,
+    pub exports: Vec<Exports>,
+    pub opens: Vec<Exports>,
+    pub uses: Vec<String>,
+    pub provides: Vec<Provides>,
+    /// All packages of the module, from the ModulePackages attribute.
+    pub packages: Vec<String>,
+    /// The main class of the module, from the ModuleMainClass attribute.
+    pub main_class: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Requires {
+    pub name: String,
+    pub flags: RequiresFlags,
+    pub version: Option<String>,
+}
+
+/// An exported or opened package.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Exports {
+    pub package: String,
+    pub flags: PackageFlags,
+    /// The modules to which the package is exported or opened; empty if it is unqualified.
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Provides {
+    pub service: String,
+    pub implementations: Vec<String>,
+}
+
+fn module_name(constant_pool: &ConstantPool, index: u16) -> Result<String, ClassFileError> {
+    let name_idx = constant_pool.module_entry(index as usize)?;
+    Ok(constant_pool.string_entry(name_idx)?.clone())
+}
+
+fn package_name(constant_pool: &ConstantPool, index: u16) -> Result<String, ClassFileError> {
+    let name_idx = constant_pool.package_entry(index as usize)?;
+    Ok(constant_pool.string_entry(name_idx)?.clone())
+}
+
+fn optional_string(
+    constant_pool: &ConstantPool,
+    index: u16,
+) -> Result<Option<String>, ClassFileError> {
+    match index {
+        0 => Ok(None),
+        _ => Ok(Some(constant_pool.string_entry(index)?.clone())),
+    }
+}
+
+fn exports(
+    constant_pool: &ConstantPool,
+    packages: &[ModulePackage],
+) -> Result<Vec<Exports>, ClassFileError> {
+    packages
+        .iter()
+        .map(|package| {
+            Ok(Exports {
+                package: package_name(constant_pool, package.package_idx)?,
+                flags: package.flags,
+                targets: package
+                    .to
+                    .iter()
+                    .map(|target| module_name(constant_pool, *target))
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect()
+}
+
+impl ClassFile {
+    /// Whether this class file is a `module-info` class.
+    pub fn is_module(&self) -> bool {
+        self.access_flags.contains(AccessFlags::ACC_MODULE)
+    }
+
+    /// The module declaration of this class file, or `None` if it does not have a Module attribute.
+    pub fn module_descriptor(&self) -> Result<Option<ModuleDescriptor>, ClassFileError> {
+        let pool = &self.constant_pool;
+        let Some(module) = self
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Module(module) => Some(module),
+                _ => None,
+            })
+        else {
+            return Ok(None);
+        };
+
+        let mut packages = Vec::new();
+        let mut main_class = None;
+        for attribute in &self.attributes {
+            match attribute {
+                Attribute::ModulePackages(package_idxs) => {
+                    for package_idx in package_idxs {
+                        packages.push(package_name(pool, *package_idx)?);
+                    }
+                }
+                Attribute::ModuleMainClass(class_idx) => {
+                    main_class = Some(pool.class_name(*class_idx as usize)?.clone());
+                }
+                _ => (),
+            }
+        }
+
+        let requires = module
+            .requires
+            .iter()
+            .map(|requires| {
+                Ok(Requires {
+                    name: module_name(pool, requires.requires_idx)?,
+                    flags: requires.flags,
+                    version: optional_string(pool, requires.version_idx)?,
+                })
+            })
+            .collect::<Result<_, ClassFileError>>()?;
+        let uses = module
+            .uses
+            .iter()
+            .map(|class_idx| Ok(pool.class_name(*class_idx as usize)?.clone()))
+            .collect::<Result<_, ClassFileError>>()?;
+        let provides = module
+            .provides
+            .iter()
+            .map(|provides| {
+                Ok(Provides {
+                    service: pool.class_name(provides.provides_idx as usize)?.clone(),
+                    implementations: provides
+                        .with
+                        .iter()
+                        .map(|class_idx| Ok(pool.class_name(*class_idx as usize)?.clone()))
+                        .collect::<Result<_, ClassFileError>>()?,
+                })
+            })
+            .collect::<Result<_, ClassFileError>>()?;
+
+        Ok(Some(ModuleDescriptor {
+            name: module_name(pool, module.name_idx)?,
+            flags: module.flags,
+            version: optional_string(pool, module.version_idx)?,
+            requires,
+            exports: exports(pool, &module.exports)?,
+            opens: exports(pool, &module.opens)?,
+            uses,
+            provides,
+            packages,
+            main_class,
+        }))
+    }
+}
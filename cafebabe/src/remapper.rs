@@ -0,0 +1,668 @@
+//! Renaming classes, fields and methods in class files, e.g. to shade a library into another package or to undo the
+//! obfuscation of a jar with its ProGuard mapping.
+//!
+//! The constant pool entries that refer to classes and members are changed in place, so the bytecode keeps pointing
+//! to the same indices. New names are added to the constant pool, and the old names stay in it even when they are no
+//! longer used, as other entries may share them.
+
+mod annotations;
+mod mapping;
+mod types;
+
+use std::collections::HashMap;
+use std::result::Result;
+
+use log::error;
+
+pub use self::mapping::Mapping;
+pub use self::types::{MappingError, RemapOptions};
+use crate::attributes::Attribute;
+use crate::bootstrap::{BootstrapArgument, BootstrapKind};
+use crate::constant_pool::ConstantPool;
+use crate::constant_pool::types::{
+    ConstantPoolEntry, DynamicRef, FieldRef, MethodRef, NameTypeDescriptor,
+};
+use crate::descriptor::{FieldType, MethodDescriptor, map_signature_class_names};
+use crate::errors::ClassFileError;
+use crate::hierarchy::ClassHierarchy;
+use crate::types::{ClassFile, FieldInfo, MethodInfo};
+use crate::version::{ParseOptions, Strictness};
+
+const INNER_CLASSES: &str = "InnerClasses";
+const ENCLOSING_METHOD: &str = "EnclosingMethod";
+const RECORD: &str = "Record";
+const SIGNATURE: &str = "Signature";
+
+/// Decides the new names of classes, fields and methods. All names are in the internal form, e.g. `java/lang/String`,
+/// and members are identified by the old names of their owner and themselves and their old descriptor.
+///
+/// Members are looked up with the owner that refers to them, which is not necessarily the class that declares them,
+/// e.g. a call to an inherited method names the subclass. Wrap a remapper in an [`InheritingRemapper`] to also look
+/// them up in the supertypes of their owner.
+pub trait Remapper {
+    /// The new name of a class, or `None` to keep its name.
+    fn map_class(&self, class_name: &str) -> Option<String>;
+
+    /// The new name of a field, or `None` to keep its name.
+    fn map_field(&self, _owner: &str, _name: &str, _descriptor: &str) -> Option<String> {
+        None
+    }
+
+    /// The new name of a method, or `None` to keep its name.
+    fn map_method(&self, _owner: &str, _name: &str, _descriptor: &str) -> Option<String> {
+        None
+    }
+
+    /// The new name of a package that a module descriptor names, e.g. `java/lang`, or `None` to keep its name.
+    fn map_package(&self, _package: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Renames only classes, with a closure that returns the new name of a class or `None` to keep it.
+impl<F: Fn(&str) -> Option<String>> Remapper for F {
+    fn map_class(&self, class_name: &str) -> Option<String> {
+        self(class_name)
+    }
+}
+
+/// Looks members up in the supertypes of the class that refers to them when the remapper has no new name for them
+/// under that class, nearest first, the way the JVM resolves them (JVMS 5.4.3.2 and 5.4.3.3). Mappings such as those
+/// of ProGuard name the class that declares a member, while a call to an inherited method names the class it is
+/// called on.
+pub struct InheritingRemapper<'a> {
+    remapper: &'a dyn Remapper,
+    hierarchy: &'a ClassHierarchy,
+}
+
+impl<'a> InheritingRemapper<'a> {
+    pub fn new(
+        remapper: &'a dyn Remapper,
+        hierarchy: &'a ClassHierarchy,
+    ) -> InheritingRemapper<'a> {
+        InheritingRemapper {
+            remapper,
+            hierarchy,
+        }
+    }
+
+    fn map_member(&self, owner: &str, map: impl Fn(&str) -> Option<String>) -> Option<String> {
+        map(owner).or_else(|| self.hierarchy.supertypes(owner).into_iter().find_map(&map))
+    }
+}
+
+impl Remapper for InheritingRemapper<'_> {
+    fn map_class(&self, class_name: &str) -> Option<String> {
+        self.remapper.map_class(class_name)
+    }
+
+    fn map_field(&self, owner: &str, name: &str, descriptor: &str) -> Option<String> {
+        self.map_member(owner, |owner| {
+            self.remapper.map_field(owner, name, descriptor)
+        })
+    }
+
+    fn map_method(&self, owner: &str, name: &str, descriptor: &str) -> Option<String> {
+        // Constructors and static initializers aren't inherited.
+        if name.starts_with('<') {
+            return self.remapper.map_method(owner, name, descriptor);
+        }
+        self.map_member(owner, |owner| {
+            self.remapper.map_method(owner, name, descriptor)
+        })
+    }
+
+    fn map_package(&self, package: &str) -> Option<String> {
+        self.remapper.map_package(package)
+    }
+}
+
+fn map_field_type(field_type: &mut FieldType, remapper: &dyn Remapper) {
+    match field_type {
+        FieldType::Object(class_name) => {
+            if let Some(new_name) = remapper.map_class(class_name) {
+                *class_name = new_name;
+            }
+        }
+        FieldType::Array(component) => map_field_type(component, remapper),
+        _ => {}
+    }
+}
+
+/// Renames the classes in a field or method descriptor.
+pub(crate) fn map_descriptor(
+    descriptor: &str,
+    remapper: &dyn Remapper,
+) -> Result<String, ClassFileError> {
+    if descriptor.starts_with('(') {
+        let mut method_descriptor = MethodDescriptor::parse(descriptor)?;
+        for field_type in method_descriptor
+            .parameters
+            .iter_mut()
+            .chain(method_descriptor.return_type.iter_mut())
+        {
+            map_field_type(field_type, remapper);
+        }
+        Ok(method_descriptor.to_string())
+    } else {
+        let mut field_type = FieldType::parse(descriptor)?;
+        map_field_type(&mut field_type, remapper);
+        Ok(field_type.to_string())
+    }
+}
+
+fn u16_at(info: &[u8], idx: usize) -> Result<u16, ClassFileError> {
+    match info.get(idx..idx + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => {
+            error!("attribute ends unexpectedly; offset={idx}");
+            Err(ClassFileError::InvalidAttribute)
+        }
+    }
+}
+
+fn set_u16(info: &mut [u8], idx: usize, value: u16) {
+    info[idx..idx + 2].copy_from_slice(&value.to_be_bytes());
+}
+
+/// The simple name of an inner class after it was renamed, e.g. `Y` for `b/X$Y`. Local classes keep the number that
+/// precedes their simple name in the class name, e.g. `Outer$1Local`.
+fn inner_simple_name(old_simple_name: &str, new_class_name: &str) -> String {
+    let suffix = new_class_name
+        .rsplit(['$', '/'])
+        .next()
+        .unwrap_or(new_class_name);
+    match suffix.trim_start_matches(|c: char| c.is_ascii_digit()) {
+        "" => old_simple_name.to_string(),
+        simple_name => simple_name.to_string(),
+    }
+}
+
+/// Renames the classes and members in one class file, whose constant pool grows as needed.
+struct Context<'a> {
+    remapper: &'a dyn Remapper,
+    options: &'a RemapOptions,
+    constant_pool: &'a mut ConstantPool,
+    /// The old name of the class, which owns the fields and methods it declares.
+    this_name: String,
+    /// The indices of the String entries, to find the ones that hold new names.
+    strings: HashMap<String, u16>,
+    /// The indices of the NameTypeDescriptor entries by the indices of their name and descriptor.
+    name_types: HashMap<(u16, u16), u16>,
+}
+
+impl<'a> Context<'a> {
+    fn new(
+        remapper: &'a dyn Remapper,
+        options: &'a RemapOptions,
+        constant_pool: &'a mut ConstantPool,
+        this_name: String,
+    ) -> Context<'a> {
+        let mut strings = HashMap::new();
+        let mut name_types = HashMap::new();
+        for (position, entry) in constant_pool.items().enumerate() {
+            let index = position as u16 + 1;
+            match entry {
+                ConstantPoolEntry::String(value) => {
+                    strings.entry(value.clone()).or_insert(index);
+                }
+                ConstantPoolEntry::NameTypeDescriptor(name_type) => {
+                    let key = (name_type.name_ref, name_type.type_descriptor_ref);
+                    name_types.entry(key).or_insert(index);
+                }
+                _ => {}
+            }
+        }
+        Context {
+            remapper,
+            options,
+            constant_pool,
+            this_name,
+            strings,
+            name_types,
+        }
+    }
+
+    fn utf8(&self, index: u16) -> Result<String, ClassFileError> {
+        Ok(self.constant_pool.string_entry(index)?.clone())
+    }
+
+    fn old_class_name(&self, class_ref: u16) -> Result<String, ClassFileError> {
+        Ok(self.constant_pool.class_name(class_ref as usize)?.clone())
+    }
+
+    fn old_name_and_type(&self, index: u16) -> Result<(String, String), ClassFileError> {
+        let (name, descriptor) = self.constant_pool.name_and_type(index as usize)?;
+        Ok((name.clone(), descriptor.clone()))
+    }
+
+    /// The index of the String entry at `index` if `new_value` is its value, or else of a String entry holding
+    /// `new_value`, which is added if there is none yet.
+    fn replace_utf8(&mut self, index: u16, old_value: &str, new_value: String) -> u16 {
+        if new_value == old_value {
+            return index;
+        }
+        match self.strings.get(&new_value) {
+            Some(new_index) => *new_index,
+            None => {
+                let new_index = self
+                    .constant_pool
+                    .add(ConstantPoolEntry::String(new_value.clone()));
+                self.strings.insert(new_value, new_index);
+                new_index
+            }
+        }
+    }
+
+    fn map_class(&self, class_name: &str) -> Result<String, ClassFileError> {
+        if class_name.starts_with('[') {
+            return map_descriptor(class_name, self.remapper);
+        }
+        Ok(self
+            .remapper
+            .map_class(class_name)
+            .unwrap_or_else(|| class_name.to_string()))
+    }
+
+    fn class_name_idx(&mut self, index: u16) -> Result<u16, ClassFileError> {
+        let old_name = self.utf8(index)?;
+        let new_name = self.map_class(&old_name)?;
+        Ok(self.replace_utf8(index, &old_name, new_name))
+    }
+
+    fn descriptor_idx(&mut self, index: u16) -> Result<u16, ClassFileError> {
+        let old_descriptor = self.utf8(index)?;
+        let new_descriptor = map_descriptor(&old_descriptor, self.remapper)?;
+        Ok(self.replace_utf8(index, &old_descriptor, new_descriptor))
+    }
+
+    fn signature_idx(&mut self, index: u16) -> Result<u16, ClassFileError> {
+        let old_signature = self.utf8(index)?;
+        let new_signature = map_signature_class_names(&old_signature, &mut |class_name| {
+            self.remapper
+                .map_class(class_name)
+                .unwrap_or_else(|| class_name.to_string())
+        })?;
+        Ok(self.replace_utf8(index, &old_signature, new_signature))
+    }
+
+    /// Renames a string literal that is the name of a class, in the internal form or as a binary name.
+    fn string_idx(&mut self, index: u16) -> Result<u16, ClassFileError> {
+        let old_value = self.utf8(index)?;
+        let new_value = match self.remapper.map_class(&old_value) {
+            Some(new_value) => new_value,
+            None if old_value.contains('.') && !old_value.contains('/') => {
+                match self.remapper.map_class(&old_value.replace('.', "/")) {
+                    Some(new_value) => new_value.replace('/', "."),
+                    None => return Ok(index),
+                }
+            }
+            None => return Ok(index),
+        };
+        Ok(self.replace_utf8(index, &old_value, new_value))
+    }
+
+    /// The index of a NameTypeDescriptor entry like the one at `index`, but with a new name if given and the classes
+    /// in its descriptor renamed. The descriptors of the entries that were there from the start are renamed in place,
+    /// while entries with new names are added as needed, as references to members of other classes may share the old
+    /// one.
+    fn name_and_type(
+        &mut self,
+        index: u16,
+        new_name: Option<String>,
+    ) -> Result<u16, ClassFileError> {
+        let name_type = self.constant_pool.name_type_entry(index as usize)?;
+        let name_idx = match new_name {
+            Some(new_name) => {
+                let old_name = self.utf8(name_type.name_ref)?;
+                self.replace_utf8(name_type.name_ref, &old_name, new_name)
+            }
+            None => name_type.name_ref,
+        };
+        let descriptor_idx = self.descriptor_idx(name_type.type_descriptor_ref)?;
+        if name_idx == name_type.name_ref {
+            return Ok(index);
+        }
+
+        let key = (name_idx, descriptor_idx);
+        if let Some(new_index) = self.name_types.get(&key) {
+            return Ok(*new_index);
+        }
+        let new_index = self
+            .constant_pool
+            .add(ConstantPoolEntry::NameTypeDescriptor(NameTypeDescriptor {
+                name_ref: name_idx,
+                type_descriptor_ref: descriptor_idx,
+            }));
+        self.name_types.insert(key, new_index);
+        Ok(new_index)
+    }
+
+    fn member_name_type(
+        &mut self,
+        class_ref: u16,
+        name_type_ref: u16,
+        is_field: bool,
+    ) -> Result<u16, ClassFileError> {
+        let owner = self.old_class_name(class_ref)?;
+        let (name, descriptor) = self.old_name_and_type(name_type_ref)?;
+        let new_name = match is_field {
+            true => self.remapper.map_field(&owner, &name, &descriptor),
+            false => self.remapper.map_method(&owner, &name, &descriptor),
+        };
+        self.name_and_type(name_type_ref, new_name)
+    }
+
+    /// The new entries of the constant pool, which are only applied at the end so that the old names can be looked
+    /// up until then.
+    fn constant_pool_replacements(
+        &mut self,
+        lambda_names: &HashMap<u16, String>,
+    ) -> Result<Vec<(u16, ConstantPoolEntry)>, ClassFileError> {
+        let mut replacements = Vec::new();
+        // The descriptors first, so that renamed members can share the entries that keep their name.
+        for index in 1..=self.constant_pool.len() {
+            if let ConstantPoolEntry::NameTypeDescriptor(name_type) =
+                self.constant_pool.get_entry(index).clone()
+            {
+                let descriptor_idx = self.descriptor_idx(name_type.type_descriptor_ref)?;
+                if descriptor_idx != name_type.type_descriptor_ref {
+                    let key = (name_type.name_ref, descriptor_idx);
+                    self.name_types.entry(key).or_insert(index);
+                    let replacement = NameTypeDescriptor {
+                        type_descriptor_ref: descriptor_idx,
+                        ..name_type
+                    };
+                    replacements.push((index, ConstantPoolEntry::NameTypeDescriptor(replacement)));
+                }
+            }
+        }
+        // Only the entries that were there from the start; the added ones already hold new names.
+        for index in 1..=self.constant_pool.len() {
+            let entry = self.constant_pool.get_entry(index).clone();
+            let replacement = match entry {
+                ConstantPoolEntry::ClassRef(name_idx) => {
+                    ConstantPoolEntry::ClassRef(self.class_name_idx(name_idx)?)
+                }
+                ConstantPoolEntry::FieldRef(field_ref) => ConstantPoolEntry::FieldRef(FieldRef {
+                    name_type_ref: self.member_name_type(
+                        field_ref.class_ref,
+                        field_ref.name_type_ref,
+                        true,
+                    )?,
+                    ..field_ref
+                }),
+                ConstantPoolEntry::MethodRef(method_ref) => {
+                    ConstantPoolEntry::MethodRef(MethodRef {
+                        name_type_ref: self.member_name_type(
+                            method_ref.class_ref,
+                            method_ref.name_type_ref,
+                            false,
+                        )?,
+                        ..method_ref
+                    })
+                }
+                ConstantPoolEntry::InterfaceMethodRef(method_ref) => {
+                    ConstantPoolEntry::InterfaceMethodRef(MethodRef {
+                        name_type_ref: self.member_name_type(
+                            method_ref.class_ref,
+                            method_ref.name_type_ref,
+                            false,
+                        )?,
+                        ..method_ref
+                    })
+                }
+                ConstantPoolEntry::MethodType(descriptor_idx) => {
+                    ConstantPoolEntry::MethodType(self.descriptor_idx(descriptor_idx)?)
+                }
+                ConstantPoolEntry::Package(name_idx) => {
+                    let package = self.utf8(name_idx)?;
+                    match self.remapper.map_package(&package) {
+                        Some(new_package) => ConstantPoolEntry::Package(self.replace_utf8(
+                            name_idx,
+                            &package,
+                            new_package,
+                        )),
+                        None => continue,
+                    }
+                }
+                ConstantPoolEntry::StringRef(value_idx) if self.options.strings => {
+                    ConstantPoolEntry::StringRef(self.string_idx(value_idx)?)
+                }
+                ConstantPoolEntry::InvokeDynamic(dynamic_ref) => {
+                    ConstantPoolEntry::InvokeDynamic(DynamicRef {
+                        name_type_ref: self.name_and_type(
+                            dynamic_ref.name_type_ref,
+                            lambda_names.get(&index).cloned(),
+                        )?,
+                        ..dynamic_ref
+                    })
+                }
+                ConstantPoolEntry::Dynamic(dynamic_ref) => ConstantPoolEntry::Dynamic(DynamicRef {
+                    name_type_ref: self.name_and_type(dynamic_ref.name_type_ref, None)?,
+                    ..dynamic_ref
+                }),
+                _ => continue,
+            };
+            if replacement != entry {
+                replacements.push((index, replacement));
+            }
+        }
+        Ok(replacements)
+    }
+
+    fn fields(&mut self, fields: &mut [FieldInfo]) -> Result<(), ClassFileError> {
+        for field in fields {
+            let name = self.utf8(field.name_idx)?;
+            let descriptor = self.utf8(field.descriptor_idx)?;
+            if let Some(new_name) = self.remapper.map_field(&self.this_name, &name, &descriptor) {
+                field.name_idx = self.replace_utf8(field.name_idx, &name, new_name);
+            }
+            field.descriptor_idx = self.descriptor_idx(field.descriptor_idx)?;
+            self.attributes(&mut field.attributes)?;
+        }
+        Ok(())
+    }
+
+    fn methods(&mut self, methods: &mut [MethodInfo]) -> Result<(), ClassFileError> {
+        for method in methods {
+            let name = self.utf8(method.name_idx)?;
+            let descriptor = self.utf8(method.descriptor_idx)?;
+            if let Some(new_name) = self
+                .remapper
+                .map_method(&self.this_name, &name, &descriptor)
+            {
+                method.name_idx = self.replace_utf8(method.name_idx, &name, new_name);
+            }
+            method.descriptor_idx = self.descriptor_idx(method.descriptor_idx)?;
+            self.attributes(&mut method.attributes)?;
+        }
+        Ok(())
+    }
+
+    fn attributes(&mut self, attributes: &mut [Attribute]) -> Result<(), ClassFileError> {
+        for attribute in attributes {
+            match attribute {
+                Attribute::Signature(signature_idx) => {
+                    *signature_idx = self.signature_idx(*signature_idx)?;
+                }
+                Attribute::Code(code) => self.attributes(&mut code.attributes)?,
+                Attribute::LocalVariableTable(local_variables) => {
+                    for local_variable in local_variables {
+                        local_variable.descriptor_idx =
+                            self.descriptor_idx(local_variable.descriptor_idx)?;
+                    }
+                }
+                Attribute::LocalVariableTypeTable(local_variables) => {
+                    for local_variable in local_variables {
+                        local_variable.descriptor_idx =
+                            self.signature_idx(local_variable.descriptor_idx)?;
+                    }
+                }
+                Attribute::Unknown { name_idx, info } => {
+                    let name = self.utf8(*name_idx)?;
+                    self.unknown_attribute(&name, info)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrites the attributes that are kept as bytes. All indices take two bytes, so their length stays the same.
+    fn unknown_attribute(&mut self, name: &str, info: &mut [u8]) -> Result<(), ClassFileError> {
+        match name {
+            INNER_CLASSES => self.inner_classes(info),
+            ENCLOSING_METHOD => self.enclosing_method(info),
+            RECORD => self.record(info),
+            SIGNATURE => {
+                let signature_idx = self.signature_idx(u16_at(info, 0)?)?;
+                set_u16(info, 0, signature_idx);
+                Ok(())
+            }
+            _ => annotations::remap_annotations(self, name, info),
+        }
+    }
+
+    /// Renames the simple names of inner classes along with their classes (JVMS 4.7.6). The classes themselves are
+    /// ClassRef entries, which are renamed with the rest of the constant pool.
+    fn inner_classes(&mut self, info: &mut [u8]) -> Result<(), ClassFileError> {
+        for position in 0..usize::from(u16_at(info, 0)?) {
+            let idx = 2 + 8 * position;
+            let inner_class_idx = u16_at(info, idx)?;
+            let inner_name_idx = u16_at(info, idx + 4)?;
+            if inner_class_idx == 0 || inner_name_idx == 0 {
+                continue;
+            }
+            let old_class_name = self.old_class_name(inner_class_idx)?;
+            let new_class_name = self.map_class(&old_class_name)?;
+            if new_class_name != old_class_name {
+                let old_name = self.utf8(inner_name_idx)?;
+                let new_name = inner_simple_name(&old_name, &new_class_name);
+                let new_name_idx = self.replace_utf8(inner_name_idx, &old_name, new_name);
+                set_u16(info, idx + 4, new_name_idx);
+            }
+        }
+        Ok(())
+    }
+
+    /// Renames the method that encloses a local or anonymous class (JVMS 4.7.7).
+    fn enclosing_method(&mut self, info: &mut [u8]) -> Result<(), ClassFileError> {
+        let class_idx = u16_at(info, 0)?;
+        let method_idx = u16_at(info, 2)?;
+        if method_idx != 0 {
+            let name_type_idx = self.member_name_type(class_idx, method_idx, false)?;
+            set_u16(info, 2, name_type_idx);
+        }
+        Ok(())
+    }
+
+    /// Renames the components of a record like the fields that hold them (JVMS 4.7.30).
+    fn record(&mut self, info: &mut [u8]) -> Result<(), ClassFileError> {
+        let mut idx = 2;
+        for _ in 0..u16_at(info, 0)? {
+            let name_idx = u16_at(info, idx)?;
+            let descriptor_idx = u16_at(info, idx + 2)?;
+            let name = self.utf8(name_idx)?;
+            let descriptor = self.utf8(descriptor_idx)?;
+            if let Some(new_name) = self.remapper.map_field(&self.this_name, &name, &descriptor) {
+                let new_name_idx = self.replace_utf8(name_idx, &name, new_name);
+                set_u16(info, idx, new_name_idx);
+            }
+            let new_descriptor_idx = self.descriptor_idx(descriptor_idx)?;
+            set_u16(info, idx + 2, new_descriptor_idx);
+
+            let attributes_count = u16_at(info, idx + 4)?;
+            idx += 6;
+            for _ in 0..attributes_count {
+                let attribute_name = self.utf8(u16_at(info, idx)?)?;
+                let Some(length) = info.get(idx + 2..idx + 6) else {
+                    error!("record component ends unexpectedly; offset={idx}");
+                    return Err(ClassFileError::InvalidAttribute);
+                };
+                let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]);
+                let start = idx + 6;
+                let end = start + length as usize;
+                let Some(attribute_info) = info.get_mut(start..end) else {
+                    error!("record component ends unexpectedly; offset={start}");
+                    return Err(ClassFileError::InvalidAttribute);
+                };
+                self.unknown_attribute(&attribute_name, attribute_info)?;
+                idx = end;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renames the classes, fields and methods of a class file with a remapper: its own name and those of its members,
+/// all references in the constant pool, descriptors, generic signatures, annotations, inner classes, enclosing
+/// methods, record components and the names of lambdas, which follow the method of their functional interface.
+/// String literals are only renamed if the options say so.
+pub fn remap_class_file(
+    class_file: &mut ClassFile,
+    remapper: &dyn Remapper,
+    options: &RemapOptions,
+) -> Result<(), ClassFileError> {
+    let this_name = class_file
+        .constant_pool
+        .class_name(class_file.class.this_idx)?
+        .clone();
+
+    // The name of a lambda is the name of the method it implements, which is only known from the call site.
+    let mut lambda_names = HashMap::new();
+    for (position, entry) in class_file.constant_pool.items().enumerate() {
+        let index = position as u16 + 1;
+        if !matches!(entry, ConstantPoolEntry::InvokeDynamic(_)) {
+            continue;
+        }
+        let call_site = class_file.call_site(index)?;
+        if call_site.kind() == BootstrapKind::LambdaMetafactory
+            && let Some(BootstrapArgument::MethodType(method_type)) = call_site.arguments.first()
+            && let Some(FieldType::Object(interface)) =
+                MethodDescriptor::parse(&call_site.descriptor)?.return_type
+            && let Some(new_name) = remapper.map_method(&interface, &call_site.name, method_type)
+        {
+            lambda_names.insert(index, new_name);
+        }
+    }
+
+    let ClassFile {
+        constant_pool,
+        class,
+        fields,
+        methods,
+        attributes,
+        ..
+    } = class_file;
+    let mut context = Context::new(remapper, options, constant_pool, this_name);
+    let replacements = context.constant_pool_replacements(&lambda_names)?;
+    // The class definition holds the names of the interfaces rather than their ClassRef entries.
+    for name_idx in &mut class.interfaces {
+        *name_idx = usize::from(context.class_name_idx(*name_idx as u16)?);
+    }
+    context.fields(fields)?;
+    context.methods(methods)?;
+    context.attributes(attributes)?;
+
+    for (index, entry) in replacements {
+        constant_pool.set_entry(index, entry);
+    }
+    Ok(())
+}
+
+/// Reads a class file of any version, renames its classes and members like [`remap_class_file`] and writes it.
+pub fn remap_class_data(
+    data: &[u8],
+    remapper: &dyn Remapper,
+    options: &RemapOptions,
+) -> Result<Vec<u8>, ClassFileError> {
+    let parse_options = ParseOptions {
+        allow_preview: true,
+        strictness: Strictness::Lenient,
+        ..ParseOptions::default()
+    };
+    let mut class_file = crate::read_class_data_with_options(data, &parse_options)?;
+    remap_class_file(&mut class_file, remapper, options)?;
+    crate::write_class_data(&class_file)
+}
@@ -0,0 +1,76 @@
+//! Renaming the types, enum constants and class values in the attributes that hold annotations (JVMS 4.7.16 to
+//! 4.7.22), which are kept as unknown attributes and rewritten in place.
+
+use std::result::Result;
+
+use super::Context;
+use crate::descriptor::FieldType;
+use crate::errors::ClassFileError;
+use crate::shared::annotations::{AnnotationIndex, annotation_indices};
+
+/// Renames the classes in the descriptor at `index`.
+fn descriptor_idx(context: &mut Context, index: u16) -> Result<u16, ClassFileError> {
+    // The class value of `void.class` is the return descriptor `V`.
+    if context.utf8(index)? == "V" {
+        return Ok(index);
+    }
+    context.descriptor_idx(index)
+}
+
+/// Renames an enum constant, which is a static field of its enum class.
+fn enum_constant_idx(
+    context: &mut Context,
+    descriptor: &str,
+    index: u16,
+) -> Result<u16, ClassFileError> {
+    let Ok(FieldType::Object(owner)) = FieldType::parse(descriptor) else {
+        return Ok(index);
+    };
+    let name = context.utf8(index)?;
+    match context.remapper.map_field(&owner, &name, descriptor) {
+        Some(new_name) => Ok(context.replace_utf8(index, &name, new_name)),
+        None => Ok(index),
+    }
+}
+
+/// Renames the classes in an attribute if it holds annotations, and leaves other attributes alone. The indices are
+/// read first and written back once all of them were renamed.
+pub(super) fn remap_annotations(
+    context: &mut Context,
+    name: &str,
+    info: &mut [u8],
+) -> Result<(), ClassFileError> {
+    let mut old_indices = Vec::new();
+    annotation_indices(name, info, &mut |kind, index| {
+        old_indices.push((kind, *index))
+    })?;
+    if old_indices.is_empty() {
+        return Ok(());
+    }
+
+    let mut enum_descriptor = String::new();
+    let mut new_indices = Vec::with_capacity(old_indices.len());
+    for (kind, index) in old_indices {
+        let new_index = match kind {
+            AnnotationIndex::Type | AnnotationIndex::Class => descriptor_idx(context, index)?,
+            AnnotationIndex::EnumType => {
+                enum_descriptor = context.utf8(index)?;
+                descriptor_idx(context, index)?
+            }
+            AnnotationIndex::EnumConstant => enum_constant_idx(context, &enum_descriptor, index)?,
+            AnnotationIndex::Constant(b's') if context.options.strings => {
+                context.string_idx(index)?
+            }
+            // The names of the elements are kept, as they are methods of the annotation type whose return type is
+            // not known here.
+            AnnotationIndex::ElementName | AnnotationIndex::Constant(_) => index,
+        };
+        new_indices.push(new_index);
+    }
+    let mut new_indices = new_indices.into_iter();
+    annotation_indices(name, info, &mut |_, index| {
+        if let Some(new_index) = new_indices.next() {
+            *index = new_index;
+        }
+    })
+}
@@ -0,0 +1,169 @@
+//! Mappings of class and member names, built by hand or read from the mapping files of ProGuard and R8.
+
+use std::collections::HashMap;
+use std::result::Result;
+
+use super::types::MappingError;
+use super::{Remapper, map_descriptor};
+
+/// A member by its owner, name and descriptor.
+type MemberKey = (String, String, String);
+
+/// New names for classes, fields and methods, with class names in the internal form and members keyed by the old
+/// names of their owner and themselves and their old descriptor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mapping {
+    classes: HashMap<String, String>,
+    fields: HashMap<MemberKey, String>,
+    methods: HashMap<MemberKey, String>,
+}
+
+/// The descriptor of a type as ProGuard writes it, e.g. `int` or `java.lang.String[]`.
+fn type_descriptor(type_name: &str) -> Option<String> {
+    let element = type_name.trim_end_matches("[]");
+    let dimensions = (type_name.len() - element.len()) / 2;
+    let element = match element {
+        "boolean" => "Z".to_string(),
+        "byte" => "B".to_string(),
+        "char" => "C".to_string(),
+        "short" => "S".to_string(),
+        "int" => "I".to_string(),
+        "long" => "J".to_string(),
+        "float" => "F".to_string(),
+        "double" => "D".to_string(),
+        "void" if dimensions == 0 => "V".to_string(),
+        "" | "void" => return None,
+        class_name => format!("L{};", class_name.replace('.', "/")),
+    };
+    Some(format!("{}{element}", "[".repeat(dimensions)))
+}
+
+impl Mapping {
+    pub fn new() -> Mapping {
+        Mapping::default()
+    }
+
+    pub fn add_class(&mut self, class_name: &str, new_name: &str) {
+        self.classes
+            .insert(class_name.to_string(), new_name.to_string());
+    }
+
+    pub fn add_field(&mut self, owner: &str, name: &str, descriptor: &str, new_name: &str) {
+        let key = (owner.to_string(), name.to_string(), descriptor.to_string());
+        self.fields.insert(key, new_name.to_string());
+    }
+
+    pub fn add_method(&mut self, owner: &str, name: &str, descriptor: &str, new_name: &str) {
+        let key = (owner.to_string(), name.to_string(), descriptor.to_string());
+        self.methods.insert(key, new_name.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty() && self.fields.is_empty() && self.methods.is_empty()
+    }
+
+    /// Reads a mapping file of ProGuard or R8, which maps the original names to the obfuscated ones:
+    ///
+    /// ```text
+    /// com.example.Library -> a.a:
+    ///     java.lang.String name -> a
+    ///     12:14:void run(int,java.lang.String[]):30:32 -> b
+    /// ```
+    ///
+    /// Names that stay the same, comments and the methods that were inlined from other classes are skipped. Use
+    /// [`inverse`](Mapping::inverse) to map obfuscated names back to the original ones.
+    pub fn from_proguard(text: &str) -> Result<Mapping, MappingError> {
+        let mut mapping = Mapping::new();
+        let mut owner: Option<String> = None;
+        for (position, line) in text.lines().enumerate() {
+            let line_number = position + 1;
+            let invalid = || MappingError::InvalidLine(line_number, line.to_string());
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let Some((from, to)) = trimmed.split_once(" -> ") else {
+                return Err(invalid());
+            };
+
+            if !line.starts_with(char::is_whitespace) {
+                let to = to.strip_suffix(':').ok_or_else(invalid)?;
+                let class_name = from.replace('.', "/");
+                let new_name = to.replace('.', "/");
+                if class_name != new_name {
+                    mapping.add_class(&class_name, &new_name);
+                }
+                owner = Some(class_name);
+                continue;
+            }
+
+            let Some(owner) = &owner else {
+                return Err(MappingError::MemberOutsideClass(line_number));
+            };
+            // Methods start with the range of their line numbers, e.g. `12:14:`.
+            let member = from.trim_start_matches(|c: char| c.is_ascii_digit() || c == ':');
+            let (type_name, member) = member.split_once(' ').ok_or_else(invalid)?;
+            let member_type = type_descriptor(type_name).ok_or_else(invalid)?;
+            match member.split_once('(') {
+                Some((name, rest)) => {
+                    let (parameters, _) = rest.split_once(')').ok_or_else(invalid)?;
+                    // Methods that were inlined from another class are qualified with its name.
+                    if name.contains('.') || name == to {
+                        continue;
+                    }
+                    let parameters = parameters
+                        .split(',')
+                        .filter(|parameter| !parameter.is_empty())
+                        .map(type_descriptor)
+                        .collect::<Option<String>>()
+                        .ok_or_else(invalid)?;
+                    let descriptor = format!("({parameters}){member_type}");
+                    mapping.add_method(owner, name, &descriptor, to);
+                }
+                None if member_type == "V" => return Err(invalid()),
+                None if member != to => mapping.add_field(owner, member, &member_type, to),
+                None => {}
+            }
+        }
+        Ok(mapping)
+    }
+
+    /// The mapping in the other direction, e.g. from obfuscated names back to the original ones.
+    pub fn inverse(&self) -> Mapping {
+        let mut inverse = Mapping::new();
+        for (class_name, new_name) in &self.classes {
+            inverse.add_class(new_name, class_name);
+        }
+        let new_key = |(owner, _, descriptor): &MemberKey, new_name: &str| {
+            let owner = self.map_class(owner).unwrap_or_else(|| owner.clone());
+            let descriptor =
+                map_descriptor(descriptor, self).unwrap_or_else(|_| descriptor.clone());
+            (owner, new_name.to_string(), descriptor)
+        };
+        for (key, new_name) in &self.fields {
+            inverse.fields.insert(new_key(key, new_name), key.1.clone());
+        }
+        for (key, new_name) in &self.methods {
+            inverse
+                .methods
+                .insert(new_key(key, new_name), key.1.clone());
+        }
+        inverse
+    }
+}
+
+impl Remapper for Mapping {
+    fn map_class(&self, class_name: &str) -> Option<String> {
+        self.classes.get(class_name).cloned()
+    }
+
+    fn map_field(&self, owner: &str, name: &str, descriptor: &str) -> Option<String> {
+        let key = (owner.to_string(), name.to_string(), descriptor.to_string());
+        self.fields.get(&key).cloned()
+    }
+
+    fn map_method(&self, owner: &str, name: &str, descriptor: &str) -> Option<String> {
+        let key = (owner.to_string(), name.to_string(), descriptor.to_string());
+        self.methods.get(&key).cloned()
+    }
+}
@@ -0,0 +1,23 @@
+/// Controls what [`remap_class_file`](super::remap_class_file) rewrites besides the names of classes and members.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RemapOptions {
+    /// Whether string literals that name a mapped class are rewritten too, in the internal form (`a/b/C`) or as a
+    /// binary name (`a.b.C`), e.g. the argument of `Class.forName`.
+    pub strings: bool,
+}
+
+#[derive(Debug)]
+pub enum MappingError {
+    /// A line of a ProGuard mapping that is neither a class nor a member mapping, by its number from 1.
+    InvalidLine(usize, String),
+    /// A member mapping that comes before the first class mapping, by its line number from 1.
+    MemberOutsideClass(usize),
+}
+
+impl std::fmt::Display for MappingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for MappingError {}
@@ -0,0 +1,160 @@
+//! Relocation of the packages of a jar, which makes a private copy of a library that can't clash with other versions
+//! of it on the same class path, like the relocations of the Maven Shade plugin.
+//!
+//! The classes of the relocated packages are moved, and all classes are rewritten to refer to them by their new
+//! names. Resources in the relocated packages move along, and the service files below `META-INF/services/` are
+//! renamed after the interface and list the providers by their new names.
+
+mod types;
+
+use std::collections::HashMap;
+use std::result::Result;
+
+use log::debug;
+
+pub use self::types::{Relocation, ShadeReport};
+use crate::archive::{ArchiveError, Compression, JarFile, JarWriter, is_signature_file};
+use crate::remapper::{RemapOptions, Remapper, remap_class_data};
+
+const SERVICES: &str = "META-INF/services/";
+const VERSIONS: &str = "META-INF/versions/";
+
+/// Renames classes, packages and entries with the first relocation that applies to them.
+struct Relocator<'a>(&'a [Relocation]);
+
+impl Relocator<'_> {
+    fn relocate(&self, name: &str) -> Option<String> {
+        self.0
+            .iter()
+            .find_map(|relocation| relocation.relocate(name))
+    }
+
+    /// The new binary name of a class in its usual form, e.g. `com.example.Foo`.
+    fn relocate_binary_name(&self, class_name: &str) -> Option<String> {
+        self.relocate(&class_name.replace('.', "/"))
+            .map(|new_name| new_name.replace('/', "."))
+    }
+
+    /// The new name of an entry, or `None` to keep it. Entries below `META-INF/` stay where they are, except for the
+    /// service files and the versioned entries of multi-release jars.
+    fn entry_name(&self, name: &str) -> Option<String> {
+        if let Some(interface) = name.strip_prefix(SERVICES) {
+            return self
+                .relocate_binary_name(interface)
+                .map(|new_interface| format!("{SERVICES}{new_interface}"));
+        }
+        if let Some(versioned_name) = name.strip_prefix(VERSIONS)
+            && let Some((release, name)) = versioned_name.split_once('/')
+        {
+            return self
+                .entry_name(name)
+                .map(|new_name| format!("{VERSIONS}{release}/{new_name}"));
+        }
+        if name.starts_with("META-INF/") {
+            return None;
+        }
+        match name.strip_suffix('/') {
+            Some(directory) => self
+                .relocate(directory)
+                .map(|new_directory| format!("{new_directory}/")),
+            None => self.relocate(name),
+        }
+    }
+
+    /// Renames the providers in a service file, which has a binary name on each line, optionally followed by a
+    /// comment.
+    fn relocate_providers(&self, content: &str) -> String {
+        let mut new_content = String::with_capacity(content.len());
+        for line in content.lines() {
+            let provider = line.split('#').next().unwrap_or_default().trim();
+            match self.relocate_binary_name(provider) {
+                Some(new_provider) if !provider.is_empty() => {
+                    new_content.push_str(&line.replacen(provider, &new_provider, 1));
+                }
+                _ => new_content.push_str(line),
+            }
+            new_content.push('\n');
+        }
+        new_content
+    }
+}
+
+impl Remapper for Relocator<'_> {
+    fn map_class(&self, class_name: &str) -> Option<String> {
+        self.relocate(class_name)
+    }
+
+    fn map_package(&self, package: &str) -> Option<String> {
+        self.relocate(package)
+    }
+}
+
+/// Relocates the packages of a jar and gives the new jar, whose entries keep their order and compression.
+///
+/// Service files that end up with the same name are merged, and other entries whose new name is taken by an earlier
+/// entry are left out. The signature files of signed jars are removed, as they no longer match. The manifest is kept
+/// as it is.
+pub fn shade_jar(
+    jar_file: &JarFile,
+    relocations: &[Relocation],
+    options: &RemapOptions,
+) -> Result<(Vec<u8>, ShadeReport), ArchiveError> {
+    let relocator = Relocator(relocations);
+    let mut report = ShadeReport::default();
+    let mut entries: Vec<(String, Vec<u8>, Compression)> = Vec::new();
+    // The position of each entry in `entries` by its new name.
+    let mut positions: HashMap<String, usize> = HashMap::new();
+
+    for entry in jar_file.entries() {
+        if is_signature_file(&entry.name) {
+            report.removed_signatures.push(entry.name.clone());
+            continue;
+        }
+        let mut data = jar_file.read(entry)?;
+        let is_class = entry.class_name().is_some();
+        if is_class {
+            data = remap_class_data(&data, &relocator, options)
+                .map_err(|error| ArchiveError::InvalidClass(entry.name.clone(), error))?;
+        } else if entry.name.starts_with(SERVICES) {
+            data = match String::from_utf8(data) {
+                Ok(content) => relocator.relocate_providers(&content).into_bytes(),
+                Err(error) => error.into_bytes(),
+            };
+        }
+
+        let name = match relocator.entry_name(&entry.name) {
+            Some(new_name) => {
+                debug!("relocating entry; name={}, new_name={new_name}", entry.name);
+                match is_class {
+                    true => report.relocated_classes.push(entry.name.clone()),
+                    false => report.relocated_resources.push(entry.name.clone()),
+                }
+                new_name
+            }
+            None => entry.name.clone(),
+        };
+        match positions.get(&name) {
+            Some(position) if name.starts_with(SERVICES) => {
+                let content = &mut entries[*position].1;
+                if !content.is_empty() && !content.ends_with(b"\n") {
+                    content.push(b'\n');
+                }
+                content.extend_from_slice(&data);
+                if !report.merged_services.contains(&name) {
+                    report.merged_services.push(name);
+                }
+            }
+            Some(_) => report.duplicate_entries.push(entry.name.clone()),
+            None => {
+                positions.insert(name.clone(), entries.len());
+                entries.push((name, data, entry.compression));
+            }
+        }
+    }
+
+    let mut writer = JarWriter::new();
+    for (name, data, compression) in &entries {
+        writer.add(name, data, *compression)?;
+    }
+    Ok((writer.finish()?, report))
+}
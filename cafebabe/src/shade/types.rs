@@ -0,0 +1,45 @@
+/// Moves a package and its subpackages to another package, e.g. `com/google/common` to `acme/shaded/guava`. Package
+/// names are in the internal form.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Relocation {
+    pub from: String,
+    pub to: String,
+}
+
+impl Relocation {
+    /// A relocation between two packages, given with dots or slashes, e.g. `com.google.common`.
+    pub fn new(from: &str, to: &str) -> Relocation {
+        let internal_name = |package: &str| package.trim_matches(['.', '/']).replace('.', "/");
+        Relocation {
+            from: internal_name(from),
+            to: internal_name(to),
+        }
+    }
+
+    /// The new name of a class, package or resource path, or `None` if it isn't in the package or its subpackages.
+    pub fn relocate(&self, name: &str) -> Option<String> {
+        match name.strip_prefix(&self.from) {
+            Some("") => Some(self.to.clone()),
+            Some(rest) if rest.starts_with('/') => Some(format!("{}{rest}", self.to)),
+            _ => None,
+        }
+    }
+}
+
+/// What was changed to relocate a jar, by the names of the entries in the original jar.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ShadeReport {
+    /// The classes that were moved to another package.
+    pub relocated_classes: Vec<String>,
+    /// The other entries that were moved, e.g. resources in a relocated package or service files of relocated
+    /// interfaces.
+    pub relocated_resources: Vec<String>,
+    /// The new names of the service files that two or more service files were merged into.
+    pub merged_services: Vec<String>,
+    /// The entries that were left out because an earlier entry has the same name after relocation.
+    pub duplicate_entries: Vec<String>,
+    /// The signature files of a signed jar, which no longer match the relocated classes.
+    pub removed_signatures: Vec<String>,
+}
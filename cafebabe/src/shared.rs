@@ -1,6 +1,121 @@
-pub fn to_u16(bytes: &[u8], start: usize, end: usize) -> u16 {
-    let input: [u8; 2] = bytes[start..end + 1]
+pub mod annotations;
+
+use std::result::Result;
+
+use crate::errors::ClassFileError;
+use log::error;
+
+/// The `length` bytes at `start`, or an error if the data ends before them.
+pub fn to_bytes(bytes: &[u8], start: usize, length: usize) -> Result<&[u8], ClassFileError> {
+    match start
+        .checked_add(length)
+        .and_then(|end| bytes.get(start..end))
+    {
+        Some(slice) => Ok(slice),
+        None => {
+            error!(
+                "data ends unexpectedly; offset={start}, length={length}, size={}",
+                bytes.len()
+            );
+            Err(ClassFileError::Truncated)
+        }
+    }
+}
+
+pub fn to_u8(bytes: &[u8], idx: usize) -> Result<u8, ClassFileError> {
+    Ok(to_bytes(bytes, idx, 1)?[0])
+}
+
+pub fn to_u16(bytes: &[u8], start: usize, end: usize) -> Result<u16, ClassFileError> {
+    let input: [u8; 2] = to_bytes(bytes, start, end + 1 - start)?
+        .try_into()
+        .map_err(|_| ClassFileError::Truncated)?;
+    Ok(u16::from_be_bytes(input))
+}
+
+pub fn to_u32(bytes: &[u8], start: usize, end: usize) -> Result<u32, ClassFileError> {
+    let input: [u8; 4] = to_bytes(bytes, start, end + 1 - start)?
         .try_into()
-        .expect("incorrect length of slice");
-    u16::from_be_bytes(input)
+        .map_err(|_| ClassFileError::Truncated)?;
+    Ok(u32::from_be_bytes(input))
+}
+
+pub fn write_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+pub fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Reads the bytes of an attribute that is kept as bytes, and hands the indices in it to a visitor, which may change
+/// them in place.
+pub struct Cursor<'a> {
+    pub data: &'a mut [u8],
+    pub idx: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(data: &'a mut [u8]) -> Self {
+        Cursor { data, idx: 0 }
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Result<&[u8], ClassFileError> {
+        let end = self.idx.checked_add(count);
+        match end.and_then(|end| self.data.get(self.idx..end)) {
+            Some(bytes) => {
+                self.idx += count;
+                Ok(bytes)
+            }
+            None => {
+                error!(
+                    "attribute ends unexpectedly; offset={}, length={count}",
+                    self.idx
+                );
+                Err(ClassFileError::InvalidAttribute)
+            }
+        }
+    }
+
+    pub fn u8(&mut self) -> Result<u8, ClassFileError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, ClassFileError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, ClassFileError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Hands the next index to the visitor unless it is 0, which stands for no entry.
+    pub fn index(&mut self, visit: &mut dyn FnMut(&mut u16)) -> Result<(), ClassFileError> {
+        let at = self.idx;
+        let mut index = self.u16()?;
+        if index != 0 {
+            visit(&mut index);
+            self.data[at..at + 2].copy_from_slice(&index.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    pub fn indices(
+        &mut self,
+        count: usize,
+        visit: &mut dyn FnMut(&mut u16),
+    ) -> Result<(), ClassFileError> {
+        for _ in 0..count {
+            self.index(visit)?;
+        }
+        Ok(())
+    }
+
+    /// Visits a table of indices that is preceded by its length.
+    pub fn index_table(&mut self, visit: &mut dyn FnMut(&mut u16)) -> Result<(), ClassFileError> {
+        let count = usize::from(self.u16()?);
+        self.indices(count, visit)
+    }
 }
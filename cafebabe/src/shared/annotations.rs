@@ -0,0 +1,137 @@
+//! Walking the attributes that hold annotations (JVMS 4.7.16 to 4.7.22), which are kept as unknown attributes, and
+//! handing the indices in them to a visitor, which reads them or changes them in place.
+
+use std::result::Result;
+
+use log::error;
+
+use super::Cursor;
+use crate::errors::ClassFileError;
+
+/// The names of the attributes that hold annotations.
+pub const ANNOTATION_ATTRIBUTES: [&str; 7] = [
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleParameterAnnotations",
+    "RuntimeInvisibleParameterAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+    "AnnotationDefault",
+];
+
+/// What an index in an annotation points to. The indices are visited in the order of the attribute, so the elements
+/// of an annotation follow its type, and the name of an enum constant follows its enum class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationIndex {
+    /// The field descriptor of an annotation type, e.g. `Ljava/lang/Deprecated;`.
+    Type,
+    /// The name of an element of the annotation whose type was visited last.
+    ElementName,
+    /// A constant value with its tag: a Utf8 entry for `s`, and an Integer, Long, Float or Double entry for the others.
+    Constant(u8),
+    /// The field descriptor of the enum class of an enum constant.
+    EnumType,
+    /// The name of an enum constant.
+    EnumConstant,
+    /// The return descriptor of a class value, e.g. `Ljava/lang/String;`, or `V` for `void.class`.
+    Class,
+}
+
+type Visitor<'a> = dyn FnMut(AnnotationIndex, &mut u16) + 'a;
+
+/// Hands the indices in an attribute that holds annotations to the visitor, and leaves other attributes alone.
+pub fn annotation_indices(
+    name: &str,
+    info: &mut [u8],
+    visit: &mut Visitor,
+) -> Result<(), ClassFileError> {
+    let mut cursor = Cursor::new(info);
+    match name {
+        "RuntimeVisibleAnnotations" | "RuntimeInvisibleAnnotations" => {
+            annotations(&mut cursor, visit)
+        }
+        "RuntimeVisibleParameterAnnotations" | "RuntimeInvisibleParameterAnnotations" => {
+            for _ in 0..cursor.u8()? {
+                annotations(&mut cursor, visit)?;
+            }
+            Ok(())
+        }
+        "RuntimeVisibleTypeAnnotations" | "RuntimeInvisibleTypeAnnotations" => {
+            for _ in 0..cursor.u16()? {
+                type_annotation_target(&mut cursor)?;
+                annotation(&mut cursor, visit)?;
+            }
+            Ok(())
+        }
+        "AnnotationDefault" => element_value(&mut cursor, visit),
+        _ => Ok(()),
+    }
+}
+
+fn index(
+    cursor: &mut Cursor,
+    kind: AnnotationIndex,
+    visit: &mut Visitor,
+) -> Result<(), ClassFileError> {
+    cursor.index(&mut |index| visit(kind, index))
+}
+
+fn annotation(cursor: &mut Cursor, visit: &mut Visitor) -> Result<(), ClassFileError> {
+    index(cursor, AnnotationIndex::Type, visit)?;
+    for _ in 0..cursor.u16()? {
+        index(cursor, AnnotationIndex::ElementName, visit)?;
+        element_value(cursor, visit)?;
+    }
+    Ok(())
+}
+
+fn annotations(cursor: &mut Cursor, visit: &mut Visitor) -> Result<(), ClassFileError> {
+    for _ in 0..cursor.u16()? {
+        annotation(cursor, visit)?;
+    }
+    Ok(())
+}
+
+/// Visits the indices in an element value of an annotation (JVMS 4.7.16.1).
+fn element_value(cursor: &mut Cursor, visit: &mut Visitor) -> Result<(), ClassFileError> {
+    match cursor.u8()? {
+        tag @ (b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's') => {
+            index(cursor, AnnotationIndex::Constant(tag), visit)
+        }
+        b'e' => {
+            index(cursor, AnnotationIndex::EnumType, visit)?;
+            index(cursor, AnnotationIndex::EnumConstant, visit)
+        }
+        b'c' => index(cursor, AnnotationIndex::Class, visit),
+        b'@' => annotation(cursor, visit),
+        b'[' => {
+            for _ in 0..cursor.u16()? {
+                element_value(cursor, visit)?;
+            }
+            Ok(())
+        }
+        tag => {
+            error!("unknown element value tag; tag={tag}");
+            Err(ClassFileError::InvalidAttribute)
+        }
+    }
+}
+
+/// Skips the target and the path of a type annotation, which hold no indices (JVMS 4.7.20.1 and 4.7.20.2).
+fn type_annotation_target(cursor: &mut Cursor) -> Result<(), ClassFileError> {
+    let length = match cursor.u8()? {
+        0x00 | 0x01 | 0x16 => 1,
+        0x10 | 0x11 | 0x12 | 0x17 | 0x42..=0x46 => 2,
+        0x13..=0x15 => 0,
+        0x47..=0x4b => 3,
+        0x40 | 0x41 => 6 * usize::from(cursor.u16()?),
+        target_type => {
+            error!("unknown type annotation target; target_type={target_type}");
+            return Err(ClassFileError::InvalidAttribute);
+        }
+    };
+    cursor.bytes(length)?;
+    let path_length = usize::from(cursor.u8()?);
+    cursor.bytes(2 * path_length)?;
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Shrinking class files: removing debug attributes, then the constant pool entries that nothing uses any longer,
+//! after which the remaining entries are renumbered.
+//!
+//! The constant pool can only be shrunk if all indices into it are known, which requires knowing the format of all
+//! attributes. The formats of the attributes of the JVMS are known, so only class files with attributes of other tools
+//! keep their constant pool as it is.
+
+mod references;
+mod types;
+
+use std::result::Result;
+
+use log::debug;
+
+use self::references::{References, entry_indices};
+pub use self::types::{StripOptions, StripReport};
+use crate::archive::{ArchiveError, JarFile, JarWriter, is_signature_file};
+use crate::attributes::Attribute;
+use crate::constant_pool::ConstantPoolEntry;
+use crate::errors::ClassFileError;
+use crate::types::ClassFile;
+use crate::version::{ParseOptions, Strictness};
+
+const SOURCE_DEBUG_EXTENSION: &str = "SourceDebugExtension";
+
+/// Removes the debug attributes that the options select, and gives the number of attributes that were removed. Their
+/// constant pool entries stay, see [`shrink_constant_pool`].
+pub fn strip_debug_info(class_file: &mut ClassFile, options: &StripOptions) -> usize {
+    let constant_pool = &class_file.constant_pool;
+    let mut removed = 0;
+    let mut retain = |attributes: &mut Vec<Attribute>, strip: &dyn Fn(&Attribute) -> bool| {
+        let count = attributes.len();
+        attributes.retain(|attribute| !strip(attribute));
+        removed += count - attributes.len();
+    };
+
+    retain(&mut class_file.attributes, &|attribute| match attribute {
+        Attribute::SourceFile(_) => options.source_file,
+        Attribute::Unknown { name_idx, .. } => {
+            options.source_debug_extension
+                && constant_pool
+                    .string_entry(*name_idx)
+                    .is_ok_and(|name| name == SOURCE_DEBUG_EXTENSION)
+        }
+        _ => false,
+    });
+    for method in &mut class_file.methods {
+        for attribute in &mut method.attributes {
+            if let Attribute::Code(code) = attribute {
+                retain(&mut code.attributes, &|attribute| match attribute {
+                    Attribute::LineNumberTable(_) => options.line_numbers,
+                    Attribute::LocalVariableTable(_) => options.local_variables,
+                    Attribute::LocalVariableTypeTable(_) => options.local_variable_types,
+                    _ => false,
+                });
+            }
+        }
+    }
+    removed
+}
+
+/// Removes the constant pool entries that are no longer used and renumbers the others, which keep their order. Gives
+/// the number of entries that were removed, or `None` if the class file has an attribute whose format isn't known, in
+/// which case the constant pool is kept as it is.
+pub fn shrink_constant_pool(class_file: &mut ClassFile) -> Result<Option<usize>, ClassFileError> {
+    let ClassFile {
+        constant_pool,
+        class,
+        fields,
+        methods,
+        attributes,
+        ..
+    } = class_file;
+
+    // The entries that the class file uses, and then the entries that those entries use.
+    let mut pending = Vec::new();
+    let mut references = References {
+        constant_pool,
+        visit: &mut |index: &mut u16| pending.push(*index),
+    };
+    if !references.class_file(class, fields, methods, attributes)? {
+        return Ok(None);
+    }
+    let mut used = vec![false; usize::from(constant_pool.len()) + 1];
+    while let Some(index) = pending.pop() {
+        let mut entry = constant_pool.checked_entry(index)?.clone();
+        if std::mem::replace(&mut used[usize::from(index)], true) {
+            continue;
+        }
+        if matches!(
+            entry,
+            ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_)
+        ) {
+            used[usize::from(index) + 1] = true;
+        }
+        pending.extend(entry_indices(&mut entry).into_iter().map(|index| *index));
+    }
+
+    let removed = used[1..].iter().filter(|used| !**used).count();
+    if removed == 0 {
+        return Ok(Some(0));
+    }
+    debug!("removing unused constants; count={removed}");
+
+    let mut new_indices = vec![0; used.len()];
+    let mut count = 0;
+    for (index, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        count += 1;
+        new_indices[index] = count;
+    }
+    // The old constant pool is still needed to look up the names of attributes.
+    let mut references = References {
+        constant_pool,
+        visit: &mut |index: &mut u16| *index = new_indices[usize::from(*index)],
+    };
+    references.class_file(class, fields, methods, attributes)?;
+
+    constant_pool.retain(&used);
+    for index in 1..=constant_pool.len() {
+        let mut entry = constant_pool.checked_entry(index)?.clone();
+        let indices = entry_indices(&mut entry);
+        if indices.is_empty() {
+            continue;
+        }
+        for referenced in indices {
+            *referenced = new_indices[usize::from(*referenced)];
+        }
+        constant_pool.set_entry(index, entry);
+    }
+    Ok(Some(removed))
+}
+
+/// Strips the classes of a jar like [`strip_debug_info`] and [`shrink_constant_pool`], and gives the new jar, whose
+/// entries keep their order and compression. The other entries are kept as they are, except for the signature files of
+/// signed jars, which no longer match.
+pub fn strip_jar(
+    jar_file: &JarFile,
+    options: &StripOptions,
+) -> Result<(Vec<u8>, StripReport), ArchiveError> {
+    let parse_options = ParseOptions {
+        allow_preview: true,
+        strictness: Strictness::Lenient,
+        ..ParseOptions::default()
+    };
+    let mut report = StripReport::default();
+    let mut writer = JarWriter::new();
+
+    for entry in jar_file.entries() {
+        if is_signature_file(&entry.name) {
+            report.removed_signatures.push(entry.name.clone());
+            continue;
+        }
+        let mut data = jar_file.read(entry)?;
+        if entry.class_name().is_some() {
+            let invalid_class = |error| ArchiveError::InvalidClass(entry.name.clone(), error);
+            let mut class_file = crate::read_class_data_with_options(&data, &parse_options)
+                .map_err(invalid_class)?;
+            report.removed_attributes += strip_debug_info(&mut class_file, options);
+            match shrink_constant_pool(&mut class_file).map_err(invalid_class)? {
+                Some(removed) => report.removed_constants += removed,
+                None => report.kept_constant_pools.push(entry.name.clone()),
+            }
+            report.classes += 1;
+            report.original_size += data.len();
+            data = crate::write_class_data(&class_file).map_err(invalid_class)?;
+            report.stripped_size += data.len();
+        }
+        writer.add(&entry.name, &data, entry.compression)?;
+    }
+    Ok((writer.finish()?, report))
+}
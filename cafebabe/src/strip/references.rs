@@ -0,0 +1,343 @@
+//! Finding every index into the constant pool outside of the constant pool itself, to keep the entries that are used
+//! and to renumber them. Attributes that are kept as bytes are read and rewritten in place, as far as their format is
+//! known.
+
+use std::result::Result;
+
+use log::{debug, error};
+
+use crate::attributes::{Attribute, attribute_name};
+use crate::bytecode::{Operand, decode, encode};
+use crate::constant_pool::ConstantPool;
+use crate::constant_pool::types::ConstantPoolEntry;
+use crate::errors::ClassFileError;
+use crate::shared::Cursor;
+use crate::shared::annotations::{ANNOTATION_ATTRIBUTES, annotation_indices};
+use crate::types::{ClassDefinition, FieldInfo, MethodInfo};
+
+/// The attributes kept as bytes that hold no indices besides their name.
+const ATTRIBUTES_WITHOUT_INDICES: [&str; 3] = [
+    "SourceDebugExtension",
+    "ModuleResolution",
+    "CharacterRangeTable",
+];
+
+/// The indices in a constant pool entry, to other entries.
+pub(super) fn entry_indices(entry: &mut ConstantPoolEntry) -> Vec<&mut u16> {
+    match entry {
+        ConstantPoolEntry::ClassRef(index)
+        | ConstantPoolEntry::StringRef(index)
+        | ConstantPoolEntry::MethodType(index)
+        | ConstantPoolEntry::Module(index)
+        | ConstantPoolEntry::Package(index) => vec![index],
+        ConstantPoolEntry::FieldRef(field_ref) => {
+            vec![&mut field_ref.class_ref, &mut field_ref.name_type_ref]
+        }
+        ConstantPoolEntry::MethodRef(method_ref)
+        | ConstantPoolEntry::InterfaceMethodRef(method_ref) => {
+            vec![&mut method_ref.class_ref, &mut method_ref.name_type_ref]
+        }
+        ConstantPoolEntry::NameTypeDescriptor(name_type) => {
+            vec![&mut name_type.name_ref, &mut name_type.type_descriptor_ref]
+        }
+        ConstantPoolEntry::MethodHandle(method_handle) => vec![&mut method_handle.reference_ref],
+        ConstantPoolEntry::Dynamic(dynamic_ref) | ConstantPoolEntry::InvokeDynamic(dynamic_ref) => {
+            vec![&mut dynamic_ref.name_type_ref]
+        }
+        ConstantPoolEntry::String(_)
+        | ConstantPoolEntry::Integer(_)
+        | ConstantPoolEntry::Float(_)
+        | ConstantPoolEntry::Long(_)
+        | ConstantPoolEntry::Double(_)
+        | ConstantPoolEntry::Empty() => Vec::new(),
+    }
+}
+
+/// Hands every index into the constant pool in the parts of a class file to a visitor. The constant pool is only
+/// read, to look up the names of attributes, so it must still hold the entries at the indices from before the visit.
+pub(super) struct References<'a> {
+    pub(super) constant_pool: &'a ConstantPool,
+    pub(super) visit: &'a mut dyn FnMut(&mut u16),
+}
+
+impl References<'_> {
+    fn index(&mut self, index: &mut u16) {
+        if *index != 0 {
+            (self.visit)(index);
+        }
+    }
+
+    /// Visits an index that is looked up when the class file is written rather than stored in it, so that its entry
+    /// is kept.
+    fn implicit_index(&mut self, index: Option<u16>) {
+        if let Some(mut index) = index {
+            self.index(&mut index);
+        }
+    }
+
+    fn usize_index(&mut self, index: &mut usize) {
+        let mut value = *index as u16;
+        self.index(&mut value);
+        *index = usize::from(value);
+    }
+
+    /// Visits all indices of a class file, or gives `false` if it has an attribute whose format isn't known, whose
+    /// indices can't be found. The visit stops at that attribute.
+    pub(super) fn class_file(
+        &mut self,
+        class: &mut ClassDefinition,
+        fields: &mut [FieldInfo],
+        methods: &mut [MethodInfo],
+        attributes: &mut [Attribute],
+    ) -> Result<bool, ClassFileError> {
+        self.usize_index(&mut class.this_idx);
+        self.usize_index(&mut class.super_idx);
+        // The class definition holds the names of the interfaces, and their ClassRef entries are looked up.
+        for name_idx in &mut class.interfaces {
+            let class_ref = self.constant_pool.find_class_ref(*name_idx as u16);
+            self.implicit_index(class_ref);
+            self.usize_index(name_idx);
+        }
+        for field in fields {
+            self.index(&mut field.name_idx);
+            self.index(&mut field.descriptor_idx);
+            if !self.attributes(&mut field.attributes)? {
+                return Ok(false);
+            }
+        }
+        for method in methods {
+            self.index(&mut method.name_idx);
+            self.index(&mut method.descriptor_idx);
+            if !self.attributes(&mut method.attributes)? {
+                return Ok(false);
+            }
+        }
+        self.attributes(attributes)
+    }
+
+    fn attributes(&mut self, attributes: &mut [Attribute]) -> Result<bool, ClassFileError> {
+        for attribute in attributes {
+            // The names of known attributes are looked up when they are written.
+            if let Some(name) = attribute_name(attribute) {
+                self.implicit_index(self.constant_pool.find_string(name));
+            }
+            match attribute {
+                Attribute::BootstrapMethods(bootstrap_methods) => {
+                    for bootstrap_method in bootstrap_methods {
+                        self.index(&mut bootstrap_method.method_ref);
+                        bootstrap_method
+                            .arguments
+                            .iter_mut()
+                            .for_each(|argument| self.index(argument));
+                    }
+                }
+                Attribute::Module(module) => {
+                    self.index(&mut module.name_idx);
+                    self.index(&mut module.version_idx);
+                    for requires in &mut module.requires {
+                        self.index(&mut requires.requires_idx);
+                        self.index(&mut requires.version_idx);
+                    }
+                    for package in module.exports.iter_mut().chain(module.opens.iter_mut()) {
+                        self.index(&mut package.package_idx);
+                        package.to.iter_mut().for_each(|index| self.index(index));
+                    }
+                    module.uses.iter_mut().for_each(|index| self.index(index));
+                    for provides in &mut module.provides {
+                        self.index(&mut provides.provides_idx);
+                        provides.with.iter_mut().for_each(|index| self.index(index));
+                    }
+                }
+                Attribute::ModulePackages(indices) | Attribute::Exceptions(indices) => {
+                    indices.iter_mut().for_each(|index| self.index(index));
+                }
+                Attribute::ModuleMainClass(index)
+                | Attribute::ConstantValue(index)
+                | Attribute::SourceFile(index)
+                | Attribute::Signature(index) => self.index(index),
+                Attribute::MethodParameters(parameters) => {
+                    for parameter in parameters {
+                        self.index(&mut parameter.name_idx);
+                    }
+                }
+                Attribute::Deprecated | Attribute::Synthetic | Attribute::LineNumberTable(_) => {}
+                Attribute::Code(code) => {
+                    self.bytecode(&mut code.code)?;
+                    for handler in &mut code.exception_table {
+                        self.index(&mut handler.catch_type);
+                    }
+                    if !self.attributes(&mut code.attributes)? {
+                        return Ok(false);
+                    }
+                }
+                Attribute::LocalVariableTable(local_variables)
+                | Attribute::LocalVariableTypeTable(local_variables) => {
+                    for local_variable in local_variables {
+                        self.index(&mut local_variable.name_idx);
+                        self.index(&mut local_variable.descriptor_idx);
+                    }
+                }
+                Attribute::Unknown { name_idx, info } => {
+                    let name = self.constant_pool.string_entry(*name_idx)?;
+                    if !self.unknown_attribute(name, info)? {
+                        return Ok(false);
+                    }
+                    self.index(name_idx);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Visits the indices that instructions take as operands. The code is only encoded again if one of them changed,
+    /// which keeps the length of the code as long as no index grows beyond what `ldc` can hold.
+    fn bytecode(&mut self, code: &mut Vec<u8>) -> Result<(), ClassFileError> {
+        let mut instructions = decode(code)?;
+        let mut changed = false;
+        for instruction in &mut instructions {
+            let index = match &mut instruction.operand {
+                Operand::ConstantPool(index)
+                | Operand::InvokeInterface { index, .. }
+                | Operand::MultiANewArray { index, .. } => index,
+                _ => continue,
+            };
+            let old_index = *index;
+            self.index(index);
+            changed |= *index != old_index;
+        }
+        if changed {
+            *code = encode(&instructions)?;
+        }
+        Ok(())
+    }
+
+    /// Visits the indices in an attribute that is kept as bytes, or gives `false` if its format isn't known.
+    fn unknown_attribute(&mut self, name: &str, info: &mut [u8]) -> Result<bool, ClassFileError> {
+        if name == "Record" {
+            return self.record(info);
+        }
+        let visit = &mut *self.visit;
+        if ANNOTATION_ATTRIBUTES.contains(&name) {
+            annotation_indices(name, info, &mut |_, index| visit(index))?;
+            return Ok(true);
+        }
+        let mut cursor = Cursor::new(info);
+        match name {
+            "InnerClasses" => {
+                for _ in 0..cursor.u16()? {
+                    // The inner class, its outer class and its simple name, followed by its access flags.
+                    cursor.indices(3, visit)?;
+                    cursor.u16()?;
+                }
+            }
+            "EnclosingMethod" => cursor.indices(2, visit)?,
+            "NestHost" | "ModuleTarget" | "SourceID" | "CompilationID" => cursor.index(visit)?,
+            "NestMembers" | "PermittedSubclasses" => cursor.index_table(visit)?,
+            "StackMapTable" => stack_map_table(&mut cursor, visit)?,
+            "ModuleHashes" => {
+                cursor.index(visit)?;
+                for _ in 0..cursor.u16()? {
+                    cursor.index(visit)?;
+                    let length = usize::from(cursor.u16()?);
+                    cursor.bytes(length)?;
+                }
+            }
+            name if ATTRIBUTES_WITHOUT_INDICES.contains(&name) => {}
+            _ => {
+                debug!("attribute of unknown format; name={name}");
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Visits the names and descriptors of the components of a record, and their attributes (JVMS 4.7.30).
+    fn record(&mut self, info: &mut [u8]) -> Result<bool, ClassFileError> {
+        let mut cursor = Cursor::new(info);
+        for _ in 0..cursor.u16()? {
+            cursor.indices(2, self.visit)?;
+            for _ in 0..cursor.u16()? {
+                let name_at = cursor.idx;
+                let name_idx = cursor.u16()?;
+                let length = cursor.u32()? as usize;
+                let start = cursor.idx;
+                cursor.bytes(length)?;
+                let name = self.constant_pool.string_entry(name_idx)?;
+                if !self.unknown_attribute(name, &mut cursor.data[start..start + length])? {
+                    return Ok(false);
+                }
+                let mut name_cursor = Cursor {
+                    data: cursor.data,
+                    idx: name_at,
+                };
+                name_cursor.index(self.visit)?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Visits the ClassRef entries of the `Object` types in a stack map frame (JVMS 4.7.4).
+fn verification_types(
+    cursor: &mut Cursor,
+    count: usize,
+    visit: &mut dyn FnMut(&mut u16),
+) -> Result<(), ClassFileError> {
+    for _ in 0..count {
+        match cursor.u8()? {
+            // Top, Integer, Float, Double, Long, Null and UninitializedThis.
+            0..=6 => {}
+            7 => cursor.index(visit)?,
+            // The offset of the `new` instruction of an Uninitialized type.
+            8 => {
+                cursor.u16()?;
+            }
+            tag => {
+                error!("unknown verification type; tag={tag}");
+                return Err(ClassFileError::InvalidAttribute);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn stack_map_table(
+    cursor: &mut Cursor,
+    visit: &mut dyn FnMut(&mut u16),
+) -> Result<(), ClassFileError> {
+    for _ in 0..cursor.u16()? {
+        match cursor.u8()? {
+            // same_frame
+            0..=63 => {}
+            // same_locals_1_stack_item_frame
+            64..=127 => verification_types(cursor, 1, visit)?,
+            // same_locals_1_stack_item_frame_extended
+            247 => {
+                cursor.u16()?;
+                verification_types(cursor, 1, visit)?;
+            }
+            // chop_frame and same_frame_extended
+            248..=251 => {
+                cursor.u16()?;
+            }
+            // append_frame
+            frame_type @ 252..=254 => {
+                cursor.u16()?;
+                verification_types(cursor, usize::from(frame_type - 251), visit)?;
+            }
+            // full_frame
+            255 => {
+                cursor.u16()?;
+                let locals = usize::from(cursor.u16()?);
+                verification_types(cursor, locals, visit)?;
+                let stack = usize::from(cursor.u16()?);
+                verification_types(cursor, stack, visit)?;
+            }
+            frame_type => {
+                error!("reserved stack map frame type; frame_type={frame_type}");
+                return Err(ClassFileError::InvalidAttribute);
+            }
+        }
+    }
+    Ok(())
+}
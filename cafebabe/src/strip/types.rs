@@ -0,0 +1,48 @@
+/// Selects the debug attributes that [`strip_debug_info`](super::strip_debug_info) removes. None are removed by
+/// default, see [`StripOptions::all`] to remove them all.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StripOptions {
+    /// The LineNumberTable attributes of Code attributes, which put line numbers in stack traces.
+    pub line_numbers: bool,
+    /// The LocalVariableTable attributes of Code attributes, which name the local variables for debuggers.
+    pub local_variables: bool,
+    /// The LocalVariableTypeTable attributes of Code attributes, which give the generic types of local variables.
+    pub local_variable_types: bool,
+    /// The SourceFile attribute of the class, which puts the name of the source file in stack traces.
+    pub source_file: bool,
+    /// The SourceDebugExtension attribute of the class, e.g. the SMAP of a JSP or Kotlin inline functions.
+    pub source_debug_extension: bool,
+}
+
+impl StripOptions {
+    /// Removes all debug attributes.
+    pub fn all() -> StripOptions {
+        StripOptions {
+            line_numbers: true,
+            local_variables: true,
+            local_variable_types: true,
+            source_file: true,
+            source_debug_extension: true,
+        }
+    }
+}
+
+/// What was removed from the classes of a jar.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StripReport {
+    /// The number of classes that were stripped.
+    pub classes: usize,
+    /// The number of debug attributes that were removed.
+    pub removed_attributes: usize,
+    /// The number of constant pool entries that were removed because nothing used them any longer.
+    pub removed_constants: usize,
+    /// The classes whose constant pool was kept as it was, as they have an attribute whose format isn't known.
+    pub kept_constant_pools: Vec<String>,
+    /// The signature files of a signed jar, which no longer match the stripped classes.
+    pub removed_signatures: Vec<String>,
+    /// The total size of the classes before they were stripped, in bytes.
+    pub original_size: usize,
+    /// The total size of the classes after they were stripped, in bytes.
+    pub stripped_size: usize,
+}
@@ -1,9 +1,12 @@
+use crate::attributes::{Attribute, ParameterFlags};
 use crate::constant_pool::ConstantPool;
 use crate::constant_pool::types::Version;
 use bitflags::bitflags;
 
 bitflags! {
     /// Denote access permissions to and properties of this class or interface.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     pub struct AccessFlags: u16 {
         // Declared public; may be accessed from outside its package.
         const ACC_PUBLIC = 0x0001;
@@ -21,18 +24,112 @@ bitflags! {
         const ACC_ANNOTATION = 0x2000;
         // Declared as an enum type.
         const ACC_ENUM = 0x4000;
+        // Is a module, not a class or interface.
+        const ACC_MODULE = 0x8000;
     }
 }
 
+bitflags! {
+    /// Denote access permissions to and properties of a field.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct FieldAccessFlags: u16 {
+        // Declared public; may be accessed from outside its package.
+        const ACC_PUBLIC = 0x0001;
+        // Declared private; accessible only within the defining class and other classes belonging to the same nest.
+        const ACC_PRIVATE = 0x0002;
+        // Declared protected; may be accessed within subclasses.
+        const ACC_PROTECTED = 0x0004;
+        // Declared static.
+        const ACC_STATIC = 0x0008;
+        // Declared final; never directly assigned to after object construction.
+        const ACC_FINAL = 0x0010;
+        // Declared volatile; cannot be cached.
+        const ACC_VOLATILE = 0x0040;
+        // Declared transient; not written or read by a persistent object manager.
+        const ACC_TRANSIENT = 0x0080;
+        // Declared synthetic; not present in the source code.
+        const ACC_SYNTHETIC = 0x1000;
+        // Declared as an element of an enum class.
+        const ACC_ENUM = 0x4000;
+    }
+}
+
+bitflags! {
+    /// Denote access permissions to and properties of a method.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
+    pub struct MethodAccessFlags: u16 {
+        // Declared public; may be accessed from outside its package.
+        const ACC_PUBLIC = 0x0001;
+        // Declared private; accessible only within the defining class and other classes belonging to the same nest.
+        const ACC_PRIVATE = 0x0002;
+        // Declared protected; may be accessed within subclasses.
+        const ACC_PROTECTED = 0x0004;
+        // Declared static.
+        const ACC_STATIC = 0x0008;
+        // Declared final; must not be overridden.
+        const ACC_FINAL = 0x0010;
+        // Declared synchronized; invocation is wrapped by a monitor use.
+        const ACC_SYNCHRONIZED = 0x0020;
+        // A bridge method, generated by the compiler.
+        const ACC_BRIDGE = 0x0040;
+        // Declared with variable number of arguments.
+        const ACC_VARARGS = 0x0080;
+        // Declared native; implemented in a language other than the Java programming language.
+        const ACC_NATIVE = 0x0100;
+        // Declared abstract; no implementation is provided.
+        const ACC_ABSTRACT = 0x0400;
+        // In a class file whose major version number is at least 46 and at most 60: Declared strictfp.
+        const ACC_STRICT = 0x0800;
+        // Declared synthetic; not present in the source code.
+        const ACC_SYNTHETIC = 0x1000;
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ClassDefinition {
     pub this_idx: usize,
     pub super_idx: usize,
     pub interfaces: Vec<usize>,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldInfo {
+    pub access_flags: FieldAccessFlags,
+    /// Points to a String entry holding the name of the field.
+    pub name_idx: u16,
+    /// Points to a String entry holding the field descriptor.
+    pub descriptor_idx: u16,
+    pub attributes: Vec<Attribute>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MethodInfo {
+    pub access_flags: MethodAccessFlags,
+    /// Points to a String entry holding the name of the method.
+    pub name_idx: u16,
+    /// Points to a String entry holding the method descriptor.
+    pub descriptor_idx: u16,
+    pub attributes: Vec<Attribute>,
+}
+
+/// A formal parameter of a method, as recorded in the MethodParameters attribute.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Parameter {
+    /// The name of the parameter, or `None` if the compiler did not record it.
+    pub name: Option<String>,
+    pub flags: ParameterFlags,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ClassFile {
     pub version: Version,
     pub constant_pool: ConstantPool,
     pub access_flags: AccessFlags,
     pub class: ClassDefinition,
+    pub fields: Vec<FieldInfo>,
+    pub methods: Vec<MethodInfo>,
+    pub attributes: Vec<Attribute>,
 }
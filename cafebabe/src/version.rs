@@ -1,28 +1,185 @@
+use std::fmt::{Display, Formatter};
 use std::result::Result;
 
 use crate::constant_pool::types::Version;
 use crate::errors::ClassFileError;
+use crate::shared::to_u16;
 
-pub fn read_version(data: &[u8]) -> Result<Version, ClassFileError> {
-    let minor = u16::from_be_bytes([data[4], data[5]]);
-    let major = u16::from_be_bytes([data[6], data[7]]);
+/// The minor version of class files that depend on the preview features of their release.
+pub const PREVIEW_MINOR_VERSION: u16 = 65535;
 
-    match major {
-        0..0x2d => {
-            dbg!(major);
-            Err(ClassFileError::MajorVersionTooLow)
+/// The first major version with preview features, and from which the minor version must be 0 or 65535.
+pub(crate) const FIRST_PREVIEW_MAJOR_VERSION: u16 = 56;
+
+/// The releases of the Java platform by the major version of their class files, see table 4.1-A of the JVMS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[repr(u16)]
+pub enum JavaRelease {
+    /// JDK 1.0.2 and 1.1, which share a major version.
+    Java1_1 = 45,
+    Java1_2,
+    Java1_3,
+    Java1_4,
+    Java5,
+    Java6,
+    Java7,
+    Java8,
+    Java9,
+    Java10,
+    Java11,
+    Java12,
+    Java13,
+    Java14,
+    Java15,
+    Java16,
+    Java17,
+    Java18,
+    Java19,
+    Java20,
+    Java21,
+    Java22,
+    Java23,
+    Java24,
+    Java25,
+}
+
+impl JavaRelease {
+    /// The latest release whose class files can be read.
+    pub const LATEST: JavaRelease = JavaRelease::Java25;
+
+    pub fn from_major(major: u16) -> Option<JavaRelease> {
+        let release = match major {
+            45 => JavaRelease::Java1_1,
+            46 => JavaRelease::Java1_2,
+            47 => JavaRelease::Java1_3,
+            48 => JavaRelease::Java1_4,
+            49 => JavaRelease::Java5,
+            50 => JavaRelease::Java6,
+            51 => JavaRelease::Java7,
+            52 => JavaRelease::Java8,
+            53 => JavaRelease::Java9,
+            54 => JavaRelease::Java10,
+            55 => JavaRelease::Java11,
+            56 => JavaRelease::Java12,
+            57 => JavaRelease::Java13,
+            58 => JavaRelease::Java14,
+            59 => JavaRelease::Java15,
+            60 => JavaRelease::Java16,
+            61 => JavaRelease::Java17,
+            62 => JavaRelease::Java18,
+            63 => JavaRelease::Java19,
+            64 => JavaRelease::Java20,
+            65 => JavaRelease::Java21,
+            66 => JavaRelease::Java22,
+            67 => JavaRelease::Java23,
+            68 => JavaRelease::Java24,
+            69 => JavaRelease::Java25,
+            _ => return None,
+        };
+        Some(release)
+    }
+
+    /// The major version of the class files of the release.
+    pub fn major(self) -> u16 {
+        self as u16
+    }
+
+    /// The version number of the release as the JVMS lists it, e.g. `1.4`, `5.0` or `17`.
+    pub fn name(self) -> &'static str {
+        match self {
+            JavaRelease::Java1_1 => "1.1",
+            JavaRelease::Java1_2 => "1.2",
+            JavaRelease::Java1_3 => "1.3",
+            JavaRelease::Java1_4 => "1.4",
+            JavaRelease::Java5 => "5.0",
+            JavaRelease::Java6 => "6",
+            JavaRelease::Java7 => "7",
+            JavaRelease::Java8 => "8",
+            JavaRelease::Java9 => "9",
+            JavaRelease::Java10 => "10",
+            JavaRelease::Java11 => "11",
+            JavaRelease::Java12 => "12",
+            JavaRelease::Java13 => "13",
+            JavaRelease::Java14 => "14",
+            JavaRelease::Java15 => "15",
+            JavaRelease::Java16 => "16",
+            JavaRelease::Java17 => "17",
+            JavaRelease::Java18 => "18",
+            JavaRelease::Java19 => "19",
+            JavaRelease::Java20 => "20",
+            JavaRelease::Java21 => "21",
+            JavaRelease::Java22 => "22",
+            JavaRelease::Java23 => "23",
+            JavaRelease::Java24 => "24",
+            JavaRelease::Java25 => "25",
+        }
+    }
+}
+
+impl Display for JavaRelease {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Java {}", self.name())
+    }
+}
+
+/// How closely a class file must follow the rules of the JVMS to be read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strictness {
+    /// Reads whatever can be read: any minor version, and major versions above the maximum.
+    Lenient,
+    /// Checks the version like the JVM does when it loads a class.
+    #[default]
+    Standard,
+    /// Also only accepts preview class files of the maximum major version, as the JVM only runs the preview
+    /// features of its own release, and checks the format of the class file like JVMS 4.8 describes: names,
+    /// descriptors, access flags, constant pool entries, attributes and trailing bytes.
+    Strict,
+}
+
+/// Controls which class files [`read_class_data_with_options`](crate::read_class_data_with_options) accepts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseOptions {
+    /// Whether class files that depend on preview features, with minor version 65535, are accepted.
+    pub allow_preview: bool,
+    /// The highest major version that is accepted.
+    pub max_major_version: u16,
+    pub strictness: Strictness,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            allow_preview: false,
+            max_major_version: JavaRelease::LATEST.major(),
+            strictness: Strictness::default(),
         }
-        0x2d..=0x37 => Ok(Version { major, minor }),
-        0x38..=0x45 => match minor {
-            0 | 65535 => Ok(Version { major, minor }),
-            _ => {
-                dbg!(minor);
-                Err(ClassFileError::InvalidMinorVersion)
-            }
-        },
-        _ => {
+    }
+}
+
+pub fn read_version(data: &[u8], options: &ParseOptions) -> Result<Version, ClassFileError> {
+    let minor = to_u16(data, 4, 5)?;
+    let major = to_u16(data, 6, 7)?;
+    let lenient = options.strictness == Strictness::Lenient;
+
+    if major < JavaRelease::Java1_1.major() {
+        dbg!(major);
+        return Err(ClassFileError::MajorVersionTooLow);
+    }
+    if major > options.max_major_version && !lenient {
+        dbg!(major);
+        return Err(ClassFileError::MajorVersionTooHigh);
+    }
+    if major >= FIRST_PREVIEW_MAJOR_VERSION && minor == PREVIEW_MINOR_VERSION {
+        if !options.allow_preview
+            || (options.strictness == Strictness::Strict && major != options.max_major_version)
+        {
             dbg!(major);
-            Err(ClassFileError::MajorVersionTooHigh)
+            return Err(ClassFileError::PreviewNotAllowed);
         }
+    } else if major >= FIRST_PREVIEW_MAJOR_VERSION && minor != 0 && !lenient {
+        dbg!(minor);
+        return Err(ClassFileError::InvalidMinorVersion);
     }
+    Ok(Version { major, minor })
 }
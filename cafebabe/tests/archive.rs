@@ -0,0 +1,376 @@
+use cafebabe::archive::{
+    ArchiveError, Compression, JarFile, JarWriter, ResourceLookup, VersionConflictKind,
+    read_manifest,
+};
+use cafebabe::assembler::assemble;
+use cafebabe::{ClassFileError, JavaRelease, ParseOptions, write_class_data};
+use common::{setup_logging, validate_class_name};
+
+mod common;
+
+#[test]
+fn reads_deflated_jar() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/examples.jar").unwrap();
+
+    let entry = jar_file.entry("examples/ClassWithCode.class").unwrap();
+    assert_eq!(entry.compression, Compression::Deflated);
+    assert!(jar_file.entry("examples/").unwrap().is_directory());
+
+    let mut class_names: Vec<&str> = jar_file.class_names().collect();
+    class_names.sort();
+    assert_eq!(
+        class_names,
+        vec![
+            "examples/ClassWithCode",
+            "examples/ClassWithInterface",
+            "examples/ClassWithLambda",
+            "examples/ClassWithMethods",
+            "examples/ClassWithTwoInterfaces",
+            "examples/EmptyClass",
+            "examples/RecordClass",
+        ]
+    );
+    for (class_name, class_file) in jar_file.classes() {
+        validate_class_name(&class_file.unwrap(), class_name);
+    }
+
+    let source = jar_file.read_entry("examples/EmptyClass.java").unwrap();
+    assert!(
+        String::from_utf8(source)
+            .unwrap()
+            .contains("class EmptyClass")
+    );
+}
+
+#[test]
+fn reads_stored_jar() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/examples-stored.jar").unwrap();
+
+    let entry = jar_file.entry("examples/ClassWithLambda.class").unwrap();
+    assert_eq!(entry.compression, Compression::Stored);
+    let class_file = jar_file.read_class("examples/ClassWithLambda").unwrap();
+    assert_eq!(class_file.version.major, 52);
+    assert!(matches!(
+        jar_file.read_class("examples/Missing"),
+        Err(ArchiveError::EntryNotFound(_))
+    ));
+}
+
+#[test]
+fn parses_classes_with_the_options_of_the_jar() {
+    setup_logging();
+    let options = ParseOptions {
+        max_major_version: JavaRelease::Java16.major(),
+        ..ParseOptions::default()
+    };
+    let jar_file = JarFile::open("res/jars/examples.jar")
+        .unwrap()
+        .with_options(options.clone());
+    assert_eq!(jar_file.options(), &options);
+
+    assert!(matches!(
+        jar_file.read_class("examples/EmptyClass"),
+        Err(ArchiveError::InvalidClass(
+            _,
+            ClassFileError::MajorVersionTooHigh
+        ))
+    ));
+    assert!(matches!(
+        jar_file.find_class("examples/EmptyClass"),
+        Err(ArchiveError::InvalidClass(
+            _,
+            ClassFileError::MajorVersionTooHigh
+        ))
+    ));
+    assert!(
+        jar_file
+            .find_class_with_options("examples/EmptyClass", &ParseOptions::default())
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[test]
+fn reads_manifest() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/examples.jar").unwrap();
+    let manifest = jar_file.manifest().unwrap();
+
+    assert_eq!(
+        manifest.main_class.as_deref(),
+        Some("examples.ClassWithMethods")
+    );
+    assert_eq!(manifest.class_path, vec!["lib/first.jar", "lib/second.jar"]);
+    assert!(!manifest.multi_release);
+    assert_eq!(
+        manifest.automatic_module_name.as_deref(),
+        Some("cafebabe.examples")
+    );
+    assert_eq!(
+        manifest.attribute("implementation-title"),
+        Some(
+            "cafebabe examples with a long title that needs to be continued on the next line of the manifest"
+        )
+    );
+    let section = manifest.section("examples/ClassWithCode.class").unwrap();
+    assert_eq!(
+        section.attributes[1],
+        ("Sealed".to_string(), "true".to_string())
+    );
+}
+
+#[test]
+fn reads_multi_release_attribute() {
+    setup_logging();
+    let manifest = read_manifest(b"Manifest-Version: 1.0\nMulti-Release: TRUE\n").unwrap();
+    assert!(manifest.multi_release);
+    assert!(read_manifest(b"Manifest-Version 1.0\n").is_err());
+}
+
+#[test]
+fn detects_corrupt_entries() {
+    setup_logging();
+    let mut data = std::fs::read("res/jars/examples-stored.jar").unwrap();
+    let idx = data
+        .windows(12)
+        .position(|window| window == b"EmptyClass {")
+        .unwrap();
+    data[idx] = b'F';
+
+    let jar_file = JarFile::from_bytes(data).unwrap();
+    assert!(matches!(
+        jar_file.read_entry("examples/EmptyClass.java"),
+        Err(ArchiveError::CorruptEntry(_))
+    ));
+    assert!(matches!(
+        JarFile::from_bytes(b"not a jar".to_vec()),
+        Err(ArchiveError::InvalidArchive)
+    ));
+}
+
+#[test]
+fn rejects_sizes_beyond_the_end_of_the_archive() {
+    setup_logging();
+    // A stored entry `a` whose ZIP64 extra field gives the largest compressed size.
+    let mut data = Vec::new();
+    data.extend_from_slice(&0x04034b50u32.to_le_bytes());
+    data.extend_from_slice(&[0; 22]);
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.push(b'a');
+    let directory_offset = data.len() as u32;
+    data.extend_from_slice(&0x02014b50u32.to_le_bytes());
+    data.extend_from_slice(&[0; 16]);
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&12u16.to_le_bytes());
+    data.extend_from_slice(&[0; 14]);
+    data.push(b'a');
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&8u16.to_le_bytes());
+    data.extend_from_slice(&u64::MAX.to_le_bytes());
+    let directory_size = data.len() as u32 - directory_offset;
+    data.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&1u16.to_le_bytes());
+    data.extend_from_slice(&directory_size.to_le_bytes());
+    data.extend_from_slice(&directory_offset.to_le_bytes());
+    data.extend_from_slice(&[0; 2]);
+
+    let jar_file = JarFile::from_bytes(data).unwrap();
+    assert_eq!(jar_file.entries()[0].compressed_size, u64::MAX);
+    assert!(matches!(
+        jar_file.read_entry("a"),
+        Err(ArchiveError::InvalidArchive)
+    ));
+}
+
+#[test]
+fn resolves_multi_release_entries() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/multi-release.jar").unwrap();
+    assert!(jar_file.is_multi_release());
+
+    let resolved = |release| {
+        jar_file
+            .resolve_entry("examples/Greeter.class", release)
+            .unwrap()
+            .name
+            .as_str()
+    };
+    assert_eq!(resolved(8), "examples/Greeter.class");
+    assert_eq!(resolved(9), "META-INF/versions/9/examples/Greeter.class");
+    assert_eq!(resolved(10), "META-INF/versions/9/examples/Greeter.class");
+    assert_eq!(resolved(11), "META-INF/versions/11/examples/Greeter.class");
+    assert_eq!(resolved(21), "META-INF/versions/17/examples/Greeter.class");
+
+    let class_file = jar_file
+        .read_class_for_release("examples/Greeter", 17)
+        .unwrap();
+    assert_eq!(class_file.version.major, 61);
+    assert_eq!(class_file.methods.len(), 3);
+
+    assert!(
+        jar_file
+            .class_names()
+            .any(|class_name| class_name == "META-INF/versions/9/examples/Greeter")
+    );
+    assert_eq!(
+        jar_file.class_names_for_release(8),
+        vec!["examples/Greeter"]
+    );
+    assert_eq!(
+        jar_file.class_names_for_release(17),
+        vec!["examples/Greeter", "examples/Extra", "examples/Helper"]
+    );
+    assert!(matches!(
+        jar_file.read_class_for_release("examples/Helper", 11),
+        Err(ArchiveError::EntryNotFound(_))
+    ));
+}
+
+#[test]
+fn ignores_versions_without_multi_release_attribute() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/multi-release-disabled.jar").unwrap();
+    assert!(!jar_file.is_multi_release());
+
+    let entry = jar_file
+        .resolve_entry("examples/Greeter.class", 17)
+        .unwrap();
+    assert_eq!(entry.name, "examples/Greeter.class");
+    assert_eq!(
+        jar_file.class_names_for_release(17),
+        vec!["examples/Greeter"]
+    );
+    assert!(jar_file.check_versions().unwrap().is_empty());
+}
+
+#[test]
+fn reports_inconsistent_overrides() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/multi-release.jar").unwrap();
+    let conflicts = jar_file.check_versions().unwrap();
+
+    let kinds: Vec<(&str, &VersionConflictKind)> = conflicts
+        .iter()
+        .map(|conflict| (conflict.entry.as_str(), &conflict.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (
+                "META-INF/versions/9/examples/Greeter.class",
+                &VersionConflictKind::ClassVersionTooHigh { major: 55 }
+            ),
+            (
+                "META-INF/versions/11/examples/Extra.class",
+                &VersionConflictKind::NewPublicClass
+            ),
+            (
+                "META-INF/versions/17/examples/Greeter.class",
+                &VersionConflictKind::ApiChanged {
+                    differences: vec![
+                        "added method ACC_PUBLIC greetLoudly()Ljava/lang/String;".to_string()
+                    ]
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn reports_versioned_classes_that_cant_be_read() {
+    setup_logging();
+    let class_data = |class_name: &str| {
+        let source = format!(".class public {class_name}\n.super java/lang/Object");
+        write_class_data(&assemble(&source).unwrap()).unwrap()
+    };
+    let mut writer = JarWriter::new();
+    let manifest = b"Manifest-Version: 1.0\r\nMulti-Release: true\r\n\r\n";
+    writer
+        .add("META-INF/MANIFEST.MF", manifest, Compression::Stored)
+        .unwrap();
+    for (name, data) in [
+        (
+            "META-INF/versions/65535/a/Latest.class",
+            class_data("a/Latest"),
+        ),
+        ("META-INF/versions/11/a/Broken.class", b"broken".to_vec()),
+        ("META-INF/versions/17/a/Added.class", class_data("a/Added")),
+    ] {
+        writer.add(name, &data, Compression::Stored).unwrap();
+    }
+    let jar_file = JarFile::from_bytes(writer.finish().unwrap()).unwrap();
+
+    let conflicts = jar_file.check_versions().unwrap();
+    let kinds: Vec<(&str, &VersionConflictKind)> = conflicts
+        .iter()
+        .map(|conflict| (conflict.entry.as_str(), &conflict.kind))
+        .collect();
+    assert_eq!(kinds.len(), 3);
+    assert_eq!(
+        kinds[0],
+        (
+            "META-INF/versions/65535/a/Latest.class",
+            &VersionConflictKind::NewPublicClass
+        )
+    );
+    assert_eq!(kinds[1].0, "META-INF/versions/11/a/Broken.class");
+    assert!(matches!(
+        kinds[1].1,
+        VersionConflictKind::InvalidClass { .. }
+    ));
+    assert_eq!(
+        kinds[2],
+        (
+            "META-INF/versions/17/a/Added.class",
+            &VersionConflictKind::NewPublicClass
+        )
+    );
+}
+
+#[test]
+fn writes_jars_that_read_back() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/examples.jar").unwrap();
+    let mut writer = JarWriter::new();
+    for entry in jar_file.entries() {
+        let data = jar_file.read(entry).unwrap();
+        writer
+            .add(&entry.name, &data, Compression::Deflated)
+            .unwrap();
+    }
+    writer
+        .add("examples/stored.txt", b"stored", Compression::Stored)
+        .unwrap();
+    assert!(writer.contains("examples/EmptyClass.class"));
+    assert!(matches!(
+        writer.add("examples/stored.txt", b"again", Compression::Stored),
+        Err(ArchiveError::DuplicateEntry(_))
+    ));
+
+    let written = JarFile::from_bytes(writer.finish().unwrap()).unwrap();
+    assert_eq!(written.entries().len(), jar_file.entries().len() + 1);
+    for entry in jar_file.entries() {
+        assert_eq!(
+            written.read_entry(&entry.name).unwrap(),
+            jar_file.read(entry).unwrap(),
+            "{}",
+            entry.name
+        );
+    }
+    assert_eq!(written.manifest(), jar_file.manifest());
+    let class_entry = written.entry("examples/ClassWithCode.class").unwrap();
+    assert_eq!(class_entry.compression, Compression::Deflated);
+    let stored_entry = written.entry("examples/stored.txt").unwrap();
+    assert_eq!(stored_entry.compression, Compression::Stored);
+    assert_eq!(
+        written.entry("examples/").unwrap().compression,
+        Compression::Stored
+    );
+}
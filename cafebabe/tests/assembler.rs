@@ -0,0 +1,129 @@
+use std::fs;
+
+use cafebabe::assembler::{assemble, disassemble};
+use cafebabe::attributes::Attribute;
+use cafebabe::bytecode::Opcode;
+use cafebabe::{read_class_data, write_class_data};
+use common::{class_files, setup_logging};
+
+mod common;
+
+const HELLO: &str = r#"
+.version 52 0
+.class public super examples/Hello
+.super java/lang/Object
+.implements java/lang/Runnable
+.sourcefile "Hello.java"
+
+.field private static final GREETING Ljava/lang/String;
+    .constantvalue String "Hello, world!"
+.end field
+
+.method public <init> ()V
+    .code stack 1 locals 1
+        aload_0
+        invokespecial java/lang/Object <init> ()V
+        return
+    .end code
+.end method
+
+.method public run ()V
+    .code stack 2 locals 2
+        iconst_0
+        istore_1
+    loop:
+        getstatic java/lang/System out Ljava/io/PrintStream;
+        ldc String "Hello, world!"
+        invokevirtual java/io/PrintStream println (Ljava/lang/String;)V
+        iinc 1 1
+        iload_1
+        iconst_3
+        if_icmplt loop
+        return
+    .end code
+.end method
+"#;
+
+#[test]
+fn assembles_hand_written_class() {
+    setup_logging();
+    let class_file = assemble(HELLO).unwrap();
+    let data = write_class_data(&class_file).unwrap();
+    let class_file = read_class_data(&data).unwrap();
+
+    assert_eq!(class_file.version.major, 52);
+    assert_eq!(class_file.fields.len(), 1);
+    assert_eq!(class_file.methods.len(), 2);
+    assert!(matches!(
+        class_file.fields[0].attributes[0],
+        Attribute::ConstantValue(_)
+    ));
+
+    let instructions = class_file.methods[1]
+        .code()
+        .unwrap()
+        .instructions()
+        .unwrap();
+    let branch = instructions
+        .iter()
+        .find(|instruction| instruction.opcode == Opcode::IfIcmplt)
+        .unwrap();
+    assert_eq!(branch.branch_targets(), vec![2]);
+}
+
+#[test]
+fn reports_errors_with_line() {
+    setup_logging();
+    let source = ".class public examples/Broken\n.method public run ()V\n    .code stack 1 locals 1\n        goto nowhere\n    .end code\n.end method\n";
+    let Err(error) = assemble(source) else {
+        panic!("Expect an undefined label to be an error");
+    };
+    assert_eq!(error.line, 4);
+}
+
+#[test]
+fn reassembles_java8_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java8");
+}
+
+#[test]
+fn reassembles_java11_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java11");
+}
+
+#[test]
+fn reassembles_java17_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java17");
+}
+
+#[test]
+fn reassembles_java21_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java21");
+}
+
+#[test]
+fn reassembles_java25_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java25");
+}
+
+fn validate_round_trips(directory: &str) {
+    let paths = class_files(std::path::Path::new(directory));
+    assert!(!paths.is_empty(), "Expect class files in {directory}");
+    for path in paths {
+        let data = fs::read(&path).unwrap();
+        let class_file = read_class_data(&data).unwrap();
+        let source = disassemble(&class_file).unwrap();
+        let assembled = assemble(&source)
+            .unwrap_or_else(|error| panic!("Can't assemble {path:?}: {error}\n{source}"));
+        let written = write_class_data(&assembled).unwrap();
+        assert!(
+            written == data,
+            "Expect {path:?} to be reassembled unchanged"
+        );
+    }
+}
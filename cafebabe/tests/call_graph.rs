@@ -0,0 +1,339 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cafebabe::call_graph::{Algorithm, CallGraph, CallKind, MethodId};
+use cafebabe::class_path::ClassPath;
+use cafebabe::hierarchy::ClassHierarchy;
+use common::{setup_logging, write_classes};
+
+mod common;
+
+const SHAPE: &str = r#"
+.class public interface abstract app/Shape
+.super java/lang/Object
+
+.method public abstract area ()D
+.end method
+"#;
+
+const CIRCLE: &str = r#"
+.class public super app/Circle
+.super java/lang/Object
+.implements app/Shape
+
+.method public <init> ()V
+    .code stack 1 locals 1
+        aload_0
+        invokespecial java/lang/Object <init> ()V
+        return
+    .end code
+.end method
+
+.method public area ()D
+    .code stack 2 locals 1
+        ldc2_w Double 3.14
+        dreturn
+    .end code
+.end method
+
+.method public toString ()Ljava/lang/String;
+    .code stack 1 locals 1
+        ldc String "circle"
+        areturn
+    .end code
+.end method
+
+.method public unused ()V
+    .code stack 0 locals 1
+        return
+    .end code
+.end method
+"#;
+
+const SQUARE: &str = r#"
+.class public super app/Square
+.super java/lang/Object
+.implements app/Shape
+
+.method public <init> ()V
+    .code stack 1 locals 1
+        aload_0
+        invokespecial java/lang/Object <init> ()V
+        return
+    .end code
+.end method
+
+.method public area ()D
+    .code stack 2 locals 1
+        dconst_1
+        dreturn
+    .end code
+.end method
+"#;
+
+const CONFIG: &str = r#"
+.class public super app/Config
+.super java/lang/Object
+
+.field public static VALUE I
+
+.method static <clinit> ()V
+    .code stack 1 locals 0
+        iconst_1
+        putstatic app/Config VALUE I
+        return
+    .end code
+.end method
+"#;
+
+const MAIN: &str = r#"
+.class public super app/Main
+.super java/lang/Object
+
+.method public static main ([Ljava/lang/String;)V
+    .code stack 2 locals 2
+        new app/Circle
+        dup
+        invokespecial app/Circle <init> ()V
+        astore_1
+        aload_1
+        invokeinterface app/Shape area ()D
+        pop2
+        invokedynamic InvokeDynamic 0 run ()Ljava/lang/Runnable;
+        invokeinterface java/lang/Runnable run ()V
+        getstatic app/Config VALUE I
+        pop
+        return
+    .end code
+.end method
+
+.method private static lambda$main$0 ()V
+    .code stack 0 locals 0
+        invokestatic app/Main unreached ()V
+        return
+    .end code
+.end method
+
+.method static unreached ()V
+    .code stack 0 locals 0
+        return
+    .end code
+.end method
+
+.bootstrapmethods
+    MethodHandle invokeStatic Method java/lang/invoke/LambdaMetafactory metafactory (Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite; MethodType ()V MethodHandle invokeStatic Method app/Main lambda$main$0 ()V MethodType ()V
+.end bootstrapmethods
+"#;
+
+const INSTANCE_MAIN: &str = r#"
+.class super app/Instance
+.super java/lang/Object
+
+.method <init> ()V
+    .code stack 1 locals 1
+        aload_0
+        invokespecial java/lang/Object <init> ()V
+        return
+    .end code
+.end method
+
+.method main ()V
+    .code stack 0 locals 1
+        return
+    .end code
+.end method
+"#;
+
+fn example_classes() -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("cafebabe-call-graph-{}", std::process::id()));
+    write_classes(
+        &directory,
+        &[SHAPE, CIRCLE, SQUARE, CONFIG, MAIN, INSTANCE_MAIN],
+    );
+    directory
+}
+
+fn method(class_name: &str, name: &str, descriptor: &str) -> MethodId {
+    MethodId::new(class_name, name, descriptor)
+}
+
+#[test]
+fn builds_call_graphs_from_main_methods() {
+    setup_logging();
+    let directory = example_classes();
+    let mut class_path = ClassPath::new();
+    class_path.add(&directory).unwrap();
+    let hierarchy = ClassHierarchy::from_class_path(&class_path).unwrap();
+
+    let main = method("app/Main", "main", "([Ljava/lang/String;)V");
+    assert_eq!(
+        CallGraph::main_methods(&class_path).unwrap(),
+        [
+            method("app/Instance", "<init>", "()V"),
+            method("app/Instance", "main", "()V"),
+            main.clone(),
+        ]
+    );
+
+    let graph = CallGraph::build(
+        &class_path,
+        &hierarchy,
+        std::slice::from_ref(&main),
+        Algorithm::RapidType,
+    )
+    .unwrap();
+    let circle_area = method("app/Circle", "area", "()D");
+    let square_area = method("app/Square", "area", "()D");
+    let lambda = method("app/Main", "lambda$main$0", "()V");
+    for reachable in [
+        &main,
+        &circle_area,
+        &lambda,
+        &method("app/Main", "unreached", "()V"),
+        &method("app/Circle", "<init>", "()V"),
+        &method("app/Circle", "toString", "()Ljava/lang/String;"),
+        &method("app/Config", "<clinit>", "()V"),
+        &method("java/lang/Object", "<init>", "()V"),
+        &method("java/lang/Runnable", "run", "()V"),
+    ] {
+        assert!(graph.is_reachable(reachable), "{reachable}");
+    }
+    assert!(!graph.is_reachable(&square_area));
+    assert!(!graph.is_reachable(&method("app/Circle", "unused", "()V")));
+    assert_eq!(
+        graph.instantiated_classes(),
+        &["app/Circle".to_string()].into()
+    );
+    assert_eq!(
+        graph.external_methods().collect::<Vec<_>>(),
+        [
+            &method("java/lang/Object", "<init>", "()V"),
+            &method("java/lang/Runnable", "run", "()V"),
+        ]
+    );
+    assert!(graph.missing_classes().contains("java/lang/Object"));
+
+    let kinds: Vec<(CallKind, &str)> = graph
+        .callees(&main)
+        .map(|edge| (edge.kind, edge.callee.name.as_str()))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (CallKind::Callback, "toString"),
+            (CallKind::Special, "<init>"),
+            (CallKind::Interface, "area"),
+            (CallKind::Lambda, "lambda$main$0"),
+            (CallKind::Interface, "run"),
+            (CallKind::Initialization, "<clinit>"),
+        ]
+    );
+    assert_eq!(graph.callers(&lambda).count(), 1);
+
+    let path = graph
+        .path_to(&method("app/Main", "unreached", "()V"))
+        .unwrap();
+    assert_eq!(path.len(), 2);
+    assert_eq!(path[0].callee, &lambda);
+    assert_eq!(path[0].offset, 15);
+    assert!(graph.path_to(&main).unwrap().is_empty());
+    assert_eq!(graph.path_to(&square_area), None);
+    assert_eq!(
+        graph.reachable_from(&lambda),
+        [&lambda, &method("app/Main", "unreached", "()V")]
+    );
+
+    // Class hierarchy analysis dispatches to all classes that implement the interface.
+    let graph =
+        CallGraph::build(&class_path, &hierarchy, &[main], Algorithm::ClassHierarchy).unwrap();
+    assert!(graph.is_reachable(&square_area));
+    assert!(!graph.is_reachable(&method("app/Square", "<init>", "()V")));
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn follows_lambdas_and_method_references() {
+    setup_logging();
+    let mut class_path = ClassPath::new();
+    class_path.add("res/java17").unwrap();
+    let hierarchy = ClassHierarchy::from_class_path(&class_path).unwrap();
+    let class_name = "examples/ClassWithLambda";
+    let entry_points = [
+        method(
+            class_name,
+            "greeter",
+            "(Ljava/lang/String;)Ljava/util/function/Supplier;",
+        ),
+        method(class_name, "length", "()Ljava/util/function/Function;"),
+    ];
+    let graph =
+        CallGraph::build(&class_path, &hierarchy, &entry_points, Algorithm::RapidType).unwrap();
+
+    let implementation = method(
+        class_name,
+        "lambda$greeter$0",
+        "(Ljava/lang/String;)Ljava/lang/String;",
+    );
+    let edges: Vec<_> = graph.callees(&entry_points[0]).collect();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0].kind, CallKind::Lambda);
+    assert_eq!(edges[0].callee, &implementation);
+    // String concatenation calls nothing.
+    assert_eq!(graph.callees(&implementation).count(), 0);
+    // A method reference to a method of the JDK ends there.
+    let edges: Vec<_> = graph.callees(&entry_points[1]).collect();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(
+        edges[0].callee,
+        &method("java/lang/String", "length", "()I")
+    );
+    assert!(
+        graph
+            .external_methods()
+            .any(|method| method == edges[0].callee)
+    );
+    assert!(graph.invalid_classes().is_empty());
+}
+
+#[test]
+fn reports_classes_with_invalid_constant_pool_indices() {
+    setup_logging();
+    let directory = std::env::temp_dir().join(format!(
+        "cafebabe-call-graph-damaged-{}",
+        std::process::id()
+    ));
+    write_classes(
+        &directory,
+        &[r#"
+.class public app/Damaged
+.super java/lang/Object
+
+.method public static main ([Ljava/lang/String;)V
+    .code stack 1 locals 1
+        ldc #255
+        pop
+        return
+    .end code
+.end method
+"#],
+    );
+    let mut class_path = ClassPath::new();
+    class_path.add(&directory).unwrap();
+    let hierarchy = ClassHierarchy::from_class_path(&class_path).unwrap();
+
+    let main = method("app/Damaged", "main", "([Ljava/lang/String;)V");
+    let graph = CallGraph::build(
+        &class_path,
+        &hierarchy,
+        std::slice::from_ref(&main),
+        Algorithm::RapidType,
+    )
+    .unwrap();
+    assert_eq!(
+        graph.invalid_classes().iter().collect::<Vec<_>>(),
+        ["app/Damaged"]
+    );
+    fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,302 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cafebabe::archive::{ArchiveError, JImage, ResourceLookup};
+use cafebabe::class_path::{ClassPath, ClassPathSource, Directory, ShadowedClass};
+use cafebabe::hierarchy::ClassHierarchy;
+use cafebabe::{ClassFileError, JavaRelease, ParseOptions};
+use common::jimage::{ImageCompression, ImageResource, write_jimage};
+use common::{setup_logging, validate_class_name};
+
+mod common;
+
+fn example_image() -> ClassPathSource {
+    let resources = ["RecordClass", "EmptyClass", "ClassWithInterface"].map(|name| ImageResource {
+        module: "examples".to_string(),
+        name: format!("examples/{name}.class"),
+        content: fs::read(format!("res/java17/examples/{name}.class")).unwrap(),
+        compression: ImageCompression::Zip,
+    });
+    ClassPathSource::Image(JImage::from_bytes(write_jimage(&resources)).unwrap())
+}
+
+fn example_class_path() -> ClassPath {
+    let mut class_path = ClassPath::new();
+    class_path.add("res/jars/examples-stored.jar").unwrap();
+    class_path.add("res/java17").unwrap();
+    class_path.add("res/jars/examples.jmod").unwrap();
+    class_path.push("lib/modules", example_image());
+    class_path
+}
+
+#[test]
+fn finds_classes_in_the_first_entry_that_has_them() {
+    setup_logging();
+    let class_path = example_class_path();
+
+    let class_file = class_path
+        .find_class("examples/ClassWithCode")
+        .unwrap()
+        .unwrap();
+    validate_class_name(&class_file, "examples/ClassWithCode");
+    assert_eq!(class_file.version.major, 52, "Expect the class of the jar");
+    let record_class = class_path
+        .find_class("examples/RecordClass")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        record_class.version.major, 61,
+        "Expect the class of the directory"
+    );
+    assert!(class_path.find_class("examples/Main").unwrap().is_some());
+    assert!(class_path.find_class("examples/Missing").unwrap().is_none());
+
+    let location = class_path.locate("examples/spi/Greeting").unwrap().unwrap();
+    assert_eq!(location.location, PathBuf::from("res/jars/examples.jmod"));
+    assert!(matches!(location.source, ClassPathSource::Jmod(_)));
+    assert!(location.source.is_jdk());
+    let (location, data) = class_path
+        .locate_class_data("examples/RecordClass")
+        .unwrap()
+        .unwrap();
+    assert_eq!(location.location, PathBuf::from("res/java17"));
+    assert!(!location.source.is_jdk());
+    assert_eq!(
+        data,
+        fs::read("res/java17/examples/RecordClass.class").unwrap()
+    );
+    assert_eq!(
+        class_path.find_class_data("examples/EmptyClass").unwrap(),
+        Some(fs::read("res/java8/examples/EmptyClass.class").unwrap())
+    );
+    assert!(
+        class_path
+            .find_resource("META-INF/MANIFEST.MF")
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[test]
+fn finds_no_resources_outside_of_directories() {
+    setup_logging();
+    let directory = Directory::open("res/java17").unwrap();
+    assert!(
+        directory
+            .find_resource("examples/EmptyClass.class")
+            .unwrap()
+            .is_some()
+    );
+    assert!(directory.contains_resource("examples/EmptyClass.class"));
+
+    let absolute = fs::canonicalize("res/java17/examples/EmptyClass.class").unwrap();
+    let absolute = absolute.to_str().unwrap();
+    for name in [
+        absolute,
+        "/examples/EmptyClass.class",
+        "examples/../examples/EmptyClass.class",
+        "../java17/examples/EmptyClass.class",
+    ] {
+        assert!(directory.find_resource(name).unwrap().is_none(), "{name}");
+        assert!(!directory.contains_resource(name), "{name}");
+    }
+}
+
+#[test]
+fn caches_parsed_classes() {
+    setup_logging();
+    let class_path = example_class_path();
+
+    let first = class_path
+        .find_class("examples/ClassWithLambda")
+        .unwrap()
+        .unwrap();
+    let second = class_path
+        .find_class("examples/ClassWithLambda")
+        .unwrap()
+        .unwrap();
+    assert!(
+        Arc::ptr_eq(&first, &second),
+        "Expect the class to be parsed once"
+    );
+}
+
+#[test]
+fn reports_shadowed_classes() {
+    setup_logging();
+    let class_path = example_class_path();
+
+    let class_names = class_path.class_names();
+    assert!(class_names.contains(&"examples/Main"));
+    assert!(class_names.contains(&"examples/RecordClass"));
+    assert!(!class_names.iter().any(|name| name.ends_with("module-info")));
+
+    let shadowed = class_path.shadowed_classes();
+    let names: Vec<&str> = shadowed
+        .iter()
+        .map(|class| class.class_name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "examples/ClassWithCode",
+            "examples/ClassWithInterface",
+            "examples/ClassWithLambda",
+            "examples/ClassWithMethods",
+            "examples/ClassWithTwoInterfaces",
+            "examples/EmptyClass",
+            "examples/RecordClass",
+        ]
+    );
+    assert_eq!(
+        shadowed[5],
+        ShadowedClass {
+            class_name: "examples/EmptyClass".to_string(),
+            location: PathBuf::from("res/jars/examples-stored.jar"),
+            shadowed: vec![PathBuf::from("res/java17"), PathBuf::from("lib/modules")],
+        }
+    );
+    assert_eq!(shadowed[6].location, PathBuf::from("res/java17"));
+    assert_eq!(shadowed[6].shadowed, [PathBuf::from("lib/modules")]);
+}
+
+#[test]
+fn resolves_multi_release_jars_for_the_release() {
+    setup_logging();
+    let class_path = ClassPath::parse("res/java8:res/jars/multi-release.jar").unwrap();
+    assert_eq!(class_path.entries().len(), 2);
+    assert!(class_path.find_class("examples/Helper").unwrap().is_none());
+    let greeter = class_path.find_class("examples/Greeter").unwrap().unwrap();
+    assert_eq!(greeter.version.major, 52);
+
+    let class_path = class_path.with_release(17);
+    assert!(class_path.find_class("examples/Helper").unwrap().is_some());
+    let greeter = class_path.find_class("examples/Greeter").unwrap().unwrap();
+    assert_eq!(greeter.version.major, 61);
+    assert!(class_path.class_names().contains(&"examples/Extra"));
+}
+
+#[test]
+fn parses_classes_with_the_options_of_the_class_path() {
+    setup_logging();
+    let options = ParseOptions {
+        max_major_version: JavaRelease::Java16.major(),
+        ..ParseOptions::default()
+    };
+    let class_path = ClassPath::parse("res/java17")
+        .unwrap()
+        .with_options(options);
+
+    assert!(matches!(
+        class_path.find_class("examples/EmptyClass"),
+        Err(ArchiveError::InvalidClass(
+            _,
+            ClassFileError::MajorVersionTooHigh
+        ))
+    ));
+    assert!(class_path.classes().all(|class| class.class_file.is_err()));
+    let hierarchy = ClassHierarchy::from_class_path(&class_path).unwrap();
+    assert!(
+        hierarchy
+            .invalid_classes()
+            .contains(&"examples/EmptyClass".to_string())
+    );
+}
+
+/// A directory with a valid class, an invalid one and a truncated one, followed by a jmod.
+fn class_path_with_invalid_class(test: &str) -> (Vec<PathBuf>, PathBuf) {
+    let directory =
+        std::env::temp_dir().join(format!("cafebabe-scan-{test}-{}", std::process::id()));
+    fs::create_dir_all(directory.join("examples")).unwrap();
+    fs::copy(
+        "res/java17/examples/EmptyClass.class",
+        directory.join("examples/EmptyClass.class"),
+    )
+    .unwrap();
+    fs::write(directory.join("examples/Broken.class"), b"not a class").unwrap();
+    let data = fs::read("res/java17/examples/ClassWithCode.class").unwrap();
+    fs::write(directory.join("examples/Truncated.class"), &data[..200]).unwrap();
+    let paths = vec![directory.clone(), PathBuf::from("res/jars/examples.jmod")];
+    (paths, directory)
+}
+
+#[test]
+fn scans_all_classes_with_their_errors() {
+    setup_logging();
+    let (paths, directory) = class_path_with_invalid_class("sequential");
+    let mut class_path = ClassPath::new();
+    for path in &paths {
+        class_path.add(path).unwrap();
+    }
+
+    let scanned: Vec<_> = class_path.classes().collect();
+    fs::remove_dir_all(directory).unwrap();
+
+    assert_eq!(scanned[0].class_name, "examples/Broken");
+    assert!(matches!(
+        &scanned[0].class_file,
+        Err(ArchiveError::InvalidClass(name, ClassFileError::InvalidMagicNumber))
+            if name == "examples/Broken.class"
+    ));
+    assert_eq!(scanned[1].class_name, "examples/EmptyClass");
+    validate_class_name(
+        scanned[1].class_file.as_ref().unwrap(),
+        "examples/EmptyClass",
+    );
+    assert_eq!(scanned[1].entry.location, paths[0]);
+    assert_eq!(scanned[2].class_name, "examples/Truncated");
+    assert!(matches!(
+        &scanned[2].class_file,
+        Err(ArchiveError::InvalidClass(name, ClassFileError::Truncated))
+            if name == "examples/Truncated.class"
+    ));
+
+    let jmod_classes: Vec<&str> = scanned[3..].iter().map(|class| class.class_name).collect();
+    let mut jmod = ClassPath::new();
+    jmod.add(&paths[1]).unwrap();
+    assert_eq!(jmod_classes, jmod.class_names());
+    assert!(jmod_classes.contains(&"examples/spi/Greeting"));
+    assert!(
+        scanned[3..]
+            .iter()
+            .all(|class| class.class_file.is_ok() && class.entry.location == paths[1])
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn scans_classes_in_parallel() {
+    use rayon::prelude::*;
+
+    setup_logging();
+    let (paths, directory) = class_path_with_invalid_class("parallel");
+    let class_path = ClassPath::par_open(&paths).unwrap();
+    let locations: Vec<&PathBuf> = class_path
+        .entries()
+        .iter()
+        .map(|entry| &entry.location)
+        .collect();
+    assert_eq!(locations, [&paths[0], &paths[1]]);
+
+    let summarize = |class: cafebabe::class_path::ScannedClass| {
+        (class.class_name.to_string(), class.class_file.is_ok())
+    };
+    let mut sequential: Vec<(String, bool)> = class_path.classes().map(summarize).collect();
+    let mut parallel: Vec<(String, bool)> = class_path.par_classes().map(summarize).collect();
+    fs::remove_dir_all(directory).unwrap();
+
+    sequential.sort();
+    parallel.sort();
+    assert_eq!(parallel, sequential);
+    let invalid: Vec<&str> = parallel
+        .iter()
+        .filter(|(_, valid)| !valid)
+        .map(|(class_name, _)| class_name.as_str())
+        .collect();
+    assert_eq!(
+        invalid,
+        ["examples/Broken", "examples/Truncated"],
+        "Expect only the broken and the truncated class to fail"
+    );
+}
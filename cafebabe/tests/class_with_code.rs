@@ -0,0 +1,121 @@
+use cafebabe::attributes::Attribute;
+use cafebabe::bytecode::{Opcode, Operand};
+use cafebabe::constant_pool::ConstantPoolEntry;
+use cafebabe::{ClassFile, MethodInfo};
+use common::read_class_from_path;
+use common::setup_logging;
+
+mod common;
+
+#[test]
+fn reads_java8_class_with_code() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java8/examples/ClassWithCode.class");
+    assert_eq!(class_file.version.major, 52);
+    validate_code(&class_file);
+}
+
+#[test]
+fn reads_java17_class_with_code() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/ClassWithCode.class");
+    assert_eq!(class_file.version.major, 61);
+    validate_code(&class_file);
+}
+
+fn find_method<'a>(class_file: &'a ClassFile, name: &str) -> &'a MethodInfo {
+    class_file
+        .methods
+        .iter()
+        .find(|method| method.name(&class_file.constant_pool).unwrap() == name)
+        .unwrap_or_else(|| panic!("Expected method {name}"))
+}
+
+fn validate_code(class_file: &ClassFile) {
+    let pool = &class_file.constant_pool;
+    assert_eq!(
+        class_file.source_file().unwrap().unwrap(),
+        "ClassWithCode.java"
+    );
+
+    for method in &class_file.methods {
+        let code = method.code().expect("Expected method to have code");
+        let instructions = code.instructions().unwrap();
+        assert!(!instructions.is_empty());
+        assert!(!code.line_numbers().is_empty());
+    }
+
+    let big = &class_file.fields[0];
+    let value_idx = big.constant_value_idx().unwrap();
+    assert_eq!(
+        pool.get_entry(value_idx),
+        &ConstantPoolEntry::Long(1234567890123)
+    );
+
+    let describe = find_method(class_file, "describe").code().unwrap();
+    let instructions = describe.instructions().unwrap();
+    assert_eq!(instructions[1].opcode, Opcode::Tableswitch);
+    match &instructions[1].operand {
+        Operand::TableSwitch {
+            default,
+            low,
+            high,
+            targets,
+        } => {
+            assert_eq!((*low, *high), (1, 3));
+            assert_eq!(targets.len(), 3);
+            for target in targets.iter().chain([default]) {
+                assert!(
+                    instructions
+                        .iter()
+                        .any(|instruction| instruction.offset == *target)
+                );
+            }
+        }
+        other => panic!("Expected tableswitch operands, found {other:?}"),
+    }
+
+    let sparse = find_method(class_file, "sparse").code().unwrap();
+    let instructions = sparse.instructions().unwrap();
+    match &instructions[1].operand {
+        Operand::LookupSwitch { pairs, .. } => {
+            let keys: Vec<i32> = pairs.iter().map(|(key, _)| *key).collect();
+            assert_eq!(keys, vec![1, 1000]);
+        }
+        other => panic!("Expected lookupswitch operands, found {other:?}"),
+    }
+
+    let parse = find_method(class_file, "parse").code().unwrap();
+    assert_eq!(parse.exception_table.len(), 1);
+    let handler = parse.exception_table[0];
+    assert_eq!(
+        pool.class_name(handler.catch_type as usize).unwrap(),
+        "java/lang/NumberFormatException"
+    );
+    let local_names: Vec<&String> = parse
+        .local_variables()
+        .iter()
+        .map(|local| pool.string_entry(local.name_idx).unwrap())
+        .collect();
+    assert!(local_names.contains(&&"this".to_string()));
+    assert!(local_names.contains(&&"value".to_string()));
+    assert!(local_names.contains(&&"e".to_string()));
+
+    let sum = find_method(class_file, "sum").code().unwrap();
+    let instructions = sum.instructions().unwrap();
+    assert!(
+        instructions
+            .iter()
+            .any(|instruction| instruction.opcode == Opcode::Iinc)
+    );
+    let backward_jump = instructions
+        .iter()
+        .find(|instruction| instruction.opcode == Opcode::Goto)
+        .unwrap();
+    assert!(backward_jump.branch_targets()[0] < backward_jump.offset);
+    assert!(
+        sum.attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::LocalVariableTable(_)))
+    );
+}
@@ -0,0 +1,148 @@
+use cafebabe::attributes::Attribute;
+use cafebabe::bootstrap::{BootstrapArgument, BootstrapKind, CallSite};
+use cafebabe::constant_pool::ConstantPoolEntry;
+use cafebabe::{ClassFile, ClassFileError};
+use common::read_class_from_path;
+use common::setup_logging;
+
+mod common;
+
+#[test]
+fn reads_java8_class_with_lambda() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java8/examples/ClassWithLambda.class");
+    assert_eq!(class_file.version.major, 52);
+    let call_sites = invoke_dynamic_call_sites(&class_file);
+    assert_eq!(call_sites.len(), 2);
+    validate_lambda(&call_sites[0]);
+    validate_method_reference(&call_sites[1]);
+}
+
+#[test]
+fn reads_java11_class_with_lambda() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java11/examples/ClassWithLambda.class");
+    assert_eq!(class_file.version.major, 55);
+    let call_sites = invoke_dynamic_call_sites(&class_file);
+    assert_eq!(call_sites.len(), 3);
+    validate_lambda(&call_sites[0]);
+    validate_method_reference(&call_sites[1]);
+    validate_string_concatenation(&call_sites[2]);
+}
+
+#[test]
+fn reads_java17_class_with_lambda() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/ClassWithLambda.class");
+    assert_eq!(class_file.version.major, 61);
+    let call_sites = invoke_dynamic_call_sites(&class_file);
+    assert_eq!(call_sites.len(), 3);
+    validate_lambda(&call_sites[0]);
+    validate_method_reference(&call_sites[1]);
+    validate_string_concatenation(&call_sites[2]);
+}
+
+#[test]
+fn reads_java17_record_class() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/RecordClass.class");
+    let call_sites = invoke_dynamic_call_sites(&class_file);
+    assert_eq!(call_sites.len(), 3);
+
+    let names: Vec<&str> = call_sites.iter().map(|site| site.name.as_str()).collect();
+    assert!(names.contains(&"toString"));
+    assert!(names.contains(&"hashCode"));
+    assert!(names.contains(&"equals"));
+
+    for call_site in call_sites {
+        assert_eq!(call_site.kind(), BootstrapKind::ObjectMethods);
+        assert_eq!(
+            call_site.arguments[0],
+            BootstrapArgument::Class("examples/RecordClass".to_string())
+        );
+        assert_eq!(
+            call_site.arguments[1],
+            BootstrapArgument::String("x;y".to_string())
+        );
+    }
+}
+
+#[test]
+fn rejects_call_sites_outside_of_the_constant_pool() {
+    setup_logging();
+    let mut class_file = read_class_from_path("res/java17/examples/ClassWithLambda.class");
+    for index in [0, u16::MAX] {
+        assert!(matches!(
+            class_file.call_site(index),
+            Err(ClassFileError::InvalidConstantPoolIndex)
+        ));
+    }
+
+    let index = class_file
+        .constant_pool
+        .items()
+        .position(|entry| matches!(entry, ConstantPoolEntry::InvokeDynamic(_)))
+        .unwrap() as u16
+        + 1;
+    for attribute in &mut class_file.attributes {
+        if let Attribute::BootstrapMethods(methods) = attribute {
+            for method in methods {
+                method.arguments = vec![0];
+            }
+        }
+    }
+    assert!(matches!(
+        class_file.call_site(index),
+        Err(ClassFileError::InvalidConstantPoolIndex)
+    ));
+}
+
+fn invoke_dynamic_call_sites(class_file: &ClassFile) -> Vec<CallSite> {
+    let mut call_sites: Vec<CallSite> = class_file
+        .constant_pool
+        .items()
+        .enumerate()
+        .filter(|(_, entry)| matches!(entry, ConstantPoolEntry::InvokeDynamic(_)))
+        .map(|(idx, _)| class_file.call_site((idx + 1) as u16).unwrap())
+        .collect();
+    call_sites.sort_by_key(|call_site| call_site.kind() as u8);
+    call_sites
+}
+
+fn validate_lambda(call_site: &CallSite) {
+    assert_eq!(call_site.kind(), BootstrapKind::LambdaMetafactory);
+    assert_eq!(call_site.name, "get");
+    assert_eq!(
+        call_site.descriptor,
+        "(Ljava/lang/String;)Ljava/util/function/Supplier;"
+    );
+    match &call_site.arguments[1] {
+        BootstrapArgument::MethodHandle(implementation) => {
+            assert_eq!(implementation.owner, "examples/ClassWithLambda");
+            assert_eq!(implementation.name, "lambda$greeter$0");
+        }
+        other => panic!("Expected lambda implementation method, found {other:?}"),
+    }
+}
+
+fn validate_method_reference(call_site: &CallSite) {
+    assert_eq!(call_site.kind(), BootstrapKind::LambdaMetafactory);
+    assert_eq!(call_site.name, "apply");
+    match &call_site.arguments[1] {
+        BootstrapArgument::MethodHandle(implementation) => {
+            assert_eq!(implementation.owner, "java/lang/String");
+            assert_eq!(implementation.name, "length");
+            assert_eq!(implementation.descriptor, "()I");
+        }
+        other => panic!("Expected method reference, found {other:?}"),
+    }
+}
+
+fn validate_string_concatenation(call_site: &CallSite) {
+    assert_eq!(call_site.kind(), BootstrapKind::StringConcatFactory);
+    assert_eq!(call_site.name, "makeConcatWithConstants");
+    assert_eq!(
+        call_site.arguments[0],
+        BootstrapArgument::String("Hello, \u{1}".to_string())
+    );
+}
@@ -0,0 +1,70 @@
+use cafebabe::attributes::ParameterFlags;
+use cafebabe::{ClassFile, MethodInfo};
+use common::read_class_from_path;
+use common::setup_logging;
+
+mod common;
+
+#[test]
+fn reads_java8_class_with_methods() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java8/examples/ClassWithMethods.class");
+    assert_eq!(class_file.version.major, 52);
+    validate_methods(&class_file);
+}
+
+#[test]
+fn reads_java17_class_with_methods() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/ClassWithMethods.class");
+    assert_eq!(class_file.version.major, 61);
+    validate_methods(&class_file);
+}
+
+fn find_method<'a>(class_file: &'a ClassFile, name: &str) -> &'a MethodInfo {
+    class_file
+        .methods
+        .iter()
+        .find(|method| method.name(&class_file.constant_pool).unwrap() == name)
+        .unwrap_or_else(|| panic!("Expected method {name}"))
+}
+
+fn validate_methods(class_file: &ClassFile) {
+    let pool = &class_file.constant_pool;
+
+    let write = find_method(class_file, "write");
+    assert_eq!(write.descriptor(pool).unwrap(), "(Ljava/lang/String;I)V");
+    assert!(std::ptr::eq(
+        class_file
+            .declared_method("write", "(Ljava/lang/String;I)V")
+            .unwrap(),
+        write
+    ));
+    assert!(class_file.declared_method("write", "()V").is_none());
+    assert_eq!(
+        write.exceptions(pool).unwrap(),
+        vec!["java/io/IOException", "java/lang/InterruptedException"]
+    );
+    let parameters = write.parameters(pool).unwrap();
+    assert_eq!(parameters.len(), 2);
+    assert_eq!(parameters[0].name.as_deref(), Some("message"));
+    assert_eq!(parameters[0].flags, ParameterFlags::ACC_FINAL);
+    assert_eq!(parameters[1].name.as_deref(), Some("times"));
+    assert_eq!(parameters[1].flags, ParameterFlags::empty());
+    assert!(!write.is_deprecated());
+    assert!(!write.is_synthetic());
+
+    let legacy = find_method(class_file, "legacy");
+    assert!(legacy.is_deprecated());
+    assert!(legacy.exceptions(pool).unwrap().is_empty());
+    assert!(legacy.parameters(pool).unwrap().is_empty());
+
+    let lambda = find_method(class_file, "lambda$supplier$0");
+    assert!(lambda.is_synthetic());
+
+    let field = &class_file.fields[0];
+    assert_eq!(field.name(pool).unwrap(), "legacyField");
+    assert_eq!(field.descriptor(pool).unwrap(), "I");
+    assert!(field.is_deprecated());
+    assert!(!field.is_synthetic());
+}
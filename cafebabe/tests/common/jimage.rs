@@ -0,0 +1,238 @@
+//! Writes small jimages for tests, laid out like the ones of `jlink`.
+
+use std::collections::HashMap;
+
+use cafebabe::archive::jimage_hash;
+
+const HASH_MULTIPLIER: i32 = 0x01000193;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageCompression {
+    None,
+    /// Compressed like `jlink --compress=2`.
+    Zip,
+    /// The constant pool strings moved to the strings table, like `jlink --compress=1`.
+    SharedStrings,
+}
+
+pub struct ImageResource {
+    pub module: String,
+    /// The name relative to the module, e.g. `examples/EmptyClass.class`.
+    pub name: String,
+    pub content: Vec<u8>,
+    pub compression: ImageCompression,
+}
+
+#[derive(Default)]
+struct Strings {
+    bytes: Vec<u8>,
+    offsets: HashMap<String, u32>,
+}
+
+impl Strings {
+    fn add(&mut self, value: &str) -> u32 {
+        if let Some(offset) = self.offsets.get(value) {
+            return *offset;
+        }
+        let offset = self.bytes.len() as u32;
+        self.bytes.extend_from_slice(value.as_bytes());
+        self.bytes.push(0);
+        self.offsets.insert(value.to_string(), offset);
+        offset
+    }
+}
+
+fn push_attribute(out: &mut Vec<u8>, kind: u8, value: u64) {
+    if value == 0 {
+        return;
+    }
+    let length = (8 - value.leading_zeros() as usize / 8).max(1);
+    out.push((kind << 3) | (length as u8 - 1));
+    out.extend_from_slice(&value.to_be_bytes()[8 - length..]);
+}
+
+fn push_compressed_int(out: &mut Vec<u8>, value: u32) {
+    match value {
+        0..0x20 => out.push(0x80 | (1 << 5) | value as u8),
+        0x20..0x2000 => out.extend_from_slice(&[0x80 | (2 << 5) | (value >> 8) as u8, value as u8]),
+        0x2000..0x200000 => out.extend_from_slice(&[
+            0x80 | (3 << 5) | (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ]),
+        _ => out.extend_from_slice(&value.to_be_bytes()),
+    }
+}
+
+/// Moves the Utf8 entries of a class to the strings table, splitting the class names out of method descriptors.
+fn share_strings(class: &[u8], strings: &mut Strings) -> Vec<u8> {
+    let mut out = class[..10].to_vec();
+    let count = u16::from_be_bytes([class[8], class[9]]);
+    let mut idx = 10;
+    let mut entry = 1;
+    while entry < count {
+        let tag = class[idx];
+        idx += 1;
+        if tag == 1 {
+            let length = usize::from(u16::from_be_bytes([class[idx], class[idx + 1]]));
+            let value = std::str::from_utf8(&class[idx + 2..idx + 2 + length]).unwrap();
+            idx += 2 + length;
+            if value.starts_with('(') {
+                let mut descriptor = String::new();
+                let mut indices = Vec::new();
+                let mut rest = value;
+                while let Some(start) = rest.find('L') {
+                    let end = start + rest[start..].find(';').unwrap();
+                    let class_name = &rest[start + 1..end];
+                    let (package, simple_name) =
+                        class_name.rsplit_once('/').unwrap_or(("", class_name));
+                    descriptor.push_str(&rest[..=start]);
+                    push_compressed_int(&mut indices, strings.add(package));
+                    push_compressed_int(&mut indices, strings.add(simple_name));
+                    rest = &rest[end..];
+                }
+                descriptor.push_str(rest);
+                out.push(25);
+                push_compressed_int(&mut out, strings.add(&descriptor));
+                push_compressed_int(&mut out, indices.len() as u32);
+                out.extend_from_slice(&indices);
+            } else {
+                out.push(23);
+                push_compressed_int(&mut out, strings.add(value));
+            }
+        } else {
+            let size = match tag {
+                3 | 4 | 9..=12 | 17 | 18 => 4,
+                5 | 6 => 8,
+                7 | 8 | 16 | 19 | 20 => 2,
+                15 => 3,
+                _ => panic!("Unknown constant pool tag {tag}"),
+            };
+            out.extend_from_slice(&class[idx - 1..idx + size]);
+            idx += size;
+            if tag == 5 || tag == 6 {
+                entry += 1;
+            }
+        }
+        entry += 1;
+    }
+    out.extend_from_slice(&class[idx..]);
+    out
+}
+
+fn compressed_header(compressed: &[u8], uncompressed_size: usize, decompressor: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&0xcafefafau32.to_le_bytes());
+    out.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+    out.extend_from_slice(&(uncompressed_size as u64).to_le_bytes());
+    out.extend_from_slice(&decompressor.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out.push(1);
+    out.extend_from_slice(compressed);
+    out
+}
+
+/// Builds a little-endian jimage holding the resources, looked up through a perfect hash table.
+pub fn write_jimage(resources: &[ImageResource]) -> Vec<u8> {
+    let mut strings = Strings::default();
+    strings.add("");
+    let mut locations = Vec::new();
+    let mut location_offsets = Vec::new();
+    let mut names = Vec::new();
+    let mut content = Vec::new();
+
+    for resource in resources {
+        let (parent, file) = resource
+            .name
+            .rsplit_once('/')
+            .unwrap_or(("", &resource.name));
+        let (base, extension) = file.rsplit_once('.').unwrap_or((file, ""));
+        let stored = match resource.compression {
+            ImageCompression::None => resource.content.clone(),
+            ImageCompression::Zip => compressed_header(
+                &miniz_oxide::deflate::compress_to_vec_zlib(&resource.content, 6),
+                resource.content.len(),
+                strings.add("zip"),
+            ),
+            ImageCompression::SharedStrings => {
+                let shared = share_strings(&resource.content, &mut strings);
+                compressed_header(&shared, resource.content.len(), strings.add("compact-cp"))
+            }
+        };
+
+        location_offsets.push(locations.len() as u32);
+        push_attribute(&mut locations, 1, u64::from(strings.add(&resource.module)));
+        push_attribute(&mut locations, 2, u64::from(strings.add(parent)));
+        push_attribute(&mut locations, 3, u64::from(strings.add(base)));
+        push_attribute(&mut locations, 4, u64::from(strings.add(extension)));
+        push_attribute(&mut locations, 5, content.len() as u64);
+        if resource.compression != ImageCompression::None {
+            push_attribute(&mut locations, 6, stored.len() as u64);
+        }
+        push_attribute(&mut locations, 7, resource.content.len() as u64);
+        locations.push(0);
+        names.push(format!("/{}/{}", resource.module, resource.name));
+        content.extend_from_slice(&stored);
+    }
+
+    let count = names.len() as i32;
+    let mut redirect = vec![0i32; names.len()];
+    let mut slots: Vec<Option<u32>> = vec![None; names.len()];
+    let mut buckets: HashMap<i32, Vec<usize>> = HashMap::new();
+    for (idx, name) in names.iter().enumerate() {
+        buckets
+            .entry(jimage_hash(name, HASH_MULTIPLIER) % count)
+            .or_default()
+            .push(idx);
+    }
+    let mut buckets: Vec<(i32, Vec<usize>)> = buckets.into_iter().collect();
+    buckets.sort_by_key(|(bucket, members)| (std::cmp::Reverse(members.len()), *bucket));
+    for (bucket, members) in buckets {
+        if members.len() == 1 {
+            let slot = slots.iter().position(Option::is_none).unwrap();
+            slots[slot] = Some(location_offsets[members[0]]);
+            redirect[bucket as usize] = -(slot as i32) - 1;
+            continue;
+        }
+        let seed = (1..)
+            .find(|seed| {
+                let mut taken: Vec<i32> = members
+                    .iter()
+                    .map(|idx| jimage_hash(&names[*idx], *seed) % count)
+                    .collect();
+                taken.sort();
+                taken.dedup();
+                taken.len() == members.len()
+                    && taken.iter().all(|slot| slots[*slot as usize].is_none())
+            })
+            .unwrap();
+        for idx in members {
+            let slot = jimage_hash(&names[idx], seed) % count;
+            slots[slot as usize] = Some(location_offsets[idx]);
+        }
+        redirect[bucket as usize] = seed;
+    }
+
+    let mut out = Vec::new();
+    for value in [
+        0xcafedada,
+        0x00010000,
+        0,
+        names.len() as u32,
+        names.len() as u32,
+        locations.len() as u32,
+        strings.bytes.len() as u32,
+    ] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    for value in redirect {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    for slot in slots {
+        out.extend_from_slice(&slot.unwrap().to_le_bytes());
+    }
+    out.extend_from_slice(&locations);
+    out.extend_from_slice(&strings.bytes);
+    out.extend_from_slice(&content);
+    out
+}
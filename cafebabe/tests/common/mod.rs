@@ -1,9 +1,14 @@
+#[allow(dead_code)]
+pub mod jimage;
+
 use simplelog::{ColorChoice, CombinedLogger, Config, LevelFilter, TermLogger, TerminalMode};
 use std::io::BufReader;
+use std::path::{Path, PathBuf};
 use std::sync::Once;
 use std::{fs::File, io::Read};
 
-use cafebabe::{ClassFile, read_class_data};
+use cafebabe::assembler::assemble;
+use cafebabe::{ClassFile, read_class_data, write_class_data};
 
 static LOGGING_STATE: Once = Once::new();
 
@@ -19,6 +24,7 @@ pub fn setup_logging() {
     });
 }
 
+#[allow(dead_code)]
 pub fn read_class_from_path(path: &str) -> ClassFile {
     let file = File::open(path).expect("Can't open class file");
     let mut reader = BufReader::new(file);
@@ -30,6 +36,7 @@ pub fn read_class_from_path(path: &str) -> ClassFile {
     read_class_data(&data).expect("Can't parse class file")
 }
 
+#[allow(dead_code)]
 pub fn validate_class_name(class_file: &ClassFile, expected_class_name: &str) {
     let class_ref_idx = class_file.class.this_idx;
     let class_name_idx = class_file
@@ -42,3 +49,32 @@ pub fn validate_class_name(class_file: &ClassFile, expected_class_name: &str) {
         .unwrap();
     assert_eq!(class_name, expected_class_name, "Expect class to be valid");
 }
+
+/// All class files below a directory.
+#[allow(dead_code)]
+pub fn class_files(directory: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            paths.extend(class_files(&path));
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "class")
+        {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Assembles classes and writes them below a directory, each in the directory of its package.
+#[allow(dead_code)]
+pub fn write_classes(directory: &Path, sources: &[&str]) {
+    for source in sources {
+        let class_file = assemble(source).unwrap();
+        let path = directory.join(format!("{}.class", class_file.this_class_name().unwrap()));
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, write_class_data(&class_file).unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,263 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cafebabe::assembler::assemble;
+use cafebabe::class_path::ClassPath;
+use cafebabe::compatibility::Severity::{Behavioral, Breaking};
+use cafebabe::compatibility::{
+    ChangeKind, CompatibilityReport, Severity, compare, compare_classes,
+};
+use common::{read_class_from_path, setup_logging, write_classes};
+
+mod common;
+
+const OLD_VERSION: [&str; 9] = [
+    ".class public lib/Api
+.super java/lang/Object
+.implements java/lang/Runnable
+.field public static final LIMIT I
+    .constantvalue Int 10
+.end field
+.field public name Ljava/lang/String;
+.field public count I
+.field public size I
+.field public open Z
+.method public <init> ()V
+.end method
+.method public run ()V
+.end method
+.method public parse (Ljava/lang/String;)I
+.end method
+.method public size ()I
+.end method
+.method public close ()V
+.end method
+.method public help ()V
+.end method
+.method public stop ()V
+.end method
+.method protected hidden ()V
+.end method
+.method public inherited ()V
+.end method
+.method private secret ()V\n.end method",
+    ".class public lib/Shape\n.super java/lang/Object",
+    ".class public interface abstract lib/Listener
+.super java/lang/Object
+.method public abstract onEvent ()V\n.end method",
+    ".class public lib/Gone\n.super java/lang/Object",
+    ".class public lib/Sealed\n.super java/lang/Object",
+    ".class public lib/Hidden\n.super java/lang/Object",
+    ".class public lib/Kind\n.super java/lang/Object",
+    ".class lib/Internal\n.super java/lang/Object",
+    ".class public lib/Unchanged\n.super java/lang/Object\n.method public <init> ()V\n.end method",
+];
+
+const NEW_VERSION: [&str; 9] = [
+    ".class public lib/Api
+.super lib/Base
+.field public static final LIMIT I
+    .constantvalue Int 20
+.end field
+.field public count J
+.field public static size I
+.field public final open Z
+.method public <init> ()V
+.end method
+.method public parse (Ljava/lang/CharSequence;)I
+.end method
+.method public size ()J
+.end method
+.method protected close ()V
+.end method
+.method public static help ()V
+.end method
+.method public final stop ()V
+.end method
+.method private hidden ()V\n.end method",
+    ".class public lib/Base\n.super java/lang/Object\n.method public inherited ()V\n.end method",
+    ".class public abstract lib/Shape
+.super java/lang/Object
+.method public abstract area ()D\n.end method",
+    ".class public interface abstract lib/Listener
+.super java/lang/Object
+.method public abstract onEvent ()V
+.end method
+.method public abstract onClose ()V
+.end method
+.method public onOpen ()V\n.end method",
+    ".class public final lib/Sealed\n.super java/lang/Object",
+    ".class lib/Hidden\n.super java/lang/Object",
+    ".class public interface abstract lib/Kind\n.super java/lang/Object",
+    ".class public lib/Added\n.super java/lang/Object",
+    ".class public lib/Unchanged\n.super java/lang/Object\n.method public <init> ()V\n.end method",
+];
+
+/// The classes of a version in a directory of the test.
+fn version_classes(test: &str, version: &str, sources: &[&str]) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!(
+        "cafebabe-compatibility-{test}-{version}-{}",
+        std::process::id()
+    ));
+    write_classes(&directory, sources);
+    directory
+}
+
+fn compare_versions(test: &str) -> CompatibilityReport {
+    let (old_directory, new_directory) = (
+        version_classes(test, "old", &OLD_VERSION),
+        version_classes(test, "new", &NEW_VERSION),
+    );
+    let (mut old, mut new) = (ClassPath::new(), ClassPath::new());
+    old.add(&old_directory).unwrap();
+    new.add(&new_directory).unwrap();
+    let report = compare(&old, &new).unwrap();
+    fs::remove_dir_all(old_directory).unwrap();
+    fs::remove_dir_all(new_directory).unwrap();
+    report
+}
+
+#[test]
+fn reports_changes_by_class_and_member() {
+    setup_logging();
+    let report = compare_versions("by-class");
+    let changes: Vec<(String, &str, Severity)> = report
+        .changes
+        .iter()
+        .map(|change| (change.to_string(), change.section, change.severity))
+        .collect();
+
+    assert_eq!(
+        changes,
+        [
+            (
+                "lib.Api: no longer a subtype of java.lang.Runnable".to_string(),
+                "13.4.4",
+                Breaking
+            ),
+            (
+                "lib.Api.LIMIT:I: constant value changed from 10 to 20".to_string(),
+                "13.4.9",
+                Behavioral
+            ),
+            (
+                "lib.Api.name:Ljava/lang/String;: removed".to_string(),
+                "13.4.8",
+                Breaking
+            ),
+            ("lib.Api.count:I: type changed to J".to_string(), "13.4.8", Breaking),
+            ("lib.Api.size:I: made static".to_string(), "13.4.10", Breaking),
+            ("lib.Api.open:Z: made final".to_string(), "13.4.9", Breaking),
+            ("lib.Api.run()V: removed".to_string(), "13.4.12", Breaking),
+            (
+                "lib.Api.parse(Ljava/lang/String;)I: parameters changed to (Ljava/lang/CharSequence;)I"
+                    .to_string(),
+                "13.4.14",
+                Breaking
+            ),
+            (
+                "lib.Api.size()I: return type changed to ()J".to_string(),
+                "13.4.15",
+                Breaking
+            ),
+            ("lib.Api.close()V: less accessible".to_string(), "13.4.7", Breaking),
+            ("lib.Api.help()V: made static".to_string(), "13.4.19", Breaking),
+            ("lib.Api.stop()V: made final".to_string(), "13.4.17", Breaking),
+            ("lib.Api.hidden()V: less accessible".to_string(), "13.4.7", Breaking),
+            ("lib.Gone: removed".to_string(), "13.3", Breaking),
+            ("lib.Hidden: less accessible".to_string(), "13.4.3", Breaking),
+            ("lib.Kind: changed into an interface".to_string(), "13.4", Breaking),
+            (
+                "lib.Listener.onClose()V: added as abstract method".to_string(),
+                "13.5.4",
+                Behavioral
+            ),
+            ("lib.Sealed: made final".to_string(), "13.4.2.3", Breaking),
+            ("lib.Shape: made abstract".to_string(), "13.4.1", Breaking),
+            (
+                "lib.Shape.area()D: added as abstract method".to_string(),
+                "13.4.16",
+                Behavioral
+            ),
+        ]
+    );
+    assert!(report.invalid_classes.is_empty());
+    assert!(!report.is_compatible());
+}
+
+#[test]
+fn groups_changes_by_section() {
+    setup_logging();
+    let report = compare_versions("by-section");
+
+    let sections: Vec<&str> = report
+        .by_section()
+        .into_iter()
+        .map(|(section, _)| section)
+        .collect();
+    assert_eq!(
+        sections,
+        [
+            "13.3", "13.4", "13.4.1", "13.4.2.3", "13.4.3", "13.4.4", "13.4.7", "13.4.8", "13.4.9",
+            "13.4.10", "13.4.12", "13.4.14", "13.4.15", "13.4.16", "13.4.17", "13.4.19", "13.5.4",
+        ]
+    );
+
+    let text = report.to_string();
+    assert!(text.starts_with("JLS 13.3 Evolution of Packages\n  BREAKING   lib.Gone: removed\n"));
+    assert!(text.contains(
+        "JLS 13.4.9 final Fields and static Constant Variables\n  \
+         BEHAVIORAL lib.Api.LIMIT:I: constant value changed from 10 to 20\n  \
+         BREAKING   lib.Api.open:Z: made final\n"
+    ));
+}
+
+#[test]
+fn ignores_compatible_changes() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/ClassWithMethods.class");
+    assert!(
+        compare_classes(&class_file, &class_file)
+            .unwrap()
+            .is_empty()
+    );
+
+    let old = assemble(
+        ".class public lib/Config
+.super java/lang/Object
+.field public static final NAME Ljava/lang/String;
+    .constantvalue String \"config\"
+.end field
+.field protected count I
+.method protected reset ()V\n.end method",
+    )
+    .unwrap();
+    let new = assemble(
+        ".class public lib/Config
+.super java/lang/Object
+.implements java/io/Serializable
+.field public static final NAME Ljava/lang/String;
+    .constantvalue String \"settings\"
+.end field
+.field public count I
+.field public added I
+.method public reset ()V
+.end method
+.method public added ()V\n.end method",
+    )
+    .unwrap();
+    let report = CompatibilityReport {
+        changes: compare_classes(&old, &new).unwrap(),
+        invalid_classes: Vec::new(),
+    };
+    assert_eq!(report.changes.len(), 1);
+    assert_eq!(
+        report.changes[0].kind,
+        ChangeKind::ConstantValueChanged {
+            old: "\"config\"".to_string(),
+            new: Some("\"settings\"".to_string()),
+        }
+    );
+    assert_eq!(report.changes[0].severity, Severity::Behavioral);
+    assert!(report.is_compatible());
+}
@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::Path;
+
+use cafebabe::assembler::assemble;
+use cafebabe::bytecode::Opcode;
+use cafebabe::control_flow::{ControlFlowGraph, EdgeKind};
+use cafebabe::{ClassFile, ParseOptions, read_class_data};
+use common::{class_files, setup_logging};
+
+mod common;
+
+const CLASS_WITH_CODE: &str = "res/java17/examples/ClassWithCode.class";
+
+const FLOW: &str = r#"
+.version 49 0
+.class public super examples/Flow
+.super java/lang/Object
+
+.method public static nested (I)I
+    .code stack 2 locals 3
+        iconst_0
+        istore_1
+    outer:
+        iload_1
+        iload_0
+        if_icmpge done
+        iconst_0
+        istore_2
+    inner:
+        iload_2
+        iload_1
+        if_icmpge next
+        iinc 2 1
+        goto inner
+    next:
+        iinc 1 1
+        goto outer
+    done:
+        iload_1
+        ireturn
+    .end code
+.end method
+
+.method public static subroutine ()V
+    .code stack 1 locals 1
+        jsr finally
+        jsr finally
+        return
+    finally:
+        astore_0
+        ret 0
+    .end code
+.end method
+"#;
+
+fn graph(class_file: &ClassFile, name: &str) -> ControlFlowGraph {
+    let method = class_file
+        .methods
+        .iter()
+        .find(|method| method.name(&class_file.constant_pool).unwrap() == name)
+        .unwrap();
+    ControlFlowGraph::build(method.code().unwrap()).unwrap()
+}
+
+fn edges(graph: &ControlFlowGraph) -> Vec<(usize, usize, EdgeKind)> {
+    graph
+        .edges
+        .iter()
+        .map(|edge| (edge.source, edge.target, edge.kind))
+        .collect()
+}
+
+#[test]
+fn builds_blocks_and_edges_of_loops() {
+    setup_logging();
+    let class_file = assemble(FLOW).unwrap();
+    let graph = graph(&class_file, "nested");
+
+    assert_eq!(graph.blocks.len(), 7);
+    let starts: Vec<u32> = graph.blocks.iter().map(|block| block.start).collect();
+    assert_eq!(starts, [0, 2, 7, 9, 14, 20, 26]);
+    assert_eq!(graph.block_instructions(4)[0].opcode, Opcode::Iinc);
+    assert_eq!(graph.block_of(16), Some(4));
+    assert_eq!(graph.block_of(100), None);
+    assert_eq!(
+        edges(&graph),
+        [
+            (0, 1, EdgeKind::FallThrough),
+            (1, 6, EdgeKind::Branch),
+            (1, 2, EdgeKind::FallThrough),
+            (2, 3, EdgeKind::FallThrough),
+            (3, 5, EdgeKind::Branch),
+            (3, 4, EdgeKind::FallThrough),
+            (4, 3, EdgeKind::Branch),
+            (5, 1, EdgeKind::Branch),
+        ]
+    );
+    assert_eq!(graph.successors(1), [6, 2]);
+    assert_eq!(graph.predecessors(3), [2, 4]);
+
+    let dominators = graph.dominators();
+    assert_eq!(
+        dominators.immediate_dominators,
+        [None, Some(0), Some(1), Some(2), Some(3), Some(3), Some(1)]
+    );
+    assert!(dominators.dominates(1, 5));
+    assert!(!dominators.dominates(4, 5));
+    assert_eq!(dominators.children(3), [4, 5]);
+
+    let loops = graph.loops();
+    assert_eq!(loops.len(), 2);
+    assert_eq!(loops[0].header, 1);
+    assert_eq!(loops[0].latches, [5]);
+    assert_eq!(loops[0].blocks, [1, 2, 3, 4, 5]);
+    assert_eq!(loops[0].parent, None);
+    assert_eq!(loops[1].header, 3);
+    assert_eq!(loops[1].latches, [4]);
+    assert_eq!(loops[1].blocks, [3, 4]);
+    assert_eq!(loops[1].parent, Some(0));
+}
+
+#[test]
+fn connects_subroutines_to_their_callers() {
+    setup_logging();
+    let class_file = assemble(FLOW).unwrap();
+    let graph = graph(&class_file, "subroutine");
+
+    assert_eq!(graph.blocks.len(), 4);
+    assert_eq!(
+        edges(&graph),
+        [
+            (0, 3, EdgeKind::Jsr),
+            (1, 3, EdgeKind::Jsr),
+            (3, 1, EdgeKind::Ret),
+            (3, 2, EdgeKind::Ret),
+        ]
+    );
+    let dominators = graph.dominators();
+    assert_eq!(
+        dominators.immediate_dominators,
+        [None, Some(3), Some(3), Some(0)]
+    );
+    assert!(dominators.reachable.iter().all(|reachable| *reachable));
+}
+
+#[test]
+fn builds_switches_and_exception_handlers_of_java_methods() {
+    setup_logging();
+    let class_file = read_class_data(&fs::read(CLASS_WITH_CODE).unwrap()).unwrap();
+
+    let describe = graph(&class_file, "describe");
+    let keys: Vec<EdgeKind> = describe
+        .edges
+        .iter()
+        .filter(|edge| edge.source == 0)
+        .map(|edge| edge.kind)
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            EdgeKind::Switch(Some(1)),
+            EdgeKind::Switch(Some(2)),
+            EdgeKind::Switch(Some(3)),
+            EdgeKind::Switch(None),
+        ]
+    );
+    assert_eq!(describe.blocks.len(), 5);
+    assert!(describe.loops().is_empty());
+
+    let parse = graph(&class_file, "parse");
+    let handlers: Vec<_> = parse
+        .edges
+        .iter()
+        .filter_map(|edge| match edge.kind {
+            EdgeKind::Exception(catch_type) => Some((edge.source, edge.target, catch_type)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(handlers.len(), 1);
+    let (source, target, catch_type) = handlers[0];
+    assert_eq!(source, 0);
+    assert_eq!(
+        class_file
+            .constant_pool
+            .class_name(catch_type as usize)
+            .unwrap(),
+        "java/lang/NumberFormatException"
+    );
+    assert_eq!(parse.dominators().immediate_dominator(target), Some(0));
+
+    let sum = graph(&class_file, "sum");
+    let loops = sum.loops();
+    assert_eq!(loops.len(), 1);
+    assert!(loops[0].blocks.len() >= 2);
+    assert!(
+        sum.dominators()
+            .dominates(loops[0].header, loops[0].latches[0])
+    );
+}
+
+#[test]
+fn exports_graphs_to_dot() {
+    setup_logging();
+    let class_file = read_class_data(&fs::read(CLASS_WITH_CODE).unwrap()).unwrap();
+    let dot = graph(&class_file, "parse").to_dot("parse", &class_file.constant_pool);
+
+    assert!(dot.starts_with("digraph \"parse\" {\n"), "{dot}");
+    assert!(dot.ends_with("}\n"), "{dot}");
+    assert!(
+        dot.contains("invokestatic java/lang/Integer.parseInt:(Ljava/lang/String;)I\\l"),
+        "{dot}"
+    );
+    assert!(
+        dot.contains("b0 -> b2 [style=dashed, label=\"java/lang/NumberFormatException\"];"),
+        "{dot}"
+    );
+
+    let dot = graph(&class_file, "describe").to_dot("describe", &class_file.constant_pool);
+    assert!(dot.contains("ldc \\\"one\\\"\\l"), "{dot}");
+    assert!(dot.contains(" [label=\"default\"];"), "{dot}");
+}
+
+#[test]
+fn builds_graphs_of_all_methods() {
+    setup_logging();
+    let options = ParseOptions {
+        allow_preview: true,
+        ..ParseOptions::default()
+    };
+    let directories = [
+        "res/java8",
+        "res/java11",
+        "res/java17",
+        "res/java21",
+        "res/java25",
+        "res/remapper",
+    ];
+    for path in directories
+        .iter()
+        .flat_map(|directory| class_files(Path::new(directory)))
+    {
+        let class_file =
+            cafebabe::read_class_data_with_options(&fs::read(&path).unwrap(), &options).unwrap();
+        for method in &class_file.methods {
+            let Some(code) = method.code() else {
+                continue;
+            };
+            let graph = ControlFlowGraph::build(code).unwrap();
+            let instructions: usize = graph
+                .blocks
+                .iter()
+                .map(|block| block.instructions.len())
+                .sum();
+            assert_eq!(instructions, graph.instructions.len(), "{path:?}");
+            let dominators = graph.dominators();
+            assert!(dominators.reachable[0], "{path:?}");
+            for edge in &graph.edges {
+                assert!(edge.source < graph.blocks.len() && edge.target < graph.blocks.len());
+            }
+            for natural_loop in graph.loops() {
+                for block in &natural_loop.blocks {
+                    assert!(
+                        dominators.dominates(natural_loop.header, *block),
+                        "{path:?}"
+                    );
+                }
+            }
+        }
+    }
+}
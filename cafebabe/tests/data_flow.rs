@@ -0,0 +1,259 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use cafebabe::assembler::assemble;
+use cafebabe::control_flow::ControlFlowGraph;
+use cafebabe::data_flow::{
+    Constant, ConstantPropagation, Definition, Frame, Frames, Liveness, Nullness, NullnessAnalysis,
+    ReachingDefinitions, solve,
+};
+use cafebabe::{ClassFile, MethodInfo, ParseOptions, read_class_data};
+use common::{class_files, setup_logging};
+
+mod common;
+
+const CLASS_WITH_CODE: &str = "res/java17/examples/ClassWithCode.class";
+
+const FLOW: &str = r#"
+.class public super examples/Flow
+.super java/lang/Object
+
+.method public static constants (I)I
+    .code stack 6 locals 4
+        iconst_2
+        istore_1
+        iload_1
+        bipush 40
+        iadd
+        istore_2
+        iload_0
+        ifeq skip
+        iinc 1 3
+    skip:
+        iload_2
+        ldc2_w Long 10
+        dup2
+        ladd
+        l2i
+        iadd
+        istore_3
+        iload_1
+        iload_3
+        iadd
+        ireturn
+    .end code
+.end method
+
+.method public static nullness (Ljava/lang/Object;)I
+    .code stack 2 locals 2
+        aload_0
+        ifnull missing
+        aload_0
+        invokevirtual java/lang/Object hashCode ()I
+        ireturn
+    missing:
+        new java/lang/Object
+        astore_1
+        aload_1
+        invokevirtual java/lang/Object hashCode ()I
+        ireturn
+    .end code
+.end method
+"#;
+
+fn method_graph<'a>(class_file: &'a ClassFile, name: &str) -> (&'a MethodInfo, ControlFlowGraph) {
+    let method = class_file
+        .methods
+        .iter()
+        .find(|method| method.name(&class_file.constant_pool).unwrap() == name)
+        .unwrap();
+    (
+        method,
+        ControlFlowGraph::build(method.code().unwrap()).unwrap(),
+    )
+}
+
+#[test]
+fn propagates_constants() {
+    setup_logging();
+    let class_file = assemble(FLOW).unwrap();
+    let (method, graph) = method_graph(&class_file, "constants");
+    let constant_pool = &class_file.constant_pool;
+    let analysis = Frames::new(
+        ConstantPropagation::new(constant_pool),
+        constant_pool,
+        method,
+    )
+    .unwrap();
+    let solution = solve(&graph, &analysis).unwrap();
+
+    assert_eq!(graph.blocks.len(), 3);
+    let frame = solution.entry[2].as_ref().unwrap();
+    assert_eq!(
+        frame.locals,
+        [
+            Constant::Unknown,
+            Constant::Unknown,
+            Constant::Int(42),
+            Constant::Unknown
+        ]
+    );
+    let frame = solution.entry[1].as_ref().unwrap();
+    assert_eq!(frame.locals[1], Constant::Int(2));
+    assert!(frame.stack.is_empty());
+
+    let states = solution.block_states(&graph, &analysis, 2).unwrap();
+    assert_eq!(states.len(), graph.block_instructions(2).len() + 1);
+    assert_eq!(
+        states[3].as_ref().unwrap().stack,
+        [
+            Constant::Int(42),
+            Constant::Long(10),
+            Constant::Unknown,
+            Constant::Long(10),
+            Constant::Unknown
+        ]
+    );
+    assert_eq!(states[7].as_ref().unwrap().locals[3], Constant::Int(62));
+    assert_eq!(
+        solution.exit[2],
+        Some(Frame {
+            locals: vec![
+                Constant::Unknown,
+                Constant::Unknown,
+                Constant::Int(42),
+                Constant::Int(62)
+            ],
+            stack: Vec::new(),
+        })
+    );
+}
+
+#[test]
+fn finds_reaching_definitions_and_live_variables() {
+    setup_logging();
+    let class_file = assemble(FLOW).unwrap();
+    let (method, graph) = method_graph(&class_file, "constants");
+
+    let analysis = ReachingDefinitions::new(&class_file.constant_pool, method).unwrap();
+    let solution = solve(&graph, &analysis).unwrap();
+    assert_eq!(
+        solution.entry[2],
+        [
+            BTreeSet::from([Definition::Parameter]),
+            BTreeSet::from([Definition::Instruction(1), Definition::Instruction(11)]),
+            BTreeSet::from([Definition::Instruction(6)]),
+            BTreeSet::new(),
+        ]
+    );
+    assert_eq!(
+        solution.exit[2][3],
+        BTreeSet::from([Definition::Instruction(22)])
+    );
+
+    let analysis = Liveness::new(method.code().unwrap().max_locals);
+    let solution = solve(&graph, &analysis).unwrap();
+    assert_eq!(solution.entry[0], [true, false, false, false]);
+    assert_eq!(solution.entry[1], [false, true, true, false]);
+    assert_eq!(solution.entry[2], [false, true, true, false]);
+    assert_eq!(solution.exit[2], [false; 4]);
+    let states = solution.block_states(&graph, &analysis, 0).unwrap();
+    // Local 1 is not live before `istore_1` sets it, and local 2 only after `istore_2`.
+    assert_eq!(states[1], [true, false, false, false]);
+    assert_eq!(states[2], [true, true, false, false]);
+    assert_eq!(states[6], [true, true, true, false]);
+}
+
+#[test]
+fn tracks_nullness_through_branches_and_handlers() {
+    setup_logging();
+    let class_file = assemble(FLOW).unwrap();
+    let (method, graph) = method_graph(&class_file, "nullness");
+    let constant_pool = &class_file.constant_pool;
+    let analysis =
+        Frames::new(NullnessAnalysis::new(constant_pool), constant_pool, method).unwrap();
+    let solution = solve(&graph, &analysis).unwrap();
+
+    assert_eq!(
+        solution.entry[0].as_ref().unwrap().locals[0],
+        Nullness::Unknown
+    );
+    assert_eq!(
+        solution.entry[1].as_ref().unwrap().locals[0],
+        Nullness::NotNull
+    );
+    assert_eq!(
+        solution.entry[2].as_ref().unwrap().locals[0],
+        Nullness::Null
+    );
+    let states = solution.block_states(&graph, &analysis, 2).unwrap();
+    assert_eq!(states[3].as_ref().unwrap().stack, [Nullness::NotNull]);
+
+    let class_file = read_class_data(&fs::read(CLASS_WITH_CODE).unwrap()).unwrap();
+    let (method, graph) = method_graph(&class_file, "parse");
+    let constant_pool = &class_file.constant_pool;
+    let analysis =
+        Frames::new(NullnessAnalysis::new(constant_pool), constant_pool, method).unwrap();
+    let solution = solve(&graph, &analysis).unwrap();
+    let handler = graph.blocks.len() - 1;
+    let frame = solution.entry[handler].as_ref().unwrap();
+    assert_eq!(frame.stack, [Nullness::NotNull]);
+    assert_eq!(frame.locals[0], Nullness::NotNull);
+}
+
+#[test]
+fn analyses_all_methods() {
+    setup_logging();
+    let options = ParseOptions {
+        allow_preview: true,
+        ..ParseOptions::default()
+    };
+    let directories = [
+        "res/java8",
+        "res/java11",
+        "res/java17",
+        "res/java21",
+        "res/java25",
+        "res/remapper",
+    ];
+    for path in directories
+        .iter()
+        .flat_map(|directory| class_files(Path::new(directory)))
+    {
+        let class_file =
+            cafebabe::read_class_data_with_options(&fs::read(&path).unwrap(), &options).unwrap();
+        let constant_pool = &class_file.constant_pool;
+        for method in &class_file.methods {
+            let Some(code) = method.code() else {
+                continue;
+            };
+            let graph = ControlFlowGraph::build(code).unwrap();
+            let constants = Frames::new(
+                ConstantPropagation::new(constant_pool),
+                constant_pool,
+                method,
+            )
+            .unwrap();
+            let solution = solve(&graph, &constants).unwrap();
+            for block in 0..graph.blocks.len() {
+                let states = solution.block_states(&graph, &constants, block).unwrap();
+                assert_eq!(solution.entry[block], states[0], "{path:?}");
+                assert_eq!(solution.exit[block], states[states.len() - 1], "{path:?}");
+            }
+            assert!(solution.entry[0].is_some(), "{path:?}");
+
+            let nullness =
+                Frames::new(NullnessAnalysis::new(constant_pool), constant_pool, method).unwrap();
+            solve(&graph, &nullness).unwrap();
+            let definitions = ReachingDefinitions::new(constant_pool, method).unwrap();
+            solve(&graph, &definitions).unwrap();
+            let liveness = Liveness::new(code.max_locals);
+            let solution = solve(&graph, &liveness).unwrap();
+            for block in 0..graph.blocks.len() {
+                let states = solution.block_states(&graph, &liveness, block).unwrap();
+                assert_eq!(solution.entry[block], states[0], "{path:?}");
+            }
+        }
+    }
+}
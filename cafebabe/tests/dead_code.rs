@@ -0,0 +1,220 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cafebabe::call_graph::{Algorithm, MethodId};
+use cafebabe::class_path::ClassPath;
+use cafebabe::dead_code::{DeadCodeReport, EntryPoints, FieldId};
+use cafebabe::hierarchy::ClassHierarchy;
+use common::{setup_logging, write_classes};
+
+mod common;
+
+const MAIN: &str = r#"
+.class public super app/Main
+.super java/lang/Object
+
+.method public static main ([Ljava/lang/String;)V
+    .code stack 2 locals 1
+        new util/Service
+        dup
+        invokespecial util/Service <init> ()V
+        invokevirtual util/Service run ()V
+        getstatic app/Config VALUE I
+        pop
+        return
+    .end code
+.end method
+"#;
+
+const CONFIG: &str = r#"
+.class public super app/Config
+.super app/Base
+
+.field public static VALUE I
+.field public static UNUSED I
+
+.method static <clinit> ()V
+    .code stack 1 locals 0
+        iconst_1
+        putstatic app/Config VALUE I
+        return
+    .end code
+.end method
+"#;
+
+const BASE: &str = r#"
+.class public super app/Base
+.super java/lang/Object
+
+.field protected inherited J
+"#;
+
+const HANDLER: &str = r#"
+.class public super app/Handler
+.super java/lang/Object
+
+.method public static handle (Ljava/lang/String;)V
+    .code stack 0 locals 1
+        return
+    .end code
+.end method
+
+.method public static handle (I)V
+    .code stack 0 locals 1
+        return
+    .end code
+.end method
+"#;
+
+const ORPHAN: &str = r#"
+.class public super app/Orphan
+.super java/lang/Object
+
+.method public static help ()V
+    .code stack 0 locals 0
+        return
+    .end code
+.end method
+"#;
+
+const SERVICE: &str = r#"
+.class public super util/Service
+.super java/lang/Object
+
+.method public <init> ()V
+    .code stack 1 locals 1
+        aload_0
+        invokespecial java/lang/Object <init> ()V
+        return
+    .end code
+.end method
+
+.method public run ()V
+    .code stack 0 locals 1
+        return
+    .end code
+.end method
+
+.method public stop ()V
+    .code stack 0 locals 1
+        return
+    .end code
+.end method
+"#;
+
+const HELPER: &str = r#"
+.class public super lib/Helper
+.super java/lang/Object
+
+.method public static help ()V
+    .code stack 0 locals 0
+        return
+    .end code
+.end method
+"#;
+
+#[test]
+fn reports_unused_classes_members_and_archives() {
+    setup_logging();
+    let root: PathBuf =
+        std::env::temp_dir().join(format!("cafebabe-dead-code-{}", std::process::id()));
+    let (app, util, lib) = (root.join("app"), root.join("util"), root.join("lib"));
+    write_classes(&app, &[MAIN, CONFIG, BASE, HANDLER, ORPHAN]);
+    write_classes(&util, &[SERVICE]);
+    write_classes(&lib, &[HELPER]);
+    let mut class_path = ClassPath::new();
+    for directory in [&app, &util, &lib] {
+        class_path.add(directory).unwrap();
+    }
+    let hierarchy = ClassHierarchy::from_class_path(&class_path).unwrap();
+
+    let report = DeadCodeReport::analyze(
+        &class_path,
+        &hierarchy,
+        &EntryPoints::main_methods(),
+        Algorithm::RapidType,
+    )
+    .unwrap();
+    assert_eq!(
+        report.unused_classes,
+        ["app/Handler", "app/Orphan", "lib/Helper"]
+    );
+    assert_eq!(
+        report.unreachable_methods,
+        [MethodId::new("util/Service", "stop", "()V")]
+    );
+    assert_eq!(
+        report.unused_fields,
+        [
+            FieldId::new("app/Base", "inherited", "J"),
+            FieldId::new("app/Config", "UNUSED", "I"),
+        ]
+    );
+    assert_eq!(report.unused_archives, [lib]);
+    assert!(report.unknown_entry_points.is_empty());
+    assert!(report.invalid_classes.is_empty());
+
+    // Methods and kept classes are entry points too, and patterns that match nothing are reported.
+    let entry_points = EntryPoints {
+        main_methods: true,
+        methods: vec![
+            "app/Handler.handle(I)V".to_string(),
+            "app/Handler.missing".to_string(),
+            "app/Missing.handle".to_string(),
+        ],
+        keep: vec!["lib/".to_string(), "org/".to_string()],
+    };
+    let report =
+        DeadCodeReport::analyze(&class_path, &hierarchy, &entry_points, Algorithm::RapidType)
+            .unwrap();
+    assert_eq!(report.unused_classes, ["app/Orphan"]);
+    assert_eq!(
+        report.unreachable_methods,
+        [
+            MethodId::new("app/Handler", "handle", "(Ljava/lang/String;)V"),
+            MethodId::new("util/Service", "stop", "()V"),
+        ]
+    );
+    assert!(report.unused_archives.is_empty());
+    assert_eq!(
+        report.unknown_entry_points,
+        ["app/Handler.missing", "app/Missing.handle", "org/"]
+    );
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn reports_classes_with_invalid_constant_pool_indices() {
+    setup_logging();
+    let directory =
+        std::env::temp_dir().join(format!("cafebabe-dead-code-damaged-{}", std::process::id()));
+    write_classes(
+        &directory,
+        &[r#"
+.class public app/Damaged
+.super java/lang/Object
+
+.method public static main ([Ljava/lang/String;)V
+    .code stack 1 locals 1
+        ldc_w #300
+        pop
+        return
+    .end code
+.end method
+"#],
+    );
+    let mut class_path = ClassPath::new();
+    class_path.add(&directory).unwrap();
+    let hierarchy = ClassHierarchy::from_class_path(&class_path).unwrap();
+
+    let report = DeadCodeReport::analyze(
+        &class_path,
+        &hierarchy,
+        &EntryPoints::main_methods(),
+        Algorithm::RapidType,
+    )
+    .unwrap();
+    assert_eq!(report.invalid_classes, ["app/Damaged"]);
+    fs::remove_dir_all(directory).unwrap();
+}
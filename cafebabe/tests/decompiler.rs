@@ -0,0 +1,211 @@
+use cafebabe::assembler::assemble;
+use cafebabe::decompiler::decompile;
+use cafebabe::{ClassFileError, read_class_data, write_class_data};
+use common::{read_class_from_path, setup_logging};
+
+mod common;
+
+const FLOW: &str = "res/decompiler/examples/Flow.class";
+
+const NESTED: &str = r#"
+.version 49 0
+.class public super examples/Nested
+.super java/lang/Object
+
+.method public static nested (I)I
+    .code stack 2 locals 3
+        iconst_0
+        istore_1
+    outer:
+        iload_1
+        iload_0
+        if_icmpge done
+        iconst_0
+        istore_2
+    inner:
+        iload_2
+        iload_1
+        if_icmpge next
+        iinc 2 1
+        goto inner
+    next:
+        iinc 1 1
+        goto outer
+    done:
+        iload_1
+        ireturn
+    .end code
+.end method
+
+.method public static subroutine ()V
+    .code stack 1 locals 1
+        jsr finally
+        return
+    finally:
+        astore_0
+        ret 0
+    .end code
+.end method
+"#;
+
+fn flow() -> String {
+    setup_logging();
+    decompile(&read_class_from_path(FLOW)).unwrap()
+}
+
+#[test]
+fn decompiles_class_header_and_fields() {
+    let source = flow();
+    assert!(source.starts_with("package examples;\n\npublic class Flow {\n"));
+    assert!(source.contains("    private static final String GREETING = \"Hello\";\n"));
+    assert!(source.contains("    private int count;\n"));
+    assert!(source.contains("    public Flow(int count) {\n        this.count = count;\n    }\n"));
+    assert!(source.ends_with("}\n"));
+}
+
+#[test]
+fn decompiles_loops() {
+    let source = flow();
+    assert!(source.contains(
+        "        int total = 0;
+        for (int i = 0; i < values.length; i++) {
+            total += values[i];
+        }
+        return total;
+"
+    ));
+    assert!(source.contains(
+        "        while (start > 0) {
+            start -= 2;
+            steps++;
+        }
+"
+    ));
+}
+
+#[test]
+fn decompiles_conditions() {
+    let source = flow();
+    assert!(source.contains("        return a > b ? a : b;\n"));
+    assert!(source.contains("        return value >= low && value <= high;\n"));
+    assert!(source.contains(
+        "        if (value < 0) {
+            return \"negative\";
+        }
+        if (value == 0) {
+            return \"zero\";
+        }
+        return \"positive\";
+"
+    ));
+}
+
+#[test]
+fn decompiles_switches() {
+    let source = flow();
+    assert!(source.contains(
+        "        switch (day) {
+            case 1:
+                name = \"Monday\";
+                break;
+            case 2:
+                name = \"Tuesday\";
+                break;
+            default:
+                name = \"Other\";
+        }
+        return name;
+"
+    ));
+}
+
+#[test]
+fn decompiles_try_statements() {
+    let source = flow();
+    assert!(source.contains(
+        "        try {
+            return Integer.parseInt(text);
+        } catch (NumberFormatException e) {
+            return -1;
+        }
+"
+    ));
+    assert!(source.contains(
+        "        try {
+            return this.count;
+        } finally {
+            resource.close();
+        }
+    }
+"
+    ));
+}
+
+#[test]
+fn decompiles_invokedynamic() {
+    let source = flow();
+    assert!(source.contains("        return \"Hello, \" + who + \" #\" + this.count;\n"));
+    assert!(
+        source.contains(
+            "        words.forEach(word -> lengths.add(Integer.valueOf(word.length())));\n"
+        )
+    );
+    assert!(source.contains("        return Integer::parseInt;\n"));
+    assert!(source.contains("        return () -> value.trim();\n"));
+}
+
+#[test]
+fn decompiles_nested_loops_without_debug_information() {
+    setup_logging();
+    let source = decompile(&assemble(NESTED).unwrap()).unwrap();
+    assert!(source.contains(
+        "    public static int nested(int arg0) {
+        int var1;
+        for (var1 = 0; var1 < arg0; var1++) {
+            for (int var2 = 0; var2 < var1; var2++) {
+            }
+        }
+        return var1;
+    }
+"
+    ));
+    assert!(!source.contains("goto"));
+    assert!(source.contains("        // subroutines of jsr and ret aren't decompiled\n"));
+}
+
+#[test]
+fn rejects_constants_outside_of_the_constant_pool() {
+    setup_logging();
+    let class_file = assemble(
+        r#"
+.class public examples/Damaged
+.super java/lang/Object
+
+.method public static value ()I
+    .code stack 1 locals 0
+        ldc #255
+        ireturn
+    .end code
+.end method
+"#,
+    )
+    .unwrap();
+    let source = decompile(&class_file).unwrap();
+    assert!(source.contains("        // can't decompile: InvalidConstantPoolIndex\n"));
+
+    let class_file = assemble(
+        r#"
+.class public examples/Damaged
+.super java/lang/Object
+.field public static final VALUE I
+    .attribute ConstantValue "0000"
+.end field
+"#,
+    )
+    .unwrap();
+    let class_file = read_class_data(&write_class_data(&class_file).unwrap()).unwrap();
+    assert!(matches!(
+        decompile(&class_file),
+        Err(ClassFileError::InvalidConstantPoolIndex)
+    ));
+}
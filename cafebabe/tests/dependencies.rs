@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use cafebabe::assembler::assemble;
+use cafebabe::class_path::ClassPath;
+use cafebabe::dependencies::{
+    DependencyAnalysis, DependencyFilter, JdkInternalDependency, Level, NOT_FOUND,
+    class_dependencies,
+};
+use common::{read_class_from_path, setup_logging, write_classes};
+
+mod common;
+
+const ANNOTATED_CLASS: &str = r#"
+.const #1 = Utf8 "La/Marker;"
+.const #2 = Utf8 "value"
+.const #3 = Utf8 "La/Level;"
+.const #4 = Utf8 "HIGH"
+.const #5 = Utf8 "[Ljava/util/List;"
+.const #6 = Utf8 "La/TypeUse;"
+.class public a/Annotated
+.super java/lang/Object
+// @Marker(value = Level.HIGH, value = List[].class)
+.attribute RuntimeVisibleAnnotations "0001 0001 0002 0002 65 0003 0004 0002 63 0005"
+// A type annotation on the superclass.
+.attribute RuntimeVisibleTypeAnnotations "0001 10 ffff 00 0006 0000"
+.field private values Ljava/util/Map;
+    .signature "Ljava/util/Map<Ljava/lang/String;Ljava/util/Optional<La/Value;>;>;"
+.end field
+"#;
+
+/// Classes in two packages that depend on each other, on the module in `examples.jmod` and on an internal class of the
+/// JDK, assembled into a directory of the test.
+fn example_classes(test: &str) -> PathBuf {
+    let sources = [
+        ".class public p/A\n.super java/lang/Object\n.implements examples/spi/Greeting
+            .field public b Lq/B;
+            .field public unsafe Lsun/misc/Unsafe;
+            .field public greeting Lexamples/internal/EnglishGreeting;",
+        ".class public q/B\n.super java/lang/Object\n.field public a [Lp/A;",
+        ".class public r/C\n.super q/B",
+    ];
+    let directory = std::env::temp_dir().join(format!(
+        "cafebabe-dependencies-{test}-{}",
+        std::process::id()
+    ));
+    write_classes(&directory, &sources);
+    directory
+}
+
+fn example_analysis(test: &str) -> DependencyAnalysis {
+    let directory = example_classes(test);
+    let mut roots = ClassPath::new();
+    roots.add(&directory).unwrap();
+    let mut class_path = ClassPath::new();
+    class_path.add("res/jars/examples.jmod").unwrap();
+    let analysis = DependencyAnalysis::analyze(&roots, &class_path).unwrap();
+    fs::remove_dir_all(directory).unwrap();
+    analysis
+}
+
+fn edges(pairs: &[(&str, &[&str])]) -> BTreeMap<String, std::collections::BTreeSet<String>> {
+    pairs
+        .iter()
+        .map(|(source, targets)| {
+            let targets = targets.iter().map(|target| target.to_string()).collect();
+            (source.to_string(), targets)
+        })
+        .collect()
+}
+
+#[test]
+fn finds_the_dependencies_of_a_class() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/ClassWithLambda.class");
+    let dependencies = class_dependencies(&class_file).unwrap();
+
+    assert!(dependencies.contains("java/util/function/Supplier"));
+    assert!(dependencies.contains("java/lang/invoke/LambdaMetafactory"));
+    assert!(dependencies.contains("java/lang/invoke/MethodHandles$Lookup"));
+    assert!(!dependencies.contains("examples/ClassWithLambda"));
+}
+
+#[test]
+fn finds_dependencies_in_signatures_and_annotations() {
+    setup_logging();
+    let class_file = assemble(ANNOTATED_CLASS).unwrap();
+    let dependencies: Vec<String> = class_dependencies(&class_file)
+        .unwrap()
+        .into_iter()
+        .collect();
+
+    assert_eq!(
+        dependencies,
+        [
+            "a/Level",
+            "a/Marker",
+            "a/TypeUse",
+            "a/Value",
+            "java/lang/Object",
+            "java/lang/String",
+            "java/util/List",
+            "java/util/Map",
+            "java/util/Optional",
+        ]
+    );
+}
+
+#[test]
+fn builds_graphs_at_each_level() {
+    setup_logging();
+    let analysis = example_analysis("graphs");
+    let filter = DependencyFilter::default();
+
+    assert_eq!(analysis.classes().len(), 3);
+    assert!(analysis.invalid_classes().is_empty());
+    assert_eq!(
+        analysis.archive_of("examples/spi/Greeting"),
+        Some("examples")
+    );
+    assert_eq!(analysis.archive_of("sun/misc/Unsafe"), None);
+    assert_eq!(
+        analysis.missing_classes(),
+        ["java/lang/Object", "sun/misc/Unsafe"]
+    );
+
+    let directory = analysis.classes()[0].archive.clone();
+    let archives = analysis.graph(Level::Archive, &filter);
+    assert_eq!(
+        archives.edges,
+        edges(&[(&directory, &["examples", NOT_FOUND])])
+    );
+
+    let packages = analysis.graph(Level::Package, &filter);
+    assert_eq!(
+        packages.edges,
+        edges(&[
+            (
+                "p",
+                &[
+                    "examples/internal",
+                    "examples/spi",
+                    "java/lang",
+                    "q",
+                    "sun/misc"
+                ]
+            ),
+            ("q", &["java/lang", "p"]),
+            ("r", &["q"]),
+        ])
+    );
+    assert_eq!(packages.nodes["examples/spi"], "examples");
+    assert_eq!(packages.nodes["java/lang"], NOT_FOUND);
+    assert_eq!(packages.dependents("q"), ["p", "r"]);
+    assert_eq!(packages.cycles(), [["p", "q"]]);
+
+    let classes = analysis.graph(Level::Class, &filter);
+    assert_eq!(classes.dependencies("r/C").collect::<Vec<_>>(), ["q/B"]);
+    assert_eq!(classes.cycles(), [["p/A", "q/B"]]);
+}
+
+#[test]
+fn filters_dependencies() {
+    setup_logging();
+    let analysis = example_analysis("filters");
+
+    let filter = DependencyFilter {
+        include: vec!["p/".to_string(), "r/".to_string()],
+        exclude: vec!["java/".to_string(), "sun/".to_string()],
+        exclude_jdk: true,
+    };
+    let packages = analysis.graph(Level::Package, &filter);
+    assert_eq!(packages.edges, edges(&[("p", &["q"]), ("r", &["q"])]));
+    assert!(packages.cycles().is_empty());
+}
+
+#[test]
+fn detects_dependencies_on_jdk_internals() {
+    setup_logging();
+    let analysis = example_analysis("jdk-internals");
+
+    assert_eq!(
+        analysis.jdk_internal_dependencies(&DependencyFilter::default()),
+        [
+            JdkInternalDependency {
+                class_name: "p/A".to_string(),
+                archive: analysis.classes()[0].archive.clone(),
+                target: "examples/internal/EnglishGreeting".to_string(),
+                module: Some("examples".to_string()),
+            },
+            JdkInternalDependency {
+                class_name: "p/A".to_string(),
+                archive: analysis.classes()[0].archive.clone(),
+                target: "sun/misc/Unsafe".to_string(),
+                module: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn prints_graphs_in_dot() {
+    setup_logging();
+    let analysis = example_analysis("dot");
+    let filter = DependencyFilter {
+        include: vec!["r/".to_string()],
+        ..DependencyFilter::default()
+    };
+
+    let dot = analysis.graph(Level::Class, &filter).to_dot();
+    assert!(dot.starts_with("digraph dependencies {\n"));
+    let cluster = format!(
+        "    subgraph cluster_0 {{\n        label = \"{}\";\n        \"q.B\";\n        \"r.C\";\n    }}\n",
+        analysis.classes()[0].archive
+    );
+    assert!(dot.contains(&cluster));
+    assert!(dot.contains("    \"r.C\" -> \"q.B\";\n"));
+    assert!(dot.ends_with("}\n"));
+}
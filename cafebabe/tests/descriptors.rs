@@ -0,0 +1,99 @@
+use cafebabe::descriptor::{FieldType, MethodDescriptor, signature_class_names};
+
+#[test]
+fn parses_field_descriptors() {
+    assert_eq!(FieldType::parse("I").unwrap(), FieldType::Int);
+    assert_eq!(
+        FieldType::parse("Ljava/lang/String;").unwrap(),
+        FieldType::Object("java/lang/String".to_string())
+    );
+
+    let matrix = FieldType::parse("[[D").unwrap();
+    assert_eq!(
+        matrix,
+        FieldType::Array(Box::new(FieldType::Array(Box::new(FieldType::Double))))
+    );
+    assert_eq!(matrix.java_name(), "double[][]");
+    assert_eq!(matrix.to_string(), "[[D");
+}
+
+#[test]
+fn rejects_invalid_field_descriptors() {
+    assert!(FieldType::parse("").is_err());
+    assert!(FieldType::parse("V").is_err());
+    assert!(FieldType::parse("Ljava/lang/String").is_err());
+    assert!(FieldType::parse("L;").is_err());
+    assert!(FieldType::parse("II").is_err());
+}
+
+#[test]
+fn parses_method_descriptors() {
+    let descriptor = MethodDescriptor::parse("(IJ[Ljava/lang/Object;)Ljava/lang/String;").unwrap();
+    assert_eq!(
+        descriptor.parameters,
+        vec![
+            FieldType::Int,
+            FieldType::Long,
+            FieldType::Array(Box::new(FieldType::Object("java/lang/Object".to_string()))),
+        ]
+    );
+    assert_eq!(
+        descriptor.return_type,
+        Some(FieldType::Object("java/lang/String".to_string()))
+    );
+    assert_eq!(descriptor.parameter_slots(), 4);
+    assert_eq!(
+        descriptor.to_string(),
+        "(IJ[Ljava/lang/Object;)Ljava/lang/String;"
+    );
+
+    let void = MethodDescriptor::parse("()V").unwrap();
+    assert!(void.parameters.is_empty());
+    assert_eq!(void.return_type, None);
+}
+
+#[test]
+fn rejects_invalid_method_descriptors() {
+    assert!(MethodDescriptor::parse("V").is_err());
+    assert!(MethodDescriptor::parse("(I").is_err());
+    assert!(MethodDescriptor::parse("(V)V").is_err());
+    assert!(MethodDescriptor::parse("()").is_err());
+}
+
+#[test]
+fn finds_the_classes_in_signatures() {
+    assert_eq!(
+        signature_class_names(
+            "<T:Ljava/lang/Object;>Ljava/util/AbstractList<TT;>;Ljava/util/RandomAccess;"
+        )
+        .unwrap(),
+        [
+            "java/lang/Object",
+            "java/util/AbstractList",
+            "java/util/RandomAccess"
+        ]
+    );
+    assert_eq!(
+        signature_class_names(
+            "<K::Ljava/lang/Comparable<-TK;>;E:Ljava/lang/Exception;>(Ljava/util/Map<TK;+Ljava/util/List<*>;>;[I)Ljava/util/Map<TK;TK;>.Entry<TK;TK;>;^Ljava/io/IOException;^TE;"
+        )
+        .unwrap(),
+        [
+            "java/lang/Comparable",
+            "java/lang/Exception",
+            "java/util/List",
+            "java/util/Map",
+            "java/util/Map$Entry",
+            "java/io/IOException",
+        ]
+    );
+    assert_eq!(signature_class_names("[TT;").unwrap(), Vec::<String>::new());
+}
+
+#[test]
+fn rejects_invalid_signatures() {
+    assert!(signature_class_names("Ljava/util/List<").is_err());
+    assert!(signature_class_names("Ljava/util/List").is_err());
+    assert!(signature_class_names("<T>Ljava/lang/Object;").is_err());
+    assert!(signature_class_names("(I").is_err());
+}
@@ -0,0 +1,319 @@
+use std::fs;
+use std::path::Path;
+
+use cafebabe::assembler::assemble;
+use cafebabe::constant_pool::ConstantPoolEntry;
+use cafebabe::format_check::{Diagnostic, Location, check_class_data, check_class_file};
+use cafebabe::{
+    AccessFlags, ClassFileError, ParseOptions, Strictness, read_class_data,
+    read_class_data_with_options, write_class_data,
+};
+use common::{class_files, setup_logging};
+
+mod common;
+
+fn strict() -> ParseOptions {
+    ParseOptions {
+        strictness: Strictness::Strict,
+        ..ParseOptions::default()
+    }
+}
+
+fn method(name: &str, descriptor: &str) -> Location {
+    Location::Method {
+        name: name.to_string(),
+        descriptor: descriptor.to_string(),
+    }
+}
+
+fn field(name: &str, descriptor: &str) -> Location {
+    Location::Field {
+        name: name.to_string(),
+        descriptor: descriptor.to_string(),
+    }
+}
+
+/// The locations and messages of diagnostics, which are easier to compare than the diagnostics themselves.
+fn summary(diagnostics: &[Diagnostic]) -> Vec<(Location, &str, &str)> {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            (
+                diagnostic.location.clone(),
+                diagnostic.section,
+                diagnostic.message.as_str(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn accepts_class_files_from_javac() {
+    setup_logging();
+    for path in class_files(Path::new("res")) {
+        let data = fs::read(&path).unwrap();
+        assert_eq!(
+            check_class_data(&data, &ParseOptions::default()).unwrap(),
+            Vec::new(),
+            "{path:?}"
+        );
+        assert!(read_class_data_with_options(&data, &strict()).is_ok());
+    }
+}
+
+#[test]
+fn rejects_trailing_bytes_in_strict_mode() {
+    setup_logging();
+    let mut data = fs::read("res/java17/examples/EmptyClass.class").unwrap();
+    let length = data.len();
+    data.extend([0xca, 0xfe]);
+    assert!(read_class_data(&data).is_ok());
+
+    let Err(ClassFileError::FormatViolations(diagnostics)) =
+        read_class_data_with_options(&data, &strict())
+    else {
+        panic!("expected format violations");
+    };
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic {
+            location: Location::Offset { offset: length },
+            section: "4.8",
+            message: "2 trailing bytes after the attributes of the class".to_string(),
+        }]
+    );
+    assert_eq!(
+        diagnostics[0].to_string(),
+        format!("offset {length}: 2 trailing bytes after the attributes of the class (JVMS 4.8)")
+    );
+}
+
+/// Small class files with many kinds of attributes: code, bootstrap methods, inner classes and a module.
+const DAMAGEABLE: [&str; 2] = [
+    "res/java17/examples/ClassWithLambda.class",
+    "res/java17/module/module-info.class",
+];
+
+#[test]
+fn rejects_truncated_class_files() {
+    setup_logging();
+    for path in DAMAGEABLE {
+        let data = fs::read(path).unwrap();
+        for length in 0..data.len() {
+            let truncated = &data[..length];
+            assert!(read_class_data(truncated).is_err(), "{path} at {length}");
+            assert!(
+                read_class_data_with_options(truncated, &strict()).is_err(),
+                "{path} at {length}"
+            );
+        }
+    }
+    assert!(matches!(
+        read_class_data(&[0xca, 0xfe]),
+        Err(ClassFileError::Truncated)
+    ));
+    let data = fs::read("res/java17/examples/EmptyClass.class").unwrap();
+    assert!(matches!(
+        read_class_data(&data[..data.len() - 1]),
+        Err(ClassFileError::Truncated)
+    ));
+}
+
+#[test]
+fn rejects_damaged_class_files_without_panicking() {
+    setup_logging();
+    for path in DAMAGEABLE {
+        let data = fs::read(path).unwrap();
+        for idx in 0..data.len() {
+            let mut damaged = data.clone();
+            damaged[idx] = 0xff;
+            let _ = read_class_data_with_options(&damaged, &strict());
+        }
+    }
+}
+
+#[test]
+fn reports_illegal_access_flags() {
+    setup_logging();
+    let mut class_file = assemble(
+        ".class public final abstract broken/Flags
+.super java/lang/Object
+.field public private count I
+.field final volatile state I
+.method public abstract static final run ()V
+.end method
+.method public static <init> ()V
+    .code stack 0 locals 0
+        return
+    .end code
+.end method",
+    )
+    .unwrap();
+    assert_eq!(
+        summary(&check_class_file(&class_file)),
+        vec![
+            (
+                Location::Class,
+                "4.1",
+                "a class must not be both ACC_FINAL and ACC_ABSTRACT"
+            ),
+            (
+                field("count", "I"),
+                "4.5",
+                "a field must not be ACC_PUBLIC ACC_PRIVATE"
+            ),
+            (
+                field("state", "I"),
+                "4.5",
+                "a field must not be both ACC_FINAL and ACC_VOLATILE"
+            ),
+            (
+                method("run", "()V"),
+                "4.6",
+                "an abstract method must not be ACC_STATIC ACC_FINAL"
+            ),
+            (
+                method("<init>", "()V"),
+                "4.6",
+                "<init> must not be ACC_STATIC"
+            ),
+        ]
+    );
+
+    class_file.access_flags = AccessFlags::ACC_PUBLIC | AccessFlags::ACC_INTERFACE;
+    let diagnostics = check_class_file(&class_file);
+    assert!(summary(&diagnostics).contains(&(
+        Location::Class,
+        "4.1",
+        "ACC_INTERFACE requires ACC_ABSTRACT"
+    )));
+    assert!(summary(&diagnostics).contains(&(
+        field("count", "I"),
+        "4.5",
+        "a field of an interface must be ACC_PUBLIC ACC_STATIC ACC_FINAL, and may only also be ACC_SYNTHETIC"
+    )));
+    assert!(summary(&diagnostics).contains(&(
+        method("<init>", "()V"),
+        "4.6",
+        "an interface must not have an <init> method"
+    )));
+}
+
+#[test]
+fn reports_invalid_names_descriptors_duplicates_and_missing_code() {
+    setup_logging();
+    let mut class_file = assemble(
+        ".class public broken/Members
+.super java/lang/Object
+.field public count I
+.field public count I
+.field public static final NAME Ljava/lang/String;
+    .constantvalue Int 10
+.end field
+.method public run ()V
+.end method
+.method public native stop ()V
+    .code stack 0 locals 0
+        return
+    .end code
+.end method",
+    )
+    .unwrap();
+    let bad_name = class_file
+        .constant_pool
+        .add(ConstantPoolEntry::String("a.b".to_string()));
+    let bad_descriptor = class_file
+        .constant_pool
+        .add(ConstantPoolEntry::String("Lbroken".to_string()));
+    class_file.fields[1].name_idx = bad_name;
+    class_file.fields[1].descriptor_idx = bad_descriptor;
+
+    let diagnostics = check_class_file(&class_file);
+    assert_eq!(
+        summary(&diagnostics),
+        vec![
+            (
+                field("a.b", "Lbroken"),
+                "4.2.2",
+                "invalid field name \"a.b\""
+            ),
+            (
+                field("a.b", "Lbroken"),
+                "4.3.2",
+                "invalid field descriptor \"Lbroken\""
+            ),
+            (
+                field("NAME", "Ljava/lang/String;"),
+                "4.7.2",
+                "the constant #10 does not match the type of the field"
+            ),
+            (
+                method("run", "()V"),
+                "4.7.3",
+                "a method that is neither native nor abstract must have code"
+            ),
+            (
+                method("stop", "()V"),
+                "4.7.3",
+                "a native or abstract method must not have code"
+            ),
+        ]
+    );
+    assert!(matches!(
+        class_file.constant_pool.get_entry(10),
+        ConstantPoolEntry::Integer(10)
+    ));
+
+    class_file.fields[1].name_idx = class_file.fields[0].name_idx;
+    class_file.fields[1].descriptor_idx = class_file.fields[0].descriptor_idx;
+    assert!(summary(&check_class_file(&class_file)).contains(&(
+        field("count", "I"),
+        "4.5",
+        "duplicate field"
+    )));
+
+    let data = write_class_data(&class_file).unwrap();
+    assert!(read_class_data(&data).is_ok());
+    let Err(ClassFileError::FormatViolations(diagnostics)) =
+        read_class_data_with_options(&data, &strict())
+    else {
+        panic!("expected format violations");
+    };
+    assert_eq!(diagnostics.len(), 4);
+}
+
+#[test]
+fn reports_invalid_constant_pool_entries() {
+    setup_logging();
+    let mut class_file = assemble(
+        ".version 50 0
+.class public broken/Pool
+.super java/lang/Object",
+    )
+    .unwrap();
+    let bad_class = class_file
+        .constant_pool
+        .add(ConstantPoolEntry::String("broken//Pool".to_string()));
+    let class_ref = class_file
+        .constant_pool
+        .add(ConstantPoolEntry::ClassRef(bad_class));
+    let method_type = class_file
+        .constant_pool
+        .add(ConstantPoolEntry::MethodType(bad_class));
+
+    assert_eq!(
+        summary(&check_class_file(&class_file)),
+        vec![
+            (
+                Location::ConstantPool { index: class_ref },
+                "4.4.1",
+                "invalid class name \"broken//Pool\""
+            ),
+            (
+                Location::ConstantPool { index: method_type },
+                "4.4",
+                "MethodType entries require major version 51"
+            ),
+        ]
+    );
+}
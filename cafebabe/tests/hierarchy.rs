@@ -0,0 +1,164 @@
+use cafebabe::assembler::assemble;
+use cafebabe::class_path::ClassPath;
+use cafebabe::hierarchy::{ClassHierarchy, MissingSupertype};
+use common::setup_logging;
+
+mod common;
+
+fn example_hierarchy() -> ClassHierarchy {
+    let sources = [
+        ".class public interface abstract a/Animal\n.super java/lang/Object",
+        ".class public interface abstract a/Pet\n.super java/lang/Object\n.implements a/Animal",
+        ".class public a/Base\n.super java/lang/Object\n.implements a/Pet",
+        ".class public a/Dog\n.super a/Base",
+        ".class public a/Cat\n.super a/Base\n.implements java/lang/Comparable",
+        ".class public a/Rock\n.super java/lang/Object",
+    ];
+    let mut hierarchy = ClassHierarchy::new();
+    for source in sources {
+        hierarchy.add_class(&assemble(source).unwrap()).unwrap();
+    }
+    hierarchy
+}
+
+#[test]
+fn finds_supertypes_and_subtypes() {
+    setup_logging();
+    let hierarchy = example_hierarchy();
+
+    assert_eq!(hierarchy.len(), 6);
+    assert_eq!(
+        hierarchy.superclasses("a/Dog"),
+        ["a/Base", "java/lang/Object"]
+    );
+    assert_eq!(
+        hierarchy.supertypes("a/Dog"),
+        ["a/Base", "java/lang/Object", "a/Pet", "a/Animal"]
+    );
+    assert_eq!(
+        hierarchy.supertypes("a/Cat"),
+        [
+            "a/Base",
+            "java/lang/Object",
+            "java/lang/Comparable",
+            "a/Pet",
+            "a/Animal"
+        ]
+    );
+    assert_eq!(hierarchy.direct_subtypes("a/Animal"), ["a/Pet"]);
+    assert_eq!(
+        hierarchy.subtypes("a/Animal"),
+        ["a/Base", "a/Cat", "a/Dog", "a/Pet"]
+    );
+    assert_eq!(
+        hierarchy.implementors("a/Animal"),
+        ["a/Base", "a/Cat", "a/Dog"]
+    );
+    assert_eq!(hierarchy.implementors("a/Base"), ["a/Cat", "a/Dog"]);
+    assert!(hierarchy.subtypes("a/Rock").is_empty());
+    assert!(hierarchy.supertypes("a/Unknown").is_empty());
+}
+
+#[test]
+fn reports_missing_supertypes() {
+    setup_logging();
+    let hierarchy = example_hierarchy();
+
+    assert_eq!(
+        hierarchy.missing_supertypes(),
+        [
+            MissingSupertype {
+                class_name: "java/lang/Comparable".to_string(),
+                referenced_by: vec!["a/Cat".to_string()],
+            },
+            MissingSupertype {
+                class_name: "java/lang/Object".to_string(),
+                referenced_by: ["a/Animal", "a/Base", "a/Pet", "a/Rock"]
+                    .map(String::from)
+                    .to_vec(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn answers_type_questions_for_stack_map_frames() {
+    setup_logging();
+    let hierarchy = example_hierarchy();
+
+    assert!(hierarchy.is_assignable("a/Dog", "a/Animal"));
+    assert!(hierarchy.is_assignable("a/Rock", "java/lang/Object"));
+    assert!(!hierarchy.is_assignable("a/Rock", "a/Animal"));
+    assert!(!hierarchy.is_assignable("a/Base", "a/Dog"));
+    assert_eq!(hierarchy.is_interface("a/Pet"), Some(true));
+    assert_eq!(hierarchy.is_interface("a/Dog"), Some(false));
+    assert_eq!(hierarchy.is_interface("java/lang/Object"), None);
+
+    assert_eq!(
+        hierarchy.common_super_class("a/Dog", "a/Cat"),
+        Some("a/Base")
+    );
+    assert_eq!(
+        hierarchy.common_super_class("a/Dog", "a/Base"),
+        Some("a/Base")
+    );
+    assert_eq!(
+        hierarchy.common_super_class("a/Dog", "a/Pet"),
+        Some("a/Pet")
+    );
+    assert_eq!(
+        hierarchy.common_super_class("a/Dog", "a/Rock"),
+        Some("java/lang/Object")
+    );
+    assert_eq!(
+        hierarchy.common_super_class("a/Pet", "a/Rock"),
+        Some("java/lang/Object")
+    );
+    assert_eq!(hierarchy.common_super_class("a/Dog", "a/Unknown"), None);
+}
+
+#[test]
+fn replaces_classes_that_are_added_again() {
+    setup_logging();
+    let mut hierarchy = example_hierarchy();
+
+    let cat = assemble(".class public a/Cat\n.super java/lang/Object").unwrap();
+    hierarchy.add_class(&cat).unwrap();
+    assert_eq!(hierarchy.len(), 6);
+    assert_eq!(hierarchy.direct_subtypes("a/Base"), ["a/Dog"]);
+    assert_eq!(hierarchy.implementors("a/Animal"), ["a/Base", "a/Dog"]);
+}
+
+#[test]
+fn indexes_a_class_path() {
+    setup_logging();
+    let mut class_path = ClassPath::new();
+    class_path.add("res/java17").unwrap();
+    class_path.add("res/jars/examples.jmod").unwrap();
+    let hierarchy = ClassHierarchy::from_class_path(&class_path).unwrap();
+
+    assert!(hierarchy.invalid_classes().is_empty());
+    assert!(hierarchy.contains("examples/RecordClass"));
+    assert_eq!(
+        hierarchy.superclasses("examples/RecordClass"),
+        ["java/lang/Record"]
+    );
+    assert_eq!(
+        hierarchy.implementors("java/io/Serializable"),
+        [
+            "examples/ClassWithInterface",
+            "examples/ClassWithTwoInterfaces"
+        ]
+    );
+    assert_eq!(
+        hierarchy.implementors("examples/spi/Greeting"),
+        ["examples/internal/EnglishGreeting"]
+    );
+    let missing: Vec<String> = hierarchy
+        .missing_supertypes()
+        .into_iter()
+        .map(|missing| missing.class_name)
+        .collect();
+    assert!(missing.contains(&"java/lang/Object".to_string()));
+    assert!(!missing.contains(&"examples/spi/Greeting".to_string()));
+}
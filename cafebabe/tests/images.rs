@@ -0,0 +1,228 @@
+use std::fs;
+
+use cafebabe::archive::{ArchiveError, JImage, JmodFile, JmodSection, ResourceLookup};
+use cafebabe::read_class_data;
+use common::jimage::{ImageCompression, ImageResource, write_jimage};
+use common::{setup_logging, validate_class_name};
+
+mod common;
+
+fn resource(module: &str, name: &str, path: &str, compression: ImageCompression) -> ImageResource {
+    ImageResource {
+        module: module.to_string(),
+        name: name.to_string(),
+        content: fs::read(path).unwrap(),
+        compression,
+    }
+}
+
+fn example_image() -> JImage {
+    let resources = vec![
+        resource(
+            "examples",
+            "module-info.class",
+            "res/java17/module/module-info.class",
+            ImageCompression::None,
+        ),
+        resource(
+            "examples",
+            "examples/ClassWithCode.class",
+            "res/java17/examples/ClassWithCode.class",
+            ImageCompression::None,
+        ),
+        resource(
+            "examples",
+            "examples/ClassWithLambda.class",
+            "res/java17/examples/ClassWithLambda.class",
+            ImageCompression::Zip,
+        ),
+        resource(
+            "examples",
+            "examples/ClassWithMethods.class",
+            "res/java17/examples/ClassWithMethods.class",
+            ImageCompression::SharedStrings,
+        ),
+        resource(
+            "examples",
+            "examples/RecordClass.class",
+            "res/java17/examples/RecordClass.class",
+            ImageCompression::SharedStrings,
+        ),
+        resource(
+            "other",
+            "other/EmptyClass.java",
+            "res/java17/examples/EmptyClass.java",
+            ImageCompression::Zip,
+        ),
+        resource(
+            "other",
+            "module-info.class",
+            "res/java17/module/module-info.class",
+            ImageCompression::None,
+        ),
+    ];
+    JImage::from_bytes(write_jimage(&resources)).unwrap()
+}
+
+#[test]
+fn finds_jimage_locations() {
+    setup_logging();
+    let image = example_image();
+    assert_eq!(image.major_version, 1);
+    assert_eq!(image.modules().unwrap(), vec!["examples", "other"]);
+
+    for location in image.locations().unwrap() {
+        let found = image.find_location(&location.full_name()).unwrap();
+        assert_eq!(found.as_ref(), Some(&location));
+    }
+    let location = image
+        .find_location("/examples/examples/ClassWithCode.class")
+        .unwrap()
+        .unwrap();
+    assert_eq!(location.module, "examples");
+    assert_eq!(location.parent, "examples");
+    assert_eq!(location.base, "ClassWithCode");
+    assert_eq!(location.extension, "class");
+    assert!(
+        image
+            .find_location("/examples/examples/Missing.class")
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(
+        image.module_of("other/EmptyClass.java").unwrap().as_deref(),
+        Some("other")
+    );
+}
+
+#[test]
+fn reads_compressed_jimage_resources() {
+    setup_logging();
+    let image = example_image();
+
+    for (class_name, path) in [
+        (
+            "examples/ClassWithCode",
+            "res/java17/examples/ClassWithCode.class",
+        ),
+        (
+            "examples/ClassWithLambda",
+            "res/java17/examples/ClassWithLambda.class",
+        ),
+        (
+            "examples/ClassWithMethods",
+            "res/java17/examples/ClassWithMethods.class",
+        ),
+        (
+            "examples/RecordClass",
+            "res/java17/examples/RecordClass.class",
+        ),
+    ] {
+        let data = image
+            .find_resource(&format!("{class_name}.class"))
+            .unwrap()
+            .unwrap();
+        assert!(
+            data == fs::read(path).unwrap(),
+            "Expect {class_name} to be restored"
+        );
+        validate_class_name(&image.find_class(class_name).unwrap().unwrap(), class_name);
+    }
+    let source = image
+        .find_resource("other/EmptyClass.java")
+        .unwrap()
+        .unwrap();
+    assert!(
+        String::from_utf8(source)
+            .unwrap()
+            .contains("class EmptyClass")
+    );
+    assert!(image.find_class("examples/Missing").unwrap().is_none());
+
+    let mut names = ResourceLookup::resource_names(&image);
+    names.sort();
+    assert_eq!(names.len(), 6);
+    assert_eq!(names[0], "examples/ClassWithCode.class");
+}
+
+#[test]
+fn rejects_invalid_jimage() {
+    setup_logging();
+    assert!(matches!(
+        JImage::from_bytes(b"not an image".to_vec()),
+        Err(ArchiveError::InvalidArchive)
+    ));
+
+    let resources = [resource(
+        "examples",
+        "examples/ClassWithCode.class",
+        "res/java17/examples/ClassWithCode.class",
+        ImageCompression::None,
+    )];
+    let mut data = write_jimage(&resources);
+    let image = JImage::from_bytes(data.clone()).unwrap();
+    let mut location = image
+        .find_resource_location("examples/ClassWithCode.class")
+        .unwrap()
+        .unwrap();
+    location.offset = u64::MAX;
+    assert!(matches!(
+        image.read(&location),
+        Err(ArchiveError::InvalidArchive)
+    ));
+
+    // Redirects of the hash table to the lowest index.
+    let table_length = u32::from_le_bytes(data[16..20].try_into().unwrap()) as usize;
+    for slot in data[28..28 + 4 * table_length].chunks_mut(4) {
+        slot.copy_from_slice(&i32::MIN.to_le_bytes());
+    }
+    let image = JImage::from_bytes(data).unwrap();
+    assert!(matches!(
+        image.find_location("/examples/examples/ClassWithCode.class"),
+        Err(ArchiveError::InvalidArchive)
+    ));
+}
+
+#[test]
+fn reads_jmod() {
+    setup_logging();
+    let jmod = JmodFile::open("res/jars/examples.jmod").unwrap();
+    assert_eq!((jmod.major_version, jmod.minor_version), (1, 0));
+
+    let mut names = jmod.resource_names();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "examples/Main.class",
+            "examples/internal/EnglishGreeting.class",
+            "examples/spi/Greeting.class",
+            "module-info.class",
+        ]
+    );
+    validate_class_name(
+        &jmod.find_class("examples/Main").unwrap().unwrap(),
+        "examples/Main",
+    );
+    let module_info = read_class_data(
+        &jmod
+            .read(JmodSection::Classes, "module-info.class")
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        module_info.module_descriptor().unwrap().unwrap().name,
+        "examples"
+    );
+
+    let notices: Vec<&str> = jmod
+        .entries(JmodSection::LegalNotices)
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(notices, vec!["NOTICE"]);
+    assert!(jmod.entries(JmodSection::NativeLibraries).next().is_none());
+    assert!(matches!(
+        JmodFile::open("res/jars/examples.jar"),
+        Err(ArchiveError::InvalidArchive)
+    ));
+}
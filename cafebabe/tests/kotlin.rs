@@ -0,0 +1,348 @@
+use cafebabe::ClassFileError;
+use cafebabe::kotlin::{
+    Class, ClassKind, Classifier, Declarations, JvmSignature, MemberKind, Metadata, Modality, Type,
+    Variance, Visibility, kotlin_declarations, print_kotlin, read_metadata,
+};
+use common::{read_class_from_path, setup_logging};
+
+mod common;
+
+const PAIR: &str = "res/kotlin/kotlin/Pair.class";
+const SEGMENT: &str = "res/kotlin/okio/Segment.class";
+const CLOSEABLE: &str = "res/kotlin/kotlin/io/CloseableKt.class";
+const LAZY: &str = "res/kotlin/kotlin/LazyKt.class";
+const LAZY_JVM: &str = "res/kotlin/kotlin/LazyKt__LazyJVMKt.class";
+const CLASS_WITH_CODE: &str = "res/java17/examples/ClassWithCode.class";
+
+fn declarations(path: &str) -> Declarations {
+    setup_logging();
+    kotlin_declarations(&read_class_from_path(path))
+        .unwrap()
+        .unwrap()
+}
+
+fn class(path: &str) -> Class {
+    match declarations(path) {
+        Declarations::Class(class) => class,
+        declarations => panic!("not a class: {declarations:?}"),
+    }
+}
+
+fn signature(name: &str, descriptor: &str) -> Option<JvmSignature> {
+    Some(JvmSignature {
+        name: name.to_string(),
+        descriptor: Some(descriptor.to_string()),
+    })
+}
+
+/// Encodes the bytes of the protocol buffers in the 7-bit form of older compilers.
+fn encode_7_bit(bytes: &[u8]) -> String {
+    let mut bits = bytes
+        .iter()
+        .flat_map(|byte| (0..8).map(move |bit| (byte >> bit) & 1));
+    let mut encoded = String::new();
+    loop {
+        let group: Vec<u8> = bits.by_ref().take(7).collect();
+        if group.is_empty() {
+            return encoded;
+        }
+        let value = group
+            .iter()
+            .enumerate()
+            .fold(0, |value, (bit, set)| value | (set << bit));
+        encoded.push(char::from((value + 1) & 0x7f));
+    }
+}
+
+#[test]
+fn reads_metadata_annotation() {
+    setup_logging();
+    let metadata = read_metadata(&read_class_from_path(PAIR)).unwrap().unwrap();
+    assert_eq!(metadata.kind, 1);
+    assert_eq!(metadata.version, [1, 8, 0]);
+    assert_eq!(metadata.extra_int, 48);
+    assert_eq!(metadata.data1.len(), 1);
+    assert!(metadata.data1[0].starts_with('\0'));
+    assert_eq!(metadata.data2[..3], ["Lkotlin/Pair;", "A", "B"]);
+    assert_eq!(metadata.extra_string, None);
+
+    let metadata = read_metadata(&read_class_from_path(LAZY_JVM))
+        .unwrap()
+        .unwrap();
+    assert_eq!(metadata.kind, 5);
+    assert_eq!(metadata.extra_string.as_deref(), Some("kotlin/LazyKt"));
+}
+
+#[test]
+fn ignores_classes_without_metadata() {
+    setup_logging();
+    let class_file = read_class_from_path(CLASS_WITH_CODE);
+    assert_eq!(read_metadata(&class_file).unwrap(), None);
+    assert_eq!(print_kotlin(&class_file, false).unwrap(), None);
+}
+
+#[test]
+fn decodes_data_classes() {
+    let pair = class(PAIR);
+    assert_eq!(pair.name, "kotlin/Pair");
+    assert_eq!(pair.kind, ClassKind::Class);
+    assert_eq!(pair.visibility, Visibility::Public);
+    assert_eq!(pair.modality, Modality::Final);
+    assert!(pair.is_data);
+    let type_parameters: Vec<(&str, Variance)> = pair
+        .type_parameters
+        .iter()
+        .map(|type_parameter| (type_parameter.name.as_str(), type_parameter.variance))
+        .collect();
+    assert_eq!(
+        type_parameters,
+        [("A", Variance::Out), ("B", Variance::Out)]
+    );
+    assert_eq!(pair.supertypes[0].to_string(), "kotlin.io.Serializable");
+
+    assert_eq!(pair.constructors.len(), 1);
+    let parameters: Vec<String> = pair.constructors[0]
+        .parameters
+        .iter()
+        .map(|parameter| format!("{}: {}", parameter.name, parameter.parameter_type))
+        .collect();
+    assert_eq!(parameters, ["first: A", "second: B"]);
+
+    let first = &pair.properties[0];
+    assert_eq!(first.name, "first");
+    assert!(!first.is_var);
+    assert_eq!(
+        first.return_type.classifier,
+        Classifier::TypeParameter("A".to_string())
+    );
+    assert_eq!(first.field, signature("first", "Ljava/lang/Object;"));
+    assert_eq!(first.getter, signature("getFirst", "()Ljava/lang/Object;"));
+    assert_eq!(first.setter, None);
+
+    let names: Vec<&str> = pair
+        .functions
+        .iter()
+        .map(|function| function.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "component1",
+            "component2",
+            "copy",
+            "equals",
+            "hashCode",
+            "toString"
+        ]
+    );
+    let component1 = &pair.functions[0];
+    assert!(component1.is_operator);
+    assert_eq!(component1.member_kind, MemberKind::Synthesized);
+    let copy = &pair.functions[2];
+    assert!(
+        copy.parameters
+            .iter()
+            .all(|parameter| parameter.declares_default_value)
+    );
+    assert_eq!(copy.return_type.to_string(), "kotlin.Pair<A, B>");
+    assert_eq!(
+        copy.signature,
+        signature(
+            "copy",
+            "(Ljava/lang/Object;Ljava/lang/Object;)Lkotlin/Pair;"
+        )
+    );
+    assert_eq!(pair.functions[3].modality, Modality::Open);
+    assert_eq!(
+        pair.functions[3].parameters[0].parameter_type.to_string(),
+        "kotlin.Any?"
+    );
+}
+
+#[test]
+fn decodes_nullability_and_properties() {
+    let segment = class(SEGMENT);
+    assert_eq!(segment.visibility, Visibility::Internal);
+    assert_eq!(segment.companion_object.as_deref(), Some("Companion"));
+    assert_eq!(segment.nested_classes, ["Companion"]);
+    assert_eq!(segment.constructors.len(), 2);
+
+    let next = segment
+        .properties
+        .iter()
+        .find(|property| property.name == "next")
+        .unwrap();
+    assert!(next.is_var);
+    assert_eq!(
+        next.return_type,
+        Type {
+            classifier: Classifier::Class("okio/Segment".to_string()),
+            arguments: Vec::new(),
+            is_nullable: true,
+            is_suspend: false,
+            is_definitely_non_null: false,
+            flexible_upper_bound: None,
+            abbreviated_type: None,
+        }
+    );
+    let data = segment
+        .properties
+        .iter()
+        .find(|property| property.name == "data")
+        .unwrap();
+    assert!(!data.is_var);
+    assert_eq!(data.return_type.to_string(), "kotlin.ByteArray");
+
+    let pop = segment
+        .functions
+        .iter()
+        .find(|function| function.name == "pop")
+        .unwrap();
+    assert_eq!(pop.return_type.to_string(), "okio.Segment?");
+    let push = segment
+        .functions
+        .iter()
+        .find(|function| function.name == "push")
+        .unwrap();
+    assert_eq!(
+        push.parameters[0].parameter_type.to_string(),
+        "okio.Segment"
+    );
+}
+
+#[test]
+fn decodes_extension_functions() {
+    let Declarations::File(file) = declarations(CLOSEABLE) else {
+        panic!("not a file facade");
+    };
+    assert_eq!(file.functions.len(), 2);
+
+    let close_finally = &file.functions[0];
+    assert_eq!(close_finally.name, "closeFinally");
+    assert_eq!(close_finally.visibility, Visibility::Internal);
+    let receiver = close_finally.receiver.as_ref().unwrap();
+    assert_eq!(receiver.to_string(), "java.io.Closeable?");
+    assert_eq!(
+        close_finally.parameters[0].parameter_type.to_string(),
+        "kotlin.Throwable?"
+    );
+    assert_eq!(close_finally.return_type.to_string(), "kotlin.Unit");
+
+    let using = &file.functions[1];
+    assert_eq!(using.name, "use");
+    assert!(using.is_inline);
+    assert_eq!(using.type_parameters[0].name, "T");
+    assert_eq!(
+        using.type_parameters[0].upper_bounds[0].to_string(),
+        "java.io.Closeable?"
+    );
+    assert_eq!(using.receiver.as_ref().unwrap().to_string(), "T");
+    assert_eq!(
+        using.parameters[0].parameter_type.to_string(),
+        "kotlin.Function1<T, R>"
+    );
+    assert_eq!(
+        using.signature,
+        signature(
+            "use",
+            "(Ljava/io/Closeable;Lkotlin/jvm/functions/Function1;)Ljava/lang/Object;"
+        )
+    );
+}
+
+#[test]
+fn decodes_multi_file_classes() {
+    assert_eq!(
+        declarations(LAZY),
+        Declarations::MultiFileClassFacade(vec![
+            "kotlin/LazyKt__LazyJVMKt".to_string(),
+            "kotlin/LazyKt__LazyKt".to_string(),
+        ])
+    );
+    let Declarations::MultiFileClassPart { facade, package } = declarations(LAZY_JVM) else {
+        panic!("not a multi-file class part");
+    };
+    assert_eq!(facade, "kotlin/LazyKt");
+    let functions: Vec<String> = package
+        .functions
+        .iter()
+        .map(|function| function.name.clone())
+        .collect();
+    assert_eq!(functions, ["lazy", "lazy", "lazy"]);
+}
+
+#[test]
+fn decodes_7_bit_encoding() {
+    setup_logging();
+    let metadata = read_metadata(&read_class_from_path(PAIR)).unwrap().unwrap();
+    let bytes: Vec<u8> = metadata.data1[0].chars().skip(1).map(|c| c as u8).collect();
+    let legacy = Metadata {
+        data1: vec![encode_7_bit(&bytes)],
+        ..metadata.clone()
+    };
+    assert_eq!(
+        legacy.declarations().unwrap(),
+        metadata.declarations().unwrap()
+    );
+}
+
+#[test]
+fn rejects_lengths_beyond_the_end_of_the_metadata() {
+    setup_logging();
+    // The string table is preceded by the largest length that a varint holds.
+    let bytes = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    let metadata = Metadata {
+        kind: 1,
+        data1: vec![
+            std::iter::once('\u{0}')
+                .chain(bytes.iter().map(|byte| char::from(*byte)))
+                .collect(),
+        ],
+        ..Metadata::default()
+    };
+    assert!(matches!(
+        metadata.declarations(),
+        Err(ClassFileError::InvalidKotlinMetadata)
+    ));
+}
+
+#[test]
+fn prints_kotlin_declarations() {
+    setup_logging();
+    let source = print_kotlin(&read_class_from_path(PAIR), false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        source,
+        "package kotlin
+
+public data class Pair<out A, out B> : kotlin.io.Serializable {
+    public constructor(first: A, second: B)
+
+    public val first: A
+    public val second: B
+
+    public operator fun component1(): A
+    public operator fun component2(): B
+    public fun copy(first: A = ..., second: B = ...): kotlin.Pair<A, B>
+    public open operator fun equals(other: kotlin.Any?): kotlin.Boolean
+    public open fun hashCode(): kotlin.Int
+    public open fun toString(): kotlin.String
+}
+"
+    );
+
+    let source = print_kotlin(&read_class_from_path(CLOSEABLE), true)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        source,
+        "package kotlin.io
+
+// file facade CloseableKt
+internal fun java.io.Closeable?.closeFinally(cause: kotlin.Throwable?)
+public inline fun <T : java.io.Closeable?, R> T.use(block: kotlin.Function1<T, R>): R \
+         // use(Ljava/io/Closeable;Lkotlin/jvm/functions/Function1;)Ljava/lang/Object;
+"
+    );
+}
@@ -0,0 +1,95 @@
+use cafebabe::ClassFile;
+use cafebabe::attributes::{PackageFlags, RequiresFlags};
+use cafebabe::module::{Exports, Provides};
+use common::read_class_from_path;
+use common::setup_logging;
+
+mod common;
+
+#[test]
+fn reads_java11_module_info() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java11/module/module-info.class");
+    assert_eq!(class_file.version.major, 55);
+    validate_module_descriptor(&class_file);
+}
+
+#[test]
+fn reads_java17_module_info() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/module/module-info.class");
+    assert_eq!(class_file.version.major, 61);
+    validate_module_descriptor(&class_file);
+
+    // javac records the versions of required modules only when compiling for its own release.
+    let module = class_file.module_descriptor().unwrap().unwrap();
+    assert!(
+        module
+            .requires
+            .iter()
+            .all(|requires| requires.version.is_some())
+    );
+}
+
+#[test]
+fn regular_class_has_no_module_descriptor() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/EmptyClass.class");
+    assert!(!class_file.is_module());
+    assert_eq!(class_file.module_descriptor().unwrap(), None);
+}
+
+fn validate_module_descriptor(class_file: &ClassFile) {
+    assert!(class_file.is_module());
+    let module = class_file.module_descriptor().unwrap().unwrap();
+
+    assert_eq!(module.name, "examples");
+    assert_eq!(module.version, Some("1.0".to_string()));
+    assert_eq!(module.main_class, Some("examples/Main".to_string()));
+
+    let requires: Vec<(&str, RequiresFlags)> = module
+        .requires
+        .iter()
+        .map(|requires| (requires.name.as_str(), requires.flags))
+        .collect();
+    assert_eq!(
+        requires,
+        vec![
+            ("java.base", RequiresFlags::ACC_MANDATED),
+            ("java.logging", RequiresFlags::empty()),
+            ("java.sql", RequiresFlags::ACC_TRANSITIVE),
+            ("java.compiler", RequiresFlags::ACC_STATIC_PHASE),
+        ]
+    );
+
+    assert_eq!(module.exports.len(), 3);
+    assert!(module.exports.contains(&Exports {
+        package: "examples/internal".to_string(),
+        flags: PackageFlags::empty(),
+        targets: vec!["java.logging".to_string()],
+    }));
+    assert_eq!(
+        module.opens,
+        vec![Exports {
+            package: "examples/internal".to_string(),
+            flags: PackageFlags::empty(),
+            targets: vec![],
+        }]
+    );
+
+    assert_eq!(module.uses, vec!["examples/spi/Greeting".to_string()]);
+    assert_eq!(
+        module.provides,
+        vec![Provides {
+            service: "examples/spi/Greeting".to_string(),
+            implementations: vec!["examples/internal/EnglishGreeting".to_string()],
+        }]
+    );
+
+    let mut packages = module.packages.clone();
+    packages.sort();
+    assert_eq!(
+        packages,
+        vec!["examples", "examples/internal", "examples/spi"]
+    );
+}
@@ -0,0 +1,389 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use cafebabe::assembler::assemble;
+use cafebabe::attributes::Attribute;
+use cafebabe::constant_pool::{ConstantPool, ConstantPoolEntry};
+use cafebabe::dependencies::class_dependencies;
+use cafebabe::format_check::check_class_data;
+use cafebabe::hierarchy::ClassHierarchy;
+use cafebabe::remapper::{
+    InheritingRemapper, Mapping, MappingError, RemapOptions, Remapper, remap_class_data,
+    remap_class_file,
+};
+use cafebabe::{ClassFile, ParseOptions, read_class_data, write_class_data};
+use common::{class_files, setup_logging};
+
+mod common;
+
+const DIRECTORY: &str = "res/remapper/examples/remap";
+
+fn read(class_name: &str) -> Vec<u8> {
+    fs::read(format!("{DIRECTORY}/{class_name}.class")).unwrap()
+}
+
+/// Remaps a class and reads the result, checking that it is still well-formed.
+fn remap(class_name: &str, remapper: &dyn Remapper, options: &RemapOptions) -> ClassFile {
+    let data = remap_class_data(&read(class_name), remapper, options).unwrap();
+    assert_eq!(
+        check_class_data(&data, &ParseOptions::default()).unwrap(),
+        Vec::new(),
+        "{class_name}"
+    );
+    read_class_data(&data).unwrap()
+}
+
+fn shade(class_name: &str) -> Option<String> {
+    class_name
+        .strip_prefix("examples/remap/")
+        .map(|simple_name| format!("shaded/remap/{simple_name}"))
+}
+
+fn this_name(class_file: &ClassFile) -> &str {
+    class_file
+        .constant_pool
+        .class_name(class_file.class.this_idx)
+        .unwrap()
+}
+
+fn string_literals(constant_pool: &ConstantPool) -> Vec<&str> {
+    constant_pool
+        .items()
+        .filter_map(|entry| match entry {
+            ConstantPoolEntry::StringRef(value_idx) => {
+                Some(constant_pool.string_entry(*value_idx).unwrap().as_str())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn member_refs(constant_pool: &ConstantPool) -> BTreeSet<String> {
+    (1..=constant_pool.len())
+        .filter_map(|index| constant_pool.member_ref(index as usize).ok())
+        .map(|(owner, name, descriptor)| format!("{owner}.{name}:{descriptor}"))
+        .collect()
+}
+
+fn attribute_info<'a>(
+    class_file: &'a ClassFile,
+    attributes: &'a [Attribute],
+    name: &str,
+) -> &'a [u8] {
+    attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Unknown { name_idx, info }
+                if class_file.constant_pool.string_entry(*name_idx).unwrap() == name =>
+            {
+                Some(info.as_slice())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no {name} attribute"))
+}
+
+/// The String entry that the index at `offset` in the bytes of an attribute points to.
+fn utf8_at<'a>(class_file: &'a ClassFile, info: &[u8], offset: usize) -> &'a str {
+    let index = u16::from_be_bytes([info[offset], info[offset + 1]]);
+    class_file.constant_pool.string_entry(index).unwrap()
+}
+
+#[test]
+fn reads_proguard_mappings() {
+    let mapping = Mapping::from_proguard(
+        "# compiler: R8
+com.example.Library -> a.a:
+    java.lang.String name -> a
+    int[][] counts -> counts
+    1:3:void <init>() -> <init>
+    12:14:com.example.Helper helper(int,java.lang.String[]):30:32 -> b
+    15:15:void com.example.Other.inlined():40:40 -> b
+com.example.Helper -> a.b:
+",
+    )
+    .unwrap();
+    assert_eq!(
+        mapping.map_class("com/example/Library").as_deref(),
+        Some("a/a")
+    );
+    assert_eq!(
+        mapping
+            .map_field("com/example/Library", "name", "Ljava/lang/String;")
+            .as_deref(),
+        Some("a")
+    );
+    assert_eq!(
+        mapping.map_field("com/example/Library", "counts", "[[I"),
+        None
+    );
+    assert_eq!(
+        mapping
+            .map_method(
+                "com/example/Library",
+                "helper",
+                "(I[Ljava/lang/String;)Lcom/example/Helper;"
+            )
+            .as_deref(),
+        Some("b")
+    );
+    assert_eq!(
+        mapping.map_method("com/example/Library", "<init>", "()V"),
+        None
+    );
+
+    let inverse = mapping.inverse();
+    assert_eq!(
+        inverse.map_class("a/b").as_deref(),
+        Some("com/example/Helper")
+    );
+    assert_eq!(
+        inverse
+            .map_method("a/a", "b", "(I[Ljava/lang/String;)La/b;")
+            .as_deref(),
+        Some("helper")
+    );
+    assert_eq!(inverse.inverse(), mapping);
+
+    assert!(matches!(
+        Mapping::from_proguard("    int count -> a"),
+        Err(MappingError::MemberOutsideClass(1))
+    ));
+    assert!(matches!(
+        Mapping::from_proguard("a.A -> b.B:\n    count -> a"),
+        Err(MappingError::InvalidLine(2, _))
+    ));
+}
+
+#[test]
+fn shades_classes_into_another_package() {
+    setup_logging();
+    for path in class_files(std::path::Path::new(DIRECTORY)) {
+        let data = fs::read(&path).unwrap();
+        let mut class_file = read_class_data(&data).unwrap();
+        remap_class_file(&mut class_file, &shade, &RemapOptions::default()).unwrap();
+        assert!(this_name(&class_file).starts_with("shaded/remap/"));
+        let dependencies = class_dependencies(&class_file).unwrap();
+        assert!(
+            dependencies
+                .iter()
+                .all(|class_name| !class_name.starts_with("examples/")),
+            "{path:?} still depends on {dependencies:?}"
+        );
+    }
+
+    let library = remap("Library", &shade, &RemapOptions::default());
+    assert_eq!(
+        string_literals(&library.constant_pool),
+        vec!["examples.remap.Helper"]
+    );
+    let options = RemapOptions { strings: true };
+    let library = remap("Library", &shade, &options);
+    assert_eq!(
+        string_literals(&library.constant_pool),
+        vec!["shaded.remap.Helper"]
+    );
+
+    let inner = remap("Helper$Inner", &shade, &options);
+    assert_eq!(this_name(&inner), "shaded/remap/Helper$Inner");
+    let inner_classes = attribute_info(&inner, &inner.attributes, "InnerClasses");
+    assert_eq!(utf8_at(&inner, inner_classes, 6), "Inner");
+}
+
+#[test]
+fn renames_members_with_a_mapping() {
+    setup_logging();
+    let mut mapping = Mapping::new();
+    mapping.add_class("examples/remap/Helper", "examples/remap/Assistant");
+    mapping.add_class(
+        "examples/remap/Helper$Inner",
+        "examples/remap/Assistant$Nested",
+    );
+    mapping.add_method("examples/remap/Helper", "greet", "()V", "hello");
+    mapping.add_field(
+        "examples/remap/Library",
+        "items",
+        "Ljava/util/List;",
+        "elements",
+    );
+    mapping.add_field(
+        "examples/remap/Kind",
+        "SECOND",
+        "Lexamples/remap/Kind;",
+        "TWO",
+    );
+    mapping.add_field(
+        "examples/remap/Point",
+        "x",
+        "Lexamples/remap/Helper;",
+        "first",
+    );
+    mapping.add_method("java/lang/Runnable", "run", "()V", "execute");
+    let options = RemapOptions::default();
+
+    let library = remap("Library", &mapping, &options);
+    let field = &library.fields[1];
+    assert_eq!(field.name(&library.constant_pool).unwrap(), "elements");
+    assert!(
+        field.attributes.contains(&Attribute::Signature(
+            library
+                .constant_pool
+                .find_string("Ljava/util/List<Lexamples/remap/Assistant$Nested;>;")
+                .unwrap()
+        ))
+    );
+    let references = member_refs(&library.constant_pool);
+    assert!(references.contains("examples/remap/Assistant.hello:()V"));
+    assert!(references.contains("examples/remap/Assistant.<init>:()V"));
+    assert!(
+        !references
+            .iter()
+            .any(|reference| reference.contains("Helper"))
+    );
+    let call_site_names: Vec<String> = (1..=library.constant_pool.len())
+        .filter(|index| {
+            matches!(
+                library.constant_pool.get_entry(*index),
+                ConstantPoolEntry::InvokeDynamic(_)
+            )
+        })
+        .map(|index| library.call_site(index).unwrap().name)
+        .collect();
+    assert_eq!(call_site_names, vec!["execute", "get"]);
+
+    // @Marker(kind = Kind.SECOND, type = Helper.class)
+    let annotations = attribute_info(&library, &library.attributes, "RuntimeVisibleAnnotations");
+    assert_eq!(utf8_at(&library, annotations, 2), "Lexamples/remap/Marker;");
+    assert_eq!(annotations[8], b'e');
+    assert_eq!(utf8_at(&library, annotations, 9), "Lexamples/remap/Kind;");
+    assert_eq!(utf8_at(&library, annotations, 11), "TWO");
+    assert_eq!(annotations[15], b'c');
+    assert_eq!(
+        utf8_at(&library, annotations, 16),
+        "Lexamples/remap/Assistant;"
+    );
+
+    let anonymous = remap("Library$1", &mapping, &options);
+    assert!(
+        member_refs(&anonymous.constant_pool)
+            .contains("examples/remap/Library.elements:Ljava/util/List;")
+    );
+
+    let helper = remap("Helper", &mapping, &options);
+    assert_eq!(this_name(&helper), "examples/remap/Assistant");
+    let names: Vec<&String> = helper
+        .methods
+        .iter()
+        .map(|method| method.name(&helper.constant_pool).unwrap())
+        .collect();
+    assert_eq!(names, vec!["<init>", "hello"]);
+    let inner_classes = attribute_info(&helper, &helper.attributes, "InnerClasses");
+    assert_eq!(utf8_at(&helper, inner_classes, 6), "Nested");
+
+    let kind = remap("Kind", &mapping, &options);
+    assert!(
+        kind.fields
+            .iter()
+            .any(|field| field.name(&kind.constant_pool).unwrap() == "TWO")
+    );
+
+    // record Point(Helper x, Helper.Inner y)
+    let point = remap("Point", &mapping, &options);
+    let record = attribute_info(&point, &point.attributes, "Record");
+    assert_eq!(utf8_at(&point, record, 2), "first");
+    assert_eq!(utf8_at(&point, record, 4), "Lexamples/remap/Assistant;");
+}
+
+#[test]
+fn renames_nested_classes_in_signatures() {
+    setup_logging();
+    let source = r#"
+.class public p/Holder
+.super java/lang/Object
+.field public value Lp/Outer$Inner;
+    .signature "Lp/Outer<TT;>.Inner<Ljava/lang/String;>;"
+.end field
+"#;
+    let data = write_class_data(&assemble(source).unwrap()).unwrap();
+    let signature = |mapping: &Mapping| {
+        let remapped = remap_class_data(&data, mapping, &RemapOptions::default()).unwrap();
+        let class_file = read_class_data(&remapped).unwrap();
+        class_file.fields[0]
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Signature(index) => Some(
+                    class_file
+                        .constant_pool
+                        .string_entry(*index)
+                        .unwrap()
+                        .clone(),
+                ),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let mut mapping = Mapping::new();
+    mapping.add_class("p/Outer", "q/Outer");
+    mapping.add_class("p/Outer$Inner", "q/Outer$Nested");
+    assert_eq!(
+        signature(&mapping),
+        "Lq/Outer<TT;>.Nested<Ljava/lang/String;>;"
+    );
+
+    // An inner class that moves out of its outer class leaves the type arguments of the outer class behind.
+    let mut mapping = Mapping::new();
+    mapping.add_class("p/Outer$Inner", "q/Top");
+    assert_eq!(signature(&mapping), "Lq/Top<Ljava/lang/String;>;");
+}
+
+#[test]
+fn renames_inherited_members_through_the_hierarchy() {
+    setup_logging();
+    let mapping = Mapping::from_proguard(
+        "examples.remap.Base -> examples.remap.Base:
+    int count -> c
+    6:7:void reset():7:8 -> r
+examples.remap.Greeter -> examples.remap.Greeter:
+    5:5:java.lang.String greeting() -> g
+",
+    )
+    .unwrap();
+    let options = RemapOptions::default();
+
+    // The client calls the inherited members on the subclass, which the mapping doesn't name.
+    let client = remap("Client", &mapping, &options);
+    assert!(member_refs(&client.constant_pool).contains("examples/remap/Derived.reset:()V"));
+
+    let mut hierarchy = ClassHierarchy::new();
+    for class_name in ["Base", "Derived", "Greeter"] {
+        let class_file = read_class_data(&read(class_name)).unwrap();
+        hierarchy.add_class(&class_file).unwrap();
+    }
+    let remapper = InheritingRemapper::new(&mapping, &hierarchy);
+    let client = remap("Client", &remapper, &options);
+    let references = member_refs(&client.constant_pool);
+    for reference in [
+        "examples/remap/Derived.r:()V",
+        "examples/remap/Derived.c:I",
+        "examples/remap/Derived.g:()Ljava/lang/String;",
+        "java/lang/Object.<init>:()V",
+    ] {
+        assert!(references.contains(reference), "{reference}");
+    }
+    assert!(
+        !references
+            .iter()
+            .any(|reference| reference.contains("reset") || reference.contains("greeting"))
+    );
+
+    let base = remap("Base", &remapper, &options);
+    let names: Vec<&String> = base
+        .methods
+        .iter()
+        .map(|method| method.name(&base.constant_pool).unwrap())
+        .collect();
+    assert_eq!(names, vec!["<init>", "r"]);
+    assert_eq!(base.fields[0].name(&base.constant_pool).unwrap(), "c");
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "serde")]
+
+use cafebabe::ClassFile;
+use common::read_class_from_path;
+use common::setup_logging;
+use serde_json::{Value, json};
+
+mod common;
+
+#[test]
+fn serializes_java8_empty_class() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java8/examples/EmptyClass.class");
+    validate_empty_class(&class_file, 52);
+}
+
+#[test]
+fn serializes_java11_empty_class() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java11/examples/EmptyClass.class");
+    validate_empty_class(&class_file, 55);
+}
+
+#[test]
+fn serializes_java17_empty_class() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/EmptyClass.class");
+    validate_empty_class(&class_file, 61);
+}
+
+#[test]
+fn serializes_opcodes_as_mnemonics() {
+    setup_logging();
+    let class_file = read_class_from_path("res/java17/examples/ClassWithCode.class");
+    let code = class_file.methods[0].code().unwrap();
+    let instructions = code.instructions().unwrap();
+    let value = serde_json::to_value(&instructions[0]).unwrap();
+    assert_eq!(value["opcode"], json!("aload_0"));
+    assert_eq!(value["offset"], json!(0));
+}
+
+fn validate_empty_class(class_file: &ClassFile, major_version: u64) {
+    let value = serde_json::to_value(class_file).unwrap();
+    assert_eq!(value["version"]["major"], json!(major_version));
+    assert_eq!(value["access_flags"], json!("ACC_PUBLIC | ACC_SUPER"));
+
+    // The constant pool is a list of entries, where the first one has index 1.
+    let constant_pool = value["constant_pool"].as_array().unwrap();
+    assert_eq!(constant_pool.len(), class_file.constant_pool.len() as usize);
+    let this_idx = value["class"]["this_idx"].as_u64().unwrap() as usize;
+    let Value::Object(class_ref) = &constant_pool[this_idx - 1] else {
+        panic!("Expected an object for the this class entry");
+    };
+    let name_idx = class_ref["ClassRef"].as_u64().unwrap() as usize;
+    assert_eq!(
+        constant_pool[name_idx - 1],
+        json!({"String": "examples/EmptyClass"})
+    );
+
+    assert_eq!(value["fields"], json!([]));
+    assert_eq!(value["methods"][0]["access_flags"], json!("ACC_PUBLIC"));
+}
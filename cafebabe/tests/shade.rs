@@ -0,0 +1,196 @@
+use std::fs;
+
+use cafebabe::archive::{Compression, JarFile, JarWriter};
+use cafebabe::dependencies::class_dependencies;
+use cafebabe::remapper::RemapOptions;
+use cafebabe::shade::{Relocation, shade_jar};
+use common::setup_logging;
+
+mod common;
+
+const DIRECTORY: &str = "res/remapper/examples/remap";
+const CLASSES: [&str; 7] = [
+    "Helper",
+    "Helper$Inner",
+    "Kind",
+    "Library",
+    "Library$1",
+    "Marker",
+    "Point",
+];
+
+/// A jar with the classes of the remapper examples and the kinds of resources that shading has to take care of.
+fn example_jar() -> JarFile {
+    let mut writer = JarWriter::new();
+    let mut add = |name: &str, data: &[u8]| writer.add(name, data, Compression::Deflated).unwrap();
+    add("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\r\n\r\n");
+    add("META-INF/SIGNER.SF", b"Signature-Version: 1.0\r\n\r\n");
+    add("META-INF/SIGNER.RSA", b"");
+    add(
+        "META-INF/services/examples.remap.Marker",
+        b"# the markers\nexamples.remap.Library # the default\n",
+    );
+    add(
+        "META-INF/services/shaded.remap.Marker",
+        b"shaded.remap.Other",
+    );
+    add(
+        "META-INF/services/java.lang.Runnable",
+        b"examples.remap.Helper\n",
+    );
+    add("examples/", b"");
+    add("examples/remap/", b"");
+    for class_name in CLASSES {
+        let data = fs::read(format!("{DIRECTORY}/{class_name}.class")).unwrap();
+        add(&format!("examples/remap/{class_name}.class"), &data);
+    }
+    add("examples/remap/messages.properties", b"greeting=hello\n");
+    add("examples/remapped.txt", b"not in the package\n");
+    let data = fs::read(format!("{DIRECTORY}/Helper.class")).unwrap();
+    add("META-INF/versions/11/examples/remap/Helper.class", &data);
+    // A class that was shaded before, which is left out in favour of the one that is relocated there.
+    let data = fs::read(format!("{DIRECTORY}/Kind.class")).unwrap();
+    add("shaded/remap/Kind.class", &data);
+    JarFile::from_bytes(writer.finish().unwrap()).unwrap()
+}
+
+fn entry_names(jar_file: &JarFile) -> Vec<&str> {
+    jar_file
+        .entries()
+        .iter()
+        .map(|entry| entry.name.as_str())
+        .collect()
+}
+
+#[test]
+fn parses_relocations() {
+    let relocation = Relocation::new("com.google.common", "acme/shaded/guava/");
+    assert_eq!(relocation.from, "com/google/common");
+    assert_eq!(relocation.to, "acme/shaded/guava");
+    assert_eq!(
+        relocation
+            .relocate("com/google/common/collect/Lists")
+            .as_deref(),
+        Some("acme/shaded/guava/collect/Lists")
+    );
+    assert_eq!(
+        relocation.relocate("com/google/common").as_deref(),
+        Some("acme/shaded/guava")
+    );
+    assert_eq!(relocation.relocate("com/google/commonx/Lists"), None);
+}
+
+#[test]
+fn relocates_classes_resources_and_services() {
+    setup_logging();
+    let relocations = [Relocation::new("examples.remap", "shaded.remap")];
+    let options = RemapOptions { strings: true };
+    let (data, report) = shade_jar(&example_jar(), &relocations, &options).unwrap();
+    let shaded = JarFile::from_bytes(data).unwrap();
+
+    assert_eq!(
+        entry_names(&shaded),
+        vec![
+            "META-INF/MANIFEST.MF",
+            "META-INF/services/shaded.remap.Marker",
+            "META-INF/services/java.lang.Runnable",
+            "examples/",
+            "shaded/remap/",
+            "shaded/remap/Helper.class",
+            "shaded/remap/Helper$Inner.class",
+            "shaded/remap/Kind.class",
+            "shaded/remap/Library.class",
+            "shaded/remap/Library$1.class",
+            "shaded/remap/Marker.class",
+            "shaded/remap/Point.class",
+            "shaded/remap/messages.properties",
+            "examples/remapped.txt",
+            "META-INF/versions/11/shaded/remap/Helper.class",
+        ]
+    );
+    assert_eq!(
+        report.removed_signatures,
+        vec!["META-INF/SIGNER.SF", "META-INF/SIGNER.RSA"]
+    );
+    assert_eq!(
+        report.merged_services,
+        vec!["META-INF/services/shaded.remap.Marker"]
+    );
+    assert_eq!(report.duplicate_entries, vec!["shaded/remap/Kind.class"]);
+    assert_eq!(report.relocated_classes.len(), 8);
+    assert_eq!(
+        report.relocated_resources,
+        vec![
+            "META-INF/services/examples.remap.Marker",
+            "examples/remap/",
+            "examples/remap/messages.properties",
+        ]
+    );
+
+    let services = shaded
+        .read_entry("META-INF/services/shaded.remap.Marker")
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(services).unwrap(),
+        "# the markers\nshaded.remap.Library # the default\nshaded.remap.Other\n"
+    );
+    let services = shaded
+        .read_entry("META-INF/services/java.lang.Runnable")
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(services).unwrap(),
+        "shaded.remap.Helper\n"
+    );
+
+    for (class_name, class_file) in shaded.classes() {
+        let class_file = class_file.unwrap();
+        // Versioned classes are named after the class they override.
+        let expected_name = class_name
+            .strip_prefix("META-INF/versions/")
+            .and_then(|name| name.split_once('/'))
+            .map_or(class_name, |(_, name)| name);
+        assert_eq!(
+            class_file
+                .constant_pool
+                .class_name(class_file.class.this_idx)
+                .unwrap(),
+            expected_name
+        );
+        let dependencies = class_dependencies(&class_file).unwrap();
+        assert!(
+            dependencies
+                .iter()
+                .all(|dependency| !dependency.starts_with("examples/")),
+            "{class_name} depends on {dependencies:?}"
+        );
+    }
+}
+
+#[test]
+fn relocates_module_packages() {
+    setup_logging();
+    let mut writer = JarWriter::new();
+    let data = fs::read("res/java17/module/module-info.class").unwrap();
+    writer
+        .add("module-info.class", &data, Compression::Deflated)
+        .unwrap();
+    let jar_file = JarFile::from_bytes(writer.finish().unwrap()).unwrap();
+    let original = jar_file.read_class("module-info").unwrap();
+    let packages = original.module_descriptor().unwrap().unwrap().packages;
+    let Some(package) = packages.first() else {
+        panic!("the module has no packages");
+    };
+
+    let relocations = [Relocation::new(package, "shaded")];
+    let (data, report) = shade_jar(&jar_file, &relocations, &RemapOptions::default()).unwrap();
+    assert!(report.relocated_classes.is_empty());
+    let shaded = JarFile::from_bytes(data).unwrap();
+    let module = shaded
+        .read_class("module-info")
+        .unwrap()
+        .module_descriptor()
+        .unwrap()
+        .unwrap();
+    assert!(module.packages.contains(&"shaded".to_string()));
+    assert!(!module.packages.contains(package));
+}
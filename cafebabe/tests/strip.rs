@@ -0,0 +1,332 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use cafebabe::archive::JarFile;
+use cafebabe::attributes::Attribute;
+use cafebabe::bytecode::Operand;
+use cafebabe::constant_pool::{ConstantPool, ConstantPoolEntry};
+use cafebabe::dependencies::class_dependencies;
+use cafebabe::format_check::check_class_data;
+use cafebabe::strip::{StripOptions, shrink_constant_pool, strip_debug_info, strip_jar};
+use cafebabe::{ClassFile, ParseOptions, read_class_data, write_class_data};
+use common::{class_files, setup_logging};
+
+mod common;
+
+const CLASS_WITH_CODE: &str = "res/java17/examples/ClassWithCode.class";
+
+/// The content of a constant pool entry, with the entries it points to described in its place.
+fn describe(constant_pool: &ConstantPool, index: u16) -> String {
+    let describe = |index: u16| describe(constant_pool, index);
+    match constant_pool.get_entry(index) {
+        ConstantPoolEntry::ClassRef(index)
+        | ConstantPoolEntry::StringRef(index)
+        | ConstantPoolEntry::MethodType(index)
+        | ConstantPoolEntry::Module(index)
+        | ConstantPoolEntry::Package(index) => format!("({})", describe(*index)),
+        ConstantPoolEntry::FieldRef(field_ref) => format!(
+            "{}.{}",
+            describe(field_ref.class_ref),
+            describe(field_ref.name_type_ref)
+        ),
+        ConstantPoolEntry::MethodRef(method_ref)
+        | ConstantPoolEntry::InterfaceMethodRef(method_ref) => format!(
+            "{}.{}",
+            describe(method_ref.class_ref),
+            describe(method_ref.name_type_ref)
+        ),
+        ConstantPoolEntry::NameTypeDescriptor(name_type) => format!(
+            "{}:{}",
+            describe(name_type.name_ref),
+            describe(name_type.type_descriptor_ref)
+        ),
+        ConstantPoolEntry::MethodHandle(method_handle) => format!(
+            "{:?} {}",
+            method_handle.reference_kind,
+            describe(method_handle.reference_ref)
+        ),
+        ConstantPoolEntry::Dynamic(dynamic_ref) | ConstantPoolEntry::InvokeDynamic(dynamic_ref) => {
+            format!(
+                "#{} {}",
+                dynamic_ref.bootstrap_method_attr_idx,
+                describe(dynamic_ref.name_type_ref)
+            )
+        }
+        entry => format!("{entry:?}"),
+    }
+}
+
+fn describe_attributes(class_file: &ClassFile, attributes: &[Attribute], out: &mut Vec<String>) {
+    let constant_pool = &class_file.constant_pool;
+    for attribute in attributes {
+        match attribute {
+            Attribute::Code(code) => {
+                for instruction in code.instructions().unwrap() {
+                    let operand = match instruction.operand {
+                        Operand::ConstantPool(index)
+                        | Operand::InvokeInterface { index, .. }
+                        | Operand::MultiANewArray { index, .. } => describe(constant_pool, index),
+                        operand => format!("{operand:?}"),
+                    };
+                    out.push(format!("{} {operand}", instruction.opcode.mnemonic()));
+                }
+                for handler in &code.exception_table {
+                    match handler.catch_type {
+                        0 => out.push("catch any".to_string()),
+                        index => out.push(format!("catch {}", describe(constant_pool, index))),
+                    }
+                }
+                describe_attributes(class_file, &code.attributes, out);
+            }
+            Attribute::Unknown { name_idx, info } => out.push(format!(
+                "{} of {} bytes",
+                constant_pool.string_entry(*name_idx).unwrap(),
+                info.len()
+            )),
+            Attribute::ConstantValue(index) | Attribute::Signature(index) => {
+                out.push(describe(constant_pool, *index))
+            }
+            Attribute::LocalVariableTable(local_variables) => {
+                for local_variable in local_variables {
+                    out.push(format!(
+                        "local {} {}",
+                        describe(constant_pool, local_variable.name_idx),
+                        describe(constant_pool, local_variable.descriptor_idx)
+                    ));
+                }
+            }
+            Attribute::Exceptions(indices) => {
+                for index in indices {
+                    out.push(format!("throws {}", describe(constant_pool, *index)));
+                }
+            }
+            Attribute::BootstrapMethods(bootstrap_methods) => {
+                for bootstrap_method in bootstrap_methods {
+                    out.push(describe(constant_pool, bootstrap_method.method_ref));
+                    for argument in &bootstrap_method.arguments {
+                        out.push(describe(constant_pool, *argument));
+                    }
+                }
+            }
+            Attribute::MethodParameters(parameters) => {
+                for parameter in parameters {
+                    match parameter.name_idx {
+                        0 => out.push("parameter".to_string()),
+                        index => out.push(format!("parameter {}", describe(constant_pool, index))),
+                    }
+                }
+            }
+            // The module descriptor describes these.
+            Attribute::Module(_) | Attribute::ModulePackages(_) | Attribute::ModuleMainClass(_) => {
+            }
+            // The others hold no indices that matter to the classes in the examples.
+            attribute => out.push(format!("{attribute:?}").chars().take(20).collect()),
+        }
+    }
+}
+
+/// The classes, members, instructions and attributes of a class file, with the constant pool entries they point to
+/// described by their content, which doesn't change when the constant pool is renumbered.
+fn describe_class(class_file: &ClassFile) -> Vec<String> {
+    let constant_pool = &class_file.constant_pool;
+    let mut out = vec![
+        class_file.this_class_name().unwrap().clone(),
+        format!("{:?}", class_file.super_class_name().unwrap()),
+        format!("{:?}", class_file.interface_names().unwrap()),
+    ];
+    for field in &class_file.fields {
+        out.push(describe(constant_pool, field.name_idx));
+        out.push(describe(constant_pool, field.descriptor_idx));
+        describe_attributes(class_file, &field.attributes, &mut out);
+    }
+    for method in &class_file.methods {
+        out.push(describe(constant_pool, method.name_idx));
+        out.push(describe(constant_pool, method.descriptor_idx));
+        describe_attributes(class_file, &method.attributes, &mut out);
+    }
+    describe_attributes(class_file, &class_file.attributes, &mut out);
+    out.push(format!("{:?}", class_file.module_descriptor().unwrap()));
+    out
+}
+
+fn strings(constant_pool: &ConstantPool) -> BTreeSet<&str> {
+    constant_pool
+        .items()
+        .filter_map(|entry| match entry {
+            ConstantPoolEntry::String(value) => Some(value.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn code_attributes(class_file: &ClassFile) -> Vec<&Attribute> {
+    class_file
+        .methods
+        .iter()
+        .flat_map(|method| &method.attributes)
+        .flat_map(|attribute| match attribute {
+            Attribute::Code(code) => code.attributes.iter().collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+#[test]
+fn strips_debug_attributes_and_their_constants() {
+    setup_logging();
+    let data = fs::read(CLASS_WITH_CODE).unwrap();
+    let mut class_file = read_class_data(&data).unwrap();
+    let pool_size = class_file.constant_pool.len();
+    assert!(class_file.source_file().unwrap().is_some());
+
+    let removed = strip_debug_info(&mut class_file, &StripOptions::all());
+    assert!(removed > 0);
+    assert_eq!(class_file.source_file().unwrap(), None);
+    assert!(code_attributes(&class_file).iter().all(|attribute| {
+        !matches!(
+            attribute,
+            Attribute::LineNumberTable(_) | Attribute::LocalVariableTable(_)
+        )
+    }));
+    let expected = describe_class(&class_file);
+
+    let removed = shrink_constant_pool(&mut class_file).unwrap().unwrap();
+    assert!(removed > 0);
+    assert_eq!(class_file.constant_pool.len(), pool_size - removed as u16);
+    let strings = strings(&class_file.constant_pool);
+    for name in [
+        "LineNumberTable",
+        "LocalVariableTable",
+        "SourceFile",
+        "ClassWithCode.java",
+        "this",
+    ] {
+        assert!(!strings.contains(name), "{name}");
+    }
+    assert!(strings.contains("Code"));
+
+    let data = write_class_data(&class_file).unwrap();
+    assert_eq!(
+        check_class_data(&data, &ParseOptions::default()).unwrap(),
+        Vec::new()
+    );
+    assert_eq!(describe_class(&read_class_data(&data).unwrap()), expected);
+    // Shrinking again finds nothing left to remove.
+    let mut class_file = read_class_data(&data).unwrap();
+    assert_eq!(shrink_constant_pool(&mut class_file).unwrap(), Some(0));
+    assert_eq!(write_class_data(&class_file).unwrap(), data);
+}
+
+#[test]
+fn keeps_the_attributes_that_are_not_selected() {
+    setup_logging();
+    let mut class_file = read_class_data(&fs::read(CLASS_WITH_CODE).unwrap()).unwrap();
+    let options = StripOptions {
+        line_numbers: true,
+        ..StripOptions::default()
+    };
+    strip_debug_info(&mut class_file, &options);
+    shrink_constant_pool(&mut class_file).unwrap().unwrap();
+
+    let class_file = read_class_data(&write_class_data(&class_file).unwrap()).unwrap();
+    assert!(class_file.source_file().unwrap().is_some());
+    let attributes = code_attributes(&class_file);
+    assert!(
+        !attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::LineNumberTable(_)))
+    );
+    let local_variables = attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::LocalVariableTable(local_variables) => Some(local_variables),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        class_file
+            .constant_pool
+            .string_entry(local_variables[0].name_idx)
+            .unwrap(),
+        "this"
+    );
+}
+
+#[test]
+fn shrinks_constant_pools_without_changing_classes() {
+    setup_logging();
+    let options = ParseOptions {
+        allow_preview: true,
+        ..ParseOptions::default()
+    };
+    let directories = [
+        "res/java8",
+        "res/java11",
+        "res/java17",
+        "res/java21",
+        "res/java25",
+        "res/remapper",
+    ];
+    for path in directories
+        .iter()
+        .flat_map(|directory| class_files(Path::new(directory)))
+    {
+        let data = fs::read(&path).unwrap();
+        let mut class_file = cafebabe::read_class_data_with_options(&data, &options).unwrap();
+        strip_debug_info(&mut class_file, &StripOptions::all());
+        let expected = describe_class(&class_file);
+        let dependencies = class_dependencies(&class_file).unwrap();
+        let diagnostics = check_class_data(&data, &options).unwrap().len();
+
+        assert!(
+            shrink_constant_pool(&mut class_file).unwrap().is_some(),
+            "{path:?}"
+        );
+        let data = write_class_data(&class_file).unwrap();
+        let class_file = cafebabe::read_class_data_with_options(&data, &options).unwrap();
+        assert_eq!(describe_class(&class_file), expected, "{path:?}");
+        assert_eq!(
+            class_dependencies(&class_file).unwrap(),
+            dependencies,
+            "{path:?}"
+        );
+        assert!(
+            check_class_data(&data, &options).unwrap().len() <= diagnostics,
+            "{path:?}"
+        );
+    }
+}
+
+#[test]
+fn strips_the_classes_of_jars() {
+    setup_logging();
+    let jar_file = JarFile::open("res/jars/examples.jar").unwrap();
+    let (data, report) = strip_jar(&jar_file, &StripOptions::all()).unwrap();
+    let stripped = JarFile::from_bytes(data).unwrap();
+
+    let names = |jar_file: &JarFile| {
+        jar_file
+            .entries()
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&stripped), names(&jar_file));
+    assert_eq!(report.classes, jar_file.class_names().count());
+    assert!(report.removed_attributes > 0);
+    assert!(report.removed_constants > 0);
+    assert!(report.kept_constant_pools.is_empty());
+    assert!(report.stripped_size < report.original_size);
+    assert_eq!(
+        stripped.read_entry("META-INF/MANIFEST.MF").unwrap(),
+        jar_file.read_entry("META-INF/MANIFEST.MF").unwrap()
+    );
+    for (class_name, class_file) in stripped.classes() {
+        assert_eq!(
+            class_file.unwrap().source_file().unwrap(),
+            None,
+            "{class_name}"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs;
+
+use cafebabe::{
+    ClassFileError, JavaRelease, ParseOptions, Strictness, read_class_data,
+    read_class_data_with_options,
+};
+use common::setup_logging;
+
+mod common;
+
+/// The bytes of a class file compiled for Java 17, with another version.
+fn with_version(major: u16, minor: u16) -> Vec<u8> {
+    let mut data = fs::read("res/java17/examples/EmptyClass.class").unwrap();
+    data[4..6].copy_from_slice(&minor.to_be_bytes());
+    data[6..8].copy_from_slice(&major.to_be_bytes());
+    data
+}
+
+#[test]
+fn maps_major_versions_to_releases() {
+    assert_eq!(JavaRelease::from_major(45), Some(JavaRelease::Java1_1));
+    assert_eq!(JavaRelease::from_major(49), Some(JavaRelease::Java5));
+    assert_eq!(JavaRelease::from_major(61), Some(JavaRelease::Java17));
+    assert_eq!(JavaRelease::from_major(44), None);
+    assert_eq!(JavaRelease::from_major(70), None);
+
+    assert_eq!(JavaRelease::Java1_4.name(), "1.4");
+    assert_eq!(JavaRelease::Java5.to_string(), "Java 5.0");
+    assert_eq!(JavaRelease::Java21.to_string(), "Java 21");
+    assert_eq!(JavaRelease::Java8.major(), 52);
+    assert_eq!(JavaRelease::LATEST.major(), 69);
+    assert!(JavaRelease::Java11 < JavaRelease::Java17);
+}
+
+#[test]
+fn reads_the_release_of_class_files() {
+    setup_logging();
+    for (directory, release) in [
+        ("java8", JavaRelease::Java8),
+        ("java11", JavaRelease::Java11),
+        ("java17", JavaRelease::Java17),
+        ("java21", JavaRelease::Java21),
+        ("java25", JavaRelease::Java25),
+    ] {
+        let data = fs::read(format!("res/{directory}/examples/EmptyClass.class")).unwrap();
+        let class_file = read_class_data(&data).unwrap();
+        assert_eq!(class_file.release(), Some(release));
+        assert!(!class_file.is_preview());
+    }
+}
+
+#[test]
+fn accepts_preview_class_files_only_when_allowed() {
+    setup_logging();
+    let data = with_version(61, 65535);
+    assert!(matches!(
+        read_class_data(&data),
+        Err(ClassFileError::PreviewNotAllowed)
+    ));
+
+    let options = ParseOptions {
+        allow_preview: true,
+        ..ParseOptions::default()
+    };
+    let class_file = read_class_data_with_options(&data, &options).unwrap();
+    assert!(class_file.is_preview());
+    assert_eq!(class_file.release(), Some(JavaRelease::Java17));
+
+    let strict = ParseOptions {
+        strictness: Strictness::Strict,
+        ..options.clone()
+    };
+    assert!(matches!(
+        read_class_data_with_options(&data, &strict),
+        Err(ClassFileError::PreviewNotAllowed)
+    ));
+    let strict_for_java17 = ParseOptions {
+        max_major_version: 61,
+        ..strict
+    };
+    assert!(read_class_data_with_options(&data, &strict_for_java17).is_ok());
+
+    let before_previews = read_class_data(&with_version(52, 65535)).unwrap();
+    assert!(!before_previews.is_preview());
+}
+
+#[test]
+fn checks_the_major_and_minor_version() {
+    setup_logging();
+    assert!(matches!(
+        read_class_data(&with_version(70, 0)),
+        Err(ClassFileError::MajorVersionTooHigh)
+    ));
+    assert!(matches!(
+        read_class_data(&with_version(44, 0)),
+        Err(ClassFileError::MajorVersionTooLow)
+    ));
+    assert!(matches!(
+        read_class_data(&with_version(61, 3)),
+        Err(ClassFileError::InvalidMinorVersion)
+    ));
+    assert!(read_class_data(&with_version(52, 3)).is_ok());
+
+    let up_to_java11 = ParseOptions {
+        max_major_version: JavaRelease::Java11.major(),
+        ..ParseOptions::default()
+    };
+    assert!(matches!(
+        read_class_data_with_options(&with_version(61, 0), &up_to_java11),
+        Err(ClassFileError::MajorVersionTooHigh)
+    ));
+
+    let lenient = ParseOptions {
+        strictness: Strictness::Lenient,
+        ..up_to_java11
+    };
+    let class_file = read_class_data_with_options(&with_version(70, 3), &lenient).unwrap();
+    assert_eq!(class_file.version.major, 70);
+    assert_eq!(class_file.release(), None);
+    assert!(matches!(
+        read_class_data_with_options(&with_version(44, 0), &lenient),
+        Err(ClassFileError::MajorVersionTooLow)
+    ));
+}
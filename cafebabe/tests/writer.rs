@@ -0,0 +1,82 @@
+use std::fs;
+use std::path::Path;
+
+use cafebabe::bytecode::encode;
+use cafebabe::{read_class_data, write_class_data};
+use common::{class_files, setup_logging};
+
+mod common;
+
+#[test]
+fn writes_java8_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java8");
+}
+
+#[test]
+fn writes_java11_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java11");
+}
+
+#[test]
+fn writes_java17_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java17");
+}
+
+#[test]
+fn writes_java21_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java21");
+}
+
+#[test]
+fn writes_java25_classes_unchanged() {
+    setup_logging();
+    validate_round_trips("res/java25");
+}
+
+#[test]
+fn reads_and_writes_modified_utf8() {
+    setup_logging();
+    let data = fs::read("res/java17/strings/examples/ClassWithStrings.class").unwrap();
+    let class_file = read_class_data(&data).unwrap();
+    // The unpaired surrogate is replaced when read, but written back as it was.
+    for value in [
+        "a\u{0}b",
+        "smile \u{1f600}",
+        "caf\u{e9} \u{20ac}",
+        "low \u{fffd} end",
+    ] {
+        assert!(
+            class_file.constant_pool.find_string(value).is_some(),
+            "{value:?}"
+        );
+    }
+    assert_eq!(write_class_data(&class_file).unwrap(), data);
+}
+
+fn validate_round_trips(directory: &str) {
+    let mut class_count = 0;
+    for path in class_files(Path::new(directory)) {
+        let data = fs::read(&path).unwrap();
+        let class_file = read_class_data(&data).unwrap();
+        let written = write_class_data(&class_file).unwrap();
+        assert!(written == data, "Expect {path:?} to be written unchanged");
+
+        for method in &class_file.methods {
+            let Some(code) = method.code() else {
+                continue;
+            };
+            let instructions = code.instructions().unwrap();
+            for instruction in &instructions {
+                let end = instruction.offset as usize + instruction.length();
+                assert!(end <= code.code.len());
+            }
+            assert_eq!(encode(&instructions).unwrap(), code.code);
+        }
+        class_count += 1;
+    }
+    assert!(class_count > 0, "Expect class files in {directory}");
+}
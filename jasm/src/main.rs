@@ -0,0 +1,74 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use cafebabe::assembler::assemble;
+use cafebabe::write_class_data;
+
+const USAGE: &str = "Usage: jasm <options> <source files>
+where possible options include:
+  -d <directory>  Specify where to place the class files";
+
+/// Assembles a source file and writes the class file below `directory`, in the path of its class name.
+fn assemble_file(path: &str, directory: &Path) -> Result<PathBuf, String> {
+    let source = fs::read_to_string(path).map_err(|error| format!("Can't read {path}: {error}"))?;
+    let class_file = assemble(&source).map_err(|error| format!("{path}: {error}"))?;
+    let class_name = class_file
+        .constant_pool
+        .class_ref_entry(class_file.class.this_idx)
+        .and_then(|name_idx| class_file.constant_pool.string_entry(name_idx))
+        .map_err(|error| format!("Can't find the class name of {path}: {error}"))?;
+    let data =
+        write_class_data(&class_file).map_err(|error| format!("Can't write {path}: {error}"))?;
+
+    let output = directory.join(format!("{class_name}.class"));
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Can't create {}: {error}", parent.display()))?;
+    }
+    fs::write(&output, data)
+        .map_err(|error| format!("Can't write {}: {error}", output.display()))?;
+    Ok(output)
+}
+
+fn main() -> ExitCode {
+    let mut directory = PathBuf::from(".");
+    let mut paths = Vec::new();
+
+    let mut arguments = env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "-d" => match arguments.next() {
+                Some(value) => directory = PathBuf::from(value),
+                None => {
+                    eprintln!("Missing directory for -d\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "-h" | "-help" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown option: {flag}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    let mut exit_code = ExitCode::SUCCESS;
+    for path in &paths {
+        if let Err(message) = assemble_file(path, &directory) {
+            eprintln!("Error: {message}");
+            exit_code = ExitCode::FAILURE;
+        }
+    }
+    exit_code
+}
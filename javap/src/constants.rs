@@ -0,0 +1,230 @@
+use cafebabe::ClassFileError;
+use cafebabe::constant_pool::ConstantPool;
+use cafebabe::constant_pool::types::{ConstantPoolEntry, ReferenceKind};
+
+/// The name of the kind of a constant pool entry, as `javap` prints it.
+pub fn entry_kind(entry: &ConstantPoolEntry) -> &'static str {
+    match entry {
+        ConstantPoolEntry::String(_) => "Utf8",
+        ConstantPoolEntry::Integer(_) => "Integer",
+        ConstantPoolEntry::Float(_) => "Float",
+        ConstantPoolEntry::Long(_) => "Long",
+        ConstantPoolEntry::Double(_) => "Double",
+        ConstantPoolEntry::ClassRef(_) => "Class",
+        ConstantPoolEntry::StringRef(_) => "String",
+        ConstantPoolEntry::FieldRef(_) => "Fieldref",
+        ConstantPoolEntry::MethodRef(_) => "Methodref",
+        ConstantPoolEntry::InterfaceMethodRef(_) => "InterfaceMethodref",
+        ConstantPoolEntry::NameTypeDescriptor(_) => "NameAndType",
+        ConstantPoolEntry::MethodHandle(_) => "MethodHandle",
+        ConstantPoolEntry::MethodType(_) => "MethodType",
+        ConstantPoolEntry::Dynamic(_) => "Dynamic",
+        ConstantPoolEntry::InvokeDynamic(_) => "InvokeDynamic",
+        ConstantPoolEntry::Module(_) => "Module",
+        ConstantPoolEntry::Package(_) => "Package",
+        ConstantPoolEntry::Empty() => "",
+    }
+}
+
+pub fn reference_kind_name(reference_kind: ReferenceKind) -> &'static str {
+    match reference_kind {
+        ReferenceKind::GetField => "REF_getField",
+        ReferenceKind::GetStatic => "REF_getStatic",
+        ReferenceKind::PutField => "REF_putField",
+        ReferenceKind::PutStatic => "REF_putStatic",
+        ReferenceKind::InvokeVirtual => "REF_invokeVirtual",
+        ReferenceKind::InvokeStatic => "REF_invokeStatic",
+        ReferenceKind::InvokeSpecial => "REF_invokeSpecial",
+        ReferenceKind::NewInvokeSpecial => "REF_newInvokeSpecial",
+        ReferenceKind::InvokeInterface => "REF_invokeInterface",
+    }
+}
+
+/// Escapes a string the way `javap` does in comments.
+pub fn escape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '"' => result.push_str("\\\""),
+            c if (c as u32) < 0x20 || (c as u32) >= 0x7f => {
+                result.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Quotes member names that are not valid Java identifiers, such as `<init>`.
+fn quote_name(name: &str) -> String {
+    match name.starts_with('<') {
+        true => format!("\"{name}\""),
+        false => name.to_string(),
+    }
+}
+
+/// Formats a float the way Java does, e.g. `1.0f` rather than `1f`.
+pub fn format_float(value: f32) -> String {
+    match value {
+        v if v.is_nan() => "NaNf".to_string(),
+        v if v.is_infinite() => format!("{}Infinityf", if v < 0.0 { "-" } else { "" }),
+        v if v.fract() == 0.0 && v.abs() < 1e7 => format!("{v:.1}f"),
+        v => format!("{v}f"),
+    }
+}
+
+/// Formats a double the way Java does, e.g. `1.0d` rather than `1d`.
+pub fn format_double(value: f64) -> String {
+    match value {
+        v if v.is_nan() => "NaNd".to_string(),
+        v if v.is_infinite() => format!("{}Infinityd", if v < 0.0 { "-" } else { "" }),
+        v if v.fract() == 0.0 && v.abs() < 1e7 => format!("{v:.1}d"),
+        v => format!("{v}d"),
+    }
+}
+
+/// The references of a constant pool entry to other entries, e.g. `#2.#3`.
+pub fn entry_arguments(entry: &ConstantPoolEntry) -> String {
+    match entry {
+        ConstantPoolEntry::String(value) => escape(value),
+        ConstantPoolEntry::Integer(value) => value.to_string(),
+        ConstantPoolEntry::Float(value) => format_float(*value),
+        ConstantPoolEntry::Long(value) => format!("{value}l"),
+        ConstantPoolEntry::Double(value) => format_double(*value),
+        ConstantPoolEntry::ClassRef(name_ref)
+        | ConstantPoolEntry::StringRef(name_ref)
+        | ConstantPoolEntry::MethodType(name_ref)
+        | ConstantPoolEntry::Module(name_ref)
+        | ConstantPoolEntry::Package(name_ref) => format!("#{name_ref}"),
+        ConstantPoolEntry::FieldRef(field_ref) => {
+            format!("#{}.#{}", field_ref.class_ref, field_ref.name_type_ref)
+        }
+        ConstantPoolEntry::MethodRef(method_ref)
+        | ConstantPoolEntry::InterfaceMethodRef(method_ref) => {
+            format!("#{}.#{}", method_ref.class_ref, method_ref.name_type_ref)
+        }
+        ConstantPoolEntry::NameTypeDescriptor(name_type) => {
+            format!("#{}:#{}", name_type.name_ref, name_type.type_descriptor_ref)
+        }
+        ConstantPoolEntry::MethodHandle(method_handle) => format!(
+            "{}:#{}",
+            method_handle.reference_kind as u8, method_handle.reference_ref
+        ),
+        ConstantPoolEntry::Dynamic(dynamic) | ConstantPoolEntry::InvokeDynamic(dynamic) => {
+            format!(
+                "#{}:#{}",
+                dynamic.bootstrap_method_attr_idx, dynamic.name_type_ref
+            )
+        }
+        ConstantPoolEntry::Empty() => String::new(),
+    }
+}
+
+fn name_and_type(pool: &ConstantPool, index: u16) -> Result<String, ClassFileError> {
+    let (name, descriptor) = pool.name_and_type(index as usize)?;
+    Ok(format!("{}:{}", quote_name(name), descriptor))
+}
+
+fn member(pool: &ConstantPool, index: u16, this_class: &str) -> Result<String, ClassFileError> {
+    let (owner, name, descriptor) = pool.member_ref(index as usize)?;
+    match owner == this_class {
+        true => Ok(format!("{}:{}", quote_name(name), descriptor)),
+        false => Ok(format!("{}.{}:{}", owner, quote_name(name), descriptor)),
+    }
+}
+
+/// The resolved value of a constant pool entry, as printed in the comment column of the constant pool.
+pub fn entry_comment(pool: &ConstantPool, index: u16) -> Result<String, ClassFileError> {
+    match pool.get_entry(index) {
+        ConstantPoolEntry::ClassRef(name_ref) => {
+            let name = pool.string_entry(*name_ref)?;
+            match name.starts_with('[') {
+                true => Ok(format!("\"{name}\"")),
+                false => Ok(name.clone()),
+            }
+        }
+        ConstantPoolEntry::StringRef(value_ref) => Ok(escape(pool.string_entry(*value_ref)?)),
+        ConstantPoolEntry::MethodType(descriptor_ref) => {
+            Ok(format!(" {}", pool.string_entry(*descriptor_ref)?))
+        }
+        ConstantPoolEntry::Module(name_ref) | ConstantPoolEntry::Package(name_ref) => {
+            Ok(pool.string_entry(*name_ref)?.clone())
+        }
+        ConstantPoolEntry::FieldRef(_)
+        | ConstantPoolEntry::MethodRef(_)
+        | ConstantPoolEntry::InterfaceMethodRef(_) => member(pool, index, ""),
+        ConstantPoolEntry::NameTypeDescriptor(name_type) => {
+            let name = pool.string_entry(name_type.name_ref)?;
+            let descriptor = pool.string_entry(name_type.type_descriptor_ref)?;
+            Ok(format!("{}:{}", quote_name(name), descriptor))
+        }
+        ConstantPoolEntry::MethodHandle(method_handle) => Ok(format!(
+            "{} {}",
+            reference_kind_name(method_handle.reference_kind),
+            member(pool, method_handle.reference_ref, "")?
+        )),
+        ConstantPoolEntry::Dynamic(dynamic) | ConstantPoolEntry::InvokeDynamic(dynamic) => {
+            Ok(format!(
+                "#{}:{}",
+                dynamic.bootstrap_method_attr_idx,
+                name_and_type(pool, dynamic.name_type_ref)?
+            ))
+        }
+        _ => Ok(String::new()),
+    }
+}
+
+/// The comment for an instruction operand that refers to the constant pool, e.g. `Method java/lang/Object."<init>":()V`.
+pub fn operand_comment(
+    pool: &ConstantPool,
+    index: u16,
+    this_class: &str,
+) -> Result<String, ClassFileError> {
+    match pool.get_entry(index) {
+        ConstantPoolEntry::Integer(value) => Ok(format!("int {value}")),
+        ConstantPoolEntry::Float(value) => Ok(format!("float {}", format_float(*value))),
+        ConstantPoolEntry::Long(value) => Ok(format!("long {value}l")),
+        ConstantPoolEntry::Double(value) => Ok(format!("double {}", format_double(*value))),
+        ConstantPoolEntry::ClassRef(name_ref) => {
+            let name = pool.string_entry(*name_ref)?;
+            match name.starts_with('[') {
+                true => Ok(format!("class \"{name}\"")),
+                false => Ok(format!("class {name}")),
+            }
+        }
+        ConstantPoolEntry::StringRef(value_ref) => {
+            Ok(format!("String {}", escape(pool.string_entry(*value_ref)?)))
+        }
+        ConstantPoolEntry::FieldRef(_) => Ok(format!("Field {}", member(pool, index, this_class)?)),
+        ConstantPoolEntry::MethodRef(_) => {
+            Ok(format!("Method {}", member(pool, index, this_class)?))
+        }
+        ConstantPoolEntry::InterfaceMethodRef(_) => Ok(format!(
+            "InterfaceMethod {}",
+            member(pool, index, this_class)?
+        )),
+        ConstantPoolEntry::MethodType(descriptor_ref) => Ok(format!(
+            "MethodType {}",
+            pool.string_entry(*descriptor_ref)?
+        )),
+        ConstantPoolEntry::MethodHandle(method_handle) => Ok(format!(
+            "MethodHandle {} {}",
+            reference_kind_name(method_handle.reference_kind),
+            member(pool, method_handle.reference_ref, this_class)?
+        )),
+        ConstantPoolEntry::Dynamic(dynamic) => Ok(format!(
+            "Dynamic #{}:{}",
+            dynamic.bootstrap_method_attr_idx,
+            name_and_type(pool, dynamic.name_type_ref)?
+        )),
+        ConstantPoolEntry::InvokeDynamic(dynamic) => Ok(format!(
+            "InvokeDynamic #{}:{}",
+            dynamic.bootstrap_method_attr_idx,
+            name_and_type(pool, dynamic.name_type_ref)?
+        )),
+        other => Ok(format!("{} ?", entry_kind(other))),
+    }
+}
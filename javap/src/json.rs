@@ -0,0 +1,18 @@
+use cafebabe::ClassFile;
+use serde::Serialize;
+
+/// A parsed class file together with the path it was read from.
+#[derive(Serialize)]
+struct ClassFileDump<'a> {
+    path: &'a str,
+    class: &'a ClassFile,
+}
+
+/// Dumps a class file as a single line of JSON, so that dumps of several classes form a JSON Lines document.
+pub fn dump(path: &str, class_file: &ClassFile) -> Result<String, serde_json::Error> {
+    let dump = ClassFileDump {
+        path,
+        class: class_file,
+    };
+    Ok(serde_json::to_string(&dump)? + "\n")
+}
@@ -0,0 +1,156 @@
+mod constants;
+mod json;
+mod printer;
+
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use cafebabe::archive::{JarEntry, JarFile};
+use cafebabe::assembler::disassemble;
+use cafebabe::decompiler::decompile;
+use cafebabe::kotlin::print_kotlin;
+use cafebabe::{ParseOptions, read_class_data_with_options};
+use printer::{Options, Printer};
+
+const USAGE: &str = "Usage: javap <options> <classfiles or jars>
+where possible options include:
+  -c    Disassemble the code
+  -v    Print additional information
+  -p    Show all classes and members
+  -l    Print line number and local variable tables
+  -s    Print internal type signatures
+  --json  Dump the parsed class files as JSON Lines
+  --asm   Print the class files in the syntax of the jasm assembler
+  --java  Print the classes as Java-like source decompiled from their bytecode
+  --kotlin  Print the Kotlin declarations of classes compiled by Kotlin, with their JVM signatures with -s
+  --multi-release <version>  Specify the version to use for multi-release jars
+  --enable-preview  Also read class files that depend on preview features";
+
+fn print_class(path: &str, data: &[u8], options: &Options) -> Result<String, String> {
+    let parse_options = ParseOptions {
+        allow_preview: options.enable_preview,
+        ..ParseOptions::default()
+    };
+    let class_file = read_class_data_with_options(data, &parse_options)
+        .map_err(|error| format!("Can't parse {path}: {error}"))?;
+    if options.json {
+        return json::dump(path, &class_file)
+            .map_err(|error| format!("Can't dump {path}: {error}"));
+    }
+    if options.asm {
+        return disassemble(&class_file).map_err(|error| format!("Can't print {path}: {error}"));
+    }
+    if options.java {
+        return decompile(&class_file).map_err(|error| format!("Can't decompile {path}: {error}"));
+    }
+    if options.kotlin {
+        // Classes that Kotlin didn't compile are printed as usual.
+        match print_kotlin(&class_file, options.signatures) {
+            Ok(Some(source)) => return Ok(source),
+            Ok(None) => {}
+            Err(error) => return Err(format!("Can't read the Kotlin metadata of {path}: {error}")),
+        }
+    }
+    Printer::new(&class_file, options)
+        .and_then(|printer| printer.print(path, data.len()))
+        .map_err(|error| format!("Can't print {path}: {error}"))
+}
+
+/// Prints all classes of a jar, each under the path `<jar>!/<entry>`. Each class is printed or fails on its own, so
+/// that one broken entry doesn't hide the others.
+fn print_jar_file(path: &str, options: &Options) -> Result<Vec<Result<String, String>>, String> {
+    let jar_file = JarFile::open(path).map_err(|error| format!("Can't read {path}: {error}"))?;
+    let entries: Vec<&JarEntry> = match options.multi_release {
+        Some(release) => jar_file
+            .class_names_for_release(release)
+            .into_iter()
+            .filter_map(|class_name| {
+                jar_file.resolve_entry(&format!("{class_name}.class"), release)
+            })
+            .collect(),
+        None => jar_file
+            .entries()
+            .iter()
+            .filter(|entry| entry.class_name().is_some())
+            .collect(),
+    };
+    let outputs = entries
+        .into_iter()
+        .map(|entry| {
+            let entry_path = format!("{path}!/{}", entry.name);
+            let data = jar_file
+                .read(entry)
+                .map_err(|error| format!("Can't read {entry_path}: {error}"))?;
+            print_class(&entry_path, &data, options)
+        })
+        .collect();
+    Ok(outputs)
+}
+
+/// The output of a class file, or of each class of a jar.
+fn print_class_file(path: &str, options: &Options) -> Vec<Result<String, String>> {
+    if path.ends_with(".jar") {
+        return print_jar_file(path, options).unwrap_or_else(|message| vec![Err(message)]);
+    }
+    let output = fs::read(path)
+        .map_err(|error| format!("Can't read {path}: {error}"))
+        .and_then(|data| print_class(path, &data, options));
+    vec![output]
+}
+
+fn main() -> ExitCode {
+    let mut options = Options::default();
+    let mut paths = Vec::new();
+
+    let mut arguments = env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "-c" => options.code = true,
+            "-v" | "-verbose" => options.verbose = true,
+            "-p" | "-private" => options.private = true,
+            "-l" => options.lines = true,
+            "-s" => options.signatures = true,
+            "--json" => options.json = true,
+            "--asm" => options.asm = true,
+            "--java" => options.java = true,
+            "--kotlin" => options.kotlin = true,
+            "--enable-preview" => options.enable_preview = true,
+            "--multi-release" => match arguments.next().and_then(|value| value.parse().ok()) {
+                Some(release) => options.multi_release = Some(release),
+                None => {
+                    eprintln!("Missing or invalid version for --multi-release\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "-h" | "-help" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown option: {flag}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    let mut exit_code = ExitCode::SUCCESS;
+    for path in &paths {
+        for output in print_class_file(path, &options) {
+            match output {
+                Ok(output) => print!("{output}"),
+                Err(message) => {
+                    eprintln!("Error: {message}");
+                    exit_code = ExitCode::FAILURE;
+                }
+            }
+        }
+    }
+    exit_code
+}
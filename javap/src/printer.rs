@@ -0,0 +1,773 @@
+use std::fmt;
+use std::fmt::Write;
+
+use cafebabe::attributes::{Attribute, Code, LocalVariable, ParameterFlags};
+use cafebabe::bootstrap::method_handle_info;
+use cafebabe::bytecode::{Instruction, Opcode, Operand, array_type_name};
+use cafebabe::constant_pool::ConstantPoolEntry;
+use cafebabe::descriptor::{FieldType, MethodDescriptor};
+use cafebabe::{
+    AccessFlags, ClassFile, ClassFileError, FieldAccessFlags, FieldInfo, MethodAccessFlags,
+    MethodInfo,
+};
+
+use crate::constants::{
+    entry_arguments, entry_comment, entry_kind, format_double, format_float, operand_comment,
+    reference_kind_name,
+};
+
+/// Which parts of a class file to print; mirrors the command line flags of `javap`.
+#[derive(Default)]
+pub struct Options {
+    /// `-c`: disassemble the code.
+    pub code: bool,
+    /// `-v`: print additional information, such as the constant pool.
+    pub verbose: bool,
+    /// `-p`: show all classes and members, including private ones.
+    pub private: bool,
+    /// `-l`: print line number and local variable tables.
+    pub lines: bool,
+    /// `-s`: print internal type signatures.
+    pub signatures: bool,
+    /// `--json`: dump the parsed class files as JSON instead of printing them.
+    pub json: bool,
+    /// `--asm`: print the class files in the syntax of the assembler instead.
+    pub asm: bool,
+    /// `--java`: print the classes as Java-like source decompiled from their bytecode instead.
+    pub java: bool,
+    /// `--kotlin`: print the Kotlin declarations of classes compiled by Kotlin instead.
+    pub kotlin: bool,
+    /// `--multi-release <version>`: print the classes of multi-release jars as seen by that release.
+    pub multi_release: Option<u16>,
+    /// `--enable-preview`: also read class files that depend on preview features.
+    pub enable_preview: bool,
+}
+
+#[derive(Debug)]
+pub enum PrintError {
+    ClassFile(ClassFileError),
+    Format(fmt::Error),
+}
+
+impl fmt::Display for PrintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrintError::ClassFile(error) => write!(f, "{error}"),
+            PrintError::Format(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<ClassFileError> for PrintError {
+    fn from(error: ClassFileError) -> Self {
+        PrintError::ClassFile(error)
+    }
+}
+
+impl From<fmt::Error> for PrintError {
+    fn from(error: fmt::Error) -> Self {
+        PrintError::Format(error)
+    }
+}
+
+type PrintResult = Result<(), PrintError>;
+
+/// Pads `text` so that a `//` comment lines up with the ones `javap` prints.
+fn with_comment(text: &str, comment: &str) -> String {
+    format!("{text:<40}// {comment}")
+}
+
+fn java_name(internal_name: &str) -> String {
+    internal_name.replace('/', ".")
+}
+
+fn flag_names<I: Iterator<Item = (&'static str, u16)>>(bits: u16, names: I) -> String {
+    let names: Vec<&str> = names.map(|(name, _)| name).collect();
+    format!("(0x{bits:04x}) {}", names.join(", "))
+}
+
+fn class_modifiers(flags: AccessFlags) -> String {
+    let mut modifiers = Vec::new();
+    if flags.contains(AccessFlags::ACC_PUBLIC) {
+        modifiers.push("public");
+    }
+    if flags.contains(AccessFlags::ACC_ABSTRACT) && !flags.contains(AccessFlags::ACC_INTERFACE) {
+        modifiers.push("abstract");
+    }
+    if flags.contains(AccessFlags::ACC_FINAL) {
+        modifiers.push("final");
+    }
+    modifiers.push(match flags {
+        f if f.contains(AccessFlags::ACC_ANNOTATION) => "@interface",
+        f if f.contains(AccessFlags::ACC_INTERFACE) => "interface",
+        _ => "class",
+    });
+    modifiers.join(" ")
+}
+
+fn field_modifiers(flags: FieldAccessFlags) -> String {
+    let mut modifiers = Vec::new();
+    for (flag, modifier) in [
+        (FieldAccessFlags::ACC_PUBLIC, "public"),
+        (FieldAccessFlags::ACC_PROTECTED, "protected"),
+        (FieldAccessFlags::ACC_PRIVATE, "private"),
+        (FieldAccessFlags::ACC_STATIC, "static"),
+        (FieldAccessFlags::ACC_FINAL, "final"),
+        (FieldAccessFlags::ACC_TRANSIENT, "transient"),
+        (FieldAccessFlags::ACC_VOLATILE, "volatile"),
+    ] {
+        if flags.contains(flag) {
+            modifiers.push(modifier);
+        }
+    }
+    modifiers.join(" ")
+}
+
+fn method_modifiers(flags: MethodAccessFlags, in_interface: bool) -> String {
+    let mut modifiers = Vec::new();
+    for (flag, modifier) in [
+        (MethodAccessFlags::ACC_PUBLIC, "public"),
+        (MethodAccessFlags::ACC_PROTECTED, "protected"),
+        (MethodAccessFlags::ACC_PRIVATE, "private"),
+        (MethodAccessFlags::ACC_ABSTRACT, "abstract"),
+        (MethodAccessFlags::ACC_STATIC, "static"),
+        (MethodAccessFlags::ACC_FINAL, "final"),
+        (MethodAccessFlags::ACC_SYNCHRONIZED, "synchronized"),
+        (MethodAccessFlags::ACC_NATIVE, "native"),
+    ] {
+        if flags.contains(flag) {
+            modifiers.push(modifier);
+        }
+    }
+    let is_default = in_interface
+        && !flags.intersects(
+            MethodAccessFlags::ACC_ABSTRACT
+                | MethodAccessFlags::ACC_STATIC
+                | MethodAccessFlags::ACC_PRIVATE,
+        );
+    if is_default {
+        modifiers.push("default");
+    }
+    modifiers.join(" ")
+}
+
+fn join_modifiers(modifiers: &str, rest: &str) -> String {
+    match modifiers.is_empty() {
+        true => rest.to_string(),
+        false => format!("{modifiers} {rest}"),
+    }
+}
+
+pub struct Printer<'a> {
+    class_file: &'a ClassFile,
+    options: &'a Options,
+    this_class: &'a str,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(class_file: &'a ClassFile, options: &'a Options) -> Result<Self, PrintError> {
+        let this_class = class_file.this_class_name()?;
+        Ok(Printer {
+            class_file,
+            options,
+            this_class,
+            out: String::new(),
+        })
+    }
+
+    /// Prints the class file; `path` and `size` are only used in verbose mode.
+    pub fn print(mut self, path: &str, size: usize) -> Result<String, PrintError> {
+        if self.options.verbose {
+            writeln!(self.out, "Classfile {path}")?;
+            writeln!(self.out, "  size {size} bytes")?;
+        }
+        if let Some(source_file) = self.class_file.source_file()? {
+            let indent = if self.options.verbose { "  " } else { "" };
+            writeln!(self.out, "{indent}Compiled from \"{source_file}\"")?;
+        }
+
+        if self.class_file.is_module() {
+            self.print_module()?;
+            return Ok(self.out);
+        }
+
+        self.print_class_declaration()?;
+        if self.options.verbose {
+            self.print_class_header()?;
+            self.print_constant_pool()?;
+            writeln!(self.out, "{{")?;
+        }
+        self.print_members()?;
+        writeln!(self.out, "}}")?;
+        if self.options.verbose {
+            self.print_class_attributes()?;
+        }
+
+        Ok(self.out)
+    }
+
+    fn print_module(&mut self) -> PrintResult {
+        let Some(module) = self.class_file.module_descriptor()? else {
+            return Ok(());
+        };
+        let version = match &module.version {
+            Some(version) => format!("@{version}"),
+            None => String::new(),
+        };
+        writeln!(self.out, "module {}{} {{", module.name, version)?;
+        for requires in &module.requires {
+            writeln!(self.out, "  requires {};", requires.name)?;
+        }
+        for exports in &module.exports {
+            match exports.targets.is_empty() {
+                true => writeln!(self.out, "  exports {};", java_name(&exports.package))?,
+                false => writeln!(
+                    self.out,
+                    "  exports {} to {};",
+                    java_name(&exports.package),
+                    exports.targets.join(", ")
+                )?,
+            }
+        }
+        for opens in &module.opens {
+            writeln!(self.out, "  opens {};", java_name(&opens.package))?;
+        }
+        for uses in &module.uses {
+            writeln!(self.out, "  uses {};", java_name(uses))?;
+        }
+        for provides in &module.provides {
+            let implementations: Vec<String> = provides
+                .implementations
+                .iter()
+                .map(|i| java_name(i))
+                .collect();
+            writeln!(
+                self.out,
+                "  provides {} with {};",
+                java_name(&provides.service),
+                implementations.join(", ")
+            )?;
+        }
+        writeln!(self.out, "}}")?;
+        Ok(())
+    }
+
+    fn print_class_declaration(&mut self) -> PrintResult {
+        let flags = self.class_file.access_flags;
+        let mut declaration = format!("{} {}", class_modifiers(flags), java_name(self.this_class));
+
+        let interfaces: Vec<String> = self
+            .class_file
+            .interface_names()?
+            .iter()
+            .map(|name| java_name(name))
+            .collect();
+        if flags.contains(AccessFlags::ACC_INTERFACE) {
+            if !interfaces.is_empty() {
+                write!(declaration, " extends {}", interfaces.join(","))?;
+            }
+        } else {
+            if let Some(super_class) = self.class_file.super_class_name()?
+                && super_class != "java/lang/Object"
+            {
+                write!(declaration, " extends {}", java_name(super_class))?;
+            }
+            if !interfaces.is_empty() {
+                write!(declaration, " implements {}", interfaces.join(","))?;
+            }
+        }
+        match self.options.verbose {
+            true => writeln!(self.out, "{declaration}")?,
+            false => writeln!(self.out, "{declaration} {{")?,
+        }
+        Ok(())
+    }
+
+    fn print_class_header(&mut self) -> PrintResult {
+        let class_file = self.class_file;
+        writeln!(self.out, "  minor version: {}", class_file.version.minor)?;
+        writeln!(self.out, "  major version: {}", class_file.version.major)?;
+        writeln!(
+            self.out,
+            "  flags: {}",
+            flag_names(
+                class_file.access_flags.bits(),
+                class_file
+                    .access_flags
+                    .iter_names()
+                    .map(|(name, flag)| (name, flag.bits()))
+            )
+        )?;
+        writeln!(
+            self.out,
+            "  {}",
+            with_comment(
+                &format!("this_class: #{}", class_file.class.this_idx),
+                self.this_class
+            )
+        )?;
+        let super_class = match class_file.super_class_name()? {
+            Some(name) => name.clone(),
+            None => String::new(),
+        };
+        writeln!(
+            self.out,
+            "  {}",
+            with_comment(
+                &format!("super_class: #{}", class_file.class.super_idx),
+                &super_class
+            )
+        )?;
+        writeln!(
+            self.out,
+            "  interfaces: {}, fields: {}, methods: {}, attributes: {}",
+            class_file.class.interfaces.len(),
+            class_file.fields.len(),
+            class_file.methods.len(),
+            class_file.attributes.len()
+        )?;
+        Ok(())
+    }
+
+    fn print_constant_pool(&mut self) -> PrintResult {
+        let pool = &self.class_file.constant_pool;
+        writeln!(self.out, "Constant pool:")?;
+        for (position, entry) in pool.items().enumerate() {
+            if matches!(entry, ConstantPoolEntry::Empty()) {
+                continue;
+            }
+            let index = (position + 1) as u16;
+            let line = format!(
+                "{:>5} = {:<18} {}",
+                format!("#{index}"),
+                entry_kind(entry),
+                entry_arguments(entry)
+            );
+            let comment = entry_comment(pool, index)?;
+            match comment.is_empty() {
+                true => writeln!(self.out, "{line}")?,
+                false => writeln!(self.out, "{line:<41} // {comment}")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn print_members(&mut self) -> PrintResult {
+        let detailed = self.options.code
+            || self.options.verbose
+            || self.options.lines
+            || self.options.signatures;
+        let mut first = true;
+
+        for field in &self.class_file.fields {
+            if field.access_flags.contains(FieldAccessFlags::ACC_PRIVATE) && !self.options.private {
+                continue;
+            }
+            if detailed && !first {
+                writeln!(self.out)?;
+            }
+            first = false;
+            self.print_field(field)?;
+        }
+
+        for method in &self.class_file.methods {
+            if method.access_flags.contains(MethodAccessFlags::ACC_PRIVATE) && !self.options.private
+            {
+                continue;
+            }
+            if detailed && !first {
+                writeln!(self.out)?;
+            }
+            first = false;
+            self.print_method(method)?;
+        }
+        Ok(())
+    }
+
+    fn print_field(&mut self, field: &FieldInfo) -> PrintResult {
+        let pool = &self.class_file.constant_pool;
+        let descriptor = field.descriptor(pool)?;
+        let field_type = FieldType::parse(descriptor)?;
+        let declaration = format!("{} {}", field_type.java_name(), field.name(pool)?);
+        writeln!(
+            self.out,
+            "  {};",
+            join_modifiers(&field_modifiers(field.access_flags), &declaration)
+        )?;
+
+        if self.options.signatures || self.options.verbose {
+            writeln!(self.out, "    descriptor: {descriptor}")?;
+        }
+        if self.options.verbose {
+            writeln!(
+                self.out,
+                "    flags: {}",
+                flag_names(
+                    field.access_flags.bits(),
+                    field
+                        .access_flags
+                        .iter_names()
+                        .map(|(name, flag)| (name, flag.bits()))
+                )
+            )?;
+            self.print_member_attributes(&field.attributes)?;
+        }
+        Ok(())
+    }
+
+    fn print_method(&mut self, method: &MethodInfo) -> PrintResult {
+        let pool = &self.class_file.constant_pool;
+        let name = method.name(pool)?;
+        let descriptor_string = method.descriptor(pool)?;
+        let descriptor = MethodDescriptor::parse(descriptor_string)?;
+        let in_interface = self
+            .class_file
+            .access_flags
+            .contains(AccessFlags::ACC_INTERFACE);
+        let modifiers = method_modifiers(method.access_flags, in_interface);
+
+        let declaration = if name == "<clinit>" {
+            "static {}".to_string()
+        } else {
+            let mut parameters: Vec<String> = descriptor
+                .parameters
+                .iter()
+                .map(FieldType::java_name)
+                .collect();
+            if method.access_flags.contains(MethodAccessFlags::ACC_VARARGS)
+                && let Some(last) = parameters.last_mut()
+                && last.ends_with("[]")
+            {
+                last.truncate(last.len() - 2);
+                last.push_str("...");
+            }
+            let signature = match name.as_str() {
+                "<init>" => format!("{}({})", java_name(self.this_class), parameters.join(", ")),
+                _ => {
+                    let return_type = match &descriptor.return_type {
+                        Some(return_type) => return_type.java_name(),
+                        None => "void".to_string(),
+                    };
+                    format!("{} {}({})", return_type, name, parameters.join(", "))
+                }
+            };
+            let exceptions: Vec<String> = method
+                .exceptions(pool)?
+                .iter()
+                .map(|name| java_name(name))
+                .collect();
+            match exceptions.is_empty() {
+                true => join_modifiers(&modifiers, &signature),
+                false => format!(
+                    "{} throws {}",
+                    join_modifiers(&modifiers, &signature),
+                    exceptions.join(", ")
+                ),
+            }
+        };
+        writeln!(self.out, "  {declaration};")?;
+
+        if self.options.signatures || self.options.verbose {
+            writeln!(self.out, "    descriptor: {descriptor_string}")?;
+        }
+        if self.options.verbose {
+            writeln!(
+                self.out,
+                "    flags: {}",
+                flag_names(
+                    method.access_flags.bits(),
+                    method
+                        .access_flags
+                        .iter_names()
+                        .map(|(name, flag)| (name, flag.bits()))
+                )
+            )?;
+        }
+
+        if let Some(code) = method.code() {
+            let args_size = descriptor.parameter_slots()
+                + match method.access_flags.contains(MethodAccessFlags::ACC_STATIC) {
+                    true => 0,
+                    false => 1,
+                };
+            self.print_code(code, args_size)?;
+        }
+        if self.options.verbose {
+            self.print_member_attributes(&method.attributes)?;
+        }
+        Ok(())
+    }
+
+    fn print_code(&mut self, code: &Code, args_size: u16) -> PrintResult {
+        if self.options.code || self.options.verbose {
+            writeln!(self.out, "    Code:")?;
+            if self.options.verbose {
+                writeln!(
+                    self.out,
+                    "      stack={}, locals={}, args_size={}",
+                    code.max_stack, code.max_locals, args_size
+                )?;
+            }
+            for instruction in code.instructions()? {
+                self.print_instruction(&instruction)?;
+            }
+            if !code.exception_table.is_empty() {
+                writeln!(self.out, "      Exception table:")?;
+                writeln!(self.out, "         from    to  target type")?;
+                for handler in &code.exception_table {
+                    let catch_type = match handler.catch_type {
+                        0 => "any".to_string(),
+                        catch_type => format!(
+                            "Class {}",
+                            self.class_file
+                                .constant_pool
+                                .class_name(catch_type as usize)?
+                        ),
+                    };
+                    writeln!(
+                        self.out,
+                        "         {:>5} {:>5} {:>5}   {}",
+                        handler.start_pc, handler.end_pc, handler.handler_pc, catch_type
+                    )?;
+                }
+            }
+        }
+
+        if self.options.lines || self.options.verbose {
+            for attribute in &code.attributes {
+                match attribute {
+                    Attribute::LineNumberTable(line_numbers) => {
+                        writeln!(self.out, "      LineNumberTable:")?;
+                        for line_number in line_numbers {
+                            writeln!(
+                                self.out,
+                                "        line {}: {}",
+                                line_number.line_number, line_number.start_pc
+                            )?;
+                        }
+                    }
+                    Attribute::LocalVariableTable(local_variables) => {
+                        self.print_local_variables("LocalVariableTable", local_variables)?;
+                    }
+                    Attribute::LocalVariableTypeTable(local_variables) => {
+                        self.print_local_variables("LocalVariableTypeTable", local_variables)?;
+                    }
+                    Attribute::Unknown { name_idx, info } if self.options.verbose => {
+                        let name = self.class_file.constant_pool.string_entry(*name_idx)?;
+                        writeln!(self.out, "      {name}: length = 0x{:x}", info.len())?;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn print_local_variables(
+        &mut self,
+        title: &str,
+        local_variables: &[LocalVariable],
+    ) -> PrintResult {
+        let pool = &self.class_file.constant_pool;
+        writeln!(self.out, "      {title}:")?;
+        writeln!(self.out, "        Start  Length  Slot  Name   Signature")?;
+        for local in local_variables {
+            writeln!(
+                self.out,
+                "        {:>5}  {:>6}  {:>4}  {:>4}   {}",
+                local.start_pc,
+                local.length,
+                local.index,
+                pool.string_entry(local.name_idx)?,
+                pool.string_entry(local.descriptor_idx)?
+            )?;
+        }
+        Ok(())
+    }
+
+    fn print_instruction(&mut self, instruction: &Instruction) -> PrintResult {
+        let pool = &self.class_file.constant_pool;
+        let mnemonic = instruction.opcode.mnemonic();
+        let prefix = format!("{:>10}: ", instruction.offset);
+
+        match &instruction.operand {
+            Operand::None => writeln!(self.out, "{prefix}{mnemonic}")?,
+            Operand::Byte(value) => writeln!(self.out, "{prefix}{mnemonic:<13} {value}")?,
+            Operand::Short(value) => writeln!(self.out, "{prefix}{mnemonic:<13} {value}")?,
+            Operand::Local(index) => writeln!(self.out, "{prefix}{mnemonic:<13} {index}")?,
+            Operand::Branch(target) => writeln!(self.out, "{prefix}{mnemonic:<13} {target}")?,
+            Operand::Iinc { index, value } => {
+                writeln!(self.out, "{prefix}{mnemonic:<13} {index}, {value}")?
+            }
+            Operand::ArrayType(array_type) => writeln!(
+                self.out,
+                "{prefix}{mnemonic:<13} {}",
+                array_type_name(*array_type).unwrap_or("?")
+            )?,
+            Operand::ConstantPool(index) => {
+                let operand = match instruction.opcode {
+                    Opcode::Invokedynamic => format!("#{index},  0"),
+                    _ => format!("#{index}"),
+                };
+                let comment = operand_comment(pool, *index, self.this_class)?;
+                writeln!(self.out, "{prefix}{mnemonic:<13} {operand:<20}// {comment}")?
+            }
+            Operand::InvokeInterface { index, count } => {
+                let operand = format!("#{index},  {count}");
+                let comment = operand_comment(pool, *index, self.this_class)?;
+                writeln!(self.out, "{prefix}{mnemonic:<13} {operand:<20}// {comment}")?
+            }
+            Operand::MultiANewArray { index, dimensions } => {
+                let operand = format!("#{index},  {dimensions}");
+                let comment = operand_comment(pool, *index, self.this_class)?;
+                writeln!(self.out, "{prefix}{mnemonic:<13} {operand:<20}// {comment}")?
+            }
+            Operand::TableSwitch {
+                default,
+                low,
+                high,
+                targets,
+            } => {
+                writeln!(self.out, "{prefix}{mnemonic:<13} {{ // {low} to {high}")?;
+                for (key, target) in (*low..=*high).zip(targets) {
+                    writeln!(self.out, "{key:>24}: {target}")?;
+                }
+                writeln!(self.out, "{:>24}: {default}", "default")?;
+                writeln!(self.out, "          }}")?;
+            }
+            Operand::LookupSwitch { default, pairs } => {
+                writeln!(self.out, "{prefix}{mnemonic:<13} {{ // {}", pairs.len())?;
+                for (key, target) in pairs {
+                    writeln!(self.out, "{key:>24}: {target}")?;
+                }
+                writeln!(self.out, "{:>24}: {default}", "default")?;
+                writeln!(self.out, "          }}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn print_member_attributes(&mut self, attributes: &[Attribute]) -> PrintResult {
+        let pool = &self.class_file.constant_pool;
+        for attribute in attributes {
+            match attribute {
+                Attribute::ConstantValue(value_idx) => {
+                    let value = match pool.get_entry(*value_idx) {
+                        ConstantPoolEntry::Integer(value) => format!("int {value}"),
+                        ConstantPoolEntry::Float(value) => {
+                            format!("float {}", format_float(*value))
+                        }
+                        ConstantPoolEntry::Long(value) => format!("long {value}l"),
+                        ConstantPoolEntry::Double(value) => {
+                            format!("double {}", format_double(*value))
+                        }
+                        _ => operand_comment(pool, *value_idx, self.this_class)?,
+                    };
+                    writeln!(self.out, "    ConstantValue: {value}")?;
+                }
+                Attribute::Exceptions(class_idxs) => {
+                    let names = class_idxs
+                        .iter()
+                        .map(|idx| Ok(java_name(pool.class_name(*idx as usize)?)))
+                        .collect::<Result<Vec<_>, ClassFileError>>()?;
+                    writeln!(self.out, "    Exceptions:")?;
+                    writeln!(self.out, "      throws {}", names.join(", "))?;
+                }
+                Attribute::MethodParameters(parameters) => {
+                    writeln!(self.out, "    MethodParameters:")?;
+                    writeln!(self.out, "      {:<30} Flags", "Name")?;
+                    for parameter in parameters {
+                        let name = match parameter.name_idx {
+                            0 => "<no name>".to_string(),
+                            name_idx => pool.string_entry(name_idx)?.clone(),
+                        };
+                        let mut flags = Vec::new();
+                        for (flag, flag_name) in [
+                            (ParameterFlags::ACC_FINAL, "final"),
+                            (ParameterFlags::ACC_SYNTHETIC, "synthetic"),
+                            (ParameterFlags::ACC_MANDATED, "mandated"),
+                        ] {
+                            if parameter.flags.contains(flag) {
+                                flags.push(flag_name);
+                            }
+                        }
+                        let line = format!("{:<30} {}", name, flags.join(" "));
+                        writeln!(self.out, "      {}", line.trim_end())?;
+                    }
+                }
+                Attribute::Deprecated => writeln!(self.out, "    Deprecated: true")?,
+                Attribute::Synthetic => writeln!(self.out, "    Synthetic: true")?,
+                Attribute::Signature(signature_idx) => writeln!(
+                    self.out,
+                    "    {}",
+                    with_comment(
+                        &format!("Signature: #{signature_idx}"),
+                        pool.string_entry(*signature_idx)?
+                    )
+                )?,
+                Attribute::Unknown { name_idx, info } => {
+                    let name = pool.string_entry(*name_idx)?;
+                    writeln!(self.out, "    {name}: length = 0x{:x}", info.len())?;
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn print_class_attributes(&mut self) -> PrintResult {
+        let pool = &self.class_file.constant_pool;
+        for attribute in &self.class_file.attributes {
+            match attribute {
+                Attribute::SourceFile(name_idx) => writeln!(
+                    self.out,
+                    "SourceFile: \"{}\"",
+                    pool.string_entry(*name_idx)?
+                )?,
+                Attribute::Signature(signature_idx) => writeln!(
+                    self.out,
+                    "{}",
+                    with_comment(
+                        &format!("Signature: #{signature_idx}"),
+                        pool.string_entry(*signature_idx)?
+                    )
+                )?,
+                Attribute::Deprecated => writeln!(self.out, "Deprecated: true")?,
+                Attribute::Synthetic => writeln!(self.out, "Synthetic: true")?,
+                Attribute::BootstrapMethods(bootstrap_methods) => {
+                    writeln!(self.out, "BootstrapMethods:")?;
+                    for (position, bootstrap_method) in bootstrap_methods.iter().enumerate() {
+                        let handle =
+                            method_handle_info(pool, bootstrap_method.method_ref as usize)?;
+                        writeln!(
+                            self.out,
+                            "  {}: #{} {} {}.{}:{}",
+                            position,
+                            bootstrap_method.method_ref,
+                            reference_kind_name(handle.reference_kind),
+                            handle.owner,
+                            handle.name,
+                            handle.descriptor
+                        )?;
+                        writeln!(self.out, "    Method arguments:")?;
+                        for argument in &bootstrap_method.arguments {
+                            writeln!(
+                                self.out,
+                                "      #{} {}",
+                                argument,
+                                entry_comment(pool, *argument)?.trim_start()
+                            )?;
+                        }
+                    }
+                }
+                Attribute::Unknown { name_idx, info } => {
+                    let name = pool.string_entry(*name_idx)?;
+                    writeln!(self.out, "{name}: length = 0x{:x}", info.len())?;
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
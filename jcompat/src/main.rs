@@ -0,0 +1,95 @@
+use std::env;
+use std::process::ExitCode;
+
+use cafebabe::class_path::ClassPath;
+use cafebabe::compatibility::{CompatibilityReport, compare};
+
+const USAGE: &str = "Usage: jcompat <options> <old version> <new version>
+Compares two versions of a library, each given as jars or directories separated like a class path, and reports
+the changes that break binary compatibility. Exits with 1 if there are breaking changes.
+where possible options include:
+  --multi-release <version>  Specify the version to use for multi-release jars
+  --json                     Print the changes as JSON";
+
+struct Options {
+    multi_release: Option<u16>,
+    json: bool,
+}
+
+fn open_class_path(paths: &str, options: &Options) -> Result<ClassPath, String> {
+    let class_path =
+        ClassPath::parse(paths).map_err(|error| format!("Can't read {paths}: {error}"))?;
+    Ok(match options.multi_release {
+        Some(release) => class_path.with_release(release),
+        None => class_path,
+    })
+}
+
+fn run(old: &str, new: &str, options: &Options) -> Result<CompatibilityReport, String> {
+    let old = open_class_path(old, options)?;
+    let new = open_class_path(new, options)?;
+    compare(&old, &new).map_err(|error| format!("Can't compare the versions: {error}"))
+}
+
+fn main() -> ExitCode {
+    let mut options = Options {
+        multi_release: None,
+        json: false,
+    };
+    let mut paths = Vec::new();
+
+    let mut arguments = env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--multi-release" => match arguments.next().and_then(|value| value.parse().ok()) {
+                Some(release) => options.multi_release = Some(release),
+                None => {
+                    eprintln!("Invalid version for --multi-release\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "--json" => options.json = true,
+            "-h" | "-help" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown option: {flag}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    let [old, new] = paths.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+
+    let report = match run(old, new, &options) {
+        Ok(report) => report,
+        Err(message) => {
+            eprintln!("Error: {message}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for class_name in &report.invalid_classes {
+        eprintln!("Warning: can't parse {}", class_name.replace('/', "."));
+    }
+    if options.json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{json}"),
+            Err(error) => {
+                eprintln!("Error: Can't write JSON: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        print!("{report}");
+    }
+
+    match report.is_compatible() {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
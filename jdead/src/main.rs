@@ -0,0 +1,233 @@
+use std::env;
+use std::process::ExitCode;
+
+use cafebabe::call_graph::{Algorithm, MethodId};
+use cafebabe::class_path::ClassPath;
+use cafebabe::dead_code::{DeadCodeReport, EntryPoints, FieldId};
+use cafebabe::hierarchy::ClassHierarchy;
+
+const USAGE: &str = "Usage: jdead <options> <jars and directories>
+Reports the classes, methods and fields that are never used from the entry points, and the jars and directories
+that no used class comes from.
+where possible options include:
+  --java-home <directory>    Analyse calls into the JDK in the directory (default: $JAVA_HOME)
+  --multi-release <version>  Specify the version to use for multi-release jars
+  --entry <method>           Use a method as an entry point, e.g. com.acme.Handler.handle or
+                             com.acme.Handler.handle(Ljava/lang/String;)V
+  --keep <prefix>            Keep the classes whose names start with the prefix with all their methods,
+                             e.g. com.acme.api.
+  --no-main                  Don't use the main methods as entry points
+  --algorithm <cha|rta>      Build the call graph by class hierarchy analysis or rapid type analysis
+                             (default: rta)
+  --json                     Print the report as JSON";
+
+struct Options {
+    java_home: Option<String>,
+    multi_release: Option<u16>,
+    entry_points: EntryPoints,
+    algorithm: Algorithm,
+    json: bool,
+}
+
+fn java_name(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+/// Converts a method given like `com.acme.Handler.handle(Ljava/lang/String;)V` to the internal form of its class
+/// name, leaving the descriptor as it is.
+fn internal_method_name(method: &str) -> String {
+    let (member, descriptor) = method.split_at(method.find('(').unwrap_or(method.len()));
+    match member.rsplit_once('.') {
+        Some((class_name, name)) => format!("{}.{name}{descriptor}", class_name.replace('.', "/")),
+        None => method.to_string(),
+    }
+}
+
+fn method_name(method: &MethodId) -> String {
+    format!(
+        "{}.{}{}",
+        java_name(&method.class_name),
+        method.name,
+        method.descriptor
+    )
+}
+
+fn field_name(field: &FieldId) -> String {
+    format!(
+        "{}.{}:{}",
+        java_name(&field.class_name),
+        field.name,
+        field.descriptor
+    )
+}
+
+/// The JDK followed by the jars and directories, as the launcher of `java` finds classes.
+fn open_class_path(paths: &[String], options: &Options) -> Result<ClassPath, String> {
+    let mut class_path = match &options.java_home {
+        Some(java_home) => ClassPath::from_java_home(java_home)
+            .map_err(|error| format!("Can't read the JDK in {java_home}: {error}"))?,
+        None => ClassPath::new(),
+    };
+    for path in paths {
+        class_path
+            .add(path)
+            .map_err(|error| format!("Can't read {path}: {error}"))?;
+    }
+    Ok(match options.multi_release {
+        Some(release) => class_path.with_release(release),
+        None => class_path,
+    })
+}
+
+fn print_section(output: &mut String, title: &str, items: Vec<String>) {
+    if items.is_empty() {
+        return;
+    }
+    output.push_str(&format!("{title}:\n"));
+    for item in items {
+        output.push_str(&format!("   {item}\n"));
+    }
+}
+
+fn print_text(report: &DeadCodeReport) -> String {
+    let mut output = String::new();
+    print_section(
+        &mut output,
+        "Unused archives",
+        report
+            .unused_archives
+            .iter()
+            .map(|location| location.display().to_string())
+            .collect(),
+    );
+    print_section(
+        &mut output,
+        "Unused classes",
+        report
+            .unused_classes
+            .iter()
+            .map(|class_name| java_name(class_name))
+            .collect(),
+    );
+    print_section(
+        &mut output,
+        "Unreachable methods",
+        report.unreachable_methods.iter().map(method_name).collect(),
+    );
+    print_section(
+        &mut output,
+        "Unused fields",
+        report.unused_fields.iter().map(field_name).collect(),
+    );
+    output
+}
+
+fn run(paths: &[String], options: &Options) -> Result<String, String> {
+    let class_path = open_class_path(paths, options)?;
+    let hierarchy = ClassHierarchy::from_class_path(&class_path)
+        .map_err(|error| format!("Can't read the class hierarchy: {error}"))?;
+    let report = DeadCodeReport::analyze(
+        &class_path,
+        &hierarchy,
+        &options.entry_points,
+        options.algorithm,
+    )
+    .map_err(|error| format!("Can't analyse the dead code: {error}"))?;
+    for class_name in &report.invalid_classes {
+        eprintln!("Warning: can't parse {}", java_name(class_name));
+    }
+    for entry_point in &report.unknown_entry_points {
+        eprintln!(
+            "Warning: no class or method matches {}",
+            java_name(entry_point)
+        );
+    }
+
+    if options.json {
+        return serde_json::to_string_pretty(&report)
+            .map(|json| json + "\n")
+            .map_err(|error| format!("Can't write JSON: {error}"));
+    }
+    Ok(print_text(&report))
+}
+
+fn main() -> ExitCode {
+    let mut options = Options {
+        java_home: env::var("JAVA_HOME").ok(),
+        multi_release: None,
+        entry_points: EntryPoints::main_methods(),
+        algorithm: Algorithm::default(),
+        json: false,
+    };
+    let mut paths = Vec::new();
+
+    let mut arguments = env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        let mut value = |name: &str| {
+            let value = arguments.next();
+            if value.is_none() {
+                eprintln!("Missing value for {name}\n{USAGE}");
+            }
+            value
+        };
+        match argument.as_str() {
+            "--java-home" => match value(&argument) {
+                Some(java_home) => options.java_home = Some(java_home),
+                None => return ExitCode::from(2),
+            },
+            "--multi-release" => match value(&argument).and_then(|value| value.parse().ok()) {
+                Some(release) => options.multi_release = Some(release),
+                None => {
+                    eprintln!("Invalid version for --multi-release\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "--entry" => match value(&argument) {
+                Some(method) => options
+                    .entry_points
+                    .methods
+                    .push(internal_method_name(&method)),
+                None => return ExitCode::from(2),
+            },
+            "--keep" => match value(&argument) {
+                Some(prefix) => options.entry_points.keep.push(prefix.replace('.', "/")),
+                None => return ExitCode::from(2),
+            },
+            "--no-main" => options.entry_points.main_methods = false,
+            "--algorithm" => match value(&argument).as_deref() {
+                Some("cha") => options.algorithm = Algorithm::ClassHierarchy,
+                Some("rta") => options.algorithm = Algorithm::RapidType,
+                _ => {
+                    eprintln!("Invalid algorithm for --algorithm\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "--json" => options.json = true,
+            "-h" | "-help" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown option: {flag}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    match run(&paths, &options) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("Error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
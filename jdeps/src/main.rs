@@ -0,0 +1,260 @@
+use std::env;
+use std::process::ExitCode;
+
+use cafebabe::class_path::ClassPath;
+use cafebabe::dependencies::{
+    DependencyAnalysis, DependencyFilter, DependencyGraph, JdkInternalDependency, Level,
+};
+use serde::Serialize;
+
+const USAGE: &str = "Usage: jdeps <options> <jars, jmods or directories>
+where possible options include:
+  -cp, --class-path <path>   Resolve dependencies on the jars and directories of the class path
+  --java-home <directory>    Resolve dependencies on the JDK in the directory (default: $JAVA_HOME)
+  --multi-release <version>  Specify the version to use for multi-release jars
+  -s, -summary               Print the dependencies between archives only
+  -verbose:package           Print the dependencies between packages (the default)
+  -verbose:class             Print the dependencies between classes
+  --include <prefix>         Analyse only the classes whose names start with the prefix, e.g. com.acme.
+  --exclude <prefix>         Leave out dependencies on classes whose names start with the prefix
+  --no-jdk                   Leave out dependencies on the JDK
+  --cycles                   Print the cycles in the dependencies
+  --jdk-internals            Print the dependencies on internal APIs of the JDK
+  --dot                      Print the dependencies in the DOT language of Graphviz
+  --json                     Print the dependencies as JSON";
+
+struct Options {
+    class_path: Option<String>,
+    java_home: Option<String>,
+    multi_release: Option<u16>,
+    level: Level,
+    filter: DependencyFilter,
+    cycles: bool,
+    jdk_internals: bool,
+    dot: bool,
+    json: bool,
+}
+
+/// The report of `--json`, with the cycles and internal dependencies when they are asked for.
+#[derive(Serialize)]
+struct Report<'a> {
+    graph: &'a DependencyGraph,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cycles: Option<Vec<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jdk_internals: Option<Vec<JdkInternalDependency>>,
+}
+
+fn java_name(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+fn open_class_path(paths: &[String], options: &Options) -> Result<ClassPath, String> {
+    let mut class_path = ClassPath::new();
+    for path in paths {
+        class_path
+            .add(path)
+            .map_err(|error| format!("Can't read {path}: {error}"))?;
+    }
+    Ok(match options.multi_release {
+        Some(release) => class_path.with_release(release),
+        None => class_path,
+    })
+}
+
+fn open_libraries(options: &Options) -> Result<ClassPath, String> {
+    let mut libraries = match &options.java_home {
+        Some(java_home) => ClassPath::from_java_home(java_home)
+            .map_err(|error| format!("Can't read the JDK in {java_home}: {error}"))?,
+        None => ClassPath::new(),
+    };
+    for path in env::split_paths(options.class_path.as_deref().unwrap_or_default()) {
+        if !path.as_os_str().is_empty() {
+            libraries
+                .add(&path)
+                .map_err(|error| format!("Can't read {}: {error}", path.display()))?;
+        }
+    }
+    Ok(match options.multi_release {
+        Some(release) => libraries.with_release(release),
+        None => libraries,
+    })
+}
+
+/// Prints the dependencies like `jdeps`: the archives each archive depends on, followed by the dependencies at the
+/// level of packages or classes with the archive of the dependency.
+fn print_text(analysis: &DependencyAnalysis, options: &Options) -> String {
+    let mut output = String::new();
+    let summary = analysis.graph(Level::Archive, &options.filter);
+    for (source, targets) in &summary.edges {
+        for target in targets {
+            output.push_str(&format!("{source} -> {target}\n"));
+        }
+    }
+    if options.level != Level::Archive {
+        let graph = analysis.graph(options.level, &options.filter);
+        for (source, targets) in &graph.edges {
+            for target in targets {
+                output.push_str(&format!(
+                    "   {:<50} -> {:<50} {}\n",
+                    java_name(source),
+                    java_name(target),
+                    graph.nodes[target]
+                ));
+            }
+        }
+    }
+    output
+}
+
+fn print_cycles(cycles: &[Vec<String>]) -> String {
+    cycles
+        .iter()
+        .map(|cycle| {
+            let names: Vec<String> = cycle.iter().map(|name| java_name(name)).collect();
+            format!("cycle: {}\n", names.join(", "))
+        })
+        .collect()
+}
+
+fn print_jdk_internals(dependencies: &[JdkInternalDependency]) -> String {
+    let mut output = String::new();
+    let mut archives = None;
+    for dependency in dependencies {
+        let module = dependency.module.as_deref().unwrap_or("not found");
+        if archives != Some((&dependency.archive, module)) {
+            output.push_str(&format!("{} -> {module}\n", dependency.archive));
+            archives = Some((&dependency.archive, module));
+        }
+        output.push_str(&format!(
+            "   {:<50} -> {:<50} JDK internal API ({module})\n",
+            java_name(&dependency.class_name),
+            java_name(&dependency.target)
+        ));
+    }
+    output
+}
+
+fn run(paths: &[String], options: &Options) -> Result<String, String> {
+    let roots = open_class_path(paths, options)?;
+    let libraries = open_libraries(options)?;
+    let analysis = DependencyAnalysis::analyze(&roots, &libraries)
+        .map_err(|error| format!("Can't analyse the dependencies: {error}"))?;
+    for class_name in analysis.invalid_classes() {
+        eprintln!("Warning: can't parse {}", java_name(class_name));
+    }
+
+    let graph = analysis.graph(options.level, &options.filter);
+    let cycles = options.cycles.then(|| graph.cycles());
+    let jdk_internals = options
+        .jdk_internals
+        .then(|| analysis.jdk_internal_dependencies(&options.filter));
+    if options.json {
+        let report = Report {
+            graph: &graph,
+            cycles,
+            jdk_internals,
+        };
+        return serde_json::to_string_pretty(&report)
+            .map(|json| json + "\n")
+            .map_err(|error| format!("Can't write JSON: {error}"));
+    }
+
+    let mut output = String::new();
+    if options.dot {
+        output.push_str(&graph.to_dot());
+    } else if !options.jdk_internals {
+        output.push_str(&print_text(&analysis, options));
+    }
+    if let Some(cycles) = cycles {
+        output.push_str(&print_cycles(&cycles));
+    }
+    if let Some(jdk_internals) = jdk_internals {
+        output.push_str(&print_jdk_internals(&jdk_internals));
+    }
+    Ok(output)
+}
+
+fn main() -> ExitCode {
+    let mut options = Options {
+        class_path: None,
+        java_home: env::var("JAVA_HOME").ok(),
+        multi_release: None,
+        level: Level::Package,
+        filter: DependencyFilter::default(),
+        cycles: false,
+        jdk_internals: false,
+        dot: false,
+        json: false,
+    };
+    let mut paths = Vec::new();
+
+    let mut arguments = env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        let mut value = |name: &str| {
+            let value = arguments.next();
+            if value.is_none() {
+                eprintln!("Missing value for {name}\n{USAGE}");
+            }
+            value
+        };
+        match argument.as_str() {
+            "-cp" | "-classpath" | "--class-path" => match value(&argument) {
+                Some(class_path) => options.class_path = Some(class_path),
+                None => return ExitCode::from(2),
+            },
+            "--java-home" => match value(&argument) {
+                Some(java_home) => options.java_home = Some(java_home),
+                None => return ExitCode::from(2),
+            },
+            "--multi-release" => match value(&argument).and_then(|value| value.parse().ok()) {
+                Some(release) => options.multi_release = Some(release),
+                None => {
+                    eprintln!("Invalid version for --multi-release\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "--include" => match value(&argument) {
+                Some(prefix) => options.filter.include.push(prefix.replace('.', "/")),
+                None => return ExitCode::from(2),
+            },
+            "--exclude" => match value(&argument) {
+                Some(prefix) => options.filter.exclude.push(prefix.replace('.', "/")),
+                None => return ExitCode::from(2),
+            },
+            "-s" | "-summary" => options.level = Level::Archive,
+            "-verbose:package" => options.level = Level::Package,
+            "-verbose:class" => options.level = Level::Class,
+            "--no-jdk" => options.filter.exclude_jdk = true,
+            "--cycles" => options.cycles = true,
+            "--jdk-internals" | "-jdkinternals" => options.jdk_internals = true,
+            "--dot" => options.dot = true,
+            "--json" => options.json = true,
+            "-h" | "-help" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown option: {flag}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    match run(&paths, &options) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("Error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use cafebabe::archive::JarFile;
+use cafebabe::remapper::RemapOptions;
+use cafebabe::shade::{Relocation, ShadeReport, shade_jar};
+
+const USAGE: &str = "Usage: jshade <options> <input jar> <output jar>
+Moves packages of a jar to other packages, together with their resources and service files, and rewrites all
+classes to use the new names.
+where possible options include:
+  --relocate <from>=<to>  Move a package and its subpackages, e.g. com.google.common=acme.shaded.guava
+  --keep-strings          Don't rename the string constants that name relocated classes
+  --json                  Print what was relocated as JSON";
+
+struct Options {
+    relocations: Vec<Relocation>,
+    remap: RemapOptions,
+    json: bool,
+}
+
+fn parse_relocation(value: &str) -> Option<Relocation> {
+    let (from, to) = value.split_once('=')?;
+    match from.trim().is_empty() || to.trim().is_empty() {
+        true => None,
+        false => Some(Relocation::new(from.trim(), to.trim())),
+    }
+}
+
+fn run(input: &str, output: &str, options: &Options) -> Result<ShadeReport, String> {
+    let jar_file = JarFile::open(input).map_err(|error| format!("Can't read {input}: {error}"))?;
+    let (data, report) = shade_jar(&jar_file, &options.relocations, &options.remap)
+        .map_err(|error| format!("Can't relocate {input}: {error}"))?;
+    fs::write(output, data).map_err(|error| format!("Can't write {output}: {error}"))?;
+    Ok(report)
+}
+
+fn print_report(report: &ShadeReport) {
+    println!(
+        "Relocated {} classes and {} resources",
+        report.relocated_classes.len(),
+        report.relocated_resources.len()
+    );
+    for name in &report.merged_services {
+        println!("Merged service files into {name}");
+    }
+    for name in &report.removed_signatures {
+        println!("Removed signature file {name}");
+    }
+    for name in &report.duplicate_entries {
+        eprintln!("Warning: left out {name}, as an earlier entry has the same relocated name");
+    }
+}
+
+fn main() -> ExitCode {
+    let mut options = Options {
+        relocations: Vec::new(),
+        remap: RemapOptions { strings: true },
+        json: false,
+    };
+    let mut paths = Vec::new();
+
+    let mut arguments = env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--relocate" => match arguments.next().as_deref().and_then(parse_relocation) {
+                Some(relocation) => options.relocations.push(relocation),
+                None => {
+                    eprintln!("Invalid relocation for --relocate\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            },
+            "--keep-strings" => options.remap.strings = false,
+            "--json" => options.json = true,
+            "-h" | "-help" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown option: {flag}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    let [input, output] = paths.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+    if options.relocations.is_empty() {
+        eprintln!("No relocations given\n{USAGE}");
+        return ExitCode::from(2);
+    }
+
+    let report = match run(input, output, &options) {
+        Ok(report) => report,
+        Err(message) => {
+            eprintln!("Error: {message}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if options.json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{json}"),
+            Err(error) => {
+                eprintln!("Error: Can't write JSON: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        print_report(&report);
+    }
+    ExitCode::SUCCESS
+}
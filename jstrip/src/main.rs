@@ -0,0 +1,108 @@
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use cafebabe::archive::JarFile;
+use cafebabe::strip::{StripOptions, StripReport, strip_jar};
+
+const USAGE: &str = "Usage: jstrip <options> <input jar> <output jar>
+Removes the debug attributes from the classes of a jar, and the constant pool entries that are no longer used.
+where possible options include:
+  --keep <attribute>  Keep an attribute, one of LineNumberTable, LocalVariableTable, LocalVariableTypeTable,
+                      SourceFile and SourceDebugExtension
+  --json              Print what was removed as JSON";
+
+/// Stops stripping the attribute with the given name, or gives `false` for an unknown name.
+fn keep(options: &mut StripOptions, attribute: &str) -> bool {
+    let strip = match attribute {
+        "LineNumberTable" => &mut options.line_numbers,
+        "LocalVariableTable" => &mut options.local_variables,
+        "LocalVariableTypeTable" => &mut options.local_variable_types,
+        "SourceFile" => &mut options.source_file,
+        "SourceDebugExtension" => &mut options.source_debug_extension,
+        _ => return false,
+    };
+    *strip = false;
+    true
+}
+
+fn run(input: &str, output: &str, options: &StripOptions) -> Result<StripReport, String> {
+    let jar_file = JarFile::open(input).map_err(|error| format!("Can't read {input}: {error}"))?;
+    let (data, report) =
+        strip_jar(&jar_file, options).map_err(|error| format!("Can't strip {input}: {error}"))?;
+    fs::write(output, data).map_err(|error| format!("Can't write {output}: {error}"))?;
+    Ok(report)
+}
+
+fn print_report(report: &StripReport) {
+    println!(
+        "Stripped {} classes from {} to {} bytes, removing {} attributes and {} constants",
+        report.classes,
+        report.original_size,
+        report.stripped_size,
+        report.removed_attributes,
+        report.removed_constants
+    );
+    for name in &report.removed_signatures {
+        println!("Removed signature file {name}");
+    }
+    for name in &report.kept_constant_pools {
+        eprintln!(
+            "Warning: kept the constant pool of {name}, as it has an attribute of unknown format"
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    let mut options = StripOptions::all();
+    let mut json = false;
+    let mut paths = Vec::new();
+
+    let mut arguments = env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--keep" => {
+                let attribute = arguments.next().unwrap_or_default();
+                if !keep(&mut options, &attribute) {
+                    eprintln!("Unknown attribute for --keep: {attribute}\n{USAGE}");
+                    return ExitCode::from(2);
+                }
+            }
+            "--json" => json = true,
+            "-h" | "-help" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("Unknown option: {flag}\n{USAGE}");
+                return ExitCode::from(2);
+            }
+            path => paths.push(path.to_string()),
+        }
+    }
+
+    let [input, output] = paths.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::from(2);
+    };
+
+    let report = match run(input, output, &options) {
+        Ok(report) => report,
+        Err(message) => {
+            eprintln!("Error: {message}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{json}"),
+            Err(error) => {
+                eprintln!("Error: Can't write JSON: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        print_report(&report);
+    }
+    ExitCode::SUCCESS
+}
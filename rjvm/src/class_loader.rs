@@ -0,0 +1,159 @@
+//! The bootstrap class loader, which loads classes from a class path (JVMS 5.3.1).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::result::Result;
+use std::sync::{Arc, Mutex};
+
+use cafebabe::archive::ArchiveError;
+use cafebabe::class_path::ClassPath;
+use cafebabe::{AccessFlags, ClassFile, ClassFileError};
+use log::debug;
+
+/// Why a class could not be loaded, named after the error the JVM throws.
+#[derive(Debug)]
+pub enum LoadError {
+    /// No entry of the class path has the class.
+    ClassNotFound(String),
+    /// The class file found for a name declares another class.
+    NoClassDefFound {
+        class_name: String,
+        found: String,
+    },
+    /// The class is its own superclass or superinterface.
+    ClassCircularity(String),
+    /// The superclass of a class is an interface, or one of its superinterfaces is not.
+    IncompatibleClassChange {
+        class_name: String,
+        message: String,
+    },
+    ClassFormat(String, ClassFileError),
+    Archive(ArchiveError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::ClassNotFound(class_name) => write!(f, "class not found: {class_name}"),
+            LoadError::NoClassDefFound { class_name, found } => {
+                write!(f, "{class_name} (wrong name: {found})")
+            }
+            LoadError::ClassCircularity(class_name) => write!(f, "class circularity: {class_name}"),
+            LoadError::IncompatibleClassChange {
+                class_name,
+                message,
+            } => write!(f, "{class_name}: {message}"),
+            LoadError::ClassFormat(class_name, error) => write!(f, "{error} in {class_name}"),
+            LoadError::Archive(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Classes that can't be parsed are reported as [`LoadError::ClassFormat`], like a `ClassFormatError` of the JVM.
+impl From<ArchiveError> for LoadError {
+    fn from(error: ArchiveError) -> Self {
+        match error {
+            ArchiveError::InvalidClass(name, error) => {
+                let class_name = name.strip_suffix(".class").unwrap_or(&name).to_string();
+                LoadError::ClassFormat(class_name, error)
+            }
+            error => LoadError::Archive(error),
+        }
+    }
+}
+
+/// Loads classes and their superclasses and superinterfaces from a class path, each class once.
+pub struct ClassLoader {
+    class_path: ClassPath,
+    /// The classes whose supertypes have been loaded, by binary name.
+    loaded: Mutex<HashMap<String, Arc<ClassFile>>>,
+}
+
+impl ClassLoader {
+    pub fn new(class_path: ClassPath) -> ClassLoader {
+        ClassLoader {
+            class_path,
+            loaded: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn class_path(&self) -> &ClassPath {
+        &self.class_path
+    }
+
+    /// Whether a class has been loaded, by its binary name in its internal form.
+    pub fn is_loaded(&self, class_name: &str) -> bool {
+        self.loaded
+            .lock()
+            .is_ok_and(|loaded| loaded.contains_key(class_name))
+    }
+
+    /// Loads a class by its binary name in its internal form, e.g. `java/lang/String`, after loading its superclass
+    /// and superinterfaces as in JVMS 5.3.5. Array classes are not loaded from the class path and can't be asked for.
+    pub fn load_class(&self, class_name: &str) -> Result<Arc<ClassFile>, LoadError> {
+        self.load(class_name, &mut Vec::new())
+    }
+
+    fn load(
+        &self,
+        class_name: &str,
+        loading: &mut Vec<String>,
+    ) -> Result<Arc<ClassFile>, LoadError> {
+        if let Some(class_file) = self
+            .loaded
+            .lock()
+            .ok()
+            .and_then(|loaded| loaded.get(class_name).cloned())
+        {
+            return Ok(class_file);
+        }
+        if loading.iter().any(|name| name == class_name) {
+            return Err(LoadError::ClassCircularity(class_name.to_string()));
+        }
+
+        let class_file = self
+            .class_path
+            .find_class(class_name)?
+            .ok_or_else(|| LoadError::ClassNotFound(class_name.to_string()))?;
+        let invalid = |error| LoadError::ClassFormat(class_name.to_string(), error);
+        let this_class_name = class_file.this_class_name().map_err(invalid)?;
+        if this_class_name != class_name {
+            return Err(LoadError::NoClassDefFound {
+                class_name: class_name.to_string(),
+                found: this_class_name.clone(),
+            });
+        }
+
+        loading.push(class_name.to_string());
+        if let Some(super_class_name) = class_file.super_class_name().map_err(invalid)? {
+            let super_class = self.load(super_class_name, loading)?;
+            if super_class
+                .access_flags
+                .contains(AccessFlags::ACC_INTERFACE)
+            {
+                return Err(LoadError::IncompatibleClassChange {
+                    class_name: class_name.to_string(),
+                    message: format!("superclass {super_class_name} is an interface"),
+                });
+            }
+        }
+        for interface_name in class_file.interface_names().map_err(invalid)? {
+            let interface = self.load(interface_name, loading)?;
+            if !interface.access_flags.contains(AccessFlags::ACC_INTERFACE) {
+                return Err(LoadError::IncompatibleClassChange {
+                    class_name: class_name.to_string(),
+                    message: format!("{interface_name} is not an interface"),
+                });
+            }
+        }
+        loading.pop();
+
+        debug!("loaded class; class_name={class_name}");
+        if let Ok(mut loaded) = self.loaded.lock() {
+            loaded.insert(class_name.to_string(), Arc::clone(&class_file));
+        }
+        Ok(class_file)
+    }
+}
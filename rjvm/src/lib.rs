@@ -1 +1,3 @@
+pub mod class_loader;
 
+pub use class_loader::{ClassLoader, LoadError};
@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::PathBuf;
+
+use cafebabe::assembler::assemble;
+use cafebabe::class_path::ClassPath;
+use cafebabe::write_class_data;
+use rjvm::{ClassLoader, LoadError};
+
+/// A directory of classes assembled from their sources, standing in for the classes of the JDK.
+fn assembled_classes(name: &str, classes: &[(&str, &str)]) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("rjvm-{name}-{}", std::process::id()));
+    for (class_name, source) in classes {
+        let path = directory.join(format!("{class_name}.class"));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let class_file = assemble(source).unwrap();
+        fs::write(path, write_class_data(&class_file).unwrap()).unwrap();
+    }
+    directory
+}
+
+fn runtime_classes(name: &str, more: &[(&str, &str)]) -> PathBuf {
+    let mut classes = vec![
+        ("java/lang/Object", ".class public java/lang/Object"),
+        (
+            "java/io/Serializable",
+            ".class public interface abstract java/io/Serializable\n.super java/lang/Object",
+        ),
+    ];
+    classes.extend_from_slice(more);
+    assembled_classes(name, &classes)
+}
+
+#[test]
+fn loads_classes_with_their_supertypes() {
+    let runtime = runtime_classes("supertypes", &[]);
+    let mut class_path = ClassPath::new();
+    class_path.add(&runtime).unwrap();
+    class_path.add("../cafebabe/res/java17").unwrap();
+    let class_loader = ClassLoader::new(class_path);
+
+    let class_file = class_loader
+        .load_class("examples/ClassWithInterface")
+        .unwrap();
+    assert_eq!(
+        class_file.this_class_name().unwrap(),
+        "examples/ClassWithInterface"
+    );
+    assert!(class_loader.is_loaded("java/lang/Object"));
+    assert!(class_loader.is_loaded("java/io/Serializable"));
+    assert!(!class_loader.is_loaded("examples/EmptyClass"));
+
+    let Err(error) = class_loader.load_class("examples/Missing") else {
+        panic!("Expect a missing class to fail");
+    };
+    assert!(matches!(error, LoadError::ClassNotFound(name) if name == "examples/Missing"));
+    fs::remove_dir_all(runtime).unwrap();
+}
+
+#[test]
+fn reports_missing_supertypes() {
+    let mut class_path = ClassPath::new();
+    class_path.add("../cafebabe/res/java17").unwrap();
+    let class_loader = ClassLoader::new(class_path);
+
+    let Err(error) = class_loader.load_class("examples/EmptyClass") else {
+        panic!("Expect a class without java/lang/Object to fail");
+    };
+    assert_eq!(error.to_string(), "class not found: java/lang/Object");
+    assert!(!class_loader.is_loaded("examples/EmptyClass"));
+}
+
+#[test]
+fn reports_invalid_hierarchies() {
+    let runtime = runtime_classes(
+        "hierarchies",
+        &[
+            ("a/First", ".class public a/First\n.super a/Second"),
+            ("a/Second", ".class public a/Second\n.super a/First"),
+            (
+                "a/ExtendsInterface",
+                ".class public a/ExtendsInterface\n.super java/io/Serializable",
+            ),
+            (
+                "a/Renamed",
+                ".class public a/Other\n.super java/lang/Object",
+            ),
+        ],
+    );
+    let mut class_path = ClassPath::new();
+    class_path.add(&runtime).unwrap();
+    let class_loader = ClassLoader::new(class_path);
+
+    let Err(error) = class_loader.load_class("a/First") else {
+        panic!("Expect a circular hierarchy to fail");
+    };
+    assert!(matches!(error, LoadError::ClassCircularity(name) if name == "a/First"));
+    let Err(error) = class_loader.load_class("a/ExtendsInterface") else {
+        panic!("Expect extending an interface to fail");
+    };
+    assert_eq!(
+        error.to_string(),
+        "a/ExtendsInterface: superclass java/io/Serializable is an interface"
+    );
+    let Err(error) = class_loader.load_class("a/Renamed") else {
+        panic!("Expect a class under another name to fail");
+    };
+    assert_eq!(error.to_string(), "a/Renamed (wrong name: a/Other)");
+
+    fs::write(runtime.join("a/Broken.class"), b"not a class").unwrap();
+    let Err(error) = class_loader.load_class("a/Broken") else {
+        panic!("Expect a class that can't be parsed to fail");
+    };
+    assert!(matches!(error, LoadError::ClassFormat(name, _) if name == "a/Broken"));
+    fs::remove_dir_all(runtime).unwrap();
+}